@@ -0,0 +1,131 @@
+//! Block execution through the wrappers against the same call made
+//! directly on the C library; the wrappers cast the sample buffers
+//! instead of converting them, so both should run at the same speed.
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use liquid_dsp::{AgcCrcf, ChannelCccf, FftFiltCccf, FirFiltCrcf};
+use liquid_dsp_sys as raw;
+use num::complex::Complex32;
+
+const LENGTHS: [usize; 3] = [256, 4096, 65536];
+
+fn input(n: usize) -> Vec<Complex32> {
+    (0..n)
+        .map(|i| Complex32::from_polar(1.0, 0.1 * i as f32))
+        .collect()
+}
+
+fn firfilt(c: &mut Criterion) {
+    let mut group = c.benchmark_group("firfilt_crcf");
+    let mut h = vec![0.1f32; 32];
+    for &n in LENGTHS.iter() {
+        let x = input(n);
+        let mut y = vec![Complex32::default(); n];
+
+        let mut q = FirFiltCrcf::create(&h).unwrap();
+        group.bench_with_input(BenchmarkId::new("wrapper", n), &x, |b, x| {
+            b.iter(|| q.execute_block(black_box(x), &mut y))
+        });
+
+        let q = unsafe { raw::firfilt_crcf_create(h.as_mut_ptr(), h.len() as _) };
+        group.bench_with_input(BenchmarkId::new("c", n), &x, |b, x| {
+            b.iter(|| unsafe {
+                raw::firfilt_crcf_execute_block(
+                    q,
+                    black_box(x).as_ptr() as *mut _,
+                    n as _,
+                    y.as_mut_ptr() as *mut _,
+                )
+            })
+        });
+        unsafe { raw::firfilt_crcf_destroy(q) };
+    }
+    group.finish();
+}
+
+fn fftfilt(c: &mut Criterion) {
+    let mut group = c.benchmark_group("fftfilt_cccf");
+    let block = 256;
+    let mut h = vec![Complex32::new(0.1, 0.0); 64];
+    for &n in LENGTHS.iter() {
+        let x = input(n);
+        let mut y = vec![Complex32::default(); n];
+        let mut q = FftFiltCccf::create(&h, block).unwrap();
+        group.bench_with_input(BenchmarkId::new("wrapper", n), &x, |b, x| {
+            b.iter(|| {
+                for (xb, yb) in black_box(x).chunks(block).zip(y.chunks_mut(block)) {
+                    q.execute(xb, yb);
+                }
+            })
+        });
+
+        let q =
+            unsafe { raw::fftfilt_cccf_create(h.as_mut_ptr() as *mut _, h.len() as _, block as _) };
+        group.bench_with_input(BenchmarkId::new("c", n), &x, |b, x| {
+            b.iter(|| unsafe {
+                for (xb, yb) in black_box(x).chunks(block).zip(y.chunks_mut(block)) {
+                    raw::fftfilt_cccf_execute(q, xb.as_ptr() as *mut _, yb.as_mut_ptr() as *mut _);
+                }
+            })
+        });
+        unsafe { raw::fftfilt_cccf_destroy(q) };
+    }
+    group.finish();
+}
+
+fn channel(c: &mut Criterion) {
+    let mut group = c.benchmark_group("channel_cccf");
+    for &n in LENGTHS.iter() {
+        let x = input(n);
+        let mut y = vec![Complex32::default(); n];
+        let mut q = ChannelCccf::create();
+        q.add_awgn(-60.0, 20.0);
+        group.bench_with_input(BenchmarkId::new("wrapper", n), &x, |b, x| {
+            b.iter(|| q.execute_block(black_box(x), &mut y))
+        });
+
+        let q = unsafe { raw::channel_cccf_create() };
+        unsafe { raw::channel_cccf_add_awgn(q, -60.0, 20.0) };
+        group.bench_with_input(BenchmarkId::new("c", n), &x, |b, x| {
+            b.iter(|| unsafe {
+                raw::channel_cccf_execute_block(
+                    q,
+                    black_box(x).as_ptr() as *mut _,
+                    n as _,
+                    y.as_mut_ptr() as *mut _,
+                )
+            })
+        });
+        unsafe { raw::channel_cccf_destroy(q) };
+    }
+    group.finish();
+}
+
+fn agc(c: &mut Criterion) {
+    let mut group = c.benchmark_group("agc_crcf");
+    for &n in LENGTHS.iter() {
+        let x = input(n);
+        let mut y = vec![Complex32::default(); n];
+
+        let mut q = AgcCrcf::create();
+        group.bench_with_input(BenchmarkId::new("wrapper", n), &x, |b, x| {
+            b.iter(|| q.execute_block(black_box(x), &mut y))
+        });
+
+        let q = unsafe { raw::agc_crcf_create() };
+        group.bench_with_input(BenchmarkId::new("c", n), &x, |b, x| {
+            b.iter(|| unsafe {
+                raw::agc_crcf_execute_block(
+                    q,
+                    black_box(x).as_ptr() as *mut _,
+                    n as _,
+                    y.as_mut_ptr() as *mut _,
+                )
+            })
+        });
+        unsafe { raw::agc_crcf_destroy(q) };
+    }
+    group.finish();
+}
+
+criterion_group!(benches, firfilt, fftfilt, channel, agc);
+criterion_main!(benches);
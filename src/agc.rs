@@ -6,17 +6,91 @@ use crate::enums::AgcSquelchMode;
 use crate::liquid_dsp_sys as raw;
 
 use crate::errors::LiquidError;
-use crate::utils::{ToCPointer, ToCPointerMut, ToCValue};
+use crate::metrics::NoiseFloorEstimator;
+use crate::utils::{check_same_len, ToCPointer, ToCPointerMut, ToCValue};
 use crate::LiquidResult;
 
+/// squelch state transitions reported while processing a block
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub enum AgcSquelchEvent {
+    /// signal level rose above the squelch threshold
+    Rise,
+    /// signal level fell below the squelch threshold
+    Fall,
+    /// signal stayed below the threshold for the squelch timeout
+    Timeout,
+}
+
+/// agc settings applied at once with apply_config
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AgcConfig {
+    /// loop bandwidth while the signal level rises, 0 <= attack <= 1
+    pub attack: f32,
+    /// loop bandwidth while the signal level falls, 0 <= release <= 1;
+    /// None to track in both directions with the attack bandwidth
+    pub release: Option<f32>,
+    /// output scale, scale > 0
+    pub scale: f32,
+    /// squelch enabled
+    pub squelch: bool,
+    /// squelch threshold [dB]
+    pub squelch_threshold: f32,
+    /// squelch timeout [samples]
+    pub squelch_timeout: u64,
+}
+
+impl AgcConfig {
+    fn check(&self) -> LiquidResult<()> {
+        let in_range = |b: f32| (0f32..=1f32).contains(&b);
+        if !in_range(self.attack) {
+            return Err(LiquidError::InvalidValue(
+                "attack bandwidth must be in [0, 1.0]".to_owned(),
+            ));
+        } else if self.release.map(in_range) == Some(false) {
+            return Err(LiquidError::InvalidValue(
+                "release bandwidth must be in [0, 1.0]".to_owned(),
+            ));
+        } else if self.scale <= 0f32 {
+            return Err(LiquidError::InvalidValue(
+                "scale must be greater than zero".to_owned(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl Default for AgcConfig {
+    /// single bandwidth of 1e-3, unity scale and squelch disabled
+    fn default() -> Self {
+        Self {
+            attack: 1e-3,
+            release: None,
+            scale: 1.0,
+            squelch: false,
+            squelch_threshold: 0.0,
+            squelch_timeout: 100,
+        }
+    }
+}
+
 pub struct AgcCrcf {
     inner: raw::agc_crcf,
     is_locked: bool,
+    /// (attack, release) bandwidths when tracking asymmetrically
+    attack_release: Option<(f32, f32)>,
+    /// margin over the noise floor [dB] when the squelch threshold follows
+    /// a NoiseFloorEstimator
+    auto_squelch: Option<f32>,
 }
 
 pub struct AgcRrrf {
     inner: raw::agc_rrrf,
     is_locked: bool,
+    /// (attack, release) bandwidths when tracking asymmetrically
+    attack_release: Option<(f32, f32)>,
+    /// margin over the noise floor [dB] when the squelch threshold follows
+    /// a NoiseFloorEstimator
+    auto_squelch: Option<f32>,
 }
 
 macro_rules! agc_xxx_impl {
@@ -34,19 +108,20 @@ macro_rules! agc_xxx_impl {
         $gettimeout:expr, $status:expr,
         $execute:expr, $block:expr,
         $destroy:expr,
-        $type:ty, $type2:ty)) => {
+        $type:ty, $type2:ty, $energy:expr)) => {
         impl $obj {
             pub fn create() -> Self {
                 Self {
                     inner: unsafe { $create() },
                     is_locked: false,
+                    attack_release: None,
+                    auto_squelch: None,
                 }
             }
 
-
             /// initialize internal gain on input array
             ///  x      : input data array, [size: _n x 1]
-            pub fn init(&mut self, x: &mut[$type2]) -> LiquidResult<()> {
+            pub fn init(&mut self, x: &mut [$type2]) -> LiquidResult<()> {
                 if x.is_empty() {
                     return Err(LiquidError::InvalidValue(
                         "number of samples must be greater than zero".to_owned(),
@@ -78,10 +153,11 @@ macro_rules! agc_xxx_impl {
                 }
             }
 
-            /// set agc loop bandwidth
+            /// set agc loop bandwidth, used both while the signal level
+            /// rises and falls
             ///  b     :   bandwidth 0 <= b <= 1.0
             pub fn set_bandwidth(&mut self, b: f32) -> LiquidResult<()> {
-                if b < 0f32 || b > 1f32 {
+                if !(0f32..=1f32).contains(&b) {
                     return Err(LiquidError::InvalidValue(
                         "b must be in [0, 1.0]".to_owned(),
                     ));
@@ -89,9 +165,59 @@ macro_rules! agc_xxx_impl {
                 unsafe {
                     $setband(self.inner, b);
                 }
+                self.attack_release = None;
                 Ok(())
             }
 
+            /// apply a full configuration, leaving the object untouched if
+            /// any of the settings is invalid. With a release bandwidth the
+            /// loop switches, sample by sample, between the attack
+            /// bandwidth when the input level is above the estimated level
+            /// and the release bandwidth otherwise
+            pub fn apply_config(&mut self, config: &AgcConfig) -> LiquidResult<()> {
+                config.check()?;
+                unsafe {
+                    $setband(self.inner, config.attack);
+                    $setscale(self.inner, config.scale);
+                    if config.squelch {
+                        $squelche(self.inner);
+                    } else {
+                        $squelchd(self.inner);
+                    }
+                    $setthres(self.inner, config.squelch_threshold);
+                    $settimeout(self.inner, config.squelch_timeout as c_uint);
+                }
+                self.attack_release = config.release.map(|r| (config.attack, r));
+                Ok(())
+            }
+
+            /// current configuration
+            pub fn get_config(&self) -> AgcConfig {
+                let (attack, release) = match self.attack_release {
+                    Some((a, r)) => (a, Some(r)),
+                    None => (self.get_bandwidth(), None),
+                };
+                AgcConfig {
+                    attack,
+                    release,
+                    scale: self.get_scale(),
+                    squelch: self.squelch_is_enabled(),
+                    squelch_threshold: self.squelch_get_threshold(),
+                    squelch_timeout: self.squelch_get_timeout(),
+                }
+            }
+
+            /// select the attack or release bandwidth for the next sample
+            fn track_level(&mut self, x: $type2) {
+                if let Some((attack, release)) = self.attack_release {
+                    let level = 10.0 * ($energy)(x).log10();
+                    let b = if level > self.get_rssi() { attack } else { release };
+                    unsafe {
+                        $setband(self.inner, b);
+                    }
+                }
+            }
+
             /// get agc loop bandwidth
             pub fn get_bandwidth(&self) -> f32 {
                 unsafe { $getband(self.inner) }
@@ -182,7 +308,7 @@ macro_rules! agc_xxx_impl {
 
             /// set squelch threshold
             ///  th:   threshold for enabling squelch [dB]
-            pub fn squelch_set_threshold(&self, th: f32) {
+            pub fn squelch_set_threshold(&mut self, th: f32) {
                 unsafe {
                     $setthres(self.inner, th);
                 }
@@ -193,9 +319,37 @@ macro_rules! agc_xxx_impl {
                 unsafe { $getthres(self.inner) }
             }
 
+            /// let the squelch threshold follow the noise floor, see
+            /// squelch_update_noise_floor()
+            ///  margin :   threshold above the noise floor [dB], None to go
+            ///             back to a fixed threshold
+            pub fn squelch_set_auto(&mut self, margin: Option<f32>) {
+                self.auto_squelch = margin;
+            }
+
+            /// margin over the noise floor [dB] if the automatic squelch
+            /// threshold is enabled
+            pub fn squelch_get_auto(&self) -> Option<f32> {
+                self.auto_squelch
+            }
+
+            /// set the squelch threshold to the noise floor of *estimator*
+            /// plus the margin given to squelch_set_auto()
+            /// # returns
+            /// the new threshold [dB], None if the automatic threshold is
+            /// disabled or the estimator has no estimate yet
+            pub fn squelch_update_noise_floor(
+                &mut self,
+                estimator: &NoiseFloorEstimator,
+            ) -> Option<f32> {
+                let th = estimator.floor_db()? + self.auto_squelch?;
+                self.squelch_set_threshold(th);
+                Some(th)
+            }
+
             /// set squelch timeout
             ///  timeout : timeout before enabling squelch [samples]
-            pub fn squelch_set_timeout(&self, timeout: u64) {
+            pub fn squelch_set_timeout(&mut self, timeout: u64) {
                 unsafe {
                     $settimeout(self.inner, timeout as c_uint);
                 }
@@ -213,7 +367,8 @@ macro_rules! agc_xxx_impl {
             ///  x      :   input sample
             /// # Returns
             /// output sample
-            pub fn execute(&self, x: $type2) -> $type2 {
+            pub fn execute(&mut self, x: $type2) -> $type2 {
+                self.track_level(x);
                 let mut ret = <$type2>::default();
                 unsafe {
                     $execute(self.inner, x.to_c_value(), ret.to_ptr_mut());
@@ -224,11 +379,22 @@ macro_rules! agc_xxx_impl {
             /// execute automatic gain control on block of samples
             ///  x      : input data array, [size: _n x 1]
             ///  y      : output data array, [size: _n x 1]
-            pub fn execute_block(&self, x: &[$type2], y: &mut[$type2]) {
-                assert!(
-                    x.len() == y.len(),
-                    "Input and output buffers with different length"
-                );
+            /// # panics
+            /// if x and y have different lengths, see try_execute_block
+            pub fn execute_block(&mut self, x: &[$type2], y: &mut [$type2]) {
+                self.try_execute_block(x, y)
+                    .unwrap_or_else(|e| panic!("{}", e));
+            }
+
+            /// execute automatic gain control on block of samples
+            /// # returns
+            /// LiquidError::InvalidLength if x and y have different lengths
+            pub fn try_execute_block(&mut self, x: &[$type2], y: &mut [$type2]) -> LiquidResult<()> {
+                check_same_len(x.len(), y.len())?;
+                if self.attack_release.is_some() {
+                    x.iter().zip(y.iter_mut()).for_each(|(a, b)| *b = self.execute(*a));
+                    return Ok(());
+                }
                 unsafe {
                     $block(
                         self.inner,
@@ -237,6 +403,91 @@ macro_rules! agc_xxx_impl {
                         y.to_ptr_mut(),
                     );
                 }
+                Ok(())
+            }
+
+            /// execute automatic gain control on block of samples, invoking
+            /// *f* with the sample index and the event each time the squelch
+            /// rises, falls or times out
+            ///  x      : input data array, [size: _n x 1]
+            ///  y      : output data array, [size: _n x 1]
+            pub fn execute_block_with_squelch<F>(
+                &mut self,
+                x: &[$type2],
+                y: &mut [$type2],
+                mut f: F,
+            ) -> LiquidResult<()>
+            where
+                F: FnMut(usize, AgcSquelchEvent),
+            {
+                check_same_len(x.len(), y.len())?;
+                for (i, (xi, yi)) in x.iter().zip(y.iter_mut()).enumerate() {
+                    *yi = self.execute(*xi);
+                    let event = match self.squelch_status() {
+                        AgcSquelchMode::RISE => AgcSquelchEvent::Rise,
+                        AgcSquelchMode::FALL => AgcSquelchEvent::Fall,
+                        AgcSquelchMode::TIMEOUT => AgcSquelchEvent::Timeout,
+                        _ => continue,
+                    };
+                    f(i, event);
+                }
+                Ok(())
+            }
+
+            /// execute automatic gain control on block of samples
+            /// # returns
+            /// the squelch transitions, with the sample index where each
+            /// one happened
+            pub fn squelch_events(
+                &mut self,
+                x: &[$type2],
+                y: &mut [$type2],
+            ) -> LiquidResult<Vec<(usize, AgcSquelchEvent)>> {
+                let mut events = Vec::new();
+                self.execute_block_with_squelch(x, y, |i, event| events.push((i, event)))?;
+                Ok(events)
+            }
+
+            /// execute automatic gain control on block of samples, along with
+            /// the RSSI estimate [dB] the gain applied to each sample was
+            /// derived from, i.e. get_rssi() before the sample is processed
+            ///
+            /// The RSSI is recovered from the ratio of the output to the
+            /// input samples, so the block goes through liquid at once;
+            /// only zero input samples need the gain to be read back.
+            ///  x      : input data array, [size: _n x 1]
+            ///  y      : output data array, [size: _n x 1]
+            ///  rssi   : RSSI estimates, [size: _n x 1]
+            pub fn execute_block_with_rssi(
+                &mut self,
+                x: &[$type2],
+                y: &mut [$type2],
+                rssi: &mut [f32],
+            ) -> LiquidResult<()> {
+                check_same_len(x.len(), y.len())?;
+                check_same_len(x.len(), rssi.len())?;
+                let energy = $energy;
+                // y = g*scale*x and rssi = -20 log10(g)
+                let scale2 = self.get_scale() * self.get_scale();
+                let mut i = 0;
+                while i < x.len() {
+                    if energy(x[i]) == 0f32 {
+                        rssi[i] = self.get_rssi();
+                        y[i] = self.execute(x[i]);
+                        i += 1;
+                        continue;
+                    }
+                    let n = x[i..]
+                        .iter()
+                        .position(|v| energy(*v) == 0f32)
+                        .unwrap_or(x.len() - i);
+                    self.try_execute_block(&x[i..i + n], &mut y[i..i + n])?;
+                    for k in i..i + n {
+                        rssi[k] = 10.0 * (energy(x[k]) * scale2 / energy(y[k])).log10();
+                    }
+                    i += n;
+                }
+                Ok(())
             }
         }
 
@@ -255,10 +506,13 @@ macro_rules! agc_xxx_impl {
                 } else {
                     -100.0
                 };
+                let threshold = self.squelch_get_threshold();
+                let timeout = self.squelch_get_timeout();
                 write!(
                     f,
-                    "agc [rssi: {} dB, output gain: {} dB, bw: {}, locked: {}, squelch: {}]:\n",
-                    rssi, gain, bandwith, locked, status
+                    "agc [rssi: {} dB, output gain: {} dB, bw: {}, locked: {}, squelch: {}, \
+                     threshold: {} dB, timeout: {}]:\n",
+                    rssi, gain, bandwith, locked, status, threshold, timeout
                 )
             }
         }
@@ -302,7 +556,9 @@ agc_xxx_impl!(
         raw::agc_crcf_execute,
         raw::agc_crcf_execute_block,
         raw::agc_crcf_destroy,
-        f32, Complex32
+        f32,
+        Complex32,
+        |x: Complex32| x.norm_sqr()
     )
 );
 
@@ -335,13 +591,16 @@ agc_xxx_impl!(
         raw::agc_rrrf_execute,
         raw::agc_rrrf_execute_block,
         raw::agc_rrrf_destroy,
-        f32, f32
+        f32,
+        f32,
+        |x: f32| x * x
     )
 );
 
 #[cfg(test)]
 mod tests {
-    use super::AgcCrcf;
+    use super::{AgcConfig, AgcCrcf, AgcSquelchEvent};
+    use crate::metrics::NoiseFloorEstimator;
     use num::complex::Complex32;
     use num::Zero;
 
@@ -367,11 +626,181 @@ mod tests {
         assert_eq!(&output, &solution);
     }
 
+    #[test]
+    fn test_agc_crcf_execute_block_with_rssi() {
+        let mut agc = AgcCrcf::create();
+        agc.set_gain(10.0).unwrap();
+        agc.set_scale(2.0).unwrap();
+        agc.lock();
+        let mut x = vec![Complex32::new(0.06, -0.08); 8];
+        x[3] = Complex32::zero();
+        let mut y = vec![Complex32::zero(); 8];
+        let mut rssi = vec![0f32; 8];
+        assert!(agc
+            .execute_block_with_rssi(&x, &mut y, &mut rssi[1..])
+            .is_err());
+        agc.execute_block_with_rssi(&x, &mut y, &mut rssi).unwrap();
+        // the locked gain of 20 dB is an RSSI of -20 dB
+        for v in &rssi {
+            assert!((v + 20.0).abs() < 1e-4);
+        }
+        assert!((y[0] - Complex32::new(1.2, -1.6)).norm() < 1e-5);
+    }
+
+    #[test]
+    fn test_agc_crcf_execute_block_with_rssi_tracking() {
+        // silence, a strong burst with a few zero samples, then a weak tail
+        let x: Vec<Complex32> = (0..400)
+            .map(|n| match n {
+                0..=49 | 120 | 121 => Complex32::zero(),
+                50..=249 => Complex32::from_polar(0.5, 0.9 * n as f32),
+                _ => Complex32::from_polar(0.02, 0.3 * n as f32),
+            })
+            .collect();
+        let create = || {
+            let mut agc = AgcCrcf::create();
+            agc.set_bandwidth(0.05).unwrap();
+            agc.set_scale(2.0).unwrap();
+            agc
+        };
+
+        let mut agc = create();
+        let mut y = vec![Complex32::zero(); x.len()];
+        let mut rssi = vec![0f32; x.len()];
+        agc.execute_block_with_rssi(&x, &mut y, &mut rssi).unwrap();
+
+        // the rssi is the one read before every sample is processed
+        let mut reference = create();
+        for (i, &v) in x.iter().enumerate() {
+            let r = reference.get_rssi();
+            let out = reference.execute(v);
+            assert!((rssi[i] - r).abs() < 1e-2, "{}: {} != {}", i, rssi[i], r);
+            assert!((y[i] - out).norm() <= 1e-5 * out.norm().max(1.0));
+        }
+        assert!((agc.get_rssi() - reference.get_rssi()).abs() < 1e-4);
+        // the loop tracked the burst
+        assert!(rssi[249] - rssi[51] > 6.0);
+        assert!(rssi[249] - rssi[399] > 6.0);
+    }
+
     #[test]
     fn test_agc_crcf_rssi() {
-        let agc = AgcCrcf::create();
+        let mut agc = AgcCrcf::create();
         agc.execute(Complex32::new(5.9999495, -3.1499734));
         let rssi = agc.get_rssi();
         assert_eq!(0.016113421, rssi);
     }
+
+    #[test]
+    fn test_agc_crcf_squelch_events() {
+        let mut agc = AgcCrcf::create();
+        agc.squelch_enable();
+        agc.squelch_set_threshold(-20.0);
+        agc.squelch_set_timeout(10);
+        let mut input = vec![Complex32::zero(); 600];
+        for x in input[200..400].iter_mut() {
+            *x = Complex32::new(1.0, 0.0);
+        }
+        let mut output = vec![Complex32::zero(); 600];
+        let events = agc.squelch_events(&input, &mut output).unwrap();
+        let kinds: Vec<AgcSquelchEvent> = events.iter().map(|e| e.1).collect();
+        assert_eq!(
+            kinds,
+            [
+                AgcSquelchEvent::Rise,
+                AgcSquelchEvent::Fall,
+                AgcSquelchEvent::Timeout
+            ]
+        );
+        assert!(events[0].0 >= 200 && events[1].0 >= 400);
+
+        let mut short = vec![Complex32::zero(); 4];
+        assert!(agc.squelch_events(&input, &mut short).is_err());
+    }
+
+    #[test]
+    fn test_agc_crcf_try_execute_block() {
+        let mut agc = AgcCrcf::create();
+        let input = vec![Complex32::new(1.0, 0.0); 8];
+        let mut output = vec![Complex32::zero(); 4];
+        assert!(agc.try_execute_block(&input, &mut output).is_err());
+        let mut output = vec![Complex32::zero(); 8];
+        assert!(agc.try_execute_block(&input, &mut output).is_ok());
+    }
+
+    #[test]
+    fn test_agc_crcf_config() {
+        let mut agc = AgcCrcf::create();
+        let config = AgcConfig {
+            attack: 0.1,
+            release: Some(0.001),
+            scale: 0.5,
+            squelch: true,
+            squelch_threshold: -40.0,
+            squelch_timeout: 20,
+        };
+        agc.apply_config(&config).unwrap();
+        assert_eq!(agc.get_config(), config);
+
+        let invalid = AgcConfig {
+            release: Some(2.0),
+            ..AgcConfig::default()
+        };
+        assert!(agc.apply_config(&invalid).is_err());
+        assert_eq!(agc.get_config(), config);
+
+        agc.set_bandwidth(0.01).unwrap();
+        assert_eq!(agc.get_config().release, None);
+    }
+
+    #[test]
+    fn test_agc_crcf_auto_squelch() {
+        let mut agc = AgcCrcf::create();
+        let mut est = NoiseFloorEstimator::new(0.25, 1.0).unwrap();
+        agc.squelch_set_auto(Some(6.0));
+        assert_eq!(agc.squelch_update_noise_floor(&est), None);
+
+        est.update(&[-60.0; 16]).unwrap();
+        assert_eq!(agc.squelch_update_noise_floor(&est), Some(-54.0));
+        agc.squelch_set_auto(None);
+        assert_eq!(agc.squelch_get_auto(), None);
+        assert_eq!(agc.squelch_update_noise_floor(&est), None);
+    }
+
+    #[test]
+    fn test_agc_crcf_auto_squelch_burst() {
+        // a -60 dB noise floor, with a -20 dB burst over samples 300..500
+        let mut est = NoiseFloorEstimator::new(0.25, 1.0).unwrap();
+        est.update(&[-60.0; 16]).unwrap();
+        let input: Vec<Complex32> = (0..800)
+            .map(|n| {
+                let amplitude = if (300..500).contains(&n) { 0.1 } else { 1e-3 };
+                Complex32::from_polar(amplitude, 0.7 * n as f32)
+            })
+            .collect();
+
+        let mut agc = AgcCrcf::create();
+        agc.set_bandwidth(0.05).unwrap();
+        agc.set_rssi(-60.0);
+        agc.squelch_enable();
+        agc.squelch_set_timeout(10);
+        agc.squelch_set_auto(Some(6.0));
+        assert_eq!(agc.squelch_update_noise_floor(&est), Some(-54.0));
+        assert_eq!(agc.squelch_get_threshold(), -54.0);
+
+        // the noise stays squelched, the burst opens and closes it
+        let mut output = vec![Complex32::zero(); 800];
+        let events = agc.squelch_events(&input, &mut output).unwrap();
+        let kinds: Vec<AgcSquelchEvent> = events.iter().map(|e| e.1).collect();
+        assert_eq!(
+            kinds,
+            [
+                AgcSquelchEvent::Rise,
+                AgcSquelchEvent::Fall,
+                AgcSquelchEvent::Timeout
+            ]
+        );
+        assert!((300..320).contains(&events[0].0));
+        assert!((500..700).contains(&events[1].0));
+    }
 }
@@ -0,0 +1,286 @@
+//! *block* : common interface of the stream processing objects, so that
+//!           they can be boxed and chained at run time, e.g. in a
+//!           Vec<Box<dyn Block<Complex32, Complex32>>>
+use num::complex::Complex32;
+
+use crate::agc::{AgcCrcf, AgcRrrf, AgcSquelchEvent};
+use crate::channel::ChannelCccf;
+use crate::fading::Fading;
+use crate::filter::{
+    FirFiltCccf, FirFiltCrcf, FirFiltRrrf, FirInterpCccf, FirInterpCrcf, FirInterpRrrf,
+    IirFiltCccf, IirFiltCrcf, IirFiltRrrf, IirFiltSosCccf, IirFiltSosCrcf, IirFiltSosRrrf,
+    TrackingNotch,
+};
+use crate::impairments::{Corrector, IqImbalance, Nonlinearity};
+use crate::math::PhaseDifference;
+use crate::modem::{AmpModem, FmDiscriminator, Modem};
+use crate::predistort::Predistorter;
+use crate::sync::CarrierRecovery;
+use crate::tag::{propagate_tags, Tag, TagValue};
+use crate::tvmpch::TvmpchCccf;
+use crate::LiquidResult;
+
+/// stream processing object consuming samples of type I and producing
+/// samples of type O
+pub trait Block<I, O> {
+    /// process a block of input samples, appending the output samples to
+    /// output; objects that work on fixed size blocks keep the samples of
+    /// an incomplete block for the next call
+    fn process(&mut self, input: &[I], output: &mut Vec<O>) -> LiquidResult<()>;
+
+    /// number of output samples per input sample, used to size the
+    /// buffers between blocks
+    fn rate(&self) -> f32 {
+        1.0
+    }
+
+    /// latency of the block [output samples], the group delay at DC for
+    /// the filters, used to time-align the output with the input
+    fn delay(&self) -> f32 {
+        0.0
+    }
+
+    /// process a block of input samples along with the tags attached to
+    /// them, appending the output samples to output and the tags to
+    /// tags_out; by default the tags are moved to the matching position
+    /// of the output and blocks adding tags of their own override this
+    fn process_tagged(
+        &mut self,
+        input: &[I],
+        tags: &[Tag],
+        output: &mut Vec<O>,
+        tags_out: &mut Vec<Tag>,
+    ) -> LiquidResult<()> {
+        let start = output.len();
+        self.process(input, output)?;
+        propagate_tags(tags, input.len(), start, output.len() - start, tags_out);
+        Ok(())
+    }
+}
+
+/// grow output by n samples and hand the new samples to f, leaving output
+/// untouched if f fails
+pub(crate) fn append_with<O, F>(output: &mut Vec<O>, n: usize, f: F) -> LiquidResult<()>
+where
+    O: Clone + Default,
+    F: FnOnce(&mut [O]) -> LiquidResult<()>,
+{
+    let start = output.len();
+    output.resize(start + n, O::default());
+    let res = f(&mut output[start..]);
+    if res.is_err() {
+        output.truncate(start);
+    }
+    res
+}
+
+macro_rules! block_impl {
+    ($($obj:ty, $in:ty, $out:ty, |$q:ident, $x:ident, $y:ident| $body:expr
+        $(, delay |$d:ident| $delay:expr)?;)*) => {
+        $(
+            impl Block<$in, $out> for $obj {
+                fn process(&mut self, input: &[$in], output: &mut Vec<$out>) -> LiquidResult<()> {
+                    let $q = self;
+                    let $x = input;
+                    append_with(output, input.len(), |$y| $body)
+                }
+
+                $(
+                    fn delay(&self) -> f32 {
+                        let $d = self;
+                        $delay
+                    }
+                )?
+            }
+        )*
+    };
+}
+
+block_impl!(
+    ChannelCccf, Complex32, Complex32, |q, x, y| q.try_execute_block(x, y);
+    TvmpchCccf, Complex32, Complex32, |q, x, y| q.try_execute_block(x, y);
+    Fading, Complex32, Complex32, |q, x, y| q.execute_block(x, y);
+    IqImbalance, Complex32, Complex32, |q, x, y| {
+        y.iter_mut().zip(x).for_each(|(b, a)| *b = q.apply_sample(*a));
+        Ok(())
+    };
+    Corrector, Complex32, Complex32, |q, x, y| q.execute_block(x, y);
+    CarrierRecovery, Complex32, Complex32, |q, x, y| q.execute_block(x, y);
+    Nonlinearity, Complex32, Complex32, |q, x, y| {
+        y.iter_mut().zip(x).for_each(|(b, a)| *b = q.apply_sample(*a));
+        Ok(())
+    };
+    Predistorter, Complex32, Complex32, |q, x, y| {
+        y.iter_mut().zip(x).for_each(|(b, a)| *b = q.apply_sample(*a));
+        Ok(())
+    };
+    FirFiltRrrf, f32, f32, |q, x, y| q.try_execute_block(x, y), delay |q| q.group_delay(0.0);
+    FirFiltCrcf, Complex32, Complex32, |q, x, y| q.try_execute_block(x, y),
+        delay |q| q.group_delay(0.0);
+    FirFiltCccf, Complex32, Complex32, |q, x, y| q.try_execute_block(x, y),
+        delay |q| q.group_delay(0.0);
+    IirFiltRrrf, f32, f32, |q, x, y| {
+        q.execute_block(x, y);
+        Ok(())
+    }, delay |q| q.group_delay(0.0);
+    IirFiltCrcf, Complex32, Complex32, |q, x, y| {
+        q.execute_block(x, y);
+        Ok(())
+    }, delay |q| q.group_delay(0.0);
+    IirFiltCccf, Complex32, Complex32, |q, x, y| {
+        q.execute_block(x, y);
+        Ok(())
+    }, delay |q| q.group_delay(0.0);
+    IirFiltSosRrrf, f32, f32, |q, x, y| q.execute_block(x, y), delay |q| q.group_delay(0.0);
+    IirFiltSosCrcf, Complex32, Complex32, |q, x, y| q.execute_block(x, y),
+        delay |q| q.group_delay(0.0);
+    IirFiltSosCccf, Complex32, Complex32, |q, x, y| q.execute_block(x, y),
+        delay |q| q.group_delay(0.0);
+    TrackingNotch, Complex32, Complex32, |q, x, y| q.execute_block(x, y),
+        delay |q| q.get_delay() as f32;
+    AmpModem, f32, Complex32, |q, x, y| q.try_modulate_block(x, y),
+        delay |q| q.get_delay_mod() as f32;
+    AmpModem, Complex32, f32, |q, x, y| q.try_demodulate_block(x, y),
+        delay |q| q.get_delay_demod() as f32;
+    FmDiscriminator, Complex32, f32, |q, x, y| {
+        q.execute_block(x, y);
+        Ok(())
+    }, delay |q| q.get_delay() as f32;
+    PhaseDifference, Complex32, f32, |q, x, y| q.execute_block(x, y);
+    PhaseDifference, Complex32, Complex32, |q, x, y| q.execute_block_product(x, y);
+);
+
+macro_rules! agc_block_impl {
+    ($($obj:ty, $type:ty;)*) => {
+        $(
+            /// tags the samples where the squelch rises, falls or times out
+            /// with the key "agc_squelch" and the value "rise", "fall" or
+            /// "timeout"
+            impl Block<$type, $type> for $obj {
+                fn process(&mut self, input: &[$type], output: &mut Vec<$type>) -> LiquidResult<()> {
+                    append_with(output, input.len(), |y| self.try_execute_block(input, y))
+                }
+
+                fn process_tagged(
+                    &mut self,
+                    input: &[$type],
+                    tags: &[Tag],
+                    output: &mut Vec<$type>,
+                    tags_out: &mut Vec<Tag>,
+                ) -> LiquidResult<()> {
+                    let start = output.len();
+                    let mut events = Vec::new();
+                    append_with(output, input.len(), |y| {
+                        self.execute_block_with_squelch(input, y, |i, event| {
+                            events.push((i, event))
+                        })
+                    })?;
+                    propagate_tags(tags, input.len(), start, input.len(), tags_out);
+                    tags_out.extend(events.into_iter().map(|(i, event)| {
+                        let value = match event {
+                            AgcSquelchEvent::Rise => "rise",
+                            AgcSquelchEvent::Fall => "fall",
+                            AgcSquelchEvent::Timeout => "timeout",
+                        };
+                        Tag::new(
+                            (start + i) as u64,
+                            "agc_squelch",
+                            TagValue::Str(value.to_owned()),
+                        )
+                    }));
+                    Ok(())
+                }
+            }
+        )*
+    };
+}
+
+agc_block_impl!(
+    AgcCrcf, Complex32;
+    AgcRrrf, f32;
+);
+
+macro_rules! firinterp_block_impl {
+    ($($obj:ty, $type:ty;)*) => {
+        $(
+            /// produces get_interp_factor() samples per input sample, delayed
+            /// by half the prototype length assuming a linear phase design
+            impl Block<$type, $type> for $obj {
+                fn process(&mut self, input: &[$type], output: &mut Vec<$type>) -> LiquidResult<()> {
+                    let n = input.len() * self.get_interp_factor() as usize;
+                    append_with(output, n, |y| {
+                        self.execute_block(input, y);
+                        Ok(())
+                    })
+                }
+
+                fn rate(&self) -> f32 {
+                    self.get_interp_factor() as f32
+                }
+
+                fn delay(&self) -> f32 {
+                    (self.len() - 1) as f32 / 2.0
+                }
+            }
+        )*
+    };
+}
+
+firinterp_block_impl!(
+    FirInterpRrrf, f32;
+    FirInterpCrcf, Complex32;
+    FirInterpCccf, Complex32;
+);
+
+/// modulates symbols, failing on symbols outside the constellation
+impl Block<u32, Complex32> for Modem {
+    fn process(&mut self, input: &[u32], output: &mut Vec<Complex32>) -> LiquidResult<()> {
+        let start = output.len();
+        output.reserve(input.len());
+        for s in input {
+            match self.modulate(*s) {
+                Ok(v) => output.push(v),
+                Err(e) => {
+                    output.truncate(start);
+                    return Err(e);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// demodulates samples into hard decision symbols
+impl Block<Complex32, u32> for Modem {
+    fn process(&mut self, input: &[Complex32], output: &mut Vec<u32>) -> LiquidResult<()> {
+        output.extend(input.iter().map(|x| self.demodulate(*x)));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Block;
+    use crate::filter::{FirFiltCrcf, FirInterpRrrf};
+    use num::complex::Complex32;
+
+    #[test]
+    fn test_block_chain() {
+        let mut chain: Vec<Box<dyn Block<Complex32, Complex32>>> = vec![
+            Box::new(FirFiltCrcf::create(&[1.0]).unwrap()),
+            Box::new(FirFiltCrcf::create(&[0.5]).unwrap()),
+        ];
+        let mut x = vec![Complex32::new(1.0, 0.0); 8];
+        for block in chain.iter_mut() {
+            let mut y = Vec::new();
+            block.process(&x, &mut y).unwrap();
+            x = y;
+        }
+        assert_eq!(x.len(), 8);
+
+        let mut interp = FirInterpRrrf::create(4, &[1.0; 8]).unwrap();
+        let mut y = vec![0f32; 3];
+        interp.process(&[1.0, 2.0], &mut y).unwrap();
+        assert_eq!(y.len(), 11);
+    }
+}
@@ -1,5 +1,23 @@
+use crate::framing::{FrameDataStats, FrameStats};
+
+/// receives the header, header validity, payload, payload validity and
+/// statistics of every frame detected by a frame synchronizer
+pub(crate) type FramesyncCallback<'a> = Box<dyn FnMut(&[u8], bool, &[u8], bool, &FrameStats) + 'a>;
+
+/// receives the frequency of a firdespm grid point and returns the desired
+/// response and weight there through the two references
+pub(crate) type FirdespmCallback<'a> = Box<dyn FnMut(f64, &mut f64, &mut f64) -> i8 + 'a>;
+
+/// utility of a vector of parameters searched by the optim module
+pub(crate) type UtilityCallback<'a> = Box<dyn FnMut(&[f32]) -> f32 + 'a>;
+
 #[doc(hidden)]
 #[derive(Default)]
 pub(crate) struct Callbacks<'a> {
-    pub(crate) firdespm_callback: Option<Box<dyn FnMut(f64, &mut f64, &mut f64) -> i8 + 'a>>,
+    pub(crate) firdespm_callback: Option<FirdespmCallback<'a>>,
+    pub(crate) utility_callback: Option<UtilityCallback<'a>>,
+    pub(crate) framesync_callback: Option<FramesyncCallback<'a>>,
+    pub(crate) framesync_header_len: usize,
+    /// frame counters kept up to date by the frame synchronizer callbacks
+    pub(crate) framedatastats: FrameDataStats,
 }
@@ -1,25 +1,60 @@
-// use std::marker::PhantomData;
 use libc::c_uint;
 use std::fmt;
 use std::slice;
 
 use crate::errors::LiquidError;
 use crate::liquid_dsp_sys as raw;
-use crate::utils::{ToCPointer, ToCPointerMut, ToCValue};
+use crate::utils::ToCValue;
+use crate::LiquidResult;
 use num::complex::Complex32;
 
-pub struct CbufferRf {
-    inner: raw::cbufferf,
-    num_elements: u32,
+mod private {
+    pub trait Sealed {}
+
+    impl Sealed for f32 {}
+    impl Sealed for num::complex::Complex32 {}
 }
 
-pub struct CbufferCf {
-    inner: raw::cbuffercf,
-    num_elements: u32,
+/// sample types that can be stored in a Cbuffer: f32 and Complex32
+pub trait CbufferSample: private::Sealed + Copy + Default {
+    #[doc(hidden)]
+    type Raw: Copy;
+    #[doc(hidden)]
+    const NAME: &'static str;
+    #[doc(hidden)]
+    unsafe fn create(max_size: c_uint) -> Self::Raw;
+    #[doc(hidden)]
+    unsafe fn create_max(max_size: c_uint, max_read: c_uint) -> Self::Raw;
+    #[doc(hidden)]
+    unsafe fn reset(q: Self::Raw);
+    #[doc(hidden)]
+    unsafe fn size(q: Self::Raw) -> c_uint;
+    #[doc(hidden)]
+    unsafe fn max_size(q: Self::Raw) -> c_uint;
+    #[doc(hidden)]
+    unsafe fn max_read(q: Self::Raw) -> c_uint;
+    #[doc(hidden)]
+    unsafe fn space_available(q: Self::Raw) -> c_uint;
+    #[doc(hidden)]
+    unsafe fn is_full(q: Self::Raw) -> bool;
+    #[doc(hidden)]
+    unsafe fn debug_print(q: Self::Raw);
+    #[doc(hidden)]
+    unsafe fn release(q: Self::Raw, n: c_uint);
+    #[doc(hidden)]
+    unsafe fn push(q: Self::Raw, v: Self);
+    #[doc(hidden)]
+    unsafe fn write(q: Self::Raw, v: *const Self, n: c_uint);
+    #[doc(hidden)]
+    unsafe fn pop(q: Self::Raw, v: *mut Self);
+    #[doc(hidden)]
+    unsafe fn read(q: Self::Raw, n: c_uint, v: *mut *const Self, num_read: *mut c_uint);
+    #[doc(hidden)]
+    unsafe fn destroy(q: Self::Raw);
 }
 
-macro_rules! cbuffer_xxx_impl {
-    ($obj:ty, (
+macro_rules! cbuffer_sample_impl {
+    ($type:ty, $raw:ty, $name:expr, (
         $create:expr, $create_max:expr,
         $reset:expr, $size:expr,
         $max_size:expr,$max_read:expr,
@@ -27,164 +62,78 @@ macro_rules! cbuffer_xxx_impl {
         $debug_print:expr,$release:expr,
         $push:expr, $write:expr,
         $pop:expr, $read:expr,
-        $destroy:expr, $type:ty)) => {
-        impl $obj {
-            /// creates a circular buffer object that can hold up to *max_size* samples
-            pub fn create(max_size: u32) -> Self {
-                Self {
-                    inner: unsafe { $create(max_size as _) },
-                    num_elements: 0,
-                }
-            }
-
-            /// create circular buffer object of a particular size
-            ///
-            /// and specify the maximum number of elements that can be read
-            /// at any given time.
-            pub fn create_max(max_size: u32, max_read: u32) -> Self {
-                Self {
-                    inner: unsafe { $create_max(max_size as _, max_read as _) },
-                    num_elements: 0,
-                }
-            }
-
-            pub fn reset(&mut self) {
-                unsafe {
-                    $reset(self.inner);
-                }
-            }
-
-            ///  returns the number of elements currently in the buffer
-            pub fn size(&self) -> u32 {
-                unsafe { $size(self.inner) as u32 }
-            }
-
-            /// returns the maximum number of elements the buffer can hold
-            pub fn max_size(&self) -> u32 {
-                unsafe { $max_size(self.inner) as u32 }
-            }
-
-            /// Returns the maximum number of elements that can be read from
-            /// the buffer at any given time.
-            pub fn max_read(&self) -> u32 {
-                unsafe { $max_read(self.inner) as u32 }
-            }
-
-            /// return number of elements available for writing
-            pub fn space_available(&self) -> u32 {
-                unsafe { $space_available(self.inner) as u32 }
-            }
-
-            // TODO check it
-            pub fn is_full(&self) -> bool {
-                unsafe { $is_full(self.inner) == 1 }
-            }
-
-            /// print cbuffer object properties and internal state
-            pub fn debug_print(&self) {
-                unsafe {
-                    $debug_print(self.inner);
-                }
-            }
-
-            pub fn release(&mut self, n: usize) -> Result<(), LiquidError> {
-                if n > self.num_elements as usize {
-                    return Err(LiquidError::EmptyBuffer);
-                }
-                unsafe {
-                    $release(self.inner, n as c_uint);
-                    Ok(())
-                }
-            }
-            /// write a single sample into the buffer
-            pub fn push(&mut self, v: $type) -> Result<(), &'static str> {
-                if self.num_elements == self.max_size() {
-                    return Err("warning: no space available");
-                }
-                unsafe {
-                    $push(self.inner, v.to_c_value());
-                    self.num_elements += 1;
-                    Ok(())
-                }
-            }
-
-            // write samples from the buffer
-            pub fn write(&mut self, buffer: &[$type]) -> Result<(), &'static str> {
-                if buffer.len() > self.space_available() as usize {
-                    return Err("cannot write more elements than are available");
-                }
-                unsafe {
-                    $write(
-                        self.inner,
-                        buffer.to_ptr() as *mut _,
-                        buffer.len() as c_uint,
-                    );
-                    self.num_elements += buffer.len() as u32;
-                    Ok(())
-                }
-            }
-
-            /// remove and return a single element from the buffer
-            pub fn pop(&mut self) -> Option<$type> {
-                if self.num_elements == 0u32 {
-                    return None;
-                }
-                unsafe {
-                    let mut out = <$type>::default();
-                    $pop(self.inner, out.to_ptr_mut());
-                    self.num_elements -= 1;
-                    Some(out)
-                }
+        $destroy:expr)) => {
+        impl CbufferSample for $type {
+            type Raw = $raw;
+            const NAME: &'static str = $name;
+
+            unsafe fn create(max_size: c_uint) -> Self::Raw {
+                $create(max_size)
             }
 
-            pub fn read(&self) -> &[$type] {
-                let ptr = &mut <$type>::default().to_ptr_mut() as *mut _;
-                let mut len = 0u32;
-                unsafe {
-                    $read(
-                        self.inner,
-                        self.num_elements as c_uint,
-                        ptr,
-                        &mut len as *mut _,
-                    );
-                    slice::from_raw_parts(*ptr as *const _, len as usize)
-                }
+            unsafe fn create_max(max_size: c_uint, max_read: c_uint) -> Self::Raw {
+                $create_max(max_size, max_read)
+            }
+
+            unsafe fn reset(q: Self::Raw) {
+                $reset(q)
+            }
+
+            unsafe fn size(q: Self::Raw) -> c_uint {
+                $size(q)
+            }
+
+            unsafe fn max_size(q: Self::Raw) -> c_uint {
+                $max_size(q)
+            }
+
+            unsafe fn max_read(q: Self::Raw) -> c_uint {
+                $max_read(q)
+            }
+
+            unsafe fn space_available(q: Self::Raw) -> c_uint {
+                $space_available(q)
+            }
+
+            unsafe fn is_full(q: Self::Raw) -> bool {
+                $is_full(q) == 1
             }
-        }
 
-        impl fmt::Debug for $obj {
-            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-                write!(
-                    f,
-                    "cbuffer: {} [max size: {}, max read: {}, elements: {}]:\n",
-                    stringify!($obj),
-                    self.max_size(),
-                    self.max_read(),
-                    self.num_elements
-                )
-            }
-        }
-
-        impl Drop for $obj {
-            fn drop(&mut self) {
-                unsafe {
-                    $destroy(self.inner);
-                }
-            }
-        }
-
-        impl AsRef<[$type]> for $obj {
-            #[inline]
-            fn as_ref(&self) -> &[$type] {
-                self.read()
+            unsafe fn debug_print(q: Self::Raw) {
+                $debug_print(q)
+            }
+
+            unsafe fn release(q: Self::Raw, n: c_uint) {
+                $release(q, n)
+            }
+
+            unsafe fn push(q: Self::Raw, v: Self) {
+                $push(q, v.to_c_value())
+            }
+
+            unsafe fn write(q: Self::Raw, v: *const Self, n: c_uint) {
+                $write(q, v as *mut _, n)
+            }
+
+            unsafe fn pop(q: Self::Raw, v: *mut Self) {
+                $pop(q, v as *mut _)
+            }
+
+            unsafe fn read(q: Self::Raw, n: c_uint, v: *mut *const Self, num_read: *mut c_uint) {
+                $read(q, n, v as *mut _, num_read)
+            }
+
+            unsafe fn destroy(q: Self::Raw) {
+                $destroy(q)
             }
         }
     };
 }
 
-
-cbuffer_xxx_impl!(
-    CbufferRf,
+cbuffer_sample_impl!(
+    f32,
+    raw::cbufferf,
+    "CbufferRf",
     (
         raw::cbufferf_create,
         raw::cbufferf_create_max,
@@ -200,13 +149,14 @@ cbuffer_xxx_impl!(
         raw::cbufferf_write,
         raw::cbufferf_pop,
         raw::cbufferf_read,
-        raw::cbufferf_destroy,
-        f32
+        raw::cbufferf_destroy
     )
 );
 
-cbuffer_xxx_impl!(
-    CbufferCf,
+cbuffer_sample_impl!(
+    Complex32,
+    raw::cbuffercf,
+    "CbufferCf",
     (
         raw::cbuffercf_create,
         raw::cbuffercf_create_max,
@@ -222,26 +172,300 @@ cbuffer_xxx_impl!(
         raw::cbuffercf_write,
         raw::cbuffercf_pop,
         raw::cbuffercf_read,
-        raw::cbuffercf_destroy,
-        Complex32
+        raw::cbuffercf_destroy
     )
 );
 
+/// circular buffer of f32 or Complex32 samples; the element count is
+/// always queried from the liquid object
+pub struct Cbuffer<T: CbufferSample> {
+    inner: T::Raw,
+}
+
+pub type CbufferRf = Cbuffer<f32>;
+pub type CbufferCf = Cbuffer<Complex32>;
+
+impl<T: CbufferSample> Cbuffer<T> {
+    /// creates a circular buffer object that can hold up to *max_size* samples
+    pub fn create(max_size: u32) -> Self {
+        Self {
+            inner: unsafe { T::create(max_size as _) },
+        }
+    }
+
+    /// create circular buffer object of a particular size
+    ///
+    /// and specify the maximum number of elements that can be read
+    /// at any given time.
+    pub fn create_max(max_size: u32, max_read: u32) -> Self {
+        Self {
+            inner: unsafe { T::create_max(max_size as _, max_read as _) },
+        }
+    }
+
+    /// remove all the elements from the buffer
+    pub fn reset(&mut self) {
+        unsafe {
+            T::reset(self.inner);
+        }
+    }
+
+    ///  returns the number of elements currently in the buffer
+    pub fn size(&self) -> u32 {
+        unsafe { T::size(self.inner) }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.size() == 0
+    }
+
+    /// returns the maximum number of elements the buffer can hold
+    pub fn max_size(&self) -> u32 {
+        unsafe { T::max_size(self.inner) }
+    }
+
+    /// Returns the maximum number of elements that can be read from
+    /// the buffer at any given time.
+    pub fn max_read(&self) -> u32 {
+        unsafe { T::max_read(self.inner) }
+    }
+
+    /// return number of elements available for writing
+    pub fn space_available(&self) -> u32 {
+        unsafe { T::space_available(self.inner) }
+    }
+
+    // TODO check it
+    pub fn is_full(&self) -> bool {
+        unsafe { T::is_full(self.inner) }
+    }
+
+    /// print cbuffer object properties and internal state
+    pub fn debug_print(&self) {
+        unsafe {
+            T::debug_print(self.inner);
+        }
+    }
+
+    /// remove the n oldest elements from the buffer
+    /// # returns
+    /// LiquidError::EmptyBuffer, without removing anything, if the buffer
+    /// holds fewer than n elements
+    pub fn release(&mut self, n: usize) -> Result<(), LiquidError> {
+        if n > self.size() as usize {
+            return Err(LiquidError::EmptyBuffer);
+        }
+        unsafe {
+            T::release(self.inner, n as c_uint);
+            Ok(())
+        }
+    }
+
+    /// write a single sample into the buffer
+    /// # returns
+    /// LiquidError::BufferFull if there is no space available
+    pub fn push(&mut self, v: T) -> LiquidResult<()> {
+        self.try_push(v).map_err(|_| LiquidError::BufferFull)
+    }
+
+    /// write a single sample into the buffer, handing it back
+    /// if there is no space available
+    pub fn try_push(&mut self, v: T) -> Result<(), T> {
+        if self.space_available() == 0 {
+            return Err(v);
+        }
+        unsafe {
+            T::push(self.inner, v);
+        }
+        Ok(())
+    }
+
+    /// write all the samples into the buffer
+    /// # returns
+    /// LiquidError::BufferFull, without writing anything, if the
+    /// samples do not fit
+    pub fn write(&mut self, buffer: &[T]) -> LiquidResult<()> {
+        if buffer.len() > self.space_available() as usize {
+            return Err(LiquidError::BufferFull);
+        }
+        unsafe {
+            T::write(self.inner, buffer.as_ptr(), buffer.len() as c_uint);
+        }
+        Ok(())
+    }
+
+    /// write as many samples as fit into the buffer
+    /// # returns
+    /// the number of samples written
+    pub fn extend_from_slice(&mut self, buffer: &[T]) -> usize {
+        let n = buffer.len().min(self.space_available() as usize);
+        if n > 0 {
+            unsafe {
+                T::write(self.inner, buffer.as_ptr(), n as c_uint);
+            }
+        }
+        n
+    }
+
+    /// remove and return a single element from the buffer
+    pub fn pop(&mut self) -> Option<T> {
+        if self.is_empty() {
+            return None;
+        }
+        let mut out = T::default();
+        unsafe {
+            T::pop(self.inner, &mut out as *mut _);
+        }
+        Some(out)
+    }
+
+    /// remove up to out.len() samples from the buffer, oldest first, in
+    /// blocks of at most max_read() samples
+    /// # returns
+    /// the number of samples written at the start of out
+    pub fn pop_slice(&mut self, out: &mut [T]) -> usize {
+        let mut n = 0;
+        while n < out.len() && !self.is_empty() {
+            let m = {
+                let v = self.read_n(out.len() - n);
+                out[n..n + v.len()].copy_from_slice(v);
+                v.len()
+            };
+            if m == 0 {
+                break;
+            }
+            unsafe {
+                T::release(self.inner, m as c_uint);
+            }
+            n += m;
+        }
+        n
+    }
+
+    /// remove up to n samples from the buffer, oldest first; like
+    /// Vec::drain, the samples are removed even if the iterator is not
+    /// consumed
+    pub fn drain(&mut self, n: usize) -> impl ExactSizeIterator<Item = T> {
+        let mut out = vec![T::default(); n.min(self.size() as usize)];
+        let len = self.pop_slice(&mut out);
+        out.truncate(len);
+        out.into_iter()
+    }
+
+    pub fn read(&self) -> &[T] {
+        self.read_n(self.size() as usize)
+    }
+
+    /// up to n of the oldest samples, without removing them
+    fn read_n(&self, n: usize) -> &[T] {
+        let mut ptr: *const T = std::ptr::null();
+        let mut len = 0u32;
+        unsafe {
+            T::read(
+                self.inner,
+                n as c_uint,
+                &mut ptr as *mut _,
+                &mut len as *mut _,
+            );
+            if ptr.is_null() || len == 0 {
+                return &[];
+            }
+            slice::from_raw_parts(ptr, len as usize)
+        }
+    }
+}
+
+impl<T: CbufferSample> fmt::Debug for Cbuffer<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "cbuffer: {} [max size: {}, max read: {}, elements: {}]:",
+            T::NAME,
+            self.max_size(),
+            self.max_read(),
+            self.size()
+        )
+    }
+}
+
+impl<T: CbufferSample> Drop for Cbuffer<T> {
+    fn drop(&mut self) {
+        unsafe {
+            T::destroy(self.inner);
+        }
+    }
+}
+
+impl<T: CbufferSample> AsRef<[T]> for Cbuffer<T> {
+    #[inline]
+    fn as_ref(&self) -> &[T] {
+        self.read()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::CbufferRf;
 
     #[test]
     fn test_cbufferf() {
-        let mut v = [1.2, 2.5, 3.6, 4.4, 5.8, 6.9, 7.8, 8.98];
+        let v = [1.2, 2.5, 3.6, 4.4, 5.8, 6.9, 7.8, 8.98];
 
         let mut cb = CbufferRf::create(10);
 
-        cb.write(&mut v).unwrap();
+        cb.write(&v).unwrap();
         assert_eq!(cb.read(), &v);
 
         // release 2 elements from the buffer
         cb.release(2).unwrap();
         assert_eq!(cb.space_available(), 4);
     }
+
+    #[test]
+    fn test_cbufferf_full() {
+        let mut cb = CbufferRf::create(4);
+        assert_eq!(cb.extend_from_slice(&[1.0, 2.0, 3.0]), 3);
+        assert!(cb.write(&[4.0, 5.0]).is_err());
+        assert_eq!(cb.extend_from_slice(&[4.0, 5.0]), 1);
+        assert_eq!(cb.try_push(6.0), Err(6.0));
+        assert!(cb.push(6.0).is_err());
+        assert_eq!(cb.read(), &[1.0, 2.0, 3.0, 4.0]);
+    }
+
+    #[test]
+    fn test_cbufferf_pop_slice() {
+        let mut cb = CbufferRf::create_max(8, 3);
+        cb.write(&[1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0]).unwrap();
+
+        // more than max_read samples at once
+        let mut out = [0f32; 5];
+        assert_eq!(cb.pop_slice(&mut out), 5);
+        assert_eq!(out, [1.0, 2.0, 3.0, 4.0, 5.0]);
+        assert_eq!(cb.space_available(), 6);
+
+        cb.write(&[8.0, 9.0]).unwrap();
+        let v: Vec<f32> = cb.drain(3).collect();
+        assert_eq!(v, vec![6.0, 7.0, 8.0]);
+        assert_eq!(cb.drain(10).len(), 1);
+        assert_eq!(cb.pop_slice(&mut out), 0);
+        assert_eq!(cb.pop(), None);
+    }
+
+    #[test]
+    fn test_cbufferf_reset_release() {
+        let mut cb = CbufferRf::create(4);
+        assert!(cb.is_empty());
+        cb.write(&[1.0, 2.0, 3.0, 4.0]).unwrap();
+        assert!(cb.is_full());
+        cb.reset();
+        assert!(cb.is_empty());
+        // the whole capacity is available again
+        cb.write(&[5.0, 6.0, 7.0, 8.0]).unwrap();
+
+        assert!(cb.release(5).is_err());
+        cb.release(3).unwrap();
+        assert_eq!(cb.size(), 1);
+        cb.push(9.0).unwrap();
+        assert_eq!(cb.read(), &[8.0, 9.0]);
+    }
 }
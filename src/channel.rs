@@ -1,14 +1,125 @@
 use libc::c_uint;
-use std::ptr;
-
 use num::complex::Complex32;
 
 use crate::liquid_dsp_sys as raw;
 
-use crate::utils::{LiquidFloatComplex, ToCPointer, ToCPointerMut, ToCValue};
+use crate::errors::LiquidError;
+use crate::impairments::Nonlinearity;
+use crate::utils::{check_same_len, ToCPointer, ToCPointerMut, ToCValue};
+use crate::LiquidResult;
+
+const MAX_MULTIPATH_LEN: usize = 1000;
+
+/// builder for the ChannelCccf object, impairments not configured are
+/// left disabled, e.g.
+/// ChannelBuilder::new().awgn(-60.0, 20.0).carrier_offset(0.01, 0.0).build()
+#[derive(Clone, Debug, Default)]
+pub struct ChannelBuilder {
+    awgn: Option<(f32, f32)>,
+    carrier_offset: Option<(f32, f32)>,
+    multipath: Option<Vec<Complex32>>,
+    shadowing: Option<(f32, f32)>,
+    nonlinearity: Option<Nonlinearity>,
+}
+
+impl ChannelBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// additive white Gauss noise
+    ///  n0db   :   noise floor power spectral density [dB]
+    ///  snrdb  :   signal-to-noise ratio [dB]
+    pub fn awgn(mut self, n0db: f32, snrdb: f32) -> Self {
+        self.awgn = Some((n0db, snrdb));
+        self
+    }
+
+    /// carrier offset
+    ///  frequency  :   carrier frequency offset [radians/sample]
+    ///  phase      :   carrier phase offset [radians]
+    pub fn carrier_offset(mut self, frequency: f32, phase: f32) -> Self {
+        self.carrier_offset = Some((frequency, phase));
+        self
+    }
+
+    /// multi-path channel
+    ///  h      :   channel coefficients, 0 < h.len() <= 1000
+    pub fn multipath(mut self, h: &[Complex32]) -> Self {
+        self.multipath = Some(h.to_vec());
+        self
+    }
+
+    /// slowly-varying log-normal shadowing
+    ///  sigma  :   standard deviation, sigma > 0
+    ///  fd     :   Doppler frequency, in (0, 0.5)
+    pub fn shadowing(mut self, sigma: f32, fd: f32) -> Self {
+        self.shadowing = Some((sigma, fd));
+        self
+    }
+
+    /// memoryless nonlinearity of the transmitter's power amplifier,
+    /// applied before the other impairments
+    pub fn nonlinearity(mut self, model: Nonlinearity) -> Self {
+        self.nonlinearity = Some(model);
+        self
+    }
+
+    pub fn build(self) -> LiquidResult<ChannelCccf> {
+        if let Some(h) = &self.multipath {
+            check_multipath_len(h.len())?;
+        }
+        if let Some((sigma, fd)) = self.shadowing {
+            check_shadowing(sigma, fd)?;
+        }
+        let mut channel = ChannelCccf::create();
+        channel.nonlinearity = self.nonlinearity;
+        if let Some((n0db, snrdb)) = self.awgn {
+            channel.add_awgn(n0db, snrdb);
+        }
+        if let Some((frequency, phase)) = self.carrier_offset {
+            channel.add_carrier_offset(frequency, phase);
+        }
+        if let Some(h) = &self.multipath {
+            channel.add_multipath(h)?;
+        }
+        if let Some((sigma, fd)) = self.shadowing {
+            channel.add_shadowing(sigma, fd)?;
+        }
+        Ok(channel)
+    }
+}
+
+fn check_multipath_len(len: usize) -> LiquidResult<()> {
+    if len == 0 || len > MAX_MULTIPATH_LEN {
+        return Err(LiquidError::InvalidLength {
+            description: format!(
+                "number of coefficients: {} must be in [1, {}]",
+                len, MAX_MULTIPATH_LEN
+            ),
+        });
+    }
+    Ok(())
+}
+
+fn check_shadowing(sigma: f32, fd: f32) -> LiquidResult<()> {
+    if sigma <= 0f32 {
+        return Err(LiquidError::InvalidValue(
+            "standard deviation must be greater than zero".to_owned(),
+        ));
+    } else if fd <= 0f32 || fd >= 0.5 {
+        return Err(LiquidError::InvalidValue(
+            "Doppler frequency must be in (0, 0.5)".to_owned(),
+        ));
+    }
+    Ok(())
+}
 
 pub struct ChannelCccf {
     inner: raw::channel_cccf,
+    // liquid's channel has no nonlinearity, it is applied here on the
+    // input samples
+    nonlinearity: Option<Nonlinearity>,
 }
 
 impl ChannelCccf {
@@ -17,10 +128,16 @@ impl ChannelCccf {
         unsafe {
             Self {
                 inner: raw::channel_cccf_create(),
+                nonlinearity: None,
             }
         }
     }
 
+    /// builder for a channel with the impairments applied declaratively
+    pub fn builder() -> ChannelBuilder {
+        ChannelBuilder::new()
+    }
+
     /// print channel object
     pub fn print(&self) {
         unsafe {
@@ -47,50 +164,66 @@ impl ChannelCccf {
     }
 
     /// apply multi-path channel impairment
-    ///  h : channel coefficients
-    pub fn add_multipath(&mut self, h: &[Complex32]) {
-        assert!(
-            h.is_empty() || h.len() <= 1000,
-            "The number of coeficients must be > 0 and <= 1000"
-        );
+    ///  h : channel coefficients, 0 < h.len() <= 1000
+    pub fn add_multipath(&mut self, h: &[Complex32]) -> LiquidResult<()> {
+        check_multipath_len(h.len())?;
         unsafe {
             raw::channel_cccf_add_multipath(self.inner, h.to_ptr() as *mut _, h.len() as c_uint);
         }
+        Ok(())
     }
 
-    /// apply multi-path channel impairment
-    ///  len : number of auto-generated ramdom coeficients
-    pub fn add_multipath_random(&mut self, len: u32) {
-        assert!(
-            len > 0 && len <= 1000,
-            "The number of coeficients must be > 0 and <= 1000"
-        );
-        let ptr: *mut LiquidFloatComplex = ptr::null_mut();
-        unsafe {
-            raw::channel_cccf_add_multipath(self.inner, ptr, len as c_uint);
-        }
+    /// apply multi-path channel impairment with random coefficients,
+    /// a unit direct path followed by len-1 complex Gauss taps with a
+    /// standard deviation of 0.1 per component, drawn from liquid's
+    /// random number generator (C library rand())
+    ///  len : number of coefficients, 0 < len <= 1000
+    /// # returns
+    /// the generated coefficients, which can be handed to add_multipath
+    /// to reproduce the channel
+    pub fn add_multipath_random(&mut self, len: u32) -> LiquidResult<Vec<Complex32>> {
+        check_multipath_len(len as usize)?;
+        let h: Vec<Complex32> = (0..len)
+            .map(|i| {
+                if i == 0 {
+                    Complex32::new(1.0, 0.0)
+                } else {
+                    unsafe { Complex32::new(raw::randnf(), raw::randnf()) * 0.1 }
+                }
+            })
+            .collect();
+        self.add_multipath(&h)?;
+        Ok(h)
     }
 
     /// apply slowly-varying shadowing impairment
-    ///  sigma      : std. deviation for log-normal shadowing
-    ///  fd         : Doppler frequency, _fd in (0,0.5)
-    pub fn add_shadowing(&mut self, sigma: f32, fd: f32) {
-        assert!(
-            sigma <= 0f32,
-            "standard deviation less than or equal to zero"
-        );
-
-        assert!(
-            fd <= 0f32 || fd >= 0.5,
-            " Doppler frequency must be in (0,0.5)"
-        );
+    ///  sigma      : std. deviation for log-normal shadowing, sigma > 0
+    ///  fd         : Doppler frequency, fd in (0,0.5)
+    pub fn add_shadowing(&mut self, sigma: f32, fd: f32) -> LiquidResult<()> {
+        check_shadowing(sigma, fd)?;
         unsafe {
             raw::channel_cccf_add_shadowing(self.inner, sigma, fd);
         }
+        Ok(())
+    }
+
+    /// apply a memoryless power amplifier nonlinearity, replacing the
+    /// previous one
+    pub fn add_nonlinearity(&mut self, model: Nonlinearity) {
+        self.nonlinearity = Some(model);
+    }
+
+    /// nonlinearity applied on the input samples, if any
+    pub fn get_nonlinearity(&self) -> Option<Nonlinearity> {
+        self.nonlinearity
     }
 
     /// apply channel impairments on single input sample
-    pub fn execute(&self, sample: Complex32) -> Complex32 {
+    pub fn execute(&mut self, sample: Complex32) -> Complex32 {
+        let sample = match &self.nonlinearity {
+            Some(model) => model.apply_sample(sample),
+            None => sample,
+        };
         let mut out = Complex32::default();
         unsafe {
             raw::channel_cccf_execute(self.inner, sample.to_c_value(), out.to_ptr_mut());
@@ -98,11 +231,28 @@ impl ChannelCccf {
         out
     }
 
-    pub fn execute_block(&self, input: &[Complex32], output: &mut [Complex32]) {
-        assert!(
-            input.len() == output.len(),
-            "buffers must have the same lenght"
-        );
+    /// apply channel impairments on a block of samples
+    /// # panics
+    /// if the buffers have different lengths, see try_execute_block
+    pub fn execute_block(&mut self, input: &[Complex32], output: &mut [Complex32]) {
+        self.try_execute_block(input, output)
+            .unwrap_or_else(|e| panic!("{}", e));
+    }
+
+    /// apply channel impairments on a block of samples
+    /// # returns
+    /// LiquidError::InvalidLength if the buffers have different lengths
+    pub fn try_execute_block(
+        &mut self,
+        input: &[Complex32],
+        output: &mut [Complex32],
+    ) -> LiquidResult<()> {
+        check_same_len(input.len(), output.len())?;
+        if self.nonlinearity.is_some() {
+            output.copy_from_slice(input);
+            self.execute_block_in_place(output);
+            return Ok(());
+        }
         unsafe {
             raw::channel_cccf_execute_block(
                 self.inner,
@@ -111,6 +261,23 @@ impl ChannelCccf {
                 output.to_ptr_mut(),
             );
         }
+        Ok(())
+    }
+
+    /// apply channel impairments on a block of samples in place, the
+    /// output overwriting the input
+    pub fn execute_block_in_place(&mut self, x: &mut [Complex32]) {
+        if let Some(model) = &self.nonlinearity {
+            model.apply(x);
+        }
+        unsafe {
+            raw::channel_cccf_execute_block(
+                self.inner,
+                x.to_ptr_mut(),
+                x.len() as c_uint,
+                x.to_ptr_mut(),
+            );
+        }
     }
 }
 
@@ -121,3 +288,53 @@ impl Drop for ChannelCccf {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{ChannelBuilder, ChannelCccf};
+    use crate::impairments::Nonlinearity;
+    use num::complex::Complex32;
+
+    #[test]
+    fn test_channel_builder_validation() {
+        assert!(ChannelBuilder::new().shadowing(0.0, 0.1).build().is_err());
+        assert!(ChannelBuilder::new().shadowing(1.0, 0.5).build().is_err());
+        assert!(ChannelBuilder::new().multipath(&[]).build().is_err());
+        assert!(ChannelCccf::create().add_multipath_random(0).is_err());
+        assert!(ChannelBuilder::new()
+            .awgn(-60.0, 20.0)
+            .carrier_offset(0.01, 0.0)
+            .multipath(&[Complex32::new(1.0, 0.0), Complex32::new(0.0, 0.1)])
+            .shadowing(1.0, 0.1)
+            .nonlinearity(Nonlinearity::rapp(1.0, 0.8, 2.0).unwrap())
+            .build()
+            .is_ok());
+
+        let clip = Nonlinearity::clip(0.5).unwrap();
+        let channel = ChannelBuilder::new().nonlinearity(clip).build().unwrap();
+        assert_eq!(channel.get_nonlinearity(), Some(clip));
+    }
+
+    #[test]
+    fn test_channel_multipath_random() {
+        let mut channel = ChannelCccf::create();
+        let h = channel.add_multipath_random(6).unwrap();
+        assert_eq!(h.len(), 6);
+        assert_eq!(h[0], Complex32::new(1.0, 0.0));
+        // Gauss taps with a standard deviation of 0.1
+        assert!(h[1..].iter().all(|v| v.re.abs() < 0.6 && v.im.abs() < 0.6));
+        assert!(h[1..].iter().any(|v| v.norm() > 0.0));
+
+        // the channel filters with the returned taps
+        let mut x = vec![Complex32::default(); 8];
+        x[0] = Complex32::new(1.0, 0.0);
+        let mut y = vec![Complex32::default(); 8];
+        channel.execute_block(&x, &mut y);
+        for (a, b) in y
+            .iter()
+            .zip(h.iter().chain([Complex32::default(); 2].iter()))
+        {
+            assert!((a - b).norm() < 1e-6);
+        }
+    }
+}
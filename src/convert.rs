@@ -0,0 +1,303 @@
+//! Conversion between the interleaved integer I/Q delivered by SDR
+//! front-ends and Complex32 samples
+//!
+//! The slice functions are plain loops over fixed size chunks that the
+//! compiler vectorizes; the blocks wrap them for use in a Pipeline, keeping
+//! an incomplete I/Q pair for the next call and optionally removing the DC
+//! offset of the receiver.
+use num::complex::Complex32;
+
+use crate::block::Block;
+use crate::errors::LiquidError;
+use crate::LiquidResult;
+
+fn check_pairs(x: usize, y: usize) -> LiquidResult<()> {
+    if x != 2 * y {
+        return Err(LiquidError::InvalidLength {
+            description: format!(
+                "interleaved length: {} must be twice the complex length: {}",
+                x, y
+            ),
+        });
+    }
+    Ok(())
+}
+
+fn check_full_scale(full_scale: f32) -> LiquidResult<()> {
+    if full_scale <= 0f32 || !full_scale.is_finite() {
+        return Err(LiquidError::InvalidValue(format!(
+            "full scale: {} must be greater than zero",
+            full_scale
+        )));
+    }
+    Ok(())
+}
+
+/// convert interleaved signed 16-bit I/Q into complex samples
+///  x          :   interleaved I/Q [size: 2n x 1]
+///  y          :   complex samples [size: n x 1]
+///  full_scale :   integer value mapped to 1.0, e.g. 32768 for 16-bit or
+///                 2048 for 12-bit converters
+pub fn sc16_to_complex32(x: &[i16], y: &mut [Complex32], full_scale: f32) -> LiquidResult<()> {
+    check_pairs(x.len(), y.len())?;
+    let g = 1.0 / full_scale;
+    for (v, iq) in y.iter_mut().zip(x.chunks_exact(2)) {
+        *v = Complex32::new(iq[0] as f32 * g, iq[1] as f32 * g);
+    }
+    Ok(())
+}
+
+/// convert complex samples into interleaved signed 16-bit I/Q, saturating
+/// the values out of range
+///  x          :   complex samples [size: n x 1]
+///  y          :   interleaved I/Q [size: 2n x 1]
+///  full_scale :   integer value 1.0 is mapped to
+pub fn complex32_to_sc16(x: &[Complex32], y: &mut [i16], full_scale: f32) -> LiquidResult<()> {
+    check_pairs(y.len(), x.len())?;
+    let sat = |v: f32| v.round().max(i16::MIN as f32).min(i16::MAX as f32) as i16;
+    for (iq, v) in y.chunks_exact_mut(2).zip(x) {
+        iq[0] = sat(v.re * full_scale);
+        iq[1] = sat(v.im * full_scale);
+    }
+    Ok(())
+}
+
+/// convert interleaved signed 8-bit I/Q into complex samples, 128 being
+/// mapped to 1.0
+pub fn sc8_to_complex32(x: &[i8], y: &mut [Complex32]) -> LiquidResult<()> {
+    check_pairs(x.len(), y.len())?;
+    for (v, iq) in y.iter_mut().zip(x.chunks_exact(2)) {
+        *v = Complex32::new(iq[0] as f32 / 128.0, iq[1] as f32 / 128.0);
+    }
+    Ok(())
+}
+
+/// convert interleaved offset-binary 8-bit I/Q (e.g. RTL-SDR) into complex
+/// samples, mapping [0, 255] onto [-1, 1]
+pub fn cu8_to_complex32(x: &[u8], y: &mut [Complex32]) -> LiquidResult<()> {
+    check_pairs(x.len(), y.len())?;
+    for (v, iq) in y.iter_mut().zip(x.chunks_exact(2)) {
+        *v = Complex32::new(
+            (iq[0] as f32 - 127.5) / 127.5,
+            (iq[1] as f32 - 127.5) / 127.5,
+        );
+    }
+    Ok(())
+}
+
+/// single pole tracker of the DC offset, removed from every sample
+#[derive(Clone, Copy, Debug)]
+struct DcRemoval {
+    alpha: f32,
+    dc: Complex32,
+}
+
+impl DcRemoval {
+    fn apply(&mut self, y: &mut [Complex32]) {
+        for v in y {
+            self.dc += (*v - self.dc) * self.alpha;
+            *v -= self.dc;
+        }
+    }
+}
+
+fn dc_removal(alpha: Option<f32>) -> LiquidResult<Option<DcRemoval>> {
+    match alpha {
+        Some(a) if a <= 0f32 || a >= 1f32 => Err(LiquidError::InvalidValue(
+            "dc removal bandwidth must be in (0, 1)".to_owned(),
+        )),
+        Some(alpha) => Ok(Some(DcRemoval {
+            alpha,
+            dc: Complex32::default(),
+        })),
+        None => Ok(None),
+    }
+}
+
+macro_rules! iq_to_complex_impl {
+    ($(#[$doc:meta])* $obj:ident, $type:ty, |$x:ident, $y:ident, $s:ident| $convert:expr) => {
+        $(#[$doc])*
+        #[derive(Clone, Debug)]
+        pub struct $obj {
+            full_scale: f32,
+            dc: Option<DcRemoval>,
+            // first half of an I/Q pair split between two calls
+            pending: Option<$type>,
+        }
+
+        impl $obj {
+            /// set the DC offset removal
+            ///  alpha  :   bandwidth of the DC tracker, in (0, 1), None to
+            ///             disable it
+            pub fn set_dc_removal(&mut self, alpha: Option<f32>) -> LiquidResult<()> {
+                self.dc = dc_removal(alpha)?;
+                Ok(())
+            }
+
+            /// DC offset currently removed, None if the removal is disabled
+            pub fn get_dc_offset(&self) -> Option<Complex32> {
+                self.dc.map(|dc| dc.dc)
+            }
+
+            pub fn reset(&mut self) {
+                self.pending = None;
+                if let Some(ref mut dc) = self.dc {
+                    dc.dc = Complex32::default();
+                }
+            }
+
+            fn convert(&mut self, input: &[$type], output: &mut Vec<Complex32>) -> LiquidResult<()> {
+                let mut input = input;
+                let start = output.len();
+                if let Some(i) = self.pending {
+                    match input.split_first() {
+                        Some((&q, rest)) => {
+                            let pair = [i, q];
+                            output.push(Complex32::default());
+                            let ($x, $y, $s) = (&pair[..], &mut output[start..], self.full_scale);
+                            $convert?;
+                            self.pending = None;
+                            input = rest;
+                        }
+                        None => return Ok(()),
+                    }
+                }
+                let n = input.len() / 2;
+                let offset = output.len();
+                output.resize(offset + n, Complex32::default());
+                let ($x, $y, $s) = (&input[..2 * n], &mut output[offset..], self.full_scale);
+                $convert?;
+                if input.len() % 2 == 1 {
+                    self.pending = input.last().copied();
+                }
+                if let Some(ref mut dc) = self.dc {
+                    dc.apply(&mut output[start..]);
+                }
+                Ok(())
+            }
+        }
+
+        impl Block<$type, Complex32> for $obj {
+            fn process(&mut self, input: &[$type], output: &mut Vec<Complex32>) -> LiquidResult<()> {
+                self.convert(input, output)
+            }
+
+            fn rate(&self) -> f32 {
+                0.5
+            }
+        }
+    };
+}
+
+iq_to_complex_impl!(
+    /// block converting interleaved signed 16-bit I/Q into complex samples
+    Sc16ToComplex32,
+    i16,
+    |x, y, s| sc16_to_complex32(x, y, s)
+);
+
+iq_to_complex_impl!(
+    /// block converting interleaved offset-binary 8-bit I/Q (e.g. RTL-SDR)
+    /// into complex samples
+    Cu8ToComplex32,
+    u8,
+    |x, y, _s| cu8_to_complex32(x, y)
+);
+
+impl Sc16ToComplex32 {
+    /// create a converter
+    ///  full_scale :   integer value mapped to 1.0, e.g. 32768 for 16-bit
+    ///                 or 2048 for 12-bit converters
+    pub fn create(full_scale: f32) -> LiquidResult<Self> {
+        check_full_scale(full_scale)?;
+        Ok(Self {
+            full_scale,
+            dc: None,
+            pending: None,
+        })
+    }
+}
+
+impl Cu8ToComplex32 {
+    pub fn create() -> Self {
+        Self {
+            full_scale: 127.5,
+            dc: None,
+            pending: None,
+        }
+    }
+}
+
+/// block converting complex samples into interleaved signed 16-bit I/Q,
+/// e.g. to feed a transmitter
+#[derive(Clone, Copy, Debug)]
+pub struct Complex32ToSc16 {
+    full_scale: f32,
+}
+
+impl Complex32ToSc16 {
+    /// create a converter
+    ///  full_scale :   integer value 1.0 is mapped to; values out of the
+    ///                 i16 range saturate
+    pub fn create(full_scale: f32) -> LiquidResult<Self> {
+        check_full_scale(full_scale)?;
+        Ok(Self { full_scale })
+    }
+}
+
+impl Block<Complex32, i16> for Complex32ToSc16 {
+    fn process(&mut self, input: &[Complex32], output: &mut Vec<i16>) -> LiquidResult<()> {
+        let start = output.len();
+        output.resize(start + 2 * input.len(), 0);
+        complex32_to_sc16(input, &mut output[start..], self.full_scale)
+    }
+
+    fn rate(&self) -> f32 {
+        2.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{complex32_to_sc16, Complex32ToSc16, Cu8ToComplex32, Sc16ToComplex32};
+    use crate::block::Block;
+    use num::complex::Complex32;
+
+    #[test]
+    fn test_sc16_round_trip() {
+        let mut conv = Sc16ToComplex32::create(32768.0).unwrap();
+        assert!(Sc16ToComplex32::create(0.0).is_err());
+        let mut y = Vec::new();
+        // the I/Q pair split between calls is kept
+        conv.process(&[16384, -16384, 8192], &mut y).unwrap();
+        assert_eq!(y, vec![Complex32::new(0.5, -0.5)]);
+        conv.process(&[0], &mut y).unwrap();
+        assert_eq!(y[1], Complex32::new(0.25, 0.0));
+        assert_eq!(conv.rate(), 0.5);
+
+        let mut back = Complex32ToSc16::create(32768.0).unwrap();
+        let mut iq = Vec::new();
+        back.process(&y, &mut iq).unwrap();
+        assert_eq!(iq, vec![16384, -16384, 8192, 0]);
+
+        let mut sat = [0i16; 2];
+        complex32_to_sc16(&[Complex32::new(2.0, -2.0)], &mut sat, 32768.0).unwrap();
+        assert_eq!(sat, [i16::MAX, i16::MIN]);
+        assert!(complex32_to_sc16(&[Complex32::default()], &mut [0i16; 3], 1.0).is_err());
+    }
+
+    #[test]
+    fn test_cu8_dc_removal() {
+        let mut conv = Cu8ToComplex32::create();
+        assert!(conv.set_dc_removal(Some(1.0)).is_err());
+        conv.set_dc_removal(Some(0.1)).unwrap();
+        // constant input: the offset is tracked and removed
+        let x = vec![200u8; 2000];
+        let mut y = Vec::new();
+        conv.process(&x, &mut y).unwrap();
+        assert_eq!(y.len(), 1000);
+        assert!(y[999].norm() < 1e-3);
+        let dc = conv.get_dc_offset().unwrap();
+        assert!((dc.re - 72.5 / 127.5).abs() < 1e-3);
+    }
+}
@@ -0,0 +1,399 @@
+//! Regression corpus of designed filters.
+//!
+//! Enumerates a canonical set of parameter combinations for the filter
+//! design routines and hashes the resulting coefficients. Storing the
+//! output of Corpus::emit and feeding it back to Corpus::check detects when
+//! the linked liquid-dsp library changes its numerical behavior.
+use std::fmt;
+use std::str::FromStr;
+
+use crate::errors::LiquidError;
+use crate::filter::{IirdesBandType, IirdesFilterType, IirdesFormat};
+use crate::liquid_dsp_sys as raw;
+use crate::version;
+
+/// coefficients are rounded to this resolution before hashing, so that
+/// differences in the last bits of the floating point results do not
+/// count as a change of behavior
+const HASH_RESOLUTION: f64 = 1e5;
+
+/// design routines covered by the corpus
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub enum CorpusRoutine {
+    Kaiser,
+    Rkaiser,
+    Firdespm,
+    Iirdes,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Params {
+    Kaiser {
+        n: u32,
+        fc: f32,
+        as_: f32,
+        mu: f32,
+    },
+    Rkaiser {
+        k: u32,
+        m: u32,
+        beta: f32,
+        dt: f32,
+    },
+    Firdespm {
+        n: u32,
+        fp: f32,
+        fs: f32,
+    },
+    Iirdes {
+        ftype: IirdesFilterType,
+        btype: IirdesBandType,
+        order: u32,
+        fc: f32,
+        f0: f32,
+        ap: f32,
+        as_: f32,
+    },
+}
+
+/// one design routine run with one canonical parameter combination
+#[derive(Clone, Debug)]
+pub struct CorpusCase {
+    pub routine: CorpusRoutine,
+    pub name: String,
+    params: Params,
+}
+
+/// name and coefficient hash of a corpus case, stored as a single line
+/// with the hash as the last field
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct CorpusEntry {
+    pub name: String,
+    pub hash: u64,
+}
+
+/// result of checking the corpus against reference entries
+#[derive(Clone, Debug)]
+pub struct CorpusReport {
+    /// version reported by the linked library
+    pub library_version: String,
+    /// version of the headers the bindings were generated from
+    pub header_version: String,
+    /// number of cases compared against the reference
+    pub checked: usize,
+    /// cases whose coefficients changed
+    pub mismatches: Vec<String>,
+    /// reference entries without a matching case
+    pub missing: Vec<String>,
+}
+
+pub struct Corpus {}
+
+impl CorpusCase {
+    fn new(routine: CorpusRoutine, params: Params) -> Self {
+        let name = match params {
+            Params::Kaiser { n, fc, as_, mu } => {
+                format!("kaiser n={} fc={:.3} as={:.1} mu={:.3}", n, fc, as_, mu)
+            }
+            Params::Rkaiser { k, m, beta, dt } => {
+                format!("rkaiser k={} m={} beta={:.3} dt={:.3}", k, m, beta, dt)
+            }
+            Params::Firdespm { n, fp, fs } => {
+                format!("firdespm n={} fp={:.3} fs={:.3}", n, fp, fs)
+            }
+            Params::Iirdes {
+                ftype,
+                btype,
+                order,
+                fc,
+                f0,
+                ap,
+                as_,
+            } => format!(
+                "iirdes {:?} {:?} n={} fc={:.3} f0={:.3} ap={:.1} as={:.1}",
+                ftype, btype, order, fc, f0, ap, as_
+            ),
+        };
+        Self {
+            routine,
+            name,
+            params,
+        }
+    }
+
+    /// run the design routine
+    /// # returns
+    /// the filter coefficients; for IIR designs the numerator followed
+    /// by the denominator
+    pub fn design(&self) -> Vec<f32> {
+        match self.params {
+            Params::Kaiser { n, fc, as_, mu } => {
+                let mut h = vec![0f32; n as usize];
+                unsafe {
+                    raw::liquid_firdes_kaiser(n, fc, as_, mu, h.as_mut_ptr());
+                }
+                h
+            }
+            Params::Rkaiser { k, m, beta, dt } => {
+                let mut h = vec![0f32; (2 * k * m + 1) as usize];
+                unsafe {
+                    raw::liquid_firdes_rkaiser(k, m, beta, dt, h.as_mut_ptr());
+                }
+                h
+            }
+            Params::Firdespm { n, fp, fs } => {
+                let mut h = vec![0f32; n as usize];
+                let mut bands = [0.0, fp, fs, 0.5];
+                let mut des = [1.0, 0.0];
+                let mut weights = [1.0, 1.0];
+                unsafe {
+                    raw::firdespm_run(
+                        n,
+                        2,
+                        bands.as_mut_ptr(),
+                        des.as_mut_ptr(),
+                        weights.as_mut_ptr(),
+                        std::ptr::null_mut(),
+                        raw::liquid_firdespm_btype_LIQUID_FIRDESPM_BANDPASS,
+                        h.as_mut_ptr(),
+                    );
+                }
+                h
+            }
+            Params::Iirdes {
+                ftype,
+                btype,
+                order,
+                fc,
+                f0,
+                ap,
+                as_,
+            } => {
+                let len = match btype {
+                    IirdesBandType::LOWPASS | IirdesBandType::HIGHPASS => order + 1,
+                    _ => 2 * order + 1,
+                } as usize;
+                let mut b = vec![0f32; len];
+                let mut a = vec![0f32; len];
+                unsafe {
+                    raw::liquid_iirdes(
+                        u8::from(ftype) as _,
+                        u8::from(btype) as _,
+                        u8::from(IirdesFormat::TF) as _,
+                        order,
+                        fc,
+                        f0,
+                        ap,
+                        as_,
+                        b.as_mut_ptr(),
+                        a.as_mut_ptr(),
+                    );
+                }
+                b.extend_from_slice(&a);
+                b
+            }
+        }
+    }
+
+    /// hash of the designed coefficients
+    pub fn hash(&self) -> u64 {
+        hash_coefficients(&self.design())
+    }
+}
+
+impl Corpus {
+    /// the canonical set of design cases, in a fixed order
+    pub fn cases() -> Vec<CorpusCase> {
+        let mut cases = Vec::new();
+        for &n in [21, 57].iter() {
+            for &fc in [0.1, 0.25].iter() {
+                for &as_ in [40.0, 80.0].iter() {
+                    let params = Params::Kaiser {
+                        n,
+                        fc,
+                        as_,
+                        mu: 0.0,
+                    };
+                    cases.push(CorpusCase::new(CorpusRoutine::Kaiser, params));
+                }
+            }
+        }
+        for &k in [2, 4].iter() {
+            for &m in [3, 7].iter() {
+                for &beta in [0.2, 0.35].iter() {
+                    let params = Params::Rkaiser {
+                        k,
+                        m,
+                        beta,
+                        dt: 0.0,
+                    };
+                    cases.push(CorpusCase::new(CorpusRoutine::Rkaiser, params));
+                }
+            }
+        }
+        for &n in [31, 63].iter() {
+            for &(fp, fs) in [(0.1, 0.15), (0.2, 0.3)].iter() {
+                let params = Params::Firdespm { n, fp, fs };
+                cases.push(CorpusCase::new(CorpusRoutine::Firdespm, params));
+            }
+        }
+        for &ftype in [
+            IirdesFilterType::BUTTER,
+            IirdesFilterType::CHEBY1,
+            IirdesFilterType::CHEBY2,
+            IirdesFilterType::ELLIP,
+            IirdesFilterType::BESSEL,
+        ]
+        .iter()
+        {
+            for &btype in [IirdesBandType::LOWPASS, IirdesBandType::BANDPASS].iter() {
+                let params = Params::Iirdes {
+                    ftype,
+                    btype,
+                    order: 4,
+                    fc: 0.1,
+                    f0: 0.25,
+                    ap: 1.0,
+                    as_: 60.0,
+                };
+                cases.push(CorpusCase::new(CorpusRoutine::Iirdes, params));
+            }
+        }
+        cases
+    }
+
+    /// design every case and hash the coefficients
+    pub fn emit() -> Vec<CorpusEntry> {
+        Self::cases()
+            .iter()
+            .map(|case| CorpusEntry {
+                name: case.name.clone(),
+                hash: case.hash(),
+            })
+            .collect()
+    }
+
+    /// compare the linked library against reference entries,
+    /// typically produced by Corpus::emit with a known good version
+    pub fn check(reference: &[CorpusEntry]) -> CorpusReport {
+        let cases = Self::cases();
+        let mut report = CorpusReport {
+            library_version: version::version().to_owned(),
+            header_version: version::header_version().to_owned(),
+            checked: 0,
+            mismatches: Vec::new(),
+            missing: Vec::new(),
+        };
+        for entry in reference.iter() {
+            match cases.iter().find(|case| case.name == entry.name) {
+                Some(case) => {
+                    report.checked += 1;
+                    if case.hash() != entry.hash {
+                        report.mismatches.push(entry.name.clone());
+                    }
+                }
+                None => report.missing.push(entry.name.clone()),
+            }
+        }
+        report
+    }
+}
+
+impl CorpusReport {
+    /// true if every reference entry was found and matched
+    pub fn is_consistent(&self) -> bool {
+        self.mismatches.is_empty() && self.missing.is_empty()
+    }
+}
+
+impl fmt::Display for CorpusReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "liquid-dsp {} (headers {}): {} checked, {} changed, {} missing",
+            self.library_version,
+            self.header_version,
+            self.checked,
+            self.mismatches.len(),
+            self.missing.len()
+        )?;
+        for name in self.mismatches.iter() {
+            writeln!(f, "  changed: {}", name)?;
+        }
+        for name in self.missing.iter() {
+            writeln!(f, "  missing: {}", name)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for CorpusEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {:016x}", self.name, self.hash)
+    }
+}
+
+impl FromStr for CorpusEntry {
+    type Err = LiquidError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut fields = s.trim().rsplitn(2, ' ');
+        let hash = fields.next().and_then(|h| u64::from_str_radix(h, 16).ok());
+        match (hash, fields.next()) {
+            (Some(hash), Some(name)) => Ok(Self {
+                name: name.to_owned(),
+                hash,
+            }),
+            _ => Err(LiquidError::InvalidValue(format!(
+                "invalid corpus entry: {}",
+                s
+            ))),
+        }
+    }
+}
+
+/// FNV-1a hash of the coefficients rounded to HASH_RESOLUTION
+fn hash_coefficients(h: &[f32]) -> u64 {
+    h.iter()
+        .map(|&c| (c as f64 * HASH_RESOLUTION).round() as i64)
+        .flat_map(|q| q.to_le_bytes().to_vec())
+        .fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{hash_coefficients, Corpus, CorpusEntry};
+
+    #[test]
+    fn test_corpus_entry_round_trip() {
+        let entry = CorpusEntry {
+            name: "kaiser n=21 fc=0.100 as=40.0 mu=0.000".to_owned(),
+            hash: 0x0123_4567_89ab_cdef,
+        };
+        let parsed: CorpusEntry = entry.to_string().parse().unwrap();
+        assert_eq!(parsed, entry);
+        assert!("nohash".parse::<CorpusEntry>().is_err());
+    }
+
+    #[test]
+    fn test_corpus_case_names_unique() {
+        let cases = Corpus::cases();
+        let mut names: Vec<&str> = cases.iter().map(|c| c.name.as_str()).collect();
+        names.sort();
+        names.dedup();
+        assert_eq!(names.len(), cases.len());
+    }
+
+    #[test]
+    fn test_hash_ignores_rounding_noise() {
+        let h = [0.5f32, -0.25, 0.125];
+        let noisy = [0.5f32 + 1e-8, -0.25, 0.125 - 1e-8];
+        assert_eq!(hash_coefficients(&h), hash_coefficients(&noisy));
+        assert_ne!(
+            hash_coefficients(&h),
+            hash_coefficients(&[0.5, -0.25, 0.126])
+        );
+    }
+}
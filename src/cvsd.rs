@@ -28,7 +28,7 @@ impl Cvsd {
             return Err(LiquidError::InvalidValue(
                 "zeta must be greater than 1".to_owned(),
             ));
-        } else if alpha < 0f32 || alpha > 1f32 {
+        } else if !(0f32..=1f32).contains(&alpha) {
             return Err(LiquidError::InvalidValue(
                 "alpha must be in [0,1]".to_owned(),
             ));
@@ -44,12 +44,12 @@ impl Cvsd {
     }
 
     /// encode single sample
-    pub fn encode(&self, audio_sample: f32) -> u8 {
+    pub fn encode(&mut self, audio_sample: f32) -> u8 {
         unsafe { raw::cvsd_encode(self.inner, audio_sample) as u8 }
     }
 
     /// decode single sample
-    pub fn decode(&self, bit: u8) -> f32 {
+    pub fn decode(&mut self, bit: u8) -> f32 {
         unsafe { raw::cvsd_decode(self.inner, bit) }
     }
 
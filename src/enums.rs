@@ -1,5 +1,10 @@
 #![allow(non_camel_case_types, non_snake_case)]
-use std::mem::transmute;
+use std::convert::TryFrom;
+
+use crate::errors::LiquidError;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
 bitflags! {
 
@@ -16,7 +21,33 @@ bitflags! {
     }
 }
 
-#[derive(Clone, Copy, Eq, PartialEq)]
+bitflags! {
+
+    /// FFTW planner flags, with the values of fftw3.h, see FftOptions;
+    /// libliquid 1.3.2 does not forward them to FFTW, so they have no
+    /// effect. The empty set is FFTW_MEASURE.
+    pub struct FftwFlags: u32 {
+
+        const MEASURE =         0;
+        const DESTROY_INPUT =   1;
+        const UNALIGNED =       1 << 1;
+        const CONSERVE_MEMORY = 1 << 2;
+        const EXHAUSTIVE =      1 << 3;
+        const PRESERVE_INPUT =  1 << 4;
+        const PATIENT =         1 << 5;
+        const ESTIMATE =        1 << 6;
+        const WISDOM_ONLY =     1 << 21;
+    }
+}
+
+impl Default for FftwFlags {
+    fn default() -> Self {
+        FftwFlags::MEASURE
+    }
+}
+
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum FecScheme {
     UNKNOWN,
     NONE,
@@ -51,20 +82,53 @@ pub enum FecScheme {
 
 impl From<FecScheme> for u8 {
     fn from(value: FecScheme) -> u8 {
-        unsafe { transmute::<FecScheme, u8>(value) }
+        value as u8
     }
 }
 
-impl From<u8> for FecScheme {
-    fn from(value: u8) -> Self {
-        if value > 27 {
-            return FecScheme::UNKNOWN;
+impl TryFrom<u8> for FecScheme {
+    type Error = LiquidError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(FecScheme::UNKNOWN),
+            1 => Ok(FecScheme::NONE),
+            2 => Ok(FecScheme::REP3),
+            3 => Ok(FecScheme::REP5),
+            4 => Ok(FecScheme::HAMMING74),
+            5 => Ok(FecScheme::HAMMING84),
+            6 => Ok(FecScheme::HAMMING128),
+            7 => Ok(FecScheme::GOLAY2412),
+            8 => Ok(FecScheme::SECDED2216),
+            9 => Ok(FecScheme::SECDED3932),
+            10 => Ok(FecScheme::SECDED7264),
+            11 => Ok(FecScheme::CONV_V27),
+            12 => Ok(FecScheme::CONV_V29),
+            13 => Ok(FecScheme::CONV_V39),
+            14 => Ok(FecScheme::CONV_V615),
+            15 => Ok(FecScheme::CONV_V27P23),
+            16 => Ok(FecScheme::CONV_V27P34),
+            17 => Ok(FecScheme::CONV_V27P45),
+            18 => Ok(FecScheme::CONV_V27P56),
+            19 => Ok(FecScheme::CONV_V27P67),
+            20 => Ok(FecScheme::CONV_V27P78),
+            21 => Ok(FecScheme::CONV_V29P23),
+            22 => Ok(FecScheme::CONV_V29P34),
+            23 => Ok(FecScheme::CONV_V29P45),
+            24 => Ok(FecScheme::CONV_V29P56),
+            25 => Ok(FecScheme::CONV_V29P67),
+            26 => Ok(FecScheme::CONV_V29P78),
+            27 => Ok(FecScheme::RS_M8),
+            _ => Err(LiquidError::InvalidValue(format!(
+                "{} is not a valid fec scheme",
+                value
+            ))),
         }
-        unsafe { transmute::<u8, FecScheme>(value) }
     }
 }
 
 #[derive(Clone, Copy, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum CrcScheme {
     CRC_UNKNOWN,
     CRC_NONE,
@@ -77,20 +141,32 @@ pub enum CrcScheme {
 
 impl From<CrcScheme> for u8 {
     fn from(value: CrcScheme) -> u8 {
-        unsafe { transmute::<CrcScheme, u8>(value) }
+        value as u8
     }
 }
 
-impl From<u8> for CrcScheme {
-    fn from(value: u8) -> Self {
-        if value > 6 {
-            return CrcScheme::CRC_UNKNOWN;
+impl TryFrom<u8> for CrcScheme {
+    type Error = LiquidError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(CrcScheme::CRC_UNKNOWN),
+            1 => Ok(CrcScheme::CRC_NONE),
+            2 => Ok(CrcScheme::CRC_CHECKSUM),
+            3 => Ok(CrcScheme::CRC_8),
+            4 => Ok(CrcScheme::CRC_16),
+            5 => Ok(CrcScheme::CRC_24),
+            6 => Ok(CrcScheme::CRC_32),
+            _ => Err(LiquidError::InvalidValue(format!(
+                "{} is not a valid crc scheme",
+                value
+            ))),
         }
-        unsafe { transmute::<u8, CrcScheme>(value) }
     }
 }
 
 #[derive(Clone, Copy, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum FftType {
     BACKWARD = -1,
     UNKNOWN = 0,
@@ -109,19 +185,186 @@ pub enum FftType {
 
 impl From<FftType> for i8 {
     fn from(value: FftType) -> i8 {
-        unsafe { transmute::<FftType, i8>(value) }
+        value as i8
+    }
+}
+
+impl TryFrom<i8> for FftType {
+    type Error = LiquidError;
+
+    fn try_from(value: i8) -> Result<Self, Self::Error> {
+        match value {
+            -1 => Ok(FftType::BACKWARD),
+            0 => Ok(FftType::UNKNOWN),
+            1 => Ok(FftType::FORWARD),
+            10 => Ok(FftType::REDFT00),
+            11 => Ok(FftType::REDFT10),
+            12 => Ok(FftType::REDFT01),
+            13 => Ok(FftType::REDFT11),
+            20 => Ok(FftType::RODFT00),
+            21 => Ok(FftType::RODFT10),
+            22 => Ok(FftType::RODFT01),
+            23 => Ok(FftType::RODFT11),
+            30 => Ok(FftType::MDCT),
+            31 => Ok(FftType::IMDCT),
+            _ => Err(LiquidError::InvalidValue(format!(
+                "{} is not a valid fft type",
+                value
+            ))),
+        }
     }
 }
 
-impl From<i8> for FftType {
-    fn from(value: i8) -> Self {
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[repr(u8)]
+pub enum WindowType {
+    UNKNOWN,
+    HAMMING,
+    HANN,
+    BLACKMANHARRIS,
+    BLACKMANHARRIS7,
+    KAISER,
+    FLATTOP,
+    TRIANGULAR,
+    RCOSTAPER,
+    KBD,
+}
+
+impl From<WindowType> for u8 {
+    fn from(value: WindowType) -> u8 {
+        value as u8
+    }
+}
+
+impl TryFrom<u8> for WindowType {
+    type Error = LiquidError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
         match value {
-            -1 | 0 | 1 => {}
-            10..=13 => {}
-            20..=23 => {}
-            30 | 31 => {}
-            _ => return FftType::UNKNOWN,
+            0 => Ok(WindowType::UNKNOWN),
+            1 => Ok(WindowType::HAMMING),
+            2 => Ok(WindowType::HANN),
+            3 => Ok(WindowType::BLACKMANHARRIS),
+            4 => Ok(WindowType::BLACKMANHARRIS7),
+            5 => Ok(WindowType::KAISER),
+            6 => Ok(WindowType::FLATTOP),
+            7 => Ok(WindowType::TRIANGULAR),
+            8 => Ok(WindowType::RCOSTAPER),
+            9 => Ok(WindowType::KBD),
+            _ => Err(LiquidError::InvalidValue(format!(
+                "{} is not a valid window type",
+                value
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CrcScheme, FecScheme, FftType, WindowType};
+    use std::convert::TryFrom;
+
+    #[test]
+    fn test_fec_scheme_round_trip() {
+        let schemes = [
+            FecScheme::UNKNOWN,
+            FecScheme::NONE,
+            FecScheme::REP3,
+            FecScheme::REP5,
+            FecScheme::HAMMING74,
+            FecScheme::HAMMING84,
+            FecScheme::HAMMING128,
+            FecScheme::GOLAY2412,
+            FecScheme::SECDED2216,
+            FecScheme::SECDED3932,
+            FecScheme::SECDED7264,
+            FecScheme::CONV_V27,
+            FecScheme::CONV_V29,
+            FecScheme::CONV_V39,
+            FecScheme::CONV_V615,
+            FecScheme::CONV_V27P23,
+            FecScheme::CONV_V27P34,
+            FecScheme::CONV_V27P45,
+            FecScheme::CONV_V27P56,
+            FecScheme::CONV_V27P67,
+            FecScheme::CONV_V27P78,
+            FecScheme::CONV_V29P23,
+            FecScheme::CONV_V29P34,
+            FecScheme::CONV_V29P45,
+            FecScheme::CONV_V29P56,
+            FecScheme::CONV_V29P67,
+            FecScheme::CONV_V29P78,
+            FecScheme::RS_M8,
+        ];
+        for (i, &scheme) in schemes.iter().enumerate() {
+            assert_eq!(u8::from(scheme), i as u8);
+            assert_eq!(FecScheme::try_from(u8::from(scheme)).unwrap(), scheme);
+        }
+        assert!(FecScheme::try_from(schemes.len() as u8).is_err());
+    }
+
+    #[test]
+    fn test_crc_scheme_round_trip() {
+        let schemes = [
+            CrcScheme::CRC_UNKNOWN,
+            CrcScheme::CRC_NONE,
+            CrcScheme::CRC_CHECKSUM,
+            CrcScheme::CRC_8,
+            CrcScheme::CRC_16,
+            CrcScheme::CRC_24,
+            CrcScheme::CRC_32,
+        ];
+        for (i, &scheme) in schemes.iter().enumerate() {
+            assert_eq!(u8::from(scheme), i as u8);
+            assert_eq!(CrcScheme::try_from(u8::from(scheme)).unwrap(), scheme);
+        }
+        assert!(CrcScheme::try_from(7).is_err());
+    }
+
+    #[test]
+    fn test_fft_type_round_trip() {
+        let types = [
+            FftType::BACKWARD,
+            FftType::UNKNOWN,
+            FftType::FORWARD,
+            FftType::REDFT00,
+            FftType::REDFT10,
+            FftType::REDFT01,
+            FftType::REDFT11,
+            FftType::RODFT00,
+            FftType::RODFT10,
+            FftType::RODFT01,
+            FftType::RODFT11,
+            FftType::MDCT,
+            FftType::IMDCT,
+        ];
+        for &type_ in types.iter() {
+            assert_eq!(FftType::try_from(i8::from(type_)).unwrap(), type_);
+        }
+        assert_eq!(i8::from(FftType::BACKWARD), -1);
+        assert!(FftType::try_from(2).is_err());
+        assert!(FftType::try_from(-2).is_err());
+    }
+
+    #[test]
+    fn test_window_type_round_trip() {
+        let types = [
+            WindowType::UNKNOWN,
+            WindowType::HAMMING,
+            WindowType::HANN,
+            WindowType::BLACKMANHARRIS,
+            WindowType::BLACKMANHARRIS7,
+            WindowType::KAISER,
+            WindowType::FLATTOP,
+            WindowType::TRIANGULAR,
+            WindowType::RCOSTAPER,
+            WindowType::KBD,
+        ];
+        for (i, &type_) in types.iter().enumerate() {
+            assert_eq!(u8::from(type_), i as u8);
+            assert_eq!(WindowType::try_from(u8::from(type_)).unwrap(), type_);
         }
-        unsafe { transmute::<i8, Self>(value) }
+        assert!(WindowType::try_from(10).is_err());
     }
 }
@@ -1,16 +1,23 @@
-//! Least mean-squares (LMS) equalizer 
+//! Least mean-squares (LMS) equalizer
 
 use num::complex::Complex32;
-use crate::liquid_dsp_sys as raw;
 
-use crate::utils::{ToCPointer, ToCPointerMut, ToCValue};
+use crate::liquid_dsp_sys as raw;
+use crate::modem::Slicer;
+use crate::utils::{check_same_len_as, ToCPointer, ToCPointerMut, ToCValue};
 
-use crate::errors::LiquidError;
+use crate::errors::FilterError;
 use crate::LiquidResult;
-use std::slice;
 
-pub struct EqlmsCccf(raw::eqlms_cccf);
-pub struct EqlmsRrrf(raw::eqlms_rrrf);
+pub struct EqlmsCccf {
+    inner: raw::eqlms_cccf,
+    len: usize,
+}
+
+pub struct EqlmsRrrf {
+    inner: raw::eqlms_rrrf,
+    len: usize,
+}
 
 macro_rules! eqlms_impl {
     ($obj:ty, ($create:expr,
@@ -32,109 +39,146 @@ macro_rules! eqlms_impl {
         $type:ty)) => {
         impl $obj {
             /// create least mean-squares (LMS) equalizer object
-            ///  h      :   initial coefficients [size: _h_len x 1], default if NULL
-            pub fn create(
-                h: &[$type],
-            ) -> LiquidResult<$obj> {
+            ///  h      :   initial coefficients [size: _h_len x 1]
+            pub fn create(h: &[$type]) -> LiquidResult<Self> {
                 if h.is_empty() {
-                    return Err(LiquidError::InvalidValue(format!(
-                        "initials coefficients must not be empty",
-                    )));
-                } 
-                Ok(unsafe { Self($create(h.to_pointer() as _)) })
+                    return Err(FilterError::InvalidLength {
+                        description: "initial coefficients must not be empty".to_owned(),
+                    }
+                    .into());
+                }
+                Ok(Self {
+                    inner: unsafe { $create(h.to_ptr() as _, h.len() as _) },
+                    len: h.len(),
+                })
             }
 
             /// create LMS EQ initialized with low-pass filter
-            ///  _n    : filter length
-            ///   fc   : filter cut-off, _fc in (0,0.5]
-            pub fn create_lowpass(n: u32, fc: f32) -> LiquidResult<$obj> {
+            ///  n      :   filter length, n > 0
+            ///  fc     :   filter cut-off, fc in (0, 0.5]
+            pub fn create_lowpass(n: usize, fc: f32) -> LiquidResult<Self> {
                 if n == 0 {
-                    return Err(LiquidError::InvalidLength {
+                    return Err(FilterError::InvalidLength {
                         description: "filter length must be greater than zero".to_owned(),
-                    });
+                    }
+                    .into());
                 } else if fc <= 0f32 || fc > 0.5 {
-                    return Err(LiquidError::InvalidValue(
+                    return Err(FilterError::InvalidValue(
                         "filter cutoff must be in (0,0.5]".to_owned(),
-                    ));
-                }
-                unsafe {
-                    Ok(Self {
-                        $lowpass(n as _, fc)
-                    })
+                    )
+                    .into());
                 }
+                Ok(Self {
+                    inner: unsafe { $lowpass(n as _, fc) },
+                    len: n,
+                })
             }
-            
-            pub fn recreate(self, h: &[$type]) -> $obj {
-                Ok(unsafe { self.0 = $recreate(h.to_pointer() as _) })
+
+            /// re-create the equalizer with new initial coefficients
+            ///  h      :   initial coefficients [size: _h_len x 1]
+            pub fn recreate(mut self, h: &[$type]) -> LiquidResult<Self> {
+                if h.is_empty() {
+                    return Err(FilterError::InvalidLength {
+                        description: "initial coefficients must not be empty".to_owned(),
+                    }
+                    .into());
+                }
+                self.inner = unsafe { $recreate(self.inner, h.to_ptr() as _, h.len() as _) };
+                self.len = h.len();
+                Ok(self)
             }
 
-            pub fn reset(&self) {
-                unsafe { $reset(self.0) }
+            /// clear the internal buffer and restore the initial coefficients
+            pub fn reset(&mut self) {
+                unsafe { $reset(self.inner) }
             }
 
             pub fn print(&self) {
-                unsafe { $print(self.0) }
+                unsafe { $print(self.inner) }
             }
 
-            pub get_bw(&self) -> f32 {
-                unsafe {
-                    $getbw(self.0)
-                }
+            /// number of equalizer coefficients
+            pub fn len(&self) -> usize {
+                self.len
+            }
+
+            pub fn is_empty(&self) -> bool {
+                self.len == 0
+            }
+
+            /// learning rate of the equalizer
+            pub fn get_bw(&self) -> f32 {
+                unsafe { $getbw(self.inner) }
             }
 
             /// set learning rate of equalizer
-            ///  lambda     :   LMS learning rate (should be near 0), 0 < _mu < 1
+            ///  lambda :   LMS learning rate (should be near 0), lambda >= 0
             pub fn set_bw(&mut self, lambda: f32) -> LiquidResult<()> {
-                if lambda < 0 {
-                    return Err(LiquidError::InvalidValue (
+                if lambda < 0f32 {
+                    return Err(FilterError::InvalidValue(
                         "learning rate cannot be less than zero".to_owned(),
-                    ));
+                    )
+                    .into());
                 }
-                unsafe{
-                    Ok($setbw(self.0, lambda))
+                unsafe {
+                    $setbw(self.inner, lambda);
                 }
+                Ok(())
             }
 
             /// push sample into equalizer internal buffer
             pub fn push(&mut self, x: $type) {
                 unsafe {
-                    $push(self.0, x.to_c_value());
+                    $push(self.inner, x.to_c_value());
                 }
             }
 
-            /// push sample into equalizer internal buffer as block
+            /// push a block of samples into the equalizer internal buffer
             ///  x      :   input sample array
             pub fn push_block(&mut self, x: &[$type]) {
                 unsafe {
-                    $block(self.0, x.to_ptr() as _);
+                    $block(self.inner, x.to_ptr() as _, x.len() as _);
                 }
             }
 
             /// execute internal dot product
-            pub fn execute(&self) -> $type {
+            pub fn execute(&mut self) -> $type {
+                let mut out = <$type>::default();
                 unsafe {
-                    let mut out = <$type>::default();
-                    $execute(self.0, out.to_ptr_mut());
-                    out
+                    $execute(self.inner, out.to_ptr_mut());
                 }
+                out
             }
 
             /// execute equalizer with block of samples using constant
             /// modulus algorithm, operating on a decimation rate of _k
             /// samples.
-            ///  k      :   down-sampling rate
+            ///  k      :   down-sampling rate, k > 0
             ///  x      :   input sample array [size: _n x 1]
             ///  y      :   output sample array [size: _n x 1]
-            pub fn execute_block(&self, k: i32, x: &[$type], y: &mut[$type]) -> LiquidResult<()> {
+            pub fn execute_block(
+                &mut self,
+                k: usize,
+                x: &[$type],
+                y: &mut [$type],
+            ) -> LiquidResult<()> {
+                check_same_len_as::<FilterError>(x.len(), y.len())?;
+                if k == 0 {
+                    return Err(FilterError::InvalidValue(
+                        "down-sampling rate 'k' must be greater than 0".to_owned(),
+                    )
+                    .into());
+                }
                 unsafe {
-                    assert!(x.len() == y.len());
-                    if k == 0 {
-                        return Err(LiquidError::InvalidValue (
-                            "down-sampling rate 'k' must be greater than 0".to_owned(),
-                        )); 
-                    }
-                    Ok($exeblock(self.0, k as _, x.to_ptr() as _, x.len() as _, y.to_ptr_mut()))
+                    $exeblock(
+                        self.inner,
+                        k as _,
+                        x.to_ptr() as _,
+                        x.len() as _,
+                        y.to_ptr_mut(),
+                    );
                 }
+                Ok(())
             }
 
             /// step through one cycle of equalizer training
@@ -142,30 +186,53 @@ macro_rules! eqlms_impl {
             ///  d_hat  :   filtered output
             pub fn step(&mut self, d: $type, d_hat: $type) {
                 unsafe {
-                    $step(self.0, d.to_c_value(), d_hat.to_c_value());
+                    $step(self.inner, d.to_c_value(), d_hat.to_c_value());
                 }
             }
 
-            /// step through one cycle of equalizer training
+            /// step through one cycle of equalizer training, the desired
+            /// output being estimated from the constant modulus of d_hat
             ///  d_hat  :   filtered output
-            pub fn step_blind(&mut self, d_hat) {
+            pub fn step_blind(&mut self, d_hat: $type) {
                 unsafe {
-                    $stepblind(self.0, d_hat.to_c_value());
+                    $stepblind(self.inner, d_hat.to_c_value());
                 }
-
             }
 
-            pub fn get_weights(&self) -> &[$type] {
-                
+            /// current equalizer coefficients
+            pub fn get_weights(&self) -> Vec<$type> {
+                let mut w = vec![<$type>::default(); self.len];
+                unsafe {
+                    $getweights(self.inner, w.to_ptr_mut());
+                }
+                w
             }
 
-
+            /// train the equalizer on a known sequence
+            ///  w      :   initial coefficients, overwritten with the trained
+            ///             ones [size: len() x 1]
+            ///  x      :   received samples [size: _n x 1]
+            ///  d      :   desired output [size: _n x 1]
+            pub fn train(&mut self, w: &mut [$type], x: &[$type], d: &[$type]) -> LiquidResult<()> {
+                check_same_len_as::<FilterError>(self.len, w.len())?;
+                check_same_len_as::<FilterError>(x.len(), d.len())?;
+                unsafe {
+                    $train(
+                        self.inner,
+                        w.to_ptr_mut(),
+                        x.to_ptr() as _,
+                        d.to_ptr() as _,
+                        x.len() as _,
+                    );
+                }
+                Ok(())
+            }
         }
 
         impl Drop for $obj {
             fn drop(&mut self) {
                 unsafe {
-                    $destroy(self.0);
+                    $destroy(self.inner);
                 }
             }
         }
@@ -175,11 +242,22 @@ macro_rules! eqlms_impl {
 eqlms_impl!(
     EqlmsRrrf,
     (
-        raw::cpfskdem_create,
-/*         raw::cpfskdem_reset,
-        raw::cpfskdem_print,
-        raw::cpfskdem_get_delay,
-        raw::cpfskdem_destroy, */
+        raw::eqlms_rrrf_create,
+        raw::eqlms_rrrf_create_lowpass,
+        raw::eqlms_rrrf_recreate,
+        raw::eqlms_rrrf_reset,
+        raw::eqlms_rrrf_print,
+        raw::eqlms_rrrf_get_bw,
+        raw::eqlms_rrrf_set_bw,
+        raw::eqlms_rrrf_push,
+        raw::eqlms_rrrf_push_block,
+        raw::eqlms_rrrf_execute,
+        raw::eqlms_rrrf_execute_block,
+        raw::eqlms_rrrf_step,
+        raw::eqlms_rrrf_step_blind,
+        raw::eqlms_rrrf_get_weights,
+        raw::eqlms_rrrf_train,
+        raw::eqlms_rrrf_destroy,
         f32
     )
 );
@@ -188,11 +266,121 @@ eqlms_impl!(
     EqlmsCccf,
     (
         raw::eqlms_cccf_create,
-        raw::eqlms_cccf_lowpass,
+        raw::eqlms_cccf_create_lowpass,
         raw::eqlms_cccf_recreate,
         raw::eqlms_cccf_reset,
         raw::eqlms_cccf_print,
+        raw::eqlms_cccf_get_bw,
+        raw::eqlms_cccf_set_bw,
+        raw::eqlms_cccf_push,
+        raw::eqlms_cccf_push_block,
+        raw::eqlms_cccf_execute,
+        raw::eqlms_cccf_execute_block,
+        raw::eqlms_cccf_step,
+        raw::eqlms_cccf_step_blind,
+        raw::eqlms_cccf_get_weights,
+        raw::eqlms_cccf_train,
         raw::eqlms_cccf_destroy,
         Complex32
     )
-);
\ No newline at end of file
+);
+
+impl EqlmsCccf {
+    /// decision-directed step: the desired output is the constellation
+    /// point the slicer decides for d_hat
+    ///  slicer :   slicer of the modulation scheme of the signal
+    ///  d_hat  :   filtered output
+    /// # returns
+    /// the decision used as the desired output
+    pub fn step_decision(&mut self, slicer: &mut Slicer, d_hat: Complex32) -> Complex32 {
+        let d = slicer.decide(d_hat);
+        self.step(d, d_hat);
+        d
+    }
+
+    /// execute the equalizer on a block of samples, updating it in
+    /// decision-directed mode once every k samples
+    ///  k      :   samples/symbol, k > 0
+    ///  slicer :   slicer of the modulation scheme of the signal
+    ///  x      :   input sample array [size: _n x 1]
+    ///  y      :   output sample array [size: _n x 1]
+    pub fn execute_block_decision(
+        &mut self,
+        k: usize,
+        slicer: &mut Slicer,
+        x: &[Complex32],
+        y: &mut [Complex32],
+    ) -> LiquidResult<()> {
+        check_same_len_as::<FilterError>(x.len(), y.len())?;
+        if k == 0 {
+            return Err(FilterError::InvalidValue(
+                "down-sampling rate 'k' must be greater than 0".to_owned(),
+            )
+            .into());
+        }
+        for (i, (&v, out)) in x.iter().zip(y.iter_mut()).enumerate() {
+            self.push(v);
+            *out = self.execute();
+            if (i + 1) % k == 0 {
+                self.step_decision(slicer, *out);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EqlmsCccf;
+    use crate::modem::{Modem, ModulationScheme, Slicer};
+    use num::complex::Complex32;
+
+    #[test]
+    fn test_eqlms_cccf_decision_directed() {
+        assert!(EqlmsCccf::create(&[]).is_err());
+        assert!(EqlmsCccf::create_lowpass(0, 0.2).is_err());
+
+        // QPSK through a two-tap channel, one sample per symbol
+        let mut modem = Modem::create(ModulationScheme::QPSK).unwrap();
+        let mut state = 1u32;
+        let tx: Vec<Complex32> = (0..3000)
+            .map(|_| {
+                state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+                modem.modulate((state >> 16) & 3).unwrap()
+            })
+            .collect();
+        let h = [Complex32::new(1.0, 0.0), Complex32::new(0.3, 0.2)];
+        let rx: Vec<Complex32> = (0..tx.len())
+            .map(|n| {
+                tx[n] * h[0]
+                    + if n > 0 {
+                        tx[n - 1] * h[1]
+                    } else {
+                        Complex32::default()
+                    }
+            })
+            .collect();
+
+        // a centred unit tap delays the output by 3 symbols
+        let mut w = vec![Complex32::default(); 7];
+        w[3] = Complex32::new(1.0, 0.0);
+        let mut eq = EqlmsCccf::create(&w).unwrap();
+        eq.set_bw(0.05).unwrap();
+        assert!(eq.set_bw(-1.0).is_err());
+        let mut slicer = Slicer::create(ModulationScheme::QPSK).unwrap();
+        let mut y = vec![Complex32::default(); rx.len()];
+        assert!(eq
+            .execute_block_decision(0, &mut slicer, &rx, &mut y)
+            .is_err());
+        eq.execute_block_decision(1, &mut slicer, &rx, &mut y)
+            .unwrap();
+
+        let mse = y[2500..]
+            .iter()
+            .zip(tx[2497..].iter())
+            .map(|(a, b)| (a - b).norm_sqr())
+            .sum::<f32>()
+            / 500.0;
+        assert!(mse < 1e-2);
+    }
+}
@@ -0,0 +1,111 @@
+//! least-squares estimation of a FIR channel from known transmitted and
+//! received blocks
+use num::complex::Complex32;
+
+use crate::errors::FilterError;
+use crate::matrix::cgsolve_hermitian;
+use crate::LiquidResult;
+
+/// estimate the taps h of the channel rx[n] = sum_k h[k] * tx[n-k]
+///
+/// Solves the normal equations of the least-squares fit over the samples
+/// where the whole channel overlaps the transmitted block, so tx must be
+/// rich enough (e.g. a random training sequence) for them to be well
+/// conditioned.
+///  tx     :   transmitted samples
+///  rx     :   received samples, aligned with tx [size: tx.len() x 1]
+///  order  :   number of channel taps, 0 < order, 2*order-1 <= tx.len()
+pub fn channel_estimate(
+    tx: &[Complex32],
+    rx: &[Complex32],
+    order: usize,
+) -> LiquidResult<Vec<Complex32>> {
+    if tx.len() != rx.len() {
+        return Err(FilterError::InvalidLength {
+            description: format!("tx length: {} rx length: {}", tx.len(), rx.len()),
+        }
+        .into());
+    } else if order == 0 {
+        return Err(
+            FilterError::InvalidValue("channel order must be greater than 0".to_owned()).into(),
+        );
+    } else if tx.len() + 1 < 2 * order {
+        return Err(FilterError::InvalidLength {
+            description: format!(
+                "{} samples cannot estimate {} taps, at least {} are needed",
+                tx.len(),
+                order,
+                2 * order - 1
+            ),
+        }
+        .into());
+    }
+    // R[i][j] = sum_n conj(tx[n-i]) tx[n-j],  p[i] = sum_n conj(tx[n-i]) rx[n]
+    let mut r = vec![Complex32::default(); order * order];
+    let mut p = vec![Complex32::default(); order];
+    for n in order - 1..tx.len() {
+        for i in 0..order {
+            let xi = tx[n - i].conj();
+            p[i] += xi * rx[n];
+            for j in 0..order {
+                r[i * order + j] += xi * tx[n - j];
+            }
+        }
+    }
+    if r.iter().step_by(order + 1).all(|v| v.re == 0.0) {
+        return Err(
+            FilterError::InvalidValue("transmitted samples have no energy".to_owned()).into(),
+        );
+    }
+    cgsolve_hermitian(&r, &p)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::channel_estimate;
+    use num::complex::Complex32;
+
+    #[test]
+    fn test_channel_estimate_validation() {
+        let x = vec![Complex32::new(1.0, 0.0); 8];
+        assert!(channel_estimate(&x, &x[..7], 2).is_err());
+        assert!(channel_estimate(&x, &x, 0).is_err());
+        assert!(channel_estimate(&x, &x, 5).is_err());
+        let zeros = vec![Complex32::default(); 8];
+        assert!(channel_estimate(&zeros, &x, 2).is_err());
+    }
+
+    #[test]
+    fn test_channel_estimate_known_channel() {
+        // pseudo-random QPSK training sequence
+        let mut state = 1u32;
+        let tx: Vec<Complex32> = (0..64)
+            .map(|_| {
+                state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+                let b = state >> 16;
+                Complex32::new(
+                    if b & 1 == 0 { 1.0 } else { -1.0 },
+                    if b & 2 == 0 { 1.0 } else { -1.0 },
+                )
+            })
+            .collect();
+        let h = [
+            Complex32::new(1.0, 0.0),
+            Complex32::new(0.4, -0.2),
+            Complex32::new(0.1, 0.3),
+        ];
+        let rx: Vec<Complex32> = (0..tx.len())
+            .map(|n| {
+                (0..h.len())
+                    .filter(|&k| k <= n)
+                    .map(|k| h[k] * tx[n - k])
+                    .sum()
+            })
+            .collect();
+
+        let h_hat = channel_estimate(&tx, &rx, 3).unwrap();
+        for (a, b) in h_hat.iter().zip(h.iter()) {
+            assert!((a - b).norm() < 1e-3, "{:?}", h_hat);
+        }
+    }
+}
@@ -1,3 +1,5 @@
 pub use eqlms::{EqlmsCccf, EqlmsRrrf};
+pub use estimate::channel_estimate;
 
-mod eqlms;
\ No newline at end of file
+mod eqlms;
+mod estimate;
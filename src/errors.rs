@@ -1,32 +1,171 @@
 use std::error;
 use std::fmt;
 
+/// Errors reported by the filter design and filtering objects
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum FilterError {
+    InvalidLength { description: String },
+    InvalidValue(String), // when a value does not fullfill certain restrictions
+}
+
+/// Errors reported by the crc, fec and packetizer objects
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum FecError {
+    InvalidCrcScheme,
+    InvalidFecScheme,
+    UnsupportedFecScheme(String),
+    InvalidLength { description: String },
+    InvalidValue(String),
+}
+
+/// Errors reported by the fft plans and spectral objects
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum FftError {
+    InvalidType,
+    InvalidSize(String),
+    /// the FFTW function is not available, libliquid was built without it
+    FftwUnavailable(String),
+    InvalidValue(String),
+}
+
+/// Errors reported by the modulators and demodulators
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ModemError {
+    InvalidScheme,
+    InvalidLength { description: String },
+    InvalidValue(String),
+}
+
+#[non_exhaustive]
 pub enum LiquidError {
     /*     FftSize,
     WindowSize, // when window size is higher than FftSize
     NullWindowSize, // case window size is == 0
     NonPositiveValue, // case when a value is negative */
     EmptyBuffer,
+    BufferFull,
     InvalidLength { description: String },
-    InvalidCrcScheme,
-    InvalidFecScheme,
     InvalidValue(String), // when a value does not fullfill certain restrictions
+    Disconnected,         // when the other end of a queue has been dropped
+    Filter(FilterError),
+    Fec(FecError),
+    Fft(FftError),
+    Modem(ModemError),
     Unknown,
 }
 
-impl LiquidError {
+impl FilterError {
+    pub(crate) fn as_str(&self) -> &str {
+        match self {
+            Self::InvalidLength { ref description } => description,
+            Self::InvalidValue(ref detail) => detail,
+        }
+    }
+}
+
+impl FecError {
     pub(crate) fn as_str(&self) -> &str {
         match self {
             Self::InvalidFecScheme => "cannot validate with FecScheme of type UNKNOWN",
+            Self::InvalidCrcScheme => "cannot validate with CRC type UNKNOWN",
+            Self::UnsupportedFecScheme(ref detail) => detail,
+            Self::InvalidLength { ref description } => description,
+            Self::InvalidValue(ref detail) => detail,
+        }
+    }
+}
+
+impl FftError {
+    pub(crate) fn as_str(&self) -> &str {
+        match self {
+            Self::InvalidType => "invalid FftType for this operation",
+            Self::InvalidSize(ref detail) => detail,
+            Self::FftwUnavailable(ref detail) => detail,
+            Self::InvalidValue(ref detail) => detail,
+        }
+    }
+}
+
+impl ModemError {
+    pub(crate) fn as_str(&self) -> &str {
+        match self {
+            Self::InvalidScheme => "cannot create modem with ModulationScheme of type UNKNOWN",
+            Self::InvalidLength { ref description } => description,
+            Self::InvalidValue(ref detail) => detail,
+        }
+    }
+}
+
+impl LiquidError {
+    pub(crate) fn as_str(&self) -> &str {
+        match self {
             Self::EmptyBuffer => "Buffer is already empty",
+            Self::BufferFull => "Buffer is full",
             Self::InvalidLength { ref description } => description,
-            Self::InvalidCrcScheme => "cannot validate with CRC type UNKNOWN",
             Self::InvalidValue(ref detail) => detail,
+            Self::Disconnected => "the other end of the queue has been dropped",
+            Self::Filter(ref err) => err.as_str(),
+            Self::Fec(ref err) => err.as_str(),
+            Self::Fft(ref err) => err.as_str(),
+            Self::Modem(ref err) => err.as_str(),
             Self::Unknown => "liquid unknown error",
         }
     }
 }
 
+macro_rules! module_error_impl {
+    ($err:ident, $variant:ident) => {
+        impl fmt::Display for $err {
+            fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(fmt, "{}", self.as_str())
+            }
+        }
+
+        impl error::Error for $err {}
+
+        impl From<$err> for LiquidError {
+            fn from(err: $err) -> Self {
+                LiquidError::$variant(err)
+            }
+        }
+    };
+}
+
+module_error_impl!(FilterError, Filter);
+module_error_impl!(FecError, Fec);
+module_error_impl!(FftError, Fft);
+module_error_impl!(ModemError, Modem);
+
+/// errors able to report input and output buffers of different lengths,
+/// see utils::check_same_len_as()
+pub(crate) trait LengthMismatch {
+    fn length_mismatch(description: String) -> Self;
+}
+
+macro_rules! length_mismatch_impl {
+    ($($err:ident, |$d:ident| $body:expr;)*) => {
+        $(
+            impl LengthMismatch for $err {
+                fn length_mismatch($d: String) -> Self {
+                    $body
+                }
+            }
+        )*
+    };
+}
+
+length_mismatch_impl!(
+    LiquidError, |description| LiquidError::InvalidLength { description };
+    FilterError, |description| FilterError::InvalidLength { description };
+    FecError, |description| FecError::InvalidLength { description };
+    FftError, |description| FftError::InvalidSize(description);
+    ModemError, |description| ModemError::InvalidLength { description };
+);
+
 impl fmt::Display for LiquidError {
     fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(fmt, "{}", self.as_str())
@@ -49,4 +188,14 @@ impl error::Error for LiquidError {
     fn description(&self) -> &str {
         self.as_str()
     }
+
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::Filter(ref err) => Some(err),
+            Self::Fec(ref err) => Some(err),
+            Self::Fft(ref err) => Some(err),
+            Self::Modem(ref err) => Some(err),
+            _ => None,
+        }
+    }
 }
@@ -0,0 +1,198 @@
+//! *fading* : time-varying flat fading channel
+//!
+//! The complex gain is the sum of a diffuse and a line-of-sight component:
+//!   g[n] = sqrt(1/(K+1)) * d[n] + sqrt(K/(K+1)) * exp(j*2*pi*fd*cos(theta)*n)
+//! where d[n] is complex Gauss noise shaped by the Doppler filter of
+//! Firdes::doppler, normalized to unit power, so that E{|g|^2} = 1. K = 0
+//! gives Rayleigh fading, K > 0 Rician fading. Every input sample is
+//! multiplied by the gain, complementing the static multi-path of
+//! ChannelCccf.
+use num::complex::Complex32;
+use std::f32::consts::PI;
+
+use crate::errors::FilterError;
+use crate::filter::{FirFiltCrcf, Firdes};
+use crate::tvmpch::GaussRng;
+use crate::utils::check_same_len;
+use crate::LiquidResult;
+
+pub struct Fading {
+    filter: FirFiltCrcf,
+    rng: GaussRng,
+    fd: f32,
+    k: f32,
+    theta: f32,
+    // amplitudes of the diffuse and line-of-sight components
+    diffuse: f32,
+    los: f32,
+    // phase of the line-of-sight component [radians]
+    phase: f32,
+    gain: Complex32,
+}
+
+impl Fading {
+    /// create a fading generator, seeded from the C library rand()
+    ///  n      :   number of Doppler filter taps, n > 0
+    ///  fd     :   normalized maximum Doppler frequency, in (0, 0.5]
+    ///  k      :   Rice K-factor, power ratio of the line-of-sight and
+    ///             diffuse components (k >= 0, 0 for Rayleigh fading)
+    ///  theta  :   line-of-sight angle of arrival [radians]
+    pub fn create(n: usize, fd: f32, k: f32, theta: f32) -> LiquidResult<Self> {
+        let seed = unsafe { ((libc::rand() as u64) << 32) | libc::rand() as u64 };
+        Self::create_seeded(n, fd, k, theta, seed)
+    }
+
+    /// create a fading generator with a reproducible gain process
+    ///  n      :   number of Doppler filter taps, n > 0
+    ///  fd     :   normalized maximum Doppler frequency, in (0, 0.5]
+    ///  k      :   Rice K-factor (k >= 0)
+    ///  theta  :   line-of-sight angle of arrival [radians]
+    ///  seed   :   seed of the Gauss source
+    pub fn create_seeded(n: usize, fd: f32, k: f32, theta: f32, seed: u64) -> LiquidResult<Self> {
+        if n == 0 {
+            return Err(FilterError::InvalidValue(
+                "filter length must be greater than zero".to_owned(),
+            )
+            .into());
+        } else if k < 0f32 || !k.is_finite() {
+            return Err(FilterError::InvalidValue(
+                "K-factor must be greater than or equal to zero".to_owned(),
+            )
+            .into());
+        }
+        // the line-of-sight component is added separately, the filter only
+        // shapes the diffuse spectrum
+        let mut h = Firdes::doppler(n, fd, 0.0, 0.0)?;
+        let energy: f32 = h.as_ref().iter().map(|v| v * v).sum();
+        if energy <= 0f32 || !energy.is_finite() {
+            return Err(FilterError::InvalidValue(format!(
+                "doppler filter of {} taps has no energy, fd: {}",
+                n, fd
+            ))
+            .into());
+        }
+        h.as_mut().iter_mut().for_each(|v| *v /= energy.sqrt());
+        let mut q = Self {
+            filter: FirFiltCrcf::create(h.as_ref())?,
+            rng: GaussRng::new(seed),
+            fd,
+            k,
+            theta,
+            diffuse: (1.0 / (k + 1.0)).sqrt(),
+            los: (k / (k + 1.0)).sqrt(),
+            phase: 0.0,
+            gain: Complex32::default(),
+        };
+        q.reset();
+        Ok(q)
+    }
+
+    /// restart the line-of-sight phase and refill the Doppler filter, so
+    /// that the diffuse component starts in steady state; the Gauss source
+    /// is not re-seeded
+    pub fn reset(&mut self) {
+        self.filter.reset();
+        for _ in 0..self.filter.len() {
+            self.filter.push(self.rng.crandn());
+        }
+        self.phase = 0.0;
+        self.gain = Complex32::default();
+    }
+
+    /// number of Doppler filter taps
+    pub fn get_length(&self) -> usize {
+        self.filter.len()
+    }
+
+    /// normalized maximum Doppler frequency
+    pub fn get_doppler(&self) -> f32 {
+        self.fd
+    }
+
+    /// Rice K-factor
+    pub fn get_k_factor(&self) -> f32 {
+        self.k
+    }
+
+    /// line-of-sight angle of arrival [radians]
+    pub fn get_theta(&self) -> f32 {
+        self.theta
+    }
+
+    /// gain applied to the last sample
+    pub fn get_gain(&self) -> Complex32 {
+        self.gain
+    }
+
+    /// advance the channel by one sample
+    /// # returns
+    /// the gain of the new sample
+    pub fn step(&mut self) -> Complex32 {
+        self.filter.push(self.rng.crandn());
+        self.gain =
+            self.filter.execute() * self.diffuse + Complex32::from_polar(self.los, self.phase);
+        self.phase += 2.0 * PI * self.fd * self.theta.cos();
+        if self.phase > PI {
+            self.phase -= 2.0 * PI;
+        } else if self.phase < -PI {
+            self.phase += 2.0 * PI;
+        }
+        self.gain
+    }
+
+    /// generate the gains of the next g.len() samples, without a stream
+    pub fn generate(&mut self, g: &mut [Complex32]) {
+        g.iter_mut().for_each(|v| *v = self.step());
+    }
+
+    /// apply the fading to a block of samples
+    ///  x      :   input samples [size: n x 1]
+    ///  y      :   output samples [size: n x 1]
+    pub fn execute_block(&mut self, x: &[Complex32], y: &mut [Complex32]) -> LiquidResult<()> {
+        check_same_len(x.len(), y.len())?;
+        for (a, b) in x.iter().zip(y.iter_mut()) {
+            *b = a * self.step();
+        }
+        Ok(())
+    }
+
+    /// apply the fading to a block of samples in place
+    pub fn execute_block_in_place(&mut self, x: &mut [Complex32]) {
+        x.iter_mut().for_each(|v| *v *= self.step());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Fading;
+    use num::complex::Complex32;
+    use std::f32::consts::FRAC_PI_2;
+
+    #[test]
+    fn test_fading_statistics() {
+        assert!(Fading::create_seeded(0, 0.05, 0.0, 0.0, 1).is_err());
+        assert!(Fading::create_seeded(201, 0.7, 0.0, 0.0, 1).is_err());
+        assert!(Fading::create_seeded(201, 0.05, -1.0, 0.0, 1).is_err());
+
+        // Rayleigh: unit mean power
+        let mut q = Fading::create_seeded(201, 0.05, 0.0, 0.0, 3).unwrap();
+        let mut g = vec![Complex32::default(); 20000];
+        q.generate(&mut g);
+        let power = g.iter().map(|v| v.norm_sqr()).sum::<f32>() / g.len() as f32;
+        assert!((power - 1.0).abs() < 0.15);
+
+        // same seed, same realization
+        let mut q1 = Fading::create_seeded(201, 0.05, 0.0, 0.0, 3).unwrap();
+        let x = vec![Complex32::new(1.0, 0.0); 100];
+        let mut y = vec![Complex32::default(); 100];
+        q1.execute_block(&x, &mut y).unwrap();
+        assert_eq!(y, g[..100].to_vec());
+
+        // Rician with a line of sight perpendicular to the motion: the mean
+        // gain is the steady line-of-sight amplitude
+        let mut q = Fading::create_seeded(201, 0.05, 10.0, FRAC_PI_2, 3).unwrap();
+        q.generate(&mut g);
+        let mean = g.iter().sum::<Complex32>() / g.len() as f32;
+        assert!((mean - Complex32::new((10.0f32 / 11.0).sqrt(), 0.0)).norm() < 0.05);
+    }
+}
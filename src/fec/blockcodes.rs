@@ -0,0 +1,363 @@
+//! *blockcodes* : standalone encoders/decoders of the block codes used by
+//!              the fec schemes, operating on a single symbol instead of a
+//!              byte stream
+//!
+//! Every encoder takes the message bits right-aligned in an integer and
+//! returns the codeword, every decoder the opposite, correcting up to the
+//! number of errors the code is able to. Uncorrectable patterns the code is
+//! able to detect are reported as an error.
+//!
+//!   code            message   codeword  corrects  detects
+//!   Hamming(7,4)    4 bits    7 bits    1         -
+//!   Hamming(8,4)    4 bits    8 bits    1         2
+//!   Hamming(12,8)   8 bits    12 bits   1         -
+//!   Golay(24,12)    12 bits   24 bits   3         4
+//!   SEC-DED(22,16)  16 bits   22 bits   1         2
+//!   SEC-DED(39,32)  32 bits   39 bits   1         2
+//!   SEC-DED(72,64)  64 bits   72 bits   1         2
+//!
+//! The symbol functions of liquid (fec_golay2412_encode_symbol and friends)
+//! are internal to the library and not exported by liquid-dsp-sys, so the
+//! codes are implemented here; their bit layout is not guaranteed to match
+//! the one used internally by the Fec objects.
+use crate::errors::{FecError, LiquidError};
+use crate::LiquidResult;
+
+/// Hamming(7,4) encoder
+///  sym    :   message [4 bits]
+pub fn hamming74_encode(sym: u32) -> LiquidResult<u32> {
+    check_sym(sym as u128, 4)?;
+    Ok(hamming_encode(sym as u128, 7) as u32)
+}
+
+/// Hamming(7,4) decoder, corrects one error
+///  sym    :   codeword [7 bits]
+pub fn hamming74_decode(sym: u32) -> LiquidResult<u32> {
+    check_sym(sym as u128, 7)?;
+    Ok(hamming_decode(sym as u128, 7)? as u32)
+}
+
+/// Hamming(8,4) encoder, Hamming(7,4) with an overall parity bit
+///  sym    :   message [4 bits]
+pub fn hamming84_encode(sym: u32) -> LiquidResult<u32> {
+    check_sym(sym as u128, 4)?;
+    Ok(secded_encode(sym as u128, 7) as u32)
+}
+
+/// Hamming(8,4) decoder, corrects one error and detects two
+///  sym    :   codeword [8 bits]
+pub fn hamming84_decode(sym: u32) -> LiquidResult<u32> {
+    check_sym(sym as u128, 8)?;
+    Ok(secded_decode(sym as u128, 7)? as u32)
+}
+
+/// Hamming(12,8) encoder, shortened Hamming(15,11)
+///  sym    :   message [8 bits]
+pub fn hamming128_encode(sym: u32) -> LiquidResult<u32> {
+    check_sym(sym as u128, 8)?;
+    Ok(hamming_encode(sym as u128, 12) as u32)
+}
+
+/// Hamming(12,8) decoder, corrects one error
+///  sym    :   codeword [12 bits]
+pub fn hamming128_decode(sym: u32) -> LiquidResult<u32> {
+    check_sym(sym as u128, 12)?;
+    Ok(hamming_decode(sym as u128, 12)? as u32)
+}
+
+/// extended Golay(24,12) encoder
+///  sym    :   message [12 bits]
+pub fn golay2412_encode(sym: u32) -> LiquidResult<u32> {
+    check_sym(sym as u128, 12)?;
+    let c = (sym << 11) | golay_rem(sym << 11);
+    Ok(c | (c.count_ones() & 1) << 23)
+}
+
+/// extended Golay(24,12) decoder, corrects up to three errors and
+/// detects four
+///  sym    :   codeword [24 bits]
+pub fn golay2412_decode(sym: u32) -> LiquidResult<u32> {
+    check_sym(sym as u128, 24)?;
+    let r = sym & 0x7f_ffff;
+    let e = GOLAY_SYNDROME_TABLE[golay_rem(r) as usize];
+    let c = r ^ e;
+    // the (23,12) code is perfect: four errors are always mistaken for
+    // three, which only the overall parity bit is able to tell
+    if (c.count_ones() + (sym >> 23)) & 1 == 1 && e.count_ones() == 3 {
+        return Err(uncorrectable());
+    }
+    Ok(c >> 11)
+}
+
+/// SEC-DED(22,16) encoder
+///  sym    :   message [16 bits]
+pub fn secded2216_encode(sym: u32) -> LiquidResult<u32> {
+    check_sym(sym as u128, 16)?;
+    Ok(secded_encode(sym as u128, 21) as u32)
+}
+
+/// SEC-DED(22,16) decoder, corrects one error and detects two
+///  sym    :   codeword [22 bits]
+pub fn secded2216_decode(sym: u32) -> LiquidResult<u32> {
+    check_sym(sym as u128, 22)?;
+    Ok(secded_decode(sym as u128, 21)? as u32)
+}
+
+/// SEC-DED(39,32) encoder
+///  sym    :   message [32 bits]
+pub fn secded3932_encode(sym: u32) -> u64 {
+    secded_encode(sym as u128, 38) as u64
+}
+
+/// SEC-DED(39,32) decoder, corrects one error and detects two
+///  sym    :   codeword [39 bits]
+pub fn secded3932_decode(sym: u64) -> LiquidResult<u32> {
+    check_sym(sym as u128, 39)?;
+    Ok(secded_decode(sym as u128, 38)? as u32)
+}
+
+/// SEC-DED(72,64) encoder
+///  sym    :   message [64 bits]
+pub fn secded7264_encode(sym: u64) -> u128 {
+    secded_encode(sym as u128, 71)
+}
+
+/// SEC-DED(72,64) decoder, corrects one error and detects two
+///  sym    :   codeword [72 bits]
+pub fn secded7264_decode(sym: u128) -> LiquidResult<u64> {
+    check_sym(sym, 72)?;
+    Ok(secded_decode(sym, 71)? as u64)
+}
+
+fn check_sym(sym: u128, bits: u32) -> LiquidResult<()> {
+    if sym >> bits != 0 {
+        return Err(
+            FecError::InvalidValue(format!("symbol: {:#x} exceeds {} bits", sym, bits)).into(),
+        );
+    }
+    Ok(())
+}
+
+fn uncorrectable() -> LiquidError {
+    FecError::InvalidValue("uncorrectable error pattern".to_owned()).into()
+}
+
+// Hamming codeword of length n, bit i-1 holding position i: parity bits
+// sit at the power-of-two positions, message bits fill the rest in order
+fn hamming_encode(msg: u128, n: u32) -> u128 {
+    let mut c = 0u128;
+    let mut k = 0;
+    for pos in 1..=n {
+        if !pos.is_power_of_two() {
+            c |= ((msg >> k) & 1) << (pos - 1);
+            k += 1;
+        }
+    }
+    let s = hamming_syndrome(c, n);
+    let mut p = 1;
+    while p <= n {
+        c |= ((s & p != 0) as u128) << (p - 1);
+        p <<= 1;
+    }
+    c
+}
+
+// xor of the positions of the bits set, zero for a valid codeword
+fn hamming_syndrome(c: u128, n: u32) -> u32 {
+    (1..=n)
+        .filter(|pos| (c >> (pos - 1)) & 1 == 1)
+        .fold(0, |s, pos| s ^ pos)
+}
+
+fn hamming_extract(c: u128, n: u32) -> u128 {
+    (1..=n)
+        .filter(|pos| !pos.is_power_of_two())
+        .enumerate()
+        .fold(0, |msg, (k, pos)| msg | ((c >> (pos - 1)) & 1) << k)
+}
+
+fn hamming_decode(c: u128, n: u32) -> LiquidResult<u128> {
+    let s = hamming_syndrome(c, n);
+    if s > n {
+        // only possible for shortened codes
+        return Err(uncorrectable());
+    }
+    let c = if s == 0 { c } else { c ^ 1 << (s - 1) };
+    Ok(hamming_extract(c, n))
+}
+
+// Hamming codeword of length n followed by an overall parity bit
+fn secded_encode(msg: u128, n: u32) -> u128 {
+    let c = hamming_encode(msg, n);
+    c | ((c.count_ones() & 1) as u128) << n
+}
+
+fn secded_decode(c: u128, n: u32) -> LiquidResult<u128> {
+    let s = hamming_syndrome(c, n);
+    let parity = c.count_ones() & 1;
+    let c = c & ((1 << n) - 1);
+    match (s, parity) {
+        (0, _) => Ok(hamming_extract(c, n)),
+        (s, 1) if s <= n => Ok(hamming_extract(c ^ 1 << (s - 1), n)),
+        _ => Err(uncorrectable()),
+    }
+}
+
+// generator polynomial of the cyclic Golay(23,12) code,
+// x^11 + x^10 + x^6 + x^5 + x^4 + x^2 + 1
+const GOLAY_POLY: u32 = 0xc75;
+
+// remainder of a 23-bit word modulo the generator polynomial
+const fn golay_rem(mut r: u32) -> u32 {
+    let mut i = 22;
+    while i >= 11 {
+        if (r >> i) & 1 == 1 {
+            r ^= GOLAY_POLY << (i - 11);
+        }
+        i -= 1;
+    }
+    r
+}
+
+// error pattern of weight <= 3 for each of the 2048 syndromes, the code
+// being perfect every syndrome is hit exactly once
+static GOLAY_SYNDROME_TABLE: [u32; 2048] = golay_syndrome_table();
+
+const fn golay_syndrome_table() -> [u32; 2048] {
+    let mut table = [0u32; 2048];
+    let mut i = 0;
+    while i < 23 {
+        let mut j = i + 1;
+        while j < 23 {
+            let mut k = j + 1;
+            while k < 23 {
+                let e = 1 << i | 1 << j | 1 << k;
+                table[golay_rem(e) as usize] = e;
+                k += 1;
+            }
+            let e = 1 << i | 1 << j;
+            table[golay_rem(e) as usize] = e;
+            j += 1;
+        }
+        table[golay_rem(1 << i) as usize] = 1 << i;
+        i += 1;
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // every message, with no error and every single bit error
+    fn check_single(
+        k: u32,
+        n: u32,
+        enc: fn(u32) -> LiquidResult<u32>,
+        dec: fn(u32) -> LiquidResult<u32>,
+    ) {
+        for m in 0..1u32 << k {
+            let c = enc(m).unwrap();
+            assert!(c >> n == 0);
+            assert_eq!(dec(c).unwrap(), m);
+            for i in 0..n {
+                assert_eq!(dec(c ^ 1 << i).unwrap(), m);
+            }
+        }
+        assert!(enc(1 << k).is_err());
+        assert!(dec(1 << n).is_err());
+    }
+
+    // every double bit error is detected
+    fn check_double(
+        k: u32,
+        n: u32,
+        enc: fn(u32) -> LiquidResult<u32>,
+        dec: fn(u32) -> LiquidResult<u32>,
+    ) {
+        for m in 0..1u32 << k {
+            let c = enc(m).unwrap();
+            for i in 0..n {
+                for j in i + 1..n {
+                    assert!(dec(c ^ 1 << i ^ 1 << j).is_err());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_hamming() {
+        check_single(4, 7, hamming74_encode, hamming74_decode);
+        check_single(4, 8, hamming84_encode, hamming84_decode);
+        check_double(4, 8, hamming84_encode, hamming84_decode);
+        check_single(8, 12, hamming128_encode, hamming128_decode);
+        // minimum distance of 3
+        for a in 0..16 {
+            for b in 0..a {
+                let d = hamming74_encode(a).unwrap() ^ hamming74_encode(b).unwrap();
+                assert!(d.count_ones() >= 3);
+            }
+        }
+    }
+
+    #[test]
+    fn test_golay2412() {
+        for m in 0..1u32 << 12 {
+            let c = golay2412_encode(m).unwrap();
+            assert!(c >> 24 == 0);
+            assert_eq!(c.count_ones() % 4, 0);
+            assert_eq!(golay2412_decode(c).unwrap(), m);
+            for i in 0..24 {
+                let r = c ^ 1 << i;
+                assert_eq!(golay2412_decode(r).unwrap(), m);
+                for j in i + 1..24 {
+                    let r = r ^ 1 << j;
+                    assert_eq!(golay2412_decode(r).unwrap(), m);
+                    for l in j + 1..24 {
+                        assert_eq!(golay2412_decode(r ^ 1 << l).unwrap(), m);
+                    }
+                }
+            }
+        }
+        // four errors are detected
+        let c = golay2412_encode(0xa5c).unwrap();
+        for i in 0..24 {
+            for j in i + 1..24 {
+                for l in j + 1..24 {
+                    for q in l + 1..24 {
+                        assert!(golay2412_decode(c ^ 1 << i ^ 1 << j ^ 1 << l ^ 1 << q).is_err());
+                    }
+                }
+            }
+        }
+        assert!(golay2412_encode(1 << 12).is_err());
+    }
+
+    #[test]
+    fn test_secded() {
+        check_single(16, 22, secded2216_encode, secded2216_decode);
+        for m in [0u32, 1, 0x8000_0000, 0xdead_beef, u32::MAX] {
+            let c = secded3932_encode(m);
+            assert!(c >> 39 == 0);
+            assert_eq!(secded3932_decode(c).unwrap(), m);
+            for i in 0..39 {
+                assert_eq!(secded3932_decode(c ^ 1 << i).unwrap(), m);
+                for j in i + 1..39 {
+                    assert!(secded3932_decode(c ^ 1 << i ^ 1 << j).is_err());
+                }
+            }
+        }
+        for m in [0u64, 1, 1 << 63, 0x0123_4567_89ab_cdef, u64::MAX] {
+            let c = secded7264_encode(m);
+            assert!(c >> 72 == 0);
+            assert_eq!(secded7264_decode(c).unwrap(), m);
+            for i in 0..72 {
+                assert_eq!(secded7264_decode(c ^ 1 << i).unwrap(), m);
+                for j in i + 1..72 {
+                    assert!(secded7264_decode(c ^ 1 << i ^ 1 << j).is_err());
+                }
+            }
+        }
+        assert!(secded7264_decode(1 << 72).is_err());
+    }
+}
@@ -1,7 +1,10 @@
-use std::ffi::{CString, NulError};
+use std::convert::TryFrom;
+use std::ffi::{CStr, CString, NulError};
+use std::fmt;
+use std::str::FromStr;
 
 use crate::enums::CrcScheme;
-use crate::errors::LiquidError;
+use crate::errors::{FecError, LiquidError};
 
 use crate::liquid_dsp_sys as raw;
 
@@ -13,27 +16,44 @@ impl CrcScheme {
         }
     }
 
+    /// liquid name of the scheme, e.g. "crc16"
+    pub fn as_str(&self) -> &'static str {
+        let index = u8::from(*self) as usize;
+        unsafe {
+            let name = raw::crc_scheme_str[index][0];
+            CStr::from_ptr(name).to_str().unwrap_or("unknown")
+        }
+    }
+
     pub fn getopt_str2crc(s: &str) -> Result<Self, NulError> {
         unsafe {
-            CString::new(s)
-                .and_then(|c| Ok(CrcScheme::from(raw::liquid_getopt_str2crc(c.as_ptr()) as u8)))
+            CString::new(s).map(|c| {
+                CrcScheme::try_from(raw::liquid_getopt_str2crc(c.as_ptr()) as u8)
+                    .unwrap_or(CrcScheme::CRC_UNKNOWN)
+            })
         }
     }
 
     /// get length of CRC (bytes)
     pub fn get_length(&self) -> usize {
-        unsafe { raw::crc_get_length(u8::from(self.clone()) as _) as usize }
+        unsafe { raw::crc_get_length(u8::from(*self) as _) as usize }
+    }
+
+    /// size of the error-detection key (bytes), 0 for CRC_NONE up to 4
+    /// for CRC_32, see sizeof_key()
+    pub fn key_size(&self) -> Result<usize, LiquidError> {
+        Self::sizeof_key(*self)
     }
 
     /// generates error-detection key
     ///  msg        :   input data message,
     pub fn generate_key<T: AsRef<[u8]>>(&self, msg: T) -> Result<usize, LiquidError> {
         match self {
-            Self::CRC_UNKNOWN => return Err(LiquidError::InvalidCrcScheme),
+            Self::CRC_UNKNOWN => Err(FecError::InvalidCrcScheme.into()),
             _ => {
                 let i = unsafe {
                     raw::crc_generate_key(
-                        u8::from(self.clone()) as _,
+                        u8::from(*self) as _,
                         msg.as_ref().as_ptr() as _,
                         msg.as_ref().len() as _,
                     ) as usize
@@ -43,17 +63,25 @@ impl CrcScheme {
         }
     }
 
-    // TODO: check if there are lenght constrains
+    /// generates error-detection key of a message given as a sequence of
+    /// bytes, e.g. the output of an iterator adapter
+    pub fn generate_key_iter<I: IntoIterator<Item = u8>>(
+        &self,
+        msg: I,
+    ) -> Result<usize, LiquidError> {
+        let msg: Vec<u8> = msg.into_iter().collect();
+        self.generate_key(msg)
+    }
+
     /// generate error-detection key and append to end of message
-    ///  msg        :   input data message
-    pub fn append_key<T: AsMut<[u8]>>(&self, mut msg: T) {
+    ///  msg        :   input data message, grows by key_size() bytes
+    pub fn append_key(&self, msg: &mut Vec<u8>) -> Result<(), LiquidError> {
+        let n = msg.len();
+        msg.resize(n + self.key_size()?, 0);
         unsafe {
-            raw::crc_append_key(
-                u8::from(self.clone()) as _,
-                msg.as_mut().as_ptr() as _,
-                msg.as_mut().len() as _,
-            );
+            raw::crc_append_key(u8::from(*self) as _, msg.as_mut_ptr() as _, n as _);
         }
+        Ok(())
     }
 
     /// validate message using error-detection key
@@ -65,10 +93,10 @@ impl CrcScheme {
         key: usize,
     ) -> Result<bool, LiquidError> {
         match self {
-            Self::CRC_UNKNOWN => return Err(LiquidError::InvalidCrcScheme),
+            Self::CRC_UNKNOWN => Err(FecError::InvalidCrcScheme.into()),
             _ => unsafe {
                 Ok(raw::crc_validate_message(
-                    u8::from(self.clone()) as _,
+                    u8::from(*self) as _,
                     msg.as_ref().as_ptr() as _,
                     msg.as_ref().len() as _,
                     key as _,
@@ -78,17 +106,30 @@ impl CrcScheme {
     }
 
     /// check message with key appended to end of array
-    ///  msg        :   input data message, [size: _n+p x 1], input data message size (excluding key at end)
+    ///  msg        :   input data message followed by the key,
+    ///                 [size: n+key_size() x 1]
+    /// # returns
+    /// FecError::InvalidLength if the message is shorter than the key
     pub fn check_key<T: AsRef<[u8]>>(&self, msg: T) -> Result<bool, LiquidError> {
-        match self {
-            Self::CRC_UNKNOWN => return Err(LiquidError::InvalidCrcScheme),
-            _ => unsafe {
-                Ok(raw::crc_check_key(
-                    u8::from(self.clone()) as _,
-                    msg.as_ref().as_ptr() as _,
-                    msg.as_ref().len() as _,
-                ) == 1)
-            },
+        let msg = msg.as_ref();
+        let key_size = self.key_size()?;
+        if msg.len() < key_size {
+            return Err(FecError::InvalidLength {
+                description: format!(
+                    "message length: {} is shorter than the key: {} bytes",
+                    msg.len(),
+                    key_size
+                ),
+            }
+            .into());
+        }
+        // liquid takes the message length without the key
+        unsafe {
+            Ok(raw::crc_check_key(
+                u8::from(*self) as _,
+                msg.as_ptr() as _,
+                (msg.len() - key_size) as _,
+            ) == 1)
         }
     }
 
@@ -96,9 +137,70 @@ impl CrcScheme {
     pub fn sizeof_key(scheme: CrcScheme) -> Result<usize, LiquidError> {
         unsafe {
             match scheme {
-                Self::CRC_UNKNOWN => return Err(LiquidError::InvalidCrcScheme),
+                Self::CRC_UNKNOWN => Err(FecError::InvalidCrcScheme.into()),
                 _ => Ok(raw::crc_sizeof_key(u8::from(scheme) as _) as usize),
             }
         }
     }
 }
+
+impl FromStr for CrcScheme {
+    type Err = LiquidError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match Self::getopt_str2crc(s) {
+            Ok(CrcScheme::CRC_UNKNOWN) | Err(_) => Err(FecError::InvalidCrcScheme.into()),
+            Ok(scheme) => Ok(scheme),
+        }
+    }
+}
+
+impl fmt::Display for CrcScheme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::enums::CrcScheme;
+
+    #[test]
+    fn test_crc_scheme_str() {
+        assert_eq!(CrcScheme::CRC_16.to_string(), "crc16");
+        assert_eq!("crc32".parse::<CrcScheme>().unwrap(), CrcScheme::CRC_32);
+        assert!("bogus".parse::<CrcScheme>().is_err());
+    }
+
+    #[test]
+    fn test_crc_append_key() {
+        assert_eq!(CrcScheme::CRC_NONE.key_size().unwrap(), 0);
+        assert_eq!(CrcScheme::CRC_24.key_size().unwrap(), 3);
+        assert!(CrcScheme::CRC_UNKNOWN.key_size().is_err());
+
+        let mut msg = vec![0x12, 0x34, 0x56];
+        CrcScheme::CRC_32.append_key(&mut msg).unwrap();
+        assert_eq!(msg.len(), 7);
+        assert_eq!(&msg[..3], &[0x12, 0x34, 0x56]);
+        assert!(CrcScheme::CRC_UNKNOWN.append_key(&mut msg).is_err());
+
+        assert!(CrcScheme::CRC_32.check_key(&msg[..3]).is_err());
+        assert!(CrcScheme::CRC_UNKNOWN.check_key(&msg).is_err());
+
+        for &(scheme, size) in &[
+            (CrcScheme::CRC_CHECKSUM, 1),
+            (CrcScheme::CRC_8, 1),
+            (CrcScheme::CRC_16, 2),
+            (CrcScheme::CRC_24, 3),
+            (CrcScheme::CRC_32, 4),
+        ] {
+            assert_eq!(scheme.key_size().unwrap(), size);
+            let mut msg: Vec<u8> = (0..16u8).map(|i| i.wrapping_mul(37)).collect();
+            scheme.append_key(&mut msg).unwrap();
+            assert_eq!(msg.len(), 16 + size);
+            assert!(scheme.check_key(&msg).unwrap(), "{}", scheme);
+            msg[5] ^= 0x10;
+            assert!(!scheme.check_key(&msg).unwrap(), "{}", scheme);
+        }
+    }
+}
@@ -1,27 +1,225 @@
 use libc::{c_uint, c_void};
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+use std::convert::TryFrom;
+use std::ffi::{CStr, CString};
+use std::fmt;
 use std::ptr;
+use std::str::FromStr;
 
 use crate::enums::FecScheme;
-use crate::errors::LiquidError;
+use crate::errors::{FecError, LiquidError};
 use crate::liquid_dsp_sys as raw;
+use crate::version::has_libfec;
+use crate::LiquidResult;
+
+/// code word structure of a block code, in symbols of symbol_bits bits
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct FecBlockProperties {
+    /// code word length [symbols]
+    pub n: usize,
+    /// message length [symbols]
+    pub k: usize,
+    /// bits per symbol, 1 for the binary codes and 8 for Reed-Solomon
+    pub symbol_bits: usize,
+}
+
+impl FecBlockProperties {
+    /// parity symbols per code word
+    pub fn parity(&self) -> usize {
+        self.n - self.k
+    }
+}
+
+impl FecScheme {
+    /// Print compact list of existing and available fec schemes
+    pub fn print_fec_schemes() {
+        unsafe {
+            raw::liquid_print_fec_schemes();
+        }
+    }
+
+    /// liquid name of the scheme, e.g. "h74" or "v27p23"
+    pub fn as_str(&self) -> &'static str {
+        let index = u8::from(*self) as usize;
+        unsafe {
+            let name = raw::fec_scheme_str[index][0];
+            CStr::from_ptr(name).to_str().unwrap_or("unknown")
+        }
+    }
+
+    /// true if the linked library can run this scheme; the convolutional
+    /// and Reed-Solomon codes are only available when libliquid was built
+    /// against libfec, see version::has_libfec()
+    pub fn is_supported(&self) -> bool {
+        match self {
+            FecScheme::UNKNOWN => false,
+            scheme if scheme.requires_libfec() => has_libfec(),
+            _ => true,
+        }
+    }
+
+    /// is the scheme implemented by libfec, the convolutional and
+    /// Reed-Solomon codes? liquid exits the process when such a Fec is
+    /// created without libfec, so they are checked before calling it
+    pub fn requires_libfec(&self) -> bool {
+        self.is_convolutional() || *self == FecScheme::RS_M8
+    }
+
+    /// error out if the linked library cannot run this scheme
+    pub(crate) fn check_supported(&self) -> LiquidResult<()> {
+        if *self == FecScheme::UNKNOWN {
+            return Err(FecError::InvalidFecScheme.into());
+        } else if !self.is_supported() {
+            return Err(FecError::UnsupportedFecScheme(format!(
+                "fec scheme {} is not available, libliquid {} was built without libfec",
+                self.as_str(),
+                crate::version()
+            ))
+            .into());
+        }
+        Ok(())
+    }
+
+    /// code rate as (k, n), k message bits for every n encoded bits; the
+    /// termination bits of the convolutional codes are not accounted for,
+    /// see Fec::get_enc_msg_length for the exact encoded length
+    pub fn rate_fraction(&self) -> (u32, u32) {
+        use FecScheme::*;
+        match self {
+            UNKNOWN => (0, 1),
+            NONE => (1, 1),
+            REP3 => (1, 3),
+            REP5 => (1, 5),
+            HAMMING74 => (4, 7),
+            HAMMING84 => (4, 8),
+            HAMMING128 => (8, 12),
+            GOLAY2412 => (12, 24),
+            SECDED2216 => (16, 22),
+            SECDED3932 => (32, 39),
+            SECDED7264 => (64, 72),
+            CONV_V27 | CONV_V29 => (1, 2),
+            CONV_V39 => (1, 3),
+            CONV_V615 => (1, 6),
+            CONV_V27P23 | CONV_V29P23 => (2, 3),
+            CONV_V27P34 | CONV_V29P34 => (3, 4),
+            CONV_V27P45 | CONV_V29P45 => (4, 5),
+            CONV_V27P56 | CONV_V29P56 => (5, 6),
+            CONV_V27P67 | CONV_V29P67 => (6, 7),
+            CONV_V27P78 | CONV_V29P78 => (7, 8),
+            RS_M8 => (223, 255),
+        }
+    }
+
+    /// code rate, message bits per encoded bit, 0 for UNKNOWN; the rates
+    /// are tabulated here, so unlike Fec::get_rate they are also known for
+    /// the libfec schemes when libliquid was built without libfec
+    pub fn rate(&self) -> f32 {
+        let (k, n) = self.rate_fraction();
+        k as f32 / n as f32
+    }
+
+    /// is the scheme a convolutional code, punctured or not?
+    pub fn is_convolutional(&self) -> bool {
+        self.constraint_length().is_some()
+    }
+
+    /// is the scheme a punctured convolutional code?
+    pub fn is_punctured(&self) -> bool {
+        use FecScheme::*;
+        matches!(
+            self,
+            CONV_V27P23
+                | CONV_V27P34
+                | CONV_V27P45
+                | CONV_V27P56
+                | CONV_V27P67
+                | CONV_V27P78
+                | CONV_V29P23
+                | CONV_V29P34
+                | CONV_V29P45
+                | CONV_V29P56
+                | CONV_V29P67
+                | CONV_V29P78
+        )
+    }
+
+    /// constraint length K of the convolutional codes, the encoder
+    /// appending K-1 termination bits to every message
+    pub fn constraint_length(&self) -> Option<u32> {
+        use FecScheme::*;
+        match self {
+            CONV_V27 | CONV_V27P23 | CONV_V27P34 | CONV_V27P45 | CONV_V27P56 | CONV_V27P67
+            | CONV_V27P78 => Some(7),
+            CONV_V29 | CONV_V29P23 | CONV_V29P34 | CONV_V29P45 | CONV_V29P56 | CONV_V29P67
+            | CONV_V29P78 | CONV_V39 => Some(9),
+            CONV_V615 => Some(15),
+            _ => None,
+        }
+    }
+
+    /// code word structure of the block codes, None for the convolutional
+    /// codes and the UNKNOWN and NONE schemes
+    pub fn block_properties(&self) -> Option<FecBlockProperties> {
+        use FecScheme::*;
+        let symbol_bits = match self {
+            UNKNOWN | NONE => return None,
+            RS_M8 => 8,
+            _ if self.is_convolutional() => return None,
+            _ => 1,
+        };
+        let (k, n) = self.rate_fraction();
+        Some(FecBlockProperties {
+            n: n as usize,
+            k: k as usize,
+            symbol_bits,
+        })
+    }
+
+    /// list of the schemes supported by the linked library
+    pub fn available() -> Vec<FecScheme> {
+        (1..raw::LIQUID_FEC_NUM_SCHEMES as u8)
+            .filter_map(|v| FecScheme::try_from(v).ok())
+            .filter(FecScheme::is_supported)
+            .collect()
+    }
+}
+
+impl FromStr for FecScheme {
+    type Err = LiquidError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let c = CString::new(s).map_err(|_| LiquidError::from(FecError::InvalidFecScheme))?;
+        match FecScheme::try_from(unsafe { raw::liquid_getopt_str2fec(c.as_ptr()) } as u8) {
+            Ok(FecScheme::UNKNOWN) | Err(_) => Err(FecError::InvalidFecScheme.into()),
+            Ok(scheme) => Ok(scheme),
+        }
+    }
+}
+
+impl fmt::Display for FecScheme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
 pub struct Fec {
     inner: raw::fec,
+    scheme: FecScheme,
 }
 
 impl Fec {
     /// create a fec object of a particular scheme
     ///  scheme     :   error-correction scheme( FecScheme)
+    /// # returns
+    /// an error if the scheme is UNKNOWN or not available in the linked library
     pub fn create(scheme: FecScheme) -> Result<Self, LiquidError> {
+        scheme.check_supported()?;
         let ptr: *mut c_void = ptr::null_mut();
-        unsafe {
-            if scheme != FecScheme::UNKNOWN {
-                return Ok(Self {
-                    inner: raw::fec_create(u8::from(scheme) as c_uint, ptr),
-                });
-            }
-
-            Err(LiquidError::InvalidFecScheme)
-        }
+        Ok(Self {
+            inner: unsafe { raw::fec_create(u8::from(scheme) as c_uint, ptr) },
+            scheme,
+        })
     }
 
     /// return the encoded message length using a particular error-
@@ -40,12 +238,18 @@ impl Fec {
 
     /// recreate a fec object
     ///  scheme :   new scheme (FecScheme)
-    pub fn recreate(mut self, scheme: FecScheme) -> Self {
+    pub fn recreate(mut self, scheme: FecScheme) -> LiquidResult<Self> {
+        scheme.check_supported()?;
         let ptr: *mut c_void = ptr::null_mut();
         unsafe {
             self.inner = raw::fec_recreate(self.inner, u8::from(scheme) as c_uint, ptr);
         }
-        self
+        self.scheme = scheme;
+        Ok(self)
+    }
+
+    pub fn get_scheme(&self) -> FecScheme {
+        self.scheme
     }
 
     /// print channel object
@@ -83,6 +287,48 @@ impl Fec {
         }
     }
 
+    /// encode a message, resizing *encoded* to the encoded length; the
+    /// buffer is only reallocated when its capacity is too small
+    ///  raw        :   decoded message
+    ///  encoded    :   encoded message
+    pub fn encode_to_vec(&self, raw: &[u8], encoded: &mut Vec<u8>) {
+        let len = Fec::get_enc_msg_length(self.scheme, raw.len() as u32) as usize;
+        encoded.clear();
+        encoded.resize(len, 0);
+        self.encode(raw, encoded);
+    }
+
+    /// decode a message, resizing *raw* to the decoded length
+    ///  encoded    :   encoded message
+    ///  msg_len    :   raw uncoded message length, several lengths may
+    ///                 share the same encoded length
+    ///  raw        :   decoded message
+    /// # returns
+    /// an error if the encoded length does not match msg_len
+    pub fn decode_to_vec(
+        &self,
+        encoded: &[u8],
+        msg_len: usize,
+        raw: &mut Vec<u8>,
+    ) -> LiquidResult<()> {
+        let len = Fec::get_enc_msg_length(self.scheme, msg_len as u32) as usize;
+        if encoded.len() != len {
+            return Err(FecError::InvalidLength {
+                description: format!(
+                    "encoded length: {} valid length: {} for a message of {} bytes",
+                    encoded.len(),
+                    len,
+                    msg_len
+                ),
+            }
+            .into());
+        }
+        raw.clear();
+        raw.resize(msg_len, 0);
+        self.decode(encoded, raw);
+        Ok(())
+    }
+
     pub fn decode_soft(&self, encoded: &[u8], raw: &mut [u8]) {
         unsafe {
             raw::fec_decode_soft(
@@ -93,6 +339,113 @@ impl Fec {
             );
         }
     }
+
+    /// encode a sequence of independent messages on the rayon thread pool
+    ///  msg_len    :   raw uncoded length of each message
+    ///  raw        :   decoded messages [size: k*msg_len x 1]
+    ///  encoded    :   encoded messages [size: k*enc_msg_len x 1]
+    #[cfg(feature = "rayon")]
+    pub fn encode_block_par(
+        &self,
+        msg_len: usize,
+        raw: &[u8],
+        encoded: &mut [u8],
+    ) -> LiquidResult<()> {
+        let enc_len = self.check_block_lengths(msg_len, raw.len(), encoded.len(), 1)?;
+        let scheme = self.scheme;
+        raw.par_chunks(msg_len)
+            .zip(encoded.par_chunks_mut(enc_len))
+            .for_each_init(
+                || Fec::create(scheme).unwrap(),
+                |fec, (dec, enc)| fec.encode(dec, enc),
+            );
+        Ok(())
+    }
+
+    /// decode a sequence of independent messages on the rayon thread pool
+    ///  msg_len    :   raw uncoded length of each message
+    ///  encoded    :   encoded messages [size: k*enc_msg_len x 1]
+    ///  raw        :   decoded messages [size: k*msg_len x 1]
+    #[cfg(feature = "rayon")]
+    pub fn decode_block_par(
+        &self,
+        msg_len: usize,
+        encoded: &[u8],
+        raw: &mut [u8],
+    ) -> LiquidResult<()> {
+        let enc_len = self.check_block_lengths(msg_len, raw.len(), encoded.len(), 1)?;
+        let scheme = self.scheme;
+        encoded
+            .par_chunks(enc_len)
+            .zip(raw.par_chunks_mut(msg_len))
+            .for_each_init(
+                || Fec::create(scheme).unwrap(),
+                |fec, (enc, dec)| fec.decode(enc, dec),
+            );
+        Ok(())
+    }
+
+    /// soft-decode a sequence of independent messages on the rayon thread pool
+    ///  msg_len    :   raw uncoded length of each message
+    ///  encoded    :   soft bits of the encoded messages [size: 8*k*enc_msg_len x 1]
+    ///  raw        :   decoded messages [size: k*msg_len x 1]
+    #[cfg(feature = "rayon")]
+    pub fn decode_soft_block_par(
+        &self,
+        msg_len: usize,
+        encoded: &[u8],
+        raw: &mut [u8],
+    ) -> LiquidResult<()> {
+        let enc_len = self.check_block_lengths(msg_len, raw.len(), encoded.len(), 8)?;
+        let scheme = self.scheme;
+        encoded
+            .par_chunks(enc_len)
+            .zip(raw.par_chunks_mut(msg_len))
+            .for_each_init(
+                || Fec::create(scheme).unwrap(),
+                |fec, (enc, dec)| fec.decode_soft(enc, dec),
+            );
+        Ok(())
+    }
+
+    /// validates the lengths of a sequence of messages, returning
+    /// the length of each encoded message in the encoded buffer
+    #[cfg(feature = "rayon")]
+    fn check_block_lengths(
+        &self,
+        msg_len: usize,
+        raw_len: usize,
+        encoded_len: usize,
+        bits_per_byte: usize,
+    ) -> LiquidResult<usize> {
+        let enc_len = bits_per_byte * Fec::get_enc_msg_length(self.scheme, msg_len as u32) as usize;
+        if msg_len == 0 || raw_len % msg_len != 0 {
+            return Err(FecError::InvalidLength {
+                description: format!(
+                    "raw length: {} must be a multiple of the message length {}",
+                    raw_len, msg_len
+                ),
+            }
+            .into());
+        } else if encoded_len != (raw_len / msg_len) * enc_len {
+            return Err(FecError::InvalidLength {
+                description: format!(
+                    "encoded length: {} valid length: {}",
+                    encoded_len,
+                    (raw_len / msg_len) * enc_len
+                ),
+            }
+            .into());
+        }
+        Ok(enc_len)
+    }
+}
+
+impl Clone for Fec {
+    /// create an independent fec object of the same scheme
+    fn clone(&self) -> Self {
+        Fec::create(self.scheme).unwrap()
+    }
 }
 
 impl Drop for Fec {
@@ -107,6 +460,58 @@ impl Drop for Fec {
 mod tests {
     use super::Fec;
     use crate::enums::FecScheme;
+    use crate::version::has_libfec;
+    use std::convert::TryFrom;
+
+    #[test]
+    fn test_fec_scheme_str() {
+        assert_eq!(FecScheme::HAMMING74.as_str(), "h74");
+        assert_eq!("h74".parse::<FecScheme>().unwrap(), FecScheme::HAMMING74);
+        assert!("bogus".parse::<FecScheme>().is_err());
+        for scheme in FecScheme::available() {
+            assert_eq!(scheme.as_str().parse::<FecScheme>().unwrap(), scheme);
+        }
+        assert!(FecScheme::available().contains(&FecScheme::REP3));
+        assert!(!FecScheme::UNKNOWN.is_supported());
+        assert!(FecScheme::RS_M8.requires_libfec());
+        assert!(!FecScheme::SECDED7264.requires_libfec());
+        assert_eq!(FecScheme::CONV_V27.is_supported(), has_libfec());
+        if !has_libfec() {
+            assert!(Fec::create(FecScheme::CONV_V29P23).is_err());
+        }
+    }
+
+    #[test]
+    fn test_fec_scheme_properties() {
+        assert_eq!(FecScheme::HAMMING128.rate_fraction(), (8, 12));
+        assert_eq!(FecScheme::UNKNOWN.rate(), 0.0);
+        assert!(FecScheme::CONV_V29P34.is_punctured());
+        assert!(!FecScheme::CONV_V29.is_punctured());
+        assert_eq!(FecScheme::CONV_V27P78.constraint_length(), Some(7));
+        assert_eq!(FecScheme::CONV_V615.rate_fraction(), (1, 6));
+        assert_eq!(FecScheme::GOLAY2412.constraint_length(), None);
+        assert_eq!(FecScheme::CONV_V39.block_properties(), None);
+        assert_eq!(FecScheme::NONE.block_properties(), None);
+        let rs = FecScheme::RS_M8.block_properties().unwrap();
+        assert_eq!((rs.n, rs.k, rs.symbol_bits), (255, 223, 8));
+        assert_eq!(rs.parity(), 32);
+        assert_eq!(
+            FecScheme::SECDED7264.block_properties().unwrap().parity(),
+            8
+        );
+
+        assert_eq!(FecScheme::CONV_V29P56.rate(), 5.0 / 6.0);
+        assert_eq!(FecScheme::RS_M8.rate(), 223.0 / 255.0);
+
+        // same rates as liquid, which only knows the libfec schemes when
+        // built with it
+        for v in 1..28u8 {
+            let scheme = FecScheme::try_from(v).unwrap();
+            if scheme.is_supported() {
+                assert!((scheme.rate() - Fec::get_rate(scheme)).abs() < 1e-6);
+            }
+        }
+    }
 
     #[test]
     fn test_encode_decode() {
@@ -120,7 +525,7 @@ mod tests {
         let mut encoded_data = vec![0u8; enc_len as usize];
         let fec = Fec::create(FecScheme::HAMMING74).unwrap();
 
-        fec.encode(&raw, &mut encoded_data);
+        fec.encode(raw, &mut encoded_data);
 
         assert_eq!(expected_result, encoded_data.as_slice());
 
@@ -133,4 +538,41 @@ mod tests {
 
         assert_eq!(raw, decoded_data.as_slice());
     }
+
+    #[test]
+    fn test_encode_decode_to_vec() {
+        let fec = Fec::create(FecScheme::HAMMING74).unwrap();
+        let raw = [0x67, 0xC6, 0x69, 0x73];
+        let mut encoded = vec![0xff; 32];
+        fec.encode_to_vec(&raw, &mut encoded);
+        assert_eq!(encoded, [0xCC, 0x3D, 0xE6, 0x6C, 0xC6, 0x47, 0xC3]);
+
+        let mut decoded = Vec::new();
+        assert!(fec.decode_to_vec(&encoded, 5, &mut decoded).is_err());
+        fec.decode_to_vec(&encoded, 4, &mut decoded).unwrap();
+        assert_eq!(decoded, raw);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_encode_decode_block_par() {
+        let fec = Fec::create(FecScheme::HAMMING74).unwrap();
+        let raw: Vec<u8> = (0..32u8).map(|i| i.wrapping_mul(37)).collect();
+        let enc_len = Fec::get_enc_msg_length(FecScheme::HAMMING74, 4) as usize;
+
+        let mut encoded = vec![0u8; 8 * enc_len];
+        fec.encode_block_par(4, &raw, &mut encoded).unwrap();
+
+        let mut expected = vec![0u8; enc_len];
+        fec.encode(&raw[4..8], &mut expected);
+        assert_eq!(&encoded[enc_len..2 * enc_len], expected.as_slice());
+
+        let mut decoded = vec![0u8; raw.len()];
+        fec.clone()
+            .decode_block_par(4, &encoded, &mut decoded)
+            .unwrap();
+        assert_eq!(raw, decoded);
+
+        assert!(fec.encode_block_par(5, &raw, &mut encoded).is_err());
+    }
 }
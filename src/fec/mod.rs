@@ -1,8 +1,14 @@
-pub use fec::Fec;
+pub use fec::{Fec, FecBlockProperties};
 pub use interleaver::Interleaver;
-pub use packetizer::Packetizer;
+pub use packetizer::{Packetizer, PacketizerConfig};
+pub use soft::{Llr, LlrConvention, SoftDeinterleaver};
+pub use stream::{FecStream, FecStreamBlock};
 
-// mod crc;
+pub mod blockcodes;
+mod crc;
+#[allow(clippy::module_inception)]
 mod fec;
 mod interleaver;
 mod packetizer;
+mod soft;
+mod stream;
@@ -1,5 +1,24 @@
+use std::convert::TryFrom;
+
 use crate::enums::{CrcScheme, FecScheme};
+use crate::errors::FecError;
 use crate::liquid_dsp_sys as raw;
+use crate::LiquidResult;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// parameters of a packetizer, e.g. to describe it in a configuration file
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PacketizerConfig {
+    /// uncoded message length, in bytes
+    pub n: u32,
+    pub crc: CrcScheme,
+    /// inner forward error-correction scheme
+    pub fec0: FecScheme,
+    /// outer forward error-correction scheme
+    pub fec1: FecScheme,
+}
 
 pub struct Packetizer {
     inner: raw::packetizer,
@@ -8,9 +27,13 @@ pub struct Packetizer {
 
 impl Packetizer {
     /// creates and returns a packetizer object which accepts *n* uncoded input bytes and uses the specified CRC and bi-level FEC schemes.
-    pub fn create(n: u32, crc: CrcScheme, fec0: FecScheme, fec1: FecScheme) -> Self {
+    /// # returns
+    /// an error if one of the fec schemes is not available in the linked library
+    pub fn create(n: u32, crc: CrcScheme, fec0: FecScheme, fec1: FecScheme) -> LiquidResult<Self> {
+        fec0.check_supported()?;
+        fec1.check_supported()?;
         unsafe {
-            Self {
+            Ok(Self {
                 inner: raw::packetizer_create(
                     n as _,
                     u8::from(crc) as _,
@@ -18,12 +41,36 @@ impl Packetizer {
                     u8::from(fec1) as _,
                 ),
                 n,
-            }
+            })
+        }
+    }
+
+    /// creates a packetizer object from its configuration
+    pub fn from_config(config: &PacketizerConfig) -> LiquidResult<Self> {
+        Self::create(config.n, config.crc, config.fec0, config.fec1)
+    }
+
+    /// configuration of the packetizer, Packetizer::from_config builds
+    /// an identical object from it
+    pub fn config(&self) -> PacketizerConfig {
+        PacketizerConfig {
+            n: self.n,
+            crc: self.get_crc(),
+            fec0: self.get_fec0(),
+            fec1: self.get_fec1(),
         }
     }
 
     /// re-creates an existing packetizer object with new parameters.
-    pub fn recreate(mut self, n: u32, crc: CrcScheme, fec0: FecScheme, fec1: FecScheme) -> Self {
+    pub fn recreate(
+        mut self,
+        n: u32,
+        crc: CrcScheme,
+        fec0: FecScheme,
+        fec1: FecScheme,
+    ) -> LiquidResult<Self> {
+        fec0.check_supported()?;
+        fec1.check_supported()?;
         unsafe {
             self.inner = raw::packetizer_recreate(
                 self.inner,
@@ -34,7 +81,7 @@ impl Packetizer {
             );
             self.n = n;
         }
-        self
+        Ok(self)
     }
 
     /// prints the internal state of the packetizer object to the standard output.
@@ -87,70 +134,100 @@ impl Packetizer {
     }
 
     pub fn get_crc(&self) -> CrcScheme {
-        CrcScheme::from(unsafe { raw::packetizer_get_crc(self.inner) as u8 })
+        CrcScheme::try_from(unsafe { raw::packetizer_get_crc(self.inner) as u8 })
+            .unwrap_or(CrcScheme::CRC_UNKNOWN)
     }
 
     pub fn get_fec0(&self) -> FecScheme {
-        FecScheme::from(unsafe { raw::packetizer_get_fec0(self.inner) as u8 })
+        FecScheme::try_from(unsafe { raw::packetizer_get_fec0(self.inner) as u8 })
+            .unwrap_or(FecScheme::UNKNOWN)
     }
 
     pub fn get_fec1(&self) -> FecScheme {
-        FecScheme::from(unsafe { raw::packetizer_get_fec1(self.inner) as u8 })
+        FecScheme::try_from(unsafe { raw::packetizer_get_fec1(self.inner) as u8 })
+            .unwrap_or(FecScheme::UNKNOWN)
+    }
+
+    fn check_lengths(&self, raw: usize, pckt: usize) -> LiquidResult<()> {
+        if raw != self.get_dec_msg_len() {
+            return Err(FecError::InvalidLength {
+                description: format!(
+                    "raw data length: {} valid length: {}, use get_dec_msg_len",
+                    raw,
+                    self.get_dec_msg_len()
+                ),
+            }
+            .into());
+        } else if pckt != self.get_enc_msg_len() {
+            return Err(FecError::InvalidLength {
+                description: format!(
+                    "pckt length: {} valid length: {}, use get_enc_msg_len",
+                    pckt,
+                    self.get_enc_msg_len()
+                ),
+            }
+            .into());
+        }
+        Ok(())
     }
 
     /// encodes the n -byte input message storing the result in the k -byte encoded output message.
-    /// panics if the provided buffers are not the same len as n and k internal buffers.
-    pub fn encode(&self, raw: &[u8], pckt: &mut [u8]) {
-        assert!(
-            raw.len() == self.get_dec_msg_len(),
-            "raw data must have the same size as the internal buffer, 
-            use packetizer_get_dec_msg_len"
-        );
-        assert!(
-            pckt.len() == self.get_enc_msg_len(),
-            "pckt array must have the same size as the pckt internal buffer, 
-            use packetizer_get_enc_msg_len"
-        );
+    /// returns an error if the provided buffers are not the same len as n and k internal buffers.
+    pub fn encode(&self, raw: &[u8], pckt: &mut [u8]) -> LiquidResult<()> {
+        self.check_lengths(raw.len(), pckt.len())?;
         unsafe {
             raw::packetizer_encode(self.inner, raw.as_ptr() as _, pckt.as_mut_ptr() as _);
         }
+        Ok(())
+    }
+
+    /// encodes the n -byte input message, resizing pckt to the k -byte encoded length.
+    /// returns an error if the input message is not n bytes long.
+    pub fn encode_to_vec(&self, raw: &[u8], pckt: &mut Vec<u8>) -> LiquidResult<()> {
+        pckt.clear();
+        pckt.resize(self.get_enc_msg_len(), 0);
+        self.encode(raw, pckt)
+    }
+
+    /// decodes the k -byte encoded input message, resizing raw to the n -byte decoded
+    /// length. The function returns true if the internal CRC passed and false if it failed.
+    pub fn decode_to_vec(&self, pckt: &[u8], raw: &mut Vec<u8>) -> LiquidResult<bool> {
+        raw.clear();
+        raw.resize(self.get_dec_msg_len(), 0);
+        self.decode(pckt, raw)
     }
 
     /// decodes the k -byte encoded input message storing the result in the n -byte output.
-    /// The function returns a 1 if the internal CRC passed and a 0 if it failed.
-    /// If no CRC was specified (e.g. LIQUID_CRC_NONE ) then a 1 is always returned.
-    pub fn decode(&self, pckt: &[u8], raw: &mut [u8]) -> u8 {
-        assert!(
-            raw.len() == self.get_dec_msg_len(),
-            "raw data must have the same size as the internal buffer, 
-            use packetizer_get_dec_msg_len"
-        );
-        assert!(
-            pckt.len() == self.get_enc_msg_len(),
-            "pckt array must have the same size as the pckt internal buffer, 
-            use packetizer_get_enc_msg_len"
-        );
+    /// The function returns true if the internal CRC passed and false if it failed.
+    /// If no CRC was specified (e.g. CrcScheme::CRC_NONE) then true is always returned.
+    pub fn decode(&self, pckt: &[u8], raw: &mut [u8]) -> LiquidResult<bool> {
+        self.check_lengths(raw.len(), pckt.len())?;
         unsafe {
-            raw::packetizer_decode(self.inner, pckt.as_ptr() as _, raw.as_mut_ptr() as _) as u8
+            Ok(raw::packetizer_decode(self.inner, pckt.as_ptr() as _, raw.as_mut_ptr() as _) == 1)
         }
     }
 
-    /// decodes the encoded input message just like packetizer_decode() but with soft bits instead of hard bytes.
+    /// decodes the encoded input message just like decode() but with soft bits instead of hard bytes.
     /// The input is an array of type unsigned char with 8×k elements representing soft bits.
-    /// As before, the function returns a 1 if the internal CRC passed and a 0 if it failed.
+    /// As before, the function returns true if the internal CRC passed and false if it failed.
     /// See [section-fec-soft] for more information on soft-decision decoding.
-    pub fn decode_soft(&self, pckt: &[u8], raw: &mut [u8]) {
-        assert!(
-            raw.len() == self.get_dec_msg_len(),
-            "raw data must have the same size as the internal buffer, 
-            use packetizer_get_dec_msg_len"
-        );
-        assert!(
-            pckt.len() == 8 * self.get_enc_msg_len(),
-            "pckt array must have 8 * k elements"
-        );
+    pub fn decode_soft(&self, pckt: &[u8], raw: &mut [u8]) -> LiquidResult<bool> {
+        if !pckt.len().is_multiple_of(8) {
+            return Err(FecError::InvalidLength {
+                description: format!(
+                    "pckt length: {} valid length: {}, must have 8 * k elements",
+                    pckt.len(),
+                    8 * self.get_enc_msg_len()
+                ),
+            }
+            .into());
+        }
+        self.check_lengths(raw.len(), pckt.len() / 8)?;
         unsafe {
-            raw::packetizer_decode_soft(self.inner, pckt.as_ptr() as _, raw.as_mut_ptr() as _);
+            Ok(
+                raw::packetizer_decode_soft(self.inner, pckt.as_ptr() as _, raw.as_mut_ptr() as _)
+                    == 1,
+            )
         }
     }
 }
@@ -165,7 +242,7 @@ impl Drop for Packetizer {
 
 #[cfg(test)]
 mod tests {
-    use super::Packetizer;
+    use super::{Packetizer, PacketizerConfig};
     use crate::enums::{CrcScheme, FecScheme};
 
     #[test]
@@ -185,19 +262,83 @@ mod tests {
         let mut msg_dec = vec![0u8; n]; // decoded message
 
         // create the packetizer object
-        let p = Packetizer::create(n as _, crc, fec0, fec1);
+        let p = Packetizer::create(n as _, crc, fec0, fec1).unwrap();
 
         // initialize msg here
-        for i in 0..n {
-            msg[i] = (i & (0xff as usize)) as u8;
+        for (i, v) in msg.iter_mut().enumerate() {
+            *v = (i & 0xff) as u8;
         }
 
         // encode the packet
-        p.encode(&msg, &mut packet);
+        p.encode(&msg, &mut packet).unwrap();
 
         // decode the packet, returning validity
-        p.decode(&packet, &mut msg_dec);
+        let valid = p.decode(&packet, &mut msg_dec).unwrap();
 
+        assert!(valid);
         assert_eq!(&msg, &msg_dec);
     }
+
+    #[test]
+    fn test_packetizer_to_vec() {
+        let p = Packetizer::create(16, CrcScheme::CRC_32, FecScheme::HAMMING74, FecScheme::NONE)
+            .unwrap();
+        let msg: Vec<u8> = (0..16).collect();
+        let mut packet = Vec::new();
+        assert!(p.encode_to_vec(&msg[1..], &mut packet).is_err());
+        p.encode_to_vec(&msg, &mut packet).unwrap();
+        assert_eq!(packet.len(), p.get_enc_msg_len());
+
+        let mut decoded = vec![0u8; 3];
+        assert!(p.decode_to_vec(&packet[1..], &mut decoded).is_err());
+        assert!(p.decode_to_vec(&packet, &mut decoded).unwrap());
+        assert_eq!(decoded, msg);
+    }
+
+    #[test]
+    fn test_packetizer_invalid_length() {
+        let p =
+            Packetizer::create(16, CrcScheme::CRC_32, FecScheme::NONE, FecScheme::NONE).unwrap();
+        let msg = vec![0u8; 8];
+        let mut packet = vec![0u8; p.get_enc_msg_len()];
+        assert!(p.encode(&msg, &mut packet).is_err());
+
+        let mut msg_dec = vec![0u8; 16];
+        assert!(p.decode_soft(&packet, &mut msg_dec).is_err());
+    }
+
+    #[test]
+    fn test_packetizer_unknown_scheme() {
+        assert!(
+            Packetizer::create(16, CrcScheme::CRC_32, FecScheme::UNKNOWN, FecScheme::NONE).is_err()
+        );
+    }
+
+    #[test]
+    fn test_packetizer_config() {
+        let config = PacketizerConfig {
+            n: 32,
+            crc: CrcScheme::CRC_16,
+            fec0: FecScheme::HAMMING74,
+            fec1: FecScheme::NONE,
+        };
+        let p = Packetizer::from_config(&config).unwrap();
+        assert_eq!(p.config(), config);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_packetizer_config_serde() {
+        let config = PacketizerConfig {
+            n: 32,
+            crc: CrcScheme::CRC_16,
+            fec0: FecScheme::HAMMING74,
+            fec1: FecScheme::CONV_V29P23,
+        };
+        let json = serde_json::to_string(&config).unwrap();
+        assert_eq!(
+            serde_json::from_str::<PacketizerConfig>(&json).unwrap(),
+            config
+        );
+    }
 }
@@ -0,0 +1,278 @@
+//! Glue between demodulators producing log-likelihood ratios and the soft
+//! decoders of liquid, which take one u8 soft bit per bit (0: strong '0',
+//! 255: strong '1', 127: erasure).
+use crate::errors::FecError;
+use crate::fec::Interleaver;
+use crate::modem::LLR_SOFT_GAIN;
+use crate::LiquidResult;
+
+/// sign convention of the log-likelihood ratios
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub enum LlrConvention {
+    /// LLR = log(P(1)/P(0)), as returned by Modem::demodulate_llr
+    PositiveOne,
+    /// LLR = log(P(0)/P(1)), the usual convention of the literature
+    PositiveZero,
+}
+
+/// mapping of log-likelihood ratios onto liquid soft bits,
+///   soft = 127 + gain * LLR, clipped to [0, 255]
+#[derive(Clone, Copy, Debug)]
+pub struct Llr {
+    gain: f32,
+    saturation: Option<f32>,
+    convention: LlrConvention,
+}
+
+impl Default for Llr {
+    /// the mapping used by liquid's demodulators, LLRs favouring '1'
+    fn default() -> Self {
+        Self {
+            gain: LLR_SOFT_GAIN,
+            saturation: None,
+            convention: LlrConvention::PositiveOne,
+        }
+    }
+}
+
+impl Llr {
+    /// create a mapping
+    ///  gain       :   soft bit units per LLR unit, gain > 0
+    ///  convention :   sign convention of the input LLRs
+    pub fn create(gain: f32, convention: LlrConvention) -> LiquidResult<Self> {
+        if gain <= 0f32 || !gain.is_finite() {
+            return Err(FecError::InvalidValue(format!(
+                "LLR gain: {} must be greater than zero",
+                gain
+            ))
+            .into());
+        }
+        Ok(Self {
+            gain,
+            saturation: None,
+            convention,
+        })
+    }
+
+    /// limit the magnitude of the LLRs before they are mapped, e.g. so that
+    /// a few overconfident bits cannot outweigh a whole codeword
+    ///  saturation :   maximum LLR magnitude, greater than 0, None to only
+    ///                 clip to the soft bit range
+    pub fn set_saturation(&mut self, saturation: Option<f32>) -> LiquidResult<()> {
+        if let Some(s) = saturation {
+            if s <= 0f32 || s.is_nan() {
+                return Err(FecError::InvalidValue(format!(
+                    "LLR saturation: {} must be greater than zero",
+                    s
+                ))
+                .into());
+            }
+        }
+        self.saturation = saturation;
+        Ok(())
+    }
+
+    pub fn get_saturation(&self) -> Option<f32> {
+        self.saturation
+    }
+
+    pub fn get_gain(&self) -> f32 {
+        self.gain
+    }
+
+    pub fn get_convention(&self) -> LlrConvention {
+        self.convention
+    }
+
+    /// map a single LLR onto a soft bit, NaN being an erasure
+    pub fn soft_bit(&self, llr: f32) -> u8 {
+        if llr.is_nan() {
+            return 127;
+        }
+        let mut l = match self.convention {
+            LlrConvention::PositiveOne => llr,
+            LlrConvention::PositiveZero => -llr,
+        };
+        if let Some(s) = self.saturation {
+            l = l.clamp(-s, s);
+        }
+        (127.0 + l * self.gain).round().clamp(0.0, 255.0) as u8
+    }
+
+    /// map a soft bit back onto an LLR in the configured convention
+    pub fn llr(&self, soft_bit: u8) -> f32 {
+        let l = (soft_bit as f32 - 127.0) / self.gain;
+        match self.convention {
+            LlrConvention::PositiveOne => l,
+            LlrConvention::PositiveZero => -l,
+        }
+    }
+
+    /// map a block of LLRs onto soft bits
+    ///  llr        :   LLR input
+    ///  soft_bits  :   soft bits output [size: llr.len() x 1]
+    pub fn to_soft_bits(&self, llr: &[f32], soft_bits: &mut [u8]) -> LiquidResult<()> {
+        check_len(soft_bits.len(), llr.len())?;
+        llr.iter()
+            .zip(soft_bits.iter_mut())
+            .for_each(|(&l, b)| *b = self.soft_bit(l));
+        Ok(())
+    }
+
+    /// map a block of soft bits back onto LLRs
+    ///  soft_bits  :   soft bits input
+    ///  llr        :   LLR output [size: soft_bits.len() x 1]
+    pub fn from_soft_bits(&self, soft_bits: &[u8], llr: &mut [f32]) -> LiquidResult<()> {
+        check_len(llr.len(), soft_bits.len())?;
+        soft_bits
+            .iter()
+            .zip(llr.iter_mut())
+            .for_each(|(&b, l)| *l = self.llr(b));
+        Ok(())
+    }
+}
+
+fn check_len(len: usize, required: usize) -> LiquidResult<()> {
+    if len != required {
+        return Err(FecError::InvalidLength {
+            description: format!("soft bits length: {} valid length: {}", len, required),
+        }
+        .into());
+    }
+    Ok(())
+}
+
+/// maps the LLRs of an interleaved packet onto soft bits and deinterleaves
+/// them, ready for Packetizer::decode_soft
+pub struct SoftDeinterleaver {
+    interleaver: Interleaver,
+    llr: Llr,
+    scratch: Vec<u8>,
+}
+
+impl SoftDeinterleaver {
+    /// create a soft deinterleaver
+    ///  n      :   packet length in bytes, e.g. Packetizer::get_enc_msg_len(), n > 0
+    ///  depth  :   interleaver depth, as set on the transmitter's Interleaver
+    ///  llr    :   mapping of the LLRs onto soft bits
+    pub fn create(n: usize, depth: u32, llr: Llr) -> LiquidResult<Self> {
+        if n == 0 {
+            return Err(FecError::InvalidLength {
+                description: "packet length must be greater than zero".to_owned(),
+            }
+            .into());
+        }
+        let mut interleaver = Interleaver::create(n as _);
+        interleaver.set_depth(depth);
+        Ok(Self {
+            interleaver,
+            llr,
+            scratch: vec![0u8; 8 * n],
+        })
+    }
+
+    /// packet length in bytes
+    pub fn len(&self) -> usize {
+        self.scratch.len() / 8
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.scratch.is_empty()
+    }
+
+    pub fn get_llr(&self) -> &Llr {
+        &self.llr
+    }
+
+    pub fn set_llr(&mut self, llr: Llr) {
+        self.llr = llr;
+    }
+
+    /// deinterleave a packet
+    ///  llr        :   LLRs of the interleaved bits, msb first [size: 8n x 1]
+    ///  soft_bits  :   deinterleaved soft bits [size: 8n x 1]
+    pub fn process(&mut self, llr: &[f32], soft_bits: &mut [u8]) -> LiquidResult<()> {
+        check_len(llr.len(), self.scratch.len())?;
+        check_len(soft_bits.len(), self.scratch.len())?;
+        self.llr.to_soft_bits(llr, &mut self.scratch)?;
+        self.interleaver.decode_soft(&self.scratch, soft_bits);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Llr, LlrConvention, SoftDeinterleaver};
+    use crate::enums::{CrcScheme, FecScheme};
+    use crate::errors::{FecError, LiquidError};
+    use crate::fec::{Interleaver, Packetizer};
+
+    #[test]
+    fn test_llr_mapping() {
+        let llr = Llr::default();
+        assert_eq!(llr.soft_bit(0.0), 127);
+        assert_eq!(llr.soft_bit(100.0), 255);
+        assert_eq!(llr.soft_bit(-100.0), 0);
+        assert_eq!(llr.soft_bit(f32::NAN), 127);
+        assert!((llr.llr(llr.soft_bit(2.0)) - 2.0).abs() < 1.0 / 16.0);
+
+        let mut zero = Llr::create(8.0, LlrConvention::PositiveZero).unwrap();
+        assert!(zero.soft_bit(4.0) < 127);
+        assert!(matches!(
+            zero.set_saturation(Some(0.0)),
+            Err(LiquidError::Fec(FecError::InvalidValue(_)))
+        ));
+        zero.set_saturation(Some(2.0)).unwrap();
+        assert_eq!(zero.soft_bit(-100.0), 127 + 16);
+        assert!(matches!(
+            Llr::create(0.0, LlrConvention::PositiveOne),
+            Err(LiquidError::Fec(FecError::InvalidValue(_)))
+        ));
+
+        let mut soft = [0u8; 3];
+        assert!(llr.to_soft_bits(&[1.0, -1.0], &mut soft).is_err());
+        llr.to_soft_bits(&[1.0, -1.0, 0.0], &mut soft).unwrap();
+        assert_eq!(soft, [143, 111, 127]);
+    }
+
+    #[test]
+    fn test_soft_deinterleaver_packetizer() {
+        let n = 16;
+        let (crc, fec0, fec1) = (CrcScheme::CRC_32, FecScheme::HAMMING74, FecScheme::REP3);
+        let p = Packetizer::create(n as _, crc, fec0, fec1).unwrap();
+        let k = p.get_enc_msg_len();
+        let msg: Vec<u8> = (0..n as u8).map(|i| i.wrapping_mul(37)).collect();
+        let mut packet = vec![0u8; k];
+        p.encode(&msg, &mut packet).unwrap();
+
+        let mut interleaver = Interleaver::create(k as _);
+        interleaver.set_depth(4);
+        let mut interleaved = vec![0u8; k];
+        interleaver.encode(&packet, &mut interleaved);
+
+        // BPSK-like LLRs, favouring '0' when positive, with a few weak
+        // and flipped bits the codes can correct
+        let mut llr: Vec<f32> = interleaved
+            .iter()
+            .flat_map(|&b| {
+                (0..8)
+                    .rev()
+                    .map(move |i| if (b >> i) & 1 == 1 { -4.0 } else { 4.0 })
+            })
+            .collect();
+        for i in (0..llr.len()).step_by(97) {
+            llr[i] *= -0.25;
+        }
+        let mut map = Llr::create(16.0, LlrConvention::PositiveZero).unwrap();
+        map.set_saturation(Some(6.0)).unwrap();
+        let mut deinterleaver = SoftDeinterleaver::create(k, 4, map).unwrap();
+        assert_eq!(deinterleaver.len(), k);
+        let mut soft = vec![0u8; 8 * k];
+        assert!(deinterleaver.process(&llr[1..], &mut soft).is_err());
+        deinterleaver.process(&llr, &mut soft).unwrap();
+
+        let mut decoded = vec![0u8; n];
+        assert!(p.decode_soft(&soft, &mut decoded).unwrap());
+        assert_eq!(decoded, msg);
+    }
+}
@@ -0,0 +1,211 @@
+//! *stream* : splits byte streams of any length into fixed-size blocks,
+//!           each one carrying a small header and protected by a packetizer,
+//!           so the receiving side can check and reassemble them in order
+use crate::enums::{CrcScheme, FecScheme};
+use crate::errors::FecError;
+use crate::fec::Packetizer;
+use crate::LiquidResult;
+
+/// header prepended to the payload of every block:
+/// sequence number (u32), payload length (u16), flags (u8), reserved (u8)
+const HEADER_LEN: usize = 8;
+const FLAG_LAST: u8 = 0x01;
+
+/// a block recovered by FecStream::decode
+#[derive(Clone, Debug)]
+pub struct FecStreamBlock {
+    /// position of the block in the stream
+    pub seq: u32,
+    /// true for the block that closes the stream
+    pub last: bool,
+    /// true if the CRC of the block passed; the other fields of
+    /// an invalid block can not be trusted
+    pub valid: bool,
+    pub data: Vec<u8>,
+}
+
+/// chunked encoder/decoder on top of the packetizer, e.g. for
+/// sending files over a simulated channel
+pub struct FecStream {
+    packetizer: Packetizer,
+    block_size: usize,
+    pending: Vec<u8>,
+    seq: u32,
+}
+
+impl FecStream {
+    /// create a stream coder
+    ///  block_size :   payload bytes per block, in [1, 65535]
+    ///  crc        :   error-detection scheme of each block
+    ///  fec0       :   inner forward error-correction scheme
+    ///  fec1       :   outer forward error-correction scheme
+    pub fn create(
+        block_size: usize,
+        crc: CrcScheme,
+        fec0: FecScheme,
+        fec1: FecScheme,
+    ) -> LiquidResult<Self> {
+        if block_size == 0 || block_size > u16::MAX as usize {
+            return Err(FecError::InvalidLength {
+                description: "block size must be in [1, 65535]".to_owned(),
+            }
+            .into());
+        } else if crc == CrcScheme::CRC_UNKNOWN {
+            return Err(FecError::InvalidCrcScheme.into());
+        }
+        let packetizer = Packetizer::create((HEADER_LEN + block_size) as u32, crc, fec0, fec1)?;
+        Ok(Self {
+            packetizer,
+            block_size,
+            pending: Vec::with_capacity(block_size),
+            seq: 0,
+        })
+    }
+
+    /// payload bytes per block
+    pub fn get_block_size(&self) -> usize {
+        self.block_size
+    }
+
+    /// length of every encoded block, in bytes
+    pub fn get_enc_block_len(&self) -> usize {
+        self.packetizer.get_enc_msg_len()
+    }
+
+    /// append data to the stream
+    /// # returns
+    /// the blocks completed by this data, bytes that do not fill a
+    /// block are kept until the next call or finish()
+    pub fn encode(&mut self, data: &[u8]) -> LiquidResult<Vec<Vec<u8>>> {
+        let mut blocks = Vec::new();
+        let mut data = data;
+        while !data.is_empty() {
+            let n = (self.block_size - self.pending.len()).min(data.len());
+            self.pending.extend_from_slice(&data[..n]);
+            data = &data[n..];
+            if self.pending.len() == self.block_size {
+                blocks.push(self.encode_pending(false)?);
+            }
+        }
+        Ok(blocks)
+    }
+
+    /// close the stream
+    /// # returns
+    /// the last block, holding the remaining bytes (possibly none); the
+    /// next call to encode() starts a new stream
+    pub fn finish(&mut self) -> LiquidResult<Vec<u8>> {
+        let block = self.encode_pending(true)?;
+        self.seq = 0;
+        Ok(block)
+    }
+
+    fn encode_pending(&mut self, last: bool) -> LiquidResult<Vec<u8>> {
+        let mut msg = vec![0u8; HEADER_LEN + self.block_size];
+        msg[0..4].copy_from_slice(&self.seq.to_le_bytes());
+        msg[4..6].copy_from_slice(&(self.pending.len() as u16).to_le_bytes());
+        msg[6] = if last { FLAG_LAST } else { 0 };
+        msg[HEADER_LEN..HEADER_LEN + self.pending.len()].copy_from_slice(&self.pending);
+
+        let mut block = vec![0u8; self.get_enc_block_len()];
+        self.packetizer.encode(&msg, &mut block)?;
+        self.pending.clear();
+        self.seq = self.seq.wrapping_add(1);
+        Ok(block)
+    }
+
+    /// decode a single block
+    ///  block  :   encoded block [size: get_enc_block_len() x 1]
+    pub fn decode(&self, block: &[u8]) -> LiquidResult<FecStreamBlock> {
+        let mut msg = vec![0u8; HEADER_LEN + self.block_size];
+        let valid = self.packetizer.decode(block, &mut msg)?;
+        let seq = u32::from_le_bytes([msg[0], msg[1], msg[2], msg[3]]);
+        let len = (u16::from_le_bytes([msg[4], msg[5]]) as usize).min(self.block_size);
+        Ok(FecStreamBlock {
+            seq,
+            last: msg[6] & FLAG_LAST != 0,
+            valid,
+            data: msg[HEADER_LEN..HEADER_LEN + len].to_vec(),
+        })
+    }
+
+    /// decode the blocks of a whole stream, in any order, and concatenate
+    /// their payloads
+    /// # returns
+    /// an error if a block is lost or corrupted, or the last block is missing
+    pub fn decode_stream<'a, I>(&self, blocks: I) -> LiquidResult<Vec<u8>>
+    where
+        I: IntoIterator<Item = &'a [u8]>,
+    {
+        let mut decoded = Vec::new();
+        for block in blocks {
+            let block = self.decode(block)?;
+            if block.valid {
+                decoded.push(block);
+            }
+        }
+        decoded.sort_by_key(|b| b.seq);
+        decoded.dedup_by_key(|b| b.seq);
+
+        let mut data = Vec::new();
+        for (i, block) in decoded.iter().enumerate() {
+            if block.seq != i as u32 {
+                return Err(FecError::InvalidLength {
+                    description: format!("block {} of the stream is missing or corrupted", i),
+                }
+                .into());
+            }
+            data.extend_from_slice(&block.data);
+            if block.last {
+                return Ok(data);
+            }
+        }
+        Err(FecError::InvalidLength {
+            description: format!(
+                "stream is incomplete, block {} is missing or corrupted",
+                decoded.len()
+            ),
+        }
+        .into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FecStream;
+    use crate::enums::{CrcScheme, FecScheme};
+
+    #[test]
+    fn test_fec_stream_round_trip() {
+        let mut stream =
+            FecStream::create(16, CrcScheme::CRC_32, FecScheme::HAMMING74, FecScheme::NONE)
+                .unwrap();
+        let data: Vec<u8> = (0..100).map(|i| i as u8).collect();
+
+        let mut blocks = stream.encode(&data[..40]).unwrap();
+        blocks.extend(stream.encode(&data[40..]).unwrap());
+        blocks.push(stream.finish().unwrap());
+        assert_eq!(blocks.len(), 7);
+        assert!(blocks.iter().all(|b| b.len() == stream.get_enc_block_len()));
+
+        blocks.swap(1, 4);
+        let decoded = stream
+            .decode_stream(blocks.iter().map(|b| b.as_slice()))
+            .unwrap();
+        assert_eq!(decoded, data);
+
+        blocks.remove(2);
+        assert!(stream
+            .decode_stream(blocks.iter().map(|b| b.as_slice()))
+            .is_err());
+    }
+
+    #[test]
+    fn test_fec_stream_invalid_block_size() {
+        assert!(FecStream::create(0, CrcScheme::CRC_32, FecScheme::NONE, FecScheme::NONE).is_err());
+        assert!(
+            FecStream::create(1 << 16, CrcScheme::CRC_32, FecScheme::NONE, FecScheme::NONE)
+                .is_err()
+        );
+    }
+}
@@ -3,7 +3,7 @@ use std::str;
 
 use num::complex::Complex32;
 
-use crate::errors::LiquidError;
+use crate::errors::{FftError, LiquidError};
 use crate::liquid_dsp_sys as raw;
 use crate::utils::{ToCPointer, ToCPointerMut, ToCValue};
 
@@ -30,10 +30,9 @@ macro_rules! asgram_xxx_impl {
         impl $obj {
             pub fn create(nfft: u32) -> Result<Self, LiquidError> {
                 if nfft < 2 {
-                    return Err(LiquidError::InvalidValue(format!(
-                        "nfft size must be at least {}",
-                        2
-                    )));
+                    return Err(
+                        FftError::InvalidSize(format!("nfft size must be at least {}", 2)).into(),
+                    );
                 }
                 Ok(Self {
                     inner: unsafe { $create(nfft as _) },
@@ -0,0 +1,182 @@
+//! *asgram_stream* : drives an ASCII spectrogram from a stream of sample
+//!           slices of any length, producing one line every period samples
+use num::complex::Complex32;
+
+use crate::errors::FftError;
+use crate::fft::{AsgramCf, AsgramRf};
+use crate::LiquidResult;
+
+/// ASCII spectrograms that can be driven by an AsgramStream
+pub trait Asgram {
+    type Sample: Copy;
+
+    /// write samples into the spectrogram buffer
+    fn write_samples(&mut self, x: &[Self::Sample]);
+
+    /// compute a line from the current buffer contents
+    /// # returns
+    /// the line, the peak value and the frequency of the peak
+    fn execute_line(&mut self) -> (&str, f32, f32);
+}
+
+impl Asgram for AsgramCf {
+    type Sample = Complex32;
+
+    fn write_samples(&mut self, x: &[Complex32]) {
+        self.write(x);
+    }
+
+    fn execute_line(&mut self) -> (&str, f32, f32) {
+        self.execute()
+    }
+}
+
+impl Asgram for AsgramRf {
+    type Sample = f32;
+
+    fn write_samples(&mut self, x: &[f32]) {
+        self.write(x);
+    }
+
+    fn execute_line(&mut self) -> (&str, f32, f32) {
+        self.execute()
+    }
+}
+
+/// one line of the spectrogram
+#[derive(Clone, Debug)]
+pub struct AsgramFrame {
+    /// time of the last sample of the frame [s], counted from the first
+    /// sample written to the stream
+    pub timestamp: f64,
+    pub line: String,
+    /// value at the peak [dB]
+    pub peak: f32,
+    /// frequency of the peak, normalized to [-0.5, 0.5]
+    pub peak_freq: f32,
+}
+
+/// feeds an asgram with slices of any length and computes a line every
+/// period samples, e.g. for a terminal spectrum monitor
+pub struct AsgramStream<A: Asgram> {
+    asgram: A,
+    period: usize,
+    sample_rate: f64,
+    pending: usize,
+    num_samples: u64,
+}
+
+impl<A: Asgram> AsgramStream<A> {
+    /// create a stream
+    ///  asgram      :   configured spectrogram
+    ///  period      :   number of samples between lines, period > 0
+    ///  sample_rate :   sample rate [Hz] used for the timestamps, sample_rate > 0
+    pub fn new(asgram: A, period: usize, sample_rate: f64) -> LiquidResult<Self> {
+        if period == 0 {
+            return Err(
+                FftError::InvalidValue("period must be greater than zero".to_owned()).into(),
+            );
+        } else if sample_rate <= 0.0 {
+            return Err(
+                FftError::InvalidValue("sample rate must be greater than zero".to_owned()).into(),
+            );
+        }
+        Ok(Self {
+            asgram,
+            period,
+            sample_rate,
+            pending: 0,
+            num_samples: 0,
+        })
+    }
+
+    /// write samples, invoking f with each line completed by them
+    pub fn write_with<F>(&mut self, x: &[A::Sample], mut f: F)
+    where
+        F: FnMut(AsgramFrame),
+    {
+        let mut x = x;
+        while !x.is_empty() {
+            let n = (self.period - self.pending).min(x.len());
+            self.asgram.write_samples(&x[..n]);
+            self.pending += n;
+            self.num_samples += n as u64;
+            x = &x[n..];
+            if self.pending == self.period {
+                self.pending = 0;
+                let timestamp = (self.num_samples - 1) as f64 / self.sample_rate;
+                let (line, peak, peak_freq) = self.asgram.execute_line();
+                f(AsgramFrame {
+                    timestamp,
+                    line: line.trim_end_matches(['\0', '\n']).to_owned(),
+                    peak,
+                    peak_freq,
+                });
+            }
+        }
+    }
+
+    /// write samples
+    /// # returns
+    /// the lines completed by the samples
+    pub fn write(&mut self, x: &[A::Sample]) -> Vec<AsgramFrame> {
+        let mut frames = Vec::new();
+        self.write_with(x, |frame| frames.push(frame));
+        frames
+    }
+
+    /// number of samples written since the stream was created
+    pub fn get_num_samples(&self) -> u64 {
+        self.num_samples
+    }
+
+    pub fn get_ref(&self) -> &A {
+        &self.asgram
+    }
+
+    pub fn get_mut(&mut self) -> &mut A {
+        &mut self.asgram
+    }
+
+    pub fn into_inner(self) -> A {
+        self.asgram
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Asgram, AsgramStream};
+
+    /// records the size of each write and fakes a line on execute
+    struct Probe {
+        writes: Vec<usize>,
+    }
+
+    impl Asgram for Probe {
+        type Sample = f32;
+
+        fn write_samples(&mut self, x: &[f32]) {
+            self.writes.push(x.len());
+        }
+
+        fn execute_line(&mut self) -> (&str, f32, f32) {
+            ("..++##\0", -10.0, 0.25)
+        }
+    }
+
+    #[test]
+    fn test_asgram_stream_cadence() {
+        let probe = Probe { writes: Vec::new() };
+        let mut stream = AsgramStream::new(probe, 100, 1000.0).unwrap();
+        assert!(stream.write(&[0f32; 60]).is_empty());
+        let frames = stream.write(&[0f32; 250]);
+        assert_eq!(frames.len(), 3);
+        assert_eq!(frames[0].line, "..++##");
+        assert!((frames[0].timestamp - 0.099).abs() < 1e-9);
+        assert!((frames[2].timestamp - 0.299).abs() < 1e-9);
+        assert_eq!(stream.get_num_samples(), 310);
+        assert_eq!(stream.get_ref().writes, [60, 40, 100, 100, 10]);
+
+        assert!(AsgramStream::new(Probe { writes: Vec::new() }, 0, 1000.0).is_err());
+    }
+}
@@ -1,8 +1,10 @@
 use num::complex::Complex32;
 
-use crate::enums::FftType;
+use crate::enums::{FftType, WindowType};
+use crate::errors::FftError;
 use crate::liquid_dsp_sys as raw;
 use crate::utils::{ToCPointer, ToCPointerMut};
+use crate::LiquidResult;
 
 pub struct Fft {}
 
@@ -46,4 +48,94 @@ impl Fft {
             raw::fft_shift(x.to_ptr_mut(), x.len() as _);
         }
     }
+
+    /// swap the halves of a real spectrum so the zero frequency is at the
+    /// center, the real-valued counterpart of Fft::shift
+    pub fn shift_real(x: &mut [f32]) {
+        let n = x.len();
+        x.rotate_right(n / 2);
+    }
+
+    /// perform n-point inverse FFT scaled by 1/n, so that it undoes
+    /// Fft::run with FftType::FORWARD
+    ///  x      :   x array [size: n]
+    ///  y      :   y array [size: n]
+    pub fn inverse_normalized(x: &[Complex32], y: &mut [Complex32]) -> LiquidResult<()> {
+        if x.len() != y.len() || x.is_empty() {
+            return Err(FftError::InvalidSize(format!(
+                "input length: {} output length: {}, must be equal and non zero",
+                x.len(),
+                y.len()
+            ))
+            .into());
+        }
+        Self::run(x, y, FftType::BACKWARD);
+        let n = x.len() as f32;
+        y.iter_mut().for_each(|v| *v /= n);
+        Ok(())
+    }
+
+    /// estimate the power spectral density of a buffer with Welch's method:
+    /// segments of nfft samples overlapping by half are windowed,
+    /// transformed and their squared magnitudes averaged
+    ///  x      :   input samples, at least nfft
+    ///  nfft   :   FFT size, at least 2, even for WindowType::KBD
+    ///  window :   window applied to each segment, e.g. WindowType::HANN
+    /// # returns
+    /// the psd [dB] with the zero frequency at the center [size: nfft x 1]
+    pub fn psd(x: &[Complex32], nfft: usize, window: WindowType) -> LiquidResult<Vec<f32>> {
+        if nfft < 2 {
+            return Err(FftError::InvalidSize("nfft must be at least 2".to_owned()).into());
+        } else if x.len() < nfft {
+            return Err(FftError::InvalidSize(format!(
+                "input length: {} shorter than nfft: {}",
+                x.len(),
+                nfft
+            ))
+            .into());
+        } else if window == WindowType::KBD && !nfft.is_multiple_of(2) {
+            return Err(FftError::InvalidSize("KBD window length must be even".to_owned()).into());
+        } else if window == WindowType::UNKNOWN {
+            return Err(FftError::InvalidType.into());
+        }
+        let mut psd = vec![0f32; nfft];
+        unsafe {
+            let q = raw::spgramcf_create(
+                nfft as _,
+                u8::from(window) as _,
+                nfft as _,
+                (nfft / 2).max(1) as _,
+            );
+            raw::spgramcf_write(q, x.to_ptr() as _, x.len() as _);
+            raw::spgramcf_get_psd(q, psd.as_mut_ptr());
+            raw::spgramcf_destroy(q);
+        }
+        Ok(psd)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Fft;
+    use crate::enums::WindowType;
+    use num::complex::Complex32;
+
+    #[test]
+    fn test_shift_real() {
+        let mut x = [0f32, 1.0, 2.0, 3.0, -4.0, -3.0, -2.0, -1.0];
+        Fft::shift_real(&mut x);
+        assert_eq!(x, [-4.0, -3.0, -2.0, -1.0, 0.0, 1.0, 2.0, 3.0]);
+        let mut x = [0f32, 1.0, 2.0, -2.0, -1.0];
+        Fft::shift_real(&mut x);
+        assert_eq!(x, [-2.0, -1.0, 0.0, 1.0, 2.0]);
+    }
+
+    #[test]
+    fn test_psd_validation() {
+        let x = vec![Complex32::new(1.0, 0.0); 64];
+        assert!(Fft::psd(&x, 128, WindowType::HANN).is_err());
+        assert!(Fft::psd(&x, 1, WindowType::HANN).is_err());
+        assert!(Fft::psd(&x, 15, WindowType::KBD).is_err());
+        assert_eq!(Fft::psd(&x, 16, WindowType::HANN).unwrap().len(), 16);
+    }
 }
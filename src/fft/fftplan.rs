@@ -1,40 +1,106 @@
-use std::marker::PhantomData;
-
 use num::complex::Complex32;
 
 use crate::liquid_dsp_sys as raw;
 
-use crate::enums::FftType;
-use crate::utils::{ToCPointer, ToCPointerMut};
+use crate::enums::{FftType, FftwFlags};
+use crate::errors::FftError;
+use crate::fft::fftw;
+use crate::utils::ToCPointerMut;
+use crate::LiquidResult;
+
+/// buffers owned by the plan; liquid keeps pointers to them, so they are
+/// allocated once and never resized
+enum Buffers {
+    Complex(Vec<Complex32>, Vec<Complex32>),
+    Real(Vec<f32>, Vec<f32>),
+}
+
+/// plan-time options of an FftPlan
+///
+/// The options are handed to liquid's fft_create_plan(), which in
+/// libliquid 1.3.2 ignores its flags argument: with FFTW the plans are
+/// always created with FFTW_ESTIMATE, so the options currently have no
+/// effect on the plan.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct FftOptions {
+    /// FFTW planner flags, e.g. FftwFlags::PATIENT, see above
+    pub flags: FftwFlags,
+}
 
-pub struct FftPlan<'a> {
+/// FFT plan owning its input and output buffers, so one plan can
+/// transform any number of blocks of the same size
+pub struct FftPlan {
     inner: raw::fftplan,
-    data: PhantomData<&'a Complex32>,
+    kind: FftType,
+    normalize: bool,
+    buffers: Buffers,
 }
 
-impl<'a> FftPlan<'a> {
-    pub fn create(
-        x: &'a [Complex32],
-        y: &'a mut [Complex32],
-        direction: FftType,
-    ) -> Result<Self, &'static str> {
-        assert!(x.len() == y.len(), "x/y buffers must have the same size");
-        if direction == FftType::FORWARD || direction == FftType::BACKWARD {
-            unsafe {
-                return Ok(Self {
-                    inner: raw::fft_create_plan(
-                        x.len() as _,
-                        x.to_ptr() as _,
-                        y.to_ptr_mut(),
-                        i8::from(direction) as _,
-                        0,
-                    ),
-                    data: PhantomData,
-                });
-            }
+impl FftPlan {
+    /// create a plan of n points
+    ///  n      :   transform size
+    ///  kind   :   FftType::FORWARD or FftType::BACKWARD for complex
+    ///             transforms, one of the REDFT/RODFT types for real-to-real
+    ///             transforms
+    pub fn create(n: usize, kind: FftType) -> LiquidResult<Self> {
+        Self::create_with_options(n, kind, &FftOptions::default())
+    }
+
+    /// create a plan of n points with explicit planner options
+    ///  n      :   transform size
+    ///  kind   :   transform type, see create()
+    ///  opts   :   planner options, ignored by libliquid 1.3.2
+    pub fn create_with_options(n: usize, kind: FftType, opts: &FftOptions) -> LiquidResult<Self> {
+        if n == 0 {
+            return Err(
+                FftError::InvalidSize("fft size must be greater than zero".to_owned()).into(),
+            );
         }
-        // TODO: check if this is really needed
-        Err("Either FftType::FORWARD or FftType::BACKWARD are the only valid values for direction")
+        let mut buffers = match kind {
+            FftType::FORWARD | FftType::BACKWARD => {
+                Buffers::Complex(vec![Complex32::default(); n], vec![Complex32::default(); n])
+            }
+            FftType::REDFT00 | FftType::RODFT00 if n < 2 => {
+                return Err(FftError::InvalidSize(
+                    "fft size must be at least 2 for REDFT00 and RODFT00".to_owned(),
+                )
+                .into());
+            }
+            FftType::REDFT00
+            | FftType::REDFT10
+            | FftType::REDFT01
+            | FftType::REDFT11
+            | FftType::RODFT00
+            | FftType::RODFT10
+            | FftType::RODFT01
+            | FftType::RODFT11 => Buffers::Real(vec![0f32; n], vec![0f32; n]),
+            _ => return Err(FftError::InvalidType.into()),
+        };
+        let _planner = fftw::planner_lock();
+        let inner = unsafe {
+            match buffers {
+                Buffers::Complex(ref mut x, ref mut y) => raw::fft_create_plan(
+                    n as _,
+                    x.to_ptr_mut(),
+                    y.to_ptr_mut(),
+                    i8::from(kind) as _,
+                    opts.flags.bits() as _,
+                ),
+                Buffers::Real(ref mut x, ref mut y) => raw::fft_create_plan_r2r_1d(
+                    n as _,
+                    x.as_mut_ptr(),
+                    y.as_mut_ptr(),
+                    i8::from(kind) as _,
+                    opts.flags.bits() as _,
+                ),
+            }
+        };
+        Ok(Self {
+            inner,
+            kind,
+            normalize: false,
+            buffers,
+        })
     }
 
     pub fn print(&self) {
@@ -43,17 +109,224 @@ impl<'a> FftPlan<'a> {
         }
     }
 
-    pub fn execute(&self) {
+    /// transform size
+    pub fn len(&self) -> usize {
+        match self.buffers {
+            Buffers::Complex(ref x, _) => x.len(),
+            Buffers::Real(ref x, _) => x.len(),
+        }
+    }
+
+    /// true if the plan has no points, which create() never returns
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn get_type(&self) -> FftType {
+        self.kind
+    }
+
+    /// true if liquid runs this plan with its radix-2 algorithm, i.e. a
+    /// complex transform whose size is a power of two. Padding blocks to
+    /// such sizes gives the fastest plans.
+    pub fn is_radix2(&self) -> bool {
+        matches!(self.buffers, Buffers::Complex(..)) && self.len().is_power_of_two()
+    }
+
+    /// scale the output of the inverse transforms (BACKWARD, REDFT01 and
+    /// RODFT01) so that a forward transform followed by its inverse returns
+    /// the original block; disabled by default, as in liquid
+    pub fn set_normalization(&mut self, normalize: bool) {
+        self.normalize = normalize;
+    }
+
+    pub fn get_normalization(&self) -> bool {
+        self.normalize
+    }
+
+    /// input buffer of a complex plan
+    pub fn input_mut(&mut self) -> LiquidResult<&mut [Complex32]> {
+        match self.buffers {
+            Buffers::Complex(ref mut x, _) => Ok(x),
+            Buffers::Real(..) => Err(FftError::InvalidType.into()),
+        }
+    }
+
+    /// output buffer of a complex plan
+    pub fn output(&self) -> LiquidResult<&[Complex32]> {
+        match self.buffers {
+            Buffers::Complex(_, ref y) => Ok(y),
+            Buffers::Real(..) => Err(FftError::InvalidType.into()),
+        }
+    }
+
+    /// input buffer of a real-to-real plan
+    pub fn input_real_mut(&mut self) -> LiquidResult<&mut [f32]> {
+        match self.buffers {
+            Buffers::Real(ref mut x, _) => Ok(x),
+            Buffers::Complex(..) => Err(FftError::InvalidType.into()),
+        }
+    }
+
+    /// output buffer of a real-to-real plan
+    pub fn output_real(&self) -> LiquidResult<&[f32]> {
+        match self.buffers {
+            Buffers::Real(_, ref y) => Ok(y),
+            Buffers::Complex(..) => Err(FftError::InvalidType.into()),
+        }
+    }
+
+    /// transform the input buffer into the output buffer
+    pub fn execute(&mut self) {
         unsafe {
             raw::fft_execute(self.inner);
         }
+        if !self.normalize {
+            return;
+        }
+        let n = self.len() as f32;
+        match (self.kind, &mut self.buffers) {
+            (FftType::BACKWARD, Buffers::Complex(_, y)) => y.iter_mut().for_each(|v| *v /= n),
+            (FftType::REDFT01, Buffers::Real(_, y)) | (FftType::RODFT01, Buffers::Real(_, y)) => {
+                y.iter_mut().for_each(|v| *v /= 2.0 * n)
+            }
+            _ => {}
+        }
+    }
+
+    /// transform a block of a complex plan
+    ///  x      :   input block [size: len x 1]
+    ///  y      :   output block [size: len x 1]
+    pub fn execute_into(&mut self, x: &[Complex32], y: &mut [Complex32]) -> LiquidResult<()> {
+        self.check_len(x.len(), y.len())?;
+        self.input_mut()?.copy_from_slice(x);
+        self.execute();
+        y.copy_from_slice(self.output()?);
+        Ok(())
+    }
+
+    /// transform a block of a real-to-real plan
+    ///  x      :   input block [size: len x 1]
+    ///  y      :   output block [size: len x 1]
+    pub fn execute_r2r_into(&mut self, x: &[f32], y: &mut [f32]) -> LiquidResult<()> {
+        self.check_len(x.len(), y.len())?;
+        self.input_real_mut()?.copy_from_slice(x);
+        self.execute();
+        y.copy_from_slice(self.output_real()?);
+        Ok(())
+    }
+
+    fn check_len(&self, x_len: usize, y_len: usize) -> LiquidResult<()> {
+        if x_len != self.len() || y_len != self.len() {
+            return Err(FftError::InvalidSize(format!(
+                "input length: {} output length: {} plan size: {}",
+                x_len,
+                y_len,
+                self.len()
+            ))
+            .into());
+        }
+        Ok(())
     }
 }
 
-impl<'a> Drop for FftPlan<'a> {
+impl Drop for FftPlan {
     fn drop(&mut self) {
+        let _planner = fftw::planner_lock();
         unsafe {
             raw::fft_destroy_plan(self.inner);
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{FftOptions, FftPlan};
+    use crate::enums::{FftType, FftwFlags};
+    use num::complex::Complex32;
+
+    #[test]
+    fn test_fftplan_buffers() {
+        let mut plan = FftPlan::create(8, FftType::FORWARD).unwrap();
+        assert_eq!(plan.len(), 8);
+        assert!(plan.is_radix2());
+        assert!(plan.input_real_mut().is_err());
+        let x = vec![Complex32::new(1.0, 0.0); 8];
+        let mut y = vec![Complex32::default(); 4];
+        assert!(plan.execute_into(&x, &mut y).is_err());
+
+        let plan = FftPlan::create(6, FftType::REDFT10).unwrap();
+        assert!(!plan.is_radix2());
+        assert!(plan.output().is_err());
+
+        assert!(FftPlan::create(8, FftType::MDCT).is_err());
+        assert!(FftPlan::create(0, FftType::FORWARD).is_err());
+
+        let opts = FftOptions {
+            flags: FftwFlags::ESTIMATE | FftwFlags::PRESERVE_INPUT,
+        };
+        let plan = FftPlan::create_with_options(16, FftType::BACKWARD, &opts).unwrap();
+        assert_eq!(plan.len(), 16);
+        assert_eq!(FftOptions::default().flags, FftwFlags::MEASURE);
+    }
+
+    #[test]
+    fn test_fftplan_round_trip() {
+        let n = 16;
+        let mut state = 1u32;
+        let x: Vec<Complex32> = (0..n)
+            .map(|_| {
+                state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+                let re = ((state >> 16) & 0xff) as f32 / 128.0 - 1.0;
+                state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+                let im = ((state >> 16) & 0xff) as f32 / 128.0 - 1.0;
+                Complex32::new(re, im)
+            })
+            .collect();
+
+        let mut fwd = FftPlan::create(n, FftType::FORWARD).unwrap();
+        assert!(!fwd.is_empty());
+        // the transform of a unit impulse is flat
+        let mut impulse = vec![Complex32::default(); n];
+        impulse[0] = Complex32::new(1.0, 0.0);
+        let mut y = vec![Complex32::default(); n];
+        fwd.execute_into(&impulse, &mut y).unwrap();
+        assert!(y
+            .iter()
+            .all(|v| (v - Complex32::new(1.0, 0.0)).norm() < 1e-6));
+        // bin 0 is the sum of the block
+        fwd.execute_into(&x, &mut y).unwrap();
+        assert!((y[0] - x.iter().sum::<Complex32>()).norm() < 1e-4);
+
+        let mut bwd = FftPlan::create(n, FftType::BACKWARD).unwrap();
+        bwd.set_normalization(true);
+        assert!(bwd.get_normalization());
+        let mut z = vec![Complex32::default(); n];
+        bwd.execute_into(&y, &mut z).unwrap();
+        for (a, b) in z.iter().zip(x.iter()) {
+            assert!((a - b).norm() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_fftplan_r2r_round_trip() {
+        let n = 12;
+        let x: Vec<f32> = (0..n).map(|i| ((i * 7) % 5) as f32 - 2.0).collect();
+
+        let mut dct = FftPlan::create(n, FftType::REDFT10).unwrap();
+        // REDFT10 of a constant only has a DC term of 2*n
+        let mut y = vec![0f32; n];
+        dct.execute_r2r_into(&vec![1.0; n], &mut y).unwrap();
+        assert!((y[0] - 2.0 * n as f32).abs() < 1e-4);
+        assert!(y[1..].iter().all(|v| v.abs() < 1e-4));
+
+        dct.execute_r2r_into(&x, &mut y).unwrap();
+        let mut idct = FftPlan::create(n, FftType::REDFT01).unwrap();
+        idct.set_normalization(true);
+        let mut z = vec![0f32; n];
+        idct.execute_r2r_into(&y, &mut z).unwrap();
+        for (a, b) in z.iter().zip(x.iter()) {
+            assert!((a - b).abs() < 1e-5);
+        }
+    }
+}
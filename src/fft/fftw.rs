@@ -0,0 +1,228 @@
+//! *fftw* : wisdom and threading of FFTW, for a libliquid built against it
+//!
+//! The bindings do not link FFTW themselves, so its functions are looked up
+//! among the symbols already loaded in the process, the same probe used by
+//! has_fftw(). Every function returns FftError::FftwUnavailable when the
+//! symbol is missing, e.g. when liquid uses its own transforms or, for
+//! set_threads(), when libfftw3f_threads is not loaded.
+//!
+//! Wisdom only helps plans created after it is imported; a typical program
+//! imports it at start-up and exports the updated wisdom before exiting.
+//! Note that libliquid 1.3.2 plans with FFTW_ESTIMATE, which neither uses
+//! nor accumulates measured wisdom.
+//!
+//! The FFTW planner is not thread-safe: the functions of this module and
+//! the creation and destruction of FftPlan objects are serialized by a
+//! process wide lock. Objects creating their own plans inside liquid
+//! (e.g. SpgramCf, FftFiltCccf) do not take it.
+use std::ffi::{CStr, CString};
+use std::os::raw::{c_char, c_int, c_void};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, MutexGuard, Once};
+
+use crate::errors::{FftError, LiquidError};
+use crate::LiquidResult;
+
+#[cfg(unix)]
+fn lookup(name: &str) -> LiquidResult<*mut c_void> {
+    let symbol = CString::new(name).expect("symbol names have no nul bytes");
+    let f = unsafe { libc::dlsym(libc::RTLD_DEFAULT, symbol.as_ptr()) };
+    if f.is_null() {
+        return Err(unavailable(name));
+    }
+    Ok(f)
+}
+
+#[cfg(not(unix))]
+fn lookup(name: &str) -> LiquidResult<*mut c_void> {
+    Err(unavailable(name))
+}
+
+/// serializes the calls into the FFTW planner
+static PLANNER: Mutex<()> = Mutex::new(());
+
+pub(crate) fn planner_lock() -> MutexGuard<'static, ()> {
+    // nothing is left half done by a panic while holding the lock
+    PLANNER.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn unavailable(name: &str) -> LiquidError {
+    FftError::FftwUnavailable(format!("{} is not available, FFTW is not loaded", name)).into()
+}
+
+// look up an FFTW function as a function pointer of the given type
+macro_rules! fftw_fn {
+    ($name:expr, $ty:ty) => {
+        lookup($name).map(|f| unsafe { std::mem::transmute::<*mut c_void, $ty>(f) })
+    };
+}
+
+fn path_to_cstring(path: &Path) -> LiquidResult<CString> {
+    path.to_str()
+        .and_then(|p| CString::new(p).ok())
+        .ok_or_else(|| {
+            FftError::InvalidValue(format!(
+                "path: {} is not valid UTF-8 or contains nul bytes",
+                path.display()
+            ))
+            .into()
+        })
+}
+
+/// merge the wisdom stored in a file into the current wisdom
+pub fn import_wisdom_from_file<P: AsRef<Path>>(path: P) -> LiquidResult<()> {
+    let f = fftw_fn!(
+        "fftwf_import_wisdom_from_filename",
+        unsafe extern "C" fn(*const c_char) -> c_int
+    )?;
+    let c = path_to_cstring(path.as_ref())?;
+    let _planner = planner_lock();
+    if unsafe { f(c.as_ptr()) } == 0 {
+        return Err(FftError::InvalidValue(format!(
+            "could not import wisdom from {}",
+            path.as_ref().display()
+        ))
+        .into());
+    }
+    Ok(())
+}
+
+/// save the current wisdom to a file
+pub fn export_wisdom_to_file<P: AsRef<Path>>(path: P) -> LiquidResult<()> {
+    let f = fftw_fn!(
+        "fftwf_export_wisdom_to_filename",
+        unsafe extern "C" fn(*const c_char) -> c_int
+    )?;
+    let c = path_to_cstring(path.as_ref())?;
+    let _planner = planner_lock();
+    if unsafe { f(c.as_ptr()) } == 0 {
+        return Err(FftError::InvalidValue(format!(
+            "could not export wisdom to {}",
+            path.as_ref().display()
+        ))
+        .into());
+    }
+    Ok(())
+}
+
+/// merge wisdom, as returned by export_wisdom(), into the current wisdom
+pub fn import_wisdom(wisdom: &str) -> LiquidResult<()> {
+    let f = fftw_fn!(
+        "fftwf_import_wisdom_from_string",
+        unsafe extern "C" fn(*const c_char) -> c_int
+    )?;
+    let c = CString::new(wisdom)
+        .map_err(|_| FftError::InvalidValue("wisdom cannot contain nul bytes".to_owned()))?;
+    let _planner = planner_lock();
+    if unsafe { f(c.as_ptr()) } == 0 {
+        return Err(FftError::InvalidValue("could not parse wisdom".to_owned()).into());
+    }
+    Ok(())
+}
+
+/// current wisdom as a string
+pub fn export_wisdom() -> LiquidResult<String> {
+    let f = fftw_fn!(
+        "fftwf_export_wisdom_to_string",
+        unsafe extern "C" fn() -> *mut c_char
+    )?;
+    let _planner = planner_lock();
+    unsafe {
+        let s = f();
+        if s.is_null() {
+            return Err(FftError::InvalidValue("could not export wisdom".to_owned()).into());
+        }
+        let wisdom = CStr::from_ptr(s).to_string_lossy().into_owned();
+        // the string is allocated by FFTW with malloc()
+        libc::free(s as *mut c_void);
+        Ok(wisdom)
+    }
+}
+
+/// drop all the accumulated wisdom
+pub fn forget_wisdom() -> LiquidResult<()> {
+    let f = fftw_fn!("fftwf_forget_wisdom", unsafe extern "C" fn())?;
+    let _planner = planner_lock();
+    unsafe { f() };
+    Ok(())
+}
+
+/// number of threads used by the plans created from now on
+///  n      :   number of threads, n > 0
+pub fn set_threads(n: usize) -> LiquidResult<()> {
+    static INIT: Once = Once::new();
+    static THREADS_OK: AtomicBool = AtomicBool::new(false);
+    if n == 0 {
+        return Err(
+            FftError::InvalidValue("number of threads must be greater than 0".to_owned()).into(),
+        );
+    }
+    let init = fftw_fn!("fftwf_init_threads", unsafe extern "C" fn() -> c_int)?;
+    let plan_with = fftw_fn!("fftwf_plan_with_nthreads", unsafe extern "C" fn(c_int))?;
+    let _planner = planner_lock();
+    // fftwf_init_threads() is only called once per process
+    INIT.call_once(|| THREADS_OK.store(unsafe { init() } != 0, Ordering::SeqCst));
+    if !THREADS_OK.load(Ordering::SeqCst) {
+        return Err(
+            FftError::FftwUnavailable("could not initialize FFTW threads".to_owned()).into(),
+        );
+    }
+    unsafe { plan_with(n as c_int) };
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        export_wisdom, export_wisdom_to_file, forget_wisdom, import_wisdom,
+        import_wisdom_from_file, set_threads,
+    };
+    use crate::enums::FftType;
+    use crate::errors::{FftError, LiquidError};
+    use crate::fft::FftPlan;
+    use crate::version::has_fftw;
+    use std::thread;
+
+    #[test]
+    fn test_fftw_wisdom() {
+        assert!(set_threads(0).is_err());
+        let path = std::env::temp_dir().join(format!("liquid_fftw_{}", std::process::id()));
+        let unavailable = |r: Result<(), LiquidError>| {
+            matches!(r, Err(LiquidError::Fft(FftError::FftwUnavailable(_))))
+        };
+        if !has_fftw() {
+            assert!(unavailable(export_wisdom().map(|_| ())));
+            assert!(unavailable(import_wisdom("")));
+            assert!(unavailable(forget_wisdom()));
+            assert!(unavailable(export_wisdom_to_file(&path)));
+            assert!(unavailable(import_wisdom_from_file(&path)));
+            return;
+        }
+
+        let _plan = FftPlan::create(1024, FftType::FORWARD).unwrap();
+        let wisdom = export_wisdom().unwrap();
+        assert!(wisdom.starts_with("(fftw-3"), "{}", wisdom);
+        assert!(import_wisdom("not wisdom").is_err());
+        assert!(import_wisdom("wis\0dom").is_err());
+
+        export_wisdom_to_file(&path).unwrap();
+        forget_wisdom().unwrap();
+        import_wisdom_from_file(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert!(import_wisdom_from_file(&path).is_err());
+
+        // the planner calls of several threads are serialized
+        let threads: Vec<_> = (0..4)
+            .map(|_| {
+                thread::spawn(|| {
+                    for _ in 0..20 {
+                        import_wisdom(&export_wisdom().unwrap()).unwrap();
+                        FftPlan::create(256, FftType::BACKWARD).unwrap();
+                    }
+                })
+            })
+            .collect();
+        threads.into_iter().for_each(|t| t.join().unwrap());
+    }
+}
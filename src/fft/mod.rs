@@ -1,7 +1,14 @@
 mod asgram;
+mod asgram_stream;
 mod common;
 mod fftplan;
+pub mod fftw;
+mod spgram;
+mod spwaterfall;
 
 pub use asgram::{AsgramCf, AsgramRf};
+pub use asgram_stream::{Asgram, AsgramFrame, AsgramStream};
 pub use common::Fft;
-pub use fftplan::FftPlan;
+pub use fftplan::{FftOptions, FftPlan};
+pub use spgram::{SpgramCf, SpgramRf};
+pub use spwaterfall::{SpwaterfallCf, SpwaterfallRf};
@@ -0,0 +1,280 @@
+//! *spgram* : spectral periodogram, estimates the power spectral density
+//!          of a signal by averaging windowed transforms
+use num::complex::Complex32;
+
+use crate::enums::WindowType;
+use crate::errors::FftError;
+use crate::liquid_dsp_sys as raw;
+use crate::utils::{ToCPointer, ToCValue};
+use crate::LiquidResult;
+
+pub struct SpgramCf {
+    inner: raw::spgramcf,
+}
+
+pub struct SpgramRf {
+    inner: raw::spgramf,
+}
+
+macro_rules! spgram_xxx_impl {
+    ($obj:ty, (
+        $create:expr, $create_default:expr,
+        $clear:expr, $reset:expr,
+        $print:expr,
+        $setalpha:expr, $getalpha:expr,
+        $setfreq:expr, $setrate:expr,
+        $nfft:expr, $window_len:expr, $delay:expr,
+        $num_samples:expr, $num_transforms:expr,
+        $push:expr, $write:expr,
+        $psd:expr,
+        $destroy:expr,
+        $type:ty)) => {
+        impl $obj {
+            /// create spgram object
+            ///  nfft       :   FFT size, at least 2
+            ///  wtype      :   window type, e.g. WindowType::HAMMING
+            ///  window_len :   window length, in [1, nfft], even for WindowType::KBD
+            ///  delay      :   delay between transforms, greater than 0
+            pub fn create(
+                nfft: usize,
+                wtype: WindowType,
+                window_len: usize,
+                delay: usize,
+            ) -> LiquidResult<Self> {
+                if nfft < 2 {
+                    return Err(FftError::InvalidSize("nfft must be at least 2".to_owned()).into());
+                } else if window_len == 0 || window_len > nfft {
+                    return Err(FftError::InvalidSize(format!(
+                        "window length: {} out of range [1, {}]",
+                        window_len, nfft
+                    ))
+                    .into());
+                } else if wtype == WindowType::KBD && window_len % 2 != 0 {
+                    return Err(
+                        FftError::InvalidSize("KBD window length must be even".to_owned()).into(),
+                    );
+                } else if wtype == WindowType::UNKNOWN {
+                    return Err(FftError::InvalidType.into());
+                } else if delay == 0 {
+                    return Err(FftError::InvalidSize(
+                        "delay must be greater than zero".to_owned(),
+                    )
+                    .into());
+                }
+                Ok(Self {
+                    inner: unsafe {
+                        $create(nfft as _, u8::from(wtype) as _, window_len as _, delay as _)
+                    },
+                })
+            }
+
+            /// create spgram object with a Kaiser window of nfft/2 samples
+            /// and a delay of nfft/4 samples
+            ///  nfft       :   FFT size, at least 2
+            pub fn create_default(nfft: usize) -> LiquidResult<Self> {
+                if nfft < 2 {
+                    return Err(FftError::InvalidSize("nfft must be at least 2".to_owned()).into());
+                }
+                Ok(Self {
+                    inner: unsafe { $create_default(nfft as _) },
+                })
+            }
+
+            /// clear the internal buffers and the accumulated psd
+            pub fn clear(&mut self) {
+                unsafe {
+                    $clear(self.inner);
+                }
+            }
+
+            /// reset the object to its original state completely
+            pub fn reset(&mut self) {
+                unsafe {
+                    $reset(self.inner);
+                }
+            }
+
+            pub fn print(&self) {
+                unsafe {
+                    $print(self.inner);
+                }
+            }
+
+            /// set the forgetting factor of the psd average
+            ///  alpha  :   in [0, 1], or -1 to average all the transforms
+            pub fn set_alpha(&mut self, alpha: f32) -> LiquidResult<()> {
+                if alpha != -1.0 && !(0.0..=1.0).contains(&alpha) {
+                    return Err(
+                        FftError::InvalidSize("alpha must be in [0, 1] or -1".to_owned()).into(),
+                    );
+                }
+                unsafe {
+                    $setalpha(self.inner, alpha);
+                }
+                Ok(())
+            }
+
+            pub fn get_alpha(&self) -> f32 {
+                unsafe { $getalpha(self.inner) }
+            }
+
+            /// set the center frequency used to label the exported plots
+            pub fn set_freq(&mut self, freq: f32) {
+                unsafe {
+                    $setfreq(self.inner, freq);
+                }
+            }
+
+            /// set the sample rate used to label the exported plots
+            ///  rate   :   sample rate, greater than 0
+            pub fn set_rate(&mut self, rate: f32) -> LiquidResult<()> {
+                if rate <= 0f32 {
+                    return Err(FftError::InvalidSize(
+                        "sample rate must be greater than zero".to_owned(),
+                    )
+                    .into());
+                }
+                unsafe {
+                    $setrate(self.inner, rate);
+                }
+                Ok(())
+            }
+
+            pub fn get_nfft(&self) -> usize {
+                unsafe { $nfft(self.inner) as usize }
+            }
+
+            pub fn get_window_len(&self) -> usize {
+                unsafe { $window_len(self.inner) as usize }
+            }
+
+            /// delay between transforms [samples]
+            pub fn get_delay(&self) -> usize {
+                unsafe { $delay(self.inner) as usize }
+            }
+
+            /// total number of samples pushed since the object was created
+            pub fn get_num_samples_total(&self) -> u64 {
+                unsafe { $num_samples(self.inner) }
+            }
+
+            /// total number of transforms since the object was created
+            pub fn get_num_transforms_total(&self) -> u64 {
+                unsafe { $num_transforms(self.inner) }
+            }
+
+            pub fn push(&mut self, x: $type) {
+                unsafe {
+                    $push(self.inner, x.to_c_value());
+                }
+            }
+
+            pub fn write(&mut self, x: &[$type]) {
+                unsafe {
+                    $write(self.inner, x.to_ptr() as _, x.len() as _);
+                }
+            }
+
+            /// accumulated power spectral density [dB], fft-shifted so
+            /// that bin 0 is the frequency -0.5 [size: nfft x 1]
+            pub fn get_psd(&self) -> Vec<f32> {
+                let mut psd = vec![0f32; self.get_nfft()];
+                unsafe {
+                    $psd(self.inner, psd.as_mut_ptr());
+                }
+                psd
+            }
+
+            /// render the accumulated psd as an SVG file
+            #[cfg(feature = "plot")]
+            pub fn plot_psd<P: AsRef<std::path::Path>>(&self, path: P) -> std::io::Result<()> {
+                let psd = self.get_psd();
+                let nfft = psd.len() as f32;
+                let f: Vec<f32> = (0..psd.len()).map(|i| i as f32 / nfft - 0.5).collect();
+                let labels = crate::plot::Labels {
+                    title: "power spectral density",
+                    x: "normalized frequency",
+                    y: "psd [dB]",
+                };
+                crate::plot::save_lines(path, labels, &f, &[("psd", &psd)])
+            }
+        }
+
+        impl Drop for $obj {
+            fn drop(&mut self) {
+                unsafe {
+                    $destroy(self.inner);
+                }
+            }
+        }
+    };
+}
+
+spgram_xxx_impl!(
+    SpgramCf,
+    (
+        raw::spgramcf_create,
+        raw::spgramcf_create_default,
+        raw::spgramcf_clear,
+        raw::spgramcf_reset,
+        raw::spgramcf_print,
+        raw::spgramcf_set_alpha,
+        raw::spgramcf_get_alpha,
+        raw::spgramcf_set_freq,
+        raw::spgramcf_set_rate,
+        raw::spgramcf_get_nfft,
+        raw::spgramcf_get_window_len,
+        raw::spgramcf_get_delay,
+        raw::spgramcf_get_num_samples_total,
+        raw::spgramcf_get_num_transforms_total,
+        raw::spgramcf_push,
+        raw::spgramcf_write,
+        raw::spgramcf_get_psd,
+        raw::spgramcf_destroy,
+        Complex32
+    )
+);
+
+spgram_xxx_impl!(
+    SpgramRf,
+    (
+        raw::spgramf_create,
+        raw::spgramf_create_default,
+        raw::spgramf_clear,
+        raw::spgramf_reset,
+        raw::spgramf_print,
+        raw::spgramf_set_alpha,
+        raw::spgramf_get_alpha,
+        raw::spgramf_set_freq,
+        raw::spgramf_set_rate,
+        raw::spgramf_get_nfft,
+        raw::spgramf_get_window_len,
+        raw::spgramf_get_delay,
+        raw::spgramf_get_num_samples_total,
+        raw::spgramf_get_num_transforms_total,
+        raw::spgramf_push,
+        raw::spgramf_write,
+        raw::spgramf_get_psd,
+        raw::spgramf_destroy,
+        f32
+    )
+);
+
+#[cfg(test)]
+mod tests {
+    use super::SpgramCf;
+    use crate::enums::WindowType;
+
+    #[test]
+    fn test_spgram_create_validation() {
+        assert!(SpgramCf::create(1, WindowType::HAMMING, 1, 1).is_err());
+        assert!(SpgramCf::create(64, WindowType::HAMMING, 65, 16).is_err());
+        assert!(SpgramCf::create(64, WindowType::KBD, 31, 16).is_err());
+        assert!(SpgramCf::create(64, WindowType::HANN, 32, 0).is_err());
+        assert!(SpgramCf::create_default(1).is_err());
+
+        let mut q = SpgramCf::create_default(64).unwrap();
+        assert!(q.set_alpha(1.5).is_err());
+        assert!(q.set_alpha(-1.0).is_ok());
+    }
+}
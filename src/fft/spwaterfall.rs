@@ -0,0 +1,348 @@
+//! *spwaterfall* : spectral periodogram waterfall, accumulates the power
+//!               spectral density of a signal over time
+#[cfg(feature = "gnuplot")]
+use std::ffi::{CString, NulError};
+use std::slice;
+
+use num::complex::Complex32;
+
+use crate::enums::WindowType;
+use crate::errors::FftError;
+use crate::liquid_dsp_sys as raw;
+use crate::utils::{ToCPointer, ToCValue};
+use crate::LiquidResult;
+
+pub struct SpwaterfallCf {
+    inner: raw::spwaterfallcf,
+}
+
+pub struct SpwaterfallRf {
+    inner: raw::spwaterfallf,
+}
+
+macro_rules! spwaterfall_xxx_impl {
+    ($obj:ty, (
+        $create:expr, $create_default:expr,
+        $clear:expr, $reset:expr,
+        $print:expr,
+        $num_samples:expr,
+        $num_freq:expr, $num_time:expr,
+        $psd:expr,
+        $setfreq:expr, $setrate:expr,
+        $setdims:expr, $setcommands:expr,
+        $push:expr, $write:expr,
+        $export:expr,
+        $destroy:expr,
+        $type:ty)) => {
+        impl $obj {
+            /// create spwaterfall object
+            ///  nfft       :   FFT size, at least 2
+            ///  wtype      :   window type, e.g. WindowType::HAMMING
+            ///  window_len :   window length, in [1, nfft], even for WindowType::KBD
+            ///  delay      :   delay between transforms, greater than 0
+            ///  time       :   number of aggregated transforms per time row, greater than 0
+            pub fn create(
+                nfft: usize,
+                wtype: WindowType,
+                window_len: usize,
+                delay: usize,
+                time: usize,
+            ) -> LiquidResult<Self> {
+                if nfft < 2 {
+                    return Err(FftError::InvalidSize("nfft must be at least 2".to_owned()).into());
+                } else if window_len == 0 || window_len > nfft {
+                    return Err(FftError::InvalidSize(format!(
+                        "window length: {} out of range [1, {}]",
+                        window_len, nfft
+                    ))
+                    .into());
+                } else if wtype == WindowType::KBD && window_len % 2 != 0 {
+                    return Err(
+                        FftError::InvalidSize("KBD window length must be even".to_owned()).into(),
+                    );
+                } else if wtype == WindowType::UNKNOWN {
+                    return Err(FftError::InvalidType.into());
+                } else if delay == 0 || time == 0 {
+                    return Err(FftError::InvalidSize(
+                        "delay and time must be greater than zero".to_owned(),
+                    )
+                    .into());
+                }
+                let inner = unsafe {
+                    $create(
+                        nfft as _,
+                        u8::from(wtype) as _,
+                        window_len as _,
+                        delay as _,
+                        time as _,
+                    )
+                };
+                if inner.is_null() {
+                    return Err(FftError::InvalidValue(
+                        "liquid could not create the spwaterfall object".to_owned(),
+                    )
+                    .into());
+                }
+                Ok(Self { inner })
+            }
+
+            /// create spwaterfall object with a Kaiser window of
+            /// nfft/2 samples and a delay of nfft/4 samples
+            ///  nfft       :   FFT size, at least 4 so that the delay is not zero
+            ///  time       :   number of aggregated transforms per time row
+            pub fn create_default(nfft: usize, time: usize) -> LiquidResult<Self> {
+                if nfft < 4 {
+                    return Err(FftError::InvalidSize("nfft must be at least 4".to_owned()).into());
+                } else if time == 0 {
+                    return Err(
+                        FftError::InvalidSize("time must be greater than zero".to_owned()).into(),
+                    );
+                }
+                let inner = unsafe { $create_default(nfft as _, time as _) };
+                if inner.is_null() {
+                    return Err(FftError::InvalidValue(
+                        "liquid could not create the spwaterfall object".to_owned(),
+                    )
+                    .into());
+                }
+                Ok(Self { inner })
+            }
+
+            /// clear the internal buffers and the accumulated psd
+            pub fn clear(&mut self) {
+                unsafe {
+                    $clear(self.inner);
+                }
+            }
+
+            /// reset the object to its original state completely
+            pub fn reset(&mut self) {
+                unsafe {
+                    $reset(self.inner);
+                }
+            }
+
+            pub fn print(&self) {
+                unsafe {
+                    $print(self.inner);
+                }
+            }
+
+            /// total number of samples pushed since the object was created
+            pub fn get_num_samples_total(&self) -> u64 {
+                unsafe { $num_samples(self.inner) }
+            }
+
+            /// number of frequency bins in each psd row
+            pub fn get_num_freq(&self) -> usize {
+                unsafe { $num_freq(self.inner) as usize }
+            }
+
+            /// number of accumulated psd rows
+            pub fn get_num_time(&self) -> usize {
+                unsafe { $num_time(self.inner) as usize }
+            }
+
+            /// accumulated power spectral density [dB]
+            /// # returns
+            /// the psd in time-major order [size: num_time x num_freq],
+            /// the number of time rows and the number of frequency bins
+            pub fn get_psd(&self) -> (Vec<f32>, usize, usize) {
+                let (num_time, num_freq) = (self.get_num_time(), self.get_num_freq());
+                let len = num_time * num_freq;
+                if len == 0 {
+                    return (Vec::new(), num_time, num_freq);
+                }
+                let psd = unsafe { slice::from_raw_parts($psd(self.inner), len) };
+                (psd.to_vec(), num_time, num_freq)
+            }
+
+            /// set the center frequency used to label the exported plots
+            pub fn set_freq(&mut self, freq: f32) {
+                unsafe {
+                    $setfreq(self.inner, freq);
+                }
+            }
+
+            /// set the sample rate used to label the exported plots
+            ///  rate   :   sample rate, greater than 0
+            pub fn set_rate(&mut self, rate: f32) -> LiquidResult<()> {
+                if rate <= 0f32 {
+                    return Err(FftError::InvalidSize(
+                        "sample rate must be greater than zero".to_owned(),
+                    )
+                    .into());
+                }
+                unsafe {
+                    $setrate(self.inner, rate);
+                }
+                Ok(())
+            }
+
+            /// set the dimensions of the exported image [pixels]
+            #[cfg(feature = "gnuplot")]
+            pub fn set_dims(&mut self, width: u32, height: u32) {
+                unsafe {
+                    $setdims(self.inner, width as _, height as _);
+                }
+            }
+
+            /// set extra gnuplot commands for the exported script
+            #[cfg(feature = "gnuplot")]
+            pub fn set_commands(&mut self, commands: &str) -> Result<(), NulError> {
+                CString::new(commands).map(|c| unsafe {
+                    $setcommands(self.inner, c.as_ptr());
+                })
+            }
+
+            pub fn push(&mut self, x: $type) {
+                unsafe {
+                    $push(self.inner, x.to_c_value());
+                }
+            }
+
+            pub fn write(&mut self, x: &[$type]) {
+                unsafe {
+                    $write(self.inner, x.to_ptr() as _, x.len() as _);
+                }
+            }
+
+            /// render the accumulated psd as an SVG heat map, frequency on
+            /// the horizontal axis and time growing downwards
+            #[cfg(feature = "plot")]
+            pub fn plot<P: AsRef<std::path::Path>>(&self, path: P) -> std::io::Result<()> {
+                let (psd, _, num_freq) = self.get_psd();
+                let labels = crate::plot::Labels {
+                    title: "spectral waterfall",
+                    x: "normalized frequency",
+                    y: "time [rows]",
+                };
+                crate::plot::save_heatmap(path, labels, (-0.5, 0.5), &psd, num_freq)
+            }
+
+            /// export the gnuplot script <base>.gnu and the psd data
+            /// <base>.bin; running the script renders <base>.png
+            #[cfg(feature = "gnuplot")]
+            pub fn export(&self, base: &str) -> LiquidResult<()> {
+                let c = CString::new(base).map_err(|_| {
+                    FftError::InvalidValue("base name cannot contain nul bytes".to_owned())
+                })?;
+                if unsafe { $export(self.inner, c.as_ptr()) } != 0 {
+                    return Err(FftError::InvalidValue(format!(
+                        "could not export waterfall to {}",
+                        base
+                    ))
+                    .into());
+                }
+                Ok(())
+            }
+        }
+
+        impl Drop for $obj {
+            fn drop(&mut self) {
+                unsafe {
+                    $destroy(self.inner);
+                }
+            }
+        }
+    };
+}
+
+spwaterfall_xxx_impl!(
+    SpwaterfallCf,
+    (
+        raw::spwaterfallcf_create,
+        raw::spwaterfallcf_create_default,
+        raw::spwaterfallcf_clear,
+        raw::spwaterfallcf_reset,
+        raw::spwaterfallcf_print,
+        raw::spwaterfallcf_get_num_samples_total,
+        raw::spwaterfallcf_get_num_freq,
+        raw::spwaterfallcf_get_num_time,
+        raw::spwaterfallcf_get_psd,
+        raw::spwaterfallcf_set_freq,
+        raw::spwaterfallcf_set_rate,
+        raw::spwaterfallcf_set_dims,
+        raw::spwaterfallcf_set_commands,
+        raw::spwaterfallcf_push,
+        raw::spwaterfallcf_write,
+        raw::spwaterfallcf_export,
+        raw::spwaterfallcf_destroy,
+        Complex32
+    )
+);
+
+spwaterfall_xxx_impl!(
+    SpwaterfallRf,
+    (
+        raw::spwaterfallf_create,
+        raw::spwaterfallf_create_default,
+        raw::spwaterfallf_clear,
+        raw::spwaterfallf_reset,
+        raw::spwaterfallf_print,
+        raw::spwaterfallf_get_num_samples_total,
+        raw::spwaterfallf_get_num_freq,
+        raw::spwaterfallf_get_num_time,
+        raw::spwaterfallf_get_psd,
+        raw::spwaterfallf_set_freq,
+        raw::spwaterfallf_set_rate,
+        raw::spwaterfallf_set_dims,
+        raw::spwaterfallf_set_commands,
+        raw::spwaterfallf_push,
+        raw::spwaterfallf_write,
+        raw::spwaterfallf_export,
+        raw::spwaterfallf_destroy,
+        f32
+    )
+);
+
+#[cfg(test)]
+mod tests {
+    use super::SpwaterfallCf;
+    use crate::enums::WindowType;
+    use num::complex::Complex32;
+    use std::f32::consts::PI;
+
+    #[test]
+    fn test_spwaterfall_create_validation() {
+        assert!(SpwaterfallCf::create(1, WindowType::HAMMING, 1, 1, 1).is_err());
+        assert!(SpwaterfallCf::create(64, WindowType::HAMMING, 65, 16, 8).is_err());
+        assert!(SpwaterfallCf::create(64, WindowType::KBD, 31, 16, 8).is_err());
+        assert!(SpwaterfallCf::create(64, WindowType::UNKNOWN, 32, 16, 8).is_err());
+        assert!(SpwaterfallCf::create(64, WindowType::HANN, 32, 0, 8).is_err());
+        assert!(SpwaterfallCf::create_default(64, 0).is_err());
+        assert!(SpwaterfallCf::create_default(3, 8).is_err());
+    }
+
+    #[test]
+    fn test_spwaterfall_psd() {
+        let (nfft, time) = (64, 8);
+        let mut q = SpwaterfallCf::create_default(nfft, time).unwrap();
+        let (psd, num_time, _) = q.get_psd();
+        assert!(psd.is_empty());
+        assert_eq!(num_time, 0);
+
+        // tone at an eighth of the sample rate, a transform every nfft/4
+        // samples and a row every time transforms
+        let x: Vec<Complex32> = (0..nfft * time)
+            .map(|n| Complex32::from_polar(1.0, 2.0 * PI * 0.125 * n as f32))
+            .collect();
+        q.write(&x);
+        assert_eq!(q.get_num_samples_total(), x.len() as u64);
+
+        let (psd, num_time, num_freq) = q.get_psd();
+        assert_eq!(num_freq, nfft);
+        assert!(num_time > 0);
+        assert_eq!(psd.len(), num_time * num_freq);
+        // the psd rows are centred on DC, so the tone is nfft/8 bins right
+        // of the middle
+        for row in psd.chunks(num_freq) {
+            let peak = row
+                .iter()
+                .enumerate()
+                .fold(0, |k, (i, &v)| if v > row[k] { i } else { k });
+            assert_eq!(peak, nfft / 2 + nfft / 8);
+            assert!(row[peak] > row[nfft / 2] + 20.0);
+        }
+    }
+}
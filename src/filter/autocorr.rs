@@ -3,7 +3,7 @@ use std::fmt;
 use crate::liquid_dsp_sys as raw;
 use num::complex::Complex32;
 
-use crate::utils::{ToCPointerMut, ToCValue};
+use crate::utils::{ToCPointer, ToCPointerMut, ToCValue};
 
 pub struct AutoCorrRrrf {
     inner: raw::autocorr_rrrf,
@@ -22,10 +22,11 @@ macro_rules! autocorr_xxx_impl {
         $destroy:expr,
         $push:expr, $execute:expr,
         $block:expr,$energy:expr,
+        $conj:expr,
         $type:ty, $type2:ty)) => {
         impl $obj {
-            /// create auto-correlator object                            
-            ///  n    : size of the correlator window         
+            /// create auto-correlator object
+            ///  n    : size of the correlator window
             ///  d    : correlator delay [samples]
             pub fn create(n: u32, d: u32) -> Self {
                 Self {
@@ -42,14 +43,14 @@ macro_rules! autocorr_xxx_impl {
             }
 
             /// push sample into auto-correlator object
-            pub fn push(&self, sample: $type2) {
+            pub fn push(&mut self, sample: $type2) {
                 unsafe {
                     $push(self.inner, sample.to_c_value());
                 }
             }
 
             /// compute auto-correlation output
-            pub fn execute(&self) -> $type2 {
+            pub fn execute(&mut self) -> $type2 {
                 unsafe {
                     let mut out = <$type2>::default();
                     $execute(self.inner, out.to_ptr_mut());
@@ -60,26 +61,67 @@ macro_rules! autocorr_xxx_impl {
             /// compute auto-correlation on block of samples; the input
             /// and output arrays may have the same pointer
             ///  input      :   input array [size: _n x 1]
-            ///  output     :   input array [size: _n x 1]
-            pub fn execute_block(&self, input: &[$type2], output: &mut [$type2]) {
+            ///  output     :   output array [size: _n x 1]
+            pub fn execute_block(&mut self, input: &[$type2], output: &mut [$type2]) {
                 assert!(
                     input.len() == output.len(),
                     "Input and output buffers with different length"
                 );
-                input
-                    .iter()
-                    .zip(output.iter_mut())
-                    .for_each(|(isample, osample)| {
-                        self.push(*isample);
-                        unsafe {
-                            $execute(self.inner, osample.to_ptr_mut());
-                        }
-                    });
+                unsafe {
+                    $block(
+                        self.inner,
+                        input.to_ptr() as _,
+                        input.len() as _,
+                        output.to_ptr_mut(),
+                    );
+                }
             }
 
             pub fn get_energy(&self) -> $type {
                 unsafe { $energy(self.inner) }
             }
+
+            /// compute auto-correlation output normalized by the energy of
+            /// the samples in the window, zero while the window is empty
+            pub fn execute_normalized(&mut self) -> $type2 {
+                let energy = self.get_energy();
+                if energy > 0.0 {
+                    self.execute() / energy
+                } else {
+                    <$type2>::default()
+                }
+            }
+
+            /// auto-correlation of a block of samples over a range of lags,
+            /// independent of the object state
+            ///  input      :   input array
+            ///  max_lag    :   largest lag
+            /// # returns
+            /// rxx[l] = sum(input[n] * conj(input[n-l])) for l in [0, max_lag],
+            /// zero for lags without overlap [size: max_lag+1 x 1]
+            pub fn correlate(&mut self, input: &[$type2], max_lag: usize) -> Vec<$type2> {
+                let conj = $conj;
+                (0..=max_lag)
+                    .map(|lag| {
+                        input
+                            .iter()
+                            .skip(lag)
+                            .zip(input.iter())
+                            .fold(<$type2>::default(), |acc, (a, b)| acc + *a * conj(*b))
+                    })
+                    .collect()
+            }
+
+            /// same as correlate, normalized by the energy of the block so
+            /// that rxx[0] = 1
+            pub fn correlate_normalized(&mut self, input: &[$type2], max_lag: usize) -> Vec<$type2> {
+                let mut rxx = self.correlate(input, max_lag);
+                let energy = rxx[0];
+                if energy != <$type2>::default() {
+                    rxx.iter_mut().for_each(|v| *v /= energy);
+                }
+                rxx
+            }
         }
 
         /// return sum of squares of buffered samples
@@ -109,7 +151,9 @@ autocorr_xxx_impl!(
         raw::autocorr_cccf_execute,
         raw::autocorr_cccf_execute_block,
         raw::autocorr_cccf_get_energy,
-        f32, Complex32
+        |v: Complex32| v.conj(),
+        f32,
+        Complex32
     )
 );
 
@@ -123,7 +167,9 @@ autocorr_xxx_impl!(
         raw::autocorr_rrrf_execute,
         raw::autocorr_rrrf_execute_block,
         raw::autocorr_rrrf_get_energy,
-        f32, f32
+        |v: f32| v,
+        f32,
+        f32
     )
 );
 
@@ -144,9 +190,9 @@ mod tests {
         let mut input = Vec::with_capacity(4);
         let mut output = vec![Complex32::zero(); 4];
         for i in 0..4 {
-            input.push(Complex32::new(0.0 + i as f32, 4.5 - i as f32 * (-1.0)));
+            input.push(Complex32::new(0.0 + i as f32, 4.5 + i as f32));
         }
-        let auto_cccf = AutoCorrCccf::create(4, 0);
+        let mut auto_cccf = AutoCorrCccf::create(4, 0);
         auto_cccf.execute_block(&input, &mut output);
         let solution = [
             Complex32::new(20.25, 0.0),
@@ -157,6 +203,24 @@ mod tests {
         assert_eq!(&output, &solution);
     }
 
+    #[test]
+    fn test_autocorr_cccf_correlate() {
+        let mut auto_cccf = AutoCorrCccf::create(4, 1);
+        let input: Vec<Complex32> = (0..32)
+            .map(|n| Complex32::from_polar(1.0, 0.25 * n as f32))
+            .collect();
+        let rxx = auto_cccf.correlate_normalized(&input, 3);
+        assert_eq!(rxx.len(), 4);
+        assert!((rxx[0] - Complex32::new(1.0, 0.0)).norm() < 1e-6);
+        // a tone correlates with itself delayed by l at phase 0.25*l, with
+        // 32-l overlapping samples
+        let expected = Complex32::from_polar(31.0 / 32.0, 0.25);
+        assert!((rxx[1] - expected).norm() < 1e-5);
+
+        let rxx = auto_cccf.correlate(&input[..2], 3);
+        assert_eq!(rxx[2], Complex32::zero());
+    }
+
     #[test]
     fn test_autocorr_rrrf() {
         let auto_rrrf = AutoCorrRrrf::create(16, 8);
@@ -167,9 +231,9 @@ mod tests {
     fn test_autocorr_rrrf_execute_block() {
         let mut input = Vec::with_capacity(4);
         let mut output = vec![0.0f32; 4];
-        let auto_rrrf = AutoCorrRrrf::create(4, 0);
+        let mut auto_rrrf = AutoCorrRrrf::create(4, 0);
         for i in 0..4 {
-            input.push(4.5 - i as f32 * (-1.0));
+            input.push(4.5 + i as f32);
         }
         auto_rrrf.execute_block(&input, &mut output);
         let solution = [20.25, 50.5, 92.75, 149.0];
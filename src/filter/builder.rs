@@ -0,0 +1,285 @@
+//! *builder* : single entry point for the design of FIR filters, selects
+//!           the liquid design routine from the requested response
+use num::complex::Complex32;
+
+use crate::errors::FilterError;
+use crate::filter::{
+    FirFiltCccf, FirFiltCrcf, FirFiltRrrf, FirdesFilterType, Firdespm, FirdespmBtype,
+};
+use crate::liquid_dsp_sys as raw;
+use crate::LiquidResult;
+
+#[derive(Clone, Copy, Debug)]
+enum Response {
+    Lowpass(f32),
+    RaisedCosine { k: u32, m: u32, beta: f32 },
+    RootRaisedCosine { k: u32, m: u32, beta: f32 },
+    Notch(f32),
+}
+
+/// builder for FIR filters, e.g.
+/// FirFilterBuilder::new().lowpass(0.1).attenuation(60.0).build_crcf()
+#[derive(Clone, Copy, Debug)]
+pub struct FirFilterBuilder {
+    response: Option<Response>,
+    method: FirdesFilterType,
+    as_: f32,
+    transition: f32,
+    len: Option<usize>,
+    mu: f32,
+}
+
+impl Default for FirFilterBuilder {
+    fn default() -> Self {
+        Self {
+            response: None,
+            method: FirdesFilterType::Kaiser,
+            as_: 60.0,
+            transition: 0.05,
+            len: None,
+            mu: 0.0,
+        }
+    }
+}
+
+impl FirFilterBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// low-pass response
+    ///  fc     :   cut-off frequency, in (0, 0.5)
+    pub fn lowpass(mut self, fc: f32) -> Self {
+        self.response = Some(Response::Lowpass(fc));
+        self
+    }
+
+    /// raised-cosine (Nyquist) response, length 2*k*m+1
+    ///  k      :   samples/symbol, at least 2
+    ///  m      :   symbol delay, at least 1
+    ///  beta   :   excess bandwidth factor, in [0, 1]
+    pub fn raised_cosine(mut self, k: u32, m: u32, beta: f32) -> Self {
+        self.response = Some(Response::RaisedCosine { k, m, beta });
+        self
+    }
+
+    /// square-root raised-cosine response, length 2*k*m+1
+    ///  k      :   samples/symbol, at least 2
+    ///  m      :   symbol delay, at least 1
+    ///  beta   :   excess bandwidth factor, in (0, 1]
+    pub fn root_raised_cosine(mut self, k: u32, m: u32, beta: f32) -> Self {
+        self.response = Some(Response::RootRaisedCosine { k, m, beta });
+        self
+    }
+
+    /// notch response
+    ///  f0     :   notch frequency, in [-0.5, 0.5]
+    pub fn notch(mut self, f0: f32) -> Self {
+        self.response = Some(Response::Notch(f0));
+        self
+    }
+
+    /// design method of the low-pass response, FirdesFilterType::Kaiser
+    /// (windowed sinc, default) or FirdesFilterType::Pm (Parks-McClellan)
+    pub fn method(mut self, method: FirdesFilterType) -> Self {
+        self.method = method;
+        self
+    }
+
+    /// stop-band attenuation [dB], default 60
+    pub fn attenuation(mut self, as_: f32) -> Self {
+        self.as_ = as_;
+        self
+    }
+
+    /// transition bandwidth used to estimate the filter length when
+    /// none is given, and as the transition band of Pm designs
+    pub fn transition(mut self, df: f32) -> Self {
+        self.transition = df;
+        self
+    }
+
+    /// filter length; ignored by the Nyquist responses, must be odd
+    /// for the notch response
+    pub fn len(mut self, n: usize) -> Self {
+        self.len = Some(n);
+        self
+    }
+
+    /// fractional sample offset of the Kaiser and Nyquist designs, in [-0.5, 0.5]
+    pub fn offset(mut self, mu: f32) -> Self {
+        self.mu = mu;
+        self
+    }
+
+    fn validate(&self) -> LiquidResult<Response> {
+        let invalid = |msg: &str| Err(FilterError::InvalidValue(msg.to_owned()).into());
+        let response = match self.response {
+            Some(response) => response,
+            None => return invalid("no filter response selected"),
+        };
+        if self.as_ <= 0f32 {
+            return invalid("attenuation must be greater than zero");
+        } else if self.transition <= 0f32 || self.transition >= 0.5 {
+            return invalid("transition bandwidth must be in (0, 0.5)");
+        } else if self.mu < -0.5 || self.mu > 0.5 {
+            return invalid("fractional sample offset must be in [-0.5, 0.5]");
+        } else if self.len == Some(0) {
+            return invalid("filter length must be greater than zero");
+        }
+        match response {
+            Response::Lowpass(fc) => {
+                if fc <= 0f32 || fc >= 0.5 {
+                    return invalid("cut-off frequency must be in (0, 0.5)");
+                } else if self.method != FirdesFilterType::Kaiser
+                    && self.method != FirdesFilterType::Pm
+                {
+                    return invalid("low-pass method must be Kaiser or Pm");
+                } else if self.method == FirdesFilterType::Pm
+                    && (fc - self.transition / 2.0 <= 0f32 || fc + self.transition / 2.0 >= 0.5)
+                {
+                    return invalid("transition band must lie within (0, 0.5)");
+                }
+            }
+            Response::RaisedCosine { k, m, beta } | Response::RootRaisedCosine { k, m, beta } => {
+                if k < 2 {
+                    return invalid("samples/symbol must be at least 2");
+                } else if m == 0 {
+                    return invalid("symbol delay must be greater than zero");
+                } else if !(0f32..=1.0).contains(&beta) {
+                    return invalid("excess bandwidth factor must be in [0, 1]");
+                }
+            }
+            Response::Notch(f0) => {
+                if !(-0.5..=0.5).contains(&f0) {
+                    return invalid("notch frequency must be in [-0.5, 0.5]");
+                } else if self.len.is_some_and(|n| n < 3 || n % 2 == 0) {
+                    return invalid("notch filter length must be odd and at least 3");
+                }
+            }
+        }
+        Ok(response)
+    }
+
+    fn filter_len(&self) -> usize {
+        self.len.unwrap_or_else(|| unsafe {
+            raw::estimate_req_filter_len(self.transition, self.as_) as usize
+        })
+    }
+
+    /// design the filter coefficients
+    pub fn design(&self) -> LiquidResult<Vec<f32>> {
+        let h = match self.validate()? {
+            Response::Lowpass(fc) if self.method == FirdesFilterType::Pm => {
+                let mut h = vec![0f32; self.filter_len()];
+                let df = self.transition / 2.0;
+                let bands = [0.0, fc - df, fc + df, 0.5];
+                Firdespm::run(
+                    h.len(),
+                    2,
+                    &bands,
+                    &[1.0, 0.0],
+                    &[1.0, 1.0],
+                    None,
+                    FirdespmBtype::BANDPASS,
+                    &mut h,
+                )?;
+                h
+            }
+            Response::Lowpass(fc) => {
+                let mut h = vec![0f32; self.filter_len()];
+                unsafe {
+                    raw::liquid_firdes_kaiser(h.len() as _, fc, self.as_, self.mu, h.as_mut_ptr());
+                }
+                h
+            }
+            Response::RaisedCosine { k, m, beta } => {
+                let mut h = vec![0f32; (2 * k * m + 1) as usize];
+                unsafe {
+                    raw::liquid_firdes_rcos(k, m, beta, self.mu, h.as_mut_ptr());
+                }
+                h
+            }
+            Response::RootRaisedCosine { k, m, beta } => {
+                let mut h = vec![0f32; (2 * k * m + 1) as usize];
+                unsafe {
+                    raw::liquid_firdes_rrcos(k, m, beta, self.mu, h.as_mut_ptr());
+                }
+                h
+            }
+            Response::Notch(f0) => {
+                let mut h = vec![0f32; self.len.unwrap_or(25)];
+                unsafe {
+                    raw::liquid_firdes_notch((h.len() / 2) as _, f0, self.as_, h.as_mut_ptr());
+                }
+                h
+            }
+        };
+        Ok(h)
+    }
+
+    pub fn build_rrrf(&self) -> LiquidResult<FirFiltRrrf> {
+        FirFiltRrrf::create(&self.design()?)
+    }
+
+    pub fn build_crcf(&self) -> LiquidResult<FirFiltCrcf> {
+        FirFiltCrcf::create(&self.design()?)
+    }
+
+    pub fn build_cccf(&self) -> LiquidResult<FirFiltCccf> {
+        let h: Vec<Complex32> = self.design()?.into_iter().map(Complex32::from).collect();
+        FirFiltCccf::create(&h)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FirFilterBuilder;
+    use crate::filter::FirdesFilterType;
+
+    #[test]
+    fn test_fir_builder_validation() {
+        assert!(FirFilterBuilder::new().design().is_err());
+        assert!(FirFilterBuilder::new().lowpass(0.6).design().is_err());
+        assert!(FirFilterBuilder::new()
+            .lowpass(0.2)
+            .attenuation(-3.0)
+            .design()
+            .is_err());
+        assert!(FirFilterBuilder::new()
+            .lowpass(0.2)
+            .method(FirdesFilterType::Rcos)
+            .design()
+            .is_err());
+        assert!(FirFilterBuilder::new()
+            .lowpass(0.01)
+            .method(FirdesFilterType::Pm)
+            .transition(0.1)
+            .design()
+            .is_err());
+        assert!(FirFilterBuilder::new()
+            .notch(0.2)
+            .len(24)
+            .build_crcf()
+            .is_err());
+        assert!(FirFilterBuilder::new()
+            .root_raised_cosine(1, 3, 0.3)
+            .design()
+            .is_err());
+    }
+
+    #[test]
+    fn test_fir_builder_lengths() {
+        let h = FirFilterBuilder::new()
+            .raised_cosine(4, 3, 0.3)
+            .design()
+            .unwrap();
+        assert_eq!(h.len(), 25);
+        let h = FirFilterBuilder::new()
+            .lowpass(0.1)
+            .len(31)
+            .design()
+            .unwrap();
+        assert_eq!(h.len(), 31);
+    }
+}
@@ -1,6 +1,17 @@
-use std::mem::transmute;
+use std::convert::TryFrom;
+use std::ffi::CString;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::errors::{FilterError, LiquidError};
+use crate::liquid_dsp_sys as raw;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Copy, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[repr(u8)]
 pub enum FirdespmBtype {
     BANDPASS,
     DIFFERENTIATOR,
@@ -9,20 +20,29 @@ pub enum FirdespmBtype {
 
 impl From<FirdespmBtype> for u8 {
     fn from(value: FirdespmBtype) -> u8 {
-        unsafe { transmute::<FirdespmBtype, u8>(value) }
+        value as u8
     }
 }
 
-impl From<u8> for FirdespmBtype {
-    fn from(value: u8) -> Self {
-        if value > 3 {
-            unimplemented!();
+impl TryFrom<u8> for FirdespmBtype {
+    type Error = LiquidError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(FirdespmBtype::BANDPASS),
+            1 => Ok(FirdespmBtype::DIFFERENTIATOR),
+            2 => Ok(FirdespmBtype::HILBERT),
+            _ => Err(FilterError::InvalidValue(format!(
+                "{} is not a valid firdespm band type",
+                value
+            ))
+            .into()),
         }
-        unsafe { transmute::<u8, FirdespmBtype>(value) }
     }
 }
 
 #[derive(Clone, Copy, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[repr(u32)]
 pub enum FirdespmWtype {
     FLATWEIGHT,
@@ -32,20 +52,31 @@ pub enum FirdespmWtype {
 
 impl From<FirdespmWtype> for u32 {
     fn from(value: FirdespmWtype) -> u32 {
-        unsafe { transmute::<FirdespmWtype, u32>(value) }
+        value as u32
     }
 }
 
-impl From<u32> for FirdespmWtype {
-    fn from(value: u32) -> Self {
-        if value > 3 {
-            unimplemented!();
+impl TryFrom<u32> for FirdespmWtype {
+    type Error = LiquidError;
+
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(FirdespmWtype::FLATWEIGHT),
+            1 => Ok(FirdespmWtype::EXPWEIGHT),
+            2 => Ok(FirdespmWtype::LINWEIGHT),
+            _ => Err(FilterError::InvalidValue(format!(
+                "{} is not a valid firdespm weight type",
+                value
+            ))
+            .into()),
         }
-        unsafe { transmute::<u32, FirdespmWtype>(value) }
     }
 }
 
 #[derive(Clone, Copy, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[repr(u8)]
+#[allow(clippy::upper_case_acronyms)]
 pub enum IirdesFilterType {
     BUTTER,
     CHEBY1,
@@ -56,20 +87,33 @@ pub enum IirdesFilterType {
 
 impl From<IirdesFilterType> for u8 {
     fn from(value: IirdesFilterType) -> u8 {
-        unsafe { transmute::<IirdesFilterType, u8>(value) }
+        value as u8
     }
 }
 
-/* impl From<u8> for IirdesFilterType {
-    fn from(value: u8) -> Self {
-        if value > 15 {
-            return Self::UNKNOWN
+impl TryFrom<u8> for IirdesFilterType {
+    type Error = LiquidError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(IirdesFilterType::BUTTER),
+            1 => Ok(IirdesFilterType::CHEBY1),
+            2 => Ok(IirdesFilterType::CHEBY2),
+            3 => Ok(IirdesFilterType::ELLIP),
+            4 => Ok(IirdesFilterType::BESSEL),
+            _ => Err(FilterError::InvalidValue(format!(
+                "{} is not a valid iir filter type",
+                value
+            ))
+            .into()),
         }
-        unsafe { transmute::<u8, IirdesFilterType>(value) }
     }
-} */
+}
 
 #[derive(Clone, Copy, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[repr(u8)]
+#[allow(clippy::upper_case_acronyms)]
 pub enum IirdesBandType {
     LOWPASS,
     HIGHPASS,
@@ -79,11 +123,30 @@ pub enum IirdesBandType {
 
 impl From<IirdesBandType> for u8 {
     fn from(value: IirdesBandType) -> u8 {
-        unsafe { transmute::<IirdesBandType, u8>(value) }
+        value as u8
+    }
+}
+
+impl TryFrom<u8> for IirdesBandType {
+    type Error = LiquidError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(IirdesBandType::LOWPASS),
+            1 => Ok(IirdesBandType::HIGHPASS),
+            2 => Ok(IirdesBandType::BANDPASS),
+            3 => Ok(IirdesBandType::BANDSTOP),
+            _ => Err(
+                FilterError::InvalidValue(format!("{} is not a valid iir band type", value)).into(),
+            ),
+        }
     }
 }
 
 #[derive(Clone, Copy, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[repr(u8)]
+#[allow(clippy::upper_case_acronyms)]
 pub enum IirdesFormat {
     SOS,
     TF,
@@ -91,11 +154,29 @@ pub enum IirdesFormat {
 
 impl From<IirdesFormat> for u8 {
     fn from(value: IirdesFormat) -> u8 {
-        unsafe { transmute::<IirdesFormat, u8>(value) }
+        value as u8
+    }
+}
+
+impl TryFrom<u8> for IirdesFormat {
+    type Error = LiquidError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(IirdesFormat::SOS),
+            1 => Ok(IirdesFormat::TF),
+            _ => Err(FilterError::InvalidValue(format!(
+                "{} is not a valid iir filter format",
+                value
+            ))
+            .into()),
+        }
     }
 }
 
 #[derive(Clone, Copy, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[repr(u8)]
 pub enum FirdesFilterType {
     Unknown,
     Kaiser,
@@ -117,6 +198,241 @@ pub enum FirdesFilterType {
 
 impl From<FirdesFilterType> for u8 {
     fn from(value: FirdesFilterType) -> u8 {
-        unsafe { transmute::<FirdesFilterType, u8>(value) }
+        value as u8
+    }
+}
+
+impl TryFrom<u8> for FirdesFilterType {
+    type Error = LiquidError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(FirdesFilterType::Unknown),
+            1 => Ok(FirdesFilterType::Kaiser),
+            2 => Ok(FirdesFilterType::Pm),
+            3 => Ok(FirdesFilterType::Rcos),
+            4 => Ok(FirdesFilterType::Fexp),
+            5 => Ok(FirdesFilterType::Fsech),
+            6 => Ok(FirdesFilterType::FarcSech),
+            7 => Ok(FirdesFilterType::Arkaiser),
+            8 => Ok(FirdesFilterType::Rkaiser),
+            9 => Ok(FirdesFilterType::Rrc),
+            10 => Ok(FirdesFilterType::Hm3),
+            11 => Ok(FirdesFilterType::Gmsktx),
+            12 => Ok(FirdesFilterType::Gmskrx),
+            13 => Ok(FirdesFilterType::Rfexp),
+            14 => Ok(FirdesFilterType::Rfsech),
+            15 => Ok(FirdesFilterType::RfarcSech),
+            _ => Err(FilterError::InvalidValue(format!(
+                "{} is not a valid fir filter type",
+                value
+            ))
+            .into()),
+        }
+    }
+}
+
+/// as_str and Display for a design enum from a table of names
+macro_rules! enum_str_impl {
+    ($obj:ident, [$($variant:ident => $name:expr),+ $(,)?]) => {
+        impl $obj {
+            /// name of the variant as accepted on the command line
+            pub fn as_str(&self) -> &'static str {
+                match self {
+                    $($obj::$variant => $name,)+
+                }
+            }
+        }
+
+        impl fmt::Display for $obj {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "{}", self.as_str())
+            }
+        }
+    };
+}
+
+/// FromStr for a design enum liquid has no getopt helper for
+macro_rules! enum_from_str_impl {
+    ($obj:ident, $what:expr, [$($variant:ident),+ $(,)?]) => {
+        impl FromStr for $obj {
+            type Err = LiquidError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                [$($obj::$variant),+]
+                    .iter()
+                    .find(|v| v.as_str() == s)
+                    .copied()
+                    .ok_or_else(|| {
+                        FilterError::InvalidValue(format!("unknown {}: {}", $what, s)).into()
+                    })
+            }
+        }
+    };
+}
+
+enum_str_impl!(
+    FirdespmBtype,
+    [
+        BANDPASS => "bandpass",
+        DIFFERENTIATOR => "differentiator",
+        HILBERT => "hilbert",
+    ]
+);
+enum_from_str_impl!(
+    FirdespmBtype,
+    "firdespm band type",
+    [BANDPASS, DIFFERENTIATOR, HILBERT]
+);
+
+enum_str_impl!(
+    IirdesFilterType,
+    [
+        BUTTER => "butter",
+        CHEBY1 => "cheby1",
+        CHEBY2 => "cheby2",
+        ELLIP => "ellip",
+        BESSEL => "bessel",
+    ]
+);
+enum_from_str_impl!(
+    IirdesFilterType,
+    "iir filter type",
+    [BUTTER, CHEBY1, CHEBY2, ELLIP, BESSEL]
+);
+
+enum_str_impl!(
+    IirdesBandType,
+    [
+        LOWPASS => "lowpass",
+        HIGHPASS => "highpass",
+        BANDPASS => "bandpass",
+        BANDSTOP => "bandstop",
+    ]
+);
+enum_from_str_impl!(
+    IirdesBandType,
+    "iir band type",
+    [LOWPASS, HIGHPASS, BANDPASS, BANDSTOP]
+);
+
+enum_str_impl!(IirdesFormat, [SOS => "sos", TF => "tf"]);
+enum_from_str_impl!(IirdesFormat, "iir filter format", [SOS, TF]);
+
+enum_str_impl!(
+    FirdesFilterType,
+    [
+        Unknown => "unknown",
+        Kaiser => "kaiser",
+        Pm => "pm",
+        Rcos => "rcos",
+        Fexp => "fexp",
+        Fsech => "fsech",
+        FarcSech => "farcsech",
+        Arkaiser => "arkaiser",
+        Rkaiser => "rkaiser",
+        Rrc => "rrcos",
+        Hm3 => "hM3",
+        Gmsktx => "gmsktx",
+        Gmskrx => "gmskrx",
+        Rfexp => "rfexp",
+        Rfsech => "rfsech",
+        RfarcSech => "rfarcsech",
+    ]
+);
+
+impl FromStr for FirdesFilterType {
+    type Err = LiquidError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || FilterError::InvalidValue(format!("unknown fir filter type: {}", s));
+        let c = CString::new(s).map_err(|_| err())?;
+        let index = unsafe { raw::liquid_getopt_str2firfilt(c.as_ptr()) };
+        [
+            FirdesFilterType::Kaiser,
+            FirdesFilterType::Pm,
+            FirdesFilterType::Rcos,
+            FirdesFilterType::Fexp,
+            FirdesFilterType::Fsech,
+            FirdesFilterType::FarcSech,
+            FirdesFilterType::Arkaiser,
+            FirdesFilterType::Rkaiser,
+            FirdesFilterType::Rrc,
+            FirdesFilterType::Hm3,
+            FirdesFilterType::Gmsktx,
+            FirdesFilterType::Gmskrx,
+            FirdesFilterType::Rfexp,
+            FirdesFilterType::Rfsech,
+            FirdesFilterType::RfarcSech,
+        ]
+        .iter()
+        .find(|&&v| u8::from(v) as i32 == index)
+        .copied()
+        .ok_or_else(|| err().into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        FirdesFilterType, FirdespmBtype, FirdespmWtype, IirdesBandType, IirdesFilterType,
+        IirdesFormat,
+    };
+    use std::convert::TryFrom;
+
+    #[test]
+    fn test_design_enums_str() {
+        for &v in [
+            IirdesFilterType::BUTTER,
+            IirdesFilterType::CHEBY1,
+            IirdesFilterType::CHEBY2,
+            IirdesFilterType::ELLIP,
+            IirdesFilterType::BESSEL,
+        ]
+        .iter()
+        {
+            assert_eq!(v.to_string().parse::<IirdesFilterType>().unwrap(), v);
+        }
+        assert_eq!(
+            "bandstop".parse::<IirdesBandType>().unwrap(),
+            IirdesBandType::BANDSTOP
+        );
+        assert_eq!(IirdesFormat::SOS.to_string(), "sos");
+        assert_eq!("tf".parse::<IirdesFormat>().unwrap(), IirdesFormat::TF);
+        assert_eq!(
+            "hilbert".parse::<FirdespmBtype>().unwrap(),
+            FirdespmBtype::HILBERT
+        );
+        assert!("butterworth".parse::<IirdesFilterType>().is_err());
+    }
+    #[test]
+    fn test_design_enums_try_from() {
+        for value in 0..3u8 {
+            assert_eq!(u8::from(FirdespmBtype::try_from(value).unwrap()), value);
+        }
+        assert!(FirdespmBtype::try_from(3).is_err());
+        for value in 0..3u32 {
+            assert_eq!(u32::from(FirdespmWtype::try_from(value).unwrap()), value);
+        }
+        assert!(FirdespmWtype::try_from(3).is_err());
+        for value in 0..5u8 {
+            assert_eq!(u8::from(IirdesFilterType::try_from(value).unwrap()), value);
+        }
+        assert!(IirdesFilterType::try_from(5).is_err());
+        assert_eq!(
+            IirdesBandType::try_from(3).unwrap(),
+            IirdesBandType::BANDSTOP
+        );
+        assert!(IirdesBandType::try_from(4).is_err());
+        assert_eq!(IirdesFormat::try_from(1).unwrap(), IirdesFormat::TF);
+        assert!(IirdesFormat::try_from(2).is_err());
+        for value in 0..16u8 {
+            assert_eq!(u8::from(FirdesFilterType::try_from(value).unwrap()), value);
+        }
+        assert_eq!(
+            FirdesFilterType::try_from(9).unwrap(),
+            FirdesFilterType::Rrc
+        );
+        assert!(FirdesFilterType::try_from(16).is_err());
     }
 }
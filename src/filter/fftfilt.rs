@@ -1,23 +1,31 @@
 //! *fftfilt* : finite impulse response (FIR) filter using fast Fourier
 //!           transforms (FFTs)
 use num::complex::Complex32;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
 
 use crate::liquid_dsp_sys as raw;
 use crate::utils::{ToCPointer, ToCPointerMut, ToCValue};
 
-use crate::errors::LiquidError;
+use crate::errors::FilterError;
 use crate::LiquidResult;
 
 pub struct FftFiltRrrf {
     inner: raw::fftfilt_rrrf,
+    h: Vec<f32>,
+    n: usize,
 }
 
 pub struct FftFiltCrcf {
     inner: raw::fftfilt_crcf,
+    h: Vec<f32>,
+    n: usize,
 }
 
 pub struct FftFiltCccf {
     inner: raw::fftfilt_cccf,
+    h: Vec<Complex32>,
+    n: usize,
 }
 
 macro_rules! fftfilt_impl {
@@ -46,23 +54,45 @@ macro_rules! fftfilt_impl {
             pub fn len(&self) -> usize {
                 unsafe { $len(self.inner) as usize }
             }
+
+            pub fn is_empty(&self) -> bool {
+                self.len() == 0
+            }
+
+            /// filter coefficients, without the output scaling
+            pub fn coefficients(&self) -> &[$type] {
+                &self.h
+            }
             /// create FFT-based FIR filter using external coefficients
             ///  h      : filter coefficients [size: _h_len x 1]
             ///  n      : block size = nfft/2, at least _h_len-1
             pub fn create(h: &[$type], n: usize) -> LiquidResult<Self> {
                 if h.is_empty() {
-                    return Err(LiquidError::InvalidLength {
+                    return Err(FilterError::InvalidLength {
                         description: "filter length must be greater than zero".to_owned(),
-                    });
+                    }
+                    .into());
                 } else if n < h.len() - 1 {
-                    return Err(LiquidError::InvalidValue(
+                    return Err(FilterError::InvalidValue(
                         "block length must be greater than h.len()-1".to_owned(),
-                    ));
+                    )
+                    .into());
                 }
 
-                Ok(Self {
+                Ok(Self::build(h, n))
+            }
+
+            fn build(h: &[$type], n: usize) -> Self {
+                Self {
                     inner: unsafe { $create(h.to_ptr() as _, h.len() as _, n as _) },
-                })
+                    h: h.to_vec(),
+                    n,
+                }
+            }
+
+            /// get the block size of the filter
+            pub fn block_len(&self) -> usize {
+                self.n
             }
 
             /// set output scaling for filter
@@ -84,12 +114,97 @@ macro_rules! fftfilt_impl {
             /// execute the filter on internal buffer and coefficients
             ///  x      : pointer to input data array  [size: _n x 1]
             ///  y      : pointer to output data array [size: _n x 1]
-            pub fn execute(&self, x: &[$type2], y: &mut [$type2]) {
-                assert!(x.len() == y.len(), "x and y must be the same length");
+            pub fn execute(&mut self, x: &[$type2], y: &mut [$type2]) {
+                // liquid reads and writes exactly one block of n samples
+                assert!(
+                    x.len() == self.n && y.len() == self.n,
+                    "x and y length must be the block size"
+                );
                 unsafe {
                     $execute(self.inner, x.to_ptr() as _, y.to_ptr_mut());
                 }
             }
+
+            /// filter one block of samples in place, the output
+            /// overwriting the input; liquid copies the block before
+            /// writing the output
+            ///  x      :   input/output array [size: n x 1]
+            pub fn execute_block_in_place(&mut self, x: &mut [$type2]) {
+                assert!(x.len() == self.n, "x length must be the block size");
+                unsafe {
+                    $execute(self.inner, x.to_ptr_mut(), x.to_ptr_mut());
+                }
+            }
+
+            /// filter a long capture on the rayon thread pool. The input is
+            /// split into tasks of blocks_per_task blocks, each one filtered
+            /// by an independent filter object primed with the block that
+            /// precedes the task, so the output matches calling execute on
+            /// every block in order on a freshly reset filter.
+            /// The state of this filter is left untouched.
+            ///  x      : input data array  [size: k*n x 1]
+            ///  y      : output data array [size: k*n x 1]
+            ///  blocks_per_task : number of blocks filtered by each task
+            #[cfg(feature = "rayon")]
+            pub fn execute_block_par(
+                &self,
+                x: &[$type2],
+                y: &mut [$type2],
+                blocks_per_task: usize,
+            ) -> LiquidResult<()> {
+                let n = self.n;
+                if x.len() != y.len() || x.len() % n != 0 {
+                    return Err(FilterError::InvalidLength {
+                        description: format!(
+                            "x length: {} and y length: {} must be equal multiples of the block size {}",
+                            x.len(),
+                            y.len(),
+                            n
+                        ),
+                    }
+                    .into());
+                } else if blocks_per_task == 0 {
+                    return Err(FilterError::InvalidValue(
+                        "blocks per task must be greater than zero".to_owned(),
+                    )
+                    .into());
+                }
+
+                let (h, scale) = (&self.h[..], self.get_scale());
+                let task_len = n * blocks_per_task;
+                x.par_chunks(task_len)
+                    .zip(y.par_chunks_mut(task_len))
+                    .enumerate()
+                    .for_each_init(
+                        || {
+                            let mut filter = Self::build(h, n);
+                            filter.set_scale(scale);
+                            (filter, vec![<$type2>::default(); n])
+                        },
+                        |(filter, scratch), (i, (xt, yt))| {
+                            filter.reset();
+                            if i > 0 {
+                                let start = i * task_len - n;
+                                filter.execute(&x[start..start + n], scratch);
+                            }
+                            xt.chunks(n)
+                                .zip(yt.chunks_mut(n))
+                                .for_each(|(xb, yb)| filter.execute(xb, yb));
+                        },
+                    );
+                Ok(())
+            }
+        }
+
+        impl Clone for $obj {
+            /// create an independent filter with the same coefficients,
+            /// block size and scaling. The internal buffer of the new
+            /// filter starts reset.
+            fn clone(&self) -> Self {
+                let mut filter = Self::build(&self.h, self.n);
+                filter.set_scale(self.get_scale());
+                filter
+            }
         }
 
         impl Drop for $obj {
@@ -146,3 +261,59 @@ fftfilt_impl!(
         f32, f32
     )
 );
+
+#[cfg(test)]
+mod tests {
+    use super::FftFiltRrrf;
+
+    #[test]
+    fn test_fftfilt_clone() {
+        let h = [0.25f32; 4];
+        let filter = FftFiltRrrf::create(&h, 8).unwrap();
+        let copy = filter.clone();
+        assert_eq!(copy.len(), filter.len());
+        assert_eq!(copy.block_len(), 8);
+    }
+
+    #[test]
+    fn test_fftfilt_execute_block_in_place() {
+        let h = [0.5f32, 0.25, 0.125];
+        let x: Vec<f32> = (0..16).map(|i| i as f32).collect();
+        let mut filter = FftFiltRrrf::create(&h, 8).unwrap();
+        let mut y = [0f32; 16];
+        x.chunks(8)
+            .zip(y.chunks_mut(8))
+            .for_each(|(xb, yb)| filter.execute(xb, yb));
+
+        let mut filter = FftFiltRrrf::create(&h, 8).unwrap();
+        let mut z = x.clone();
+        z.chunks_mut(8)
+            .for_each(|b| filter.execute_block_in_place(b));
+        y.iter()
+            .zip(z.iter())
+            .for_each(|(a, b)| assert!((a - b).abs() < 1e-5));
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_fftfilt_execute_block_par() {
+        let h: Vec<f32> = (0..7).map(|i| 1.0 / (i as f32 + 1.0)).collect();
+        let n = 8;
+        let x: Vec<f32> = (0..10 * n).map(|i| ((i * 7) % 13) as f32 - 6.0).collect();
+
+        let mut filter = FftFiltRrrf::create(&h, n).unwrap();
+        let mut expected = vec![0f32; x.len()];
+        x.chunks(n)
+            .zip(expected.chunks_mut(n))
+            .for_each(|(xb, yb)| filter.execute(xb, yb));
+
+        let filter = FftFiltRrrf::create(&h, n).unwrap();
+        let mut y = vec![0f32; x.len()];
+        filter.execute_block_par(&x, &mut y, 3).unwrap();
+        y.iter()
+            .zip(expected.iter())
+            .for_each(|(a, b)| assert!((a - b).abs() < 1e-4));
+
+        assert!(filter.execute_block_par(&x[1..], &mut y[1..], 3).is_err());
+    }
+}
@@ -1,3 +1,9 @@
+use std::f32::consts::PI;
+
+use num::complex::Complex32;
+
+use crate::LiquidResult;
+
 pub trait FilterAnalysis
 where
     Self: AsRef<[f32]>,
@@ -31,3 +37,46 @@ where
     ///  nfft   :   fft size
     fn energy(&self, fc: f32, nfft: usize) -> f32;
 }
+
+/// Frequency domain analysis shared by the FIR and IIR designs
+pub trait FrequencyResponse {
+    /// Compute the complex frequency response
+    ///
+    ///  fc     :   normalized frequency, in [-0.5, 0.5]
+    fn freq_response(&self, fc: f32) -> LiquidResult<Complex32>;
+
+    /// Compute the group delay [samples]
+    ///
+    ///  fc     :   normalized frequency, in [-0.5, 0.5]
+    fn group_delay(&self, fc: f32) -> LiquidResult<f32>;
+
+    /// Render the magnitude response [dB] over [0, 0.5] as an SVG file
+    #[cfg(feature = "plot")]
+    fn plot_response<P: AsRef<std::path::Path>>(&self, path: P) -> std::io::Result<()> {
+        let f: Vec<f32> = (0..=500).map(|i| i as f32 / 1000.0).collect();
+        let mag = f
+            .iter()
+            .map(|&fc| {
+                self.freq_response(fc)
+                    .map(|h| 20.0 * h.norm().log10())
+                    .map_err(|e| {
+                        std::io::Error::new(std::io::ErrorKind::InvalidInput, e.to_string())
+                    })
+            })
+            .collect::<std::io::Result<Vec<f32>>>()?;
+        let labels = crate::plot::Labels {
+            title: "frequency response",
+            x: "normalized frequency",
+            y: "magnitude [dB]",
+        };
+        crate::plot::save_lines(path, labels, &f, &[("magnitude", &mag)])
+    }
+}
+
+/// evaluates sum(c[n] * exp(-j*2*pi*fc*n))
+pub(crate) fn polyval_unit_circle(c: &[f32], fc: f32) -> Complex32 {
+    c.iter()
+        .enumerate()
+        .map(|(n, &v)| Complex32::from_polar(v, -2.0 * PI * fc * n as f32))
+        .sum()
+}
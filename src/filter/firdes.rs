@@ -1,12 +1,18 @@
-use crate::errors::LiquidError;
+use num::complex::Complex32;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::enums::WindowType;
+use crate::errors::FilterError;
+use crate::filter::filter::polyval_unit_circle;
+use crate::filter::{FilterAnalysis, FirdesFilterType, FrequencyResponse};
 use crate::liquid_dsp_sys as raw;
-use filter::FirdesFilterType;
 
 use crate::utils::ToCPointerMut;
 use crate::LiquidResult;
-pub use filter::FilterAnalysis;
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Fir {
     h: Vec<f32>,
 }
@@ -20,13 +26,15 @@ impl Fir {
         self.h.len()
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
     /// Compute group delay for a FIR filter
     ///  fc     : frequency at which delay is evaluated (-0.5 < _fc < 0.5)
     pub fn group_delay(&self, fc: f32) -> LiquidResult<f32> {
-        if fc < -0.5 || fc > 0.5 {
-            return Err(LiquidError::InvalidValue(
-                "fc must be in [0, 0.5]".to_owned(),
-            ));
+        if !(-0.5..=0.5).contains(&fc) {
+            return Err(FilterError::InvalidValue("fc must be in [-0.5, 0.5]".to_owned()).into());
         }
         unsafe {
             Ok(raw::fir_group_delay(
@@ -36,6 +44,44 @@ impl Fir {
             ))
         }
     }
+
+    /// Compute the complex frequency response at any frequency, the
+    /// response being periodic in fc; FrequencyResponse::freq_response is
+    /// the range-checked version
+    ///  fc     : normalized frequency
+    pub fn response(&self, fc: f32) -> Complex32 {
+        polyval_unit_circle(&self.h, fc)
+    }
+
+    /// Compute the magnitude response [dB] on nfft frequencies evenly
+    /// spaced over [-0.5, 0.5), i.e. in the order of an fftshifted
+    /// spectrum, fc = k/nfft - 0.5 for the k-th value
+    pub fn response_curve(&self, nfft: usize) -> Vec<f32> {
+        (0..nfft)
+            .map(|k| {
+                let fc = k as f32 / nfft as f32 - 0.5;
+                20.0 * self.response(fc).norm().log10()
+            })
+            .collect()
+    }
+
+    /// Impulse response, i.e. the filter coefficients
+    pub fn impulse_response(&self) -> Vec<f32> {
+        self.h.clone()
+    }
+
+    /// Step response, the running sum of the coefficients
+    ///  n      : number of output samples, the response staying at the
+    ///           DC gain past the filter length
+    pub fn step_response(&self, n: usize) -> Vec<f32> {
+        let mut sum = 0f32;
+        (0..n)
+            .map(|i| {
+                sum += self.h.get(i).cloned().unwrap_or_default();
+                sum
+            })
+            .collect()
+    }
 }
 
 impl AsRef<[f32]> for Fir {
@@ -50,6 +96,19 @@ impl AsMut<[f32]> for Fir {
     }
 }
 
+impl FrequencyResponse for Fir {
+    fn freq_response(&self, fc: f32) -> LiquidResult<Complex32> {
+        if !(-0.5..=0.5).contains(&fc) {
+            return Err(FilterError::InvalidValue("fc must be in [-0.5, 0.5]".to_owned()).into());
+        }
+        Ok(self.response(fc))
+    }
+
+    fn group_delay(&self, fc: f32) -> LiquidResult<f32> {
+        Fir::group_delay(self, fc)
+    }
+}
+
 impl FilterAnalysis for Fir {
     fn auto_corr(&self, lag: usize) -> f32 {
         unsafe {
@@ -108,13 +167,14 @@ impl Firdes {
     ///  as_    :   stopband suppression level [dB] (_As > 0)
     pub fn estimate_filter_len(df: f32, as_: f32) -> LiquidResult<usize> {
         if df > 0.5 || df <= 0f32 {
-            return Err(LiquidError::InvalidValue(
+            return Err(FilterError::InvalidValue(
                 "invalid bandwidth, valid values are (0, 0.5)".to_owned(),
-            ));
+            )
+            .into());
         } else if as_ <= 0f32 {
-            return Err(LiquidError::InvalidValue(
-                "invalid stopband level, as > 0".to_owned(),
-            ));
+            return Err(
+                FilterError::InvalidValue("invalid stopband level, as > 0".to_owned()).into(),
+            );
         }
         unsafe { Ok(raw::estimate_req_filter_len(df, as_) as usize) }
     }
@@ -124,9 +184,10 @@ impl Firdes {
     ///  n      :   filter length
     pub fn estimate_filter_as(df: f32, n: usize) -> LiquidResult<f32> {
         if df > 0.5 || df <= 0f32 {
-            return Err(LiquidError::InvalidValue(
+            return Err(FilterError::InvalidValue(
                 "invalid bandwidth, valid values are (0, 0.5)".to_owned(),
-            ));
+            )
+            .into());
         }
         unsafe { Ok(raw::estimate_req_filter_As(df, n as _)) }
     }
@@ -136,9 +197,10 @@ impl Firdes {
     ///  n      :   filter length
     pub fn estimate_filter_df(as_: f32, n: usize) -> LiquidResult<f32> {
         if as_ <= 0f32 {
-            return Err(LiquidError::InvalidValue(
+            return Err(FilterError::InvalidValue(
                 "stop-band attenuation must be greater than 0".to_owned(),
-            ));
+            )
+            .into());
         }
         unsafe { Ok(raw::estimate_req_filter_df(as_, n as _)) }
     }
@@ -170,20 +232,21 @@ impl Firdes {
     ///  f0     : filter notch frequency (normalized), -0.5 <= _fc <= 0.5
     ///  as_    : stop-band attenuation [dB], _As > 0
     pub fn notch(m: usize, f0: f32, as_: f32) -> LiquidResult<Fir> {
-        if m < 1 || m > 1000 {
-            return Err(LiquidError::InvalidValue(format!(
-                "m: {} out of range [1,1000]",
-                m
-            )));
-        } else if f0 < -0.5 || f0 > 0.5 {
-            return Err(LiquidError::InvalidValue(format!(
+        if !(1..=1000).contains(&m) {
+            return Err(
+                FilterError::InvalidValue(format!("m: {} out of range [1,1000]", m)).into(),
+            );
+        } else if !(-0.5..=0.5).contains(&f0) {
+            return Err(FilterError::InvalidValue(format!(
                 "notch frequency {} must be in [-0.5,0.5]",
                 f0
-            )));
+            ))
+            .into());
         } else if as_ <= 0f32 {
-            return Err(LiquidError::InvalidValue(
+            return Err(FilterError::InvalidValue(
                 "as_ stop-band suppression must be greater than zero".to_owned(),
-            ));
+            )
+            .into());
         }
         let mut filter = Fir::new(2 * m + 1);
         unsafe {
@@ -198,18 +261,18 @@ impl Firdes {
     ///  As     : stop-band attenuation [dB], _As > 0
     ///  mu     : fractional sample offset, -0.5 < _mu < 0.5
     pub fn kaiser(n: usize, fc: f32, as_: f32, mu: f32) -> LiquidResult<Fir> {
-        if mu < -0.5 || mu > 0.5 {
-            return Err(LiquidError::InvalidValue(
-                "mu out of range [-0.5,0.5]".to_owned(),
-            ));
-        } else if fc < 0f32 || fc > 0.5 {
-            return Err(LiquidError::InvalidValue(
+        if !(-0.5..=0.5).contains(&mu) {
+            return Err(FilterError::InvalidValue("mu out of range [-0.5,0.5]".to_owned()).into());
+        } else if !(0f32..=0.5).contains(&fc) {
+            return Err(FilterError::InvalidValue(
                 "cutoff frequency out of range (0, 0.5)".to_owned(),
-            ));
+            )
+            .into());
         } else if n == 0 {
-            return Err(LiquidError::InvalidValue(
+            return Err(FilterError::InvalidValue(
                 "filter length must be greater than zero".to_owned(),
-            ));
+            )
+            .into());
         }
         let mut filter = Fir::new(n);
         unsafe {
@@ -217,6 +280,103 @@ impl Firdes {
         }
         Ok(filter)
     }
+
+    /// Design FIR using kaiser window, computed in double precision
+    ///  n      : filter length, _n > 0
+    ///  fc     : cutoff frequency, 0 < _fc < 0.5
+    ///  As     : stop-band attenuation [dB], _As > 0
+    ///  mu     : fractional sample offset, -0.5 < _mu < 0.5
+    pub fn kaiser_f64(n: usize, fc: f64, as_: f64, mu: f64) -> LiquidResult<Vec<f64>> {
+        if !(-0.5..=0.5).contains(&mu) {
+            return Err(FilterError::InvalidValue("mu out of range [-0.5,0.5]".to_owned()).into());
+        } else if !(0.0..=0.5).contains(&fc) {
+            return Err(FilterError::InvalidValue(
+                "cutoff frequency out of range (0, 0.5)".to_owned(),
+            )
+            .into());
+        } else if n == 0 {
+            return Err(FilterError::InvalidValue(
+                "filter length must be greater than zero".to_owned(),
+            )
+            .into());
+        }
+        // same design as liquid_firdes_kaiser: windowed sinc, with the
+        // window shape beta derived from the stop-band attenuation
+        let beta = if as_ > 50.0 {
+            0.1102 * (as_ - 8.7)
+        } else if as_ > 21.0 {
+            0.5842 * (as_ - 21.0).powf(0.4) + 0.07886 * (as_ - 21.0)
+        } else {
+            0.0
+        };
+        let half = (n as f64 - 1.0) / 2.0;
+        Ok((0..n)
+            .map(|i| {
+                let t = i as f64 - half + mu;
+                let r = 2.0 * (i as f64 - half) / n as f64;
+                let w = besseli0(beta * (1.0 - r * r).max(0.0).sqrt()) / besseli0(beta);
+                sinc(2.0 * fc * t) * w
+            })
+            .collect())
+    }
+
+    /// Design windowed-sinc low-pass filter, with unity gain at DC
+    ///  n      : filter length, _n > 0
+    ///  fc     : cutoff frequency, 0 < _fc < 0.5
+    ///  wtype  : window type, e.g. WindowType::HAMMING
+    ///  as_    : stop-band attenuation [dB] setting the shape of the
+    ///           KAISER and KBD windows, ignored by the others
+    pub fn lowpass(n: usize, fc: f32, wtype: WindowType, as_: f32) -> LiquidResult<Fir> {
+        check_window_band(fc, 0.0)?;
+        let fc = fc as f64;
+        windowed(n, wtype, as_, 0.0, |t| 2.0 * fc * sinc(2.0 * fc * t))
+    }
+
+    /// Design windowed-sinc high-pass filter, with unity gain at Nyquist
+    ///  n      : filter length, odd
+    ///  fc     : cutoff frequency, 0 < _fc < 0.5
+    ///  wtype  : window type, e.g. WindowType::HAMMING
+    ///  as_    : stop-band attenuation [dB] setting the shape of the
+    ///           KAISER and KBD windows, ignored by the others
+    pub fn highpass(n: usize, fc: f32, wtype: WindowType, as_: f32) -> LiquidResult<Fir> {
+        check_window_band(fc, 0.0)?;
+        check_odd_len(n)?;
+        let fc = fc as f64;
+        windowed(n, wtype, as_, 0.5, |t| {
+            delta(t) - 2.0 * fc * sinc(2.0 * fc * t)
+        })
+    }
+
+    /// Design windowed-sinc band-pass filter, with unity gain at the
+    /// center frequency
+    ///  n      : filter length, _n > 0
+    ///  f0     : center frequency, in (0, 0.5)
+    ///  bw     : bandwidth, the pass-band [f0-bw/2, f0+bw/2] being in
+    ///           (0, 0.5)
+    ///  wtype  : window type, e.g. WindowType::HAMMING
+    ///  as_    : stop-band attenuation [dB] setting the shape of the
+    ///           KAISER and KBD windows, ignored by the others
+    pub fn bandpass(n: usize, f0: f32, bw: f32, wtype: WindowType, as_: f32) -> LiquidResult<Fir> {
+        check_window_band(f0, bw)?;
+        let (f0, bw) = (f0 as f64, bw as f64);
+        windowed(n, wtype, as_, f0, |t| bandpass_ideal(t, f0, bw))
+    }
+
+    /// Design windowed-sinc band-stop filter, with unity gain at DC
+    ///  n      : filter length, odd
+    ///  f0     : center frequency of the stop band, in (0, 0.5)
+    ///  bw     : bandwidth, the stop-band [f0-bw/2, f0+bw/2] being in
+    ///           (0, 0.5)
+    ///  wtype  : window type, e.g. WindowType::HAMMING
+    ///  as_    : stop-band attenuation [dB] setting the shape of the
+    ///           KAISER and KBD windows, ignored by the others
+    pub fn bandstop(n: usize, f0: f32, bw: f32, wtype: WindowType, as_: f32) -> LiquidResult<Fir> {
+        check_window_band(f0, bw)?;
+        check_odd_len(n)?;
+        let (f0, bw) = (f0 as f64, bw as f64);
+        windowed(n, wtype, as_, 0.0, |t| delta(t) - bandpass_ideal(t, f0, bw))
+    }
+
     /// Design frequency-shifted root-Nyquist filter based on
     /// the Kaiser-windowed sinc.
     ///
@@ -226,23 +386,15 @@ impl Firdes {
     ///  dt     :   filter fractional sample delay
     pub fn rkaiser(k: usize, m: usize, beta: f32, dt: f32) -> LiquidResult<Fir> {
         if k < 2 {
-            return Err(LiquidError::InvalidValue(
-                "k must be at least 2".to_owned(),
-            ));
+            return Err(FilterError::InvalidValue("k must be at least 2".to_owned()).into());
         } else if m < 1 {
-            return Err(LiquidError::InvalidValue(
-                "m must be at least 1".to_owned(),
-            ));
+            return Err(FilterError::InvalidValue("m must be at least 1".to_owned()).into());
         } else if beta <= 0.0 || beta >= 1.0 {
-            return Err(LiquidError::InvalidValue(
-                "beta must be in (0,1)".to_owned(),
-            ));
-        } else if dt < -1.0 || dt > 1.0 {
-            return Err(LiquidError::InvalidValue(
-                "dt must be in [-1,1]".to_owned(),
-            ));
-        }
-        let mut filter = Fir::new((2 * k * m + 1) as usize);
+            return Err(FilterError::InvalidValue("beta must be in (0,1)".to_owned()).into());
+        } else if !(-1.0..=1.0).contains(&dt) {
+            return Err(FilterError::InvalidValue("dt must be in [-1,1]".to_owned()).into());
+        }
+        let mut filter = Fir::new(2 * k * m + 1);
         unsafe {
             raw::liquid_firdes_rkaiser(k as _, m as _, beta, dt, filter.as_mut().as_mut_ptr());
         }
@@ -258,23 +410,15 @@ impl Firdes {
     ///  dt     :   filter fractional sample delay
     pub fn arkaiser(k: usize, m: usize, beta: f32, dt: f32) -> LiquidResult<Fir> {
         if k < 2 {
-            return Err(LiquidError::InvalidValue(
-                "k must be at least 2".to_owned(),
-            ));
+            return Err(FilterError::InvalidValue("k must be at least 2".to_owned()).into());
         } else if m < 1 {
-            return Err(LiquidError::InvalidValue(
-                "m must be at least 1".to_owned(),
-            ));
+            return Err(FilterError::InvalidValue("m must be at least 1".to_owned()).into());
         } else if beta <= 0.0 || beta >= 1.0 {
-            return Err(LiquidError::InvalidValue(
-                "beta must be in (0,1)".to_owned(),
-            ));
-        } else if dt < -1.0 || dt > 1.0 {
-            return Err(LiquidError::InvalidValue(
-                "dt must be in [-1,1]".to_owned(),
-            ));
-        }
-        let mut filter = Fir::new((2 * k * m + 1) as usize);
+            return Err(FilterError::InvalidValue("beta must be in (0,1)".to_owned()).into());
+        } else if !(-1.0..=1.0).contains(&dt) {
+            return Err(FilterError::InvalidValue("dt must be in [-1,1]".to_owned()).into());
+        }
+        let mut filter = Fir::new(2 * k * m + 1);
         unsafe {
             raw::liquid_firdes_arkaiser(k as _, m as _, beta, dt, filter.as_mut().as_mut_ptr());
         }
@@ -288,13 +432,9 @@ impl Firdes {
     ///  theta  : LoS component angle of arrival
     pub fn doppler(n: usize, fd: f32, k: f32, theta: f32) -> LiquidResult<Fir> {
         if fd <= 0f32 || fd > 0.5 {
-            return Err(LiquidError::InvalidValue(
-                "fd must be in (0, 0.5)".to_owned(),
-            ));
+            return Err(FilterError::InvalidValue("fd must be in (0, 0.5)".to_owned()).into());
         } else if k < 0f32 {
-            return Err(LiquidError::InvalidValue(
-                "k must be greater than 0".to_owned(),
-            ));
+            return Err(FilterError::InvalidValue("k must be greater than 0".to_owned()).into());
         }
 
         // there seem not to be an FirdesFilterType for this kinf of filter
@@ -314,17 +454,11 @@ impl Firdes {
     ///  dt     : fractional sample delay
     pub fn rcos(k: usize, m: usize, beta: f32, dt: f32) -> LiquidResult<Fir> {
         if k < 1 {
-            return Err(LiquidError::InvalidValue(
-                "k must be greater than 0".to_owned(),
-            ));
+            return Err(FilterError::InvalidValue("k must be greater than 0".to_owned()).into());
         } else if m < 1 {
-            return Err(LiquidError::InvalidValue(
-                "m must be greater than 0".to_owned(),
-            ));
-        } else if beta < 0f32 || beta > 1.0 {
-            return Err(LiquidError::InvalidValue(
-                "beta must be in [0, 1.0]".to_owned(),
-            ));
+            return Err(FilterError::InvalidValue("m must be greater than 0".to_owned()).into());
+        } else if !(0f32..=1.0).contains(&beta) {
+            return Err(FilterError::InvalidValue("beta must be in [0, 1.0]".to_owned()).into());
         }
         let mut filter = Fir::new(2 * k * m + 1);
         unsafe {
@@ -340,17 +474,11 @@ impl Firdes {
     //  dt     : fractional sample delay
     pub fn rrcos(k: usize, m: usize, beta: f32, dt: f32) -> LiquidResult<Fir> {
         if k < 1 {
-            return Err(LiquidError::InvalidValue(
-                "k must be greater than 0".to_owned(),
-            ));
+            return Err(FilterError::InvalidValue("k must be greater than 0".to_owned()).into());
         } else if m < 1 {
-            return Err(LiquidError::InvalidValue(
-                "m must be greater than 0".to_owned(),
-            ));
-        } else if beta < 0f32 || beta > 1.0 {
-            return Err(LiquidError::InvalidValue(
-                "beta must be in [0, 1.0]".to_owned(),
-            ));
+            return Err(FilterError::InvalidValue("m must be greater than 0".to_owned()).into());
+        } else if !(0f32..=1.0).contains(&beta) {
+            return Err(FilterError::InvalidValue("beta must be in [0, 1.0]".to_owned()).into());
         }
         let mut filter = Fir::new(2 * k * m + 1);
         unsafe {
@@ -361,17 +489,11 @@ impl Firdes {
 
     pub fn hm3(k: usize, m: usize, beta: f32, dt: f32) -> LiquidResult<Fir> {
         if k < 2 {
-            return Err(LiquidError::InvalidValue(
-                "k must be greater than 1".to_owned(),
-            ));
+            return Err(FilterError::InvalidValue("k must be greater than 1".to_owned()).into());
         } else if m < 1 {
-            return Err(LiquidError::InvalidValue(
-                "m must be greater than 0".to_owned(),
-            ));
-        } else if beta < 0f32 || beta > 1.0 {
-            return Err(LiquidError::InvalidValue(
-                "beta must be in [0, 1.0]".to_owned(),
-            ));
+            return Err(FilterError::InvalidValue("m must be greater than 0".to_owned()).into());
+        } else if !(0f32..=1.0).contains(&beta) {
+            return Err(FilterError::InvalidValue("beta must be in [0, 1.0]".to_owned()).into());
         }
         let mut filter = Fir::new(2 * k * m + 1);
         unsafe {
@@ -387,17 +509,11 @@ impl Firdes {
     ///  dt     : fractional sample delay
     pub fn gmsktx(k: usize, m: usize, beta: f32, dt: f32) -> LiquidResult<Fir> {
         if k < 1 {
-            return Err(LiquidError::InvalidValue(
-                "k must be greater than 0".to_owned(),
-            ));
+            return Err(FilterError::InvalidValue("k must be greater than 0".to_owned()).into());
         } else if m < 1 {
-            return Err(LiquidError::InvalidValue(
-                "m must be greater than 0".to_owned(),
-            ));
-        } else if beta < 0f32 || beta > 1.0 {
-            return Err(LiquidError::InvalidValue(
-                "beta must be in [0, 1.0]".to_owned(),
-            ));
+            return Err(FilterError::InvalidValue("m must be greater than 0".to_owned()).into());
+        } else if !(0f32..=1.0).contains(&beta) {
+            return Err(FilterError::InvalidValue("beta must be in [0, 1.0]".to_owned()).into());
         }
         let mut filter = Fir::new(2 * k * m + 1);
         unsafe {
@@ -413,17 +529,11 @@ impl Firdes {
     ///  dt     : fractional sample delay
     pub fn gmskrx(k: usize, m: usize, beta: f32, dt: f32) -> LiquidResult<Fir> {
         if k < 1 {
-            return Err(LiquidError::InvalidValue(
-                "k must be greater than 0".to_owned(),
-            ));
+            return Err(FilterError::InvalidValue("k must be greater than 0".to_owned()).into());
         } else if m < 1 {
-            return Err(LiquidError::InvalidValue(
-                "m must be greater than 0".to_owned(),
-            ));
-        } else if beta < 0f32 || beta > 1.0 {
-            return Err(LiquidError::InvalidValue(
-                "beta must be in [0, 1.0]".to_owned(),
-            ));
+            return Err(FilterError::InvalidValue("m must be greater than 0".to_owned()).into());
+        } else if !(0f32..=1.0).contains(&beta) {
+            return Err(FilterError::InvalidValue("beta must be in [0, 1.0]".to_owned()).into());
         }
         let mut filter = Fir::new(2 * k * m + 1);
         unsafe {
@@ -438,10 +548,8 @@ impl Firdes {
     ///  beta   : rolloff factor (0 < beta <= 1)
     ///  dt     : fractional sample delay
     pub fn fexp(k: usize, m: usize, beta: f32, dt: f32) -> LiquidResult<Fir> {
-        if beta < 0f32 || beta > 1.0 {
-            return Err(LiquidError::InvalidValue(
-                "beta must be in (0, 1.0]".to_owned(),
-            ));
+        if !(0f32..=1.0).contains(&beta) {
+            return Err(FilterError::InvalidValue("beta must be in (0, 1.0]".to_owned()).into());
         }
         let mut filter = Fir::new(2 * k * m + 1);
         unsafe {
@@ -456,10 +564,8 @@ impl Firdes {
     ///  beta   : rolloff factor (0 < beta <= 1)
     ///  dt     : fractional sample delay
     pub fn rfexp(k: usize, m: usize, beta: f32, dt: f32) -> LiquidResult<Fir> {
-        if beta < 0f32 || beta > 1.0 {
-            return Err(LiquidError::InvalidValue(
-                "beta must be in (0, 1.0]".to_owned(),
-            ));
+        if !(0f32..=1.0).contains(&beta) {
+            return Err(FilterError::InvalidValue("beta must be in (0, 1.0]".to_owned()).into());
         }
         let mut filter = Fir::new(2 * k * m + 1);
         unsafe {
@@ -474,10 +580,8 @@ impl Firdes {
     ///  beta   : rolloff factor (0 < beta <= 1)
     ///  dt     : fractional sample delay
     pub fn fsech(k: usize, m: usize, beta: f32, dt: f32) -> LiquidResult<Fir> {
-        if beta < 0f32 || beta > 1.0 {
-            return Err(LiquidError::InvalidValue(
-                "beta must be in (0, 1.0]".to_owned(),
-            ));
+        if !(0f32..=1.0).contains(&beta) {
+            return Err(FilterError::InvalidValue("beta must be in (0, 1.0]".to_owned()).into());
         }
         let mut filter = Fir::new(2 * k * m + 1);
         unsafe {
@@ -492,10 +596,8 @@ impl Firdes {
     ///  beta   : rolloff factor (0 < beta <= 1)
     ///  dt     : fractional sample delay
     pub fn rfsech(k: usize, m: usize, beta: f32, dt: f32) -> LiquidResult<Fir> {
-        if beta < 0f32 || beta > 1.0 {
-            return Err(LiquidError::InvalidValue(
-                "beta must be in (0, 1.0]".to_owned(),
-            ));
+        if !(0f32..=1.0).contains(&beta) {
+            return Err(FilterError::InvalidValue("beta must be in (0, 1.0]".to_owned()).into());
         }
         let mut filter = Fir::new(2 * k * m + 1);
         unsafe {
@@ -510,10 +612,8 @@ impl Firdes {
     ///  beta   : rolloff factor (0 < beta <= 1)
     ///  dt     : fractional sample delay
     pub fn farcsech(k: usize, m: usize, beta: f32, dt: f32) -> LiquidResult<Fir> {
-        if beta < 0f32 || beta > 1.0 {
-            return Err(LiquidError::InvalidValue(
-                "beta must be in (0, 1.0]".to_owned(),
-            ));
+        if !(0f32..=1.0).contains(&beta) {
+            return Err(FilterError::InvalidValue("beta must be in (0, 1.0]".to_owned()).into());
         }
         let mut filter = Fir::new(2 * k * m + 1);
         unsafe {
@@ -528,10 +628,8 @@ impl Firdes {
     ///  beta   : rolloff factor (0 < beta <= 1)
     ///  dt     : fractional sample delay
     pub fn rfarcsech(k: usize, m: usize, beta: f32, dt: f32) -> LiquidResult<Fir> {
-        if beta < 0f32 || beta > 1.0 {
-            return Err(LiquidError::InvalidValue(
-                "beta must be in (0, 1.0]".to_owned(),
-            ));
+        if !(0f32..=1.0).contains(&beta) {
+            return Err(FilterError::InvalidValue("beta must be in (0, 1.0]".to_owned()).into());
         }
         let mut filter = Fir::new(2 * k * m + 1);
         unsafe {
@@ -541,6 +639,148 @@ impl Firdes {
     }
 }
 
+/// edges of a band centered on f0 of width bw must be in (0, 0.5), bw
+/// being zero for a cutoff frequency
+fn check_window_band(f0: f32, bw: f32) -> LiquidResult<()> {
+    if bw < 0f32 || bw.is_nan() {
+        return Err(FilterError::InvalidValue(format!(
+            "bandwidth: {} must be greater than zero",
+            bw
+        ))
+        .into());
+    } else if !(f0 - bw / 2.0 > 0f32 && f0 + bw / 2.0 < 0.5) {
+        return Err(FilterError::InvalidValue(format!(
+            "band [{}, {}] must be in (0, 0.5)",
+            f0 - bw / 2.0,
+            f0 + bw / 2.0
+        ))
+        .into());
+    }
+    Ok(())
+}
+
+/// high-pass and band-stop responses need a sample at the center
+fn check_odd_len(n: usize) -> LiquidResult<()> {
+    if n.is_multiple_of(2) {
+        return Err(FilterError::InvalidValue(format!("filter length: {} must be odd", n)).into());
+    }
+    Ok(())
+}
+
+/// window of length n, from liquid's window functions
+fn window(n: usize, wtype: WindowType, as_: f32) -> LiquidResult<Vec<f32>> {
+    if n == 0 {
+        return Err(FilterError::InvalidValue(
+            "filter length must be greater than zero".to_owned(),
+        )
+        .into());
+    } else if (wtype == WindowType::KAISER || wtype == WindowType::KBD) && as_ <= 0f32 {
+        return Err(FilterError::InvalidValue(
+            "stop-band attenuation must be greater than zero".to_owned(),
+        )
+        .into());
+    } else if wtype == WindowType::KBD && !n.is_multiple_of(2) {
+        return Err(FilterError::InvalidValue(format!(
+            "filter length: {} must be even for a KBD window",
+            n
+        ))
+        .into());
+    } else if wtype == WindowType::UNKNOWN {
+        return Err(FilterError::InvalidValue("unknown window type".to_owned()).into());
+    }
+    let len = n as u32;
+    let mut w = vec![0f32; n];
+    unsafe {
+        if wtype == WindowType::KBD {
+            raw::liquid_kbd_window(len, raw::kaiser_beta_As(as_), w.as_mut_ptr());
+            return Ok(w);
+        }
+        let beta = raw::kaiser_beta_As(as_);
+        for (i, v) in w.iter_mut().enumerate() {
+            let i = i as u32;
+            *v = match wtype {
+                WindowType::HAMMING => raw::hamming(i, len),
+                WindowType::HANN => raw::hann(i, len),
+                WindowType::BLACKMANHARRIS => raw::blackmanharris(i, len),
+                WindowType::BLACKMANHARRIS7 => raw::blackmanharris7(i, len),
+                WindowType::KAISER => raw::kaiser(i, len, beta, 0.0),
+                WindowType::FLATTOP => raw::flattop(i, len),
+                WindowType::TRIANGULAR => raw::triangular(i, len, len),
+                // taper over a quarter of the window on each side
+                WindowType::RCOSTAPER => raw::liquid_rcostaper_windowf(i, len / 4, len),
+                WindowType::KBD | WindowType::UNKNOWN => unreachable!(),
+            };
+        }
+    }
+    Ok(w)
+}
+
+/// window the ideal response h(t), t being centered on the filter, and
+/// normalize the gain at the reference frequency f to one
+fn windowed<F>(n: usize, wtype: WindowType, as_: f32, f: f64, h: F) -> LiquidResult<Fir>
+where
+    F: Fn(f64) -> f64,
+{
+    let w = window(n, wtype, as_)?;
+    let half = (n as f64 - 1.0) / 2.0;
+    let v: Vec<f64> = w
+        .iter()
+        .enumerate()
+        .map(|(i, &wi)| h(i as f64 - half) * wi as f64)
+        .collect();
+    let (mut re, mut im) = (0f64, 0f64);
+    for (i, vi) in v.iter().enumerate() {
+        let theta = 2.0 * std::f64::consts::PI * f * i as f64;
+        re += vi * theta.cos();
+        im -= vi * theta.sin();
+    }
+    let gain = (re * re + im * im).sqrt();
+    let mut filter = Fir::new(n);
+    filter
+        .as_mut()
+        .iter_mut()
+        .zip(v)
+        .for_each(|(hi, vi)| *hi = (vi / gain) as f32);
+    Ok(filter)
+}
+
+/// unit impulse at t = 0
+fn delta(t: f64) -> f64 {
+    if t == 0.0 {
+        1.0
+    } else {
+        0.0
+    }
+}
+
+/// ideal band-pass response, a low-pass of cutoff bw/2 shifted to +/-f0
+fn bandpass_ideal(t: f64, f0: f64, bw: f64) -> f64 {
+    2.0 * (2.0 * std::f64::consts::PI * f0 * t).cos() * bw * sinc(bw * t)
+}
+
+/// modified Bessel function of the first kind, order zero
+fn besseli0(x: f64) -> f64 {
+    let mut sum = 1f64;
+    let mut term = 1f64;
+    let mut k = 1f64;
+    while term > 1e-16 * sum {
+        term *= (x / (2.0 * k)) * (x / (2.0 * k));
+        sum += term;
+        k += 1.0;
+    }
+    sum
+}
+
+/// sin(pi*x) / (pi*x)
+fn sinc(x: f64) -> f64 {
+    if x.abs() < 1e-12 {
+        1.0
+    } else {
+        let px = std::f64::consts::PI * x;
+        px.sin() / px
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -548,7 +788,6 @@ mod tests {
     #[test]
     fn test_firdes_filter_autocorr() {
         let f1 = Firdes::fexp(10, 2, 0.2, 0.5).unwrap();
-        let x = vec![1.5f32; 50];
         assert_eq!(f1.auto_corr(5), 6.012687);
     }
 
@@ -565,4 +804,58 @@ mod tests {
         let delay = f1.group_delay(-0.2).unwrap();
         assert_eq!(delay, 100.00711);
     }
+
+    #[test]
+    fn test_firdes_kaiser_f64() {
+        let h = Firdes::kaiser_f64(31, 0.2, 60.0, 0.0).unwrap();
+        assert_eq!(h.len(), 31);
+        assert!((h[15] - 1.0).abs() < 1e-12);
+        for i in 0..15 {
+            assert!((h[i] - h[30 - i]).abs() < 1e-12);
+        }
+        assert!(Firdes::kaiser_f64(31, 0.6, 60.0, 0.0).is_err());
+    }
+
+    #[test]
+    fn test_fir_responses() {
+        let mut h = Fir::new(4);
+        h.as_mut().copy_from_slice(&[0.25, 0.5, 0.5, -0.25]);
+        assert!((h.response(0.0) - Complex32::new(1.0, 0.0)).norm() < 1e-6);
+        assert!((h.response(0.5) - h.response(-0.5)).norm() < 1e-6);
+        assert!((h.response(1.2) - h.response(0.2)).norm() < 1e-6);
+        assert!(h.freq_response(0.7).is_err());
+        assert_eq!(h.freq_response(0.2).unwrap(), h.response(0.2));
+
+        let curve = h.response_curve(8);
+        assert_eq!(curve.len(), 8);
+        // DC is in the middle of the shifted spectrum
+        assert!(curve[4].abs() < 1e-5);
+        assert!((curve[0] - 20.0 * h.response(-0.5).norm().log10()).abs() < 1e-5);
+
+        assert_eq!(h.impulse_response(), vec![0.25, 0.5, 0.5, -0.25]);
+        assert_eq!(h.step_response(6), vec![0.25, 0.75, 1.25, 1.0, 1.0, 1.0]);
+        assert!(h.step_response(0).is_empty());
+    }
+
+    #[test]
+    fn test_firdes_windowed_sinc() {
+        let gain = |h: &Fir, f: f32| h.response(f).norm();
+        let lp = Firdes::lowpass(51, 0.1, WindowType::HAMMING, 60.0).unwrap();
+        assert!((gain(&lp, 0.0) - 1.0).abs() < 1e-5);
+        assert!(gain(&lp, 0.25) < 0.01);
+        let hp = Firdes::highpass(51, 0.2, WindowType::KAISER, 60.0).unwrap();
+        assert!((gain(&hp, 0.5) - 1.0).abs() < 1e-5);
+        assert!(gain(&hp, 0.05) < 0.01);
+        let bp = Firdes::bandpass(64, 0.25, 0.1, WindowType::BLACKMANHARRIS, 60.0).unwrap();
+        assert!((gain(&bp, 0.25) - 1.0).abs() < 1e-5);
+        assert!(gain(&bp, 0.05) < 0.01);
+        let bs = Firdes::bandstop(81, 0.25, 0.1, WindowType::HANN, 60.0).unwrap();
+        assert!((gain(&bs, 0.0) - 1.0).abs() < 1e-5);
+        assert!(gain(&bs, 0.25) < 0.01);
+
+        assert!(Firdes::highpass(50, 0.2, WindowType::HAMMING, 60.0).is_err());
+        assert!(Firdes::bandpass(64, 0.05, 0.2, WindowType::HAMMING, 60.0).is_err());
+        assert!(Firdes::lowpass(51, 0.1, WindowType::KBD, 60.0).is_err());
+        assert!(Firdes::lowpass(51, 0.1, WindowType::UNKNOWN, 60.0).is_err());
+    }
 }
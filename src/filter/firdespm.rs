@@ -1,13 +1,12 @@
 use libc::{c_int, c_void};
 use std::marker::PhantomData;
-use std::mem::transmute;
 
 use crate::liquid_dsp_sys as raw;
 
 use crate::callbacks::Callbacks;
-use crate::errors::LiquidError;
+use crate::errors::{FilterError, LiquidError};
 use crate::filter::enums::{FirdespmBtype, FirdespmWtype};
-use crate::utils::catch;
+use crate::utils::{catch, check_same_len_as};
 
 pub extern "C" fn firdespm_callback_f(
     frecuency: f64,
@@ -66,13 +65,14 @@ impl<'a> Firdespm<'a> {
         wtype: &Option<&[FirdespmWtype]>,
     ) -> Result<(), LiquidError> {
         if bands.is_empty() || bands.len() / 2 != num_bands {
-            return Err(LiquidError::InvalidLength {
+            return Err(FilterError::InvalidLength {
                 description: format!(
                     "bands length: {} valid length: {}",
                     bands.len(),
                     num_bands * 2
                 ),
-            });
+            }
+            .into());
         }
 
         let invalid = if let Some(w) = wtype {
@@ -91,7 +91,7 @@ impl<'a> Firdespm<'a> {
                 weights.len(),
                 num_bands
             );
-            return Err(LiquidError::InvalidLength { description });
+            return Err(FilterError::InvalidLength { description }.into());
         }
 
         Ok(())
@@ -129,11 +129,11 @@ impl<'a> Firdespm<'a> {
                     bands.as_ptr() as _,
                     des.as_ptr() as _,
                     weights.as_ptr() as _,
-                    transmute::<*mut FirdespmWtype, *mut u32>(ptr as _),
+                    ptr as _,
                     u8::from(btype) as _,
                 ),
                 h_len,
-                callback: std::ptr::null_mut() as _,
+                callback: std::ptr::null_mut(),
                 phantom: PhantomData,
             })
         }
@@ -150,17 +150,19 @@ impl<'a> Firdespm<'a> {
         F: FnMut(f64, &mut f64, &mut f64) -> i8 + 'a,
     {
         if num_bands == 0 || num_bands != bands.len() / 2 {
-            return Err(LiquidError::InvalidLength {
+            return Err(FilterError::InvalidLength {
                 description: format!(
                     "bands length: {} valid length: {}",
                     bands.len(),
                     num_bands * 2
                 ),
-            });
+            }
+            .into());
         }
-        let mut userdata = Callbacks::default();
-        userdata.firdespm_callback = Some(Box::new(callback));
-        let userdata = Box::into_raw(Box::new(userdata));
+        let userdata = Box::into_raw(Box::new(Callbacks {
+            firdespm_callback: Some(Box::new(callback)),
+            ..Default::default()
+        }));
         unsafe {
             Ok(Self {
                 inner: raw::firdespm_create_callback(
@@ -184,6 +186,13 @@ impl<'a> Firdespm<'a> {
         }
     }
 
+    /// length of the designed filter (number of taps)
+    pub fn h_len(&self) -> usize {
+        self.h_len
+    }
+
+    /// execute the design
+    ///  h      :   output coefficients array [size: h_len() x 1]
     pub fn execute(&self, h: &mut [f32]) {
         assert!(h.len() == self.h_len, "h array len must be = h_len");
         unsafe {
@@ -191,15 +200,27 @@ impl<'a> Firdespm<'a> {
         }
     }
 
+    /// execute the design
+    /// # returns
+    /// the filter coefficients [size: h_len() x 1]
+    pub fn execute_vec(&self) -> Vec<f32> {
+        let mut h = vec![0f32; self.h_len];
+        self.execute(&mut h);
+        h
+    }
+
     /// run filter design (full life cycle of object)
+    ///  h_len      :   length of filter (number of taps)
     ///  num_bands  :   number of frequency bands
     ///  bands      :   band edges, f in [0,0.5], [size: _num_bands x 2]
     ///  des        :   desired response [size: _num_bands x 1]
     ///  weights    :   response weighting [size: _num_bands x 1]
     ///  wtype      :   weight types (e.g. LIQUID_FIRDESPM_FLATWEIGHT) [size: _num_bands x 1]
     ///  btype      :   band type (e.g. LIQUID_FIRDESPM_BANDPASS)
-    ///  output      :   output coefficients array [size: _h_len x 1]
+    ///  output     :   output coefficients array [size: _h_len x 1]
+    #[allow(clippy::too_many_arguments)]
     pub fn run(
+        h_len: usize,
         num_bands: usize,
         bands: &[f32],
         des: &[f32],
@@ -209,6 +230,13 @@ impl<'a> Firdespm<'a> {
         output: &mut [f32],
     ) -> Result<(), LiquidError> {
         Self::validate_inputs_length(num_bands, bands, des, weights, &wtype)?;
+        if h_len == 0 {
+            return Err(FilterError::InvalidLength {
+                description: "filter length must be greater than zero".to_owned(),
+            }
+            .into());
+        }
+        check_same_len_as::<FilterError>(h_len, output.len())?;
 
         let ptr = if let Some(w) = wtype {
             w.as_ptr()
@@ -218,12 +246,12 @@ impl<'a> Firdespm<'a> {
 
         unsafe {
             raw::firdespm_run(
-                output.len() as _,
+                h_len as _,
                 num_bands as _,
                 bands.as_ptr() as _,
                 des.as_ptr() as _,
                 weights.as_ptr() as _,
-                transmute::<*mut FirdespmWtype, *mut u32>(ptr as _),
+                ptr as _,
                 u8::from(btype) as _,
                 output.as_mut_ptr(),
             );
@@ -236,27 +264,229 @@ impl<'a> Firdespm<'a> {
             !output.is_empty(),
             "filter length must be greater than zero"
         );
-        if mu < -0.5 || mu > 0.5 {
-            return Err(LiquidError::InvalidValue(
-                "mu out of range [-0.5,0.5]".to_owned(),
-            ));
-        } else if fc < 0f32 || fc > 0.5 {
-            return Err(LiquidError::InvalidValue(
+        if !(-0.5..=0.5).contains(&mu) {
+            return Err(FilterError::InvalidValue("mu out of range [-0.5,0.5]".to_owned()).into());
+        } else if !(0f32..=0.5).contains(&fc) {
+            return Err(FilterError::InvalidValue(
                 "cutoff frequency out of range (0, 0.5)".to_owned(),
-            ));
+            )
+            .into());
         }
         unsafe {
             raw::firdespm_lowpass(output.len() as _, fc, as_, mu, output.as_mut_ptr());
         }
         Ok(())
     }
+
+    /// transition bandwidth of an n tap design reaching as_ dB of
+    /// stop-band attenuation, as estimated by liquid for firdespm_lowpass
+    fn check_design(n: usize, as_: f32) -> Result<f32, LiquidError> {
+        if n == 0 {
+            return Err(FilterError::InvalidValue(
+                "filter length must be greater than zero".to_owned(),
+            )
+            .into());
+        } else if as_ <= 0f32 {
+            return Err(FilterError::InvalidValue(
+                "stop-band attenuation must be greater than zero".to_owned(),
+            )
+            .into());
+        }
+        Ok(unsafe { raw::estimate_req_filter_df(as_, n as _) })
+    }
+
+    /// design a high-pass filter, the transition band is centered on the
+    /// cut-off frequency with the width liquid estimates for n and as_
+    ///  n      :   filter length, odd
+    ///  fc     :   cut-off frequency, in (0, 0.5)
+    ///  as_    :   stop-band attenuation [dB], as_ > 0
+    pub fn highpass(n: usize, fc: f32, as_: f32) -> Result<Vec<f32>, LiquidError> {
+        let ft = Self::check_design(n, as_)?;
+        if n.is_multiple_of(2) {
+            return Err(FilterError::InvalidValue(
+                "high-pass filter length must be odd".to_owned(),
+            )
+            .into());
+        } else if fc - 0.5 * ft <= 0f32 || fc + 0.5 * ft >= 0.5 {
+            return Err(FilterError::InvalidValue(format!(
+                "cutoff frequency: {} with transition band: {} out of range (0, 0.5)",
+                fc, ft
+            ))
+            .into());
+        }
+        let mut h = vec![0f32; n];
+        Self::run(
+            n,
+            2,
+            &[0.0, fc - 0.5 * ft, fc + 0.5 * ft, 0.5],
+            &[0.0, 1.0],
+            &[1.0, 1.0],
+            None,
+            FirdespmBtype::BANDPASS,
+            &mut h,
+        )?;
+        Ok(h)
+    }
+
+    /// design a band-pass filter, the transition bands are centered on the
+    /// band edges with the width liquid estimates for n and as_
+    ///  n      :   filter length
+    ///  f0     :   lower band edge, in (0, f1)
+    ///  f1     :   upper band edge, in (f0, 0.5)
+    ///  as_    :   stop-band attenuation [dB], as_ > 0
+    pub fn bandpass(n: usize, f0: f32, f1: f32, as_: f32) -> Result<Vec<f32>, LiquidError> {
+        let ft = Self::check_design(n, as_)?;
+        if f0 - 0.5 * ft <= 0f32 || f1 + 0.5 * ft >= 0.5 || f1 - f0 <= ft {
+            return Err(FilterError::InvalidValue(format!(
+                "band edges: [{}, {}] with transition bands: {} out of range (0, 0.5)",
+                f0, f1, ft
+            ))
+            .into());
+        }
+        let mut h = vec![0f32; n];
+        Self::run(
+            n,
+            3,
+            &[
+                0.0,
+                f0 - 0.5 * ft,
+                f0 + 0.5 * ft,
+                f1 - 0.5 * ft,
+                f1 + 0.5 * ft,
+                0.5,
+            ],
+            &[0.0, 1.0, 0.0],
+            &[1.0, 1.0, 1.0],
+            None,
+            FirdespmBtype::BANDPASS,
+            &mut h,
+        )?;
+        Ok(h)
+    }
+
+    /// design a differentiator, with a response proportional to the
+    /// frequency over [0, fc]
+    ///  n      :   filter length, n > 1
+    ///  fc     :   upper edge of the band, in (0, 0.5]
+    pub fn differentiator(n: usize, fc: f32) -> Result<Vec<f32>, LiquidError> {
+        if n < 2 {
+            return Err(
+                FilterError::InvalidValue("filter length must be at least 2".to_owned()).into(),
+            );
+        } else if fc <= 0f32 || fc > 0.5 {
+            return Err(
+                FilterError::InvalidValue("band edge out of range (0, 0.5]".to_owned()).into(),
+            );
+        }
+        let mut h = vec![0f32; n];
+        Self::run(
+            n,
+            1,
+            &[0.0, fc],
+            &[1.0],
+            &[1.0],
+            None,
+            FirdespmBtype::DIFFERENTIATOR,
+            &mut h,
+        )?;
+        Ok(h)
+    }
 }
 
 impl<'a> Drop for Firdespm<'a> {
     fn drop(&mut self) {
         unsafe {
             raw::firdespm_destroy(self.inner);
-            let _ = Box::from_raw(self.callback);
+            if !self.callback.is_null() {
+                let _ = Box::from_raw(self.callback);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Firdespm;
+    use crate::filter::FirdespmBtype;
+    use num::complex::Complex32;
+    use std::f32::consts::PI;
+
+    #[test]
+    fn test_firdespm_convenience_validation() {
+        assert!(Firdespm::highpass(0, 0.2, 60.0).is_err());
+        assert!(Firdespm::highpass(56, 0.2, 60.0).is_err());
+        assert!(Firdespm::highpass(57, 0.2, 0.0).is_err());
+        assert!(Firdespm::bandpass(57, 0.3, 0.2, 60.0).is_err());
+        assert!(Firdespm::differentiator(1, 0.4).is_err());
+        assert!(Firdespm::differentiator(21, 0.6).is_err());
+        assert_eq!(Firdespm::differentiator(21, 0.4).unwrap().len(), 21);
+    }
+
+    /// magnitude response of h at the normalized frequency f
+    fn gain(h: &[f32], f: f32) -> f32 {
+        h.iter()
+            .enumerate()
+            .map(|(i, v)| Complex32::from_polar(*v, -2.0 * PI * f * i as f32))
+            .sum::<Complex32>()
+            .norm()
+    }
+
+    #[test]
+    fn test_firdespm_highpass() {
+        let h = Firdespm::highpass(57, 0.2, 60.0).unwrap();
+        assert_eq!(h.len(), 57);
+        for i in 0..=30 {
+            assert!(gain(&h, 0.005 * i as f32) < 0.01);
         }
+        for i in 0..=50 {
+            assert!((gain(&h, 0.25 + 0.005 * i as f32) - 1.0).abs() < 0.01);
+        }
+    }
+
+    #[test]
+    fn test_firdespm_bandpass() {
+        let h = Firdespm::bandpass(57, 0.15, 0.3, 60.0).unwrap();
+        assert_eq!(h.len(), 57);
+        for i in 0..=20 {
+            assert!(gain(&h, 0.005 * i as f32) < 0.01);
+            assert!(gain(&h, 0.4 + 0.005 * i as f32) < 0.01);
+        }
+        for i in 0..=10 {
+            assert!((gain(&h, 0.2 + 0.005 * i as f32) - 1.0).abs() < 0.01);
+        }
+    }
+
+    #[test]
+    fn test_firdespm_execute_vec() {
+        let q = Firdespm::create(
+            31,
+            2,
+            &[0.0, 0.1, 0.2, 0.5],
+            &[1.0, 0.0],
+            &[1.0, 1.0],
+            None,
+            FirdespmBtype::BANDPASS,
+        )
+        .unwrap();
+        assert_eq!(q.h_len(), 31);
+        assert_eq!(q.execute_vec().len(), 31);
+
+        let run = |h_len: usize, h: &mut [f32]| {
+            Firdespm::run(
+                h_len,
+                1,
+                &[0.0, 0.4],
+                &[1.0],
+                &[1.0],
+                None,
+                FirdespmBtype::BANDPASS,
+                h,
+            )
+        };
+        assert!(run(0, &mut []).is_err());
+        let mut h = [0f32; 20];
+        assert!(run(21, &mut h).is_err());
+        let mut h = [0f32; 21];
+        assert!(run(21, &mut h).is_ok());
     }
 }
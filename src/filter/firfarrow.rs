@@ -0,0 +1,249 @@
+//! *firfarrow* : FIR filter whose coefficients are polynomials of a
+//!             fractional delay, for interpolating between input samples
+use num::complex::Complex32;
+
+use crate::liquid_dsp_sys as raw;
+use crate::utils::{check_same_len_as, ToCPointer, ToCPointerMut, ToCValue};
+
+use crate::errors::FilterError;
+use crate::LiquidResult;
+
+pub struct FirFarrowRrrf {
+    inner: raw::firfarrow_rrrf,
+    len: usize,
+    mu: f32,
+}
+
+pub struct FirFarrowCrcf {
+    inner: raw::firfarrow_crcf,
+    len: usize,
+    mu: f32,
+}
+
+macro_rules! firfarrow_impl {
+    ($obj:ty, (
+        $create:expr, $print:expr,
+        $reset:expr, $push:expr,
+        $set_delay:expr, $execute:expr,
+        $block:expr, $coefficients:expr,
+        $freq_response:expr, $group_delay:expr,
+        $destroy:expr,
+        $type:ty)) => {
+        impl $obj {
+            /// create firfarrow object
+            ///  h_len  :   filter length, h_len >= 2
+            ///  p      :   polynomial order, p > 0
+            ///  fc     :   filter cutoff frequency, in (0, 0.5)
+            ///  as_    :   stopband attenuation [dB], as_ > 0
+            pub fn create(h_len: usize, p: u32, fc: f32, as_: f32) -> LiquidResult<Self> {
+                if h_len < 2 {
+                    return Err(FilterError::InvalidValue(
+                        "filter length must be at least 2".to_owned(),
+                    )
+                    .into());
+                } else if p == 0 {
+                    return Err(FilterError::InvalidValue(
+                        "polynomial order must be greater than 0".to_owned(),
+                    )
+                    .into());
+                } else if fc <= 0f32 || fc >= 0.5 {
+                    return Err(
+                        FilterError::InvalidValue("fc must be in (0, 0.5)".to_owned()).into(),
+                    );
+                } else if as_ <= 0f32 {
+                    return Err(FilterError::InvalidValue(
+                        "stop-band attenuation must be greater than 0".to_owned(),
+                    )
+                    .into());
+                }
+                Ok(Self {
+                    inner: unsafe { $create(h_len as _, p as _, fc, as_) },
+                    len: h_len,
+                    mu: 0.0,
+                })
+            }
+
+            pub fn print(&self) {
+                unsafe {
+                    $print(self.inner);
+                }
+            }
+
+            /// clear the internal buffer, keeping the delay
+            pub fn reset(&mut self) {
+                unsafe {
+                    $reset(self.inner);
+                }
+            }
+
+            /// filter length
+            pub fn len(&self) -> usize {
+                self.len
+            }
+
+            pub fn is_empty(&self) -> bool {
+                self.len == 0
+            }
+
+            /// set the fractional delay; the output is delayed by
+            /// (len()-1)/2 - mu samples
+            ///  mu     :   fractional sample delay, in [-1, 1]
+            pub fn set_delay(&mut self, mu: f32) -> LiquidResult<()> {
+                if !(-1.0..=1.0).contains(&mu) {
+                    return Err(
+                        FilterError::InvalidValue("mu must be in [-1, 1]".to_owned()).into(),
+                    );
+                }
+                unsafe {
+                    $set_delay(self.inner, mu);
+                }
+                self.mu = mu;
+                Ok(())
+            }
+
+            pub fn get_delay(&self) -> f32 {
+                self.mu
+            }
+
+            /// filter coefficients at the current delay
+            pub fn coefficients(&self) -> Vec<f32> {
+                let mut h = vec![0f32; self.len];
+                unsafe {
+                    $coefficients(self.inner, h.as_mut_ptr());
+                }
+                h
+            }
+
+            /// frequency response at the current delay
+            ///  fc     :   normalized frequency, in [-0.5, 0.5]
+            pub fn freq_response(&self, fc: f32) -> Complex32 {
+                let mut h = Complex32::default();
+                unsafe {
+                    $freq_response(self.inner, fc, h.to_ptr_mut());
+                }
+                h
+            }
+
+            /// group delay at the current delay [samples]
+            ///  fc     :   normalized frequency, in [-0.5, 0.5]
+            pub fn group_delay(&self, fc: f32) -> f32 {
+                unsafe { $group_delay(self.inner, fc) }
+            }
+
+            /// push a sample into the internal buffer
+            pub fn push(&mut self, x: $type) {
+                unsafe {
+                    $push(self.inner, x.to_c_value());
+                }
+            }
+
+            /// output sample at the current delay
+            pub fn execute(&mut self) -> $type {
+                let mut y = <$type>::default();
+                unsafe {
+                    $execute(self.inner, y.to_ptr_mut());
+                }
+                y
+            }
+
+            /// push a block of samples, computing an output for every one
+            ///  x      :   input samples [size: n x 1]
+            ///  y      :   output samples [size: n x 1]
+            pub fn execute_block(&mut self, x: &[$type], y: &mut [$type]) -> LiquidResult<()> {
+                check_same_len_as::<FilterError>(x.len(), y.len())?;
+                unsafe {
+                    $block(self.inner, x.to_ptr() as _, x.len() as _, y.to_ptr_mut());
+                }
+                Ok(())
+            }
+        }
+
+        impl Drop for $obj {
+            fn drop(&mut self) {
+                unsafe {
+                    $destroy(self.inner);
+                }
+            }
+        }
+    };
+}
+
+firfarrow_impl!(
+    FirFarrowRrrf,
+    (
+        raw::firfarrow_rrrf_create,
+        raw::firfarrow_rrrf_print,
+        raw::firfarrow_rrrf_reset,
+        raw::firfarrow_rrrf_push,
+        raw::firfarrow_rrrf_set_delay,
+        raw::firfarrow_rrrf_execute,
+        raw::firfarrow_rrrf_execute_block,
+        raw::firfarrow_rrrf_get_coefficients,
+        raw::firfarrow_rrrf_freqresponse,
+        raw::firfarrow_rrrf_groupdelay,
+        raw::firfarrow_rrrf_destroy,
+        f32
+    )
+);
+
+firfarrow_impl!(
+    FirFarrowCrcf,
+    (
+        raw::firfarrow_crcf_create,
+        raw::firfarrow_crcf_print,
+        raw::firfarrow_crcf_reset,
+        raw::firfarrow_crcf_push,
+        raw::firfarrow_crcf_set_delay,
+        raw::firfarrow_crcf_execute,
+        raw::firfarrow_crcf_execute_block,
+        raw::firfarrow_crcf_get_coefficients,
+        raw::firfarrow_crcf_freqresponse,
+        raw::firfarrow_crcf_groupdelay,
+        raw::firfarrow_crcf_destroy,
+        Complex32
+    )
+);
+
+#[cfg(test)]
+mod tests {
+    use super::{FirFarrowCrcf, FirFarrowRrrf};
+    use num::complex::Complex32;
+
+    #[test]
+    fn test_firfarrow_set_delay() {
+        assert!(FirFarrowRrrf::create(1, 4, 0.45, 60.0).is_err());
+        assert!(FirFarrowRrrf::create(19, 0, 0.45, 60.0).is_err());
+        assert!(FirFarrowRrrf::create(19, 4, 0.5, 60.0).is_err());
+        assert!(FirFarrowRrrf::create(19, 4, 0.45, 0.0).is_err());
+
+        let mut q = FirFarrowRrrf::create(19, 4, 0.45, 60.0).unwrap();
+        assert_eq!(q.len(), 19);
+        assert_eq!(q.get_delay(), 0.0);
+        q.set_delay(0.3).unwrap();
+        assert!(q.set_delay(1.5).is_err());
+        assert!(q.set_delay(-1.01).is_err());
+        assert!(q.set_delay(f32::NAN).is_err());
+        // a rejected delay leaves the filter untouched
+        assert_eq!(q.get_delay(), 0.3);
+        assert!((q.group_delay(0.0) - (9.0 - 0.3)).abs() < 0.05);
+    }
+
+    #[test]
+    fn test_firfarrow_fractional_delay() {
+        // complex tone, the output being delayed by (len-1)/2 - mu samples
+        let f = 0.05;
+        let tone = |t: f32| Complex32::from_polar(1.0, std::f32::consts::TAU * f * t);
+        let mut q = FirFarrowCrcf::create(19, 4, 0.45, 60.0).unwrap();
+        for &mu in &[-0.5, -0.2, 0.0, 0.3, 0.5] {
+            q.reset();
+            q.set_delay(mu).unwrap();
+            let x: Vec<Complex32> = (0..100).map(|n| tone(n as f32)).collect();
+            let mut y = vec![Complex32::default(); x.len()];
+            q.execute_block(&x, &mut y).unwrap();
+            for (n, v) in y.iter().enumerate().skip(q.len()) {
+                let expected = tone(n as f32 - 9.0 + mu);
+                assert!((v - expected).norm() < 0.02, "mu {}", mu);
+            }
+        }
+    }
+}
@@ -2,21 +2,24 @@ use num::complex::Complex32;
 
 use crate::filter::FirdesFilterType;
 use crate::liquid_dsp_sys as raw;
-use crate::utils::{ToCPointer, ToCPointerMut, ToCValue};
+use crate::utils::{check_same_len_as, ToCPointer, ToCPointerMut, ToCValue};
 
-use crate::errors::LiquidError;
+use crate::errors::FilterError;
 use crate::LiquidResult;
 
 pub struct FirFiltRrrf {
     inner: raw::firfilt_rrrf,
+    h: Vec<f32>,
 }
 
 pub struct FirFiltCrcf {
     inner: raw::firfilt_crcf,
+    h: Vec<f32>,
 }
 
 pub struct FirFiltCccf {
     inner: raw::firfilt_cccf,
+    h: Vec<Complex32>,
 }
 
 macro_rules! firfilt_impl {
@@ -26,11 +29,6 @@ macro_rules! firfilt_impl {
         $glen:expr,
         $freq_response:expr,
         $group_delay:expr,
-        $rect:expr,
-        $dc_blocker:expr,
-        $kaiser:expr,
-        $rnyquist:expr,
-        $notch:expr,
         $setscale:expr, $getscale:expr,
         $push:expr, $write:expr,
         $execute:expr, $block:expr,
@@ -41,50 +39,77 @@ macro_rules! firfilt_impl {
             //  h      :  filter coefficients.
             pub fn create(h: &[$type]) -> LiquidResult<Self> {
                 if h.is_empty() {
-                    return Err(LiquidError::InvalidValue(
+                    return Err(FilterError::InvalidValue(
                         "filter length must be greater than zero".to_owned(),
-                    ));
+                    )
+                    .into());
                 }
                 Ok(Self {
                     inner: unsafe { $create(h.to_ptr() as _, h.len() as _) },
+                    h: h.to_vec(),
                 })
             }
 
+            /// create the filter from a real prototype designed by liquid
+            fn from_prototype(hf: &[f32]) -> LiquidResult<Self> {
+                let h: Vec<$type> = hf.iter().map(|&v| <$type>::from(v)).collect();
+                Self::create(&h)
+            }
+
             // re-create firfilt object
             //  h      :   new coefficients.
-            pub fn recreate(self, h: &[$type]) -> LiquidResult<Self> {
+            pub fn recreate(mut self, h: &[$type]) -> LiquidResult<Self> {
+                self.update_coefficients(h)?;
+                Ok(self)
+            }
+
+            /// replace the filter coefficients in place, e.g. with the
+            /// weights of an adaptive equalizer; when the length is
+            /// unchanged the object and its buffer of past samples are
+            /// reused, otherwise liquid reallocates them
+            ///  h      :   new coefficients, h.len() > 0
+            pub fn update_coefficients(&mut self, h: &[$type]) -> LiquidResult<()> {
                 if h.is_empty() {
-                    return Err(LiquidError::InvalidValue(
+                    return Err(FilterError::InvalidValue(
                         "filter length must be greater than zero".to_owned(),
-                    ));
+                    )
+                    .into());
+                }
+                if h.len() == self.h.len() {
+                    self.h.copy_from_slice(h);
+                } else {
+                    self.h.clear();
+                    self.h.extend_from_slice(h);
                 }
                 unsafe {
-                    $recreate(self.inner, h.to_ptr() as _, h.len() as _);
-                };
-                Ok(self)
+                    self.inner = $recreate(self.inner, self.h.to_ptr() as _, self.h.len() as _);
+                }
+                Ok(())
             }
             pub fn create_rect(n: usize) -> LiquidResult<Self> {
                 if n == 0 {
-                    return Err(LiquidError::InvalidValue(
+                    return Err(FilterError::InvalidValue(
                         "filter order must be greater than zero".to_owned(),
-                    ));
+                    )
+                    .into());
                 }
 
-                Ok(Self {
-                    inner: unsafe { $rect(n as _) },
-                })
+                Self::from_prototype(&vec![1f32; n])
             }
 
             pub fn create_kaiser(n: usize, fc: f32, as_: f32, mu: f32) -> LiquidResult<Self> {
                 if n == 0 {
-                    return Err(LiquidError::InvalidValue(
+                    return Err(FilterError::InvalidValue(
                         "filter order must be greater than zero".to_owned(),
-                    ));
+                    )
+                    .into());
                 }
 
-                Ok(Self {
-                    inner: unsafe { $kaiser(n as _, fc, as_, mu) },
-                })
+                let mut hf = vec![0f32; n];
+                unsafe {
+                    raw::liquid_firdes_kaiser(n as _, fc, as_, mu, hf.as_mut_ptr());
+                }
+                Self::from_prototype(&hf)
             }
 
             pub fn create_rnyquist(
@@ -95,50 +120,77 @@ macro_rules! firfilt_impl {
                 mu: f32,
             ) -> LiquidResult<Self> {
                 if k < 2 {
-                    return Err(LiquidError::InvalidValue(
+                    return Err(FilterError::InvalidValue(
                         "filter samples/symbol must be greater than 1".to_owned(),
-                    ));
+                    )
+                    .into());
                 } else if m == 0 {
-                    return Err(LiquidError::InvalidValue(
+                    return Err(FilterError::InvalidValue(
                         "filter delay must be greater than zero".to_owned(),
-                    ));
-                } else if beta < 0f32 || beta > 1.0 {
-                    return Err(LiquidError::InvalidValue(
+                    )
+                    .into());
+                } else if !(0f32..=1.0).contains(&beta) {
+                    return Err(FilterError::InvalidValue(
                         "filter excess bandwith factor must be in [0, 1.0]".to_owned(),
-                    ));
-                } else if mu < -0.5 || mu > 0.5 {
-                    return Err(LiquidError::InvalidValue(
+                    )
+                    .into());
+                } else if !(-0.5..=0.5).contains(&mu) {
+                    return Err(FilterError::InvalidValue(
                         "filter fractional sample offser factor must be in [-0.5, 0.5]".to_owned(),
-                    ));
+                    )
+                    .into());
                 } else {
                     let ftype: u8 = ftype.into();
-                    Ok(Self {
-                        inner: unsafe { $rnyquist(ftype as _, k as _, m as _, beta, mu) },
-                    })
+                    let mut hf = vec![0f32; (2 * k * m + 1) as usize];
+                    unsafe {
+                        raw::liquid_firdes_prototype(
+                            ftype as _,
+                            k as _,
+                            m as _,
+                            beta,
+                            mu,
+                            hf.as_mut_ptr(),
+                        );
+                    }
+                    Self::from_prototype(&hf)
                 }
             }
 
+            /// square-root raised-cosine filter, the usual pulse shape and
+            /// matched filter of a linear modem
+            ///  k      :   samples/symbol, k > 1
+            ///  m      :   filter delay in symbols, m > 0
+            ///  beta   :   excess bandwidth factor, beta in [0, 1]
+            pub fn rrc_preset(k: u32, m: u32, beta: f32) -> LiquidResult<Self> {
+                Self::create_rnyquist(FirdesFilterType::Rrc, k, m, beta, 0.0)
+            }
+
             pub fn create_notch(m: u16, as_: f32, f0: f32) -> LiquidResult<Self> {
-                if m < 1 || m > 1000 {
-                    return Err(LiquidError::InvalidValue(
+                if !(1..=1000).contains(&m) {
+                    return Err(FilterError::InvalidValue(
                         "filter semi-length must be in [1, 1000]".to_owned(),
-                    ));
+                    )
+                    .into());
                 } else if as_ < 0f32 {
-                    return Err(LiquidError::InvalidValue(
+                    return Err(FilterError::InvalidValue(
                         "filter prototype stop-band suppression be greater than zero".to_owned(),
-                    ));
-                } else if f0 < -0.5 || f0 > 0.5 {
-                    return Err(LiquidError::InvalidValue(
+                    )
+                    .into());
+                } else if !(-0.5..=0.5).contains(&f0) {
+                    return Err(FilterError::InvalidValue(
                         "filter notch frequency must be in [-0.5, 0.5]".to_owned(),
-                    ));
+                    )
+                    .into());
                 } else {
-                    Ok(Self {
-                        inner: unsafe { $notch(m as _, as_, f0) },
-                    })
+                    let mut hf = vec![0f32; 2 * m as usize + 1];
+                    unsafe {
+                        raw::liquid_firdes_notch(m as _, f0, as_, hf.as_mut_ptr());
+                    }
+                    Self::from_prototype(&hf)
                 }
             }
 
-            pub fn reset(&self) {
+            pub fn reset(&mut self) {
                 unsafe { $reset(self.inner) }
             }
 
@@ -150,6 +202,15 @@ macro_rules! firfilt_impl {
                 unsafe { $glen(self.inner) as _ }
             }
 
+            pub fn is_empty(&self) -> bool {
+                self.len() == 0
+            }
+
+            /// filter coefficients, without the output scaling
+            pub fn coefficients(&self) -> &[$type] {
+                &self.h
+            }
+
             pub fn freq_response(&self, fc: f32) -> Complex32 {
                 let mut f = Complex32::default();
                 unsafe {
@@ -198,7 +259,7 @@ macro_rules! firfilt_impl {
             /// filter coefficients and internal buffer)
             /// # returns
             ///  y      :   output sample
-            pub fn execute(&self) -> $type2 {
+            pub fn execute(&mut self) -> $type2 {
                 unsafe {
                     let mut y = <$type2>::default();
                     $execute(self.inner, y.to_ptr_mut());
@@ -210,17 +271,52 @@ macro_rules! firfilt_impl {
             /// input and output buffers may be the same
             ///  x      : pointer to input array [size: _n x 1]
             ///  y      : pointer to output array [size: _n x 1]
-            pub fn execute_block(&self, x: &[$type2], y: &mut [$type2]) {
-                assert!(x.len() == y.len(), "x and y buffers must have the same len");
+            /// # panics
+            /// if x and y have different lengths, see try_execute_block
+            pub fn execute_block(&mut self, x: &[$type2], y: &mut [$type2]) {
+                self.try_execute_block(x, y)
+                    .unwrap_or_else(|e| panic!("{}", e));
+            }
+
+            /// execute the filter on a block of input samples
+            /// # returns
+            /// FilterError::InvalidLength if x and y have different lengths
+            pub fn try_execute_block(&mut self, x: &[$type2], y: &mut [$type2]) -> LiquidResult<()> {
+                check_same_len_as::<FilterError>(x.len(), y.len())?;
+                unsafe {
+                    $block(self.inner, x.to_ptr() as _, x.len() as _, y.to_ptr_mut());
+                }
+                Ok(())
+            }
+
+            /// execute the filter on a block of samples in place, the
+            /// output overwriting the input
+            ///  x      : input/output array [size: _n x 1]
+            pub fn execute_block_in_place(&mut self, x: &mut [$type2]) {
                 unsafe {
-                    $block(
-                        self.inner,
-                        x.to_ptr() as _,
-                        x.len() as _,
-                        y.to_ptr_mut(),
-                    );
+                    $block(self.inner, x.to_ptr_mut(), x.len() as _, x.to_ptr_mut());
                 }
             }
+
+            /// push zeros through the filter for its group delay, (len-1)/2
+            /// samples assuming a linear phase design, appending the tail to
+            /// out so that the last input sample is emitted; the filter is
+            /// reset afterwards, ready for the next burst
+            pub fn flush(&mut self, out: &mut Vec<$type2>) {
+                let n = self.h.len() / 2;
+                let zeros = vec![<$type2>::default(); n];
+                let start = out.len();
+                out.resize(start + n, <$type2>::default());
+                self.execute_block(&zeros, &mut out[start..]);
+                self.reset();
+            }
+        }
+
+        /// pass-through filter with a single unit tap
+        impl Default for $obj {
+            fn default() -> Self {
+                Self::from_prototype(&[1.0]).expect("single tap filter")
+            }
         }
 
         impl Drop for $obj {
@@ -243,11 +339,6 @@ firfilt_impl!(
         raw::firfilt_cccf_get_length,
         raw::firfilt_cccf_freqresponse,
         raw::firfilt_cccf_groupdelay,
-        raw::firfilt_cccf_create_rect,
-        raw::firfilt_cccf_create_dc_blocker,
-        raw::firfilt_cccf_create_kaiser,
-        raw::firfilt_cccf_create_rnyquist,
-        raw::firfilt_cccf_create_notch,
         raw::firfilt_cccf_set_scale,
         raw::firfilt_cccf_get_scale,
         raw::firfilt_cccf_push,
@@ -269,11 +360,6 @@ firfilt_impl!(
         raw::firfilt_crcf_get_length,
         raw::firfilt_crcf_freqresponse,
         raw::firfilt_crcf_groupdelay,
-        raw::firfilt_crcf_create_rect,
-        raw::firfilt_crcf_create_dc_blocker,
-        raw::firfilt_crcf_create_kaiser,
-        raw::firfilt_crcf_create_rnyquist,
-        raw::firfilt_crcf_create_notch,
         raw::firfilt_crcf_set_scale,
         raw::firfilt_crcf_get_scale,
         raw::firfilt_crcf_push,
@@ -295,11 +381,6 @@ firfilt_impl!(
         raw::firfilt_rrrf_get_length,
         raw::firfilt_rrrf_freqresponse,
         raw::firfilt_rrrf_groupdelay,
-        raw::firfilt_rrrf_create_rect,
-        raw::firfilt_rrrf_create_dc_blocker,
-        raw::firfilt_rrrf_create_kaiser,
-        raw::firfilt_rrrf_create_rnyquist,
-        raw::firfilt_rrrf_create_notch,
         raw::firfilt_rrrf_set_scale,
         raw::firfilt_rrrf_get_scale,
         raw::firfilt_rrrf_push,
@@ -310,3 +391,58 @@ firfilt_impl!(
         f32, f32
     )
 );
+
+#[cfg(test)]
+mod tests {
+    use super::{FirFiltCrcf, FirFiltRrrf};
+    use num::complex::Complex32;
+
+    /// real part of the first n samples of the impulse response
+    fn impulse_response(q: &mut FirFiltCrcf, n: usize) -> Vec<f32> {
+        q.reset();
+        (0..n)
+            .map(|i| {
+                q.push(Complex32::new(if i == 0 { 1.0 } else { 0.0 }, 0.0));
+                q.execute().re
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_firfilt_update_coefficients() {
+        let mut q = FirFiltCrcf::create(&[1.0, 0.5, 0.25]).unwrap();
+        assert!(q.update_coefficients(&[]).is_err());
+        assert_eq!(q.coefficients(), &[1.0, 0.5, 0.25]);
+        assert_eq!(impulse_response(&mut q, 4), [1.0, 0.5, 0.25, 0.0]);
+
+        // same length, the object is reused
+        q.update_coefficients(&[0.1, 0.2, 0.3]).unwrap();
+        assert_eq!(q.coefficients(), &[0.1, 0.2, 0.3]);
+        assert_eq!(impulse_response(&mut q, 4), [0.1, 0.2, 0.3, 0.0]);
+
+        // different length, liquid reallocates
+        let h = [0.5, -0.5, 0.25, -0.25, 0.125];
+        q.update_coefficients(&h).unwrap();
+        assert_eq!(q.coefficients(), &h);
+        assert_eq!(q.len(), 5);
+        assert_eq!(
+            impulse_response(&mut q, 6),
+            [0.5, -0.5, 0.25, -0.25, 0.125, 0.0]
+        );
+    }
+
+    #[test]
+    fn test_firfilt_flush() {
+        let h = [1.0, 2.0, 3.0, 4.0, 5.0];
+        let mut q = FirFiltRrrf::create(&h).unwrap();
+        let mut y = vec![0f32; 3];
+        q.execute_block(&[1.0, 0.0, 0.0], &mut y);
+        // the last (len-1)/2 taps of the impulse response
+        q.flush(&mut y);
+        assert_eq!(y, h);
+
+        let mut y = vec![0f32; 1];
+        q.execute_block(&[0.0], &mut y);
+        assert_eq!(y, [0.0]);
+    }
+}
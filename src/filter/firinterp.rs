@@ -5,28 +5,31 @@ use num::complex::Complex32;
 use crate::liquid_dsp_sys as raw;
 use crate::utils::{ToCPointer, ToCPointerMut, ToCValue};
 
-use crate::errors::LiquidError;
+use crate::errors::FilterError;
 use crate::filter::enums::FirdesFilterType;
+use crate::filter::polyphase;
 use crate::LiquidResult;
 
 pub struct FirInterpRrrf {
     inner: raw::firinterp_rrrf,
-    len: usize,
+    h: Vec<f32>,
+    m: u32,
 }
 
 pub struct FirInterpCrcf {
     inner: raw::firinterp_crcf,
-    len: usize,
+    h: Vec<f32>,
+    m: u32,
 }
 
 pub struct FirInterpCccf {
     inner: raw::firinterp_cccf,
-    len: usize,
+    h: Vec<Complex32>,
+    m: u32,
 }
 
 macro_rules! firinterp_impl {
-    ($obj:ty, ($create:expr, $prototype:expr,
-        $kaiser:expr,
+    ($obj:ty, ($create:expr,
         $print:expr,$reset:expr,
         $scale:expr, $get_scale:expr,
         $execute:expr, $block:expr,
@@ -39,20 +42,29 @@ macro_rules! firinterp_impl {
             pub fn create(m: u32, h: &[$type2]) -> LiquidResult<$obj> {
                 // check params
                 if m < 2 {
-                    return Err(LiquidError::InvalidValue(
+                    return Err(FilterError::InvalidValue(
                         "interp factor must be greater than 2".to_owned(),
-                    ));
+                    )
+                    .into());
                 } else if h.len() < m as usize {
-                    return Err(LiquidError::InvalidValue(
+                    return Err(FilterError::InvalidValue(
                         "filter length cannot be less than interp factor".to_owned(),
-                    ));
+                    )
+                    .into());
                 }
                 Ok(Self {
                     inner: unsafe { $create(m as _, h.to_ptr() as _, h.len() as _) },
-                    len: h.len(),
+                    h: h.to_vec(),
+                    m,
                 })
             }
 
+            /// create the interpolator from a real prototype designed by liquid
+            fn from_prototype(m: u32, hf: &[f32]) -> LiquidResult<Self> {
+                let h: Vec<$type2> = hf.iter().map(|&v| <$type2>::from(v)).collect();
+                Self::create(m, &h)
+            }
+
             /// create prototype (root-)Nyquist interpolator
             ///  type_  :   filter type (e.g. LIQUID_NYQUIST_RCOS)
             ///  k      :   samples/symbol,          k > 1
@@ -68,29 +80,32 @@ macro_rules! firinterp_impl {
             ) -> LiquidResult<Self> {
                 // check params
                 if k < 1 {
-                    return Err(LiquidError::InvalidValue(
+                    return Err(FilterError::InvalidValue(
                         "interp factor must be greater than 1".to_owned(),
-                    ));
+                    )
+                    .into());
                 } else if m <= 0 {
-                    return Err(LiquidError::InvalidValue(
+                    return Err(FilterError::InvalidValue(
                         "filter delay must be greater than 0".to_owned(),
-                    ));
-                } else if beta < 0f32 || beta > 1f32 {
-                    return Err(LiquidError::InvalidValue(
+                    )
+                    .into());
+                } else if !(0f32..=1f32).contains(&beta) {
+                    return Err(FilterError::InvalidValue(
                         "filter excess bandwidth factor must be in [0,1]".to_owned(),
-                    ));
-                } else if dt < -1f32 || dt > 1f32 {
-                    return Err(LiquidError::InvalidValue(
+                    )
+                    .into());
+                } else if !(-1f32..=1f32).contains(&dt) {
+                    return Err(FilterError::InvalidValue(
                         "filter fractional sample delay must be in [-1,1]".to_owned(),
-                    ));
+                    )
+                    .into());
                 }
+                let t: u8 = type_.into();
+                let mut hf = vec![0f32; (2 * k * m + 1) as usize];
                 unsafe {
-                    let t: u8 = type_.into();
-                    Ok(Self {
-                        inner: $prototype(t as _, k as _, m as _, beta, dt),
-                        len: (2 * k * m + 1) as usize,
-                    })
+                    raw::liquid_firdes_prototype(t as _, k as _, m as _, beta, dt, hf.as_mut_ptr());
                 }
+                Self::from_prototype(k, &hf)
             }
 
             /// create interpolator from Kaiser prototype
@@ -100,23 +115,33 @@ macro_rules! firinterp_impl {
             pub fn create_kaiser(M: u32, m: u32, as_: f32) -> LiquidResult<Self> {
                 // check params
                 if M < 2 {
-                    return Err(LiquidError::InvalidValue(
+                    return Err(FilterError::InvalidValue(
                         "interp factor must be greater than 2".to_owned(),
-                    ));
+                    )
+                    .into());
                 } else if m <= 0 {
-                    return Err(LiquidError::InvalidValue(
+                    return Err(FilterError::InvalidValue(
                         "filter delay must be greater than 0".to_owned(),
-                    ));
+                    )
+                    .into());
                 } else if as_ < 0f32 {
-                    return Err(LiquidError::InvalidValue(
+                    return Err(FilterError::InvalidValue(
                         "stop-band attenuation must be positive".to_owned(),
-                    ));
+                    )
+                    .into());
                 }
 
-                Ok(Self {
-                    inner: unsafe { $kaiser(M as _, m as _, as_) },
-                    len: (2 * M * m + 1) as usize,
-                })
+                let mut hf = vec![0f32; (2 * M * m + 1) as usize];
+                unsafe {
+                    raw::liquid_firdes_kaiser(
+                        hf.len() as _,
+                        0.5 / M as f32,
+                        as_,
+                        0f32,
+                        hf.as_mut_ptr(),
+                    );
+                }
+                Self::from_prototype(M, &hf)
             }
 
             /// print to stdout a firinterp object internals
@@ -150,17 +175,50 @@ macro_rules! firinterp_impl {
 
             /// Get the filters length
             pub fn len(&self) -> usize {
-                self.len
+                self.h.len()
+            }
+
+            pub fn is_empty(&self) -> bool {
+                self.len() == 0
+            }
+
+            /// interpolation factor, number of output samples per input sample
+            pub fn get_interp_factor(&self) -> u32 {
+                self.m
+            }
+
+            /// number of output samples execute_block() produces from
+            /// *input_len* samples
+            pub fn output_len_for(&self, input_len: usize) -> usize {
+                input_len * self.m as usize
+            }
+
+            /// number of input samples needed to produce at least
+            /// *output_len* samples
+            pub fn required_input_for(&self, output_len: usize) -> usize {
+                output_len.div_ceil(self.m as usize)
+            }
+
+            /// filter coefficients, without the output scaling
+            pub fn coefficients(&self) -> &[$type2] {
+                &self.h
+            }
+
+            /// filter coefficients split into its interp factor polyphase
+            /// branches, branch i producing output sample i of every input
+            pub fn branches(&self) -> Vec<Vec<$type2>> {
+                polyphase::decompose(&self.h, self.m as usize)
+                    .expect("filter length cannot be less than interp factor")
             }
 
             // execute interpolator
             //  q      : interpolator object
             //  x      : input sample
-            //  y      : output array
-            pub fn execute(&self, x: $type, y: &mut [$type]) {
+            //  y      : output array [size: interp factor x 1]
+            pub fn execute(&mut self, x: $type, y: &mut [$type]) {
                 assert!(
-                    y.len() == self.len,
-                    "y.len() is not equal to the filter length"
+                    y.len() == self.m as usize,
+                    "y.len() is not equal to the interpolation factor"
                 );
                 unsafe {
                     $execute(self.inner, x.to_c_value(), y.to_ptr_mut());
@@ -168,12 +226,30 @@ macro_rules! firinterp_impl {
             }
 
             /// execute interpolation on block of input samples
-            pub fn execute_block(&self, x: &[$type], y: &mut [$type]) {
-                assert!(x.len() == y.len(), "x and y must have same length");
+            ///  x      :   input array [size: n x 1]
+            ///  y      :   output array [size: interp factor * n x 1]
+            pub fn execute_block(&mut self, x: &[$type], y: &mut [$type]) {
+                assert!(
+                    y.len() == x.len() * self.m as usize,
+                    "y.len() must be the interpolation factor times x.len()"
+                );
                 unsafe {
                     $block(self.inner, x.to_ptr() as _, x.len() as _, y.to_ptr_mut());
                 }
             }
+
+            /// push zero input samples through the interpolator until its
+            /// group delay, half the prototype length, is covered, appending
+            /// the tail to out so that a burst is not truncated; the
+            /// interpolator is reset afterwards, ready for the next burst
+            pub fn flush(&mut self, out: &mut Vec<$type>) {
+                let n = ((self.h.len() - 1) / 2).div_ceil(self.m as usize);
+                let zeros = vec![<$type>::default(); n];
+                let start = out.len();
+                out.resize(start + self.output_len_for(n), <$type>::default());
+                self.execute_block(&zeros, &mut out[start..]);
+                self.reset();
+            }
         }
 
         impl Drop for $obj {
@@ -190,8 +266,6 @@ firinterp_impl!(
     FirInterpRrrf,
     (
         raw::firinterp_rrrf_create,
-        raw::firinterp_rrrf_create_prototype,
-        raw::firinterp_rrrf_create_kaiser,
         raw::firinterp_rrrf_print,
         raw::firinterp_rrrf_reset,
         raw::firinterp_rrrf_set_scale,
@@ -208,8 +282,6 @@ firinterp_impl!(
     FirInterpCrcf,
     (
         raw::firinterp_crcf_create,
-        raw::firinterp_crcf_create_prototype,
-        raw::firinterp_crcf_create_kaiser,
         raw::firinterp_crcf_print,
         raw::firinterp_crcf_reset,
         raw::firinterp_crcf_set_scale,
@@ -226,8 +298,6 @@ firinterp_impl!(
     FirInterpCccf,
     (
         raw::firinterp_cccf_create,
-        raw::firinterp_cccf_create_prototype,
-        raw::firinterp_cccf_create_kaiser,
         raw::firinterp_cccf_print,
         raw::firinterp_cccf_reset,
         raw::firinterp_cccf_set_scale,
@@ -247,10 +317,42 @@ mod tests {
     #[test]
     fn test_execute_rrrf() {
         let h = [2.0; 6];
-        let firinterp_rrrf = FirInterpRrrf::create(6, &h).unwrap();
+        let mut firinterp_rrrf = FirInterpRrrf::create(6, &h).unwrap();
         let mut res = vec![0f32; firinterp_rrrf.len()];
         firinterp_rrrf.execute(0.5, &mut res);
         println!("res {:?}", res);
         assert_eq!(res, vec![1.0; firinterp_rrrf.len()]);
     }
+
+    #[test]
+    fn test_coefficients_rrrf() {
+        let h = [0.5, 1.0, 0.5, 0.25];
+        let interp = FirInterpRrrf::create(2, &h).unwrap();
+        assert_eq!(interp.coefficients(), &h);
+        assert_eq!(interp.branches(), vec![vec![0.5, 0.5], vec![1.0, 0.25]]);
+        assert_eq!(interp.output_len_for(5), 10);
+        assert_eq!(interp.required_input_for(9), 5);
+
+        let interp = FirInterpRrrf::create_kaiser(4, 3, 60.0).unwrap();
+        assert_eq!(interp.coefficients().len(), 25);
+    }
+
+    #[test]
+    fn test_flush_rrrf() {
+        // 25 taps, delay of 12 output samples, 3 input samples
+        let mut q = FirInterpRrrf::create_kaiser(4, 3, 60.0).unwrap();
+        let h = q.coefficients().to_vec();
+        let mut y = vec![0f32; 4];
+        q.execute(1.0, &mut y);
+        q.flush(&mut y);
+        assert_eq!(y.len(), 16);
+        // the impulse response up to and past its peak at sample 12
+        for (a, b) in y.iter().zip(h.iter()) {
+            assert!((a - b).abs() < 1e-6);
+        }
+
+        let mut y = vec![0f32; 4];
+        q.execute(0.0, &mut y);
+        assert_eq!(y, [0.0; 4]);
+    }
 }
@@ -0,0 +1,139 @@
+//! *gdcorr* : group delay equalization, designs all-pass correctors that
+//!           flatten the group delay of a filter across a band
+use std::f32::consts::PI;
+
+/// largest pole radius of a section, keeps the corrector away from
+/// the unit circle
+const MAX_RADIUS: f32 = 0.98;
+const MAX_ITERATIONS: usize = 4000;
+const MIN_STEP: f32 = 1e-5;
+
+/// second-order all-pass section with poles at r*exp(+/-j*theta)
+#[derive(Clone, Copy)]
+struct Section {
+    r: f32,
+    theta: f32,
+}
+
+impl Section {
+    /// group delay of the section [samples] at angular frequency w
+    fn group_delay(&self, w: f32) -> f32 {
+        let r2 = self.r * self.r;
+        let pole = |theta: f32| (1.0 - r2) / (1.0 - 2.0 * self.r * (w - theta).cos() + r2);
+        pole(self.theta) + pole(-self.theta)
+    }
+}
+
+/// variance of the total group delay across the band
+fn cost(delay: &[f32], freqs: &[f32], sections: &[Section]) -> f32 {
+    let total: Vec<f32> = delay
+        .iter()
+        .zip(freqs.iter())
+        .map(|(&d, &f)| {
+            d + sections
+                .iter()
+                .map(|s| s.group_delay(2.0 * PI * f))
+                .sum::<f32>()
+        })
+        .collect();
+    let mean = total.iter().sum::<f32>() / total.len() as f32;
+    total.iter().map(|t| (t - mean).powi(2)).sum::<f32>() / total.len() as f32
+}
+
+/// design the second-order sections of an all-pass corrector by
+/// coordinate descent on the pole radius and angle of each section
+///  delay  :   group delay to equalize [samples] at each frequency
+///  freqs  :   increasing normalized frequencies of the delay, in [0, 0.5]
+///  nsos   :   number of second-order sections
+/// # returns
+/// the numerator and denominator coefficients [size: 3*nsos x 1 each]
+pub(crate) fn design_allpass_sos(
+    delay: &[f32],
+    freqs: &[f32],
+    nsos: usize,
+) -> (Vec<f32>, Vec<f32>) {
+    let (f0, f1) = (freqs[0], freqs[freqs.len() - 1]);
+    let mut sections: Vec<Section> = (0..nsos)
+        .map(|k| Section {
+            r: 0.5,
+            theta: 2.0 * PI * (f0 + (k as f32 + 0.5) * (f1 - f0) / nsos as f32),
+        })
+        .collect();
+
+    let mut best = cost(delay, freqs, &sections);
+    let mut step = 0.1f32;
+    for _ in 0..MAX_ITERATIONS {
+        if step < MIN_STEP {
+            break;
+        }
+        let mut improved = false;
+        for k in 0..2 * nsos {
+            for &dir in [1f32, -1f32].iter() {
+                let mut trial = sections.clone();
+                let s = &mut trial[k / 2];
+                if k % 2 == 0 {
+                    s.r = (s.r + dir * step).clamp(0.0, MAX_RADIUS);
+                } else {
+                    s.theta = (s.theta + dir * step * PI).clamp(0.0, PI);
+                }
+                let c = cost(delay, freqs, &trial);
+                if c < best {
+                    best = c;
+                    sections = trial;
+                    improved = true;
+                    break;
+                }
+            }
+        }
+        if !improved {
+            step *= 0.5;
+        }
+    }
+
+    let mut b = Vec::with_capacity(3 * nsos);
+    let mut a = Vec::with_capacity(3 * nsos);
+    for s in sections.iter() {
+        let c = -2.0 * s.r * s.theta.cos();
+        let r2 = s.r * s.r;
+        b.extend_from_slice(&[r2, c, 1.0]);
+        a.extend_from_slice(&[1.0, c, r2]);
+    }
+    (b, a)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{cost, design_allpass_sos, Section};
+
+    #[test]
+    fn test_allpass_flattens_delay() {
+        let freqs: Vec<f32> = (0..64).map(|i| 0.2 * i as f32 / 63.0).collect();
+        // delay peaking at the band edge, as in an elliptic low-pass
+        let delay: Vec<f32> = freqs
+            .iter()
+            .map(|&f| 4.0 + 6.0 * (-((f - 0.2) / 0.05).powi(2)).exp())
+            .collect();
+        let initial = cost(&delay, &freqs, &[]);
+
+        let (b, a) = design_allpass_sos(&delay, &freqs, 2);
+        assert_eq!(b.len(), 6);
+        for (bs, as_) in b.chunks(3).zip(a.chunks(3)) {
+            assert_eq!(bs, [as_[2], as_[1], as_[0]]);
+            assert!(as_[2] < 1.0);
+        }
+
+        let sections: Vec<Section> = a
+            .chunks(3)
+            .map(|c| {
+                let r = c[2].sqrt();
+                let theta = if r > 0.0 {
+                    (-c[1] / (2.0 * r)).clamp(-1.0, 1.0).acos()
+                } else {
+                    0.0
+                };
+                Section { r, theta }
+            })
+            .collect();
+        assert!(cost(&delay, &freqs, &sections) < 0.25 * initial);
+    }
+}
@@ -1,20 +1,26 @@
 use num::complex::Complex32;
 
-use crate::filter::IirdesFilterType;
+use crate::filter::{IirdesBandType, IirdesFilterType, IirdesFormat};
 use crate::liquid_dsp_sys as raw;
-use crate::utils::{ToCPointer, ToCPointerMut, ToCValue};
+use crate::utils::{check_same_len_as, ToCPointer, ToCPointerMut, ToCValue};
 
-use crate::errors::LiquidError;
+use crate::errors::FilterError;
 use crate::LiquidResult;
 
 /// infinite impulse response (IIR) Hilbert transform
 pub struct IirHilbt {
     inner: raw::iirhilbf,
+    ftype: IirdesFilterType,
+    n: usize,
+    ap: f32,
+    as_: f32,
 }
 
 /// finite impulse response (FIR) Hilbert transform
 pub struct FirHilbt {
     inner: raw::firhilbf,
+    m: u32,
+    as_: f32,
 }
 
 macro_rules! hilbertimpl {
@@ -48,9 +54,26 @@ macro_rules! hilbertimpl {
                 y
             }
 
+            /// execute Hilbert transform (real to complex) on a block of
+            /// samples
+            ///  x      :   real-valued input array [size: _n x 1]
+            ///  y      :   complex-valued output array [size: _n x 1]
+            pub fn r2c_execute_block(&self, x: &[f32], y: &mut [Complex32]) -> LiquidResult<()> {
+                check_same_len_as::<FilterError>(x.len(), y.len())?;
+                x.iter()
+                    .zip(y.iter_mut())
+                    .for_each(|(x, y)| unsafe { $r2c(self.inner, *x, y.to_ptr_mut()) });
+                Ok(())
+            }
+
+            /// stop-band attenuation [dB]
+            pub fn get_as(&self) -> f32 {
+                self.as_
+            }
+
             /// execute Hilbert transform decimator (real to complex)
             ///  x      :   real-valued input array [size: 2 x 1]
-            pub fn decim_execute(&self, x: (f32, f32)) -> Complex32 {
+            pub fn decim_execute(&mut self, x: (f32, f32)) -> Complex32 {
                 let mut y = Complex32::default();
                 let x: *const f32 = &x.0;
                 unsafe {
@@ -63,7 +86,7 @@ macro_rules! hilbertimpl {
             /// a block of samples
             ///  x      :   real-valued input array [size: 2*_n x 1]
             ///  y      :   complex-valued output array [size: _n x 1]
-            pub fn decim_execute_block(&self, x: &[f32], y: &mut [Complex32]) {
+            pub fn decim_execute_block(&mut self, x: &[f32], y: &mut [Complex32]) {
                 assert!(
                     x.len() == 2 * y.len(),
                     "x must be 2 times more elements than y"
@@ -75,7 +98,7 @@ macro_rules! hilbertimpl {
 
             /// execute Hilbert transform interpolator (complex to real)
             ///  x      :   real-valued output array [size: 2 x 1]
-            pub fn interp_execute(&self, x: Complex32) -> (f32, f32) {
+            pub fn interp_execute(&mut self, x: Complex32) -> (f32, f32) {
                 unsafe {
                     let mut y = (0f32, 0f32);
                     let ptr: *mut f32 = &mut y.0;
@@ -88,7 +111,7 @@ macro_rules! hilbertimpl {
             /// on a block of samples
             ///  x      :   complex-valued input array [size: _n x 1]
             ///  y      :   real-valued output array [size: 2*_n x 1]
-            pub fn interp_execute_block(&self, x: &[Complex32], y: &mut [f32]) {
+            pub fn interp_execute_block(&mut self, x: &[Complex32], y: &mut [f32]) {
                 assert!(
                     y.len() >= 2 * x.len(),
                     "y must have 2 times more elements than x"
@@ -145,29 +168,79 @@ impl IirHilbt {
     ///  as_    : stop-band ripple [dB], as_ > 0
     pub fn create(ftype: IirdesFilterType, n: usize, ap: f32, as_: f32) -> LiquidResult<Self> {
         if n == 0 {
-            return Err(LiquidError::InvalidValue(
+            return Err(FilterError::InvalidValue(
                 "filter order must be greater than 0".to_owned(),
-            ));
+            )
+            .into());
         }
-        let ftype: u8 = ftype.into();
+        let t: u8 = ftype.into();
         Ok(Self {
-            inner: unsafe { raw::iirhilbf_create(ftype as _, n as _, ap, as_) },
+            inner: unsafe { raw::iirhilbf_create(t as _, n as _, ap, as_) },
+            ftype,
+            n,
+            ap,
+            as_,
         })
     }
 
-    /// Create a default iirhilb object with a particular filter order.
+    /// Create a default iirhilb object with a particular filter order,
+    /// a Butterworth design with 0.1 dB pass-band and 60 dB stop-band ripple
     ///  n      : filter order, n > 0
     pub fn create_default(n: usize) -> LiquidResult<Self> {
         if n == 0 {
-            return Err(LiquidError::InvalidValue(
+            return Err(FilterError::InvalidValue(
                 "filter order must be greater than 0".to_owned(),
-            ));
+            )
+            .into());
         }
         Ok(Self {
             inner: unsafe { raw::iirhilbf_create_default(n as _) },
+            ftype: IirdesFilterType::BUTTER,
+            n,
+            ap: 0.1,
+            as_: 60.0,
         })
     }
 
+    /// filter type
+    pub fn get_filter_type(&self) -> IirdesFilterType {
+        self.ftype
+    }
+
+    /// filter order
+    pub fn get_order(&self) -> usize {
+        self.n
+    }
+
+    /// pass-band ripple [dB]
+    pub fn get_ap(&self) -> f32 {
+        self.ap
+    }
+
+    /// delay of the transform [samples], the group delay of the half-band
+    /// low-pass prototype at the centre of the retained side-band; being an
+    /// IIR design the delay varies across the band
+    pub fn delay(&self) -> f32 {
+        let len = self.n + 1;
+        let mut b = vec![0f32; len];
+        let mut a = vec![0f32; len];
+        unsafe {
+            raw::liquid_iirdes(
+                u8::from(self.ftype) as _,
+                u8::from(IirdesBandType::LOWPASS) as _,
+                u8::from(IirdesFormat::TF) as _,
+                self.n as _,
+                0.25,
+                0.0,
+                self.ap,
+                self.as_,
+                b.as_mut_ptr(),
+                a.as_mut_ptr(),
+            );
+            raw::iir_group_delay(b.as_mut_ptr(), len as _, a.as_mut_ptr(), len as _, 0.0)
+        }
+    }
+
     /// execute Hilbert transform (complex to real)
     ///  x      :   complex-valued input sample
     pub fn c2r_execute(&self, x: Complex32) -> f32 {
@@ -177,23 +250,63 @@ impl IirHilbt {
         }
         y
     }
+
+    /// execute Hilbert transform (complex to real) on a block of samples
+    ///  x      :   complex-valued input array [size: _n x 1]
+    ///  y      :   real-valued output array [size: _n x 1]
+    pub fn c2r_execute_block(&self, x: &[Complex32], y: &mut [f32]) -> LiquidResult<()> {
+        check_same_len_as::<FilterError>(x.len(), y.len())?;
+        x.iter()
+            .zip(y.iter_mut())
+            .for_each(|(x, y)| *y = self.c2r_execute(*x));
+        Ok(())
+    }
 }
 
 impl FirHilbt {
     /// create firhilb object
-    ///  m      :   filter semi-length m > 2. note: (delay: 2*m+1)
+    ///  m      :   filter semi-length m >= 2. note: (length: 4*m+1, delay: 2*m)
     ///  as_    : stop-band ripple [dB], as_ > 0
     pub fn create(m: u32, as_: f32) -> LiquidResult<Self> {
         if m < 2 {
-            return Err(LiquidError::InvalidValue(
-                "filter order must be greater than 0".to_owned(),
-            ));
+            return Err(FilterError::InvalidValue(
+                "filter semi-length must be at least 2".to_owned(),
+            )
+            .into());
         }
         Ok(Self {
             inner: unsafe { raw::firhilbf_create(m as _, as_) },
+            m,
+            as_,
         })
     }
 
+    /// Create a default firhilb object with a particular semi-length and
+    /// 60 dB of stop-band attenuation
+    ///  m      :   filter semi-length m >= 2
+    pub fn create_default(m: u32) -> LiquidResult<Self> {
+        Self::create(m, 60.0)
+    }
+
+    /// filter semi-length
+    pub fn get_semi_length(&self) -> u32 {
+        self.m
+    }
+
+    /// filter length, 4*m+1
+    pub fn len(&self) -> usize {
+        4 * self.m as usize + 1
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// delay of the transform [samples], 2*m
+    pub fn delay(&self) -> usize {
+        2 * self.m as usize
+    }
+
     /// execute Hilbert transform (complex to real)
     ///  x      :   complex-valued input sample
     /// # returns
@@ -209,4 +322,55 @@ impl FirHilbt {
         }
         y
     }
+
+    /// execute Hilbert transform (complex to real) on a block of samples
+    ///  x      :   complex-valued input array [size: _n x 1]
+    ///  y0     :   real-valued output array, lower side-band retained [size: _n x 1]
+    ///  y1     :   real-valued output array, upper side-band retained [size: _n x 1]
+    pub fn c2r_execute_block(
+        &self,
+        x: &[Complex32],
+        y0: &mut [f32],
+        y1: &mut [f32],
+    ) -> LiquidResult<()> {
+        check_same_len_as::<FilterError>(x.len(), y0.len())?;
+        check_same_len_as::<FilterError>(x.len(), y1.len())?;
+        for (i, v) in x.iter().enumerate() {
+            let (a, b) = self.c2r_execute(*v);
+            y0[i] = a;
+            y1[i] = b;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FirHilbt, IirHilbt};
+    use crate::filter::IirdesFilterType;
+
+    #[test]
+    fn test_hilbert_parameters() {
+        let fir = FirHilbt::create_default(5).unwrap();
+        assert_eq!(fir.len(), 21);
+        assert_eq!(fir.delay(), 10);
+        assert_eq!(fir.get_as(), 60.0);
+        assert!(!fir.is_empty());
+        assert!(FirHilbt::create_default(1).is_err());
+
+        let iir = IirHilbt::create(IirdesFilterType::CHEBY1, 7, 0.5, 40.0).unwrap();
+        assert_eq!(iir.get_filter_type(), IirdesFilterType::CHEBY1);
+        assert_eq!(iir.get_order(), 7);
+        assert_eq!(iir.get_ap(), 0.5);
+        // a higher order prototype is slower
+        let delay = iir.delay();
+        assert!(delay > 0.0 && delay.is_finite());
+        let iir3 = IirHilbt::create(IirdesFilterType::CHEBY1, 3, 0.5, 40.0).unwrap();
+        assert!(iir3.delay() < delay);
+
+        let mut y = vec![0f32; 3];
+        assert!(iir
+            .c2r_execute_block(&[Default::default(); 4], &mut y)
+            .is_err());
+    }
 }
@@ -1,15 +1,41 @@
+use num::complex::{Complex32, Complex64};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "serde")]
+use std::convert::TryFrom;
+
 use crate::liquid_dsp_sys as raw;
 
+use crate::errors::FilterError;
+use crate::filter::filter::polyval_unit_circle;
+use crate::filter::{FrequencyResponse, IirdesBandType};
 use crate::LiquidResult;
-use crate::errors::LiquidError;
-
-pub use filter::{FilterAnalysis, IirdesBandType, IirdesFilterType};
 
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "IirCoefficients"))]
 pub struct Iir {
     a: Vec<f32>,
     b: Vec<f32>,
 }
 
+/// unchecked coefficients of a deserialized design, validated by Iir::create
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+struct IirCoefficients {
+    a: Vec<f32>,
+    b: Vec<f32>,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<IirCoefficients> for Iir {
+    type Error = crate::errors::LiquidError;
+
+    fn try_from(c: IirCoefficients) -> Result<Self, Self::Error> {
+        Iir::create(&c.b, &c.a)
+    }
+}
+
 pub struct Iirdes {}
 
 impl Iir {
@@ -20,6 +46,37 @@ impl Iir {
         }
     }
 
+    /// create an IIR design from its transfer function coefficients
+    ///  b      :   numerator, feed-forward coefficients
+    ///  a      :   denominator, feed-back coefficients, a[0] != 0
+    pub fn create(b: &[f32], a: &[f32]) -> LiquidResult<Self> {
+        if b.is_empty() || a.is_empty() {
+            return Err(FilterError::InvalidLength {
+                description: "numerator and denominator cannot be empty".to_owned(),
+            }
+            .into());
+        } else if a[0] == 0f32 {
+            return Err(FilterError::InvalidValue(
+                "first denominator coefficient cannot be zero".to_owned(),
+            )
+            .into());
+        }
+        Ok(Self {
+            a: a.to_vec(),
+            b: b.to_vec(),
+        })
+    }
+
+    /// numerator, feed-forward coefficients
+    pub fn b(&self) -> &[f32] {
+        self.b.as_ref()
+    }
+
+    /// denominator, feed-back coefficients
+    pub fn a(&self) -> &[f32] {
+        self.a.as_ref()
+    }
+
     pub fn is_stable(&self) -> bool {
         unsafe {
             raw::iirdes_isstable(
@@ -31,6 +88,32 @@ impl Iir {
     }
 }
 
+impl FrequencyResponse for Iir {
+    fn freq_response(&self, fc: f32) -> LiquidResult<Complex32> {
+        if !(-0.5..=0.5).contains(&fc) {
+            return Err(FilterError::InvalidValue("fc must be in [-0.5, 0.5]".to_owned()).into());
+        }
+        Ok(polyval_unit_circle(&self.b, fc) / polyval_unit_circle(&self.a, fc))
+    }
+
+    /// Compute group delay for an IIR filter
+    ///  fc     : frequency at which delay is evaluated (-0.5 < _fc < 0.5)
+    fn group_delay(&self, fc: f32) -> LiquidResult<f32> {
+        if !(-0.5..=0.5).contains(&fc) {
+            return Err(FilterError::InvalidValue("fc must be in [-0.5, 0.5]".to_owned()).into());
+        }
+        unsafe {
+            Ok(raw::iir_group_delay(
+                self.b.as_ptr() as _,
+                self.b.len() as _,
+                self.a.as_ptr() as _,
+                self.a.len() as _,
+                fc,
+            ))
+        }
+    }
+}
+
 impl Iirdes {
     /// Compute frequency pre-warping factor.  See [Constantinides:1967]
     ///  btype  :   band type (e.g. IirdesBandType::HIGHPASS)
@@ -38,9 +121,9 @@ impl Iirdes {
     ///  f0     :   center frequency (band-pass|stop cases only)
     pub fn freq_prewarp(btype: IirdesBandType, fc: f32, f0: f32) -> LiquidResult<f32> {
         if fc <= 0f32 {
-            return Err(LiquidError::InvalidValue(
-                "fc must  be higher than zero".to_owned(),
-            ));
+            return Err(
+                FilterError::InvalidValue("fc must  be higher than zero".to_owned()).into(),
+            );
         }
         let btype: u8 = btype.into();
         unsafe { Ok(raw::iirdes_freqprewarp(btype as _, fc, f0)) }
@@ -56,17 +139,13 @@ impl Iirdes {
     ///  K      :   loop gain (1000 suggested)
     pub fn pll_active_lag(w: f32, zeta: f32, k: f32) -> LiquidResult<Iir> {
         if w <= 0f32 {
-            return Err(LiquidError::InvalidValue(
-                "w must be greater than zero".to_owned(),
-            ));
+            return Err(FilterError::InvalidValue("w must be greater than zero".to_owned()).into());
         } else if zeta <= 0f32 {
-            return Err(LiquidError::InvalidValue(
-                "zeta must be greater than zero".to_owned(),
-            ));
+            return Err(
+                FilterError::InvalidValue("zeta must be greater than zero".to_owned()).into(),
+            );
         } else if k <= 0f32 {
-            return Err(LiquidError::InvalidValue(
-                "k must be greater than zero".to_owned(),
-            ));
+            return Err(FilterError::InvalidValue("k must be greater than zero".to_owned()).into());
         }
 
         let mut iir = Iir::new(3, 3);
@@ -86,17 +165,13 @@ impl Iirdes {
     ///  K      :   loop gain (1000 suggested)
     pub fn pll_active_pi(w: f32, zeta: f32, k: f32) -> LiquidResult<Iir> {
         if w <= 0f32 {
-            return Err(LiquidError::InvalidValue(
-                "w must be greater than zero".to_owned(),
-            ));
+            return Err(FilterError::InvalidValue("w must be greater than zero".to_owned()).into());
         } else if zeta <= 0f32 {
-            return Err(LiquidError::InvalidValue(
-                "zeta must be greater than zero".to_owned(),
-            ));
+            return Err(
+                FilterError::InvalidValue("zeta must be greater than zero".to_owned()).into(),
+            );
         } else if k <= 0f32 {
-            return Err(LiquidError::InvalidValue(
-                "k must be greater than zero".to_owned(),
-            ));
+            return Err(FilterError::InvalidValue("k must be greater than zero".to_owned()).into());
         }
         let mut iir = Iir::new(3, 3);
         unsafe {
@@ -104,4 +179,102 @@ impl Iirdes {
         }
         Ok(iir)
     }
+
+    /// design digital low-pass Butterworth filter in double precision,
+    /// through the bilinear transform of the analog prototype
+    ///  order  :   filter order, order > 0
+    ///  fc     :   cutoff frequency, 0 < fc < 0.5
+    /// # returns
+    /// the numerator b and denominator a coefficients [size: order+1 x 1],
+    /// high orders only remain stable when filtered in double precision
+    pub fn butter_f64(order: usize, fc: f64) -> LiquidResult<(Vec<f64>, Vec<f64>)> {
+        if order == 0 {
+            return Err(FilterError::InvalidValue(
+                "filter order must be greater than zero".to_owned(),
+            )
+            .into());
+        } else if fc <= 0.0 || fc >= 0.5 {
+            return Err(FilterError::InvalidValue(
+                "cutoff frequency out of range (0, 0.5)".to_owned(),
+            )
+            .into());
+        }
+        // pre-warped analog poles, mapped to the z-plane
+        let wc = (std::f64::consts::PI * fc).tan();
+        let one = Complex64::new(1.0, 0.0);
+        let mut a = vec![one];
+        for k in 0..order {
+            let theta = std::f64::consts::PI * (2 * k + order + 1) as f64 / (2 * order) as f64;
+            let p = Complex64::from_polar(wc, theta);
+            let z = (one + p) / (one - p);
+            // a(z) *= (1 - z_k z^-1)
+            a.push(Complex64::default());
+            for i in (1..a.len()).rev() {
+                a[i] = a[i] - z * a[i - 1];
+            }
+        }
+        let a: Vec<f64> = a.iter().map(|v| v.re).collect();
+        // all zeros at z = -1, scaled for unity gain at DC
+        let mut b = vec![1f64; order + 1];
+        for i in 1..=order {
+            b[i] = b[i - 1] * (order + 1 - i) as f64 / i as f64;
+        }
+        let g = a.iter().sum::<f64>() / b.iter().sum::<f64>();
+        b.iter_mut().for_each(|v| *v *= g);
+        Ok((b, a))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Iir, Iirdes};
+    use crate::filter::FrequencyResponse;
+    use num::complex::Complex64;
+
+    #[test]
+    fn test_iir_freq_response() {
+        // one-pole low-pass: H(z) = 0.5 / (1 - 0.5 z^-1)
+        let iir = Iir::create(&[0.5], &[1.0, -0.5]).unwrap();
+        assert_eq!(iir.b(), &[0.5]);
+        assert_eq!(iir.a(), &[1.0, -0.5]);
+        assert!((iir.freq_response(0.0).unwrap().norm() - 1.0).abs() < 1e-6);
+        assert!((iir.freq_response(0.5).unwrap().norm() - 1.0 / 3.0).abs() < 1e-6);
+        assert!(iir.freq_response(0.7).is_err());
+        assert!(Iir::create(&[1.0], &[0.0, 1.0]).is_err());
+    }
+
+    #[test]
+    fn test_iirdes_butter_f64() {
+        let (b, a) = Iirdes::butter_f64(12, 0.1).unwrap();
+        assert_eq!((b.len(), a.len()), (13, 13));
+        assert!((a[0] - 1.0).abs() < 1e-12);
+        let h = |f: f64| {
+            let z = |c: &[f64]| -> Complex64 {
+                c.iter()
+                    .enumerate()
+                    .map(|(n, &v)| {
+                        Complex64::from_polar(v, -2.0 * std::f64::consts::PI * f * n as f64)
+                    })
+                    .sum()
+            };
+            (z(&b) / z(&a)).norm()
+        };
+        assert!((h(0.0) - 1.0).abs() < 1e-9);
+        assert!((h(0.1) - 0.5f64.sqrt()).abs() < 1e-9);
+        assert!(h(0.3) < 1e-6);
+        assert!(Iirdes::butter_f64(0, 0.1).is_err());
+        assert!(Iirdes::butter_f64(4, 0.5).is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_iir_serde() {
+        let iir = Iir::create(&[0.5, 0.5], &[1.0, -0.2]).unwrap();
+        let json = serde_json::to_string(&iir).unwrap();
+        let back: Iir = serde_json::from_str(&json).unwrap();
+        assert_eq!((back.b(), back.a()), (iir.b(), iir.a()));
+        // the coefficients are validated as by Iir::create
+        assert!(serde_json::from_str::<Iir>(r#"{"a":[0.0,1.0],"b":[1.0]}"#).is_err());
+        assert!(serde_json::from_str::<Iir>(r#"{"a":[],"b":[1.0]}"#).is_err());
+    }
 }
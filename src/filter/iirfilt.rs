@@ -1,11 +1,12 @@
 #![allow(non_camel_case_types, non_upper_case_globals, non_snake_case)]
 use num::complex::Complex32;
 
+use crate::filter::gdcorr::design_allpass_sos;
 use crate::filter::{IirdesBandType, IirdesFilterType, IirdesFormat};
 use crate::liquid_dsp_sys as raw;
 use crate::utils::{ToCPointer, ToCPointerMut, ToCValue};
 
-use crate::errors::LiquidError;
+use crate::errors::FilterError;
 use crate::LiquidResult;
 
 pub struct IirFiltRrrf {
@@ -44,14 +45,16 @@ macro_rules! iirfilt_impl {
             ///  a      :   denominator, feed-back coefficients
             pub fn create(a: &[$type], b: &[$type]) -> LiquidResult<$obj> {
                 if b.is_empty() {
-                    return Err(LiquidError::InvalidValue(
+                    return Err(FilterError::InvalidValue(
                         "numerator length cannot be zero".to_owned(),
-                    ));
+                    )
+                    .into());
                 }
                 if a.is_empty() {
-                    return Err(LiquidError::InvalidValue(
+                    return Err(FilterError::InvalidValue(
                         "denominator length cannot be zero".to_owned(),
-                    ));
+                    )
+                    .into());
                 }
 
                 Ok(Self {
@@ -61,6 +64,7 @@ macro_rules! iirfilt_impl {
                 })
             }
 
+            #[allow(clippy::too_many_arguments)]
             pub fn create_prototype(
                 ftype: IirdesFilterType,
                 btype: IirdesBandType,
@@ -72,25 +76,27 @@ macro_rules! iirfilt_impl {
                 as_: f32,
             ) -> LiquidResult<Self> {
                 if fc <= 0f32 || fc >= 0.5 {
-                    return Err(LiquidError::InvalidValue(
-                        "fc must be in (0, 0.5)".to_owned(),
-                    ));
-                } else if f0 < 0f32 || f0 > 0.5 {
-                    return Err(LiquidError::InvalidValue(
-                        "f0 must be in [0, 0.5]".to_owned(),
-                    ));
+                    return Err(
+                        FilterError::InvalidValue("fc must be in (0, 0.5)".to_owned()).into(),
+                    );
+                } else if !(0f32..=0.5).contains(&f0) {
+                    return Err(
+                        FilterError::InvalidValue("f0 must be in [0, 0.5]".to_owned()).into(),
+                    );
                 } else if ap <= 0f32 {
-                    return Err(LiquidError::InvalidValue(
-                        "ap must be greater than 0".to_owned(),
-                    ));
+                    return Err(
+                        FilterError::InvalidValue("ap must be greater than 0".to_owned()).into(),
+                    );
                 } else if as_ <= 0f32 {
-                    return Err(LiquidError::InvalidValue(
+                    return Err(FilterError::InvalidValue(
                         "as(stop-band ripple) must be greater than 0".to_owned(),
-                    ));
+                    )
+                    .into());
                 } else if order == 0 {
-                    return Err(LiquidError::InvalidValue(
+                    return Err(FilterError::InvalidValue(
                         "order must be greater than 0".to_owned(),
-                    ));
+                    )
+                    .into());
                 }
                 let ftype: u8 = ftype.into();
                 let btype: u8 = btype.into();
@@ -125,19 +131,21 @@ macro_rules! iirfilt_impl {
             pub fn create_sos(a: &[$type], b: &[$type], nsos: usize) -> LiquidResult<Self> {
                 let res;
                 if a.len() != b.len() {
-                    res = Err(LiquidError::InvalidLength {
+                    res = Err(FilterError::InvalidLength {
                         description: "numerator and denominator slices must have the same size"
                             .to_owned(),
-                    });
+                    }
+                    .into());
                 } else if a.is_empty() || a.len() < (3 * nsos) {
-                    res = Err(LiquidError::InvalidLength {
+                    res = Err(FilterError::InvalidLength {
                         description:
                             "numerator and denominator lengt cannot be zero or lesser than 3 * nsos"
                                 .to_owned(),
-                    });
+                    }
+                    .into());
                 } else {
                     res = Ok(Self {
-                        inner: unsafe { $sos(b.to_ptr() as _, a.to_ptr() as _, a.len() as _) },
+                        inner: unsafe { $sos(b.to_ptr() as _, a.to_ptr() as _, nsos as _) },
                     })
                 }
                 res
@@ -145,13 +153,14 @@ macro_rules! iirfilt_impl {
 
             pub fn create_lowpass(n: usize, fc: f32) -> LiquidResult<Self> {
                 if fc <= 0f32 || fc >= 0.5 {
-                    return Err(LiquidError::InvalidValue(
-                        "fc must be in (0, 0.5)".to_owned(),
-                    ));
+                    return Err(
+                        FilterError::InvalidValue("fc must be in (0, 0.5)".to_owned()).into(),
+                    );
                 } else if n == 0 {
-                    return Err(LiquidError::InvalidValue(
+                    return Err(FilterError::InvalidValue(
                         "order must be greater than 0".to_owned(),
-                    ));
+                    )
+                    .into());
                 }
 
                 Ok(Self {
@@ -159,6 +168,52 @@ macro_rules! iirfilt_impl {
                 })
             }
 
+            /// Butterworth low-pass filter in second-order sections
+            ///  order  :   filter order, order > 0
+            ///  fc     :   cutoff frequency, fc in (0, 0.5)
+            pub fn butter_lowpass(order: usize, fc: f32) -> LiquidResult<Self> {
+                Self::create_prototype(
+                    IirdesFilterType::BUTTER,
+                    IirdesBandType::LOWPASS,
+                    IirdesFormat::SOS,
+                    order,
+                    fc,
+                    0.0,
+                    1.0,
+                    60.0,
+                )
+            }
+
+            /// DC blocker with a notch bandwidth suitable for audio and
+            /// baseband signals, see create_dc_blocker()
+            pub fn dc_block() -> Self {
+                Self {
+                    inner: unsafe { $create_dc_blocker(1e-3) },
+                }
+            }
+
+            /// single pole de-emphasis filter, unity gain at DC
+            ///  tau         :   time constant in seconds (75e-6 for broadcast FM
+            ///                  in the Americas, 50e-6 elsewhere), tau > 0
+            ///  sample_rate :   sample rate in Hz, sample_rate > 0
+            pub fn deemphasis(tau: f32, sample_rate: f32) -> LiquidResult<Self> {
+                if tau <= 0f32 {
+                    return Err(FilterError::InvalidValue(
+                        "time constant must be greater than 0".to_owned(),
+                    )
+                    .into());
+                } else if sample_rate <= 0f32 {
+                    return Err(FilterError::InvalidValue(
+                        "sample rate must be greater than 0".to_owned(),
+                    )
+                    .into());
+                }
+                let p = (-1.0 / (tau * sample_rate)).exp();
+                let a = [<$type>::from(1.0), <$type>::from(-p)];
+                let b = [<$type>::from(1.0 - p)];
+                Self::create(&a, &b)
+            }
+
             pub fn create_integrator() -> Self {
                 Self {
                     inner: unsafe { $create_integrator() },
@@ -173,9 +228,10 @@ macro_rules! iirfilt_impl {
 
             pub fn create_dc_blocker(alpha: f32) -> LiquidResult<Self> {
                 if alpha <= 0f32 {
-                    return Err(LiquidError::InvalidValue(
+                    return Err(FilterError::InvalidValue(
                         "alpha must be greater than 0".to_owned(),
-                    ));
+                    )
+                    .into());
                 }
                 Ok(Self {
                     inner: unsafe { $create_dc_blocker(alpha) },
@@ -184,17 +240,20 @@ macro_rules! iirfilt_impl {
 
             pub fn create_pll(w: f32, zeta: f32, k: f32) -> LiquidResult<Self> {
                 if w <= 0f32 || w >= 1.0 {
-                    return Err(LiquidError::InvalidValue(
+                    return Err(FilterError::InvalidValue(
                         "bandwidth must be in (0, 1.0)".to_owned(),
-                    ));
+                    )
+                    .into());
                 } else if zeta <= 0f32 || zeta >= 1.0 {
-                    return Err(LiquidError::InvalidValue(
+                    return Err(FilterError::InvalidValue(
                         "damping factor must be in (0, 0.5)".to_owned(),
-                    ));
+                    )
+                    .into());
                 } else if k <= 0f32 {
-                    return Err(LiquidError::InvalidValue(
+                    return Err(FilterError::InvalidValue(
                         "loop gain must be greater than 0".to_owned(),
-                    ));
+                    )
+                    .into());
                 }
                 Ok(Self {
                     inner: unsafe { $create_pll(w, zeta, k) },
@@ -217,6 +276,10 @@ macro_rules! iirfilt_impl {
                 unsafe { $len(self.inner) as usize }
             }
 
+            pub fn is_empty(&self) -> bool {
+                self.len() == 0
+            }
+
             pub fn freq_response(&self, fc: f32) -> Complex32 {
                 let mut freq = Complex32::default();
                 unsafe {
@@ -229,9 +292,45 @@ macro_rules! iirfilt_impl {
                 unsafe { $group_delay(self.inner, fc) }
             }
 
+            /// design an all-pass corrector that flattens the group delay of
+            /// this filter across a band. Cascading the returned filter after
+            /// this one gives an approximately constant delay in [f0, f1]
+            /// without changing the magnitude response.
+            ///  f0     :   lower band edge, 0 <= f0 < f1
+            ///  f1     :   upper band edge, f1 <= 0.5
+            ///  order  :   order of the corrector, even and greater than 0
+            pub fn design_group_delay_corrector(
+                &self,
+                f0: f32,
+                f1: f32,
+                order: usize,
+            ) -> LiquidResult<Self> {
+                if f0 < 0f32 || f1 > 0.5 || f0 >= f1 {
+                    return Err(FilterError::InvalidValue(
+                        "band edges must satisfy 0 <= f0 < f1 <= 0.5".to_owned(),
+                    )
+                    .into());
+                } else if order == 0 || order % 2 != 0 {
+                    return Err(FilterError::InvalidValue(
+                        "order must be even and greater than 0".to_owned(),
+                    )
+                    .into());
+                }
+                let num_points = 16 * order + 32;
+                let freqs: Vec<f32> = (0..num_points)
+                    .map(|i| f0 + (f1 - f0) * i as f32 / (num_points - 1) as f32)
+                    .collect();
+                let delay: Vec<f32> = freqs.iter().map(|&f| self.group_delay(f)).collect();
+
+                let (b, a) = design_allpass_sos(&delay, &freqs, order / 2);
+                let b: Vec<$type> = b.into_iter().map(<$type>::from).collect();
+                let a: Vec<$type> = a.into_iter().map(<$type>::from).collect();
+                Self::create_sos(&a, &b, order / 2)
+            }
+
             /// execute iir filter, switching to type-specific function
             ///  input      :   input sample
-            pub fn execute(&self, input: $type2) -> $type2 {
+            pub fn execute(&mut self, input: $type2) -> $type2 {
                 let mut output = <$type2>::default();
                 unsafe {
                     $execute(self.inner, input.to_c_value(), output.to_ptr_mut());
@@ -243,7 +342,7 @@ macro_rules! iirfilt_impl {
             /// input and output buffers may be the same
             ///  input      : pointer to input array [size: _n x 1]
             ///  output      : pointer to output array [size: _n x 1]
-            pub fn execute_block(&self, input: &[$type2], output: &mut [$type2]) {
+            pub fn execute_block(&mut self, input: &[$type2], output: &mut [$type2]) {
                 assert_eq!(input.len(), output.len());
                 unsafe {
                     $block(
@@ -254,6 +353,42 @@ macro_rules! iirfilt_impl {
                     );
                 }
             }
+
+            /// execute the filter on a block of samples in place, the
+            /// output overwriting the input
+            ///  x      : input/output array [size: _n x 1]
+            pub fn execute_block_in_place(&mut self, x: &mut [$type2]) {
+                unsafe {
+                    $block(self.inner, x.to_ptr_mut(), x.len() as _, x.to_ptr_mut());
+                }
+            }
+
+            /// push zeros through the filter for its group delay at DC plus
+            /// its order, appending the tail to out; the impulse response of
+            /// an iir filter never ends, so the tail is truncated there. The
+            /// filter is reset afterwards, ready for the next burst
+            pub fn flush(&mut self, out: &mut Vec<$type2>) {
+                let delay = self.group_delay(0.0);
+                let delay = if delay.is_finite() && delay > 0f32 {
+                    delay.ceil() as usize
+                } else {
+                    0
+                };
+                let n = delay + self.len();
+                let zeros = vec![<$type2>::default(); n];
+                let start = out.len();
+                out.resize(start + n, <$type2>::default());
+                self.execute_block(&zeros, &mut out[start..]);
+                self.reset();
+            }
+        }
+
+        /// pass-through filter, b = a = [1]
+        impl Default for $obj {
+            fn default() -> Self {
+                let one = [<$type>::from(1.0)];
+                Self::create(&one, &one).expect("unit filter")
+            }
         }
 
         impl Drop for $obj {
@@ -337,3 +472,34 @@ iirfilt_impl!(
         f32
     )
 );
+
+#[cfg(test)]
+mod tests {
+    use super::IirFiltRrrf;
+
+    #[test]
+    fn test_iirfilt_presets() {
+        assert!(IirFiltRrrf::butter_lowpass(0, 0.1).is_err());
+        assert!(IirFiltRrrf::butter_lowpass(4, 0.6).is_err());
+        assert!(IirFiltRrrf::deemphasis(0.0, 48e3).is_err());
+        assert!(IirFiltRrrf::deemphasis(75e-6, 0.0).is_err());
+        assert!(IirFiltRrrf::deemphasis(75e-6, 48e3).is_ok());
+    }
+
+    #[test]
+    fn test_iirfilt_flush() {
+        // single pole at 0.5, h[n] = 0.5^n
+        let mut q = IirFiltRrrf::create(&[1.0, -0.5], &[1.0]).unwrap();
+        let mut y = vec![0f32; 1];
+        q.execute_block(&[1.0], &mut y);
+        q.flush(&mut y);
+        assert!(y.len() > 2);
+        for (n, v) in y.iter().enumerate() {
+            assert!((v - 0.5f32.powi(n as i32)).abs() < 1e-6);
+        }
+
+        let mut y = vec![0f32; 1];
+        q.execute_block(&[0.0], &mut y);
+        assert_eq!(y, [0.0]);
+    }
+}
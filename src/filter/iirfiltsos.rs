@@ -0,0 +1,220 @@
+//! *iirfiltsos* : single second-order section (biquad) of an iir filter
+//!
+//! liquid keeps its iirfiltsos object internal, so the section is
+//! implemented here with the same direct-form structures. Unlike IirFilt,
+//! the coefficients can be changed while running without losing the
+//! filter state, e.g. for a tunable notch or a parametric equalizer.
+use num::complex::Complex32;
+
+use crate::errors::FilterError;
+use crate::LiquidResult;
+
+#[derive(Clone, Debug)]
+pub struct IirFiltSosRrrf {
+    b: [f32; 3],
+    a: [f32; 3],
+    // direct form II state
+    v: [f32; 3],
+    // direct form I state
+    x: [f32; 3],
+    y: [f32; 3],
+}
+
+#[derive(Clone, Debug)]
+pub struct IirFiltSosCrcf {
+    b: [f32; 3],
+    a: [f32; 3],
+    v: [Complex32; 3],
+    x: [Complex32; 3],
+    y: [Complex32; 3],
+}
+
+#[derive(Clone, Debug)]
+pub struct IirFiltSosCccf {
+    b: [Complex32; 3],
+    a: [Complex32; 3],
+    v: [Complex32; 3],
+    x: [Complex32; 3],
+    y: [Complex32; 3],
+}
+
+/// sum_k c[k] e^{-j 2 pi fc k} and sum_k k c[k] e^{-j 2 pi fc k}
+fn poly_eval(c: &[Complex32; 3], fc: f32) -> (Complex32, Complex32) {
+    let mut h = Complex32::default();
+    let mut dh = Complex32::default();
+    for (k, &ck) in c.iter().enumerate() {
+        let v = ck * Complex32::from_polar(1.0, -2.0 * std::f32::consts::PI * fc * k as f32);
+        h += v;
+        dh += v * k as f32;
+    }
+    (h, dh)
+}
+
+macro_rules! iirfiltsos_impl {
+    ($obj:ty, $type:ty, $type2:ty) => {
+        impl $obj {
+            /// create a second-order section
+            ///  a      :   feed-back coefficients [size: 3 x 1], a[0] != 0
+            ///  b      :   feed-forward coefficients [size: 3 x 1]
+            pub fn create(a: &[$type], b: &[$type]) -> LiquidResult<Self> {
+                let mut q = Self {
+                    b: [<$type>::default(); 3],
+                    a: [<$type>::default(); 3],
+                    v: [<$type2>::default(); 3],
+                    x: [<$type2>::default(); 3],
+                    y: [<$type2>::default(); 3],
+                };
+                q.set_coefficients(a, b)?;
+                Ok(q)
+            }
+
+            /// set the coefficients, keeping the filter state; both sets
+            /// are normalized by a[0]
+            ///  a      :   feed-back coefficients [size: 3 x 1], a[0] != 0
+            ///  b      :   feed-forward coefficients [size: 3 x 1]
+            pub fn set_coefficients(&mut self, a: &[$type], b: &[$type]) -> LiquidResult<()> {
+                if a.len() != 3 || b.len() != 3 {
+                    return Err(FilterError::InvalidLength {
+                        description: format!(
+                            "numerator length: {} denominator length: {}, a section has 3 \
+                             coefficients each",
+                            b.len(),
+                            a.len()
+                        ),
+                    }
+                    .into());
+                } else if a[0] == <$type>::default() {
+                    return Err(FilterError::InvalidValue("a[0] cannot be zero".to_owned()).into());
+                }
+                let a0 = a[0];
+                for i in 0..3 {
+                    self.b[i] = b[i] / a0;
+                    self.a[i] = a[i] / a0;
+                }
+                Ok(())
+            }
+
+            /// feed-back coefficients, normalized so that a[0] = 1
+            pub fn get_a(&self) -> [$type; 3] {
+                self.a
+            }
+
+            /// feed-forward coefficients, normalized by a[0]
+            pub fn get_b(&self) -> [$type; 3] {
+                self.b
+            }
+
+            /// clear the filter state
+            pub fn reset(&mut self) {
+                self.v = [<$type2>::default(); 3];
+                self.x = [<$type2>::default(); 3];
+                self.y = [<$type2>::default(); 3];
+            }
+
+            pub fn print(&self) {
+                println!("iir filter single second-order section:");
+                println!("  b : {:?}", self.b);
+                println!("  a : {:?}", self.a);
+            }
+
+            /// compute filter output, direct form II
+            pub fn execute(&mut self, x: $type2) -> $type2 {
+                self.execute_df2(x)
+            }
+
+            /// compute filter output, direct form I
+            pub fn execute_df1(&mut self, x: $type2) -> $type2 {
+                self.x[2] = self.x[1];
+                self.x[1] = self.x[0];
+                self.x[0] = x;
+                self.y[2] = self.y[1];
+                self.y[1] = self.y[0];
+                let v = self.x[0] * self.b[0] + self.x[1] * self.b[1] + self.x[2] * self.b[2];
+                self.y[0] = v - self.y[1] * self.a[1] - self.y[2] * self.a[2];
+                self.y[0]
+            }
+
+            /// compute filter output, direct form II
+            pub fn execute_df2(&mut self, x: $type2) -> $type2 {
+                self.v[2] = self.v[1];
+                self.v[1] = self.v[0];
+                self.v[0] = x - self.v[1] * self.a[1] - self.v[2] * self.a[2];
+                self.v[0] * self.b[0] + self.v[1] * self.b[1] + self.v[2] * self.b[2]
+            }
+
+            /// execute the filter on a block of samples, direct form II
+            ///  x      :   input array [size: n x 1]
+            ///  y      :   output array [size: n x 1]
+            pub fn execute_block(&mut self, x: &[$type2], y: &mut [$type2]) -> LiquidResult<()> {
+                crate::utils::check_same_len_as::<FilterError>(x.len(), y.len())?;
+                x.iter()
+                    .zip(y.iter_mut())
+                    .for_each(|(&xi, yi)| *yi = self.execute_df2(xi));
+                Ok(())
+            }
+
+            /// frequency response at the normalized frequency fc
+            pub fn freq_response(&self, fc: f32) -> Complex32 {
+                let (b, _) = poly_eval(&self.b.map(Complex32::from), fc);
+                let (a, _) = poly_eval(&self.a.map(Complex32::from), fc);
+                b / a
+            }
+
+            /// group delay [samples] at the normalized frequency fc
+            pub fn group_delay(&self, fc: f32) -> f32 {
+                let (b, db) = poly_eval(&self.b.map(Complex32::from), fc);
+                let (a, da) = poly_eval(&self.a.map(Complex32::from), fc);
+                (db / b).re - (da / a).re
+            }
+        }
+    };
+}
+
+iirfiltsos_impl!(IirFiltSosRrrf, f32, f32);
+iirfiltsos_impl!(IirFiltSosCrcf, f32, Complex32);
+iirfiltsos_impl!(IirFiltSosCccf, Complex32, Complex32);
+
+#[cfg(test)]
+mod tests {
+    use super::{IirFiltSosCrcf, IirFiltSosRrrf};
+    use num::complex::Complex32;
+
+    #[test]
+    fn test_iirfiltsos_forms() {
+        assert!(IirFiltSosRrrf::create(&[1.0, 0.5], &[1.0, 0.0, 0.0]).is_err());
+        assert!(IirFiltSosRrrf::create(&[0.0, 0.5, 0.1], &[1.0, 0.0, 0.0]).is_err());
+
+        let a = [2.0, -0.6, 0.2];
+        let b = [0.4, 0.8, 0.4];
+        let mut q = IirFiltSosRrrf::create(&a, &b).unwrap();
+        assert_eq!(q.get_a(), [1.0, -0.3, 0.1]);
+        let mut y1 = Vec::new();
+        let mut y2 = Vec::new();
+        for n in 0..32 {
+            let x = if n == 0 { 1.0 } else { 0.0 };
+            y1.push(q.execute_df1(x));
+            y2.push(q.execute_df2(x));
+        }
+        for (u, v) in y1.iter().zip(y2.iter()) {
+            assert!((u - v).abs() < 1e-6);
+        }
+        assert!((y1[0] - 0.2).abs() < 1e-6);
+        // DC gain, sum(b) / sum(a)
+        assert!((q.freq_response(0.0).re - 0.8 / 0.8).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_iirfiltsos_retune() {
+        // pure delay of one sample
+        let mut q = IirFiltSosCrcf::create(&[1.0, 0.0, 0.0], &[0.0, 1.0, 0.0]).unwrap();
+        assert!((q.group_delay(0.1) - 1.0).abs() < 1e-5);
+        let x = [Complex32::new(1.0, 2.0), Complex32::new(3.0, 4.0)];
+        let mut y = [Complex32::default(); 2];
+        q.execute_block(&x, &mut y).unwrap();
+        assert_eq!(y[1], x[0]);
+        // the state survives a coefficient update
+        q.set_coefficients(&[1.0, 0.0, 0.0], &[0.0, 0.0, 1.0])
+            .unwrap();
+        assert_eq!(q.execute(Complex32::default()), x[0]);
+    }
+}
@@ -1,21 +1,44 @@
+pub use builder::FirFilterBuilder;
 pub use enums::{
     FirdesFilterType, FirdespmBtype, FirdespmWtype, IirdesBandType, IirdesFilterType, IirdesFormat,
 };
 pub use fftfilt::{FftFiltCccf, FftFiltCrcf, FftFiltRrrf};
-pub use filter::FilterAnalysis;
+pub use filter::{FilterAnalysis, FrequencyResponse};
+pub use firdes::{Fir, Firdes};
 pub use firdespm::Firdespm;
+pub use firfarrow::{FirFarrowCrcf, FirFarrowRrrf};
 pub use firfilt::{FirFiltCccf, FirFiltCrcf, FirFiltRrrf};
 pub use firinterp::{FirInterpCccf, FirInterpCrcf, FirInterpRrrf};
 pub use hilbertf::{FirHilbt, IirHilbt};
+pub use iirdes::{Iir, Iirdes};
 pub use iirfilt::{IirFiltCccf, IirFiltCrcf, IirFiltRrrf};
+pub use iirfiltsos::{IirFiltSosCccf, IirFiltSosCrcf, IirFiltSosRrrf};
 pub use autocorr::{AutoCorrRrrf, AutoCorrCccf};
+pub use msdecim::{MsDecimCrcf, MsDecimRrrf, MsDecimStage};
+pub use notch::TrackingNotch;
+pub use rresamp::{RresampCccf, RresampCrcf, RresampRrrf};
+pub use transfer::Transfer;
+pub use zpk::Zpk;
 
 mod autocorr;
+mod builder;
 mod enums;
 mod fftfilt;
+#[allow(clippy::module_inception)]
 mod filter;
+mod firdes;
 mod firdespm;
+mod firfarrow;
 mod firfilt;
 mod firinterp;
+mod gdcorr;
 mod hilbertf;
+mod iirdes;
 mod iirfilt;
+mod iirfiltsos;
+mod msdecim;
+mod notch;
+pub mod polyphase;
+mod rresamp;
+mod transfer;
+pub mod zpk;
@@ -0,0 +1,356 @@
+//! *msdecim* : multi-stage decimator, a cascade of half-band resamp2
+//!           stages followed by a single firdecim stage for the remaining
+//!           odd factor
+//!
+//! Decimating by D in a single stage needs a filter whose transition band
+//! is a fraction of the output rate, running at the input rate. Splitting
+//! D = 2^k * r lets the early half-band stages use wide transition bands,
+//! as only the final band has to be protected from aliasing, so the total
+//! number of operations drops by an order of magnitude for large D.
+//!
+//! The design keeps the lower 80% of the output Nyquist band, i.e. the
+//! output is flat up to 0.4 times the output sample rate.
+use num::complex::Complex32;
+
+use crate::block::Block;
+use crate::errors::FilterError;
+use crate::filter::Firdes;
+use crate::liquid_dsp_sys as raw;
+use crate::utils::{ToCPointer, ToCPointerMut};
+use crate::LiquidResult;
+
+/// fraction of the output sample rate that is kept
+const PASSBAND: f32 = 0.4;
+
+/// parameters of one decimation stage
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MsDecimStage {
+    /// decimation factor of the stage
+    pub factor: u32,
+    /// number of filter taps
+    pub len: usize,
+    /// group delay of the stage [input samples of the stage]
+    pub delay: f32,
+    /// multiply-accumulates per output sample of the stage
+    pub macs: usize,
+}
+
+/// split the decimation factor and size the filter of every stage
+fn design_stages(factor: u32, as_: f32) -> LiquidResult<Vec<MsDecimStage>> {
+    if factor < 2 {
+        return Err(
+            FilterError::InvalidValue("decimation factor must be at least 2".to_owned()).into(),
+        );
+    } else if as_ <= 0f32 {
+        return Err(FilterError::InvalidValue(
+            "stop-band attenuation must be greater than zero".to_owned(),
+        )
+        .into());
+    }
+    let halfbands = factor.trailing_zeros();
+    let r = factor >> halfbands;
+    let d = factor as f32;
+    let mut stages = Vec::new();
+    for i in 0..halfbands {
+        // band kept at the input rate of the stage, its alias must not
+        // fall onto it after decimation by 2
+        let fp = PASSBAND * (1u32 << i) as f32 / d;
+        let len = Firdes::estimate_filter_len(0.5 - 2.0 * fp, as_)?;
+        let m = (len.saturating_sub(1)).div_ceil(4).max(1);
+        stages.push(MsDecimStage {
+            factor: 2,
+            len: 4 * m + 1,
+            delay: 2.0 * m as f32,
+            // half of the taps are zero except the center one
+            macs: 2 * m + 1,
+        });
+    }
+    if r > 1 {
+        let len =
+            Firdes::estimate_filter_len((1.0 - 2.0 * PASSBAND) / r as f32, as_)?.max(r as usize);
+        stages.push(MsDecimStage {
+            factor: r,
+            len,
+            delay: (len - 1) as f32 / 2.0,
+            macs: len,
+        });
+    }
+    Ok(stages)
+}
+
+macro_rules! msdecim_impl {
+    ($obj:ident, $stage:ident, (
+        $hb_create:expr, $hb_reset:expr, $hb_execute:expr, $hb_destroy:expr,
+        $fd_create:expr, $fd_reset:expr, $fd_execute:expr, $fd_destroy:expr,
+        $resamp2:ty, $firdecim:ty,
+        $type:ty)) => {
+        enum $stage {
+            Halfband($resamp2),
+            Final($firdecim),
+        }
+
+        pub struct $obj {
+            stages: Vec<($stage, MsDecimStage)>,
+            /// input samples of every stage not consumed yet
+            pending: Vec<Vec<$type>>,
+            factor: u32,
+        }
+
+        impl $obj {
+            /// design a multi-stage decimator
+            ///  factor :   total decimation factor, factor >= 2
+            ///  as_    :   stop-band attenuation of every stage [dB], as_ > 0
+            pub fn design(factor: u32, as_: f32) -> LiquidResult<Self> {
+                let design = design_stages(factor, as_)?;
+                let stages = design
+                    .into_iter()
+                    .map(|s| {
+                        let q = if s.factor == 2 {
+                            let m = (s.len - 1) / 4;
+                            $stage::Halfband(unsafe { $hb_create(m as _, 0.0, as_) })
+                        } else {
+                            let mut h = Firdes::kaiser(s.len, 0.5 / s.factor as f32, as_, 0.0)?;
+                            // unity gain at DC
+                            let sum: f32 = h.as_ref().iter().sum();
+                            h.as_mut().iter_mut().for_each(|v| *v /= sum);
+                            $stage::Final(unsafe {
+                                $fd_create(s.factor as _, h.as_mut().as_mut_ptr(), s.len as _)
+                            })
+                        };
+                        Ok((q, s))
+                    })
+                    .collect::<LiquidResult<Vec<_>>>()?;
+                Ok(Self {
+                    pending: vec![Vec::new(); stages.len()],
+                    stages,
+                    factor,
+                })
+            }
+
+            /// total decimation factor
+            pub fn get_decim_factor(&self) -> u32 {
+                self.factor
+            }
+
+            /// parameters of the stages, in processing order
+            pub fn stages(&self) -> Vec<MsDecimStage> {
+                self.stages.iter().map(|(_, s)| *s).collect()
+            }
+
+            /// total group delay [input samples]
+            pub fn get_delay_input(&self) -> f32 {
+                let mut rate = 1u32;
+                let mut delay = 0f32;
+                for (_, s) in &self.stages {
+                    delay += s.delay * rate as f32;
+                    rate *= s.factor;
+                }
+                delay
+            }
+
+            /// total group delay [output samples]
+            pub fn get_delay(&self) -> f32 {
+                self.get_delay_input() / self.factor as f32
+            }
+
+            /// multiply-accumulates per input sample, summed over the stages
+            pub fn macs_per_sample(&self) -> f32 {
+                let mut rate = 1u32;
+                let mut macs = 0f32;
+                for (_, s) in &self.stages {
+                    rate *= s.factor;
+                    macs += s.macs as f32 / rate as f32;
+                }
+                macs
+            }
+
+            /// estimate of the processing load in millions of
+            /// multiply-accumulates per second
+            ///  sample_rate    :   input sample rate [samples/s]
+            pub fn mips(&self, sample_rate: f32) -> f32 {
+                self.macs_per_sample() * sample_rate / 1e6
+            }
+
+            /// clear the filter states and drop the buffered samples
+            pub fn reset(&mut self) {
+                for (q, _) in &self.stages {
+                    unsafe {
+                        match q {
+                            $stage::Halfband(q) => $hb_reset(*q),
+                            $stage::Final(q) => $fd_reset(*q),
+                        }
+                    }
+                }
+                self.pending.iter_mut().for_each(|p| p.clear());
+            }
+
+            /// number of output samples the next execute() produces from
+            /// *input_len* samples, taking the buffered samples into account
+            pub fn output_len_for(&self, input_len: usize) -> usize {
+                let mut n = input_len;
+                for ((_, s), p) in self.stages.iter().zip(&self.pending) {
+                    n = (n + p.len()) / s.factor as usize;
+                }
+                n
+            }
+
+            /// decimate a block of samples, appending the output to y; the
+            /// samples that do not fill a whole output sample of a stage are
+            /// kept for the next call
+            pub fn execute(&mut self, x: &[$type], y: &mut Vec<$type>) {
+                let mut buf = x.to_vec();
+                for ((q, s), p) in self.stages.iter().zip(self.pending.iter_mut()) {
+                    p.extend_from_slice(&buf);
+                    let f = s.factor as usize;
+                    let n = p.len() / f;
+                    buf = vec![<$type>::default(); n];
+                    unsafe {
+                        match q {
+                            $stage::Halfband(q) => {
+                                for (xi, yi) in p.chunks_exact(2).zip(buf.iter_mut()) {
+                                    $hb_execute(*q, xi.to_ptr() as _, yi.to_ptr_mut());
+                                }
+                            }
+                            $stage::Final(q) => {
+                                $fd_execute(*q, p.to_ptr() as _, n as _, buf.to_ptr_mut())
+                            }
+                        }
+                    }
+                    p.drain(..n * f);
+                }
+                y.extend_from_slice(&buf);
+            }
+        }
+
+        impl Block<$type, $type> for $obj {
+            fn process(&mut self, input: &[$type], output: &mut Vec<$type>) -> LiquidResult<()> {
+                self.execute(input, output);
+                Ok(())
+            }
+
+            fn rate(&self) -> f32 {
+                1.0 / self.factor as f32
+            }
+
+            fn delay(&self) -> f32 {
+                self.get_delay()
+            }
+        }
+
+        impl Drop for $obj {
+            fn drop(&mut self) {
+                for (q, _) in &self.stages {
+                    unsafe {
+                        match q {
+                            $stage::Halfband(q) => $hb_destroy(*q),
+                            $stage::Final(q) => $fd_destroy(*q),
+                        }
+                    }
+                }
+            }
+        }
+    };
+}
+
+msdecim_impl!(
+    MsDecimRrrf,
+    MsDecimRrrfStage,
+    (
+        raw::resamp2_rrrf_create,
+        raw::resamp2_rrrf_reset,
+        raw::resamp2_rrrf_decim_execute,
+        raw::resamp2_rrrf_destroy,
+        raw::firdecim_rrrf_create,
+        raw::firdecim_rrrf_reset,
+        raw::firdecim_rrrf_execute_block,
+        raw::firdecim_rrrf_destroy,
+        raw::resamp2_rrrf,
+        raw::firdecim_rrrf,
+        f32
+    )
+);
+
+msdecim_impl!(
+    MsDecimCrcf,
+    MsDecimCrcfStage,
+    (
+        raw::resamp2_crcf_create,
+        raw::resamp2_crcf_reset,
+        raw::resamp2_crcf_decim_execute,
+        raw::resamp2_crcf_destroy,
+        raw::firdecim_crcf_create,
+        raw::firdecim_crcf_reset,
+        raw::firdecim_crcf_execute_block,
+        raw::firdecim_crcf_destroy,
+        raw::resamp2_crcf,
+        raw::firdecim_crcf,
+        Complex32
+    )
+);
+
+#[cfg(test)]
+mod tests {
+    use super::{design_stages, MsDecimCrcf};
+    use num::complex::Complex32;
+
+    #[test]
+    fn test_msdecim_stages() {
+        assert!(design_stages(1, 60.0).is_err());
+        assert!(design_stages(8, 0.0).is_err());
+
+        let factors: Vec<u32> = design_stages(24, 60.0)
+            .unwrap()
+            .iter()
+            .map(|s| s.factor)
+            .collect();
+        assert_eq!(factors, vec![2, 2, 2, 3]);
+        let stages = design_stages(16, 60.0).unwrap();
+        assert_eq!(stages.len(), 4);
+        // the last stages protect a wider fraction of their band
+        assert!(stages[3].len > stages[0].len);
+    }
+
+    #[test]
+    fn test_msdecim_execute() {
+        let mut q = MsDecimCrcf::design(12, 60.0).unwrap();
+        assert_eq!(q.get_decim_factor(), 12);
+        let x = vec![Complex32::new(1.0, 0.0); 100];
+        let mut y = Vec::new();
+        assert_eq!(q.output_len_for(100), 8);
+        q.execute(&x, &mut y);
+        assert_eq!(y.len(), 8);
+        // the sample left in the last stage completes the next output
+        assert_eq!(q.output_len_for(8), 1);
+        q.execute(&x[..8], &mut y);
+        assert_eq!(y.len(), 9);
+        assert!(q.macs_per_sample() > 0.0);
+        assert!(q.get_delay() > 0.0);
+    }
+
+    #[test]
+    fn test_msdecim_response() {
+        let mut q = MsDecimCrcf::design(12, 60.0).unwrap();
+        let settle = q.get_delay().ceil() as usize + 4;
+
+        // unity gain at DC
+        let x = vec![Complex32::new(1.0, 0.0); 12 * 100];
+        let mut y = Vec::new();
+        q.execute(&x, &mut y);
+        assert_eq!(y.len(), 100);
+        for v in &y[settle..] {
+            assert!((v - Complex32::new(1.0, 0.0)).norm() < 0.01);
+        }
+
+        // a tone at 0.1, above the output Nyquist rate 0.5/12, would alias
+        // into the passband
+        q.reset();
+        let x: Vec<Complex32> = (0..12 * 100)
+            .map(|n| Complex32::from_polar(1.0, std::f32::consts::TAU * 0.1 * n as f32))
+            .collect();
+        let mut y = Vec::new();
+        q.execute(&x, &mut y);
+        let power =
+            y[settle..].iter().map(|v| v.norm_sqr()).sum::<f32>() / (y.len() - settle) as f32;
+        assert!(power < 1e-4);
+    }
+}
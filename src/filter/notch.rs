@@ -0,0 +1,225 @@
+//! *notch* : FIR notch filter following a drifting interferer
+//!
+//! The notch is re-designed when the target frequency moves by more than a
+//! threshold, at most once every min_interval samples; the filter keeps its
+//! internal buffer across re-designs so no transient is introduced.
+use num::complex::Complex32;
+
+use crate::errors::FilterError;
+use crate::filter::FirFiltCrcf;
+use crate::liquid_dsp_sys as raw;
+use crate::utils::check_same_len_as;
+use crate::LiquidResult;
+
+fn check_frequency(f0: f32) -> LiquidResult<()> {
+    if !(-0.5..=0.5).contains(&f0) {
+        return Err(FilterError::InvalidValue(
+            "filter notch frequency must be in [-0.5, 0.5]".to_owned(),
+        )
+        .into());
+    }
+    Ok(())
+}
+
+/// notch filter on complex samples whose frequency can be changed while
+/// running; the prototype has real coefficients, so -f0 is nulled as well
+pub struct TrackingNotch {
+    filter: FirFiltCrcf,
+    m: u16,
+    as_: f32,
+    f0: f32,
+    threshold: f32,
+    min_interval: usize,
+    // samples filtered since the last design
+    elapsed: usize,
+    pending: Option<f32>,
+    num_designs: usize,
+}
+
+impl TrackingNotch {
+    /// create a tracking notch, re-designed on every frequency change
+    ///  m      :   filter semi-length, in [1, 1000]
+    ///  as_    :   prototype stop-band suppression [dB], as_ > 0
+    ///  f0     :   initial notch frequency, in [-0.5, 0.5]
+    pub fn create(m: u16, as_: f32, f0: f32) -> LiquidResult<Self> {
+        Ok(Self {
+            filter: FirFiltCrcf::create_notch(m, as_, f0)?,
+            m,
+            as_,
+            f0,
+            threshold: 0.0,
+            min_interval: 0,
+            elapsed: usize::MAX,
+            pending: None,
+            num_designs: 1,
+        })
+    }
+
+    /// set the frequency change below which the notch is not re-designed
+    ///  threshold  :   normalized frequency, threshold >= 0
+    pub fn set_threshold(&mut self, threshold: f32) -> LiquidResult<()> {
+        if threshold < 0f32 || threshold.is_nan() {
+            return Err(FilterError::InvalidValue(
+                "threshold must be greater than or equal to zero".to_owned(),
+            )
+            .into());
+        }
+        self.threshold = threshold;
+        Ok(())
+    }
+
+    pub fn get_threshold(&self) -> f32 {
+        self.threshold
+    }
+
+    /// set the minimum number of samples between two designs; a change
+    /// requested earlier is applied once the interval has elapsed
+    pub fn set_min_interval(&mut self, samples: usize) {
+        self.min_interval = samples;
+    }
+
+    pub fn get_min_interval(&self) -> usize {
+        self.min_interval
+    }
+
+    /// move the notch to a new frequency
+    ///  f0     :   notch frequency, in [-0.5, 0.5]
+    /// # returns
+    /// true if the notch was re-designed right away, false if the change is
+    /// under the threshold or delayed by the rate limit
+    pub fn set_frequency(&mut self, f0: f32) -> LiquidResult<bool> {
+        check_frequency(f0)?;
+        if (f0 - self.f0).abs() <= self.threshold {
+            self.pending = None;
+            return Ok(false);
+        }
+        self.pending = Some(f0);
+        self.apply_pending();
+        Ok(self.pending.is_none())
+    }
+
+    /// frequency of the current notch
+    pub fn get_frequency(&self) -> f32 {
+        self.f0
+    }
+
+    /// frequency the notch is moving to once the rate limit allows it
+    pub fn get_pending_frequency(&self) -> Option<f32> {
+        self.pending
+    }
+
+    /// number of designs since the object was created
+    pub fn get_num_designs(&self) -> usize {
+        self.num_designs
+    }
+
+    /// group delay of the filter [samples]
+    pub fn get_delay(&self) -> usize {
+        self.m as usize
+    }
+
+    pub fn reset(&mut self) {
+        self.filter.reset();
+    }
+
+    fn apply_pending(&mut self) {
+        let f0 = match self.pending {
+            Some(f0) if self.elapsed >= self.min_interval => f0,
+            _ => return,
+        };
+        let mut h = vec![0f32; 2 * self.m as usize + 1];
+        unsafe {
+            raw::liquid_firdes_notch(self.m as _, f0, self.as_, h.as_mut_ptr());
+        }
+        // the buffer is kept, the length being unchanged
+        self.filter
+            .update_coefficients(&h)
+            .expect("notch filter length is 2m+1");
+        self.f0 = f0;
+        self.pending = None;
+        self.elapsed = 0;
+        self.num_designs += 1;
+    }
+
+    /// filter a block of samples, applying a delayed frequency change as
+    /// soon as the rate limit allows it
+    ///  x      :   input samples [size: n x 1]
+    ///  y      :   output samples [size: n x 1]
+    pub fn execute_block(&mut self, x: &[Complex32], y: &mut [Complex32]) -> LiquidResult<()> {
+        check_same_len_as::<FilterError>(x.len(), y.len())?;
+        let mut i = 0;
+        while i < x.len() {
+            self.apply_pending();
+            let n = match self.pending {
+                Some(_) => (self.min_interval - self.elapsed).min(x.len() - i),
+                None => x.len() - i,
+            };
+            self.filter
+                .try_execute_block(&x[i..i + n], &mut y[i..i + n])?;
+            self.elapsed = self.elapsed.saturating_add(n);
+            i += n;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TrackingNotch;
+    use num::complex::Complex32;
+
+    #[test]
+    fn test_tracking_notch_rate_limit() {
+        assert!(TrackingNotch::create(12, 60.0, 0.7).is_err());
+        let mut q = TrackingNotch::create(12, 60.0, 0.1).unwrap();
+        assert!(q.set_threshold(-1.0).is_err());
+        q.set_threshold(0.01).unwrap();
+        q.set_min_interval(100);
+        assert!(q.set_frequency(0.6).is_err());
+
+        // under the threshold
+        assert!(!q.set_frequency(0.105).unwrap());
+        assert_eq!(q.get_num_designs(), 1);
+        // first change goes through right away
+        assert!(q.set_frequency(0.2).unwrap());
+        assert_eq!(q.get_frequency(), 0.2);
+
+        // the next one waits for 100 samples
+        assert!(!q.set_frequency(0.3).unwrap());
+        assert_eq!(q.get_pending_frequency(), Some(0.3));
+        let x = vec![Complex32::default(); 60];
+        let mut y = vec![Complex32::default(); 60];
+        q.execute_block(&x, &mut y).unwrap();
+        assert_eq!(q.get_frequency(), 0.2);
+        q.execute_block(&x, &mut y).unwrap();
+        assert_eq!(q.get_frequency(), 0.3);
+        assert_eq!(q.get_pending_frequency(), None);
+        assert_eq!(q.get_num_designs(), 3);
+    }
+
+    #[test]
+    fn test_tracking_notch_removes_tone() {
+        let tone = |f: f32| -> Vec<Complex32> {
+            (0..500)
+                .map(|n| Complex32::from_polar(1.0, std::f32::consts::TAU * f * n as f32))
+                .collect()
+        };
+        // mean power once the filter buffer is full of the tone
+        let power = |y: &[Complex32]| y[100..].iter().map(|v| v.norm_sqr()).sum::<f32>() / 400.0;
+
+        let mut q = TrackingNotch::create(12, 60.0, 0.1).unwrap();
+        let mut y = vec![Complex32::default(); 500];
+        q.execute_block(&tone(0.1), &mut y).unwrap();
+        assert!(power(&y) < 1e-3);
+        // the prototype is real, so the image at -f0 is nulled too
+        q.execute_block(&tone(-0.1), &mut y).unwrap();
+        assert!(power(&y) < 1e-3);
+
+        // after moving the notch the old tone passes and the new one does not
+        assert!(q.set_frequency(0.3).unwrap());
+        q.execute_block(&tone(0.1), &mut y).unwrap();
+        assert!(power(&y) > 0.5);
+        q.execute_block(&tone(0.3), &mut y).unwrap();
+        assert!(power(&y) < 1e-3);
+    }
+}
@@ -0,0 +1,102 @@
+//! *polyphase* : decomposition of a prototype filter into its polyphase
+//!             branches, and reconstruction of the prototype from them
+//!
+//! Branch i of an M-branch decomposition holds the taps h[i], h[i+M],
+//! h[i+2M], ..., which is how the firinterp and rresamp objects split their
+//! prototype. The branches are not zero padded, so that a decomposition
+//! followed by a reconstruction gives back the very same prototype.
+use crate::errors::FilterError;
+use crate::filter::Fir;
+use crate::LiquidResult;
+
+/// split coefficients into polyphase branches
+///  h      :   prototype coefficients
+///  m      :   number of branches, 0 < m <= h.len()
+pub fn decompose<T: Copy>(h: &[T], m: usize) -> LiquidResult<Vec<Vec<T>>> {
+    if m == 0 {
+        return Err(FilterError::InvalidValue(
+            "number of branches must be greater than 0".to_owned(),
+        )
+        .into());
+    } else if m > h.len() {
+        return Err(FilterError::InvalidLength {
+            description: format!(
+                "filter length: {} is less than the number of branches: {}",
+                h.len(),
+                m
+            ),
+        }
+        .into());
+    }
+    Ok((0..m)
+        .map(|i| h.iter().skip(i).step_by(m).copied().collect())
+        .collect())
+}
+
+/// interleave polyphase branches back into the prototype coefficients
+///  branches   :   branches, as returned by decompose()
+pub fn reconstruct<T: Copy>(branches: &[Vec<T>]) -> LiquidResult<Vec<T>> {
+    let m = branches.len();
+    if m == 0 {
+        return Err(FilterError::InvalidValue("no branches to reconstruct".to_owned()).into());
+    }
+    // branch i of a filter of length n holds ceil((n-i)/m) taps
+    let n: usize = branches.iter().map(|b| b.len()).sum();
+    for (i, b) in branches.iter().enumerate() {
+        if b.len() != (n + m - 1 - i) / m {
+            return Err(FilterError::InvalidLength {
+                description: format!(
+                    "branch {} length: {} valid length: {}",
+                    i,
+                    b.len(),
+                    (n + m - 1 - i) / m
+                ),
+            }
+            .into());
+        }
+    }
+    Ok((0..n).map(|k| branches[k % m][k / m]).collect())
+}
+
+/// split a prototype filter into polyphase branches
+///  proto  :   prototype filter
+///  m      :   number of branches, 0 < m <= proto.len()
+pub fn decompose_fir(proto: &Fir, m: usize) -> LiquidResult<Vec<Vec<f32>>> {
+    decompose(proto.as_ref(), m)
+}
+
+/// rebuild a prototype filter from its polyphase branches
+///  branches   :   branches, as returned by decompose_fir()
+pub fn reconstruct_fir(branches: &[Vec<f32>]) -> LiquidResult<Fir> {
+    let h = reconstruct(branches)?;
+    let mut proto = Fir::new(h.len());
+    proto.as_mut().copy_from_slice(&h);
+    Ok(proto)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decompose, decompose_fir, reconstruct, reconstruct_fir};
+    use crate::filter::Firdes;
+
+    #[test]
+    fn test_polyphase_round_trip() {
+        let h: Vec<u32> = (0..10).collect();
+        let branches = decompose(&h, 3).unwrap();
+        assert_eq!(
+            branches,
+            vec![vec![0, 3, 6, 9], vec![1, 4, 7], vec![2, 5, 8]]
+        );
+        assert_eq!(reconstruct(&branches).unwrap(), h);
+
+        assert!(decompose(&h, 0).is_err());
+        assert!(decompose(&h, 11).is_err());
+        assert!(reconstruct(&[vec![0u32], vec![1, 2]]).is_err());
+        assert!(reconstruct::<u32>(&[]).is_err());
+
+        let proto = Firdes::kaiser(37, 0.1, 60.0, 0.0).unwrap();
+        let branches = decompose_fir(&proto, 4).unwrap();
+        assert_eq!(branches.len(), 4);
+        assert_eq!(reconstruct_fir(&branches).unwrap().as_ref(), proto.as_ref());
+    }
+}
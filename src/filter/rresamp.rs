@@ -0,0 +1,427 @@
+//! *rresamp* : rational rate resampler, converts blocks of Q input samples
+//!           into blocks of P output samples, e.g. 48 kHz to 44.1 kHz
+//!           with P/Q = 147/160
+use num::complex::Complex32;
+
+use crate::liquid_dsp_sys as raw;
+use crate::utils::{ToCPointer, ToCPointerMut, ToCValue};
+
+use crate::block::{append_with, Block};
+use crate::errors::FilterError;
+use crate::LiquidResult;
+
+pub struct RresampRrrf {
+    inner: raw::rresamp_rrrf,
+    /// samples of an incomplete block, see Block::process
+    pending: Vec<f32>,
+}
+
+pub struct RresampCrcf {
+    inner: raw::rresamp_crcf,
+    /// samples of an incomplete block, see Block::process
+    pending: Vec<Complex32>,
+}
+
+pub struct RresampCccf {
+    inner: raw::rresamp_cccf,
+    /// samples of an incomplete block, see Block::process
+    pending: Vec<Complex32>,
+}
+
+macro_rules! rresamp_impl {
+    ($obj:ty, ($create:expr,
+        $kaiser:expr, $default:expr,
+        $print:expr, $reset:expr,
+        $setscale:expr, $getscale:expr,
+        $delay:expr,
+        $p:expr, $interp:expr,
+        $q:expr, $decim:expr,
+        $block_len:expr, $rate:expr,
+        $execute:expr,
+        $destroy:expr,
+        $type:ty, $type2:ty)) => {
+        impl $obj {
+            /// create rational resampler with a Kaiser-windowed prototype
+            ///  p      :   interpolation factor, p > 0
+            ///  q      :   decimation factor, q > 0
+            ///  m      :   filter semi-length (delay), m > 0
+            ///  bw     :   filter bandwidth relative to the sample rate, in (0, 0.5)
+            ///  as_    :   filter stop-band attenuation [dB], as_ > 0
+            pub fn create(p: u32, q: u32, m: u32, bw: f32, as_: f32) -> LiquidResult<Self> {
+                Self::check_rate(p, q, m)?;
+                if bw <= 0f32 || bw >= 0.5 {
+                    return Err(FilterError::InvalidValue(
+                        "bandwidth must be in (0, 0.5)".to_owned(),
+                    )
+                    .into());
+                } else if as_ <= 0f32 {
+                    return Err(FilterError::InvalidValue(
+                        "stop-band attenuation must be greater than zero".to_owned(),
+                    )
+                    .into());
+                }
+                Ok(Self {
+                    pending: Vec::new(),
+                    inner: unsafe { $kaiser(p as _, q as _, m as _, bw, as_) },
+                })
+            }
+
+            /// create rational resampler with external coefficients
+            ///  p      :   interpolation factor, p > 0
+            ///  q      :   decimation factor, q > 0
+            ///  m      :   filter semi-length (delay), m > 0
+            ///  h      :   filter coefficients [size: 2*p*m x 1]
+            pub fn create_coefficients(p: u32, q: u32, m: u32, h: &[$type]) -> LiquidResult<Self> {
+                Self::check_rate(p, q, m)?;
+                if h.len() != (2 * p * m) as usize {
+                    return Err(FilterError::InvalidLength {
+                        description: format!(
+                            "filter length: {} expected length: {}",
+                            h.len(),
+                            2 * p * m
+                        ),
+                    }
+                    .into());
+                }
+                Ok(Self {
+                    pending: Vec::new(),
+                    inner: unsafe { $create(p as _, q as _, m as _, h.to_ptr() as _) },
+                })
+            }
+
+            /// create rational resampler with default parameters
+            ///  p      :   interpolation factor, p > 0
+            ///  q      :   decimation factor, q > 0
+            pub fn create_default(p: u32, q: u32) -> LiquidResult<Self> {
+                Self::check_rate(p, q, 1)?;
+                Ok(Self {
+                    pending: Vec::new(),
+                    inner: unsafe { $default(p as _, q as _) },
+                })
+            }
+
+            fn check_rate(p: u32, q: u32, m: u32) -> LiquidResult<()> {
+                if p == 0 || q == 0 {
+                    return Err(FilterError::InvalidValue(
+                        "interpolation and decimation factors must be greater than zero".to_owned(),
+                    )
+                    .into());
+                } else if m == 0 {
+                    return Err(FilterError::InvalidValue(
+                        "filter semi-length must be greater than zero".to_owned(),
+                    )
+                    .into());
+                }
+                Ok(())
+            }
+
+            pub fn print(&self) {
+                unsafe {
+                    $print(self.inner);
+                }
+            }
+
+            /// reset the filter state and drop the buffered samples
+            pub fn reset(&mut self) {
+                self.pending.clear();
+                unsafe {
+                    $reset(self.inner);
+                }
+            }
+
+            /// set output scaling for resampler
+            pub fn set_scale(&mut self, scale: $type) {
+                unsafe {
+                    $setscale(self.inner, scale.to_c_value());
+                }
+            }
+
+            /// get output scaling for resampler
+            pub fn get_scale(&self) -> $type {
+                let mut scale = <$type>::default();
+                unsafe {
+                    $getscale(self.inner, scale.to_ptr_mut());
+                }
+                scale
+            }
+
+            /// get resampler delay (filter semi-length m)
+            pub fn get_delay(&self) -> u32 {
+                unsafe { $delay(self.inner) as u32 }
+            }
+
+            /// number of output samples of each block, block_len * interpolation factor
+            pub fn get_p(&self) -> u32 {
+                unsafe { $p(self.inner) as u32 }
+            }
+
+            /// interpolation factor, after removing the common factors of p and q
+            pub fn get_interp(&self) -> u32 {
+                unsafe { $interp(self.inner) as u32 }
+            }
+
+            /// number of input samples of each block, block_len * decimation factor
+            pub fn get_q(&self) -> u32 {
+                unsafe { $q(self.inner) as u32 }
+            }
+
+            /// decimation factor, after removing the common factors of p and q
+            pub fn get_decim(&self) -> u32 {
+                unsafe { $decim(self.inner) as u32 }
+            }
+
+            /// greatest common divisor of p and q
+            pub fn get_block_len(&self) -> u32 {
+                unsafe { $block_len(self.inner) as u32 }
+            }
+
+            /// resampling rate p/q
+            pub fn get_rate(&self) -> f32 {
+                unsafe { $rate(self.inner) }
+            }
+
+            /// number of output samples execute_block() produces from
+            /// *input_len* samples; a trailing partial block yields nothing
+            pub fn output_len_for(&self, input_len: usize) -> usize {
+                input_len / self.get_q() as usize * self.get_p() as usize
+            }
+
+            /// number of input samples execute_block() needs to produce at
+            /// least *output_len* samples, rounded up to whole blocks
+            pub fn required_input_for(&self, output_len: usize) -> usize {
+                output_len.div_ceil(self.get_p() as usize) * self.get_q() as usize
+            }
+
+            /// resample one block
+            ///  x      :   input block [size: get_q() x 1]
+            ///  y      :   output block [size: get_p() x 1]
+            pub fn execute(&mut self, x: &[$type2], y: &mut [$type2]) -> LiquidResult<()> {
+                if x.len() != self.get_q() as usize || y.len() != self.get_p() as usize {
+                    return Err(FilterError::InvalidLength {
+                        description: format!(
+                            "input length: {} output length: {}, expected {} and {}",
+                            x.len(),
+                            y.len(),
+                            self.get_q(),
+                            self.get_p()
+                        ),
+                    }
+                    .into());
+                }
+                unsafe {
+                    $execute(self.inner, x.to_ptr() as _, y.to_ptr_mut());
+                }
+                Ok(())
+            }
+
+            /// resample a number of consecutive blocks
+            ///  x      :   input samples [size: k*get_q() x 1]
+            ///  y      :   output samples [size: k*get_p() x 1]
+            pub fn execute_block(&mut self, x: &[$type2], y: &mut [$type2]) -> LiquidResult<()> {
+                let (p, q) = (self.get_p() as usize, self.get_q() as usize);
+                if x.len() % q != 0 || y.len() != x.len() / q * p {
+                    return Err(FilterError::InvalidLength {
+                        description: format!(
+                            "input length: {} output length: {}, input must hold a multiple \
+                             of {} samples and the output {} samples per block",
+                            x.len(),
+                            y.len(),
+                            q,
+                            p
+                        ),
+                    }
+                    .into());
+                }
+                for (xb, yb) in x.chunks(q).zip(y.chunks_mut(p)) {
+                    unsafe {
+                        $execute(self.inner, xb.to_ptr() as _, yb.to_ptr_mut());
+                    }
+                }
+                Ok(())
+            }
+
+            /// complete the buffered partial block and push zeros through the
+            /// resampler for its delay of get_delay() input samples, rounded
+            /// up to whole blocks, appending the tail to out; the resampler is
+            /// reset afterwards, ready for the next burst
+            pub fn flush(&mut self, out: &mut Vec<$type2>) {
+                let q = self.get_q() as usize;
+                let n = (self.pending.len() + self.get_delay() as usize).div_ceil(q) * q;
+                let mut x = std::mem::take(&mut self.pending);
+                x.resize(n, <$type2>::default());
+                let start = out.len();
+                out.resize(start + self.output_len_for(n), <$type2>::default());
+                self.execute_block(&x, &mut out[start..])
+                    .expect("whole blocks of input");
+                self.reset();
+            }
+        }
+
+        /// buffers the input until a whole block of get_q() samples is
+        /// available, producing get_p() samples per block
+        impl Block<$type2, $type2> for $obj {
+            fn process(&mut self, input: &[$type2], output: &mut Vec<$type2>) -> LiquidResult<()> {
+                let (p, q) = (self.get_p() as usize, self.get_q() as usize);
+                self.pending.extend_from_slice(input);
+                let blocks = self.pending.len() / q;
+                let x = std::mem::take(&mut self.pending);
+                let res = append_with(output, blocks * p, |y| {
+                    self.execute_block(&x[..blocks * q], y)
+                });
+                self.pending = x;
+                if res.is_ok() {
+                    self.pending.drain(..blocks * q);
+                }
+                res
+            }
+
+            fn rate(&self) -> f32 {
+                self.get_rate()
+            }
+        }
+
+        impl Drop for $obj {
+            fn drop(&mut self) {
+                unsafe {
+                    $destroy(self.inner);
+                }
+            }
+        }
+    };
+}
+
+rresamp_impl!(
+    RresampRrrf,
+    (
+        raw::rresamp_rrrf_create,
+        raw::rresamp_rrrf_create_kaiser,
+        raw::rresamp_rrrf_create_default,
+        raw::rresamp_rrrf_print,
+        raw::rresamp_rrrf_reset,
+        raw::rresamp_rrrf_set_scale,
+        raw::rresamp_rrrf_get_scale,
+        raw::rresamp_rrrf_get_delay,
+        raw::rresamp_rrrf_get_P,
+        raw::rresamp_rrrf_get_interp,
+        raw::rresamp_rrrf_get_Q,
+        raw::rresamp_rrrf_get_decim,
+        raw::rresamp_rrrf_get_block_len,
+        raw::rresamp_rrrf_get_rate,
+        raw::rresamp_rrrf_execute,
+        raw::rresamp_rrrf_destroy,
+        f32,
+        f32
+    )
+);
+
+rresamp_impl!(
+    RresampCrcf,
+    (
+        raw::rresamp_crcf_create,
+        raw::rresamp_crcf_create_kaiser,
+        raw::rresamp_crcf_create_default,
+        raw::rresamp_crcf_print,
+        raw::rresamp_crcf_reset,
+        raw::rresamp_crcf_set_scale,
+        raw::rresamp_crcf_get_scale,
+        raw::rresamp_crcf_get_delay,
+        raw::rresamp_crcf_get_P,
+        raw::rresamp_crcf_get_interp,
+        raw::rresamp_crcf_get_Q,
+        raw::rresamp_crcf_get_decim,
+        raw::rresamp_crcf_get_block_len,
+        raw::rresamp_crcf_get_rate,
+        raw::rresamp_crcf_execute,
+        raw::rresamp_crcf_destroy,
+        f32,
+        Complex32
+    )
+);
+
+rresamp_impl!(
+    RresampCccf,
+    (
+        raw::rresamp_cccf_create,
+        raw::rresamp_cccf_create_kaiser,
+        raw::rresamp_cccf_create_default,
+        raw::rresamp_cccf_print,
+        raw::rresamp_cccf_reset,
+        raw::rresamp_cccf_set_scale,
+        raw::rresamp_cccf_get_scale,
+        raw::rresamp_cccf_get_delay,
+        raw::rresamp_cccf_get_P,
+        raw::rresamp_cccf_get_interp,
+        raw::rresamp_cccf_get_Q,
+        raw::rresamp_cccf_get_decim,
+        raw::rresamp_cccf_get_block_len,
+        raw::rresamp_cccf_get_rate,
+        raw::rresamp_cccf_execute,
+        raw::rresamp_cccf_destroy,
+        Complex32,
+        Complex32
+    )
+);
+
+#[cfg(test)]
+mod tests {
+    use super::RresampRrrf;
+    use crate::block::Block;
+
+    #[test]
+    fn test_rresamp_rrrf_block_lengths() {
+        assert!(RresampRrrf::create(0, 3, 12, 0.4, 60.0).is_err());
+        assert!(RresampRrrf::create(2, 3, 12, 0.6, 60.0).is_err());
+
+        let mut resamp = RresampRrrf::create(147, 160, 12, 0.45, 60.0).unwrap();
+        assert_eq!(resamp.get_p(), 147);
+        assert_eq!(resamp.get_q(), 160);
+        assert_eq!(resamp.output_len_for(479), 294);
+        assert_eq!(resamp.required_input_for(294), 320);
+        assert_eq!(resamp.required_input_for(295), 480);
+
+        let x = vec![0f32; 320];
+        let mut y = vec![0f32; 294];
+        resamp.execute_block(&x, &mut y).unwrap();
+        assert!(resamp.execute(&x, &mut y).is_err());
+        let mut short = vec![0f32; 200];
+        assert!(resamp.execute_block(&x, &mut short).is_err());
+    }
+
+    #[test]
+    fn test_rresamp_rrrf_process() {
+        let mut resamp = RresampRrrf::create(3, 4, 12, 0.4, 60.0).unwrap();
+        let mut y = Vec::new();
+        resamp.process(&[0f32; 6], &mut y).unwrap();
+        assert_eq!(y.len(), 3);
+        resamp.process(&[0f32; 6], &mut y).unwrap();
+        assert_eq!(y.len(), 9);
+    }
+
+    #[test]
+    fn test_rresamp_rrrf_flush() {
+        let mut resamp = RresampRrrf::create(3, 4, 12, 0.4, 60.0).unwrap();
+        let mut impulse = [0f32; 20];
+        impulse[0] = 1.0;
+        let mut y = Vec::new();
+        resamp.process(&impulse[..6], &mut y).unwrap();
+        // 2 pending samples plus a delay of 12 rounded up to 4 blocks
+        resamp.flush(&mut y);
+        assert_eq!(y.len(), 3 + 12);
+
+        // same as running the impulse followed by zeros at once
+        let mut reference = RresampRrrf::create(3, 4, 12, 0.4, 60.0).unwrap();
+        let mut expected = vec![0f32; 15];
+        reference.execute_block(&impulse, &mut expected).unwrap();
+        for (a, b) in y.iter().zip(expected.iter()) {
+            assert!((a - b).abs() < 1e-6);
+        }
+        // the peak of the response, delayed by 9 output samples, is in the
+        // flushed tail
+        let energy = |v: &[f32]| v.iter().map(|s| s * s).sum::<f32>();
+        assert!(energy(&y[3..]) > 10.0 * energy(&y[..3]));
+
+        resamp.process(&[0f32; 4], &mut y).unwrap();
+        assert_eq!(y.len(), 18);
+        assert!(y[15..].iter().all(|&v| v == 0.0));
+    }
+}
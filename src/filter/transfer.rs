@@ -1,4 +1,247 @@
+use num::complex::Complex32;
+use std::fmt;
+
+use crate::errors::FilterError;
+use crate::filter::filter::polyval_unit_circle;
+use crate::filter::{FrequencyResponse, IirFiltCrcf, IirFiltRrrf};
+use crate::liquid_dsp_sys as raw;
+use crate::LiquidResult;
+
+/// filter coefficients, either as a transfer function or as second-order
+/// sections stacked 3 coefficients per section
+#[derive(Clone, Debug, PartialEq)]
 pub struct Transfer {
-    pub a: Vec<f32>,
-    pub b: Vec<f32>,
+    pub(crate) a: Vec<f32>,
+    pub(crate) b: Vec<f32>,
+    pub(crate) sos: bool,
+}
+
+impl Transfer {
+    /// numerator, feed-forward coefficients
+    pub fn b(&self) -> &[f32] {
+        self.b.as_ref()
+    }
+
+    /// denominator, feed-back coefficients
+    pub fn a(&self) -> &[f32] {
+        self.a.as_ref()
+    }
+
+    /// are the coefficients second-order sections?
+    pub fn is_sos(&self) -> bool {
+        self.sos
+    }
+
+    /// number of second-order sections, zero for a transfer function
+    pub fn num_sections(&self) -> usize {
+        if self.sos {
+            self.a.len() / 3
+        } else {
+            0
+        }
+    }
+
+    /// (b, a) coefficients of every section, the whole transfer function
+    /// being a single section
+    fn sections(&self) -> Vec<(&[f32], &[f32])> {
+        if self.sos {
+            self.b.chunks(3).zip(self.a.chunks(3)).collect()
+        } else {
+            vec![(self.b.as_ref(), self.a.as_ref())]
+        }
+    }
+
+    /// scale the coefficients so that the leading denominator coefficient
+    /// of every section is one
+    pub fn normalize(&mut self) -> LiquidResult<()> {
+        let n = if self.sos { 3 } else { self.a.len().max(1) };
+        if self.a.chunks(n).any(|a| a[0] == 0f32) {
+            return Err(FilterError::InvalidValue(
+                "first denominator coefficient cannot be zero".to_owned(),
+            )
+            .into());
+        }
+        if self.sos {
+            for (b, a) in self.b.chunks_mut(3).zip(self.a.chunks_mut(3)) {
+                let a0 = a[0];
+                b.iter_mut().for_each(|v| *v /= a0);
+                a.iter_mut().for_each(|v| *v /= a0);
+            }
+        } else if let Some(&a0) = self.a.first() {
+            // the numerator can be longer than the denominator
+            self.b.iter_mut().for_each(|v| *v /= a0);
+            self.a.iter_mut().for_each(|v| *v /= a0);
+        }
+        Ok(())
+    }
+
+    /// are all the poles inside the unit circle?
+    pub fn is_stable(&self) -> bool {
+        self.sections().into_iter().all(|(b, a)| {
+            // liquid expects numerator and denominator of the same length
+            let n = a.len().max(b.len());
+            let mut b = b.to_vec();
+            let mut a = a.to_vec();
+            b.resize(n, 0.0);
+            a.resize(n, 0.0);
+            unsafe { raw::iirdes_isstable(b.as_mut_ptr(), a.as_mut_ptr(), n as _) == 1 }
+        })
+    }
+
+    /// build a runnable filter from the coefficients
+    pub fn into_iirfilt_rrrf(self) -> LiquidResult<IirFiltRrrf> {
+        if self.sos {
+            IirFiltRrrf::create_sos(&self.a, &self.b, self.num_sections())
+        } else {
+            IirFiltRrrf::create(&self.a, &self.b)
+        }
+    }
+
+    /// build a runnable filter on complex samples from the coefficients
+    pub fn into_iirfilt_crcf(self) -> LiquidResult<IirFiltCrcf> {
+        if self.sos {
+            IirFiltCrcf::create_sos(&self.a, &self.b, self.num_sections())
+        } else {
+            IirFiltCrcf::create(&self.a, &self.b)
+        }
+    }
+}
+
+impl FrequencyResponse for Transfer {
+    fn freq_response(&self, fc: f32) -> LiquidResult<Complex32> {
+        if !(-0.5..=0.5).contains(&fc) {
+            return Err(FilterError::InvalidValue("fc must be in [-0.5, 0.5]".to_owned()).into());
+        }
+        Ok(self
+            .sections()
+            .into_iter()
+            .map(|(b, a)| polyval_unit_circle(b, fc) / polyval_unit_circle(a, fc))
+            .product())
+    }
+
+    /// Compute group delay, summed over the sections
+    ///  fc     : frequency at which delay is evaluated (-0.5 < _fc < 0.5)
+    fn group_delay(&self, fc: f32) -> LiquidResult<f32> {
+        if !(-0.5..=0.5).contains(&fc) {
+            return Err(FilterError::InvalidValue("fc must be in [-0.5, 0.5]".to_owned()).into());
+        }
+        Ok(self
+            .sections()
+            .into_iter()
+            .map(|(b, a)| unsafe {
+                raw::iir_group_delay(
+                    b.as_ptr() as _,
+                    b.len() as _,
+                    a.as_ptr() as _,
+                    a.len() as _,
+                    fc,
+                )
+            })
+            .sum())
+    }
+}
+
+/// polynomial in z^-1, e.g. "1 - 0.5 z^-1 + 0.25 z^-2"
+fn fmt_poly(c: &[f32]) -> String {
+    let mut s = String::new();
+    for (i, &v) in c.iter().enumerate() {
+        if i == 0 {
+            s.push_str(&format!("{}", v));
+        } else {
+            let sign = if v < 0f32 { '-' } else { '+' };
+            s.push_str(&format!(" {} {} z^-{}", sign, v.abs(), i));
+        }
+    }
+    s
+}
+
+impl fmt::Display for Transfer {
+    /// the transfer function, one line per second-order section
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, (b, a)) in self.sections().into_iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            if self.sos {
+                write!(f, "H{}(z) = ", i)?;
+            } else {
+                write!(f, "H(z) = ")?;
+            }
+            write!(f, "({}) / ({})", fmt_poly(b), fmt_poly(a))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Transfer;
+    use crate::filter::FrequencyResponse;
+
+    #[test]
+    fn test_transfer_normalize_display() {
+        let mut tf = Transfer {
+            a: vec![2.0, -1.0],
+            b: vec![1.0, 1.0],
+            sos: false,
+        };
+        tf.normalize().unwrap();
+        assert_eq!(tf.a(), &[1.0, -0.5]);
+        assert_eq!(tf.b(), &[0.5, 0.5]);
+        assert_eq!(tf.to_string(), "H(z) = (0.5 + 0.5 z^-1) / (1 - 0.5 z^-1)");
+        // DC gain, sum(b) / sum(a)
+        assert!((tf.freq_response(0.0).unwrap().re - 2.0).abs() < 1e-6);
+        assert!(tf.freq_response(0.6).is_err());
+
+        let mut sos = Transfer {
+            a: vec![1.0, 0.0, 0.0, 2.0, 0.0, 0.0],
+            b: vec![1.0, 0.0, 0.0, 1.0, 0.0, 0.0],
+            sos: true,
+        };
+        assert_eq!(sos.num_sections(), 2);
+        sos.normalize().unwrap();
+        assert_eq!(sos.a(), &[1.0, 0.0, 0.0, 1.0, 0.0, 0.0]);
+        assert_eq!(sos.b(), &[1.0, 0.0, 0.0, 0.5, 0.0, 0.0]);
+        assert_eq!(sos.to_string().lines().count(), 2);
+
+        sos.a[0] = 0.0;
+        assert!(sos.normalize().is_err());
+
+        // the whole numerator is scaled when it is longer than a
+        let mut fir = Transfer {
+            a: vec![2.0],
+            b: vec![1.0, 2.0, 4.0],
+            sos: false,
+        };
+        fir.normalize().unwrap();
+        assert_eq!(fir.a(), &[1.0]);
+        assert_eq!(fir.b(), &[0.5, 1.0, 2.0]);
+    }
+
+    #[test]
+    fn test_transfer_is_stable() {
+        let stable = Transfer {
+            a: vec![1.0, -0.5],
+            b: vec![1.0, 0.0],
+            sos: false,
+        };
+        assert!(stable.is_stable());
+        // pole at z = 2
+        let unstable = Transfer {
+            a: vec![1.0, -2.0],
+            b: vec![1.0, 0.0],
+            sos: false,
+        };
+        assert!(!unstable.is_stable());
+
+        // a single unstable section makes the cascade unstable
+        let mut sos = Transfer {
+            a: vec![1.0, -0.5, 0.0, 1.0, 0.0, 0.25],
+            b: vec![1.0, 0.0, 0.0, 1.0, 0.0, 0.0],
+            sos: true,
+        };
+        assert!(sos.is_stable());
+        sos.a[5] = 4.0;
+        assert!(!sos.is_stable());
+    }
 }
@@ -1,9 +1,12 @@
 use num::complex::Complex32;
 use std::marker::PhantomData;
 
+use crate::errors::FilterError;
+use crate::filter::transfer::Transfer;
+use crate::filter::{IirFiltCrcf, IirFiltRrrf, Iirdes, IirdesBandType, IirdesFilterType};
 use crate::liquid_dsp_sys as raw;
 use crate::utils::{ToCPointerMut, ToCValue};
-use filter::transfer::Transfer;
+use crate::LiquidResult;
 
 // filter type
 pub enum Discrete {}
@@ -33,17 +36,6 @@ pub struct Zpk<B, T, R> {
     band: PhantomData<B>,
 }
 
-/* impl Zpk<Discrete> {
-    pub fn new_discrete(nz: usize, np: usize, k: Complex32) -> Self {
-        Self {
-            k,
-            z: vec![Complex32::default(); nz],
-            p: vec![Complex32::default(); np],
-            data: PhantomData,
-        }
-    }
-} */
-
 impl<B, T, R> Zpk<B, T, R> {
     fn new(n: usize, k: Complex32) -> Self {
         Self {
@@ -57,24 +49,29 @@ impl<B, T, R> Zpk<B, T, R> {
         }
     }
 
+    /// same zeros, poles and gain under other type markers
+    fn retype<B2, T2, R2>(self) -> Zpk<B2, T2, R2> {
+        Zpk {
+            k: self.k,
+            p: self.p,
+            z: self.z,
+            n: self.n,
+            data: PhantomData,
+            ftype: PhantomData,
+            band: PhantomData,
+        }
+    }
+
     pub fn len(&self) -> usize {
         self.n
     }
+
+    pub fn is_empty(&self) -> bool {
+        self.n == 0
+    }
 }
 
 impl<B, R> Zpk<B, Analog, R> {
-    /*     fn new_analog(n: usize, k: Complex32) -> Self {
-        Self {
-            k,
-            z: vec![Complex32::default(); n],
-            p: vec![Complex32::default(); n],
-            n,
-            data: PhantomData,
-            ftype: PhantomData,
-            band: PhantomData,
-        }
-    } */
-
     /// convert analog zeros, poles, gain to digital zeros, poles gain
     ///  m      :   frequency pre-warping factor
     ///
@@ -86,7 +83,7 @@ impl<B, R> Zpk<B, Analog, R> {
         unsafe {
             raw::bilinear_zpkf(
                 self.z.as_mut_slice().to_ptr_mut(),
-                self.len() as _,
+                self.z.len() as _,
                 self.p.as_mut_slice().to_ptr_mut(),
                 self.p.len() as _,
                 self.k.to_c_value(),
@@ -96,10 +93,37 @@ impl<B, R> Zpk<B, Analog, R> {
                 new.k.to_ptr_mut() as _,
             );
         }
+        // liquid derives the gain from 1 - zd, which vanishes for analog
+        // zeros at the origin, e.g. of high-pass prototypes; the factors
+        // of the substitution s = (z-1)/(m(z+1)) are used instead
+        let nz = self.z.len() as i32;
+        let np = self.p.len() as i32;
+        new.k =
+            self.k * m.powi(np - nz) * self.z.iter().map(|z| 1.0 - z * m).product::<Complex32>()
+                / self.p.iter().map(|p| 1.0 - p * m).product::<Complex32>();
         new
     }
 }
 
+impl<R> Zpk<LowPass, Analog, R> {
+    /// analog low-pass to high-pass transformation s -> 1/s, keeping the
+    /// unit cutoff; the zeros missing in the low-pass prototype end up at
+    /// the origin. Use the low-pass pre-warping factor for the bilinear
+    /// transform of the result.
+    pub fn lp_to_hp(self) -> Zpk<HighPass, Analog, R> {
+        let mut hp = Zpk::new(self.len(), Complex32::default());
+        hp.k = self.k * self.z.iter().map(|z| -z).product::<Complex32>()
+            / self.p.iter().map(|p| -p).product::<Complex32>();
+        for (i, z) in hp.z.iter_mut().enumerate() {
+            *z = self.z.get(i).map_or(Complex32::default(), |z| 1.0 / z);
+        }
+        for (p, q) in hp.p.iter_mut().zip(&self.p) {
+            *p = 1.0 / q;
+        }
+        hp
+    }
+}
+
 impl Zpk<LowPass, Analog, Butter> {
     /// Compute analog zeros, poles, gain of low-pass Butterworth
     /// filter, grouping complex conjugates together. If filter
@@ -117,6 +141,8 @@ impl Zpk<LowPass, Analog, Butter> {
                 f.k.to_ptr_mut(),
             );
         }
+        // no analog zeros
+        f.z.clear();
         f
     }
 }
@@ -140,6 +166,8 @@ impl Zpk<LowPass, Analog, Cheby1> {
                 f.k.to_ptr_mut(),
             );
         }
+        // no analog zeros
+        f.z.clear();
         f
     }
 }
@@ -162,6 +190,8 @@ impl Zpk<LowPass, Analog, Cheby2> {
                 f.k.to_ptr_mut(),
             );
         }
+        // one zero per conjugate pole pair
+        f.z.truncate(n - n % 2);
         f
     }
 }
@@ -188,6 +218,8 @@ impl Zpk<LowPass, Analog, Ellip> {
                 f.k.to_ptr_mut(),
             );
         }
+        // one zero per conjugate pole pair
+        f.z.truncate(n - n % 2);
         f
     }
 }
@@ -209,6 +241,8 @@ impl Zpk<LowPass, Analog, Bessel> {
                 f.k.to_ptr_mut(),
             );
         }
+        // no analog zeros
+        f.z.clear();
         f
     }
 }
@@ -219,6 +253,7 @@ impl<B, R> Zpk<B, Discrete, R> {
         let mut transfer = Transfer {
             a: vec![0f32; self.len() + 1],
             b: vec![0f32; self.len() + 1],
+            sos: false,
         };
         unsafe {
             raw::iirdes_dzpk2tff(
@@ -233,6 +268,8 @@ impl<B, R> Zpk<B, Discrete, R> {
         transfer
     }
 
+    /// convert discrete Zpk form to second-order sections, L+r sections
+    /// for L conjugate pairs and r = n % 2 real pole
     pub fn to_sosf(mut self) -> Transfer {
         let r = self.len() % 2;
         let l = (self.len() - r) / 2;
@@ -240,6 +277,7 @@ impl<B, R> Zpk<B, Discrete, R> {
         let mut transfer = Transfer {
             a: vec![0f32; len],
             b: vec![0f32; len],
+            sos: true,
         };
         unsafe {
             raw::iirdes_dzpk2sosf(
@@ -253,6 +291,34 @@ impl<B, R> Zpk<B, Discrete, R> {
         }
         transfer
     }
+
+    /// replace every zero and pole by the pair satisfying the band-pass
+    /// substitution around f0
+    fn lp2bp<B2>(mut self, f0: f32) -> Zpk<B2, Discrete, R> {
+        let mut bp = Zpk::new(2 * self.len(), self.k);
+        unsafe {
+            raw::iirdes_dzpk_lp2bp(
+                self.z.as_mut_slice().to_ptr_mut(),
+                self.p.as_mut_slice().to_ptr_mut(),
+                self.len() as _,
+                f0,
+                bp.z.as_mut_slice().to_ptr_mut(),
+                bp.p.as_mut_slice().to_ptr_mut(),
+            );
+        }
+        bp
+    }
+
+    /// build a runnable filter, in second-order sections which are less
+    /// sensitive to coefficient quantization than the transfer function
+    pub fn into_iirfilt_rrrf(self) -> LiquidResult<IirFiltRrrf> {
+        self.to_sosf().into_iirfilt_rrrf()
+    }
+
+    /// build a runnable filter on complex samples, in second-order sections
+    pub fn into_iirfilt_crcf(self) -> LiquidResult<IirFiltCrcf> {
+        self.to_sosf().into_iirfilt_crcf()
+    }
 }
 
 impl<R> Zpk<LowPass, Discrete, R> {
@@ -271,20 +337,150 @@ impl<R> Zpk<LowPass, Discrete, R> {
         hp
     }
 
-    /// digital z/p/k low-pass to band-pass transformation
+    /// digital z/p/k low-pass to band-pass transformation, doubling the
+    /// number of zeros and poles
     ///  f0     :   center frequency
-    pub fn lp_to_bp(mut self, f0: f32) -> Zpk<BandPass, Discrete, R> {
-        let mut hp = Zpk::new(self.len(), self.k);
-        unsafe {
-            raw::iirdes_dzpk_lp2bp(
-                self.z.as_mut_slice().to_ptr_mut(),
-                self.p.as_mut_slice().to_ptr_mut(),
-                self.len() as _,
-                f0,
-                hp.z.as_mut_slice().to_ptr_mut(),
-                hp.p.as_mut_slice().to_ptr_mut(),
+    pub fn lp_to_bp(self, f0: f32) -> Zpk<BandPass, Discrete, R> {
+        self.lp2bp(f0)
+    }
+
+    /// digital z/p/k low-pass to band-stop transformation, the band-pass
+    /// transformation of the high-pass filter, doubling the number of
+    /// zeros and poles
+    ///  f0     :   center frequency
+    pub fn lp_to_bs(self, f0: f32) -> Zpk<StopB, Discrete, R> {
+        self.lp_to_hp().lp2bp(f0)
+    }
+}
+
+impl Zpk<(), Discrete, ()> {
+    /// design a digital filter in one call: analog low-pass prototype,
+    /// bilinear transform, band transformation and second-order sections,
+    /// as liquid_iirdes does; the band and prototype being chosen at run
+    /// time, the intermediate steps carry no type markers
+    ///  ftype  :   analog prototype, e.g. IirdesFilterType::ELLIP
+    ///  btype  :   band type, e.g. IirdesBandType::BANDSTOP
+    ///  order  :   order of the prototype, order > 0
+    ///  fc     :   low-pass prototype cutoff frequency, in (0, 0.5)
+    ///  f0     :   center frequency (band-pass|stop cases only), in [0, 0.5]
+    ///  ap     :   pass-band ripple [dB], ap > 0
+    ///  as_    :   stop-band ripple [dB], as_ > 0
+    #[allow(clippy::too_many_arguments)]
+    pub fn design(
+        ftype: IirdesFilterType,
+        btype: IirdesBandType,
+        order: usize,
+        fc: f32,
+        f0: f32,
+        ap: f32,
+        as_: f32,
+    ) -> LiquidResult<Transfer> {
+        if fc <= 0f32 || fc >= 0.5 {
+            return Err(FilterError::InvalidValue("fc must be in (0, 0.5)".to_owned()).into());
+        } else if !(0.0..=0.5).contains(&f0) {
+            return Err(FilterError::InvalidValue("f0 must be in [0, 0.5]".to_owned()).into());
+        } else if ap <= 0f32 {
+            return Err(FilterError::InvalidValue("ap must be greater than 0".to_owned()).into());
+        } else if as_ <= 0f32 {
+            return Err(FilterError::InvalidValue(
+                "as(stop-band ripple) must be greater than 0".to_owned(),
+            )
+            .into());
+        } else if order == 0 {
+            return Err(
+                FilterError::InvalidValue("order must be greater than 0".to_owned()).into(),
             );
         }
-        hp
+        // ripples as epsilon values
+        let gp = 10f32.powf(-ap / 20.0);
+        let gs = 10f32.powf(-as_ / 20.0);
+        let ep = (1.0 / (gp * gp) - 1.0).sqrt();
+        let es = (1.0 / (gs * gs) - 1.0).sqrt();
+        let prototype: Zpk<LowPass, Analog, ()> = match ftype {
+            IirdesFilterType::BUTTER => Zpk::butterf(order).retype(),
+            IirdesFilterType::CHEBY1 => Zpk::cheby1(order, ep).retype(),
+            IirdesFilterType::CHEBY2 => Zpk::cheby2(order, es).retype(),
+            IirdesFilterType::ELLIP => Zpk::ellip(order, ep, es).retype(),
+            IirdesFilterType::BESSEL => Zpk::bessel(order).retype(),
+        };
+        let m = Iirdes::freq_prewarp(btype, fc, f0)?;
+        let lp = prototype.bilinear_zpkf(m);
+        let zpk: Zpk<(), Discrete, ()> = match btype {
+            IirdesBandType::LOWPASS => lp.retype(),
+            IirdesBandType::HIGHPASS => lp.lp_to_hp().retype(),
+            IirdesBandType::BANDPASS => lp.lp_to_bp(f0).retype(),
+            IirdesBandType::BANDSTOP => lp.lp_to_bs(f0).retype(),
+        };
+        Ok(zpk.to_sosf())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Analog, Butter, Cheby2, LowPass, Zpk};
+    use crate::filter::{Iirdes, IirdesBandType};
+    use num::complex::Complex32;
+
+    #[test]
+    fn test_zpk_into_iirfilt() {
+        assert!(Zpk::<LowPass, _, Butter>::butterf(5).z.is_empty());
+        assert_eq!(Zpk::cheby2(5, 0.1).z.len(), 4);
+        let _: &Zpk<LowPass, _, Cheby2> = &Zpk::cheby2(2, 0.1);
+
+        let fc = 0.1;
+        let m = Iirdes::freq_prewarp(IirdesBandType::LOWPASS, fc, 0.0).unwrap();
+        let zpk = Zpk::butterf(5).bilinear_zpkf(m);
+        let q = zpk.into_iirfilt_rrrf().unwrap();
+        assert!((q.freq_response(0.0).norm() - 1.0).abs() < 1e-3);
+        assert!((q.freq_response(fc).norm() - 0.5f32.sqrt()).abs() < 1e-2);
+        assert!(q.freq_response(0.3).norm() < 1e-3);
+
+        let tf = Zpk::butterf(5).bilinear_zpkf(m).to_tff();
+        assert!(!tf.is_sos());
+        assert!(tf.is_stable());
+        let q = tf.into_iirfilt_crcf().unwrap();
+        assert!((q.freq_response(0.0) - Complex32::new(1.0, 0.0)).norm() < 1e-3);
+    }
+
+    #[test]
+    fn test_zpk_analog_highpass() {
+        // first-order Butterworth, s/(s+1) once transformed
+        let mut lp = Zpk::<LowPass, Analog, Butter>::new(1, Complex32::new(1.0, 0.0));
+        lp.z.clear();
+        lp.p[0] = Complex32::new(-1.0, 0.0);
+        let hp = lp.lp_to_hp();
+        assert_eq!(hp.z, vec![Complex32::default()]);
+        assert_eq!(hp.p, vec![Complex32::new(-1.0, 0.0)]);
+        assert!((hp.k - Complex32::new(1.0, 0.0)).norm() < 1e-6);
+        // unity gain at z = -1 despite the zero at z = 1
+        let m = 0.5;
+        let hd = hp.bilinear_zpkf(m);
+        assert!((hd.k - Complex32::new(1.0 / (1.0 + m), 0.0)).norm() < 1e-6);
+    }
+
+    #[test]
+    fn test_zpk_design() {
+        use crate::filter::{FrequencyResponse, IirdesFilterType};
+        let design =
+            |btype, fc, f0| Zpk::design(IirdesFilterType::BUTTER, btype, 4, fc, f0, 1.0, 60.0);
+        assert!(design(IirdesBandType::LOWPASS, 0.5, 0.0).is_err());
+        assert!(design(IirdesBandType::BANDSTOP, 0.1, 0.6).is_err());
+        assert!(Zpk::design(
+            IirdesFilterType::ELLIP,
+            IirdesBandType::LOWPASS,
+            0,
+            0.1,
+            0.0,
+            1.0,
+            60.0
+        )
+        .is_err());
+
+        let bs = design(IirdesBandType::BANDSTOP, 0.05, 0.25).unwrap();
+        assert_eq!(bs.num_sections(), 4);
+        assert!(bs.is_stable());
+        assert!(bs.freq_response(0.25).unwrap().norm() < 1e-2);
+        assert!((bs.freq_response(0.0).unwrap().norm() - 1.0).abs() < 1e-2);
+        assert!((bs.freq_response(0.5).unwrap().norm() - 1.0).abs() < 1e-2);
     }
 }
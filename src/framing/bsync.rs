@@ -0,0 +1,284 @@
+//! *bsync* : binary synchronizer, correlates the received samples with a
+//!         known binary pattern for timing acquisition, e.g. on a simple
+//!         BPSK link
+use num::complex::Complex32;
+
+use crate::liquid_dsp_sys as raw;
+use crate::utils::{ToCPointer, ToCPointerMut, ToCValue};
+
+use crate::errors::LiquidError;
+use crate::LiquidResult;
+
+/// correlation peak reported by the synchronizers
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BsyncPeak<T> {
+    /// index of the peak, counted from the first sample pushed since the
+    /// object was created or reset
+    pub index: u64,
+    /// correlator output at the peak
+    pub rxy: T,
+}
+
+pub struct BsyncRrrf {
+    inner: raw::bsync_rrrf,
+    len: usize,
+    threshold: f32,
+    num_samples: u64,
+    // largest output of the ongoing peak
+    peak: Option<BsyncPeak<f32>>,
+}
+
+pub struct BsyncCrcf {
+    inner: raw::bsync_crcf,
+    len: usize,
+    threshold: f32,
+    num_samples: u64,
+    peak: Option<BsyncPeak<Complex32>>,
+}
+
+pub struct BsyncCccf {
+    inner: raw::bsync_cccf,
+    len: usize,
+    threshold: f32,
+    num_samples: u64,
+    peak: Option<BsyncPeak<Complex32>>,
+}
+
+macro_rules! bsync_impl {
+    ($obj:ty, (
+        $create:expr, $create_msequence:expr,
+        $print:expr, $correlate:expr,
+        $destroy:expr,
+        |$v:ident| $magnitude:expr,
+        $type:ty, $type2:ty)) => {
+        impl $obj {
+            /// create a synchronizer from a known pattern
+            ///  v          :   pattern, e.g. +/-1 symbols, v.len() > 0
+            ///  threshold  :   correlation magnitude above which a peak is
+            ///                 reported, threshold > 0
+            pub fn create(v: &[$type], threshold: f32) -> LiquidResult<Self> {
+                if v.is_empty() {
+                    return Err(LiquidError::InvalidLength {
+                        description: "pattern length must be greater than zero".to_owned(),
+                    });
+                }
+                Self::check_threshold(threshold)?;
+                Ok(Self {
+                    inner: unsafe { $create(v.len() as _, v.to_ptr() as _) },
+                    len: v.len(),
+                    threshold,
+                    num_samples: 0,
+                    peak: None,
+                })
+            }
+
+            /// create a synchronizer from the m-sequence of a generator
+            /// polynomial
+            ///  g          :   generator polynomial, e.g. 0x0067 for m = 6,
+            ///                 with a degree in [2, 15]
+            ///  k          :   samples per symbol, k > 0
+            ///  threshold  :   correlation magnitude above which a peak is
+            ///                 reported, threshold > 0
+            pub fn create_msequence(g: u32, k: u32, threshold: f32) -> LiquidResult<Self> {
+                let m = 31 - g.max(1).leading_zeros();
+                if !(2..=15).contains(&m) {
+                    return Err(LiquidError::InvalidValue(format!(
+                        "generator polynomial: {:#x} degree must be in [2, 15]",
+                        g
+                    )));
+                } else if k == 0 {
+                    return Err(LiquidError::InvalidValue(
+                        "samples per symbol must be greater than zero".to_owned(),
+                    ));
+                }
+                Self::check_threshold(threshold)?;
+                Ok(Self {
+                    inner: unsafe { $create_msequence(g as _, k as _) },
+                    len: ((1usize << m) - 1) * k as usize,
+                    threshold,
+                    num_samples: 0,
+                    peak: None,
+                })
+            }
+
+            fn check_threshold(threshold: f32) -> LiquidResult<()> {
+                if threshold <= 0f32 || threshold.is_nan() {
+                    return Err(LiquidError::InvalidValue(
+                        "threshold must be greater than zero".to_owned(),
+                    ));
+                }
+                Ok(())
+            }
+
+            pub fn print(&self) {
+                unsafe {
+                    $print(self.inner);
+                }
+            }
+
+            /// length of the pattern [samples]
+            pub fn len(&self) -> usize {
+                self.len
+            }
+
+            pub fn is_empty(&self) -> bool {
+                self.len == 0
+            }
+
+            pub fn set_threshold(&mut self, threshold: f32) -> LiquidResult<()> {
+                Self::check_threshold(threshold)?;
+                self.threshold = threshold;
+                Ok(())
+            }
+
+            pub fn get_threshold(&self) -> f32 {
+                self.threshold
+            }
+
+            /// restart the sample count and drop the ongoing peak; the
+            /// correlator buffer is not cleared by liquid
+            pub fn reset(&mut self) {
+                self.num_samples = 0;
+                self.peak = None;
+            }
+
+            /// push a sample into the correlator
+            /// # returns
+            /// the correlator output
+            pub fn correlate(&mut self, x: $type2) -> $type2 {
+                let mut y = <$type2>::default();
+                unsafe {
+                    $correlate(self.inner, x.to_c_value(), y.to_ptr_mut());
+                }
+                y
+            }
+
+            /// push a sample and look for peaks: while the output magnitude
+            /// is above the threshold, the largest one is tracked and
+            /// reported once the magnitude falls back below it
+            pub fn push(&mut self, x: $type2) -> Option<BsyncPeak<$type2>> {
+                let y = self.correlate(x);
+                let index = self.num_samples;
+                self.num_samples += 1;
+                let magnitude = |$v: $type2| -> f32 { $magnitude };
+                if magnitude(y) > self.threshold {
+                    match self.peak {
+                        Some(p) if magnitude(p.rxy) >= magnitude(y) => {}
+                        _ => self.peak = Some(BsyncPeak { index, rxy: y }),
+                    }
+                    None
+                } else {
+                    self.peak.take()
+                }
+            }
+
+            /// run the synchronizer on a block of samples, invoking f on
+            /// every peak
+            pub fn execute_block_with<F>(&mut self, x: &[$type2], mut f: F)
+            where
+                F: FnMut(BsyncPeak<$type2>),
+            {
+                for v in x {
+                    if let Some(peak) = self.push(*v) {
+                        f(peak);
+                    }
+                }
+            }
+
+            /// run the synchronizer on a block of samples
+            /// # returns
+            /// the peaks found in the block
+            pub fn execute_block(&mut self, x: &[$type2]) -> Vec<BsyncPeak<$type2>> {
+                let mut peaks = Vec::new();
+                self.execute_block_with(x, |p| peaks.push(p));
+                peaks
+            }
+
+            /// iterator over the peaks of a block of samples, the samples
+            /// being pushed as the iterator advances
+            pub fn peaks<'a>(
+                &'a mut self,
+                x: &'a [$type2],
+            ) -> impl Iterator<Item = BsyncPeak<$type2>> + 'a {
+                x.iter().filter_map(move |v| self.push(*v))
+            }
+        }
+
+        impl Drop for $obj {
+            fn drop(&mut self) {
+                unsafe {
+                    $destroy(self.inner);
+                }
+            }
+        }
+    };
+}
+
+bsync_impl!(
+    BsyncRrrf,
+    (
+        raw::bsync_rrrf_create,
+        raw::bsync_rrrf_create_msequence,
+        raw::bsync_rrrf_print,
+        raw::bsync_rrrf_correlate,
+        raw::bsync_rrrf_destroy,
+        |y| y.abs(),
+        f32,
+        f32
+    )
+);
+
+bsync_impl!(
+    BsyncCrcf,
+    (
+        raw::bsync_crcf_create,
+        raw::bsync_crcf_create_msequence,
+        raw::bsync_crcf_print,
+        raw::bsync_crcf_correlate,
+        raw::bsync_crcf_destroy,
+        |y| y.norm(),
+        f32,
+        Complex32
+    )
+);
+
+bsync_impl!(
+    BsyncCccf,
+    (
+        raw::bsync_cccf_create,
+        raw::bsync_cccf_create_msequence,
+        raw::bsync_cccf_print,
+        raw::bsync_cccf_correlate,
+        raw::bsync_cccf_destroy,
+        |y| y.norm(),
+        Complex32,
+        Complex32
+    )
+);
+
+#[cfg(test)]
+mod tests {
+    use super::BsyncRrrf;
+
+    #[test]
+    fn test_bsync_rrrf_peak() {
+        assert!(BsyncRrrf::create(&[], 0.5).is_err());
+        assert!(BsyncRrrf::create(&[1.0], 0.0).is_err());
+        assert!(BsyncRrrf::create_msequence(0x1, 2, 0.5).is_err());
+        assert_eq!(
+            BsyncRrrf::create_msequence(0x0067, 2, 0.5).unwrap().len(),
+            126
+        );
+
+        let v = [1.0, -1.0, 1.0, 1.0, -1.0, -1.0, -1.0, 1.0];
+        let mut q = BsyncRrrf::create(&v, 0.8).unwrap();
+        let mut x = vec![0f32; 20];
+        x.extend_from_slice(&v);
+        x.extend(vec![0f32; 20]);
+        let peaks = q.execute_block(&x);
+        assert_eq!(peaks.len(), 1);
+        // the pattern is complete with its last sample
+        assert_eq!(peaks[0].index, 27);
+        assert!(peaks[0].rxy > 0.8);
+    }
+}
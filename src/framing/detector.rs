@@ -0,0 +1,183 @@
+//! *detector* : pre-demodulation detector, cross-correlates the received
+//!           signal with a known sequence over a bank of carrier
+//!           frequency offsets and reports the timing, carrier and gain
+//!           of the detected preamble
+use num::complex::Complex32;
+
+use crate::liquid_dsp_sys as raw;
+use crate::utils::{ToCPointer, ToCValue};
+
+use crate::errors::LiquidError;
+use crate::LiquidResult;
+
+/// estimates reported by the detector on a detection
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DetectorOutput {
+    /// fractional timing offset, in (-0.5, 0.5) samples
+    pub tau_hat: f32,
+    /// carrier frequency offset [radians/sample]
+    pub dphi_hat: f32,
+    /// signal level estimate
+    pub gamma_hat: f32,
+}
+
+pub struct DetectorCccf {
+    inner: raw::detector_cccf,
+}
+
+impl DetectorCccf {
+    /// create pre-demodulation detector
+    ///  s          :   known sequence, s.len() > 0
+    ///  threshold  :   detection threshold, in (0, 1]
+    ///  dphi_max   :   maximum carrier offset [radians/sample], dphi_max >= 0
+    pub fn create(s: &[Complex32], threshold: f32, dphi_max: f32) -> LiquidResult<Self> {
+        if s.is_empty() {
+            return Err(LiquidError::InvalidLength {
+                description: "sequence length must be greater than zero".to_owned(),
+            });
+        } else if threshold <= 0f32 || threshold > 1f32 {
+            return Err(LiquidError::InvalidValue(
+                "threshold must be in (0, 1]".to_owned(),
+            ));
+        } else if dphi_max < 0f32 {
+            return Err(LiquidError::InvalidValue(
+                "maximum carrier offset must not be negative".to_owned(),
+            ));
+        }
+        Ok(Self {
+            inner: unsafe {
+                raw::detector_cccf_create(s.to_ptr() as _, s.len() as _, threshold, dphi_max)
+            },
+        })
+    }
+
+    pub fn print(&self) {
+        unsafe {
+            raw::detector_cccf_print(self.inner);
+        }
+    }
+
+    pub fn reset(&mut self) {
+        unsafe {
+            raw::detector_cccf_reset(self.inner);
+        }
+    }
+
+    /// push a sample and run the detector
+    ///  x      :   input sample
+    /// # returns
+    /// the estimates if the sequence was detected, the detector is reset
+    /// afterwards and starts looking for the next one
+    pub fn correlate(&mut self, x: Complex32) -> Option<DetectorOutput> {
+        let mut out = DetectorOutput {
+            tau_hat: 0.0,
+            dphi_hat: 0.0,
+            gamma_hat: 0.0,
+        };
+        let detected = unsafe {
+            raw::detector_cccf_correlate(
+                self.inner,
+                x.to_c_value(),
+                &mut out.tau_hat,
+                &mut out.dphi_hat,
+                &mut out.gamma_hat,
+            )
+        };
+        if detected != 0 {
+            Some(out)
+        } else {
+            None
+        }
+    }
+
+    /// run the detector on a block of samples, invoking f with the index
+    /// of the sample that triggered each detection
+    pub fn execute_block_with<F>(&mut self, x: &[Complex32], mut f: F)
+    where
+        F: FnMut(usize, DetectorOutput),
+    {
+        for (i, v) in x.iter().enumerate() {
+            if let Some(out) = self.correlate(*v) {
+                f(i, out);
+            }
+        }
+    }
+
+    /// run the detector on a block of samples
+    /// # returns
+    /// the detections, each with the index of the sample that triggered it
+    pub fn execute_block(&mut self, x: &[Complex32]) -> Vec<(usize, DetectorOutput)> {
+        let mut detections = Vec::new();
+        self.execute_block_with(x, |i, out| detections.push((i, out)));
+        detections
+    }
+}
+
+impl Drop for DetectorCccf {
+    fn drop(&mut self) {
+        unsafe {
+            raw::detector_cccf_destroy(self.inner);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DetectorCccf;
+    use num::complex::Complex32;
+
+    #[test]
+    fn test_detector_cccf_create() {
+        let s = vec![Complex32::new(1.0, 0.0); 16];
+        assert!(DetectorCccf::create(&[], 0.5, 0.1).is_err());
+        assert!(DetectorCccf::create(&s, 0.0, 0.1).is_err());
+        assert!(DetectorCccf::create(&s, 0.5, -0.1).is_err());
+        assert!(DetectorCccf::create(&s, 0.5, 0.1).is_ok());
+    }
+
+    #[test]
+    fn test_detector_cccf_detect() {
+        // pseudo-random QPSK sequence and low-level noise
+        let mut state = 7u32;
+        let mut rand = move || {
+            state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            (state >> 16) & 0x7fff
+        };
+        let n = 64;
+        let s: Vec<Complex32> = (0..n)
+            .map(|_| {
+                let b = rand();
+                Complex32::new(
+                    if b & 1 == 0 { 1.0 } else { -1.0 },
+                    if b & 2 == 0 { 1.0 } else { -1.0 },
+                ) * std::f32::consts::FRAC_1_SQRT_2
+            })
+            .collect();
+
+        // sequence embedded at offset 100 with a carrier offset
+        let offset = 100;
+        let dphi = 0.02f32;
+        let x: Vec<Complex32> = (0..300)
+            .map(|i| {
+                let noise =
+                    Complex32::new(rand() as f32 / 32768.0 - 0.5, rand() as f32 / 32768.0 - 0.5);
+                let v = if i >= offset && i < offset + n {
+                    s[i - offset]
+                } else {
+                    Complex32::default()
+                };
+                v * Complex32::from_polar(1.0, dphi * i as f32) + noise * 0.02
+            })
+            .collect();
+
+        let mut detector = DetectorCccf::create(&s, 0.5, 0.05).unwrap();
+        let detections = detector.execute_block(&x);
+        assert_eq!(detections.len(), 1);
+        let (i, out) = detections[0];
+        // the peak is reported once the last sequence sample is in
+        assert!(i >= offset + n - 1 && i <= offset + n + 1);
+        assert!(out.tau_hat.abs() < 0.3);
+        assert!((out.dphi_hat - dphi).abs() < 0.01);
+        assert!(out.gamma_hat > 0.5 && out.gamma_hat < 1.5);
+    }
+}
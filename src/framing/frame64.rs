@@ -0,0 +1,203 @@
+//! *frame64* : fixed size frame generator and synchronizer, carrying an
+//!           8-byte header and a 64-byte payload in a frame of 1440
+//!           samples (QPSK, 2 samples/symbol, Golay/CRC-24 protected)
+use num::complex::Complex32;
+use std::marker::PhantomData;
+
+use crate::liquid_dsp_sys as raw;
+use crate::utils::{ToCPointer, ToCPointerMut};
+
+use crate::callbacks::Callbacks;
+use crate::errors::LiquidError;
+use crate::framing::framesync::{framesync_callback_f, framesync_userdata};
+use crate::framing::{FrameDataStats, FrameStats};
+use crate::LiquidResult;
+
+/// header length [bytes]
+pub const FRAME64_HEADER_LEN: usize = 8;
+/// payload length [bytes]
+pub const FRAME64_PAYLOAD_LEN: usize = 64;
+/// frame length [samples]
+pub const FRAME64_LEN: usize = raw::LIQUID_FRAME64_LEN as usize;
+
+pub struct Framegen64 {
+    inner: raw::framegen64,
+}
+
+impl Framegen64 {
+    /// create frame generator
+    pub fn create() -> Self {
+        Self {
+            inner: unsafe { raw::framegen64_create() },
+        }
+    }
+
+    pub fn print(&self) {
+        unsafe {
+            raw::framegen64_print(self.inner);
+        }
+    }
+
+    /// generate a frame
+    ///  header     :   frame header [size: 8 x 1]
+    ///  payload    :   frame payload [size: 64 x 1]
+    ///  frame      :   output frame samples [size: 1440 x 1]
+    pub fn execute_into(
+        &mut self,
+        header: &[u8],
+        payload: &[u8],
+        frame: &mut [Complex32],
+    ) -> LiquidResult<()> {
+        check_len("header", header.len(), FRAME64_HEADER_LEN)?;
+        check_len("payload", payload.len(), FRAME64_PAYLOAD_LEN)?;
+        check_len("frame", frame.len(), FRAME64_LEN)?;
+        unsafe {
+            raw::framegen64_execute(
+                self.inner,
+                header.as_ptr() as *mut _,
+                payload.as_ptr() as *mut _,
+                frame.to_ptr_mut(),
+            );
+        }
+        Ok(())
+    }
+
+    /// generate a frame
+    ///  header     :   frame header [size: 8 x 1]
+    ///  payload    :   frame payload [size: 64 x 1]
+    /// # returns
+    /// the 1440 frame samples
+    pub fn execute(&mut self, header: &[u8], payload: &[u8]) -> LiquidResult<Vec<Complex32>> {
+        let mut frame = vec![Complex32::default(); FRAME64_LEN];
+        self.execute_into(header, payload, &mut frame)?;
+        Ok(frame)
+    }
+}
+
+impl Default for Framegen64 {
+    fn default() -> Self {
+        Self::create()
+    }
+}
+
+impl Drop for Framegen64 {
+    fn drop(&mut self) {
+        unsafe {
+            raw::framegen64_destroy(self.inner);
+        }
+    }
+}
+
+pub struct Framesync64<'a> {
+    inner: raw::framesync64,
+    callback: *mut Callbacks<'a>,
+    phantom: PhantomData<&'a ()>,
+}
+
+impl<'a> Framesync64<'a> {
+    /// create frame synchronizer
+    ///  callback   :   called on every detected frame with the header,
+    ///                 header validity, payload, payload validity and
+    ///                 the frame statistics
+    pub fn create<F>(callback: F) -> Self
+    where
+        F: FnMut(&[u8], bool, &[u8], bool, &FrameStats) + 'a,
+    {
+        let userdata = framesync_userdata(FRAME64_HEADER_LEN, callback);
+        Self {
+            inner: unsafe { raw::framesync64_create(Some(framesync_callback_f), userdata as _) },
+            callback: userdata,
+            phantom: PhantomData,
+        }
+    }
+
+    pub fn print(&self) {
+        unsafe {
+            raw::framesync64_print(self.inner);
+        }
+    }
+
+    pub fn reset(&mut self) {
+        unsafe {
+            raw::framesync64_reset(self.inner);
+        }
+    }
+
+    /// push samples through the synchronizer, invoking the callback on
+    /// every frame found
+    ///  x      :   input samples
+    pub fn execute(&mut self, x: &[Complex32]) {
+        unsafe {
+            raw::framesync64_execute(self.inner, x.to_ptr() as *mut _, x.len() as _);
+        }
+    }
+
+    /// frame counters accumulated since creation or the last
+    /// reset_framedatastats
+    pub fn get_framedatastats(&self) -> FrameDataStats {
+        unsafe { (*self.callback).framedatastats }
+    }
+
+    pub fn reset_framedatastats(&mut self) {
+        unsafe {
+            (*self.callback).framedatastats.reset();
+        }
+    }
+
+    pub fn debug_enable(&mut self) {
+        unsafe {
+            raw::framesync64_debug_enable(self.inner);
+        }
+    }
+
+    pub fn debug_disable(&mut self) {
+        unsafe {
+            raw::framesync64_debug_disable(self.inner);
+        }
+    }
+}
+
+impl<'a> Drop for Framesync64<'a> {
+    fn drop(&mut self) {
+        unsafe {
+            raw::framesync64_destroy(self.inner);
+            let _ = Box::from_raw(self.callback);
+        }
+    }
+}
+
+fn check_len(name: &str, len: usize, expected: usize) -> LiquidResult<()> {
+    if len != expected {
+        return Err(LiquidError::InvalidLength {
+            description: format!("{} length: {} valid length: {}", name, len, expected),
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Framegen64, Framesync64, FRAME64_LEN};
+    use num::complex::Complex32;
+
+    #[test]
+    fn test_frame64_loopback() {
+        let header: Vec<u8> = (0..8).collect();
+        let payload: Vec<u8> = (0..64).map(|i| (i * 3) as u8).collect();
+        let mut gen = Framegen64::create();
+        assert!(gen.execute(&header[..7], &payload).is_err());
+        let mut frame = gen.execute(&header, &payload).unwrap();
+        assert_eq!(frame.len(), FRAME64_LEN);
+        frame.extend(vec![Complex32::default(); 100]);
+
+        let mut frames = Vec::new();
+        {
+            let mut sync = Framesync64::create(|h, h_valid, p, p_valid, _stats| {
+                frames.push((h.to_vec(), h_valid, p.to_vec(), p_valid))
+            });
+            sync.execute(&frame);
+        }
+        assert_eq!(frames.len(), 1);
+        assert_eq!(frames[0], (header, true, payload, true));
+    }
+}
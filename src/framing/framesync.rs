@@ -0,0 +1,63 @@
+//! *framesync* : callback shared by the frame synchronizers, forwarding
+//!           liquid's framesync_callback to the user closure
+use libc::{c_int, c_uchar, c_uint, c_void};
+
+use crate::liquid_dsp_sys as raw;
+use crate::utils::catch;
+
+use crate::callbacks::Callbacks;
+use crate::framing::FrameStats;
+
+/// userdata of a frame synchronizer
+///  header_len :   length of the header handed to the callback [bytes]
+///  callback   :   user closure
+pub(crate) fn framesync_userdata<'a, F>(header_len: usize, callback: F) -> *mut Callbacks<'a>
+where
+    F: FnMut(&[u8], bool, &[u8], bool, &FrameStats) + 'a,
+{
+    Box::into_raw(Box::new(Callbacks {
+        framesync_callback: Some(Box::new(callback)),
+        framesync_header_len: header_len,
+        ..Default::default()
+    }))
+}
+
+/// the bytes liquid hands to the callback, which may be a null pointer,
+/// e.g. the payload of a frame whose header could not be decoded
+unsafe fn bytes<'a>(ptr: *const c_uchar, len: usize) -> &'a [u8] {
+    if ptr.is_null() || len == 0 {
+        return &[];
+    }
+    std::slice::from_raw_parts(ptr, len)
+}
+
+pub(crate) extern "C" fn framesync_callback_f(
+    header: *mut c_uchar,
+    header_valid: c_int,
+    payload: *mut c_uchar,
+    payload_len: c_uint,
+    payload_valid: c_int,
+    stats: raw::framesyncstats_s,
+    userdata: *mut c_void,
+) -> c_int {
+    catch(|| unsafe {
+        let callbacks = &mut *(userdata as *mut Callbacks);
+        let stats = FrameStats::from_raw(&stats, header_valid != 0, payload_valid != 0);
+        callbacks
+            .framedatastats
+            .update(&stats, payload_len as usize);
+        if let Some(fun) = callbacks.framesync_callback.as_mut() {
+            let header = bytes(header, callbacks.framesync_header_len);
+            let payload = bytes(payload, payload_len as usize);
+            fun(
+                header,
+                stats.header_valid,
+                payload,
+                stats.payload_valid,
+                &stats,
+            );
+        }
+        0
+    })
+    .unwrap()
+}
@@ -0,0 +1,18 @@
+pub use bsync::{BsyncCccf, BsyncCrcf, BsyncPeak, BsyncRrrf};
+pub use detector::{DetectorCccf, DetectorOutput};
+pub use frame64::{
+    Framegen64, Framesync64, FRAME64_HEADER_LEN, FRAME64_LEN, FRAME64_PAYLOAD_LEN,
+};
+pub use ofdmflexframe::{
+    OfdmFlexFrameGen, OfdmFlexFrameGenProps, OfdmFlexFrameSync, OFDMFLEXFRAME_HEADER_LEN,
+};
+pub use stats::{FrameDataStats, FrameStats};
+pub use symtrack::SymtrackCccf;
+
+mod bsync;
+mod detector;
+mod frame64;
+mod framesync;
+mod ofdmflexframe;
+mod stats;
+mod symtrack;
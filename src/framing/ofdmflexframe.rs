@@ -0,0 +1,525 @@
+//! *ofdmflexframe* : flexible OFDM frame generator and synchronizer, with
+//!           configurable subcarrier allocation, cyclic prefix and
+//!           taper, and per-frame payload properties (CRC, FEC and
+//!           modulation scheme)
+use num::complex::Complex32;
+use std::convert::TryFrom;
+use std::ffi::CString;
+use std::marker::PhantomData;
+
+use crate::liquid_dsp_sys as raw;
+use crate::utils::{ToCPointer, ToCPointerMut};
+
+use crate::callbacks::Callbacks;
+use crate::enums::{CrcScheme, FecScheme};
+use crate::errors::LiquidError;
+use crate::framing::framesync::{framesync_callback_f, framesync_userdata};
+use crate::framing::{FrameDataStats, FrameStats};
+use crate::modem::ModulationScheme;
+use crate::LiquidResult;
+
+/// default length of the user header [bytes]
+pub const OFDMFLEXFRAME_HEADER_LEN: usize = 8;
+
+/// payload properties of the frames generated by OfdmFlexFrameGen
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub struct OfdmFlexFrameGenProps {
+    /// data validity check
+    pub check: CrcScheme,
+    /// inner forward error-correction scheme
+    pub fec0: FecScheme,
+    /// outer forward error-correction scheme
+    pub fec1: FecScheme,
+    /// modulation scheme
+    pub mod_scheme: ModulationScheme,
+}
+
+impl OfdmFlexFrameGenProps {
+    fn check(&self) -> LiquidResult<()> {
+        self.fec0.check_supported()?;
+        self.fec1.check_supported()?;
+        match self.mod_scheme {
+            ModulationScheme::UNKNOWN | ModulationScheme::ARB => {
+                return Err(LiquidError::InvalidValue(format!(
+                    "invalid modulation scheme: {:?}",
+                    self.mod_scheme
+                )));
+            }
+            _ => {}
+        }
+        if self.check == CrcScheme::CRC_UNKNOWN {
+            return Err(LiquidError::InvalidValue(
+                "invalid crc scheme: CRC_UNKNOWN".to_owned(),
+            ));
+        }
+        Ok(())
+    }
+
+    fn to_raw(self) -> raw::ofdmflexframegenprops_s {
+        raw::ofdmflexframegenprops_s {
+            check: u8::from(self.check) as _,
+            fec0: u8::from(self.fec0) as _,
+            fec1: u8::from(self.fec1) as _,
+            mod_scheme: u8::from(self.mod_scheme) as _,
+        }
+    }
+}
+
+impl Default for OfdmFlexFrameGenProps {
+    /// liquid's defaults: CRC-32, no FEC, QPSK
+    fn default() -> Self {
+        Self {
+            check: CrcScheme::CRC_32,
+            fec0: FecScheme::NONE,
+            fec1: FecScheme::NONE,
+            mod_scheme: ModulationScheme::QPSK,
+        }
+    }
+}
+
+impl From<raw::ofdmflexframegenprops_s> for OfdmFlexFrameGenProps {
+    fn from(props: raw::ofdmflexframegenprops_s) -> Self {
+        Self {
+            check: CrcScheme::try_from(props.check as u8).unwrap_or(CrcScheme::CRC_UNKNOWN),
+            fec0: FecScheme::try_from(props.fec0 as u8).unwrap_or(FecScheme::UNKNOWN),
+            fec1: FecScheme::try_from(props.fec1 as u8).unwrap_or(FecScheme::UNKNOWN),
+            mod_scheme: ModulationScheme::try_from(props.mod_scheme as u8)
+                .unwrap_or(ModulationScheme::UNKNOWN),
+        }
+    }
+}
+
+/// validate the OFDM parameters shared by the generator and synchronizer
+fn check_ofdm_params(m: u32, cp_len: u32, taper_len: u32, p: Option<&[u8]>) -> LiquidResult<()> {
+    if m < 2 || !m.is_multiple_of(2) {
+        return Err(LiquidError::InvalidValue(format!(
+            "number of subcarriers: {} must be even and at least 2",
+            m
+        )));
+    } else if cp_len > m {
+        return Err(LiquidError::InvalidValue(format!(
+            "cyclic prefix length: {} cannot exceed the number of subcarriers: {}",
+            cp_len, m
+        )));
+    } else if taper_len > cp_len {
+        return Err(LiquidError::InvalidValue(format!(
+            "taper length: {} cannot exceed the cyclic prefix length: {}",
+            taper_len, cp_len
+        )));
+    }
+    if let Some(p) = p {
+        if p.len() != m as usize {
+            return Err(LiquidError::InvalidLength {
+                description: format!(
+                    "subcarrier allocation length: {} valid length: {}",
+                    p.len(),
+                    m
+                ),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// subcarrier allocation pointer handed to liquid, NULL for the default
+fn allocation_ptr(p: Option<&[u8]>) -> *mut u8 {
+    p.map_or(std::ptr::null_mut(), |p| p.as_ptr() as *mut _)
+}
+
+pub struct OfdmFlexFrameGen {
+    inner: raw::ofdmflexframegen,
+    m: u32,
+    cp_len: u32,
+    header_len: usize,
+}
+
+impl OfdmFlexFrameGen {
+    /// create OFDM flexible frame generator
+    ///  m          :   number of subcarriers, even and m >= 2
+    ///  cp_len     :   cyclic prefix length, cp_len <= m
+    ///  taper_len  :   taper length (OFDM symbol overlap), taper_len <= cp_len
+    ///  p          :   subcarrier allocation (null, pilot, data) [size: m x 1],
+    ///                 None for liquid's default allocation
+    ///  props      :   payload properties
+    pub fn create(
+        m: u32,
+        cp_len: u32,
+        taper_len: u32,
+        p: Option<&[u8]>,
+        props: &OfdmFlexFrameGenProps,
+    ) -> LiquidResult<Self> {
+        check_ofdm_params(m, cp_len, taper_len, p)?;
+        props.check()?;
+        let mut fgprops = props.to_raw();
+        Ok(Self {
+            inner: unsafe {
+                raw::ofdmflexframegen_create(
+                    m as _,
+                    cp_len as _,
+                    taper_len as _,
+                    allocation_ptr(p),
+                    &mut fgprops,
+                )
+            },
+            m,
+            cp_len,
+            header_len: OFDMFLEXFRAME_HEADER_LEN,
+        })
+    }
+
+    pub fn print(&self) {
+        unsafe {
+            raw::ofdmflexframegen_print(self.inner);
+        }
+    }
+
+    pub fn reset(&mut self) {
+        unsafe {
+            raw::ofdmflexframegen_reset(self.inner);
+        }
+    }
+
+    /// a frame has been assembled and not completely written yet
+    pub fn is_assembled(&self) -> bool {
+        unsafe { raw::ofdmflexframegen_is_assembled(self.inner) == 1 }
+    }
+
+    pub fn get_props(&self) -> OfdmFlexFrameGenProps {
+        let mut props = OfdmFlexFrameGenProps::default().to_raw();
+        unsafe {
+            raw::ofdmflexframegen_getprops(self.inner, &mut props);
+        }
+        props.into()
+    }
+
+    /// set the payload properties of the next assembled frame
+    pub fn set_props(&mut self, props: &OfdmFlexFrameGenProps) -> LiquidResult<()> {
+        props.check()?;
+        let mut fgprops = props.to_raw();
+        unsafe {
+            raw::ofdmflexframegen_setprops(self.inner, &mut fgprops);
+        }
+        Ok(())
+    }
+
+    /// set the length of the user header, the synchronizer must be set
+    /// to the same length
+    pub fn set_header_len(&mut self, len: usize) {
+        unsafe {
+            raw::ofdmflexframegen_set_header_len(self.inner, len as _);
+        }
+        self.header_len = len;
+    }
+
+    pub fn get_header_len(&self) -> usize {
+        self.header_len
+    }
+
+    /// set the properties protecting the header, the synchronizer must be
+    /// set to the same properties
+    pub fn set_header_props(&mut self, props: &OfdmFlexFrameGenProps) -> LiquidResult<()> {
+        props.check()?;
+        let mut fgprops = props.to_raw();
+        unsafe {
+            raw::ofdmflexframegen_set_header_props(self.inner, &mut fgprops);
+        }
+        Ok(())
+    }
+
+    /// length of the assembled frame [OFDM symbols]
+    pub fn get_frame_len(&self) -> usize {
+        unsafe { raw::ofdmflexframegen_getframelen(self.inner) as usize }
+    }
+
+    /// number of samples of an OFDM symbol, m + cp_len
+    pub fn get_symbol_len(&self) -> usize {
+        (self.m + self.cp_len) as usize
+    }
+
+    /// assemble a frame from the header and payload
+    ///  header     :   user header [size: header_len x 1]
+    ///  payload    :   payload
+    pub fn assemble(&mut self, header: &[u8], payload: &[u8]) -> LiquidResult<()> {
+        if header.len() != self.header_len {
+            return Err(LiquidError::InvalidLength {
+                description: format!(
+                    "header length: {} valid length: {}",
+                    header.len(),
+                    self.header_len
+                ),
+            });
+        }
+        unsafe {
+            raw::ofdmflexframegen_assemble(
+                self.inner,
+                header.as_ptr(),
+                payload.as_ptr(),
+                payload.len() as _,
+            );
+        }
+        Ok(())
+    }
+
+    /// write samples of the assembled frame, zero-padding once the frame
+    /// is complete
+    ///  buf    :   output samples
+    /// # returns
+    /// true once the last sample of the frame has been written
+    pub fn write(&mut self, buf: &mut [Complex32]) -> bool {
+        unsafe { raw::ofdmflexframegen_write(self.inner, buf.to_ptr_mut(), buf.len() as _) == 1 }
+    }
+
+    /// write the whole assembled frame, one OFDM symbol at a time
+    /// # returns
+    /// the frame samples
+    pub fn write_frame(&mut self) -> LiquidResult<Vec<Complex32>> {
+        if !self.is_assembled() {
+            return Err(LiquidError::InvalidValue(
+                "no frame has been assembled".to_owned(),
+            ));
+        }
+        let symbol_len = self.get_symbol_len();
+        let mut frame = Vec::with_capacity(self.get_frame_len() * symbol_len);
+        let mut buf = vec![Complex32::default(); symbol_len];
+        loop {
+            let last = self.write(&mut buf);
+            frame.extend_from_slice(&buf);
+            if last {
+                return Ok(frame);
+            }
+        }
+    }
+}
+
+impl Drop for OfdmFlexFrameGen {
+    fn drop(&mut self) {
+        unsafe {
+            raw::ofdmflexframegen_destroy(self.inner);
+        }
+    }
+}
+
+pub struct OfdmFlexFrameSync<'a> {
+    inner: raw::ofdmflexframesync,
+    callback: *mut Callbacks<'a>,
+    phantom: PhantomData<&'a ()>,
+}
+
+impl<'a> OfdmFlexFrameSync<'a> {
+    /// create OFDM flexible frame synchronizer
+    ///  m          :   number of subcarriers, even and m >= 2
+    ///  cp_len     :   cyclic prefix length, cp_len <= m
+    ///  taper_len  :   taper length (OFDM symbol overlap), taper_len <= cp_len
+    ///  p          :   subcarrier allocation (null, pilot, data) [size: m x 1],
+    ///                 None for liquid's default allocation
+    ///  callback   :   called on every detected frame with the header,
+    ///                 header validity, payload, payload validity and
+    ///                 the frame statistics
+    pub fn create<F>(
+        m: u32,
+        cp_len: u32,
+        taper_len: u32,
+        p: Option<&[u8]>,
+        callback: F,
+    ) -> LiquidResult<Self>
+    where
+        F: FnMut(&[u8], bool, &[u8], bool, &FrameStats) + 'a,
+    {
+        check_ofdm_params(m, cp_len, taper_len, p)?;
+        let userdata = framesync_userdata(OFDMFLEXFRAME_HEADER_LEN, callback);
+        Ok(Self {
+            inner: unsafe {
+                raw::ofdmflexframesync_create(
+                    m as _,
+                    cp_len as _,
+                    taper_len as _,
+                    allocation_ptr(p),
+                    Some(framesync_callback_f),
+                    userdata as _,
+                )
+            },
+            callback: userdata,
+            phantom: PhantomData,
+        })
+    }
+
+    pub fn print(&self) {
+        unsafe {
+            raw::ofdmflexframesync_print(self.inner);
+        }
+    }
+
+    pub fn reset(&mut self) {
+        unsafe {
+            raw::ofdmflexframesync_reset(self.inner);
+        }
+    }
+
+    /// set the length of the user header, must match the generator
+    pub fn set_header_len(&mut self, len: usize) {
+        unsafe {
+            raw::ofdmflexframesync_set_header_len(self.inner, len as _);
+            (*self.callback).framesync_header_len = len;
+        }
+    }
+
+    /// set the properties protecting the header, must match the generator
+    pub fn set_header_props(&mut self, props: &OfdmFlexFrameGenProps) -> LiquidResult<()> {
+        props.check()?;
+        let mut fgprops = props.to_raw();
+        unsafe {
+            raw::ofdmflexframesync_set_header_props(self.inner, &mut fgprops);
+        }
+        Ok(())
+    }
+
+    /// enable or disable soft decoding of the header
+    pub fn decode_header_soft(&mut self, soft: bool) {
+        unsafe {
+            raw::ofdmflexframesync_decode_header_soft(self.inner, soft as _);
+        }
+    }
+
+    /// enable or disable soft decoding of the payload
+    pub fn decode_payload_soft(&mut self, soft: bool) {
+        unsafe {
+            raw::ofdmflexframesync_decode_payload_soft(self.inner, soft as _);
+        }
+    }
+
+    /// the synchronizer is in the middle of receiving a frame
+    pub fn is_frame_open(&self) -> bool {
+        unsafe { raw::ofdmflexframesync_is_frame_open(self.inner) == 1 }
+    }
+
+    /// push samples through the synchronizer, invoking the callback on
+    /// every frame found
+    ///  x      :   input samples
+    pub fn execute(&mut self, x: &[Complex32]) {
+        unsafe {
+            raw::ofdmflexframesync_execute(self.inner, x.to_ptr() as *mut _, x.len() as _);
+        }
+    }
+
+    /// received signal strength indicator [dB]
+    pub fn get_rssi(&self) -> f32 {
+        unsafe { raw::ofdmflexframesync_get_rssi(self.inner) }
+    }
+
+    /// carrier frequency offset estimate [radians/sample]
+    pub fn get_cfo(&self) -> f32 {
+        unsafe { raw::ofdmflexframesync_get_cfo(self.inner) }
+    }
+
+    pub fn set_cfo(&mut self, cfo: f32) {
+        unsafe {
+            raw::ofdmflexframesync_set_cfo(self.inner, cfo);
+        }
+    }
+
+    /// frame counters accumulated since creation or the last
+    /// reset_framedatastats
+    pub fn get_framedatastats(&self) -> FrameDataStats {
+        unsafe { (*self.callback).framedatastats }
+    }
+
+    pub fn reset_framedatastats(&mut self) {
+        unsafe {
+            (*self.callback).framedatastats.reset();
+        }
+    }
+
+    pub fn debug_enable(&mut self) {
+        unsafe {
+            raw::ofdmflexframesync_debug_enable(self.inner);
+        }
+    }
+
+    pub fn debug_disable(&mut self) {
+        unsafe {
+            raw::ofdmflexframesync_debug_disable(self.inner);
+        }
+    }
+
+    /// write the debugging information to an octave script
+    ///  filename   :   output file name
+    pub fn debug_print(&self, filename: &str) -> LiquidResult<()> {
+        let c = CString::new(filename)
+            .map_err(|_| LiquidError::InvalidValue(format!("invalid file name: {}", filename)))?;
+        unsafe {
+            raw::ofdmflexframesync_debug_print(self.inner, c.as_ptr());
+        }
+        Ok(())
+    }
+}
+
+impl<'a> Drop for OfdmFlexFrameSync<'a> {
+    fn drop(&mut self) {
+        unsafe {
+            raw::ofdmflexframesync_destroy(self.inner);
+            let _ = Box::from_raw(self.callback);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{OfdmFlexFrameGen, OfdmFlexFrameGenProps, OfdmFlexFrameSync};
+    use num::complex::Complex32;
+
+    #[test]
+    fn test_ofdmflexframe_loopback() {
+        let props = OfdmFlexFrameGenProps::default();
+        assert!(OfdmFlexFrameGen::create(63, 16, 4, None, &props).is_err());
+        assert!(OfdmFlexFrameGen::create(64, 16, 32, None, &props).is_err());
+
+        let mut gen = OfdmFlexFrameGen::create(64, 16, 4, None, &props).unwrap();
+        let header = [1u8, 2, 3, 4, 5, 6, 7, 8];
+        let payload: Vec<u8> = (0..120).collect();
+        assert!(gen.assemble(&header[..4], &payload).is_err());
+        gen.assemble(&header, &payload).unwrap();
+        let mut frame = gen.write_frame().unwrap();
+        assert_eq!(frame.len() % gen.get_symbol_len(), 0);
+        frame.extend(vec![Complex32::default(); 2 * gen.get_symbol_len()]);
+
+        let mut frames = Vec::new();
+        let mut sync = OfdmFlexFrameSync::create(64, 16, 4, None, |h, h_valid, p, p_valid, _| {
+            frames.push((h.to_vec(), h_valid, p.to_vec(), p_valid))
+        })
+        .unwrap();
+        sync.execute(&frame);
+        assert_eq!(sync.get_framedatastats().num_payloads_valid, 1);
+        drop(sync);
+        assert_eq!(frames, vec![(header.to_vec(), true, payload, true)]);
+    }
+
+    #[test]
+    fn test_ofdmflexframe_corrupted_header() {
+        let props = OfdmFlexFrameGenProps::default();
+        let mut gen = OfdmFlexFrameGen::create(64, 16, 4, None, &props).unwrap();
+        let payload: Vec<u8> = (0..120).collect();
+        gen.assemble(&[1u8, 2, 3, 4, 5, 6, 7, 8], &payload).unwrap();
+        let mut frame = gen.write_frame().unwrap();
+        let n = gen.get_symbol_len();
+        frame.extend(vec![Complex32::default(); 2 * n]);
+
+        // scramble the phase of the first header symbol, after the
+        // S0a, S0b and S1 preamble symbols
+        let mut state = 5u32;
+        for v in frame[3 * n..4 * n].iter_mut() {
+            state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            *v *= Complex32::from_polar(1.0, (state >> 16) as f32);
+        }
+
+        let mut frames = Vec::new();
+        let mut sync = OfdmFlexFrameSync::create(64, 16, 4, None, |h, h_valid, p, p_valid, _| {
+            frames.push((h.len(), h_valid, p.to_vec(), p_valid))
+        })
+        .unwrap();
+        sync.execute(&frame);
+        assert_eq!(sync.get_framedatastats().num_payloads_valid, 0);
+        drop(sync);
+        // no payload is handed over without a valid header
+        assert_eq!(frames, vec![(8, false, Vec::new(), false)]);
+    }
+}
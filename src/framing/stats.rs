@@ -0,0 +1,175 @@
+//! *stats* : statistics reported by the frame synchronizers along with
+//!           each received frame, and their accumulation over many frames
+use num::complex::Complex32;
+use std::convert::TryFrom;
+
+use crate::enums::{CrcScheme, FecScheme};
+use crate::liquid_dsp_sys as raw;
+use crate::modem::ModulationScheme;
+
+/// frame statistics reported to the synchronizer callback
+#[derive(Clone, Debug, PartialEq)]
+pub struct FrameStats {
+    /// error vector magnitude [dB]
+    pub evm: f32,
+    /// received signal strength indicator [dB]
+    pub rssi: f32,
+    /// carrier frequency offset [f/Fs]
+    pub cfo: f32,
+    /// demodulated payload symbols
+    pub framesyms: Vec<Complex32>,
+    /// payload modulation scheme
+    pub mod_scheme: ModulationScheme,
+    /// payload bits per symbol
+    pub mod_bps: u32,
+    /// payload error-detection scheme
+    pub check: CrcScheme,
+    /// payload inner forward error-correction scheme
+    pub fec0: FecScheme,
+    /// payload outer forward error-correction scheme
+    pub fec1: FecScheme,
+    /// the header passed its error-detection check
+    pub header_valid: bool,
+    /// the payload passed its error-detection check
+    pub payload_valid: bool,
+}
+
+impl FrameStats {
+    /// copy the statistics handed to a liquid callback; the frame symbols
+    /// are only valid for the duration of the callback
+    pub(crate) unsafe fn from_raw(
+        stats: &raw::framesyncstats_s,
+        header_valid: bool,
+        payload_valid: bool,
+    ) -> Self {
+        let framesyms = if stats.framesyms.is_null() {
+            Vec::new()
+        } else {
+            std::slice::from_raw_parts(
+                stats.framesyms as *const Complex32,
+                stats.num_framesyms as usize,
+            )
+            .to_vec()
+        };
+        let mut q = Self::from(*stats);
+        q.framesyms = framesyms;
+        q.header_valid = header_valid;
+        q.payload_valid = payload_valid;
+        q
+    }
+}
+
+impl From<raw::framesyncstats_s> for FrameStats {
+    /// scalar statistics only, the frame symbols are not copied and the
+    /// validity flags, which liquid reports separately, are cleared
+    fn from(stats: raw::framesyncstats_s) -> Self {
+        Self {
+            evm: stats.evm,
+            rssi: stats.rssi,
+            cfo: stats.cfo,
+            framesyms: Vec::new(),
+            mod_scheme: ModulationScheme::try_from(stats.mod_scheme as u8)
+                .unwrap_or(ModulationScheme::UNKNOWN),
+            mod_bps: stats.mod_bps,
+            check: CrcScheme::try_from(stats.check as u8).unwrap_or(CrcScheme::CRC_UNKNOWN),
+            fec0: FecScheme::try_from(stats.fec0 as u8).unwrap_or(FecScheme::UNKNOWN),
+            fec1: FecScheme::try_from(stats.fec1 as u8).unwrap_or(FecScheme::UNKNOWN),
+            header_valid: false,
+            payload_valid: false,
+        }
+    }
+}
+
+/// frame counters accumulated by a synchronizer over its lifetime
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct FrameDataStats {
+    pub num_frames_detected: u32,
+    pub num_headers_valid: u32,
+    pub num_payloads_valid: u32,
+    /// bytes of the valid payloads
+    pub num_bytes_received: u64,
+}
+
+impl FrameDataStats {
+    /// account for a received frame
+    ///  stats          :   statistics of the frame
+    ///  payload_len    :   payload length [bytes]
+    pub fn update(&mut self, stats: &FrameStats, payload_len: usize) {
+        self.num_frames_detected += 1;
+        if stats.header_valid {
+            self.num_headers_valid += 1;
+        }
+        if stats.payload_valid {
+            self.num_payloads_valid += 1;
+            self.num_bytes_received += payload_len as u64;
+        }
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// fraction of the detected frames with a valid payload
+    pub fn payload_success_rate(&self) -> f32 {
+        if self.num_frames_detected == 0 {
+            return 0f32;
+        }
+        self.num_payloads_valid as f32 / self.num_frames_detected as f32
+    }
+
+    pub fn print(&self) {
+        println!("  frames detected     : {}", self.num_frames_detected);
+        println!("  headers valid       : {}", self.num_headers_valid);
+        println!("  payloads valid      : {}", self.num_payloads_valid);
+        println!("  bytes received      : {}", self.num_bytes_received);
+    }
+}
+
+impl From<raw::framedatastats_s> for FrameDataStats {
+    fn from(stats: raw::framedatastats_s) -> Self {
+        Self {
+            num_frames_detected: stats.num_frames_detected,
+            num_headers_valid: stats.num_headers_valid,
+            num_payloads_valid: stats.num_payloads_valid,
+            num_bytes_received: stats.num_bytes_received,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FrameDataStats, FrameStats};
+    use crate::enums::{CrcScheme, FecScheme};
+    use crate::liquid_dsp_sys as raw;
+    use crate::modem::ModulationScheme;
+
+    #[test]
+    fn test_frame_stats_accumulate() {
+        let raw_stats = raw::framesyncstats_s {
+            evm: -20.0,
+            rssi: -3.0,
+            cfo: 0.01,
+            framesyms: std::ptr::null_mut(),
+            num_framesyms: 0,
+            mod_scheme: u8::from(ModulationScheme::QPSK) as _,
+            mod_bps: 2,
+            check: u8::from(CrcScheme::CRC_24) as _,
+            fec0: u8::from(FecScheme::GOLAY2412) as _,
+            fec1: u8::from(FecScheme::NONE) as _,
+        };
+        let mut stats = unsafe { FrameStats::from_raw(&raw_stats, true, true) };
+        assert_eq!(stats.mod_scheme, ModulationScheme::QPSK);
+        assert_eq!(stats.check, CrcScheme::CRC_24);
+        assert_eq!(stats.fec0, FecScheme::GOLAY2412);
+
+        let mut acc = FrameDataStats::default();
+        acc.update(&stats, 64);
+        stats.payload_valid = false;
+        acc.update(&stats, 64);
+        assert_eq!(acc.num_frames_detected, 2);
+        assert_eq!(acc.num_headers_valid, 2);
+        assert_eq!(acc.num_payloads_valid, 1);
+        assert_eq!(acc.num_bytes_received, 64);
+        assert_eq!(acc.payload_success_rate(), 0.5);
+    }
+}
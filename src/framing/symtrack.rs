@@ -0,0 +1,226 @@
+//! *symtrack* : symbol tracker, recovers the symbols of a linearly
+//!           modulated signal with automatic gain control, matched
+//!           filter, timing recovery, equalization and carrier recovery
+use num::complex::Complex32;
+
+use crate::filter::FirdesFilterType;
+use crate::liquid_dsp_sys as raw;
+use crate::modem::ModulationScheme;
+use crate::utils::{ToCPointerMut, ToCValue};
+
+use crate::errors::LiquidError;
+use crate::LiquidResult;
+
+/// size of liquid's internal buffer of symbols produced by one sample
+const MAX_SYMBOLS_PER_SAMPLE: usize = 8;
+
+pub struct SymtrackCccf {
+    inner: raw::symtrack_cccf,
+}
+
+impl SymtrackCccf {
+    /// create symbol tracker
+    ///  ftype  :   filter type (e.g. FirdesFilterType::Rrc)
+    ///  k      :   samples/symbol, k >= 2
+    ///  m      :   filter delay [symbols], m > 0
+    ///  beta   :   filter excess bandwidth factor, in (0, 1]
+    ///  ms     :   modulation scheme
+    pub fn create(
+        ftype: FirdesFilterType,
+        k: u32,
+        m: u32,
+        beta: f32,
+        ms: ModulationScheme,
+    ) -> LiquidResult<Self> {
+        if ftype == FirdesFilterType::Unknown {
+            return Err(LiquidError::InvalidValue("unknown filter type".to_owned()));
+        } else if k < 2 {
+            return Err(LiquidError::InvalidValue(
+                "samples/symbol must be at least 2".to_owned(),
+            ));
+        } else if m == 0 {
+            return Err(LiquidError::InvalidValue(
+                "filter delay must be greater than zero".to_owned(),
+            ));
+        } else if beta <= 0f32 || beta > 1f32 {
+            return Err(LiquidError::InvalidValue(
+                "filter excess bandwidth factor must be in (0,1]".to_owned(),
+            ));
+        } else if ms == ModulationScheme::UNKNOWN {
+            return Err(LiquidError::InvalidValue(
+                "unknown modulation scheme".to_owned(),
+            ));
+        }
+        let t: u8 = ftype.into();
+        let ms: u8 = ms.into();
+        Ok(Self {
+            inner: unsafe { raw::symtrack_cccf_create(t as _, k, m, beta, ms as _) },
+        })
+    }
+
+    /// create symbol tracker with default parameters
+    pub fn create_default() -> Self {
+        Self {
+            inner: unsafe { raw::symtrack_cccf_create_default() },
+        }
+    }
+
+    pub fn print(&self) {
+        unsafe {
+            raw::symtrack_cccf_print(self.inner);
+        }
+    }
+
+    pub fn reset(&mut self) {
+        unsafe {
+            raw::symtrack_cccf_reset(self.inner);
+        }
+    }
+
+    /// set modulation scheme
+    pub fn set_modscheme(&mut self, ms: ModulationScheme) -> LiquidResult<()> {
+        if ms == ModulationScheme::UNKNOWN {
+            return Err(LiquidError::InvalidValue(
+                "unknown modulation scheme".to_owned(),
+            ));
+        }
+        let ms: u8 = ms.into();
+        unsafe {
+            raw::symtrack_cccf_set_modscheme(self.inner, ms as _);
+        }
+        Ok(())
+    }
+
+    /// set bandwidth of the tracking loops
+    ///  bw     :   bandwidth, bw >= 0
+    pub fn set_bandwidth(&mut self, bw: f32) -> LiquidResult<()> {
+        if bw < 0f32 {
+            return Err(LiquidError::InvalidValue(
+                "bandwidth must not be negative".to_owned(),
+            ));
+        }
+        unsafe {
+            raw::symtrack_cccf_set_bandwidth(self.inner, bw);
+        }
+        Ok(())
+    }
+
+    /// adjust internal NCO by the requested phase
+    ///  dphi   :   phase adjustment [radians]
+    pub fn adjust_phase(&mut self, dphi: f32) {
+        unsafe {
+            raw::symtrack_cccf_adjust_phase(self.inner, dphi);
+        }
+    }
+
+    /// run the tracker on a sample, appending the recovered symbols to y
+    pub fn execute(&mut self, x: Complex32, y: &mut Vec<Complex32>) {
+        let mut buf = [Complex32::default(); MAX_SYMBOLS_PER_SAMPLE];
+        let mut ny = 0u32;
+        unsafe {
+            raw::symtrack_cccf_execute(self.inner, x.to_c_value(), buf.to_ptr_mut(), &mut ny);
+        }
+        y.extend_from_slice(&buf[..ny as usize]);
+    }
+
+    /// run the tracker on a block of samples, appending the recovered
+    /// symbols to y
+    pub fn execute_block_into(&mut self, x: &[Complex32], y: &mut Vec<Complex32>) {
+        for v in x {
+            self.execute(*v, y);
+        }
+    }
+
+    /// run the tracker on a block of samples
+    /// # returns
+    /// the recovered symbols
+    pub fn execute_block(&mut self, x: &[Complex32]) -> Vec<Complex32> {
+        let mut y = Vec::new();
+        self.execute_block_into(x, &mut y);
+        y
+    }
+}
+
+impl Drop for SymtrackCccf {
+    fn drop(&mut self) {
+        unsafe {
+            raw::symtrack_cccf_destroy(self.inner);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SymtrackCccf;
+    use crate::filter::{FirInterpCrcf, FirdesFilterType};
+    use crate::modem::{Modem, ModulationScheme, Slicer};
+    use num::complex::Complex32;
+
+    #[test]
+    fn test_symtrack_cccf_create() {
+        let rrc = FirdesFilterType::Rrc;
+        let qpsk = ModulationScheme::QPSK;
+        assert!(SymtrackCccf::create(FirdesFilterType::Unknown, 2, 7, 0.3, qpsk).is_err());
+        assert!(SymtrackCccf::create(rrc, 1, 7, 0.3, qpsk).is_err());
+        assert!(SymtrackCccf::create(rrc, 2, 0, 0.3, qpsk).is_err());
+        assert!(SymtrackCccf::create(rrc, 2, 7, 0.0, qpsk).is_err());
+        assert!(SymtrackCccf::create(rrc, 2, 7, 0.3, ModulationScheme::UNKNOWN).is_err());
+        assert!(SymtrackCccf::create(rrc, 2, 7, 0.3, qpsk).is_ok());
+    }
+
+    #[test]
+    fn test_symtrack_cccf_recover_qpsk() {
+        let (k, m, beta) = (2, 7, 0.3);
+        let mut modem = Modem::create(ModulationScheme::QPSK).unwrap();
+        let mut state = 3u32;
+        let tx: Vec<Complex32> = (0..1200)
+            .map(|_| {
+                state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+                modem.modulate((state >> 16) & 3).unwrap()
+            })
+            .collect();
+
+        // RRC-shaped signal with a gain and a phase offset
+        let mut interp =
+            FirInterpCrcf::create_prototype(FirdesFilterType::Rrc, k, m, beta, 0.0).unwrap();
+        let mut x = vec![Complex32::default(); tx.len() * k as usize];
+        interp.execute_block(&tx, &mut x);
+        let g = Complex32::from_polar(0.5, 0.7);
+        x.iter_mut().for_each(|v| *v *= g);
+
+        let mut symtrack =
+            SymtrackCccf::create(FirdesFilterType::Rrc, k, m, beta, ModulationScheme::QPSK)
+                .unwrap();
+        let y = symtrack.execute_block(&x);
+        assert!(y.len() > 1000);
+
+        // once locked, the decisions are the transmitted symbols up to the
+        // filters delay and the QPSK phase ambiguity
+        let mut slicer = Slicer::create(ModulationScheme::QPSK).unwrap();
+        let tail = y.len() - 200..y.len();
+        let decisions: Vec<Complex32> = y[tail.clone()].iter().map(|v| slicer.decide(*v)).collect();
+        let rotations = [
+            Complex32::new(1.0, 0.0),
+            Complex32::new(0.0, 1.0),
+            Complex32::new(-1.0, 0.0),
+            Complex32::new(0.0, -1.0),
+        ];
+        let recovered = (0..4 * m as usize).any(|d| {
+            rotations.iter().any(|r| {
+                decisions
+                    .iter()
+                    .zip(tail.clone())
+                    .all(|(v, j)| tx.get(j - d).is_some_and(|t| (v - t * r).norm() < 1e-3))
+            })
+        });
+        assert!(recovered);
+        // and the symbols sit on the constellation
+        let evm = y[tail.clone()]
+            .iter()
+            .zip(decisions.iter())
+            .map(|(a, b)| (a - b).norm_sqr())
+            .sum::<f32>()
+            / 200.0;
+        assert!(evm < 0.05);
+    }
+}
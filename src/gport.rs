@@ -0,0 +1,358 @@
+//! *gport* : thread-safe single producer, single consumer sample queue
+//!
+//! Mirrors liquid's gport object, which is not part of the bindings: a
+//! fixed size buffer shared between a producer (e.g. a capture thread) and
+//! a consumer (e.g. a DSP thread), with blocking and non-blocking
+//! transfers of whole slices. Dropping either end disconnects the queue;
+//! the consumer can still drain whatever the producer left behind.
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use crate::errors::LiquidError;
+use crate::LiquidResult;
+
+struct State<T> {
+    buffer: VecDeque<T>,
+    disconnected: bool,
+}
+
+struct Shared<T> {
+    capacity: usize,
+    state: Mutex<State<T>>,
+    // signaled when samples are pushed or the producer goes away
+    readable: Condvar,
+    // signaled when samples are popped or the consumer goes away
+    writable: Condvar,
+}
+
+impl<T> Shared<T> {
+    fn lock(&self) -> MutexGuard<'_, State<T>> {
+        // neither end panics while holding the lock
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn disconnect(&self) {
+        self.lock().disconnected = true;
+        self.readable.notify_all();
+        self.writable.notify_all();
+    }
+}
+
+/// create a queue holding up to *capacity* samples
+/// # returns
+/// the producer and consumer ends of the queue
+pub fn channel<T: Copy>(capacity: usize) -> LiquidResult<(GportProducer<T>, GportConsumer<T>)> {
+    if capacity == 0 {
+        return Err(LiquidError::InvalidValue(
+            "queue capacity must be greater than 0".to_owned(),
+        ));
+    }
+    let shared = Arc::new(Shared {
+        capacity,
+        state: Mutex::new(State {
+            buffer: VecDeque::with_capacity(capacity),
+            disconnected: false,
+        }),
+        readable: Condvar::new(),
+        writable: Condvar::new(),
+    });
+    Ok((
+        GportProducer {
+            shared: shared.clone(),
+        },
+        GportConsumer { shared },
+    ))
+}
+
+/// writing end of a queue created with channel()
+pub struct GportProducer<T> {
+    shared: Arc<Shared<T>>,
+}
+
+/// reading end of a queue created with channel()
+pub struct GportConsumer<T> {
+    shared: Arc<Shared<T>>,
+}
+
+impl<T: Copy> GportProducer<T> {
+    /// maximum number of samples the queue can hold
+    pub fn capacity(&self) -> usize {
+        self.shared.capacity
+    }
+
+    /// number of samples waiting to be read
+    pub fn len(&self) -> usize {
+        self.shared.lock().buffer.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// true once the consumer has been dropped
+    pub fn is_disconnected(&self) -> bool {
+        self.shared.lock().disconnected
+    }
+
+    /// write as many samples as currently fit, without blocking
+    /// # returns
+    /// the number of samples written
+    pub fn try_push(&self, x: &[T]) -> LiquidResult<usize> {
+        let mut state = self.shared.lock();
+        if state.disconnected {
+            return Err(LiquidError::Disconnected);
+        }
+        let n = self.write(&mut state, x);
+        Ok(n)
+    }
+
+    /// write all the samples, blocking while the queue is full
+    /// # returns
+    /// LiquidError::Disconnected if the consumer goes away before
+    /// everything is written
+    pub fn push(&self, x: &[T]) -> LiquidResult<()> {
+        self.push_until(x, None).map(|_| ())
+    }
+
+    /// write the samples, blocking at most *timeout* while the queue is full
+    /// # returns
+    /// the number of samples written before the timeout expired
+    pub fn push_timeout(&self, x: &[T], timeout: Duration) -> LiquidResult<usize> {
+        self.push_until(x, Some(Instant::now() + timeout))
+    }
+
+    fn push_until(&self, mut x: &[T], deadline: Option<Instant>) -> LiquidResult<usize> {
+        let total = x.len();
+        let mut state = self.shared.lock();
+        loop {
+            if state.disconnected {
+                return Err(LiquidError::Disconnected);
+            }
+            let n = self.write(&mut state, x);
+            x = &x[n..];
+            if x.is_empty() {
+                return Ok(total);
+            }
+            state = match wait(&self.shared.writable, state, deadline) {
+                Some(state) => state,
+                None => return Ok(total - x.len()),
+            };
+        }
+    }
+
+    fn write(&self, state: &mut State<T>, x: &[T]) -> usize {
+        let n = x.len().min(self.shared.capacity - state.buffer.len());
+        if n > 0 {
+            state.buffer.extend(&x[..n]);
+            self.shared.readable.notify_one();
+        }
+        n
+    }
+}
+
+impl<T: Copy> GportConsumer<T> {
+    /// maximum number of samples the queue can hold
+    pub fn capacity(&self) -> usize {
+        self.shared.capacity
+    }
+
+    /// number of samples waiting to be read
+    pub fn len(&self) -> usize {
+        self.shared.lock().buffer.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// true once the producer has been dropped; samples it left behind
+    /// can still be read
+    pub fn is_disconnected(&self) -> bool {
+        self.shared.lock().disconnected
+    }
+
+    /// read as many samples as are currently available, without blocking
+    /// # returns
+    /// the number of samples read into *y*
+    pub fn try_pop(&self, y: &mut [T]) -> usize {
+        let mut state = self.shared.lock();
+        self.read(&mut state, y)
+    }
+
+    /// fill *y*, blocking while the queue is empty
+    /// # returns
+    /// the number of samples read, less than y.len() only if the producer
+    /// went away; LiquidError::Disconnected if the producer is gone and
+    /// nothing was left to read
+    pub fn pop(&self, y: &mut [T]) -> LiquidResult<usize> {
+        self.pop_until(y, None)
+    }
+
+    /// fill *y*, blocking at most *timeout* while the queue is empty
+    /// # returns
+    /// the number of samples read before the timeout expired
+    pub fn pop_timeout(&self, y: &mut [T], timeout: Duration) -> LiquidResult<usize> {
+        self.pop_until(y, Some(Instant::now() + timeout))
+    }
+
+    fn pop_until(&self, y: &mut [T], deadline: Option<Instant>) -> LiquidResult<usize> {
+        let mut num_read = 0;
+        let mut state = self.shared.lock();
+        loop {
+            num_read += self.read(&mut state, &mut y[num_read..]);
+            if num_read == y.len() {
+                return Ok(num_read);
+            }
+            if state.disconnected {
+                return if num_read == 0 && !y.is_empty() {
+                    Err(LiquidError::Disconnected)
+                } else {
+                    Ok(num_read)
+                };
+            }
+            state = match wait(&self.shared.readable, state, deadline) {
+                Some(state) => state,
+                None => return Ok(num_read),
+            };
+        }
+    }
+
+    fn read(&self, state: &mut State<T>, y: &mut [T]) -> usize {
+        let n = y.len().min(state.buffer.len());
+        if n > 0 {
+            for (dst, src) in y.iter_mut().zip(state.buffer.drain(..n)) {
+                *dst = src;
+            }
+            self.shared.writable.notify_one();
+        }
+        n
+    }
+}
+
+// wait on *cond* until notified or until the deadline passes, returning
+// None in the latter case
+fn wait<'a, T>(
+    cond: &Condvar,
+    state: MutexGuard<'a, State<T>>,
+    deadline: Option<Instant>,
+) -> Option<MutexGuard<'a, State<T>>> {
+    match deadline {
+        None => Some(cond.wait(state).unwrap_or_else(|e| e.into_inner())),
+        Some(deadline) => {
+            let now = Instant::now();
+            if now >= deadline {
+                return None;
+            }
+            let (state, _) = cond
+                .wait_timeout(state, deadline - now)
+                .unwrap_or_else(|e| e.into_inner());
+            Some(state)
+        }
+    }
+}
+
+impl<T> Drop for GportProducer<T> {
+    fn drop(&mut self) {
+        self.shared.disconnect();
+    }
+}
+
+impl<T> Drop for GportConsumer<T> {
+    fn drop(&mut self) {
+        self.shared.disconnect();
+    }
+}
+
+impl<T> fmt::Debug for GportProducer<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = self.shared.lock();
+        write!(
+            f,
+            "gport producer [capacity: {}, size: {}]",
+            self.shared.capacity,
+            state.buffer.len()
+        )
+    }
+}
+
+impl<T> fmt::Debug for GportConsumer<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = self.shared.lock();
+        write!(
+            f,
+            "gport consumer [capacity: {}, size: {}]",
+            self.shared.capacity,
+            state.buffer.len()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::channel;
+    use crate::errors::LiquidError;
+    use num::complex::Complex32;
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn test_gport_threads() {
+        let (tx, rx) = channel::<Complex32>(16).unwrap();
+        let producer = thread::spawn(move || {
+            for k in 0..50 {
+                let block: Vec<Complex32> = (0..10)
+                    .map(|i| Complex32::new((k * 10 + i) as f32, 0.0))
+                    .collect();
+                tx.push(&block).unwrap();
+            }
+        });
+
+        let mut received = Vec::new();
+        let mut block = [Complex32::default(); 7];
+        while let Ok(n) = rx.pop(&mut block) {
+            received.extend_from_slice(&block[..n]);
+        }
+        producer.join().unwrap();
+
+        assert_eq!(received.len(), 500);
+        for (i, v) in received.iter().enumerate() {
+            assert_eq!(v.re, i as f32);
+        }
+    }
+
+    #[test]
+    fn test_gport_non_blocking() {
+        let (tx, rx) = channel::<f32>(4).unwrap();
+        assert!(channel::<f32>(0).is_err());
+
+        assert_eq!(tx.try_push(&[1.0, 2.0, 3.0, 4.0, 5.0]).unwrap(), 4);
+        assert_eq!(
+            tx.push_timeout(&[5.0], Duration::from_millis(1)).unwrap(),
+            0
+        );
+        let mut y = [0f32; 3];
+        assert_eq!(rx.try_pop(&mut y), 3);
+        assert_eq!(y, [1.0, 2.0, 3.0]);
+        assert_eq!(rx.len(), 1);
+
+        drop(tx);
+        assert_eq!(rx.pop(&mut y).unwrap(), 1);
+        assert_eq!(y[0], 4.0);
+        assert!(matches!(rx.pop(&mut y), Err(LiquidError::Disconnected)));
+        assert!(matches!(
+            rx.pop_timeout(&mut y, Duration::from_millis(1)),
+            Err(LiquidError::Disconnected)
+        ));
+
+        let (tx, rx) = channel::<f32>(4).unwrap();
+        drop(rx);
+        assert!(tx.is_disconnected());
+        assert!(matches!(
+            tx.try_push(&[1.0]),
+            Err(LiquidError::Disconnected)
+        ));
+        assert!(matches!(tx.push(&[1.0]), Err(LiquidError::Disconnected)));
+    }
+}
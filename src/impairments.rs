@@ -0,0 +1,536 @@
+//! Front-end impairments: gain and phase imbalance between the I and Q
+//! branches of a quadrature receiver, its blind estimation and correction
+//! along with the DC offset and carrier frequency offset, and memoryless
+//! nonlinearities of a power amplifier
+//!
+//! The imbalance is modeled on the Q branch, with the I branch taken as
+//! reference:
+//!   y_i = x_i
+//!   y_q = g*(x_q*cos(phi) - x_i*sin(phi))
+//! or equivalently y = mu*x + nu*conj(x), with mu = (1 + g*exp(-j*phi))/2
+//! and nu = (1 - g*exp(j*phi))/2.
+//!
+//! The nonlinearities act on the amplitude r of the input sample, as an
+//! AM/AM conversion A(r) and an AM/PM conversion P(r):
+//!   y = A(r)*exp(j*(arg(x) + P(r)))
+use num::complex::Complex32;
+
+use crate::errors::LiquidError;
+use crate::utils::check_same_len;
+use crate::LiquidResult;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct IqImbalance {
+    gain_db: f32,
+    phase: f32,
+}
+
+impl IqImbalance {
+    /// create gain/phase imbalance model
+    ///  gain_db    :   gain of the Q branch relative to the I branch [dB]
+    ///  phase      :   phase skew of the Q branch [radians], |phase| < pi/2
+    pub fn new(gain_db: f32, phase: f32) -> LiquidResult<Self> {
+        if !gain_db.is_finite() {
+            return Err(LiquidError::InvalidValue(format!(
+                "gain: {} must be finite",
+                gain_db
+            )));
+        } else if phase.abs() >= std::f32::consts::FRAC_PI_2 {
+            return Err(LiquidError::InvalidValue(format!(
+                "phase: {} must be in (-pi/2, pi/2)",
+                phase
+            )));
+        }
+        Ok(Self { gain_db, phase })
+    }
+
+    /// blind estimate of the imbalance of a block of samples, assuming
+    /// the transmitted signal is circular (I and Q uncorrelated with equal
+    /// power), e.g. a PSK/QAM signal or noise
+    ///  x      :   received samples
+    pub fn estimate(x: &[Complex32]) -> LiquidResult<Self> {
+        if x.is_empty() {
+            return Err(LiquidError::EmptyBuffer);
+        }
+        let (mut pi, mut pq, mut piq) = (0f32, 0f32, 0f32);
+        for v in x {
+            pi += v.re * v.re;
+            pq += v.im * v.im;
+            piq += v.re * v.im;
+        }
+        if pi == 0f32 || pq == 0f32 {
+            return Err(LiquidError::InvalidValue(
+                "both branches must carry energy".to_owned(),
+            ));
+        }
+        let gain = (pq / pi).sqrt();
+        let phase = (-piq / (pi * pq).sqrt()).clamp(-1.0, 1.0).asin();
+        Self::new(20.0 * gain.log10(), phase)
+    }
+
+    pub fn get_gain_db(&self) -> f32 {
+        self.gain_db
+    }
+
+    pub fn get_phase(&self) -> f32 {
+        self.phase
+    }
+
+    /// coefficients (mu, nu) of the widely linear model
+    /// y = mu*x + nu*conj(x)
+    pub fn coefficients(&self) -> (Complex32, Complex32) {
+        let g = self.gain();
+        let mu = (Complex32::new(1.0, 0.0) + Complex32::from_polar(g, -self.phase)) * 0.5;
+        let nu = (Complex32::new(1.0, 0.0) - Complex32::from_polar(g, self.phase)) * 0.5;
+        (mu, nu)
+    }
+
+    /// image rejection ratio [dB], the power of the signal relative to
+    /// the power of its mirror image
+    pub fn image_rejection_db(&self) -> f32 {
+        let (mu, nu) = self.coefficients();
+        10.0 * (mu.norm_sqr() / nu.norm_sqr()).log10()
+    }
+
+    /// apply the imbalance to a single sample
+    pub fn apply_sample(&self, x: Complex32) -> Complex32 {
+        let g = self.gain();
+        let (sin, cos) = self.phase.sin_cos();
+        Complex32::new(x.re, g * (x.im * cos - x.re * sin))
+    }
+
+    /// remove the imbalance from a single sample
+    pub fn correct_sample(&self, y: Complex32) -> Complex32 {
+        let g = self.gain();
+        let (sin, cos) = self.phase.sin_cos();
+        Complex32::new(y.re, (y.im / g + y.re * sin) / cos)
+    }
+
+    /// apply the imbalance to a block of samples in place
+    pub fn apply(&self, x: &mut [Complex32]) {
+        x.iter_mut().for_each(|v| *v = self.apply_sample(*v));
+    }
+
+    /// remove the imbalance from a block of samples in place
+    pub fn correct(&self, y: &mut [Complex32]) {
+        y.iter_mut().for_each(|v| *v = self.correct_sample(*v));
+    }
+
+    fn gain(&self) -> f32 {
+        10f32.powf(self.gain_db / 20.0)
+    }
+}
+
+impl Default for IqImbalance {
+    /// balanced front-end
+    fn default() -> Self {
+        Self {
+            gain_db: 0.0,
+            phase: 0.0,
+        }
+    }
+}
+
+/// front-end corrections, as estimated by Estimator
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Correction {
+    /// DC offset, removed first
+    pub dc_offset: Complex32,
+    /// I/Q imbalance, corrected after the DC offset removal
+    pub imbalance: IqImbalance,
+    /// carrier frequency offset [radians/sample], removed last
+    pub cfo: f32,
+}
+
+impl Correction {
+    /// corrector applying the corrections to a stream of samples
+    pub fn corrector(&self) -> Corrector {
+        Corrector {
+            correction: *self,
+            phase: 0.0,
+        }
+    }
+}
+
+/// applies a Correction to a stream of samples, keeping the phase of the
+/// carrier frequency offset across blocks
+#[derive(Clone, Debug)]
+pub struct Corrector {
+    correction: Correction,
+    phase: f32,
+}
+
+impl Corrector {
+    pub fn get_correction(&self) -> &Correction {
+        &self.correction
+    }
+
+    /// replace the corrections, e.g. after a new estimation, keeping the
+    /// carrier phase
+    pub fn set_correction(&mut self, correction: Correction) {
+        self.correction = correction;
+    }
+
+    /// restart the carrier phase at zero
+    pub fn reset(&mut self) {
+        self.phase = 0.0;
+    }
+
+    /// correct a single sample
+    pub fn apply_sample(&mut self, x: Complex32) -> Complex32 {
+        let c = &self.correction;
+        let y =
+            c.imbalance.correct_sample(x - c.dc_offset) * Complex32::from_polar(1.0, -self.phase);
+        self.phase = (self.phase + c.cfo) % (2.0 * std::f32::consts::PI);
+        y
+    }
+
+    /// correct a block of samples
+    ///  x      :   input samples [size: n x 1]
+    ///  y      :   output samples [size: n x 1]
+    pub fn execute_block(&mut self, x: &[Complex32], y: &mut [Complex32]) -> LiquidResult<()> {
+        check_same_len(x.len(), y.len())?;
+        x.iter()
+            .zip(y.iter_mut())
+            .for_each(|(a, b)| *b = self.apply_sample(*a));
+        Ok(())
+    }
+}
+
+/// estimator of the DC offset, the I/Q imbalance and a coarse carrier
+/// frequency offset of a received signal, accumulating second-order
+/// statistics over any number of blocks
+///
+/// The imbalance is estimated as in IqImbalance::estimate on the samples
+/// with the DC offset removed. The carrier offset is the phase of the
+/// lag-1 autocorrelation of the corrected samples, which assumes the
+/// transmitted signal has a real autocorrelation at lag 1, e.g. a
+/// symmetric spectrum, and an offset within (-pi, pi) radians/sample.
+#[derive(Clone, Debug, Default)]
+pub struct Estimator {
+    num_samples: u64,
+    // sums of i, q, i^2, q^2 and i*q
+    s_i: f64,
+    s_q: f64,
+    s_ii: f64,
+    s_qq: f64,
+    s_iq: f64,
+    // lag-1 sums, i[n]*i[n-1], q[n]*q[n-1], i[n]*q[n-1] and q[n]*i[n-1]
+    num_lags: u64,
+    r_ii: f64,
+    r_qq: f64,
+    r_iq: f64,
+    r_qi: f64,
+    last: Option<Complex32>,
+}
+
+impl Estimator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// drop the accumulated statistics
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// number of samples accumulated
+    pub fn num_samples(&self) -> u64 {
+        self.num_samples
+    }
+
+    /// accumulate a block of received samples, contiguous with the
+    /// previous block
+    pub fn push_block(&mut self, x: &[Complex32]) {
+        for v in x {
+            let (i, q) = (v.re as f64, v.im as f64);
+            self.s_i += i;
+            self.s_q += q;
+            self.s_ii += i * i;
+            self.s_qq += q * q;
+            self.s_iq += i * q;
+            if let Some(u) = self.last {
+                let (i1, q1) = (u.re as f64, u.im as f64);
+                self.r_ii += i * i1;
+                self.r_qq += q * q1;
+                self.r_iq += i * q1;
+                self.r_qi += q * i1;
+                self.num_lags += 1;
+            }
+            self.last = Some(*v);
+        }
+        self.num_samples += x.len() as u64;
+    }
+
+    /// estimate the corrections from the accumulated samples
+    pub fn estimate(&self) -> LiquidResult<Correction> {
+        if self.num_lags == 0 {
+            return Err(LiquidError::InvalidLength {
+                description: "at least 2 samples are needed".to_owned(),
+            });
+        }
+        let n = self.num_samples as f64;
+        let (mi, mq) = (self.s_i / n, self.s_q / n);
+        let cii = self.s_ii / n - mi * mi;
+        let cqq = self.s_qq / n - mq * mq;
+        let ciq = self.s_iq / n - mi * mq;
+        if cii <= 0f64 || cqq <= 0f64 {
+            return Err(LiquidError::InvalidValue(
+                "both branches must carry energy".to_owned(),
+            ));
+        }
+        let gain = (cqq / cii).sqrt();
+        let phase = (-ciq / (cii * cqq).sqrt()).clamp(-1.0, 1.0).asin();
+        let imbalance = IqImbalance::new(20.0 * gain.log10() as f32, phase as f32)?;
+
+        // lag-1 autocorrelation of z = zi + j*zq with zi = i and
+        // zq = a*q + b*i, the imbalance corrected samples
+        let nl = self.num_lags as f64;
+        let rii = self.r_ii / nl - mi * mi;
+        let rqq = self.r_qq / nl - mq * mq;
+        let riq = self.r_iq / nl - mi * mq;
+        let rqi = self.r_qi / nl - mq * mi;
+        let a = 1.0 / (gain * phase.cos());
+        let b = phase.tan();
+        let re = rii + a * a * rqq + a * b * (riq + rqi) + b * b * rii;
+        let im = a * (rqi - riq);
+        Ok(Correction {
+            dc_offset: Complex32::new(mi as f32, mq as f32),
+            imbalance,
+            cfo: im.atan2(re) as f32,
+        })
+    }
+}
+
+/// memoryless nonlinearity of a power amplifier
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Nonlinearity {
+    /// Saleh model of a travelling-wave tube amplifier,
+    ///   A(r) = alpha_a*r/(1 + beta_a*r^2)
+    ///   P(r) = alpha_p*r^2/(1 + beta_p*r^2)
+    Saleh {
+        alpha_a: f32,
+        beta_a: f32,
+        alpha_p: f32,
+        beta_p: f32,
+    },
+    /// Rapp model of a solid-state amplifier, without AM/PM conversion,
+    ///   A(r) = g*r/(1 + (g*r/saturation)^(2p))^(1/(2p))
+    Rapp {
+        gain: f32,
+        saturation: f32,
+        smoothness: f32,
+    },
+    /// hard clipping of the amplitude, the phase being preserved,
+    ///   A(r) = min(r, level)
+    Clip { level: f32 },
+}
+
+impl Nonlinearity {
+    /// Saleh model
+    ///  alpha_a    :   small-signal gain, alpha_a > 0
+    ///  beta_a     :   AM/AM compression, beta_a >= 0
+    ///  alpha_p    :   AM/PM conversion [radians]
+    ///  beta_p     :   AM/PM compression, beta_p >= 0
+    pub fn saleh(alpha_a: f32, beta_a: f32, alpha_p: f32, beta_p: f32) -> LiquidResult<Self> {
+        check_positive("alpha_a", alpha_a)?;
+        check_non_negative("beta_a", beta_a)?;
+        if !alpha_p.is_finite() {
+            return Err(LiquidError::InvalidValue(format!(
+                "alpha_p: {} must be finite",
+                alpha_p
+            )));
+        }
+        check_non_negative("beta_p", beta_p)?;
+        Ok(Nonlinearity::Saleh {
+            alpha_a,
+            beta_a,
+            alpha_p,
+            beta_p,
+        })
+    }
+
+    /// Rapp model
+    ///  gain       :   small-signal gain, gain > 0
+    ///  saturation :   output saturation amplitude, saturation > 0
+    ///  smoothness :   smoothness of the transition to saturation, p > 0,
+    ///                 e.g. 2 to 3 for a typical amplifier
+    pub fn rapp(gain: f32, saturation: f32, smoothness: f32) -> LiquidResult<Self> {
+        check_positive("gain", gain)?;
+        check_positive("saturation", saturation)?;
+        check_positive("smoothness", smoothness)?;
+        Ok(Nonlinearity::Rapp {
+            gain,
+            saturation,
+            smoothness,
+        })
+    }
+
+    /// hard clipper
+    ///  level      :   largest output amplitude, level > 0
+    pub fn clip(level: f32) -> LiquidResult<Self> {
+        check_positive("level", level)?;
+        Ok(Nonlinearity::Clip { level })
+    }
+
+    /// AM/AM conversion, output amplitude for an input amplitude r
+    pub fn am_am(&self, r: f32) -> f32 {
+        match *self {
+            Nonlinearity::Saleh {
+                alpha_a, beta_a, ..
+            } => alpha_a * r / (1.0 + beta_a * r * r),
+            Nonlinearity::Rapp {
+                gain,
+                saturation,
+                smoothness,
+            } => {
+                let p2 = 2.0 * smoothness;
+                gain * r / (1.0 + (gain * r / saturation).powf(p2)).powf(1.0 / p2)
+            }
+            Nonlinearity::Clip { level } => r.min(level),
+        }
+    }
+
+    /// AM/PM conversion, phase shift [radians] for an input amplitude r
+    pub fn am_pm(&self, r: f32) -> f32 {
+        match *self {
+            Nonlinearity::Saleh {
+                alpha_p, beta_p, ..
+            } => alpha_p * r * r / (1.0 + beta_p * r * r),
+            Nonlinearity::Rapp { .. } | Nonlinearity::Clip { .. } => 0.0,
+        }
+    }
+
+    /// apply the nonlinearity to a single sample
+    pub fn apply_sample(&self, x: Complex32) -> Complex32 {
+        let r = x.norm();
+        if r == 0f32 {
+            return x;
+        }
+        x * Complex32::from_polar(self.am_am(r) / r, self.am_pm(r))
+    }
+
+    /// apply the nonlinearity to a block of samples in place
+    pub fn apply(&self, x: &mut [Complex32]) {
+        x.iter_mut().for_each(|v| *v = self.apply_sample(*v));
+    }
+}
+
+fn check_positive(name: &str, v: f32) -> LiquidResult<()> {
+    if v <= 0f32 || !v.is_finite() {
+        return Err(LiquidError::InvalidValue(format!(
+            "{}: {} must be greater than zero",
+            name, v
+        )));
+    }
+    Ok(())
+}
+
+fn check_non_negative(name: &str, v: f32) -> LiquidResult<()> {
+    if v < 0f32 || !v.is_finite() {
+        return Err(LiquidError::InvalidValue(format!(
+            "{}: {} must be greater than or equal to zero",
+            name, v
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Estimator, IqImbalance, Nonlinearity};
+    use num::complex::Complex32;
+
+    #[test]
+    fn test_iq_imbalance_estimate_correct() {
+        let x: Vec<Complex32> = (0..4000)
+            .map(|k| Complex32::from_polar(1.0, 0.1 + std::f32::consts::FRAC_PI_2 * (k % 4) as f32))
+            .collect();
+        let q = IqImbalance::new(1.0, 0.1).unwrap();
+        let mut y = x.clone();
+        q.apply(&mut y);
+        assert!(q.image_rejection_db() > 20.0);
+
+        let q_hat = IqImbalance::estimate(&y).unwrap();
+        assert!((q_hat.get_gain_db() - 1.0).abs() < 1e-3);
+        assert!((q_hat.get_phase() - 0.1).abs() < 1e-3);
+
+        q_hat.correct(&mut y);
+        for (a, b) in x.iter().zip(y.iter()) {
+            assert!((a - b).norm() < 1e-3);
+        }
+        assert!(IqImbalance::new(0.0, 2.0).is_err());
+    }
+
+    #[test]
+    fn test_nonlinearity_models() {
+        assert!(Nonlinearity::saleh(0.0, 1.0, 1.0, 1.0).is_err());
+        assert!(Nonlinearity::rapp(1.0, 1.0, -2.0).is_err());
+        assert!(Nonlinearity::clip(f32::NAN).is_err());
+
+        let x = Complex32::from_polar(0.01, 0.5);
+        let big = Complex32::from_polar(10.0, 0.5);
+        // linear at small amplitudes, saturating at large ones
+        let rapp = Nonlinearity::rapp(2.0, 1.0, 2.0).unwrap();
+        assert!((rapp.apply_sample(x) - x * 2.0).norm() < 1e-6);
+        assert!((rapp.apply_sample(big).norm() - 1.0).abs() < 1e-3);
+        assert!((rapp.apply_sample(big).arg() - 0.5).abs() < 1e-6);
+
+        let saleh = Nonlinearity::saleh(2.0, 1.0, 1.0, 3.0).unwrap();
+        assert!((saleh.am_am(1.0) - 1.0).abs() < 1e-6);
+        assert!((saleh.am_pm(1.0) - 0.25).abs() < 1e-6);
+        assert!((saleh.apply_sample(Complex32::new(1.0, 0.0)).arg() - 0.25).abs() < 1e-6);
+
+        let mut y = [x, big, Complex32::default()];
+        Nonlinearity::clip(1.0).unwrap().apply(&mut y);
+        assert_eq!(y[0], x);
+        assert!((y[1] - Complex32::from_polar(1.0, 0.5)).norm() < 1e-6);
+        assert_eq!(y[2], Complex32::default());
+    }
+
+    #[test]
+    fn test_estimator_correction() {
+        // QPSK at 4 samples per symbol
+        let mut seed = 1u32;
+        let mut x = Vec::new();
+        for _ in 0..4000 {
+            seed = seed.wrapping_mul(1664525).wrapping_add(1013904223);
+            let k = (seed >> 30) as f32;
+            let s = Complex32::from_polar(1.0, std::f32::consts::FRAC_PI_4 * (2.0 * k + 1.0));
+            x.extend_from_slice(&[s; 4]);
+        }
+        let cfo = 0.05;
+        let iq = IqImbalance::new(1.0, 0.1).unwrap();
+        let dc = Complex32::new(0.2, -0.1);
+        let y: Vec<Complex32> = x
+            .iter()
+            .enumerate()
+            .map(|(n, v)| iq.apply_sample(v * Complex32::from_polar(1.0, cfo * n as f32)) + dc)
+            .collect();
+
+        let mut q = Estimator::new();
+        assert!(q.estimate().is_err());
+        // the lag across blocks is kept
+        q.push_block(&y[..1001]);
+        q.push_block(&y[1001..]);
+        assert_eq!(q.num_samples(), y.len() as u64);
+        let c = q.estimate().unwrap();
+        assert!((c.dc_offset - dc).norm() < 0.02);
+        assert!((c.imbalance.get_gain_db() - 1.0).abs() < 0.1);
+        assert!((c.imbalance.get_phase() - 0.1).abs() < 0.02);
+        assert!((c.cfo - cfo).abs() < 1e-3);
+
+        let mut corrector = c.corrector();
+        let mut z = vec![Complex32::default(); y.len()];
+        assert!(corrector.execute_block(&y[1..], &mut z).is_err());
+        corrector.execute_block(&y, &mut z).unwrap();
+        // the residual offset makes the phase drift, look at the start
+        let err: f32 = x[..1000]
+            .iter()
+            .zip(&z)
+            .map(|(a, b)| (a - b).norm())
+            .sum::<f32>()
+            / 1000.0;
+        assert!(err < 0.1);
+    }
+}
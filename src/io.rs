@@ -0,0 +1,373 @@
+//! *io* : reading and writing complex baseband captures
+//!
+//! Two file formats are supported:
+//!   - raw interleaved little-endian f32 I/Q pairs (.cf32, .iq, ...)
+//!   - WAV files, the left channel carrying I and the right channel Q,
+//!     with 16-bit PCM or 32-bit float samples; mono files are read with
+//!     a zero Q component
+//!
+//! IqReader streams a capture in chunks of a fixed number of samples, to
+//! feed the execute_block methods without loading the whole capture.
+use num::complex::Complex32;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+const WAVE_FORMAT_PCM: u16 = 1;
+const WAVE_FORMAT_IEEE_FLOAT: u16 = 3;
+const WAVE_FORMAT_EXTENSIBLE: u16 = 0xfffe;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum SampleFormat {
+    I16,
+    F32,
+}
+
+impl SampleFormat {
+    fn size(self) -> usize {
+        match self {
+            Self::I16 => 2,
+            Self::F32 => 4,
+        }
+    }
+
+    fn decode(self, b: &[u8]) -> f32 {
+        match self {
+            Self::I16 => i16::from_le_bytes([b[0], b[1]]) as f32 / 32768.0,
+            Self::F32 => f32::from_le_bytes([b[0], b[1], b[2], b[3]]),
+        }
+    }
+}
+
+/// chunked reader of complex samples
+pub struct IqReader {
+    reader: Box<dyn Read>,
+    format: SampleFormat,
+    channels: usize,
+    chunk_len: usize,
+    sample_rate: Option<u32>,
+    bytes: Vec<u8>,
+}
+
+impl IqReader {
+    /// reader of raw interleaved f32 I/Q samples
+    ///  reader     :   source of the samples
+    ///  chunk_len  :   number of samples per chunk, chunk_len > 0
+    pub fn new<R: Read + 'static>(reader: R, chunk_len: usize) -> io::Result<Self> {
+        Self::with_format(Box::new(reader), SampleFormat::F32, 2, chunk_len, None)
+    }
+
+    /// open a raw interleaved f32 I/Q file
+    pub fn open<P: AsRef<Path>>(path: P, chunk_len: usize) -> io::Result<Self> {
+        Self::new(BufReader::new(File::open(path)?), chunk_len)
+    }
+
+    /// reader of a WAV stream, the header is parsed up to the start of
+    /// the samples
+    ///  reader     :   source of the WAV stream
+    ///  chunk_len  :   number of samples per chunk, chunk_len > 0
+    pub fn wav<R: Read + 'static>(mut reader: R, chunk_len: usize) -> io::Result<Self> {
+        let mut riff = [0u8; 12];
+        reader.read_exact(&mut riff)?;
+        if &riff[0..4] != b"RIFF" || &riff[8..12] != b"WAVE" {
+            return Err(invalid_data("not a RIFF/WAVE stream"));
+        }
+        let mut fmt: Option<(SampleFormat, usize, u32)> = None;
+        loop {
+            let mut header = [0u8; 8];
+            reader.read_exact(&mut header)?;
+            let len = u32::from_le_bytes([header[4], header[5], header[6], header[7]]) as u64;
+            match &header[0..4] {
+                b"fmt " => {
+                    let mut body = vec![0u8; len as usize];
+                    reader.read_exact(&mut body)?;
+                    fmt = Some(parse_fmt(&body)?);
+                }
+                b"data" => {
+                    let (format, channels, rate) =
+                        fmt.ok_or_else(|| invalid_data("data chunk before fmt chunk"))?;
+                    return Self::with_format(
+                        Box::new(reader.take(len)),
+                        format,
+                        channels,
+                        chunk_len,
+                        Some(rate),
+                    );
+                }
+                _ => {
+                    // chunks are padded to an even length
+                    io::copy(&mut (&mut reader).take(len + (len & 1)), &mut io::sink())?;
+                }
+            }
+        }
+    }
+
+    /// open a WAV file
+    pub fn open_wav<P: AsRef<Path>>(path: P, chunk_len: usize) -> io::Result<Self> {
+        Self::wav(BufReader::new(File::open(path)?), chunk_len)
+    }
+
+    pub(crate) fn with_format(
+        reader: Box<dyn Read>,
+        format: SampleFormat,
+        channels: usize,
+        chunk_len: usize,
+        sample_rate: Option<u32>,
+    ) -> io::Result<Self> {
+        if chunk_len == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "chunk length must be greater than zero",
+            ));
+        }
+        Ok(Self {
+            reader,
+            format,
+            channels,
+            chunk_len,
+            sample_rate,
+            bytes: vec![0u8; chunk_len * channels * format.size()],
+        })
+    }
+
+    /// sample rate [Hz], for WAV streams only
+    pub fn sample_rate(&self) -> Option<u32> {
+        self.sample_rate
+    }
+
+    /// discard the next n samples, e.g. to start reading a burst at a
+    /// known offset of a capture
+    /// # returns
+    /// the number of samples skipped, less than n at the end of the stream
+    pub fn skip_samples(&mut self, n: usize) -> io::Result<usize> {
+        let frame = (self.channels * self.format.size()) as u64;
+        let skipped = io::copy(
+            &mut (&mut self.reader).take(n as u64 * frame),
+            &mut io::sink(),
+        )?;
+        Ok((skipped / frame) as usize)
+    }
+
+    /// read the next chunk, replacing the content of buf
+    /// # returns
+    /// the number of samples read, less than the chunk length only for
+    /// the last chunk, and 0 at the end of the stream
+    pub fn read_chunk(&mut self, buf: &mut Vec<Complex32>) -> io::Result<usize> {
+        let mut filled = 0;
+        while filled < self.bytes.len() {
+            match self.reader.read(&mut self.bytes[filled..]) {
+                Ok(0) => break,
+                Ok(n) => filled += n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        let size = self.format.size();
+        let frame = self.channels * size;
+        if filled % frame != 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "stream ends in the middle of a sample",
+            ));
+        }
+        buf.clear();
+        buf.extend(self.bytes[..filled].chunks(frame).map(|b| {
+            let re = self.format.decode(b);
+            let im = if self.channels == 2 {
+                self.format.decode(&b[size..])
+            } else {
+                0.0
+            };
+            Complex32::new(re, im)
+        }));
+        Ok(buf.len())
+    }
+}
+
+impl Iterator for IqReader {
+    type Item = io::Result<Vec<Complex32>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut buf = Vec::with_capacity(self.chunk_len);
+        match self.read_chunk(&mut buf) {
+            Ok(0) => None,
+            Ok(_) => Some(Ok(buf)),
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
+
+fn parse_fmt(body: &[u8]) -> io::Result<(SampleFormat, usize, u32)> {
+    if body.len() < 16 {
+        return Err(invalid_data("fmt chunk too short"));
+    }
+    let u16_at = |i: usize| u16::from_le_bytes([body[i], body[i + 1]]);
+    let mut tag = u16_at(0);
+    if tag == WAVE_FORMAT_EXTENSIBLE {
+        if body.len() < 26 {
+            return Err(invalid_data("extensible fmt chunk too short"));
+        }
+        // the sub-format GUID starts with the format tag
+        tag = u16_at(24);
+    }
+    let channels = u16_at(2) as usize;
+    let rate = u32::from_le_bytes([body[4], body[5], body[6], body[7]]);
+    let format = match (tag, u16_at(14)) {
+        (WAVE_FORMAT_PCM, 16) => SampleFormat::I16,
+        (WAVE_FORMAT_IEEE_FLOAT, 32) => SampleFormat::F32,
+        (tag, bits) => {
+            return Err(invalid_data(&format!(
+                "unsupported sample format {} with {} bits",
+                tag, bits
+            )))
+        }
+    };
+    if channels != 1 && channels != 2 {
+        return Err(invalid_data(&format!(
+            "unsupported number of channels: {}",
+            channels
+        )));
+    }
+    Ok((format, channels, rate))
+}
+
+fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.to_owned())
+}
+
+/// read a whole raw interleaved f32 I/Q file
+pub fn read_iq_file<P: AsRef<Path>>(path: P) -> io::Result<Vec<Complex32>> {
+    IqReader::open(path, 4096)?.try_fold(Vec::new(), |mut x, chunk| {
+        x.extend(chunk?);
+        Ok(x)
+    })
+}
+
+/// write samples as raw interleaved f32 I/Q
+pub fn write_iq<W: Write>(mut writer: W, x: &[Complex32]) -> io::Result<()> {
+    for v in x {
+        writer.write_all(&v.re.to_le_bytes())?;
+        writer.write_all(&v.im.to_le_bytes())?;
+    }
+    writer.flush()
+}
+
+/// write a raw interleaved f32 I/Q file
+pub fn write_iq_file<P: AsRef<Path>>(path: P, x: &[Complex32]) -> io::Result<()> {
+    write_iq(BufWriter::new(File::create(path)?), x)
+}
+
+/// read a whole WAV file
+/// # returns
+/// the samples and the sample rate [Hz]
+pub fn read_wav_file<P: AsRef<Path>>(path: P) -> io::Result<(Vec<Complex32>, u32)> {
+    let reader = IqReader::open_wav(path, 4096)?;
+    let rate = reader.sample_rate().unwrap_or_default();
+    let x = reader.into_iter().try_fold(Vec::new(), |mut x, chunk| {
+        x.extend(chunk?);
+        Ok::<_, io::Error>(x)
+    })?;
+    Ok((x, rate))
+}
+
+/// write samples as a stereo 32-bit float WAV stream
+///  sample_rate    :   sample rate [Hz]
+pub fn write_wav<W: Write>(mut writer: W, x: &[Complex32], sample_rate: u32) -> io::Result<()> {
+    let data_len = x.len() * 8;
+    if data_len > (u32::MAX - 36) as usize {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "too many samples for a WAV file",
+        ));
+    }
+    let byte_rate = sample_rate.checked_mul(8).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "sample rate too high for a WAV file",
+        )
+    })?;
+    writer.write_all(b"RIFF")?;
+    writer.write_all(&(36 + data_len as u32).to_le_bytes())?;
+    writer.write_all(b"WAVEfmt ")?;
+    writer.write_all(&16u32.to_le_bytes())?;
+    writer.write_all(&WAVE_FORMAT_IEEE_FLOAT.to_le_bytes())?;
+    writer.write_all(&2u16.to_le_bytes())?;
+    writer.write_all(&sample_rate.to_le_bytes())?;
+    writer.write_all(&byte_rate.to_le_bytes())?;
+    writer.write_all(&8u16.to_le_bytes())?;
+    writer.write_all(&32u16.to_le_bytes())?;
+    writer.write_all(b"data")?;
+    writer.write_all(&(data_len as u32).to_le_bytes())?;
+    write_iq(writer, x)
+}
+
+/// write a stereo 32-bit float WAV file
+pub fn write_wav_file<P: AsRef<Path>>(
+    path: P,
+    x: &[Complex32],
+    sample_rate: u32,
+) -> io::Result<()> {
+    write_wav(BufWriter::new(File::create(path)?), x, sample_rate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{write_iq, write_wav, IqReader};
+    use num::complex::Complex32;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_io_round_trip() {
+        let x: Vec<Complex32> = (0..10)
+            .map(|i| Complex32::new(i as f32 * 0.1, -(i as f32) * 0.05))
+            .collect();
+
+        let mut raw = Vec::new();
+        write_iq(&mut raw, &x).unwrap();
+        assert_eq!(raw.len(), 80);
+        let chunks: Vec<Vec<Complex32>> = IqReader::new(Cursor::new(raw.clone()), 4)
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(
+            chunks.iter().map(|c| c.len()).collect::<Vec<_>>(),
+            vec![4, 4, 2]
+        );
+        assert_eq!(chunks.concat(), x);
+
+        let mut reader = IqReader::new(Cursor::new(raw.clone()), 3).unwrap();
+        assert_eq!(reader.skip_samples(8).unwrap(), 8);
+        assert_eq!(reader.next().unwrap().unwrap(), &x[8..]);
+
+        raw.pop();
+        let mut reader = IqReader::new(Cursor::new(raw), 16).unwrap();
+        assert!(reader.read_chunk(&mut Vec::new()).is_err());
+
+        let mut wav = Vec::new();
+        write_wav(&mut wav, &x, 48000).unwrap();
+        let mut reader = IqReader::wav(Cursor::new(wav), 64).unwrap();
+        assert_eq!(reader.sample_rate(), Some(48000));
+        let mut y = Vec::new();
+        assert_eq!(reader.read_chunk(&mut y).unwrap(), 10);
+        assert_eq!(y, x);
+        assert_eq!(reader.read_chunk(&mut y).unwrap(), 0);
+        assert!(write_wav(&mut Vec::new(), &x, u32::MAX / 4).is_err());
+
+        // 16-bit PCM mono, with an extra chunk before the samples
+        let mut wav = Vec::new();
+        wav.extend_from_slice(b"RIFF\x00\x00\x00\x00WAVE");
+        wav.extend_from_slice(b"fmt \x10\x00\x00\x00\x01\x00\x01\x00");
+        wav.extend_from_slice(&8000u32.to_le_bytes());
+        wav.extend_from_slice(&16000u32.to_le_bytes());
+        wav.extend_from_slice(b"\x02\x00\x10\x00LIST\x03\x00\x00\x00abc\x00");
+        wav.extend_from_slice(b"data\x04\x00\x00\x00\x00\x40\x00\xc0");
+        let y: Vec<Complex32> = IqReader::wav(Cursor::new(wav), 8)
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap();
+        assert_eq!(y, vec![Complex32::new(0.5, 0.0), Complex32::new(-0.5, 0.0)]);
+
+        assert!(IqReader::wav(Cursor::new(b"RIFX".to_vec()), 8).is_err());
+    }
+}
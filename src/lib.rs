@@ -3,41 +3,116 @@ extern crate libc;
 extern crate bitflags;
 extern crate liquid_dsp_sys;
 extern crate num;
+#[cfg(feature = "rayon")]
+extern crate rayon;
 
 mod agc;
+mod block;
 mod cbuffer;
 mod channel;
+mod corpus;
 mod cvsd;
+mod fading;
 mod fec;
 mod fft;
 mod filter;
+mod framing;
 mod modem;
+mod optim;
+mod pipeline;
+mod recorder;
+mod send;
+mod sequence;
+mod tag;
 mod tvmpch;
+mod version;
 mod equalization;
+pub mod convert;
+pub mod gport;
+pub mod impairments;
+pub mod math;
+pub mod matrix;
+#[cfg(feature = "io")]
+pub mod io;
+pub mod metrics;
+pub mod nco;
+#[cfg(feature = "plot")]
+pub mod plot;
+pub mod poly;
+pub mod predistort;
+pub mod quantization;
+pub mod random;
+#[cfg(feature = "sigmf")]
+pub mod sigmf;
+pub mod sync;
 
 mod callbacks;
 mod enums;
 mod errors;
 mod utils;
 
-pub use agc::{AgcCrcf, AgcRrrf};
-pub use equalization::{EqlmsRrrf, EqlmsCccf};
-pub use cbuffer::{CbufferCf, CbufferRf};
-pub use channel::ChannelCccf;
+pub use agc::{AgcConfig, AgcCrcf, AgcRrrf, AgcSquelchEvent};
+pub use block::Block;
+pub use equalization::{channel_estimate, EqlmsCccf, EqlmsRrrf};
+pub use cbuffer::{Cbuffer, CbufferCf, CbufferRf, CbufferSample};
+pub use channel::{ChannelBuilder, ChannelCccf};
+pub use convert::{Complex32ToSc16, Cu8ToComplex32, Sc16ToComplex32};
+pub use corpus::{Corpus, CorpusCase, CorpusEntry, CorpusReport, CorpusRoutine};
 pub use cvsd::Cvsd;
-pub use fec::{Fec, Interleaver, Packetizer};
-pub use fft::{AsgramCf, AsgramRf, Fft, FftPlan};
+pub use fading::Fading;
+pub use fec::{
+    blockcodes, Fec, FecBlockProperties, FecStream, FecStreamBlock, Interleaver, Llr,
+    LlrConvention, Packetizer, PacketizerConfig, SoftDeinterleaver,
+};
+pub use fft::{
+    fftw, Asgram, AsgramCf, AsgramFrame, AsgramRf, AsgramStream, Fft, FftOptions, FftPlan,
+    SpgramCf, SpgramRf, SpwaterfallCf, SpwaterfallRf,
+};
 pub use filter::{
-    FftFiltCccf, FftFiltCrcf, FftFiltRrrf, FirFiltCccf, FirFiltCrcf, FirFiltRrrf, FirHilbt,
-    FirInterpCccf, FirInterpCrcf, FirInterpRrrf, FirdesFilterType, Firdespm, FirdespmBtype,
-    FirdespmWtype, IirFiltCccf, IirFiltCrcf, IirFiltRrrf, IirHilbt,AutoCorrRrrf, AutoCorrCccf
+    AutoCorrCccf, AutoCorrRrrf, FftFiltCccf, FftFiltCrcf, FftFiltRrrf, FilterAnalysis, Fir,
+    FirFarrowCrcf, FirFarrowRrrf, FirFiltCccf, FirFiltCrcf, FirFiltRrrf, FirFilterBuilder,
+    FirHilbt, FirInterpCccf, FirInterpCrcf, FirInterpRrrf, Firdes, FirdesFilterType, Firdespm,
+    FirdespmBtype, FirdespmWtype, FrequencyResponse, Iir, IirFiltCccf, IirFiltCrcf, IirFiltRrrf,
+    IirFiltSosCccf, IirFiltSosCrcf, IirFiltSosRrrf, IirHilbt, Iirdes, MsDecimCrcf, MsDecimRrrf,
+    MsDecimStage, RresampCccf, RresampCrcf, RresampRrrf, TrackingNotch, Transfer, Zpk,
+};
+pub use filter::{polyphase, zpk};
+pub use gport::{GportConsumer, GportProducer};
+pub use framing::{
+    BsyncCccf, BsyncCrcf, BsyncPeak, BsyncRrrf, DetectorCccf, DetectorOutput, FrameDataStats,
+    FrameStats, Framegen64, Framesync64, OfdmFlexFrameGen, OfdmFlexFrameGenProps,
+    OfdmFlexFrameSync, SymtrackCccf, FRAME64_HEADER_LEN, FRAME64_LEN, FRAME64_PAYLOAD_LEN,
+    OFDMFLEXFRAME_HEADER_LEN,
+};
+pub use impairments::{Correction, Corrector, Estimator, IqImbalance, Nonlinearity};
+pub use optim::{Gasearch, Gradsearch, OptimDirection, Qnsearch};
+pub use pipeline::Pipeline;
+pub use predistort::Predistorter;
+pub use quantization::{CompanderType, QuantizerCf, QuantizerRf};
+pub use recorder::{RecorderSink, TriggeredRecorder};
+pub use sequence::{Bsequence, Msequence};
+pub use nco::{NcoCrcf, NcoType};
+pub use sync::{
+    CarrierRecovery, CostasMode, Gardner, LoopFilter, MatchedFilter, MuellerMuller,
+    TimingRecovery,
 };
+pub use tag::{Tag, TagValue};
 pub use tvmpch::TvmpchCccf;
+pub use version::{
+    check_version, has_fftw, has_libfec, header_version, header_version_number, version,
+    version_number,
+};
+
+pub use modem::{
+    llr_to_soft_bits, normalize_constellation, AmpModem, AmpModemType, ArbLabeling, CpfskDem,
+    CpfskFilterType, CpfskMod, FmDiscMethod, FmDiscriminator, FmDiscriminatorBuilder, Modem,
+    ModulationScheme, Slicer,
+};
 
-pub use modem::{AmpModem, AmpModemType, CpfskDem, CpfskMod};
+pub use enums::{AgcSquelchMode, CrcScheme, FecScheme, FftType, FftwFlags, WindowType};
 
-pub use enums::{AgcSquelchMode, CrcScheme, FecScheme, FftType};
+pub use errors::{FecError, FftError, FilterError, LiquidError, ModemError};
 
-pub use errors::LiquidError;
+pub use utils::{pack_bytes, repack_bytes, unpack_bytes};
 
 pub type LiquidResult<T> = Result<T, LiquidError>;
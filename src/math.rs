@@ -0,0 +1,171 @@
+//! Phase processing helpers for FM and differential PSK receivers: phase
+//! unwrapping, instantaneous frequency and phase-difference demodulation
+use num::complex::Complex32;
+
+use crate::liquid_dsp_sys as raw;
+use crate::utils::check_same_len;
+use crate::LiquidResult;
+
+/// unwrap a phase sequence in place, adding multiples of 2*pi wherever
+/// consecutive samples differ by more than pi
+///  theta  :   phase [radians]
+pub fn unwrap_phase(theta: &mut [f32]) {
+    unsafe {
+        raw::liquid_unwrap_phase(theta.as_mut_ptr(), theta.len() as _);
+    }
+}
+
+/// unwrap a phase sequence in place, the 2*pi jumps being found against
+/// the average slope of the sequence rather than the previous sample,
+/// which suits phases with a large steady frequency
+///  theta  :   phase [radians]
+pub fn unwrap_phase2(theta: &mut [f32]) {
+    unsafe {
+        raw::liquid_unwrap_phase2(theta.as_mut_ptr(), theta.len() as _);
+    }
+}
+
+/// phase of every sample
+///  x      :   complex samples
+///  theta  :   phase in (-pi, pi] [size: x.len() x 1]
+pub fn phase(x: &[Complex32], theta: &mut [f32]) -> LiquidResult<()> {
+    check_same_len(x.len(), theta.len())?;
+    x.iter()
+        .zip(theta.iter_mut())
+        .for_each(|(a, b)| *b = a.arg());
+    Ok(())
+}
+
+/// instantaneous frequency, the phase difference between consecutive
+/// samples
+///  x      :   complex samples
+///  freq   :   frequency in (-pi, pi] [radians/sample], freq[i] being
+///             taken between x[i] and x[i+1] [size: x.len()-1 x 1]
+pub fn instantaneous_frequency(x: &[Complex32], freq: &mut [f32]) -> LiquidResult<()> {
+    check_same_len(x.len().saturating_sub(1), freq.len())?;
+    x.windows(2)
+        .zip(freq.iter_mut())
+        .for_each(|(w, f)| *f = (w[1] * w[0].conj()).arg());
+    Ok(())
+}
+
+/// streaming phase-difference demodulator: every sample is compared with
+/// the previous one, the first sample with a zero phase reference
+#[derive(Clone, Debug)]
+pub struct PhaseDifference {
+    prev: Complex32,
+}
+
+impl Default for PhaseDifference {
+    fn default() -> Self {
+        Self {
+            prev: Complex32::new(1.0, 0.0),
+        }
+    }
+}
+
+impl PhaseDifference {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// forget the previous sample, the next one being compared with a zero
+    /// phase reference
+    pub fn reset(&mut self) {
+        self.prev = Complex32::new(1.0, 0.0);
+    }
+
+    /// x * conj(previous sample), whose phase is the phase difference and
+    /// which can be sliced directly by a DBPSK/DQPSK demodulator
+    pub fn execute_product(&mut self, x: Complex32) -> Complex32 {
+        let y = x * self.prev.conj();
+        self.prev = x;
+        y
+    }
+
+    /// phase difference with the previous sample, in (-pi, pi] [radians]
+    pub fn execute(&mut self, x: Complex32) -> f32 {
+        self.execute_product(x).arg()
+    }
+
+    /// phase differences of a block of samples
+    ///  x      :   complex samples
+    ///  y      :   phase differences [size: x.len() x 1]
+    pub fn execute_block(&mut self, x: &[Complex32], y: &mut [f32]) -> LiquidResult<()> {
+        check_same_len(x.len(), y.len())?;
+        x.iter()
+            .zip(y.iter_mut())
+            .for_each(|(a, b)| *b = self.execute(*a));
+        Ok(())
+    }
+
+    /// products with the previous samples of a block of samples
+    ///  x      :   complex samples
+    ///  y      :   products [size: x.len() x 1]
+    pub fn execute_block_product(
+        &mut self,
+        x: &[Complex32],
+        y: &mut [Complex32],
+    ) -> LiquidResult<()> {
+        check_same_len(x.len(), y.len())?;
+        x.iter()
+            .zip(y.iter_mut())
+            .for_each(|(a, b)| *b = self.execute_product(*a));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{instantaneous_frequency, phase, unwrap_phase, unwrap_phase2, PhaseDifference};
+    use num::complex::Complex32;
+
+    #[test]
+    fn test_phase_helpers() {
+        let x: Vec<Complex32> = (0..50)
+            .map(|n| Complex32::from_polar(2.0, 0.3 * n as f32))
+            .collect();
+        let mut freq = vec![0f32; 49];
+        assert!(instantaneous_frequency(&x, &mut freq[1..]).is_err());
+        instantaneous_frequency(&x, &mut freq).unwrap();
+        assert!(freq.iter().all(|f| (f - 0.3).abs() < 1e-4));
+
+        let mut q = PhaseDifference::new();
+        let mut y = vec![0f32; 50];
+        q.execute_block(&x, &mut y).unwrap();
+        assert_eq!(y[0], 0.0);
+        assert!(y[1..].iter().all(|f| (f - 0.3).abs() < 1e-4));
+        // DBPSK: a sign change is a half turn
+        q.reset();
+        assert_eq!(
+            q.execute_product(Complex32::new(1.0, 0.0)),
+            Complex32::new(1.0, 0.0)
+        );
+        assert!((q.execute_product(Complex32::new(-1.0, 0.0)).re + 1.0).abs() < 1e-6);
+
+        let mut theta = vec![0f32; 50];
+        phase(&x, &mut theta).unwrap();
+        assert!(theta.iter().all(|t| t.abs() <= std::f32::consts::PI));
+        unwrap_phase(&mut theta);
+        for (n, t) in theta.iter().enumerate() {
+            assert!((t - 0.3 * n as f32).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn test_unwrap_phase2() {
+        // steady slope of 4 radians/sample with two 2*pi jumps
+        let mut theta: Vec<f32> = (0..50).map(|n| 4.0 * n as f32).collect();
+        theta[20] += std::f32::consts::TAU;
+        theta[35] -= std::f32::consts::TAU;
+
+        let mut theta2 = theta.clone();
+        unwrap_phase2(&mut theta2);
+        for (n, t) in theta2.iter().enumerate() {
+            assert!((t - 4.0 * n as f32).abs() < 1e-3);
+        }
+        // against the previous sample, the slope folds to 4 - 2*pi
+        unwrap_phase(&mut theta);
+        assert!((theta[49] - 4.0 * 49.0).abs() > 1.0);
+    }
+}
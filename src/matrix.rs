@@ -0,0 +1,118 @@
+//! *matrix* : linear system solvers over row-major matrices
+//!
+//! Matrices are stored in row-major order, A[i*n + j] being the element of
+//! row i and column j.
+use num::complex::Complex32;
+
+use crate::errors::LiquidError;
+use crate::liquid_dsp_sys as raw;
+use crate::LiquidResult;
+
+fn check_system(a: usize, b: usize) -> LiquidResult<()> {
+    if b == 0 {
+        return Err(LiquidError::EmptyBuffer);
+    } else if a != b * b {
+        return Err(LiquidError::InvalidLength {
+            description: format!("matrix length: {} valid length: {}", a, b * b),
+        });
+    }
+    Ok(())
+}
+
+/// solve A*x = b with the conjugate gradient method
+///  a      :   symmetric positive definite matrix [size: n x n]
+///  b      :   right-hand side [size: n x 1]
+/// # returns
+/// the solution x [size: n x 1]
+pub fn cgsolve(a: &[f32], b: &[f32]) -> LiquidResult<Vec<f32>> {
+    check_system(a.len(), b.len())?;
+    let mut x = vec![0f32; b.len()];
+    unsafe {
+        raw::matrixf_cgsolve(
+            a.as_ptr() as _,
+            b.len() as _,
+            b.as_ptr() as _,
+            x.as_mut_ptr(),
+            std::ptr::null_mut(),
+        );
+    }
+    Ok(x)
+}
+
+/// solve A*x = b with the conjugate gradient method, for a complex
+/// Hermitian positive definite A
+///
+/// The system is solved through its real equivalent of size 2n,
+///   [Re(A) -Im(A)] [Re(x)]   [Re(b)]
+///   [Im(A)  Re(A)] [Im(x)] = [Im(b)]
+/// which is symmetric positive definite whenever A is Hermitian positive
+/// definite.
+///  a      :   Hermitian positive definite matrix [size: n x n]
+///  b      :   right-hand side [size: n x 1]
+pub fn cgsolve_hermitian(a: &[Complex32], b: &[Complex32]) -> LiquidResult<Vec<Complex32>> {
+    check_system(a.len(), b.len())?;
+    let n = b.len();
+    let mut ar = vec![0f32; 4 * n * n];
+    for i in 0..n {
+        for j in 0..n {
+            let v = a[i * n + j];
+            ar[i * 2 * n + j] = v.re;
+            ar[i * 2 * n + j + n] = -v.im;
+            ar[(i + n) * 2 * n + j] = v.im;
+            ar[(i + n) * 2 * n + j + n] = v.re;
+        }
+    }
+    let br: Vec<f32> = b
+        .iter()
+        .map(|v| v.re)
+        .chain(b.iter().map(|v| v.im))
+        .collect();
+    let xr = cgsolve(&ar, &br)?;
+    Ok((0..n).map(|i| Complex32::new(xr[i], xr[i + n])).collect())
+}
+
+/// solve A*x = b with Gauss-Jordan elimination
+///  a      :   non-singular matrix [size: n x n]
+///  b      :   right-hand side [size: n x 1]
+pub fn linsolve(a: &[f32], b: &[f32]) -> LiquidResult<Vec<f32>> {
+    check_system(a.len(), b.len())?;
+    let mut x = vec![0f32; b.len()];
+    unsafe {
+        raw::matrixf_linsolve(
+            a.as_ptr() as _,
+            b.len() as _,
+            b.as_ptr() as _,
+            x.as_mut_ptr(),
+            std::ptr::null_mut(),
+        );
+    }
+    Ok(x)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{cgsolve, cgsolve_hermitian};
+    use num::complex::Complex32;
+
+    #[test]
+    fn test_cgsolve_lengths() {
+        assert!(cgsolve(&[1.0, 0.0, 0.0], &[1.0, 2.0]).is_err());
+        assert!(cgsolve(&[], &[]).is_err());
+        let x = cgsolve(&[2.0, 0.0, 0.0, 4.0], &[1.0, 2.0]).unwrap();
+        assert!((x[0] - 0.5).abs() < 1e-5 && (x[1] - 0.5).abs() < 1e-5);
+        let a = [Complex32::new(1.0, 0.0); 4];
+        assert!(cgsolve_hermitian(&a, &a[..1]).is_err());
+
+        // A = [2 j; -j 3], x = [1, 1+j]
+        let a = [
+            Complex32::new(2.0, 0.0),
+            Complex32::new(0.0, 1.0),
+            Complex32::new(0.0, -1.0),
+            Complex32::new(3.0, 0.0),
+        ];
+        let b = [Complex32::new(1.0, 1.0), Complex32::new(3.0, 2.0)];
+        let x = cgsolve_hermitian(&a, &b).unwrap();
+        assert!((x[0] - Complex32::new(1.0, 0.0)).norm() < 1e-4);
+        assert!((x[1] - Complex32::new(1.0, 1.0)).norm() < 1e-4);
+    }
+}
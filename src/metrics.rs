@@ -0,0 +1,259 @@
+//! Measurements for test benches: error vector magnitude, bit error rate
+//! and signal-to-noise ratio estimates
+use num::complex::Complex32;
+
+use crate::errors::LiquidError;
+use crate::modem::Slicer;
+use crate::LiquidResult;
+
+fn check_lengths(x: usize, y: usize) -> LiquidResult<()> {
+    if x != y || x == 0 {
+        return Err(LiquidError::InvalidLength {
+            description: format!("lengths: {} and {}, must be equal and non zero", x, y),
+        });
+    }
+    Ok(())
+}
+
+/// error vector magnitude, the rms of the error relative to the rms of
+/// the reference symbols
+///  x      :   received symbols
+///  ref_   :   reference symbols [size: x.len() x 1]
+pub fn evm(x: &[Complex32], ref_: &[Complex32]) -> LiquidResult<f32> {
+    check_lengths(x.len(), ref_.len())?;
+    let error: f32 = x.iter().zip(ref_).map(|(a, b)| (a - b).norm_sqr()).sum();
+    let power: f32 = ref_.iter().map(|b| b.norm_sqr()).sum();
+    if power == 0f32 {
+        return Err(LiquidError::InvalidValue(
+            "reference symbols have no energy".to_owned(),
+        ));
+    }
+    Ok((error / power).sqrt())
+}
+
+/// error vector magnitude [dB]
+pub fn evm_db(x: &[Complex32], ref_: &[Complex32]) -> LiquidResult<f32> {
+    Ok(20.0 * evm(x, ref_)?.log10())
+}
+
+/// decision-directed error vector magnitude, the reference symbols being
+/// the slicer decisions for the received ones, when the transmitted
+/// symbols are unknown; only meaningful while few decisions are wrong
+///  x      :   received symbols
+///  slicer :   slicer of the modulation scheme of x
+pub fn evm_decision(x: &[Complex32], slicer: &mut Slicer) -> LiquidResult<f32> {
+    let mut ref_ = vec![Complex32::default(); x.len()];
+    slicer.decide_block(x, &mut ref_)?;
+    evm(x, &ref_)
+}
+
+/// number of differing bits between two byte slices
+pub fn bit_errors(x: &[u8], y: &[u8]) -> LiquidResult<usize> {
+    check_lengths(x.len(), y.len())?;
+    Ok(x.iter()
+        .zip(y)
+        .map(|(a, b)| (a ^ b).count_ones() as usize)
+        .sum())
+}
+
+/// bit error rate between two slices of packed bytes
+pub fn ber(x: &[u8], y: &[u8]) -> LiquidResult<f32> {
+    Ok(bit_errors(x, y)? as f32 / (8 * x.len()) as f32)
+}
+
+/// bit error rate between two slices with one bit per element
+pub fn ber_bits(x: &[u8], y: &[u8]) -> LiquidResult<f32> {
+    check_lengths(x.len(), y.len())?;
+    let errors = x.iter().zip(y).filter(|(a, b)| (*a ^ *b) & 1 != 0).count();
+    Ok(errors as f32 / x.len() as f32)
+}
+
+/// bit error rate between the transmitted bits and received bits that lag
+/// by an unknown number of bits, one bit per element
+///  tx         :   transmitted bits
+///  rx         :   received bits
+///  max_delay  :   largest delay searched
+/// # returns
+/// the delay with the fewest errors and the bit error rate at that delay,
+/// measured over the overlap of both slices
+pub fn ber_bits_delay(tx: &[u8], rx: &[u8], max_delay: usize) -> LiquidResult<(usize, f32)> {
+    let mut best: Option<(usize, f32)> = None;
+    for delay in 0..=max_delay.min(rx.len()) {
+        let n = tx.len().min(rx.len() - delay);
+        if n == 0 {
+            break;
+        }
+        let rate = ber_bits(&tx[..n], &rx[delay..delay + n])?;
+        if best.is_none_or(|(_, b)| rate < b) {
+            best = Some((delay, rate));
+        }
+    }
+    best.ok_or_else(|| LiquidError::InvalidLength {
+        description: "no overlap between transmitted and received bits".to_owned(),
+    })
+}
+
+/// data-aided signal-to-noise ratio [dB]
+///  x      :   received symbols
+///  ref_   :   transmitted symbols [size: x.len() x 1]
+pub fn snr_db(x: &[Complex32], ref_: &[Complex32]) -> LiquidResult<f32> {
+    Ok(-evm_db(x, ref_)?)
+}
+
+/// blind signal-to-noise ratio [dB] of constant-modulus symbols (e.g.
+/// PSK) in Gaussian noise, from the second and fourth order moments
+///  x      :   received symbols, one sample per symbol
+pub fn snr_m2m4_db(x: &[Complex32]) -> LiquidResult<f32> {
+    if x.is_empty() {
+        return Err(LiquidError::EmptyBuffer);
+    }
+    let n = x.len() as f32;
+    let m2 = x.iter().map(|v| v.norm_sqr()).sum::<f32>() / n;
+    let m4 = x.iter().map(|v| v.norm_sqr().powi(2)).sum::<f32>() / n;
+    let signal = (2.0 * m2 * m2 - m4).max(0.0).sqrt();
+    let noise = m2 - signal;
+    if noise <= 0f32 {
+        return Ok(f32::INFINITY);
+    }
+    Ok(10.0 * (signal / noise).log10())
+}
+
+/// tracks the noise floor of a spectrum as a low percentile of its power
+/// spectral density frames, e.g. those of SpgramCf::get_psd()
+///
+/// Signals occupying less than 1 - percentile of the band barely move the
+/// estimate, which makes it usable to set a squelch threshold while the
+/// band is busy. For a periodogram normalized as liquid's spgram, the
+/// floor [dB] is the noise power in the whole band, comparable with the
+/// rssi of an agc.
+pub struct NoiseFloorEstimator {
+    percentile: f32,
+    alpha: f32,
+    floor: Option<f32>,
+    scratch: Vec<f32>,
+}
+
+impl NoiseFloorEstimator {
+    /// create a noise floor estimator
+    ///  percentile :   percentile of the bins taken as the floor, in [0, 1]
+    ///  alpha      :   smoothing factor between frames, in (0, 1]; 1 keeps
+    ///                 the estimate of the last frame only
+    pub fn new(percentile: f32, alpha: f32) -> LiquidResult<Self> {
+        if !(0f32..=1f32).contains(&percentile) {
+            return Err(LiquidError::InvalidValue(
+                "percentile must be in [0, 1]".to_owned(),
+            ));
+        } else if alpha <= 0f32 || alpha > 1f32 {
+            return Err(LiquidError::InvalidValue(
+                "smoothing factor must be in (0, 1]".to_owned(),
+            ));
+        }
+        Ok(Self {
+            percentile,
+            alpha,
+            floor: None,
+            scratch: Vec::new(),
+        })
+    }
+
+    /// update the estimate with a psd frame [dB]; non-finite bins, e.g.
+    /// those of an empty periodogram, are ignored
+    /// # returns
+    /// the updated noise floor [dB]
+    pub fn update(&mut self, psd: &[f32]) -> LiquidResult<f32> {
+        self.scratch.clear();
+        self.scratch
+            .extend(psd.iter().copied().filter(|v| v.is_finite()));
+        if self.scratch.is_empty() {
+            return Err(LiquidError::EmptyBuffer);
+        }
+        let k = (self.percentile * (self.scratch.len() - 1) as f32).round() as usize;
+        let (_, &mut level, _) = self
+            .scratch
+            .select_nth_unstable_by(k, |a, b| a.partial_cmp(b).expect("finite values"));
+        let floor = match self.floor {
+            Some(floor) => floor + self.alpha * (level - floor),
+            None => level,
+        };
+        self.floor = Some(floor);
+        Ok(floor)
+    }
+
+    /// current noise floor [dB], None until the first update
+    pub fn floor_db(&self) -> Option<f32> {
+        self.floor
+    }
+
+    pub fn reset(&mut self) {
+        self.floor = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ber, ber_bits_delay, evm, evm_db, evm_decision, snr_m2m4_db, NoiseFloorEstimator};
+    use crate::modem::{ModulationScheme, Slicer};
+    use num::complex::Complex32;
+
+    #[test]
+    fn test_evm() {
+        let ref_ = [Complex32::new(1.0, 0.0), Complex32::new(0.0, -1.0)];
+        let x = [Complex32::new(1.1, 0.0), Complex32::new(0.0, -0.9)];
+        assert!((evm(&x, &ref_).unwrap() - 0.1).abs() < 1e-6);
+        assert!((evm_db(&x, &ref_).unwrap() + 20.0).abs() < 1e-4);
+        assert!(evm(&x[..1], &ref_).is_err());
+    }
+
+    #[test]
+    fn test_evm_decision() {
+        let mut slicer = Slicer::create(ModulationScheme::BPSK).unwrap();
+        let x = [Complex32::new(1.1, 0.0), Complex32::new(-0.9, 0.0)];
+        assert!((evm_decision(&x, &mut slicer).unwrap() - 0.1).abs() < 1e-6);
+        assert!(evm_decision(&[], &mut slicer).is_err());
+    }
+
+    #[test]
+    fn test_ber() {
+        assert_eq!(ber(&[0xff, 0x00], &[0xfe, 0x00]).unwrap(), 1.0 / 16.0);
+
+        let tx: Vec<u8> = (0..64u32)
+            .map(|i| ((i * i * 13 + i * 7) % 11 < 5) as u8)
+            .collect();
+        let mut rx = vec![0u8; 5];
+        rx.extend_from_slice(&tx);
+        rx[20] ^= 1;
+        let (delay, rate) = ber_bits_delay(&tx, &rx, 10).unwrap();
+        assert_eq!(delay, 5);
+        assert_eq!(rate, 1.0 / 64.0);
+    }
+
+    #[test]
+    fn test_snr_m2m4() {
+        let x: Vec<Complex32> = (0..4000)
+            .map(|k| {
+                let s = Complex32::from_polar(1.0, std::f32::consts::FRAC_PI_2 * (k % 4) as f32);
+                s + Complex32::from_polar(0.1, 2.4 * k as f32)
+            })
+            .collect();
+        assert!((snr_m2m4_db(&x).unwrap() - 20.0).abs() < 0.5);
+    }
+
+    #[test]
+    fn test_noise_floor_estimator() {
+        assert!(NoiseFloorEstimator::new(1.5, 0.5).is_err());
+        assert!(NoiseFloorEstimator::new(0.2, 0.0).is_err());
+
+        let mut est = NoiseFloorEstimator::new(0.2, 0.5).unwrap();
+        assert_eq!(est.floor_db(), None);
+        assert!(est.update(&[f32::NEG_INFINITY; 4]).is_err());
+
+        // noise at -60 dB with a strong carrier in a few bins
+        let mut psd = vec![-60f32; 64];
+        psd[30..34].iter_mut().for_each(|v| *v = -10.0);
+        assert_eq!(est.update(&psd).unwrap(), -60.0);
+        let psd = vec![-50f32; 64];
+        assert_eq!(est.update(&psd).unwrap(), -55.0);
+        est.reset();
+        assert_eq!(est.floor_db(), None);
+    }
+}
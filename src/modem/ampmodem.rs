@@ -3,10 +3,12 @@ use std::fmt;
 
 use num::complex::Complex32;
 
+use crate::errors::ModemError;
 use crate::liquid_dsp_sys as raw;
 use crate::modem::AmpModemType;
 
-use crate::utils::{ToCPointer, ToCPointerMut, ToCValue};
+use crate::utils::{check_same_len_as, ToCPointer, ToCPointerMut, ToCValue};
+use crate::LiquidResult;
 
 pub struct AmpModem {
     inner: raw::ampmodem,
@@ -45,7 +47,7 @@ impl AmpModem {
         unsafe { raw::ampmodem_get_delay_demod(self.inner) as u32 }
     }
 
-    pub fn modulate(&self, sample: f32) -> Complex32 {
+    pub fn modulate(&mut self, sample: f32) -> Complex32 {
         let mut out = Complex32::default();
         unsafe {
             raw::ampmodem_modulate(self.inner, sample, out.to_ptr_mut());
@@ -53,11 +55,22 @@ impl AmpModem {
         }
     }
 
-    pub fn modulate_block(&self, samples: &[f32], output: &mut [Complex32]) {
-        assert!(
-            samples.len() == output.len(),
-            "Input and output buffers with different length"
-        );
+    /// # panics
+    /// if the buffers have different lengths, see try_modulate_block
+    pub fn modulate_block(&mut self, samples: &[f32], output: &mut [Complex32]) {
+        self.try_modulate_block(samples, output)
+            .unwrap_or_else(|e| panic!("{}", e));
+    }
+
+    /// modulate a block of samples
+    /// # returns
+    /// ModemError::InvalidLength if the buffers have different lengths
+    pub fn try_modulate_block(
+        &mut self,
+        samples: &[f32],
+        output: &mut [Complex32],
+    ) -> LiquidResult<()> {
+        check_same_len_as::<ModemError>(samples.len(), output.len())?;
         unsafe {
             raw::ampmodem_modulate_block(
                 self.inner,
@@ -66,9 +79,18 @@ impl AmpModem {
                 output.to_ptr_mut(),
             );
         }
+        Ok(())
+    }
+
+    /// modulate a block of samples into a newly allocated buffer; the
+    /// output is delayed by get_delay_mod() samples
+    pub fn modulate_vec(&mut self, samples: &[f32]) -> Vec<Complex32> {
+        let mut output = vec![Complex32::default(); samples.len()];
+        self.modulate_block(samples, &mut output);
+        output
     }
 
-    pub fn demodulate(&self, sample: Complex32) -> f32 {
+    pub fn demodulate(&mut self, sample: Complex32) -> f32 {
         let ptr = &mut 0f32 as *mut f32;
         unsafe {
             raw::ampmodem_demodulate(self.inner, sample.to_c_value(), ptr);
@@ -76,11 +98,22 @@ impl AmpModem {
         }
     }
 
-    pub fn demodulate_block(&self, samples: &[Complex32], output: &mut [f32]) {
-        assert!(
-            samples.len() == output.len(),
-            "Input and output buffers with different length"
-        );
+    /// # panics
+    /// if the buffers have different lengths, see try_demodulate_block
+    pub fn demodulate_block(&mut self, samples: &[Complex32], output: &mut [f32]) {
+        self.try_demodulate_block(samples, output)
+            .unwrap_or_else(|e| panic!("{}", e));
+    }
+
+    /// demodulate a block of samples
+    /// # returns
+    /// ModemError::InvalidLength if the buffers have different lengths
+    pub fn try_demodulate_block(
+        &mut self,
+        samples: &[Complex32],
+        output: &mut [f32],
+    ) -> LiquidResult<()> {
+        check_same_len_as::<ModemError>(samples.len(), output.len())?;
         unsafe {
             raw::ampmodem_demodulate_block(
                 self.inner,
@@ -89,6 +122,15 @@ impl AmpModem {
                 output.as_mut_ptr(),
             );
         }
+        Ok(())
+    }
+
+    /// demodulate a block of samples into a newly allocated buffer; the
+    /// output is delayed by get_delay_demod() samples
+    pub fn demodulate_vec(&mut self, samples: &[Complex32]) -> Vec<f32> {
+        let mut output = vec![0f32; samples.len()];
+        self.demodulate_block(samples, &mut output);
+        output
     }
 }
 
@@ -3,21 +3,96 @@
 use num::complex::Complex32;
 use crate::liquid_dsp_sys as raw;
 
+use std::convert::TryFrom;
+
 use crate::utils::{ToCPointer, ToCPointerMut};
 
-use crate::errors::LiquidError;
+use crate::errors::ModemError;
+use crate::fft::SpgramCf;
+use crate::modem::CpfskFilterType;
+use crate::random;
 use crate::LiquidResult;
 
-pub struct CpfskDem(raw::cpfskdem);
-pub struct CpfskMod(raw::cpfskmod);
+pub struct CpfskDem {
+    inner: raw::cpfskdem,
+    k: u32,
+    bps: u32,
+    h: f32,
+    m: u32,
+    beta: f32,
+    type_: CpfskFilterType,
+    /// symbols demodulate_block still has to drop to compensate the delay
+    /// of the link, None until the first block
+    pending: Option<usize>,
+}
+
+pub struct CpfskMod {
+    inner: raw::cpfskmod,
+    k: u32,
+    bps: u32,
+    h: f32,
+    m: u32,
+    beta: f32,
+    type_: CpfskFilterType,
+}
 
 impl CpfskDem {
     /// demodulate array of samples
     ///  y      :   input sample array [size: _k x 1]
     /// # Returns
     /// Demodulated symbol
-    pub fn demodulate(&self, y: &[Complex32]) -> u32 {
-        unsafe { raw::cpfskdem_demodulate(self.0, y.to_ptr() as _) as _ }
+    pub fn demodulate(&mut self, y: &[Complex32]) -> u32 {
+        assert!(
+            y.len() == self.k as usize,
+            "y.len() is not equal to the samples per symbol"
+        );
+        unsafe { raw::cpfskdem_demodulate(self.inner, y.to_ptr() as _) as _ }
+    }
+
+    /// delay of a CpfskMod with the same parameters followed by this
+    /// demodulator [symbols]
+    pub fn get_link_delay(&self) -> LiquidResult<usize> {
+        let modulator = CpfskMod::create(self.bps, self.h, self.k, self.m, self.beta, self.type_)?;
+        Ok(modulator.get_delay() + self.get_delay())
+    }
+
+    /// demodulate a block of samples, k samples per symbol
+    ///  y      :   input sample array [size: k*n x 1]
+    /// # returns
+    /// the demodulated symbols, aligned with the symbols given to a
+    /// CpfskMod with the same parameters: the first get_link_delay()
+    /// symbols of the stream, which only hold the filters start-up, are
+    /// dropped, so feed the modulator that many extra symbols (e.g. zeros)
+    /// to flush the last ones out. The following blocks continue the
+    /// stream until reset().
+    /// ModemError::InvalidLength if y.len() is not a multiple of k
+    pub fn demodulate_block(&mut self, y: &[Complex32]) -> LiquidResult<Vec<u32>> {
+        let k = self.k as usize;
+        if !y.len().is_multiple_of(k) {
+            return Err(ModemError::InvalidLength {
+                description: format!(
+                    "samples length: {} must be a multiple of samples/symbol: {}",
+                    y.len(),
+                    k
+                ),
+            }
+            .into());
+        }
+        let pending = match self.pending {
+            Some(n) => n,
+            None => self.get_link_delay()?,
+        };
+        let symbols: Vec<u32> = y.chunks(k).map(|chunk| self.demodulate(chunk)).collect();
+        let skip = pending.min(symbols.len());
+        self.pending = Some(pending - skip);
+        Ok(symbols[skip..].to_vec())
+    }
+
+    /// reset the demodulator, demodulate_block() compensating the delay of
+    /// the link again
+    pub fn reset(&mut self) {
+        self.pending = None;
+        unsafe { raw::cpfskdem_reset(self.inner) }
     }
 }
 
@@ -25,19 +100,65 @@ impl CpfskMod {
     /// modulate sample
     ///  s      :   input symbol
     ///  y      :   output sample array [size: _k x 1]
-    pub fn modulate(&self, s: u32, y: &mut [Complex32]) {
+    pub fn modulate(&mut self, s: u32, y: &mut [Complex32]) {
+        assert!(
+            y.len() == self.k as usize,
+            "y.len() is not equal to the samples per symbol"
+        );
         unsafe {
-            raw::cpfskmod_modulate(self.0, s as _, y.to_ptr_mut() as _);
+            raw::cpfskmod_modulate(self.inner, s as _, y.to_ptr_mut() as _);
+        }
+    }
+
+    /// power spectral density of the configured modulation [dB], estimated
+    /// by modulating random symbols with a copy of the modulator, so this
+    /// object's state is not changed; bin 0 is the frequency -0.5
+    ///  nfft           :   number of frequency bins, at least 2
+    ///  num_symbols    :   number of random symbols to average over, > 0
+    pub fn spectrum(&self, nfft: usize, num_symbols: usize) -> LiquidResult<Vec<f32>> {
+        if num_symbols == 0 {
+            return Err(ModemError::InvalidValue(
+                "number of symbols must be greater than 0".to_owned(),
+            )
+            .into());
+        }
+        let mut spgram = SpgramCf::create_default(nfft)?;
+        let mut modulator = Self::create(self.bps, self.h, self.k, self.m, self.beta, self.type_)?;
+        let num_levels = 1u32 << self.bps;
+        let mut y = vec![Complex32::default(); self.k as usize];
+        for _ in 0..num_symbols {
+            let s = ((random::uniform() * num_levels as f32) as u32).min(num_levels - 1);
+            modulator.modulate(s, &mut y);
+            spgram.write(&y);
         }
+        Ok(spgram.get_psd())
+    }
+
+    /// modulate a block of symbols
+    ///  symbols    :   input symbols [size: n x 1]
+    /// # returns
+    /// the modulated samples [size: k*n x 1], delayed by get_delay()
+    /// symbols
+    pub fn modulate_block(&mut self, symbols: &[u32]) -> Vec<Complex32> {
+        let k = self.k as usize;
+        let mut y = vec![Complex32::default(); symbols.len() * k];
+        for (&s, chunk) in symbols.iter().zip(y.chunks_mut(k)) {
+            self.modulate(s, chunk);
+        }
+        y
+    }
+
+    pub fn reset(&mut self) {
+        unsafe { raw::cpfskmod_reset(self.inner) }
     }
 }
 
 macro_rules! cpfsk_impl {
     ($obj:ty, ($create:expr,
-        $reset:expr,
         $print:expr,
         $delay:expr,
-        $destroy:expr)) => {
+        $destroy:expr)
+        $(, $field:ident: $init:expr)*) => {
         impl $obj {
             /// create cpfsk object (frequency demodulator)
             ///  bps    :   bits per symbol, _bps > 0
@@ -45,57 +166,121 @@ macro_rules! cpfsk_impl {
             ///  k      :   samples/symbol, _k > 1, _k even
             ///  m      :   filter delay (symbols), _m > 0
             ///  beta   :   filter bandwidth parameter, _beta > 0
-            ///  type_  :   filter type (e.g. LIQUID_CPFSK_SQUARE)
+            ///  type_  :   filter type (e.g. CpfskFilterType::SQUARE)
             pub fn create(
                 bps: u32,
                 h: f32,
                 k: u32,
                 m: u32,
                 beta: f32,
-                type_: i32,
+                type_: CpfskFilterType,
             ) -> LiquidResult<$obj> {
                 if bps == 0 || m == 0 {
-                    return Err(LiquidError::InvalidValue(format!(
+                    return Err(ModemError::InvalidValue(format!(
                         "bps: {} and m: {} must be higher that 0",
                         bps, m
-                    )));
+                    ))
+                    .into());
                 } else if k < 2 || k % 2 != 0 {
-                    return Err(LiquidError::InvalidValue(format!(
+                    return Err(ModemError::InvalidValue(format!(
                         "k {}  must be higher than 2 and even",
                         k
-                    )));
+                    ))
+                    .into());
                 } else if beta <= 0f32 || beta >= 1f32 {
-                    return Err(LiquidError::InvalidValue(format!(
+                    return Err(ModemError::InvalidValue(format!(
                         "beta: {} must be in (0, 1.0)",
                         beta
-                    )));
+                    ))
+                    .into());
                 } else if h <= 0.0 {
-                    return Err(LiquidError::InvalidValue(format!(
+                    return Err(ModemError::InvalidValue(format!(
                         "h: {}  must be higher than 0",
                         h
-                    )));
+                    ))
+                    .into());
                 }
 
-                Ok(unsafe { Self($create(bps as _, h, k as _, m as _, beta, type_ as _)) })
+                Ok(Self {
+                    inner: unsafe {
+                        $create(bps as _, h, k as _, m as _, beta, i32::from(type_) as _)
+                    },
+                    k,
+                    bps,
+                    h,
+                    m,
+                    beta,
+                    type_,
+                    $($field: $init,)*
+                })
+            }
+
+            /// create a GMSK object, binary CPFSK with a modulation index
+            /// of 0.5 and a Gaussian pulse shape
+            ///  k      :   samples/symbol, _k > 1, _k even
+            ///  m      :   filter delay (symbols), _m > 0
+            ///  bt     :   bandwidth-time product, in (0, 1), e.g. 0.3
+            pub fn create_gmsk(k: u32, m: u32, bt: f32) -> LiquidResult<$obj> {
+                Self::create(1, 0.5, k, m, bt, CpfskFilterType::GMSK)
             }
 
-            pub fn reset(&self) {
-                unsafe { $reset(self.0) }
+            /// create cpfsk object from liquid's integer filter type
+            /// (e.g. LIQUID_CPFSK_SQUARE), see create
+            #[deprecated(note = "use create with a CpfskFilterType")]
+            pub fn create_raw(
+                bps: u32,
+                h: f32,
+                k: u32,
+                m: u32,
+                beta: f32,
+                type_: i32,
+            ) -> LiquidResult<$obj> {
+                Self::create(bps, h, k, m, beta, CpfskFilterType::try_from(type_)?)
             }
 
             pub fn print(&self) {
-                unsafe { $print(self.0) }
+                unsafe { $print(self.inner) }
             }
 
+            /// delay through the object [symbols]
             pub fn get_delay(&self) -> usize {
-                unsafe { $delay(self.0) as _ }
+                unsafe { $delay(self.inner) as _ }
+            }
+
+            /// samples per symbol
+            pub fn get_samples_per_symbol(&self) -> u32 {
+                self.k
+            }
+
+            /// bits per symbol
+            pub fn get_bps(&self) -> u32 {
+                self.bps
+            }
+
+            /// modulation index
+            pub fn get_h(&self) -> f32 {
+                self.h
+            }
+
+            /// filter delay [symbols]
+            pub fn get_m(&self) -> u32 {
+                self.m
+            }
+
+            /// filter bandwidth parameter
+            pub fn get_beta(&self) -> f32 {
+                self.beta
+            }
+
+            pub fn get_filter_type(&self) -> CpfskFilterType {
+                self.type_
             }
         }
 
         impl Drop for $obj {
             fn drop(&mut self) {
                 unsafe {
-                    $destroy(self.0);
+                    $destroy(self.inner);
                 }
             }
         }
@@ -106,20 +291,117 @@ cpfsk_impl!(
     CpfskDem,
     (
         raw::cpfskdem_create,
-        raw::cpfskdem_reset,
         raw::cpfskdem_print,
         raw::cpfskdem_get_delay,
         raw::cpfskdem_destroy
-    )
+    ),
+    pending: None
 );
 
 cpfsk_impl!(
     CpfskMod,
     (
         raw::cpfskmod_create,
-        raw::cpfskmod_reset,
         raw::cpfskmod_print,
         raw::cpfskmod_get_delay,
         raw::cpfskmod_destroy
     )
 );
+
+#[cfg(test)]
+mod tests {
+    use super::{CpfskDem, CpfskMod};
+    use crate::modem::CpfskFilterType;
+    use std::convert::TryFrom;
+
+    #[test]
+    fn test_cpfsk_block_len() {
+        let mut modulator = CpfskMod::create(1, 0.5, 4, 3, 0.35, CpfskFilterType::SQUARE).unwrap();
+        let mut demodulator =
+            CpfskDem::create(1, 0.5, 4, 3, 0.35, CpfskFilterType::SQUARE).unwrap();
+        assert_eq!(modulator.get_samples_per_symbol(), 4);
+        assert_eq!(demodulator.get_m(), 3);
+        assert_eq!(demodulator.get_filter_type(), CpfskFilterType::SQUARE);
+        assert_eq!(
+            CpfskFilterType::try_from(i32::from(CpfskFilterType::GMSK)).unwrap(),
+            CpfskFilterType::GMSK
+        );
+        assert!(CpfskFilterType::try_from(4).is_err());
+
+        let y = modulator.modulate_block(&[0, 1, 1, 0, 1]);
+        assert_eq!(y.len(), 20);
+        assert!(demodulator.demodulate_block(&y[..19]).is_err());
+    }
+
+    #[test]
+    fn test_cpfsk_block_round_trip() {
+        for &(bps, h, type_) in &[
+            (1, 0.5, CpfskFilterType::SQUARE),
+            (2, 0.5, CpfskFilterType::RCOS_FULL),
+            (1, 0.5, CpfskFilterType::GMSK),
+        ] {
+            let mut modulator = CpfskMod::create(bps, h, 8, 3, 0.35, type_).unwrap();
+            let mut demodulator = CpfskDem::create(bps, h, 8, 3, 0.35, type_).unwrap();
+            let delay = demodulator.get_link_delay().unwrap();
+            assert_eq!(delay, modulator.get_delay() + demodulator.get_delay());
+
+            let mut state = 11u32;
+            let mut symbols: Vec<u32> = (0..200)
+                .map(|_| {
+                    state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+                    (state >> 16) % (1 << bps)
+                })
+                .collect();
+            let n = symbols.len();
+            symbols.resize(n + delay, 0);
+            let y = modulator.modulate_block(&symbols);
+
+            // the delay is compensated once across the blocks
+            let split = 8 * (delay + 10);
+            let mut rx = demodulator.demodulate_block(&y[..split]).unwrap();
+            rx.extend(demodulator.demodulate_block(&y[split..]).unwrap());
+            assert_eq!(rx, &symbols[..n], "{:?}", type_);
+
+            demodulator.reset();
+            modulator.reset();
+            let y = modulator.modulate_block(&symbols);
+            assert_eq!(demodulator.demodulate_block(&y).unwrap(), &symbols[..n]);
+        }
+    }
+
+    /// bandwidth holding 99% of the power of a psd in dB
+    fn occupied_bandwidth(psd: &[f32]) -> f32 {
+        let p: Vec<f32> = psd.iter().map(|v| 10f32.powf(v / 10.0)).collect();
+        let total: f32 = p.iter().sum();
+        let c = p.len() / 2;
+        let mut acc = p[c];
+        let mut w = 0;
+        while acc < 0.99 * total && w < c {
+            w += 1;
+            acc += p[c - w] + p.get(c + w).copied().unwrap_or(0.0);
+        }
+        (2 * w + 1) as f32 / p.len() as f32
+    }
+
+    #[test]
+    fn test_cpfsk_modulation_index_sweep() {
+        let gmsk = CpfskMod::create_gmsk(8, 3, 0.3).unwrap();
+        assert_eq!(gmsk.get_filter_type(), CpfskFilterType::GMSK);
+        assert_eq!(gmsk.get_h(), 0.5);
+        assert!(gmsk.spectrum(256, 0).is_err());
+
+        let bandwidths: Vec<f32> = [0.25, 0.5, 1.0, 2.0]
+            .iter()
+            .map(|&h| {
+                let q = CpfskMod::create(1, h, 8, 3, 0.5, CpfskFilterType::SQUARE).unwrap();
+                let psd = q.spectrum(256, 2000).unwrap();
+                assert_eq!(psd.len(), 256);
+                occupied_bandwidth(&psd)
+            })
+            .collect();
+        // Carson's rule: the bandwidth grows with the modulation index
+        for w in bandwidths.windows(2) {
+            assert!(w[1] > w[0], "{:?}", bandwidths);
+        }
+    }
+}
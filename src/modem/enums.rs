@@ -1,7 +1,16 @@
+use std::convert::TryFrom;
+use std::ffi::{CStr, CString};
 use std::fmt;
-use std::mem::transmute;
+use std::str::FromStr;
+
+use crate::errors::{LiquidError, ModemError};
+use crate::liquid_dsp_sys as raw;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Copy, Eq, PartialEq)]
+#[repr(u8)]
 pub enum AmpModemType {
     Dsb,
     Usb,
@@ -10,18 +19,27 @@ pub enum AmpModemType {
 
 impl From<AmpModemType> for u8 {
     fn from(value: AmpModemType) -> u8 {
-        unsafe { transmute::<AmpModemType, u8>(value) }
+        value as u8
     }
 }
 
-impl From<u8> for AmpModemType {
-    fn from(value: u8) -> Self {
-        if value > 2 {
-            unimplemented!();
+impl TryFrom<u8> for AmpModemType {
+    type Error = LiquidError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(AmpModemType::Dsb),
+            1 => Ok(AmpModemType::Usb),
+            2 => Ok(AmpModemType::Lsb),
+            _ => Err(ModemError::InvalidValue(format!(
+                "{} is not a valid amplitude modem type",
+                value
+            ))
+            .into()),
         }
-        unsafe { transmute::<u8, AmpModemType>(value) }
     }
 }
+
 impl fmt::Debug for AmpModemType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let type_ = match self {
@@ -32,3 +50,248 @@ impl fmt::Debug for AmpModemType {
         write!(f, "{}", type_)
     }
 }
+
+/// mapping of the symbols onto the points of an arbitrary constellation
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ArbLabeling {
+    /// symbol s is mapped onto point s
+    Natural,
+    /// symbol gray(i) is mapped onto point i, so that neighbouring points
+    /// of the list differ by a single bit
+    Gray,
+}
+
+/// pulse shape of the CPFSK frequency pulse
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[allow(non_camel_case_types)]
+pub enum CpfskFilterType {
+    SQUARE,
+    RCOS_FULL,
+    RCOS_PARTIAL,
+    GMSK,
+}
+
+impl From<CpfskFilterType> for i32 {
+    fn from(value: CpfskFilterType) -> i32 {
+        match value {
+            CpfskFilterType::SQUARE => raw::liquid_cpfsk_filter_LIQUID_CPFSK_SQUARE as i32,
+            CpfskFilterType::RCOS_FULL => raw::liquid_cpfsk_filter_LIQUID_CPFSK_RCOS_FULL as i32,
+            CpfskFilterType::RCOS_PARTIAL => {
+                raw::liquid_cpfsk_filter_LIQUID_CPFSK_RCOS_PARTIAL as i32
+            }
+            CpfskFilterType::GMSK => raw::liquid_cpfsk_filter_LIQUID_CPFSK_GMSK as i32,
+        }
+    }
+}
+
+impl TryFrom<i32> for CpfskFilterType {
+    type Error = LiquidError;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(CpfskFilterType::SQUARE),
+            1 => Ok(CpfskFilterType::RCOS_FULL),
+            2 => Ok(CpfskFilterType::RCOS_PARTIAL),
+            3 => Ok(CpfskFilterType::GMSK),
+            _ => Err(ModemError::InvalidValue(format!(
+                "{} is not a valid cpfsk filter type",
+                value
+            ))
+            .into()),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[repr(u8)]
+pub enum ModulationScheme {
+    UNKNOWN,
+    PSK2,
+    PSK4,
+    PSK8,
+    PSK16,
+    PSK32,
+    PSK64,
+    PSK128,
+    PSK256,
+    DPSK2,
+    DPSK4,
+    DPSK8,
+    DPSK16,
+    DPSK32,
+    DPSK64,
+    DPSK128,
+    DPSK256,
+    ASK2,
+    ASK4,
+    ASK8,
+    ASK16,
+    ASK32,
+    ASK64,
+    ASK128,
+    ASK256,
+    QAM4,
+    QAM8,
+    QAM16,
+    QAM32,
+    QAM64,
+    QAM128,
+    QAM256,
+    APSK4,
+    APSK8,
+    APSK16,
+    APSK32,
+    APSK64,
+    APSK128,
+    APSK256,
+    BPSK,
+    QPSK,
+    OOK,
+    SQAM32,
+    SQAM128,
+    V29,
+    ARB16OPT,
+    ARB32OPT,
+    ARB64OPT,
+    ARB128OPT,
+    ARB256OPT,
+    ARB64VT,
+    ARB,
+}
+
+impl From<ModulationScheme> for u8 {
+    fn from(value: ModulationScheme) -> u8 {
+        value as u8
+    }
+}
+
+impl TryFrom<u8> for ModulationScheme {
+    type Error = LiquidError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(ModulationScheme::UNKNOWN),
+            1 => Ok(ModulationScheme::PSK2),
+            2 => Ok(ModulationScheme::PSK4),
+            3 => Ok(ModulationScheme::PSK8),
+            4 => Ok(ModulationScheme::PSK16),
+            5 => Ok(ModulationScheme::PSK32),
+            6 => Ok(ModulationScheme::PSK64),
+            7 => Ok(ModulationScheme::PSK128),
+            8 => Ok(ModulationScheme::PSK256),
+            9 => Ok(ModulationScheme::DPSK2),
+            10 => Ok(ModulationScheme::DPSK4),
+            11 => Ok(ModulationScheme::DPSK8),
+            12 => Ok(ModulationScheme::DPSK16),
+            13 => Ok(ModulationScheme::DPSK32),
+            14 => Ok(ModulationScheme::DPSK64),
+            15 => Ok(ModulationScheme::DPSK128),
+            16 => Ok(ModulationScheme::DPSK256),
+            17 => Ok(ModulationScheme::ASK2),
+            18 => Ok(ModulationScheme::ASK4),
+            19 => Ok(ModulationScheme::ASK8),
+            20 => Ok(ModulationScheme::ASK16),
+            21 => Ok(ModulationScheme::ASK32),
+            22 => Ok(ModulationScheme::ASK64),
+            23 => Ok(ModulationScheme::ASK128),
+            24 => Ok(ModulationScheme::ASK256),
+            25 => Ok(ModulationScheme::QAM4),
+            26 => Ok(ModulationScheme::QAM8),
+            27 => Ok(ModulationScheme::QAM16),
+            28 => Ok(ModulationScheme::QAM32),
+            29 => Ok(ModulationScheme::QAM64),
+            30 => Ok(ModulationScheme::QAM128),
+            31 => Ok(ModulationScheme::QAM256),
+            32 => Ok(ModulationScheme::APSK4),
+            33 => Ok(ModulationScheme::APSK8),
+            34 => Ok(ModulationScheme::APSK16),
+            35 => Ok(ModulationScheme::APSK32),
+            36 => Ok(ModulationScheme::APSK64),
+            37 => Ok(ModulationScheme::APSK128),
+            38 => Ok(ModulationScheme::APSK256),
+            39 => Ok(ModulationScheme::BPSK),
+            40 => Ok(ModulationScheme::QPSK),
+            41 => Ok(ModulationScheme::OOK),
+            42 => Ok(ModulationScheme::SQAM32),
+            43 => Ok(ModulationScheme::SQAM128),
+            44 => Ok(ModulationScheme::V29),
+            45 => Ok(ModulationScheme::ARB16OPT),
+            46 => Ok(ModulationScheme::ARB32OPT),
+            47 => Ok(ModulationScheme::ARB64OPT),
+            48 => Ok(ModulationScheme::ARB128OPT),
+            49 => Ok(ModulationScheme::ARB256OPT),
+            50 => Ok(ModulationScheme::ARB64VT),
+            51 => Ok(ModulationScheme::ARB),
+            _ => Err(ModemError::InvalidValue(format!(
+                "{} is not a valid modulation scheme",
+                value
+            ))
+            .into()),
+        }
+    }
+}
+
+impl ModulationScheme {
+    /// liquid name of the scheme, e.g. "qpsk" or "qam16"
+    pub fn as_str(&self) -> &'static str {
+        let index = u8::from(*self) as usize;
+        unsafe {
+            let name = raw::modulation_types[index].name;
+            CStr::from_ptr(name).to_str().unwrap_or("unknown")
+        }
+    }
+}
+
+impl FromStr for ModulationScheme {
+    type Err = LiquidError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let c = CString::new(s).map_err(|_| LiquidError::from(ModemError::InvalidScheme))?;
+        match ModulationScheme::try_from(unsafe { raw::liquid_getopt_str2mod(c.as_ptr()) } as u8) {
+            Ok(ModulationScheme::UNKNOWN) | Err(_) => Err(ModemError::InvalidScheme.into()),
+            Ok(scheme) => Ok(scheme),
+        }
+    }
+}
+
+impl fmt::Display for ModulationScheme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AmpModemType, ModulationScheme};
+    use std::convert::TryFrom;
+
+    #[test]
+    fn test_modulation_scheme_round_trip() {
+        for value in 0..=51u8 {
+            let scheme = ModulationScheme::try_from(value).unwrap();
+            assert_eq!(u8::from(scheme), value);
+        }
+        assert_eq!(
+            ModulationScheme::try_from(25).unwrap(),
+            ModulationScheme::QAM4
+        );
+        assert_eq!(
+            ModulationScheme::try_from(51).unwrap(),
+            ModulationScheme::ARB
+        );
+        assert!(ModulationScheme::try_from(52).is_err());
+    }
+
+    #[test]
+    fn test_ampmodem_type_round_trip() {
+        let types = [AmpModemType::Dsb, AmpModemType::Usb, AmpModemType::Lsb];
+        for (i, &type_) in types.iter().enumerate() {
+            assert_eq!(u8::from(type_), i as u8);
+            assert!(AmpModemType::try_from(i as u8).unwrap() == type_);
+        }
+        assert!(AmpModemType::try_from(3).is_err());
+    }
+}
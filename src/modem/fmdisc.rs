@@ -0,0 +1,259 @@
+//! FM discriminator: estimates the instantaneous frequency of a complex
+//! baseband signal
+use std::collections::VecDeque;
+use std::f32::consts::PI;
+
+use num::complex::Complex32;
+
+use crate::errors::ModemError;
+use crate::filter::{FirFiltCrcf, Firdespm, FirdespmBtype};
+use crate::LiquidResult;
+
+/// frequency estimation method used by the discriminator
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub enum FmDiscMethod {
+    /// differentiator filter (designed with Firdespm) applied to the
+    /// signal, combined with the delayed input:
+    /// Im{ conj(x) * dx } / |x|^2
+    Differentiator,
+    /// delay and conjugate multiply: arg{ x[n] * conj(x[n-1]) }
+    Arctan,
+}
+
+/// builder for the FmDiscriminator object
+#[derive(Clone, Copy, Debug)]
+pub struct FmDiscriminatorBuilder {
+    method: FmDiscMethod,
+    kf: f32,
+    h_len: usize,
+    bandwidth: f32,
+}
+
+impl Default for FmDiscriminatorBuilder {
+    fn default() -> Self {
+        Self {
+            method: FmDiscMethod::Arctan,
+            kf: 1.0,
+            h_len: 21,
+            bandwidth: 0.4,
+        }
+    }
+}
+
+impl FmDiscriminatorBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// select the discriminator method
+    pub fn method(mut self, method: FmDiscMethod) -> Self {
+        self.method = method;
+        self
+    }
+
+    /// modulation factor, the output is scaled by 1/(2*pi*kf)
+    pub fn kf(mut self, kf: f32) -> Self {
+        self.kf = kf;
+        self
+    }
+
+    /// differentiator filter length, must be odd and at least 3
+    pub fn filter_len(mut self, h_len: usize) -> Self {
+        self.h_len = h_len;
+        self
+    }
+
+    /// differentiator pass-band edge, in (0, 0.5)
+    pub fn bandwidth(mut self, bandwidth: f32) -> Self {
+        self.bandwidth = bandwidth;
+        self
+    }
+
+    pub fn build(self) -> LiquidResult<FmDiscriminator> {
+        if self.kf <= 0f32 {
+            return Err(ModemError::InvalidValue("kf must be greater than zero".to_owned()).into());
+        }
+        let (diff, delay) = match self.method {
+            FmDiscMethod::Arctan => (None, 0),
+            FmDiscMethod::Differentiator => {
+                if self.h_len < 3 || self.h_len.is_multiple_of(2) {
+                    return Err(ModemError::InvalidValue(format!(
+                        "filter length: {} must be odd and at least 3",
+                        self.h_len
+                    ))
+                    .into());
+                } else if self.bandwidth <= 0f32 || self.bandwidth >= 0.5 {
+                    return Err(ModemError::InvalidValue(
+                        "bandwidth must be in (0, 0.5)".to_owned(),
+                    )
+                    .into());
+                }
+                let mut h = vec![0f32; self.h_len];
+                Firdespm::run(
+                    self.h_len,
+                    1,
+                    &[0.0, self.bandwidth],
+                    &[1.0],
+                    &[1.0],
+                    None,
+                    FirdespmBtype::DIFFERENTIATOR,
+                    &mut h,
+                )?;
+                let delay = (self.h_len - 1) / 2;
+                normalize_differentiator(&mut h, delay, self.bandwidth / 4.0);
+                (Some(FirFiltCrcf::create(&h)?), delay)
+            }
+        };
+
+        Ok(FmDiscriminator {
+            method: self.method,
+            kf: self.kf,
+            diff,
+            buffer: VecDeque::from(vec![Complex32::default(); delay]),
+            prev: Complex32::default(),
+        })
+    }
+}
+
+/// scale the differentiator taps so that its response matches j*2*pi*f
+/// at the reference frequency f0, whatever the gain used by the designer
+fn normalize_differentiator(h: &mut [f32], delay: usize, f0: f32) {
+    let w = 2.0 * PI * f0;
+    let response = h
+        .iter()
+        .enumerate()
+        .map(|(n, &v)| Complex32::from_polar(v, -w * (n as f32 - delay as f32)))
+        .fold(Complex32::default(), |acc, v| acc + v);
+    let gain = response.im / w;
+    if gain != 0f32 {
+        h.iter_mut().for_each(|v| *v /= gain);
+    }
+}
+
+/// FM discriminator, see FmDiscriminatorBuilder
+pub struct FmDiscriminator {
+    method: FmDiscMethod,
+    kf: f32,
+    diff: Option<FirFiltCrcf>,
+    buffer: VecDeque<Complex32>,
+    prev: Complex32,
+}
+
+impl FmDiscriminator {
+    pub fn builder() -> FmDiscriminatorBuilder {
+        FmDiscriminatorBuilder::default()
+    }
+
+    pub fn method(&self) -> FmDiscMethod {
+        self.method
+    }
+
+    /// delay of the output with respect to the input [samples]
+    pub fn get_delay(&self) -> usize {
+        self.buffer.len()
+    }
+
+    pub fn reset(&mut self) {
+        if let Some(diff) = self.diff.as_mut() {
+            diff.reset();
+        }
+        self.buffer
+            .iter_mut()
+            .for_each(|v| *v = Complex32::default());
+        self.prev = Complex32::default();
+    }
+
+    /// demodulate a single sample
+    ///  x      :   complex baseband input sample
+    /// # Returns
+    /// the instantaneous frequency scaled by 1/(2*pi*kf)
+    pub fn execute(&mut self, x: Complex32) -> f32 {
+        let omega = match self.diff.as_mut() {
+            None => {
+                let omega = (x * self.prev.conj()).arg();
+                self.prev = x;
+                omega
+            }
+            Some(diff) => {
+                diff.push(x);
+                let dx = diff.execute();
+                self.buffer.push_back(x);
+                let xd = self.buffer.pop_front().unwrap_or_default();
+                let energy = xd.norm_sqr();
+                if energy > 0f32 {
+                    (xd.conj() * dx).im / energy
+                } else {
+                    0f32
+                }
+            }
+        };
+        omega / (2.0 * PI * self.kf)
+    }
+
+    /// demodulate a block of samples
+    ///  x      :   complex baseband input [size: _n x 1]
+    ///  y      :   instantaneous frequency output [size: _n x 1]
+    pub fn execute_block(&mut self, x: &[Complex32], y: &mut [f32]) {
+        assert!(
+            x.len() == y.len(),
+            "Input and output buffers with different length"
+        );
+        x.iter()
+            .zip(y.iter_mut())
+            .for_each(|(&sample, out)| *out = self.execute(sample));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FmDiscMethod, FmDiscriminator};
+    use num::complex::Complex32;
+    use std::f32::consts::PI;
+
+    #[test]
+    fn test_fmdisc_arctan_tone() {
+        let mut disc = FmDiscriminator::builder()
+            .method(FmDiscMethod::Arctan)
+            .build()
+            .unwrap();
+        let input: Vec<Complex32> = (0..64)
+            .map(|n| Complex32::from_polar(1.0, 2.0 * PI * 0.05 * n as f32))
+            .collect();
+        let mut output = vec![0f32; input.len()];
+        disc.execute_block(&input, &mut output);
+        for v in output.iter().skip(1) {
+            assert!((v - 0.05).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn test_fmdisc_differentiator_tone() {
+        let mut disc = FmDiscriminator::builder()
+            .method(FmDiscMethod::Differentiator)
+            .kf(0.5)
+            .build()
+            .unwrap();
+        assert_eq!(disc.get_delay(), 10);
+        for &f in [0.05f32, -0.08].iter() {
+            disc.reset();
+            let input: Vec<Complex32> = (0..128)
+                .map(|n| Complex32::from_polar(1.0, 2.0 * PI * f * n as f32))
+                .collect();
+            let mut output = vec![0f32; input.len()];
+            disc.execute_block(&input, &mut output);
+            // the filter and delay line are full after 21 samples
+            for v in output.iter().skip(21) {
+                assert!((v - f / 0.5).abs() < 5e-3);
+            }
+        }
+    }
+
+    #[test]
+    fn test_fmdisc_invalid_len() {
+        let disc = FmDiscriminator::builder()
+            .method(FmDiscMethod::Differentiator)
+            .filter_len(20)
+            .build();
+        assert!(disc.is_err());
+    }
+}
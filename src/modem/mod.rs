@@ -1,7 +1,15 @@
 mod ampmodem;
 mod cpfsk;
 mod enums;
+mod fmdisc;
+#[allow(clippy::module_inception)]
+mod modem;
+mod slicer;
 
 pub use ampmodem::AmpModem;
 pub use cpfsk::{CpfskDem, CpfskMod};
-pub use enums::AmpModemType;
+pub use enums::{AmpModemType, ArbLabeling, CpfskFilterType, ModulationScheme};
+pub use fmdisc::{FmDiscMethod, FmDiscriminator, FmDiscriminatorBuilder};
+pub(crate) use modem::LLR_SOFT_GAIN;
+pub use modem::{llr_to_soft_bits, normalize_constellation, Modem};
+pub use slicer::Slicer;
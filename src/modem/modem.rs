@@ -0,0 +1,457 @@
+//! Generic linear digital modem (PSK, DPSK, ASK, QAM, APSK and
+//! arbitrary constellations)
+use std::convert::TryFrom;
+use std::fmt;
+
+use num::complex::Complex32;
+
+use crate::errors::ModemError;
+use crate::liquid_dsp_sys as raw;
+use crate::modem::{ArbLabeling, ModulationScheme};
+use crate::utils::{ToCPointerMut, ToCValue};
+use crate::LiquidResult;
+
+/// liquid maps the internal LLR of each bit to a soft bit as
+/// soft = LLR * LLR_SOFT_GAIN + 127, clipped to [0, 255]
+pub(crate) const LLR_SOFT_GAIN: f32 = 16.0;
+
+pub struct Modem {
+    inner: raw::modem,
+    llr_scale: f32,
+}
+
+impl Modem {
+    /// create digital modem object
+    ///  scheme :   modulation scheme, must not be ModulationScheme::UNKNOWN
+    pub fn create(scheme: ModulationScheme) -> LiquidResult<Self> {
+        match scheme {
+            ModulationScheme::UNKNOWN | ModulationScheme::ARB => {
+                Err(ModemError::InvalidScheme.into())
+            }
+            _ => Ok(Self {
+                inner: unsafe { raw::modem_create(u8::from(scheme) as _) },
+                llr_scale: 1.0,
+            }),
+        }
+    }
+
+    /// create a modem from an arbitrary constellation; liquid removes the
+    /// mean of the points and scales them to a unit average energy, see
+    /// normalize_constellation
+    ///  points     :   constellation points [size: 2^bps x 1], bps in [1, 8]
+    ///  labeling   :   mapping of the symbols onto the points
+    pub fn create_arbitrary(points: &[Complex32], labeling: ArbLabeling) -> LiquidResult<Self> {
+        let m = points.len();
+        if !(2..=1 << 8).contains(&m) || !m.is_power_of_two() {
+            return Err(ModemError::InvalidLength {
+                description: format!("constellation size: {} must be a power of 2 in [2, 256]", m),
+            }
+            .into());
+        } else if points
+            .iter()
+            .any(|p| !p.re.is_finite() || !p.im.is_finite())
+        {
+            return Err(
+                ModemError::InvalidValue("constellation points must be finite".to_owned()).into(),
+            );
+        }
+        let mut table = points.to_vec();
+        if labeling == ArbLabeling::Gray {
+            for (i, &p) in points.iter().enumerate() {
+                table[i ^ (i >> 1)] = p;
+            }
+        }
+        if normalize_constellation(&table).is_none() {
+            return Err(ModemError::InvalidValue(
+                "constellation points must not all be equal".to_owned(),
+            )
+            .into());
+        }
+        Ok(Self {
+            inner: unsafe { raw::modem_create_arbitrary(table.to_ptr_mut(), m as _) },
+            llr_scale: 1.0,
+        })
+    }
+
+    /// recreate modulation scheme, re-allocating memory as necessary
+    pub fn recreate(mut self, scheme: ModulationScheme) -> LiquidResult<Self> {
+        match scheme {
+            ModulationScheme::UNKNOWN | ModulationScheme::ARB => {
+                Err(ModemError::InvalidScheme.into())
+            }
+            _ => {
+                unsafe {
+                    self.inner = raw::modem_recreate(self.inner, u8::from(scheme) as _);
+                }
+                Ok(self)
+            }
+        }
+    }
+
+    /// print modem status
+    pub fn print(&self) {
+        unsafe {
+            raw::modem_print(self.inner);
+        }
+    }
+
+    /// reset state of modem object
+    pub fn reset(&mut self) {
+        unsafe {
+            raw::modem_reset(self.inner);
+        }
+    }
+
+    /// generate random symbol
+    pub fn gen_rand_sym(&self) -> u32 {
+        unsafe { raw::modem_gen_rand_sym(self.inner) as u32 }
+    }
+
+    /// Accessor functions
+    pub fn get_bps(&self) -> u32 {
+        unsafe { raw::modem_get_bps(self.inner) as u32 }
+    }
+
+    pub fn get_scheme(&self) -> ModulationScheme {
+        ModulationScheme::try_from(unsafe { raw::modem_get_scheme(self.inner) as u8 })
+            .unwrap_or(ModulationScheme::UNKNOWN)
+    }
+
+    /// ideal constellation of a modulation scheme, the point of symbol s
+    /// at index s; differential schemes are referred to a zero phase
+    pub fn constellation(scheme: ModulationScheme) -> LiquidResult<Vec<Complex32>> {
+        let mut modem = Self::create(scheme)?;
+        (0..1u32 << modem.get_bps())
+            .map(|s| {
+                modem.reset();
+                modem.modulate(s)
+            })
+            .collect()
+    }
+
+    /// modulate symbol
+    ///  s      :   input symbol, s < 2^bps
+    pub fn modulate(&mut self, s: u32) -> LiquidResult<Complex32> {
+        self.check_symbol(s)?;
+        let mut y = Complex32::default();
+        unsafe {
+            raw::modem_modulate(self.inner, s as _, y.to_ptr_mut());
+        }
+        Ok(y)
+    }
+
+    /// demodulate sample, returning the hard decision symbol
+    ///  x      :   input sample
+    pub fn demodulate(&mut self, x: Complex32) -> u32 {
+        let mut s = 0u32;
+        unsafe {
+            raw::modem_demodulate(self.inner, x.to_c_value(), &mut s as *mut _);
+        }
+        s
+    }
+
+    /// demodulate sample with soft bits
+    ///  x          :   input sample
+    ///  soft_bits  :   soft bits output [size: bps x 1], 0: strong '0', 255: strong '1'
+    /// # returns
+    /// the hard decision symbol
+    ///
+    /// The soft bits can be concatenated and fed directly to Packetizer::decode_soft
+    pub fn demodulate_soft(&mut self, x: Complex32, soft_bits: &mut [u8]) -> LiquidResult<u32> {
+        self.check_bits_len(soft_bits.len(), 1)?;
+        let mut s = 0u32;
+        unsafe {
+            raw::modem_demodulate_soft(
+                self.inner,
+                x.to_c_value(),
+                &mut s as *mut _,
+                soft_bits.as_mut_ptr(),
+            );
+        }
+        Ok(s)
+    }
+
+    /// demodulate a block of samples into soft bits
+    ///  x          :   input samples [size: _n x 1]
+    ///  soft_bits  :   soft bits output [size: _n*bps x 1]
+    pub fn demodulate_soft_block(
+        &mut self,
+        x: &[Complex32],
+        soft_bits: &mut [u8],
+    ) -> LiquidResult<()> {
+        let bps = self.get_bps() as usize;
+        self.check_bits_len(soft_bits.len(), x.len())?;
+        for (sample, bits) in x.iter().zip(soft_bits.chunks_mut(bps)) {
+            self.demodulate_soft(*sample, bits)?;
+        }
+        Ok(())
+    }
+
+    /// demodulate sample into per-bit log-likelihood ratios
+    ///  x      :   input sample
+    ///  llr    :   LLR output [size: bps x 1], positive values favour '1'
+    /// # returns
+    /// the hard decision symbol
+    pub fn demodulate_llr(&mut self, x: Complex32, llr: &mut [f32]) -> LiquidResult<u32> {
+        let mut soft_bits = [0u8; 8];
+        let bps = self.get_bps() as usize;
+        self.check_bits_len(llr.len(), 1)?;
+        let s = self.demodulate_soft(x, &mut soft_bits[..bps])?;
+        llr.iter_mut()
+            .zip(soft_bits.iter())
+            .for_each(|(l, &b)| *l = soft_bit_to_llr(b, self.llr_scale));
+        Ok(s)
+    }
+
+    /// demodulate sample into per-bit log-likelihood ratios quantized to i8
+    ///  x      :   input sample
+    ///  llr    :   LLR output [size: bps x 1], positive values favour '1'
+    pub fn demodulate_llr_i8(&mut self, x: Complex32, llr: &mut [i8]) -> LiquidResult<u32> {
+        let mut values = [0f32; 8];
+        let bps = self.get_bps() as usize;
+        self.check_bits_len(llr.len(), 1)?;
+        let s = self.demodulate_llr(x, &mut values[..bps])?;
+        llr.iter_mut().zip(values.iter()).for_each(|(l, &v)| {
+            *l = (v * LLR_SOFT_GAIN)
+                .round()
+                .clamp(i8::MIN as f32, i8::MAX as f32) as i8
+        });
+        Ok(s)
+    }
+
+    /// set the scaling applied to the LLRs returned by demodulate_llr,
+    /// e.g. to account for the estimated SNR
+    ///  scale  :   LLR scaling factor, scale > 0
+    pub fn set_llr_scale(&mut self, scale: f32) -> LiquidResult<()> {
+        if scale <= 0f32 {
+            return Err(
+                ModemError::InvalidValue("LLR scale must be greater than zero".to_owned()).into(),
+            );
+        }
+        self.llr_scale = scale;
+        Ok(())
+    }
+
+    pub fn get_llr_scale(&self) -> f32 {
+        self.llr_scale
+    }
+
+    /// get demodulator's estimated transmit sample
+    pub fn get_demodulator_sample(&self) -> Complex32 {
+        let mut x_hat = Complex32::default();
+        unsafe {
+            raw::modem_get_demodulator_sample(self.inner, x_hat.to_ptr_mut());
+        }
+        x_hat
+    }
+
+    /// get demodulator phase error
+    pub fn get_demodulator_phase_error(&self) -> f32 {
+        unsafe { raw::modem_get_demodulator_phase_error(self.inner) }
+    }
+
+    /// get demodulator error vector magnitude
+    pub fn get_demodulator_evm(&self) -> f32 {
+        unsafe { raw::modem_get_demodulator_evm(self.inner) }
+    }
+
+    fn check_symbol(&self, s: u32) -> LiquidResult<()> {
+        let bps = self.get_bps();
+        if bps < 32 && s >= (1 << bps) {
+            return Err(ModemError::InvalidValue(format!(
+                "symbol: {} out of range [0, {})",
+                s,
+                1u32 << bps
+            ))
+            .into());
+        }
+        Ok(())
+    }
+
+    fn check_bits_len(&self, len: usize, num_symbols: usize) -> LiquidResult<()> {
+        let required = self.get_bps() as usize * num_symbols;
+        if len != required {
+            return Err(ModemError::InvalidLength {
+                description: format!("bits length: {} valid length: {}", len, required),
+            }
+            .into());
+        }
+        Ok(())
+    }
+}
+
+/// normalize a constellation the way liquid does for Modem::create_arbitrary,
+/// removing the mean of the points and scaling them to a unit average energy
+/// # returns
+/// None if the points are all equal
+pub fn normalize_constellation(points: &[Complex32]) -> Option<Vec<Complex32>> {
+    if points.is_empty() {
+        return None;
+    }
+    let n = points.len() as f32;
+    let mean = points.iter().sum::<Complex32>() / n;
+    let energy = points.iter().map(|p| (p - mean).norm_sqr()).sum::<f32>() / n;
+    if energy <= 0f32 {
+        return None;
+    }
+    let g = 1.0 / energy.sqrt();
+    Some(points.iter().map(|p| (p - mean) * g).collect())
+}
+
+/// convert a liquid soft bit into a log-likelihood ratio
+fn soft_bit_to_llr(soft_bit: u8, scale: f32) -> f32 {
+    scale * (soft_bit as f32 - 127.0) / LLR_SOFT_GAIN
+}
+
+/// convert log-likelihood ratios into soft bits accepted by
+/// Packetizer::decode_soft and Fec::decode_soft
+///  llr    :   LLR input, positive values favour '1'
+///  scale  :   scaling used when the LLRs were computed
+///  soft_bits  :   soft bits output [size: llr.len() x 1]
+pub fn llr_to_soft_bits(llr: &[f32], scale: f32, soft_bits: &mut [u8]) -> LiquidResult<()> {
+    if llr.len() != soft_bits.len() {
+        return Err(ModemError::InvalidLength {
+            description: format!(
+                "soft bits length: {} valid length: {}",
+                soft_bits.len(),
+                llr.len()
+            ),
+        }
+        .into());
+    } else if scale <= 0f32 {
+        return Err(
+            ModemError::InvalidValue("LLR scale must be greater than zero".to_owned()).into(),
+        );
+    }
+    llr.iter().zip(soft_bits.iter_mut()).for_each(|(&l, b)| {
+        *b = (127.0 + l * LLR_SOFT_GAIN / scale)
+            .round()
+            .clamp(0.0, 255.0) as u8
+    });
+    Ok(())
+}
+
+impl fmt::Debug for Modem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "modem [scheme: {:?}, bps: {}]",
+            self.get_scheme(),
+            self.get_bps()
+        )
+    }
+}
+
+impl Drop for Modem {
+    fn drop(&mut self) {
+        unsafe {
+            raw::modem_destroy(self.inner);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{llr_to_soft_bits, normalize_constellation, Modem};
+    use crate::modem::{ArbLabeling, ModulationScheme};
+    use num::complex::Complex32;
+
+    #[test]
+    fn test_modem_modulate_demodulate() {
+        let mut modem = Modem::create(ModulationScheme::QAM16).unwrap();
+        assert_eq!(modem.get_bps(), 4);
+        for s in 0..16 {
+            let x = modem.modulate(s).unwrap();
+            assert_eq!(modem.demodulate(x), s);
+        }
+        assert!(modem.modulate(16).is_err());
+    }
+
+    #[test]
+    fn test_modem_constellation() {
+        let points = Modem::constellation(ModulationScheme::QAM16).unwrap();
+        assert_eq!(points.len(), 16);
+        let energy = points.iter().map(|p| p.norm_sqr()).sum::<f32>() / 16.0;
+        assert!((energy - 1.0).abs() < 1e-3);
+        assert!(Modem::constellation(ModulationScheme::UNKNOWN).is_err());
+    }
+
+    #[test]
+    fn test_modulation_scheme_str() {
+        assert_eq!(ModulationScheme::QAM16.to_string(), "qam16");
+        assert_eq!(
+            "qpsk".parse::<ModulationScheme>().unwrap(),
+            ModulationScheme::QPSK
+        );
+        assert!("bogus".parse::<ModulationScheme>().is_err());
+    }
+
+    #[test]
+    fn test_modem_llr_sign() {
+        let mut modem = Modem::create(ModulationScheme::QPSK).unwrap();
+        let x = modem.modulate(2).unwrap();
+        let mut llr = [0f32; 2];
+        let s = modem.demodulate_llr(x, &mut llr).unwrap();
+        assert_eq!(s, 2);
+        assert!(llr[0] > 0.0);
+        assert!(llr[1] < 0.0);
+
+        let mut soft_bits = [0u8; 2];
+        llr_to_soft_bits(&llr, modem.get_llr_scale(), &mut soft_bits).unwrap();
+        assert!(soft_bits[0] > 127);
+        assert!(soft_bits[1] < 127);
+    }
+
+    #[test]
+    fn test_modem_arbitrary() {
+        let ring: Vec<Complex32> = (0..8)
+            .map(|i| Complex32::from_polar(2.0, i as f32 * std::f32::consts::PI / 4.0))
+            .collect();
+        assert!(Modem::create_arbitrary(&ring[..6], ArbLabeling::Natural).is_err());
+        assert!(
+            Modem::create_arbitrary(&[Complex32::new(1.0, 0.0); 4], ArbLabeling::Gray).is_err()
+        );
+
+        let normalized = normalize_constellation(&ring).unwrap();
+        assert!((normalized[0] - Complex32::new(1.0, 0.0)).norm() < 1e-6);
+
+        for &labeling in &[ArbLabeling::Natural, ArbLabeling::Gray] {
+            let mut modem = Modem::create_arbitrary(&ring, labeling).unwrap();
+            assert_eq!(modem.get_bps(), 3);
+            for s in 0..8 {
+                let x = modem.modulate(s).unwrap();
+                assert!((x.norm() - 1.0).abs() < 1e-4);
+                assert_eq!(modem.demodulate(x), s);
+            }
+        }
+        // gray labeling: neighbouring points differ by a single bit
+        let mut modem = Modem::create_arbitrary(&ring, ArbLabeling::Gray).unwrap();
+        let mut labels = [0u32; 8];
+        for (i, p) in normalized.iter().enumerate() {
+            labels[i] = modem.demodulate(*p);
+        }
+        for i in 0..8 {
+            assert_eq!((labels[i] ^ labels[(i + 1) % 8]).count_ones(), 1);
+        }
+    }
+
+    #[test]
+    fn test_modem_arbitrary_largest() {
+        // 16 x 16 square grid, the largest constellation liquid supports
+        let grid: Vec<Complex32> = (0..512)
+            .map(|i| Complex32::new((i % 16) as f32 - 7.5, ((i / 16) % 16) as f32 - 7.5))
+            .collect();
+        assert!(Modem::create_arbitrary(&grid, ArbLabeling::Natural).is_err());
+
+        let mut modem = Modem::create_arbitrary(&grid[..256], ArbLabeling::Gray).unwrap();
+        assert_eq!(modem.get_bps(), 8);
+        let mut llr = [0f32; 8];
+        let mut llr_i8 = [0i8; 8];
+        for s in 0..256 {
+            let x = modem.modulate(s).unwrap();
+            assert_eq!(modem.demodulate(x), s);
+            assert_eq!(modem.demodulate_llr(x, &mut llr).unwrap(), s);
+            assert_eq!(modem.demodulate_llr_i8(x, &mut llr_i8).unwrap(), s);
+        }
+    }
+}
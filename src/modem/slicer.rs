@@ -0,0 +1,146 @@
+//! Symbol slicer: maps received Complex32 symbols to hard decisions,
+//! hard bits, soft bits or packed bytes for a given modulation scheme.
+//!
+//! The decisions are taken by liquid's demodulator, so every block of the
+//! crate that slices symbols through this object gets the same answer.
+use num::complex::Complex32;
+
+use crate::errors::ModemError;
+use crate::modem::{Modem, ModulationScheme};
+use crate::utils::pack_bytes;
+use crate::LiquidResult;
+
+pub struct Slicer {
+    modem: Modem,
+    bps: usize,
+}
+
+impl Slicer {
+    /// create a slicer for a particular modulation scheme
+    ///  scheme :   modulation scheme, differential schemes keep state
+    ///             between calls
+    pub fn create(scheme: ModulationScheme) -> LiquidResult<Self> {
+        let modem = Modem::create(scheme)?;
+        let bps = modem.get_bps() as usize;
+        Ok(Self { modem, bps })
+    }
+
+    pub fn get_scheme(&self) -> ModulationScheme {
+        self.modem.get_scheme()
+    }
+
+    /// bits per symbol
+    pub fn get_bps(&self) -> usize {
+        self.bps
+    }
+
+    pub fn reset(&mut self) {
+        self.modem.reset();
+    }
+
+    /// hard decision symbol index
+    pub fn hard(&mut self, x: Complex32) -> u32 {
+        self.modem.demodulate(x)
+    }
+
+    /// nearest constellation point to the received sample
+    pub fn decide(&mut self, x: Complex32) -> Complex32 {
+        self.modem.demodulate(x);
+        self.modem.get_demodulator_sample()
+    }
+
+    /// hard decision symbol indices on a block of samples
+    ///  x      :   input samples [size: _n x 1]
+    ///  syms   :   output symbols [size: _n x 1]
+    pub fn hard_block(&mut self, x: &[Complex32], syms: &mut [u32]) -> LiquidResult<()> {
+        self.check_len(syms.len(), x.len())?;
+        x.iter()
+            .zip(syms.iter_mut())
+            .for_each(|(&v, s)| *s = self.modem.demodulate(v));
+        Ok(())
+    }
+
+    /// nearest constellation points on a block of samples
+    ///  x      :   input samples [size: _n x 1]
+    ///  y      :   output constellation points [size: _n x 1]
+    pub fn decide_block(&mut self, x: &[Complex32], y: &mut [Complex32]) -> LiquidResult<()> {
+        self.check_len(y.len(), x.len())?;
+        x.iter()
+            .zip(y.iter_mut())
+            .for_each(|(&v, out)| *out = self.decide(v));
+        Ok(())
+    }
+
+    /// hard bits, one bit per element, most-significant bit first
+    ///  x      :   input samples [size: _n x 1]
+    ///  bits   :   output bits [size: _n*bps x 1]
+    pub fn hard_bits(&mut self, x: &[Complex32], bits: &mut [u8]) -> LiquidResult<()> {
+        self.check_len(bits.len(), x.len() * self.bps)?;
+        let bps = self.bps;
+        for (&v, chunk) in x.iter().zip(bits.chunks_mut(bps)) {
+            let s = self.modem.demodulate(v);
+            chunk
+                .iter_mut()
+                .enumerate()
+                .for_each(|(i, b)| *b = ((s >> (bps - 1 - i)) & 1) as u8);
+        }
+        Ok(())
+    }
+
+    /// soft bits, one soft bit per element, 0: strong '0', 255: strong '1'
+    ///  x      :   input samples [size: _n x 1]
+    ///  soft   :   output soft bits [size: _n*bps x 1]
+    pub fn soft_bits(&mut self, x: &[Complex32], soft: &mut [u8]) -> LiquidResult<()> {
+        self.check_len(soft.len(), x.len() * self.bps)?;
+        self.modem.demodulate_soft_block(x, soft)
+    }
+
+    /// hard bits packed into bytes, most-significant bit first
+    ///  x      :   input samples [size: _n x 1]
+    ///  bytes  :   output bytes [size: ceil(_n*bps/8) x 1]
+    /// # returns
+    /// the number of bytes written
+    pub fn hard_bytes(&mut self, x: &[Complex32], bytes: &mut [u8]) -> LiquidResult<usize> {
+        let mut bits = vec![0u8; x.len() * self.bps];
+        self.hard_bits(x, &mut bits)?;
+        pack_bytes(&bits, bytes)
+    }
+
+    fn check_len(&self, len: usize, required: usize) -> LiquidResult<()> {
+        if len != required {
+            return Err(ModemError::InvalidLength {
+                description: format!("output length: {} valid length: {}", len, required),
+            }
+            .into());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Slicer;
+    use crate::modem::{Modem, ModulationScheme};
+    use num::complex::Complex32;
+
+    #[test]
+    fn test_slicer_hard_bits() {
+        let mut modem = Modem::create(ModulationScheme::QPSK).unwrap();
+        let mut slicer = Slicer::create(ModulationScheme::QPSK).unwrap();
+        let symbols: Vec<Complex32> = [0, 1, 2, 3]
+            .iter()
+            .map(|&s| modem.modulate(s).unwrap())
+            .collect();
+
+        let mut bits = [0u8; 8];
+        slicer.hard_bits(&symbols, &mut bits).unwrap();
+        assert_eq!(bits, [0, 0, 0, 1, 1, 0, 1, 1]);
+
+        let mut bytes = [0u8; 1];
+        assert_eq!(slicer.hard_bytes(&symbols, &mut bytes).unwrap(), 1);
+        assert_eq!(bytes[0], 0x1B);
+
+        let mut short = [0u8; 4];
+        assert!(slicer.hard_bits(&symbols, &mut short).is_err());
+    }
+}
@@ -0,0 +1,268 @@
+//! *nco* : numerically-controlled oscillator, for mixing and phase-locked
+//!       loops
+use num::complex::Complex32;
+
+use crate::liquid_dsp_sys as raw;
+use crate::utils::{check_same_len, ToCPointer, ToCPointerMut, ToCValue};
+use crate::LiquidResult;
+
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub enum NcoType {
+    /// table-based oscillator
+    NCO,
+    /// oscillator computing the exact sine and cosine
+    VCO,
+}
+
+impl From<NcoType> for u32 {
+    fn from(value: NcoType) -> u32 {
+        match value {
+            NcoType::NCO => raw::liquid_ncotype_LIQUID_NCO,
+            NcoType::VCO => raw::liquid_ncotype_LIQUID_VCO,
+        }
+    }
+}
+
+pub struct NcoCrcf {
+    inner: raw::nco_crcf,
+    type_: NcoType,
+}
+
+impl NcoCrcf {
+    /// create nco object with zero frequency and phase
+    pub fn create(type_: NcoType) -> Self {
+        Self {
+            inner: unsafe { raw::nco_crcf_create(u32::from(type_) as _) },
+            type_,
+        }
+    }
+
+    pub fn print(&self) {
+        unsafe {
+            raw::nco_crcf_print(self.inner);
+        }
+    }
+
+    /// set the phase and frequency to zero
+    pub fn reset(&mut self) {
+        unsafe {
+            raw::nco_crcf_reset(self.inner);
+        }
+    }
+
+    pub fn get_type(&self) -> NcoType {
+        self.type_
+    }
+
+    /// frequency [radians/sample]
+    pub fn get_frequency(&self) -> f32 {
+        unsafe { raw::nco_crcf_get_frequency(self.inner) }
+    }
+
+    /// set the frequency
+    ///  dtheta :   phase increment [radians/sample]
+    pub fn set_frequency(&mut self, dtheta: f32) {
+        unsafe {
+            raw::nco_crcf_set_frequency(self.inner, dtheta);
+        }
+    }
+
+    pub fn adjust_frequency(&mut self, step: f32) {
+        unsafe {
+            raw::nco_crcf_adjust_frequency(self.inner, step);
+        }
+    }
+
+    /// phase [radians]
+    pub fn get_phase(&self) -> f32 {
+        unsafe { raw::nco_crcf_get_phase(self.inner) }
+    }
+
+    pub fn set_phase(&mut self, phi: f32) {
+        unsafe {
+            raw::nco_crcf_set_phase(self.inner, phi);
+        }
+    }
+
+    pub fn adjust_phase(&mut self, dphi: f32) {
+        unsafe {
+            raw::nco_crcf_adjust_phase(self.inner, dphi);
+        }
+    }
+
+    /// advance the phase by the frequency
+    pub fn step(&mut self) {
+        unsafe {
+            raw::nco_crcf_step(self.inner);
+        }
+    }
+
+    pub fn sin(&self) -> f32 {
+        unsafe { raw::nco_crcf_sin(self.inner) }
+    }
+
+    pub fn cos(&self) -> f32 {
+        unsafe { raw::nco_crcf_cos(self.inner) }
+    }
+
+    /// (sin, cos) of the phase
+    pub fn sincos(&self) -> (f32, f32) {
+        let (mut s, mut c) = (0f32, 0f32);
+        unsafe {
+            raw::nco_crcf_sincos(self.inner, &mut s, &mut c);
+        }
+        (s, c)
+    }
+
+    /// exp(j*phase)
+    pub fn cexpf(&self) -> Complex32 {
+        let mut y = Complex32::default();
+        unsafe {
+            raw::nco_crcf_cexpf(self.inner, y.to_ptr_mut());
+        }
+        y
+    }
+
+    /// set the bandwidth of liquid's internal phase-locked loop
+    pub fn pll_set_bandwidth(&mut self, bw: f32) {
+        unsafe {
+            raw::nco_crcf_pll_set_bandwidth(self.inner, bw);
+        }
+    }
+
+    /// advance liquid's internal phase-locked loop
+    ///  dphi   :   phase error [radians]
+    pub fn pll_step(&mut self, dphi: f32) {
+        unsafe {
+            raw::nco_crcf_pll_step(self.inner, dphi);
+        }
+    }
+
+    /// rotate a sample by exp(j*phase), without stepping
+    pub fn mix_up(&self, x: Complex32) -> Complex32 {
+        let mut y = Complex32::default();
+        unsafe {
+            raw::nco_crcf_mix_up(self.inner, x.to_c_value(), y.to_ptr_mut());
+        }
+        y
+    }
+
+    /// rotate a sample by exp(-j*phase), without stepping
+    pub fn mix_down(&self, x: Complex32) -> Complex32 {
+        let mut y = Complex32::default();
+        unsafe {
+            raw::nco_crcf_mix_down(self.inner, x.to_c_value(), y.to_ptr_mut());
+        }
+        y
+    }
+
+    /// mix a block of samples up, stepping after every sample
+    ///  x      :   input samples [size: n x 1]
+    ///  y      :   output samples [size: n x 1]
+    pub fn mix_block_up(&mut self, x: &[Complex32], y: &mut [Complex32]) -> LiquidResult<()> {
+        check_same_len(x.len(), y.len())?;
+        unsafe {
+            raw::nco_crcf_mix_block_up(self.inner, x.to_ptr() as _, y.to_ptr_mut(), x.len() as _);
+        }
+        Ok(())
+    }
+
+    /// mix a block of samples down, stepping after every sample
+    ///  x      :   input samples [size: n x 1]
+    ///  y      :   output samples [size: n x 1]
+    pub fn mix_block_down(&mut self, x: &[Complex32], y: &mut [Complex32]) -> LiquidResult<()> {
+        check_same_len(x.len(), y.len())?;
+        unsafe {
+            raw::nco_crcf_mix_block_down(self.inner, x.to_ptr() as _, y.to_ptr_mut(), x.len() as _);
+        }
+        Ok(())
+    }
+}
+
+impl Drop for NcoCrcf {
+    fn drop(&mut self) {
+        unsafe {
+            raw::nco_crcf_destroy(self.inner);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{NcoCrcf, NcoType};
+    use crate::liquid_dsp_sys as raw;
+    use num::complex::Complex32;
+
+    #[test]
+    fn test_nco_type() {
+        assert_eq!(u32::from(NcoType::NCO), raw::liquid_ncotype_LIQUID_NCO);
+        assert_eq!(u32::from(NcoType::VCO), raw::liquid_ncotype_LIQUID_VCO);
+        assert_ne!(u32::from(NcoType::NCO), u32::from(NcoType::VCO));
+        assert_eq!(NcoCrcf::create(NcoType::NCO).get_type(), NcoType::NCO);
+        assert_eq!(NcoCrcf::create(NcoType::VCO).get_type(), NcoType::VCO);
+    }
+
+    #[test]
+    fn test_nco_frequency_phase() {
+        let mut q = NcoCrcf::create(NcoType::VCO);
+        assert_eq!(q.get_frequency(), 0.0);
+        assert_eq!(q.get_phase(), 0.0);
+        q.set_frequency(0.1);
+        assert!((q.get_frequency() - 0.1).abs() < 1e-5);
+        q.adjust_frequency(0.05);
+        assert!((q.get_frequency() - 0.15).abs() < 1e-5);
+        q.set_phase(1.0);
+        assert!((q.get_phase() - 1.0).abs() < 1e-5);
+        q.adjust_phase(0.5);
+        assert!((q.get_phase() - 1.5).abs() < 1e-5);
+
+        let (s, c) = q.sincos();
+        assert!((s - 1.5f32.sin()).abs() < 1e-4 && (c - 1.5f32.cos()).abs() < 1e-4);
+        assert!((q.sin() - s).abs() < 1e-6 && (q.cos() - c).abs() < 1e-6);
+        assert!((q.cexpf() - Complex32::new(c, s)).norm() < 1e-5);
+
+        // ten steps of 0.15 radians
+        for _ in 0..10 {
+            q.step();
+        }
+        assert!((q.cexpf() - Complex32::from_polar(1.0, 3.0)).norm() < 1e-4);
+
+        q.reset();
+        assert_eq!(q.get_frequency(), 0.0);
+        assert_eq!(q.get_phase(), 0.0);
+    }
+
+    #[test]
+    fn test_nco_mix_round_trip() {
+        let x: Vec<Complex32> = (0..64)
+            .map(|n| Complex32::new((0.3 * n as f32).cos(), 0.5))
+            .collect();
+        for &t in &[NcoType::NCO, NcoType::VCO] {
+            let create = || {
+                let mut q = NcoCrcf::create(t);
+                q.set_frequency(0.2);
+                q.set_phase(0.3);
+                q
+            };
+            let mut up = create();
+            assert!((up.mix_down(up.mix_up(x[1])) - x[1]).norm() < 1e-2);
+            let mut y = vec![Complex32::default(); x.len()];
+            assert!(up.mix_block_up(&x, &mut y[1..]).is_err());
+            up.mix_block_up(&x, &mut y).unwrap();
+            for (n, (a, b)) in x.iter().zip(y.iter()).enumerate() {
+                let expected = a * Complex32::from_polar(1.0, 0.3 + 0.2 * n as f32);
+                assert!((b - expected).norm() < 1e-2);
+            }
+            // the oscillator stepped once per sample
+            let phase = 0.3 + 0.2 * x.len() as f32;
+            assert!((up.cexpf() - Complex32::from_polar(1.0, phase)).norm() < 1e-2);
+
+            let mut down = create();
+            let mut z = vec![Complex32::default(); x.len()];
+            down.mix_block_down(&y, &mut z).unwrap();
+            for (a, b) in x.iter().zip(z.iter()) {
+                assert!((a - b).norm() < 1e-2);
+            }
+        }
+    }
+}
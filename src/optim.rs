@@ -0,0 +1,359 @@
+//! *optim* : numerical optimization of a user supplied utility function
+//!           over a vector of parameters, with liquid's gradient search,
+//!           quasi-Newton search and genetic algorithm search
+use libc::{c_uint, c_void};
+use std::marker::PhantomData;
+
+use crate::liquid_dsp_sys as raw;
+
+use crate::callbacks::Callbacks;
+use crate::errors::LiquidError;
+use crate::utils::catch;
+use crate::LiquidResult;
+
+/// whether the search minimizes or maximizes the utility
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub enum OptimDirection {
+    Minimize,
+    Maximize,
+}
+
+impl From<OptimDirection> for u32 {
+    fn from(value: OptimDirection) -> u32 {
+        match value {
+            OptimDirection::Minimize => raw::LIQUID_OPTIM_MINIMIZE,
+            OptimDirection::Maximize => raw::LIQUID_OPTIM_MAXIMIZE,
+        }
+    }
+}
+
+pub extern "C" fn utility_callback_f(userdata: *mut c_void, v: *mut f32, n: c_uint) -> f32 {
+    catch(|| unsafe {
+        if let Some(fun) = (*(userdata as *mut Callbacks)).utility_callback.as_mut() {
+            return fun(std::slice::from_raw_parts(v, n as usize));
+        }
+        0f32
+    })
+    .unwrap()
+}
+
+pub extern "C" fn gasearch_callback_f(userdata: *mut c_void, c: raw::chromosome) -> f32 {
+    catch(|| unsafe {
+        if let Some(fun) = (*(userdata as *mut Callbacks)).utility_callback.as_mut() {
+            let v: Vec<f32> = (0..raw::chromosome_get_num_traits(c))
+                .map(|i| raw::chromosome_valuef(c, i))
+                .collect();
+            return fun(&v);
+        }
+        0f32
+    })
+    .unwrap()
+}
+
+fn utility_userdata<'a, F>(utility: F) -> *mut Callbacks<'a>
+where
+    F: FnMut(&[f32]) -> f32 + 'a,
+{
+    Box::into_raw(Box::new(Callbacks {
+        utility_callback: Some(Box::new(utility)),
+        ..Default::default()
+    }))
+}
+
+/// gradient search
+pub struct Gradsearch<'a> {
+    inner: raw::gradsearch,
+    v: Vec<f32>,
+    callback: *mut Callbacks<'a>,
+    phantom: PhantomData<&'a ()>,
+}
+
+/// quasi-Newton search
+pub struct Qnsearch<'a> {
+    inner: raw::qnsearch,
+    v: Vec<f32>,
+    callback: *mut Callbacks<'a>,
+    phantom: PhantomData<&'a ()>,
+}
+
+macro_rules! search_impl {
+    ($obj:ident, ($create:expr, $print:expr, $execute:expr, $destroy:expr)) => {
+        impl<'a> $obj<'a> {
+            /// create search object
+            ///  v          :   initial parameters, v.len() > 0
+            ///  direction  :   minimize or maximize the utility
+            ///  utility    :   utility function of the parameters
+            pub fn create<F>(v: &[f32], direction: OptimDirection, utility: F) -> LiquidResult<Self>
+            where
+                F: FnMut(&[f32]) -> f32 + 'a,
+            {
+                if v.is_empty() {
+                    return Err(LiquidError::InvalidLength {
+                        description: "number of parameters must be greater than zero".to_owned(),
+                    });
+                }
+                let userdata = utility_userdata(utility);
+                // liquid keeps a pointer to the parameters and updates them
+                // in place, the vector is never resized afterwards
+                let mut v = v.to_vec();
+                Ok(Self {
+                    inner: unsafe {
+                        $create(
+                            userdata as _,
+                            v.as_mut_ptr(),
+                            v.len() as _,
+                            Some(utility_callback_f),
+                            u32::from(direction) as _,
+                        )
+                    },
+                    v,
+                    callback: userdata,
+                    phantom: PhantomData,
+                })
+            }
+
+            pub fn print(&self) {
+                unsafe {
+                    $print(self.inner);
+                }
+            }
+
+            /// run the search
+            ///  max_iterations :   maximum number of iterations
+            ///  target_utility :   the search stops once the utility reaches this value
+            /// # returns
+            /// the utility of the final parameters
+            pub fn execute(&mut self, max_iterations: u32, target_utility: f32) -> f32 {
+                unsafe { $execute(self.inner, max_iterations, target_utility) }
+            }
+
+            /// current parameters
+            pub fn get_parameters(&self) -> &[f32] {
+                &self.v
+            }
+        }
+
+        impl<'a> Drop for $obj<'a> {
+            fn drop(&mut self) {
+                unsafe {
+                    $destroy(self.inner);
+                    let _ = Box::from_raw(self.callback);
+                }
+            }
+        }
+    };
+}
+
+search_impl!(
+    Gradsearch,
+    (
+        raw::gradsearch_create,
+        raw::gradsearch_print,
+        raw::gradsearch_execute,
+        raw::gradsearch_destroy
+    )
+);
+
+search_impl!(
+    Qnsearch,
+    (
+        raw::qnsearch_create,
+        raw::qnsearch_print,
+        raw::qnsearch_execute,
+        raw::qnsearch_destroy
+    )
+);
+
+impl<'a> Gradsearch<'a> {
+    /// run a single iteration
+    /// # returns
+    /// the utility of the updated parameters
+    pub fn step(&mut self) -> f32 {
+        unsafe { raw::gradsearch_step(self.inner) }
+    }
+}
+
+impl<'a> Qnsearch<'a> {
+    /// run a single iteration
+    pub fn step(&mut self) {
+        unsafe {
+            raw::qnsearch_step(self.inner);
+        }
+    }
+
+    /// reset the internal state, keeping the current parameters
+    pub fn reset(&mut self) {
+        unsafe {
+            raw::qnsearch_reset(self.inner);
+        }
+    }
+}
+
+/// genetic algorithm search, the parameters are encoded as traits with a
+/// fixed number of bits and handed to the utility as values in [0, 1]
+pub struct Gasearch<'a> {
+    inner: raw::gasearch,
+    /// best chromosome found, owned here but updated by the search
+    parent: raw::chromosome,
+    callback: *mut Callbacks<'a>,
+    phantom: PhantomData<&'a ()>,
+}
+
+impl<'a> Gasearch<'a> {
+    /// create genetic algorithm search
+    ///  num_traits      :   number of parameters, num_traits > 0
+    ///  bits_per_trait  :   resolution of each parameter, in [1, 64]
+    ///  direction       :   minimize or maximize the utility
+    ///  population_size :   number of chromosomes of the population, at least 2
+    ///  mutation_rate   :   probability of mutating a bit, in [0, 1]
+    ///  utility         :   utility function of the parameters
+    pub fn create<F>(
+        num_traits: u32,
+        bits_per_trait: u32,
+        direction: OptimDirection,
+        population_size: u32,
+        mutation_rate: f32,
+        utility: F,
+    ) -> LiquidResult<Self>
+    where
+        F: FnMut(&[f32]) -> f32 + 'a,
+    {
+        if num_traits == 0 {
+            return Err(LiquidError::InvalidValue(
+                "number of traits must be greater than zero".to_owned(),
+            ));
+        } else if bits_per_trait == 0 || bits_per_trait > 64 {
+            return Err(LiquidError::InvalidValue(
+                "bits per trait must be in [1, 64]".to_owned(),
+            ));
+        } else if population_size < 2 {
+            return Err(LiquidError::InvalidValue(
+                "population size must be at least 2".to_owned(),
+            ));
+        } else if !(0f32..=1f32).contains(&mutation_rate) {
+            return Err(LiquidError::InvalidValue(
+                "mutation rate must be in [0, 1]".to_owned(),
+            ));
+        }
+        let userdata = utility_userdata(utility);
+        unsafe {
+            let parent = raw::chromosome_create_basic(num_traits, bits_per_trait);
+            Ok(Self {
+                inner: raw::gasearch_create_advanced(
+                    Some(gasearch_callback_f),
+                    userdata as _,
+                    parent,
+                    u32::from(direction) as _,
+                    population_size,
+                    mutation_rate,
+                ),
+                parent,
+                callback: userdata,
+                phantom: PhantomData,
+            })
+        }
+    }
+
+    pub fn print(&self) {
+        unsafe {
+            raw::gasearch_print(self.inner);
+        }
+    }
+
+    pub fn set_mutation_rate(&mut self, mutation_rate: f32) -> LiquidResult<()> {
+        if !(0f32..=1f32).contains(&mutation_rate) {
+            return Err(LiquidError::InvalidValue(
+                "mutation rate must be in [0, 1]".to_owned(),
+            ));
+        }
+        unsafe {
+            raw::gasearch_set_mutation_rate(self.inner, mutation_rate);
+        }
+        Ok(())
+    }
+
+    /// run a single generation
+    pub fn evolve(&mut self) {
+        unsafe {
+            raw::gasearch_evolve(self.inner);
+        }
+    }
+
+    /// run the search
+    ///  max_iterations :   maximum number of generations
+    ///  target_utility :   the search stops once the utility reaches this value
+    /// # returns
+    /// the utility of the best parameters
+    pub fn run(&mut self, max_iterations: u32, target_utility: f32) -> f32 {
+        unsafe { raw::gasearch_run(self.inner, max_iterations, target_utility) }
+    }
+
+    /// best parameters found, and their utility
+    pub fn get_optimum(&self) -> (Vec<f32>, f32) {
+        let mut utility = 0f32;
+        unsafe {
+            let c = raw::chromosome_create_clone(self.parent);
+            raw::gasearch_getopt(self.inner, c, &mut utility);
+            let v = (0..raw::chromosome_get_num_traits(c))
+                .map(|i| raw::chromosome_valuef(c, i))
+                .collect();
+            raw::chromosome_destroy(c);
+            (v, utility)
+        }
+    }
+}
+
+impl<'a> Drop for Gasearch<'a> {
+    fn drop(&mut self) {
+        unsafe {
+            raw::gasearch_destroy(self.inner);
+            raw::chromosome_destroy(self.parent);
+            let _ = Box::from_raw(self.callback);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Gasearch, Gradsearch, OptimDirection, Qnsearch};
+
+    #[test]
+    fn test_optim_create() {
+        let utility = |v: &[f32]| v.iter().map(|x| (x - 1.0) * (x - 1.0)).sum::<f32>();
+        assert!(Gradsearch::create(&[], OptimDirection::Minimize, utility).is_err());
+        let q = Qnsearch::create(&[0.0, 2.0], OptimDirection::Minimize, utility).unwrap();
+        assert_eq!(q.get_parameters(), &[0.0, 2.0]);
+        assert!(Gasearch::create(2, 0, OptimDirection::Maximize, 16, 0.1, utility).is_err());
+        assert!(Gasearch::create(2, 8, OptimDirection::Maximize, 16, 1.5, utility).is_err());
+    }
+
+    #[test]
+    fn test_optim_quadratic_convergence() {
+        // minimum at (1, -0.5)
+        let utility = |v: &[f32]| (v[0] - 1.0).powi(2) + 4.0 * (v[1] + 0.5).powi(2);
+        let near = |v: &[f32]| (v[0] - 1.0).abs() < 1e-2 && (v[1] + 0.5).abs() < 1e-2;
+
+        let mut gs = Gradsearch::create(&[-1.0, 1.0], OptimDirection::Minimize, utility).unwrap();
+        let u0 = utility(gs.get_parameters());
+        let u1 = gs.step();
+        assert!(u1 < u0);
+        assert!(gs.execute(1000, 1e-6) < 1e-3);
+        assert!(near(gs.get_parameters()));
+
+        let mut qs = Qnsearch::create(&[-1.0, 1.0], OptimDirection::Minimize, utility).unwrap();
+        (0..200).for_each(|_| qs.step());
+        assert!(near(qs.get_parameters()));
+        qs.reset();
+        assert!(qs.execute(100, 1e-6) < 1e-3);
+        assert!(near(qs.get_parameters()));
+
+        // traits are handed over in [0, 1]
+        let utility = |v: &[f32]| -(v[0] - 0.3).powi(2) - (v[1] - 0.7).powi(2);
+        let mut ga = Gasearch::create(2, 16, OptimDirection::Maximize, 32, 0.1, utility).unwrap();
+        ga.evolve();
+        ga.run(500, 0.0);
+        let (v, u) = ga.get_optimum();
+        assert!((v[0] - 0.3).abs() < 0.05 && (v[1] - 0.7).abs() < 0.05);
+        assert!((u - utility(&v)).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,230 @@
+//! *pipeline* : chain of blocks processing a stream, with the buffers
+//!           between the blocks managed by the chain, e.g.
+//!           Pipeline::new(channel).then(agc).then(filter).then::<u32, _>(demod)
+use crate::block::Block;
+use crate::tag::Tag;
+use crate::LiquidResult;
+
+/// two blocks connected through an intermediate buffer
+struct Chain<I, M, O> {
+    first: Box<dyn Block<I, M>>,
+    second: Box<dyn Block<M, O>>,
+    buf: Vec<M>,
+    tags: Vec<Tag>,
+}
+
+impl<I, M, O> Block<I, O> for Chain<I, M, O> {
+    fn process(&mut self, input: &[I], output: &mut Vec<O>) -> LiquidResult<()> {
+        self.buf.clear();
+        self.buf
+            .reserve((input.len() as f32 * self.first.rate()).ceil() as usize);
+        self.first.process(input, &mut self.buf)?;
+        self.second.process(&self.buf, output)
+    }
+
+    fn rate(&self) -> f32 {
+        self.first.rate() * self.second.rate()
+    }
+
+    fn delay(&self) -> f32 {
+        self.first.delay() * self.second.rate() + self.second.delay()
+    }
+
+    fn process_tagged(
+        &mut self,
+        input: &[I],
+        tags: &[Tag],
+        output: &mut Vec<O>,
+        tags_out: &mut Vec<Tag>,
+    ) -> LiquidResult<()> {
+        self.buf.clear();
+        self.tags.clear();
+        self.buf
+            .reserve((input.len() as f32 * self.first.rate()).ceil() as usize);
+        self.first
+            .process_tagged(input, tags, &mut self.buf, &mut self.tags)?;
+        self.second
+            .process_tagged(&self.buf, &self.tags, output, tags_out)
+    }
+}
+
+/// block applying a function to every sample
+struct Map<F> {
+    f: F,
+}
+
+impl<I: Copy, O, F: FnMut(I) -> O> Block<I, O> for Map<F> {
+    fn process(&mut self, input: &[I], output: &mut Vec<O>) -> LiquidResult<()> {
+        output.extend(input.iter().map(|x| (self.f)(*x)));
+        Ok(())
+    }
+}
+
+/// chain of blocks consuming samples of type I and producing samples of
+/// type O; the chain is a Block itself and can be nested
+pub struct Pipeline<I, O> {
+    head: Box<dyn Block<I, O>>,
+}
+
+impl<I: 'static, O: 'static> Pipeline<I, O> {
+    /// create a pipeline from its first block
+    pub fn new<B: Block<I, O> + 'static>(block: B) -> Self {
+        Self {
+            head: Box::new(block),
+        }
+    }
+
+    /// append a block to the pipeline
+    pub fn then<P: 'static, B: Block<O, P> + 'static>(self, block: B) -> Pipeline<I, P> {
+        Pipeline {
+            head: Box::new(Chain {
+                first: self.head,
+                second: Box::new(block),
+                buf: Vec::new(),
+                tags: Vec::new(),
+            }),
+        }
+    }
+
+    /// append a function applied to every output sample, e.g. to convert
+    /// demodulated symbols into bytes
+    pub fn map<P: 'static, F: FnMut(O) -> P + 'static>(self, f: F) -> Pipeline<I, P>
+    where
+        O: Copy,
+    {
+        self.then(Map { f })
+    }
+
+    /// process a block of input samples
+    /// # returns
+    /// the samples produced by the last block
+    pub fn process(&mut self, input: &[I]) -> LiquidResult<Vec<O>> {
+        let mut output = Vec::with_capacity((input.len() as f32 * self.rate()).ceil() as usize);
+        self.head.process(input, &mut output)?;
+        Ok(output)
+    }
+
+    /// process a block of input samples along with their tags
+    ///  input  :   input samples
+    ///  tags   :   tags attached to the input samples
+    /// # returns
+    /// the samples produced by the last block and their tags
+    pub fn process_tagged(
+        &mut self,
+        input: &[I],
+        tags: &[Tag],
+    ) -> LiquidResult<(Vec<O>, Vec<Tag>)> {
+        let mut output = Vec::with_capacity((input.len() as f32 * self.rate()).ceil() as usize);
+        let mut tags_out = Vec::new();
+        self.head
+            .process_tagged(input, tags, &mut output, &mut tags_out)?;
+        Ok((output, tags_out))
+    }
+
+    /// overall number of output samples per input sample
+    pub fn rate(&self) -> f32 {
+        self.head.rate()
+    }
+
+    /// overall latency [output samples], the delay of every block scaled
+    /// by the rate of the blocks following it
+    pub fn delay(&self) -> f32 {
+        self.head.delay()
+    }
+
+    /// time-align an output with the input that produced it, e.g. to
+    /// compare transmitted and received symbols in a BER test: the first
+    /// delay() samples (rounded) are dropped and the result is truncated
+    /// or padded with O::default() to len samples
+    ///  output :   samples produced by process()
+    ///  len    :   number of aligned samples, e.g. input.len() * rate()
+    pub fn align(&self, output: &[O], len: usize) -> Vec<O>
+    where
+        O: Clone + Default,
+    {
+        let skip = (self.delay().round() as usize).min(output.len());
+        let mut aligned: Vec<O> = output[skip..].iter().take(len).cloned().collect();
+        aligned.resize(len, O::default());
+        aligned
+    }
+}
+
+impl<I: 'static, O: 'static> Block<I, O> for Pipeline<I, O> {
+    fn process(&mut self, input: &[I], output: &mut Vec<O>) -> LiquidResult<()> {
+        self.head.process(input, output)
+    }
+
+    fn process_tagged(
+        &mut self,
+        input: &[I],
+        tags: &[Tag],
+        output: &mut Vec<O>,
+        tags_out: &mut Vec<Tag>,
+    ) -> LiquidResult<()> {
+        self.head.process_tagged(input, tags, output, tags_out)
+    }
+
+    fn rate(&self) -> f32 {
+        self.head.rate()
+    }
+
+    fn delay(&self) -> f32 {
+        self.head.delay()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Pipeline;
+    use crate::agc::AgcCrcf;
+    use crate::channel::ChannelCccf;
+    use crate::filter::{FirFiltRrrf, FirInterpRrrf};
+    use crate::modem::{Modem, ModulationScheme};
+    use crate::tag::{Tag, TagValue};
+    use num::complex::Complex32;
+
+    #[test]
+    fn test_pipeline_rate() {
+        let mut pipeline = Pipeline::new(FirInterpRrrf::create(4, &[1.0; 8]).unwrap())
+            .then(FirFiltRrrf::create(&[1.0, 0.5]).unwrap())
+            .then(FirInterpRrrf::create(2, &[1.0; 4]).unwrap())
+            .map(|v: f32| v > 0.0);
+        assert_eq!(pipeline.rate(), 8.0);
+        let y: Vec<bool> = pipeline.process(&[1.0; 5]).unwrap();
+        assert_eq!(y.len(), 40);
+    }
+
+    #[test]
+    fn test_pipeline_tags() {
+        let mut pipeline = Pipeline::new(FirInterpRrrf::create(4, &[1.0; 8]).unwrap())
+            .then(FirFiltRrrf::create(&[1.0, 0.5]).unwrap());
+        let tags = vec![Tag::new(2, "frame_start", TagValue::Int(7))];
+        let (y, tags) = pipeline.process_tagged(&[1.0; 5], &tags).unwrap();
+        assert_eq!(y.len(), 20);
+        assert_eq!(tags, vec![Tag::new(8, "frame_start", TagValue::Int(7))]);
+    }
+
+    #[test]
+    fn test_pipeline_receiver() {
+        let pipeline: Pipeline<Complex32, u8> = Pipeline::new(ChannelCccf::create())
+            .then(AgcCrcf::create())
+            .then::<u32, _>(Modem::create(ModulationScheme::QPSK).unwrap())
+            .map(|s| s as u8);
+        assert_eq!(pipeline.rate(), 1.0);
+    }
+
+    #[test]
+    fn test_pipeline_delay_align() {
+        // 3 taps delay at the input rate, then 2 samples at the output rate
+        let mut pipeline = Pipeline::new(FirFiltRrrf::create(&[0.0, 0.0, 0.0, 1.0]).unwrap())
+            .then(FirInterpRrrf::create(2, &[1.0; 5]).unwrap());
+        assert_eq!(pipeline.delay().round(), 8.0);
+        let x = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0];
+        let y = pipeline.process(&x).unwrap();
+        assert_eq!(y.len(), 12);
+        let aligned = pipeline.align(&y, 12);
+        assert_eq!(aligned.len(), 12);
+        assert_eq!(&aligned[..4], &y[8..]);
+        assert_eq!(&aligned[4..], &[0.0; 8]);
+    }
+}
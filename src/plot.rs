@@ -0,0 +1,378 @@
+//! SVG rendering of line plots and heat maps, used by the plot_* methods
+//! of the spectral and filter objects and available for any other data.
+//!
+//! The output is plain SVG 1.1 written by hand, so the feature does not
+//! pull any dependency; the files open in any browser or vector editor.
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+const WIDTH: f32 = 800.0;
+const HEIGHT: f32 = 500.0;
+const LEFT: f32 = 70.0;
+const RIGHT: f32 = 20.0;
+const TOP: f32 = 40.0;
+const BOTTOM: f32 = 50.0;
+
+// number of colors of the heat map palette
+const LEVELS: usize = 64;
+
+const PALETTE: [&str; 6] = [
+    "#1f77b4", "#d62728", "#2ca02c", "#ff7f0e", "#9467bd", "#8c564b",
+];
+
+/// title and axis labels of a plot
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Labels<'a> {
+    pub title: &'a str,
+    pub x: &'a str,
+    pub y: &'a str,
+}
+
+/// write a line plot, one polyline per series
+///  labels :   title and axis labels
+///  x      :   abscissa shared by all the series [size: n x 1]
+///  series :   name and ordinate of each series [size: n x 1]
+pub fn lines<W: Write>(
+    mut w: W,
+    labels: Labels<'_>,
+    x: &[f32],
+    series: &[(&str, &[f32])],
+) -> io::Result<()> {
+    if x.len() < 2 {
+        return Err(invalid_input("at least two points are needed"));
+    }
+    if let Some((name, _)) = series.iter().find(|(_, y)| y.len() != x.len()) {
+        return Err(invalid_input(&format!(
+            "series {} length does not match the abscissa length: {}",
+            name,
+            x.len()
+        )));
+    }
+    let xr = range(x.iter().copied());
+    let yr = range(series.iter().flat_map(|(_, y)| y.iter().copied()));
+    let frame = Frame::new(xr, yr);
+
+    let mut svg = header(labels);
+    frame.axes(&mut svg);
+    for (k, (name, y)) in series.iter().enumerate() {
+        let color = PALETTE[k % PALETTE.len()];
+        let _ = write!(
+            svg,
+            "<polyline fill=\"none\" stroke=\"{}\" stroke-width=\"1.5\" points=\"",
+            color
+        );
+        for (&xv, &yv) in x.iter().zip(y.iter()) {
+            let _ = write!(svg, "{:.2},{:.2} ", frame.px(xv), frame.py(yv));
+        }
+        svg.push_str("\"/>\n");
+        if series.len() > 1 {
+            let ly = TOP + 16.0 + 16.0 * k as f32;
+            let _ = writeln!(
+                svg,
+                "<text x=\"{:.1}\" y=\"{:.1}\" fill=\"{}\" text-anchor=\"end\">{}</text>",
+                WIDTH - RIGHT - 8.0,
+                ly,
+                color,
+                escape(name)
+            );
+        }
+    }
+    svg.push_str("</svg>\n");
+    w.write_all(svg.as_bytes())
+}
+
+/// write a heat map, e.g. a spectral waterfall; row 0 is drawn at the top
+///  labels :   title and axis labels
+///  x      :   values of the first and last columns
+///  data   :   values in row-major order [size: rows x cols]
+///  cols   :   number of columns
+pub fn heatmap<W: Write>(
+    mut w: W,
+    labels: Labels<'_>,
+    x: (f32, f32),
+    data: &[f32],
+    cols: usize,
+) -> io::Result<()> {
+    if cols == 0 || data.is_empty() || !data.len().is_multiple_of(cols) {
+        return Err(invalid_input(&format!(
+            "data length: {} is not a non-zero multiple of the columns: {}",
+            data.len(),
+            cols
+        )));
+    }
+    let rows = data.len() / cols;
+    let (lo, hi) = range(data.iter().copied());
+    let frame = Frame::new(x, (rows as f32, 0.0));
+
+    let mut svg = header(labels);
+    let cw = (WIDTH - LEFT - RIGHT) / cols as f32;
+    let ch = (HEIGHT - TOP - BOTTOM) / rows as f32;
+    svg.push_str("<g shape-rendering=\"crispEdges\">\n");
+    for (r, row) in data.chunks(cols).enumerate() {
+        // merge runs of cells sharing a color
+        let mut c = 0;
+        while c < cols {
+            let level = quantize(row[c], lo, hi);
+            let run = row[c..]
+                .iter()
+                .take_while(|&&v| quantize(v, lo, hi) == level)
+                .count();
+            let _ = writeln!(
+                svg,
+                "<rect x=\"{:.2}\" y=\"{:.2}\" width=\"{:.2}\" height=\"{:.2}\" fill=\"{}\"/>",
+                LEFT + c as f32 * cw,
+                TOP + r as f32 * ch,
+                run as f32 * cw + 0.5,
+                ch + 0.5,
+                color(level)
+            );
+            c += run;
+        }
+    }
+    svg.push_str("</g>\n");
+    frame.axes(&mut svg);
+    let _ = writeln!(
+        svg,
+        "<text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"end\">[{:.1}, {:.1}]</text>",
+        WIDTH - RIGHT,
+        TOP - 8.0,
+        lo,
+        hi
+    );
+    svg.push_str("</svg>\n");
+    w.write_all(svg.as_bytes())
+}
+
+/// write a line plot to the file at *path*, see lines()
+pub fn save_lines<P: AsRef<Path>>(
+    path: P,
+    labels: Labels<'_>,
+    x: &[f32],
+    series: &[(&str, &[f32])],
+) -> io::Result<()> {
+    let mut w = BufWriter::new(File::create(path)?);
+    lines(&mut w, labels, x, series)?;
+    w.flush()
+}
+
+/// write a heat map to the file at *path*, see heatmap()
+pub fn save_heatmap<P: AsRef<Path>>(
+    path: P,
+    labels: Labels<'_>,
+    x: (f32, f32),
+    data: &[f32],
+    cols: usize,
+) -> io::Result<()> {
+    let mut w = BufWriter::new(File::create(path)?);
+    heatmap(&mut w, labels, x, data, cols)?;
+    w.flush()
+}
+
+// maps data coordinates into the plot area
+struct Frame {
+    x: (f32, f32),
+    y: (f32, f32),
+}
+
+impl Frame {
+    fn new(x: (f32, f32), y: (f32, f32)) -> Self {
+        Self { x, y }
+    }
+
+    fn px(&self, v: f32) -> f32 {
+        LEFT + (WIDTH - LEFT - RIGHT) * (v - self.x.0) / (self.x.1 - self.x.0)
+    }
+
+    fn py(&self, v: f32) -> f32 {
+        // -inf (e.g. 0 in dB) sits on the bottom edge, NaN is dropped there too
+        let (lo, hi) = (self.y.0.min(self.y.1), self.y.0.max(self.y.1));
+        let v = if v.is_nan() { lo } else { v.max(lo).min(hi) };
+        HEIGHT - BOTTOM - (HEIGHT - TOP - BOTTOM) * (v - self.y.0) / (self.y.1 - self.y.0)
+    }
+
+    fn axes(&self, svg: &mut String) {
+        let _ = writeln!(
+            svg,
+            "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"none\" stroke=\"black\"/>",
+            LEFT,
+            TOP,
+            WIDTH - LEFT - RIGHT,
+            HEIGHT - TOP - BOTTOM
+        );
+        for t in ticks(self.x.0, self.x.1) {
+            let x = self.px(t);
+            let _ = writeln!(
+                svg,
+                "<line x1=\"{0:.2}\" y1=\"{1}\" x2=\"{0:.2}\" y2=\"{2}\" stroke=\"#ccc\"/>\
+                 <text x=\"{0:.2}\" y=\"{3}\" text-anchor=\"middle\">{4}</text>",
+                x,
+                TOP,
+                HEIGHT - BOTTOM,
+                HEIGHT - BOTTOM + 16.0,
+                tick_label(t)
+            );
+        }
+        for t in ticks(self.y.0, self.y.1) {
+            let y = self.py(t);
+            let _ = writeln!(
+                svg,
+                "<line x1=\"{1}\" y1=\"{0:.2}\" x2=\"{2}\" y2=\"{0:.2}\" stroke=\"#ccc\"/>\
+                 <text x=\"{3}\" y=\"{0:.2}\" text-anchor=\"end\" \
+                 dominant-baseline=\"middle\">{4}</text>",
+                y,
+                LEFT,
+                WIDTH - RIGHT,
+                LEFT - 6.0,
+                tick_label(t)
+            );
+        }
+    }
+}
+
+fn header(labels: Labels<'_>) -> String {
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" version=\"1.1\" width=\"{0}\" \
+         height=\"{1}\" viewBox=\"0 0 {0} {1}\" font-family=\"sans-serif\" font-size=\"12\">\n\
+         <rect width=\"100%\" height=\"100%\" fill=\"white\"/>",
+        WIDTH, HEIGHT
+    );
+    let _ = writeln!(
+        svg,
+        "<text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"middle\" font-size=\"16\">{}</text>\n\
+         <text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"middle\">{}</text>\n\
+         <text transform=\"translate({:.1},{:.1}) rotate(-90)\" \
+         text-anchor=\"middle\">{}</text>",
+        WIDTH / 2.0,
+        TOP - 14.0,
+        escape(labels.title),
+        (WIDTH + LEFT - RIGHT) / 2.0,
+        HEIGHT - 12.0,
+        escape(labels.x),
+        18.0,
+        (HEIGHT + TOP - BOTTOM) / 2.0,
+        escape(labels.y)
+    );
+    svg
+}
+
+// range of the finite values, widened when degenerate
+fn range<I: Iterator<Item = f32>>(values: I) -> (f32, f32) {
+    let (lo, hi) = values
+        .filter(|v| v.is_finite())
+        .fold((f32::INFINITY, f32::NEG_INFINITY), |(lo, hi), v| {
+            (lo.min(v), hi.max(v))
+        });
+    if lo > hi {
+        (-1.0, 1.0)
+    } else if lo == hi {
+        (lo - 1.0, hi + 1.0)
+    } else {
+        (lo, hi)
+    }
+}
+
+// 1-2-5 spaced ticks covering [a, b], at most about 10 of them
+fn ticks(a: f32, b: f32) -> Vec<f32> {
+    let (lo, hi) = (a.min(b), a.max(b));
+    let raw = (hi - lo) / 8.0;
+    let mag = 10f32.powf(raw.log10().floor());
+    let step = [1.0, 2.0, 5.0, 10.0]
+        .iter()
+        .map(|m| m * mag)
+        .find(|&s| s >= raw)
+        .unwrap_or(10.0 * mag);
+    let first = (lo / step).ceil() as i64;
+    let last = (hi / step).floor() as i64;
+    (first..=last).map(|k| k as f32 * step).collect()
+}
+
+fn tick_label(v: f32) -> String {
+    // drop the -0 and the float noise of k*step
+    let v = (v * 1e4).round() / 1e4;
+    if v == 0.0 {
+        "0".to_owned()
+    } else {
+        format!("{}", v)
+    }
+}
+
+fn quantize(v: f32, lo: f32, hi: f32) -> usize {
+    if !v.is_finite() {
+        return if v > 0.0 { LEVELS - 1 } else { 0 };
+    }
+    let t = ((v - lo) / (hi - lo)).clamp(0.0, 1.0);
+    ((t * (LEVELS - 1) as f32).round() as usize).min(LEVELS - 1)
+}
+
+// dark blue to yellow, close to the viridis color map
+fn color(level: usize) -> String {
+    const STOPS: [(f32, f32, f32); 5] = [
+        (68.0, 1.0, 84.0),
+        (59.0, 82.0, 139.0),
+        (33.0, 145.0, 140.0),
+        (94.0, 201.0, 98.0),
+        (253.0, 231.0, 37.0),
+    ];
+    let t = level as f32 / (LEVELS - 1) as f32 * (STOPS.len() - 1) as f32;
+    let i = (t.floor() as usize).min(STOPS.len() - 2);
+    let f = t - i as f32;
+    let (a, b) = (STOPS[i], STOPS[i + 1]);
+    format!(
+        "#{:02x}{:02x}{:02x}",
+        (a.0 + (b.0 - a.0) * f).round() as u8,
+        (a.1 + (b.1 - a.1) * f).round() as u8,
+        (a.2 + (b.2 - a.2) * f).round() as u8
+    )
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn invalid_input(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, msg.to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{heatmap, lines, ticks, Labels};
+
+    #[test]
+    fn test_plot_svg() {
+        let labels = Labels {
+            title: "a <test>",
+            x: "x",
+            y: "y",
+        };
+        let x = [0.0, 1.0, 2.0];
+        let y = [0.0, f32::NEG_INFINITY, 4.0];
+        let mut svg = Vec::new();
+        lines(&mut svg, labels, &x, &[("y", &y)]).unwrap();
+        let svg = String::from_utf8(svg).unwrap();
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.trim_end().ends_with("</svg>"));
+        assert!(svg.contains("a &lt;test&gt;"));
+        assert_eq!(svg.matches("<polyline").count(), 1);
+        assert!(lines(Vec::new(), labels, &x, &[("y", &y[..2])]).is_err());
+
+        let mut svg = Vec::new();
+        heatmap(&mut svg, labels, (-0.5, 0.5), &[0.0, 0.0, 1.0, 2.0], 2).unwrap();
+        let svg = String::from_utf8(svg).unwrap();
+        // the first row is a single run
+        assert_eq!(svg.matches("<rect x").count(), 1 + 2 + 1);
+        assert!(heatmap(Vec::new(), labels, (0.0, 1.0), &[0.0; 3], 2).is_err());
+
+        assert_eq!(ticks(0.0, 1.0).len(), 6);
+        assert_eq!(
+            ticks(-80.0, 0.0),
+            vec![-80.0, -70.0, -60.0, -50.0, -40.0, -30.0, -20.0, -10.0, 0.0]
+        );
+    }
+}
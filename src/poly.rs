@@ -0,0 +1,160 @@
+//! *poly* : double precision polynomial evaluation, fitting and root
+//!        finding
+//!
+//! Polynomials are stored in ascending order of power,
+//!   P(x) = p[0] + p[1]*x + ... + p[k-1]*x^(k-1)
+//! liquid implements these routines in double precision, keeping
+//! high order fits usable where the single precision versions are not.
+use num::complex::Complex64;
+
+use crate::errors::LiquidError;
+use crate::liquid_dsp_sys as raw;
+use crate::LiquidResult;
+
+/// evaluate polynomial at x
+///  p      :   coefficients [size: k x 1]
+pub fn val(p: &[f64], x: f64) -> f64 {
+    unsafe { raw::poly_val(p.as_ptr() as _, p.len() as _, x) }
+}
+
+/// least-squares polynomial fit
+///  x      :   abscissa [size: n x 1]
+///  y      :   ordinate [size: n x 1]
+///  k      :   polynomial length (order k-1), 0 < k <= n
+pub fn fit(x: &[f64], y: &[f64], k: usize) -> LiquidResult<Vec<f64>> {
+    check_points(x, y)?;
+    if k == 0 || k > x.len() {
+        return Err(LiquidError::InvalidValue(format!(
+            "polynomial length: {} must be in [1, {}]",
+            k,
+            x.len()
+        )));
+    }
+    let mut p = vec![0f64; k];
+    unsafe {
+        raw::poly_fit(
+            x.as_ptr() as _,
+            y.as_ptr() as _,
+            x.len() as _,
+            p.as_mut_ptr(),
+            k as _,
+        );
+    }
+    Ok(p)
+}
+
+/// Lagrange polynomial exactly fitting the n points
+///  x      :   abscissa [size: n x 1]
+///  y      :   ordinate [size: n x 1]
+/// # returns
+/// the n coefficients of the polynomial
+pub fn fit_lagrange(x: &[f64], y: &[f64]) -> LiquidResult<Vec<f64>> {
+    check_points(x, y)?;
+    let mut p = vec![0f64; x.len()];
+    unsafe {
+        raw::poly_fit_lagrange(
+            x.as_ptr() as _,
+            y.as_ptr() as _,
+            x.len() as _,
+            p.as_mut_ptr(),
+        );
+    }
+    Ok(p)
+}
+
+/// Lagrange polynomial interpolation
+///  x      :   abscissa [size: n x 1]
+///  y      :   ordinate [size: n x 1]
+///  x0     :   evaluation point
+pub fn interp_lagrange(x: &[f64], y: &[f64], x0: f64) -> LiquidResult<f64> {
+    check_points(x, y)?;
+    unsafe {
+        Ok(raw::poly_interp_lagrange(
+            x.as_ptr() as _,
+            y.as_ptr() as _,
+            x.len() as _,
+            x0,
+        ))
+    }
+}
+
+/// expand the polynomial (x-r[0])*(x-r[1])*...*(x-r[n-1])
+///  r      :   roots [size: n x 1]
+/// # returns
+/// the n+1 coefficients of the polynomial
+pub fn expand_roots(r: &[f64]) -> Vec<f64> {
+    let mut p = vec![0f64; r.len() + 1];
+    unsafe {
+        raw::poly_expandroots(r.as_ptr() as _, r.len() as _, p.as_mut_ptr());
+    }
+    p
+}
+
+/// find the complex roots of a polynomial
+///  p      :   coefficients [size: k x 1], k > 1
+/// # returns
+/// the k-1 roots
+pub fn find_roots(p: &[f64]) -> LiquidResult<Vec<Complex64>> {
+    if p.len() < 2 {
+        return Err(LiquidError::InvalidLength {
+            description: format!("polynomial length: {} must be greater than 1", p.len()),
+        });
+    }
+    let mut roots = vec![Complex64::default(); p.len() - 1];
+    unsafe {
+        raw::poly_findroots(p.as_ptr() as _, p.len() as _, roots.as_mut_ptr() as _);
+    }
+    Ok(roots)
+}
+
+/// multiply two polynomials
+///  a      :   coefficients [size: ka x 1], ka > 0
+///  b      :   coefficients [size: kb x 1], kb > 0
+/// # returns
+/// the ka+kb-1 coefficients of the product
+pub fn mul(a: &[f64], b: &[f64]) -> LiquidResult<Vec<f64>> {
+    if a.is_empty() || b.is_empty() {
+        return Err(LiquidError::EmptyBuffer);
+    }
+    let mut c = vec![0f64; a.len() + b.len() - 1];
+    unsafe {
+        raw::poly_mul(
+            a.as_ptr() as _,
+            (a.len() - 1) as _,
+            b.as_ptr() as _,
+            (b.len() - 1) as _,
+            c.as_mut_ptr(),
+        );
+    }
+    Ok(c)
+}
+
+fn check_points(x: &[f64], y: &[f64]) -> LiquidResult<()> {
+    if x.is_empty() {
+        return Err(LiquidError::EmptyBuffer);
+    } else if x.len() != y.len() {
+        return Err(LiquidError::InvalidLength {
+            description: format!("x length: {} y length: {}", x.len(), y.len()),
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{fit, val};
+
+    #[test]
+    fn test_poly_fit() {
+        // p(x) = 1 - 2x + 0.5x^2
+        let x: Vec<f64> = (0..20).map(|i| i as f64 * 0.25).collect();
+        let y: Vec<f64> = x.iter().map(|&v| 1.0 - 2.0 * v + 0.5 * v * v).collect();
+        let p = fit(&x, &y, 3).unwrap();
+        for (a, b) in p.iter().zip([1.0, -2.0, 0.5].iter()) {
+            assert!((a - b).abs() < 1e-9);
+        }
+        assert!((val(&p, 2.0) + 1.0).abs() < 1e-9);
+        assert!(fit(&x, &y[1..], 3).is_err());
+        assert!(fit(&x, &y, 0).is_err());
+    }
+}
@@ -0,0 +1,194 @@
+//! Digital predistortion: lookup-table corrector of the AM/AM and AM/PM
+//! distortion of a power amplifier, placed in front of it to linearize the
+//! transmitter
+//!
+//! The amplifier is characterized from measured input/output pairs:
+//!   |y| = A(|x|)                  AM/AM conversion
+//!   arg(y) - arg(x) = P(|x|)      AM/PM conversion
+//! With G the small-signal gain of A, the corrector maps an input of
+//! amplitude u onto r = A^-1(G*u) with a phase rotation of -P(r), so that
+//! the cascade behaves as a linear gain G up to the saturation of the
+//! amplifier. The curves are least-squares polynomial fits; the table is
+//! evaluated on a uniform amplitude grid and interpolated linearly.
+use num::complex::Complex32;
+
+use crate::errors::LiquidError;
+use crate::poly;
+use crate::LiquidResult;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Predistorter {
+    // amplitude at the input of the amplifier, per table entry
+    amplitude: Vec<f32>,
+    // phase rotation [radians], per table entry
+    phase: Vec<f32>,
+    // input amplitude of the last table entry
+    max_input: f32,
+    gain: f32,
+}
+
+impl Predistorter {
+    /// build the corrector from measurements of the amplifier
+    ///  x          :   amplifier input samples
+    ///  y          :   amplifier output samples [size: x.len() x 1]
+    ///  order      :   order of the fitted polynomials, order > 0
+    ///  table_len  :   number of table entries, table_len >= 2
+    pub fn from_measurements(
+        x: &[Complex32],
+        y: &[Complex32],
+        order: usize,
+        table_len: usize,
+    ) -> LiquidResult<Self> {
+        crate::utils::check_same_len(x.len(), y.len())?;
+        if order == 0 {
+            return Err(LiquidError::InvalidValue(
+                "polynomial order must be greater than zero".to_owned(),
+            ));
+        } else if table_len < 2 {
+            return Err(LiquidError::InvalidValue(format!(
+                "table length: {} must be at least 2",
+                table_len
+            )));
+        }
+        // the phase of vanishing samples is meaningless
+        let (mut r_in, mut r_out, mut phi) = (Vec::new(), Vec::new(), Vec::new());
+        for (a, b) in x.iter().zip(y) {
+            if a.norm() > 0f32 && b.norm() > 0f32 {
+                r_in.push(a.norm() as f64);
+                r_out.push(b.norm() as f64);
+                phi.push((b * a.conj()).arg() as f64);
+            }
+        }
+        if r_in.len() <= order {
+            return Err(LiquidError::InvalidValue(format!(
+                "{} non-zero measurements, at least {} are needed",
+                r_in.len(),
+                order + 1
+            )));
+        }
+
+        // the curves are fitted as gains, which stay smooth at the origin:
+        // A(r)/r, whose value at r = 0 is the small-signal gain, and the
+        // inverse A^-1(G*u)/u, the PA input over its normalized output
+        let am_gain: Vec<f64> = r_out.iter().zip(&r_in).map(|(b, a)| b / a).collect();
+        let gain = poly::fit(&r_in, &am_gain, order + 1)?[0];
+        if gain <= 0f64 || !gain.is_finite() {
+            return Err(LiquidError::InvalidValue(format!(
+                "small-signal gain: {} must be greater than zero",
+                gain
+            )));
+        }
+        let u: Vec<f64> = r_out.iter().map(|v| v / gain).collect();
+        let inv_gain: Vec<f64> = r_in.iter().zip(&u).map(|(a, b)| a / b).collect();
+        let inverse = poly::fit(&u, &inv_gain, order + 1)?;
+        let pm = poly::fit(&r_in, &phi, order + 1)?;
+
+        let max_input = u.iter().cloned().fold(0f64, f64::max);
+        let r_max = r_in.iter().cloned().fold(0f64, f64::max);
+        let mut amplitude = Vec::with_capacity(table_len);
+        let mut phase = Vec::with_capacity(table_len);
+        for i in 0..table_len {
+            let ui = max_input * i as f64 / (table_len - 1) as f64;
+            let r = (ui * poly::val(&inverse, ui)).clamp(0.0, r_max);
+            amplitude.push(r as f32);
+            phase.push(-poly::val(&pm, r) as f32);
+        }
+        Ok(Self {
+            amplitude,
+            phase,
+            max_input: max_input as f32,
+            gain: gain as f32,
+        })
+    }
+
+    /// small-signal gain of the amplifier, i.e. the gain of the linearized
+    /// cascade
+    pub fn get_gain(&self) -> f32 {
+        self.gain
+    }
+
+    /// largest input amplitude that is linearized; larger inputs drive the
+    /// amplifier to the largest measured input amplitude
+    pub fn get_max_input(&self) -> f32 {
+        self.max_input
+    }
+
+    /// number of table entries
+    pub fn len(&self) -> usize {
+        self.amplitude.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.amplitude.is_empty()
+    }
+
+    /// table entries (amplifier input amplitude, phase rotation), on a
+    /// uniform grid of the input amplitude over [0, get_max_input()]
+    pub fn table(&self) -> impl Iterator<Item = (f32, f32)> + '_ {
+        self.amplitude
+            .iter()
+            .cloned()
+            .zip(self.phase.iter().cloned())
+    }
+
+    /// predistort a single sample
+    pub fn apply_sample(&self, x: Complex32) -> Complex32 {
+        let a = x.norm();
+        if a == 0f32 || self.max_input <= 0f32 {
+            return Complex32::default();
+        }
+        let n = self.amplitude.len() - 1;
+        let pos = a / self.max_input * n as f32;
+        let (r, phi) = if pos >= n as f32 {
+            (self.amplitude[n], self.phase[n])
+        } else {
+            let i = pos as usize;
+            let t = pos - i as f32;
+            (
+                self.amplitude[i] + t * (self.amplitude[i + 1] - self.amplitude[i]),
+                self.phase[i] + t * (self.phase[i + 1] - self.phase[i]),
+            )
+        };
+        x * Complex32::from_polar(r / a, phi)
+    }
+
+    /// predistort a block of samples in place
+    pub fn apply(&self, x: &mut [Complex32]) {
+        x.iter_mut().for_each(|v| *v = self.apply_sample(*v));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Predistorter;
+    use crate::impairments::Nonlinearity;
+    use num::complex::Complex32;
+
+    #[test]
+    fn test_predistorter_saleh() {
+        let pa = Nonlinearity::saleh(2.0, 1.0, 1.0, 3.0).unwrap();
+        let saleh = |x: Complex32| pa.apply_sample(x);
+        let x: Vec<Complex32> = (1..=400)
+            .map(|k| Complex32::from_polar(0.7 * k as f32 / 400.0, 0.1 * k as f32))
+            .collect();
+        let y: Vec<Complex32> = x.iter().map(|v| saleh(*v)).collect();
+        assert!(Predistorter::from_measurements(&x, &y[1..], 5, 64).is_err());
+        assert!(Predistorter::from_measurements(&x, &y, 0, 64).is_err());
+        assert!(Predistorter::from_measurements(&x, &y, 5, 1).is_err());
+
+        let q = Predistorter::from_measurements(&x, &y, 5, 256).unwrap();
+        assert_eq!(q.len(), 256);
+        assert!((q.get_gain() - 2.0).abs() < 0.1);
+
+        // the cascade is linear up to the largest measured amplitude, while
+        // the bare amplifier is compressed there
+        let u = Complex32::from_polar(0.9 * q.get_max_input(), 0.3);
+        assert!(saleh(u).norm() < 0.9 * q.get_gain() * u.norm());
+        for k in 1..100 {
+            let u = Complex32::from_polar(q.get_max_input() * k as f32 / 100.0, 0.3);
+            let v = saleh(q.apply_sample(u));
+            assert!((v - u * q.get_gain()).norm() < 0.02 * q.get_gain() * u.norm() + 1e-3);
+        }
+        assert_eq!(q.apply_sample(Complex32::default()), Complex32::default());
+    }
+}
@@ -0,0 +1,242 @@
+//! Quantization and companding: analog-to-digital and digital-to-analog
+//! conversion with a given number of bits, and mu-law compression
+use num::complex::Complex32;
+
+use crate::liquid_dsp_sys as raw;
+use crate::utils::{check_same_len, ToCPointerMut, ToCValue};
+
+use crate::errors::LiquidError;
+use crate::LiquidResult;
+
+/// compander applied by the quantizer before the conversion
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub enum CompanderType {
+    None,
+    Linear,
+    Mulaw,
+    Alaw,
+}
+
+impl From<CompanderType> for u32 {
+    fn from(value: CompanderType) -> u32 {
+        match value {
+            CompanderType::None => raw::liquid_compander_type_LIQUID_COMPANDER_NONE,
+            CompanderType::Linear => raw::liquid_compander_type_LIQUID_COMPANDER_LINEAR,
+            CompanderType::Mulaw => raw::liquid_compander_type_LIQUID_COMPANDER_MULAW,
+            CompanderType::Alaw => raw::liquid_compander_type_LIQUID_COMPANDER_ALAW,
+        }
+    }
+}
+
+fn check_num_bits(num_bits: u32) -> LiquidResult<()> {
+    if num_bits == 0 || num_bits > 32 {
+        return Err(LiquidError::InvalidValue(format!(
+            "number of bits: {} must be in [1, 32]",
+            num_bits
+        )));
+    }
+    Ok(())
+}
+
+fn check_mu(mu: f32) -> LiquidResult<()> {
+    if mu <= 0f32 {
+        return Err(LiquidError::InvalidValue(
+            "mu must be greater than zero".to_owned(),
+        ));
+    }
+    Ok(())
+}
+
+/// quantize a sample in [-1, 1]
+///  x          :   input sample
+///  num_bits   :   number of bits, in [1, 32]
+pub fn quantize_adc(x: f32, num_bits: u32) -> LiquidResult<u32> {
+    check_num_bits(num_bits)?;
+    Ok(unsafe { raw::quantize_adc(x, num_bits) })
+}
+
+/// convert a quantized sample back to [-1, 1]
+///  s          :   quantized sample
+///  num_bits   :   number of bits, in [1, 32]
+pub fn quantize_dac(s: u32, num_bits: u32) -> LiquidResult<f32> {
+    check_num_bits(num_bits)?;
+    Ok(unsafe { raw::quantize_dac(s, num_bits) })
+}
+
+/// mu-law compression of a sample in [-1, 1]
+///  mu     :   compression factor, mu > 0 (e.g. 255)
+pub fn compress_mulaw(x: f32, mu: f32) -> LiquidResult<f32> {
+    check_mu(mu)?;
+    Ok(unsafe { raw::compress_mulaw(x, mu) })
+}
+
+/// mu-law expansion, inverse of compress_mulaw
+///  mu     :   compression factor, mu > 0 (e.g. 255)
+pub fn expand_mulaw(y: f32, mu: f32) -> LiquidResult<f32> {
+    check_mu(mu)?;
+    Ok(unsafe { raw::expand_mulaw(y, mu) })
+}
+
+/// mu-law compression of a block of samples
+///  x      :   input samples in [-1, 1]
+///  y      :   compressed samples [size: x.len() x 1]
+pub fn compress_mulaw_block(x: &[f32], y: &mut [f32], mu: f32) -> LiquidResult<()> {
+    check_mu(mu)?;
+    check_same_len(x.len(), y.len())?;
+    x.iter()
+        .zip(y.iter_mut())
+        .for_each(|(x, y)| *y = unsafe { raw::compress_mulaw(*x, mu) });
+    Ok(())
+}
+
+/// mu-law expansion of a block of samples
+///  y      :   compressed samples
+///  x      :   expanded samples [size: y.len() x 1]
+pub fn expand_mulaw_block(y: &[f32], x: &mut [f32], mu: f32) -> LiquidResult<()> {
+    check_mu(mu)?;
+    check_same_len(y.len(), x.len())?;
+    y.iter()
+        .zip(x.iter_mut())
+        .for_each(|(y, x)| *x = unsafe { raw::expand_mulaw(*y, mu) });
+    Ok(())
+}
+
+/// quantizer of real samples
+pub struct QuantizerRf {
+    inner: raw::quantizerf,
+    num_bits: u32,
+}
+
+/// quantizer of complex samples, each component is quantized with
+/// num_bits/2 bits
+pub struct QuantizerCf {
+    inner: raw::quantizercf,
+    num_bits: u32,
+}
+
+macro_rules! quantizer_impl {
+    ($obj:ty, ($create:expr, $print:expr,
+        $adc:expr, $dac:expr,
+        $destroy:expr,
+        $type:ty)) => {
+        impl $obj {
+            /// create quantizer
+            ///  ctype      :   compander type
+            ///  range      :   maximum absolute input value, range > 0
+            ///  num_bits   :   number of bits per sample, in [1, 32]
+            pub fn create(ctype: CompanderType, range: f32, num_bits: u32) -> LiquidResult<Self> {
+                check_num_bits(num_bits)?;
+                if range <= 0f32 {
+                    return Err(LiquidError::InvalidValue(
+                        "range must be greater than zero".to_owned(),
+                    ));
+                }
+                Ok(Self {
+                    inner: unsafe { $create(ctype.into(), range, num_bits) },
+                    num_bits,
+                })
+            }
+
+            pub fn print(&self) {
+                unsafe {
+                    $print(self.inner);
+                }
+            }
+
+            pub fn get_num_bits(&self) -> u32 {
+                self.num_bits
+            }
+
+            /// quantize a sample
+            pub fn execute_adc(&self, x: $type) -> u32 {
+                let mut s = 0u32;
+                unsafe {
+                    $adc(self.inner, x.to_c_value(), &mut s);
+                }
+                s
+            }
+
+            /// convert a quantized sample back
+            pub fn execute_dac(&self, s: u32) -> $type {
+                let mut x = <$type>::default();
+                unsafe {
+                    $dac(self.inner, s, x.to_ptr_mut());
+                }
+                x
+            }
+
+            /// quantize a block of samples
+            ///  x      :   input samples
+            ///  s      :   quantized samples [size: x.len() x 1]
+            pub fn execute_adc_block(&self, x: &[$type], s: &mut [u32]) -> LiquidResult<()> {
+                check_same_len(x.len(), s.len())?;
+                x.iter()
+                    .zip(s.iter_mut())
+                    .for_each(|(x, s)| *s = self.execute_adc(*x));
+                Ok(())
+            }
+
+            /// convert a block of quantized samples back
+            ///  s      :   quantized samples
+            ///  x      :   output samples [size: s.len() x 1]
+            pub fn execute_dac_block(&self, s: &[u32], x: &mut [$type]) -> LiquidResult<()> {
+                check_same_len(s.len(), x.len())?;
+                s.iter()
+                    .zip(x.iter_mut())
+                    .for_each(|(s, x)| *x = self.execute_dac(*s));
+                Ok(())
+            }
+        }
+
+        impl Drop for $obj {
+            fn drop(&mut self) {
+                unsafe {
+                    $destroy(self.inner);
+                }
+            }
+        }
+    };
+}
+
+quantizer_impl!(
+    QuantizerRf,
+    (
+        raw::quantizerf_create,
+        raw::quantizerf_print,
+        raw::quantizerf_execute_adc,
+        raw::quantizerf_execute_dac,
+        raw::quantizerf_destroy,
+        f32
+    )
+);
+
+quantizer_impl!(
+    QuantizerCf,
+    (
+        raw::quantizercf_create,
+        raw::quantizercf_print,
+        raw::quantizercf_execute_adc,
+        raw::quantizercf_execute_dac,
+        raw::quantizercf_destroy,
+        Complex32
+    )
+);
+
+#[cfg(test)]
+mod tests {
+    use super::{compress_mulaw_block, quantize_adc, CompanderType, QuantizerRf};
+
+    #[test]
+    fn test_quantizer_parameters() {
+        assert!(quantize_adc(0.5, 0).is_err());
+        assert!(quantize_adc(0.5, 33).is_err());
+        assert!(QuantizerRf::create(CompanderType::Mulaw, 0.0, 8).is_err());
+        let q = QuantizerRf::create(CompanderType::Mulaw, 1.0, 8).unwrap();
+        assert_eq!(q.get_num_bits(), 8);
+        let mut s = [0u32; 2];
+        assert!(q.execute_adc_block(&[0.0; 3], &mut s).is_err());
+
+        let mut y = [0f32; 4];
+        assert!(compress_mulaw_block(&[0.0; 4], &mut y, 0.0).is_err());
+    }
+}
@@ -0,0 +1,182 @@
+//! Random number generators of liquid, the same used internally by
+//! ChannelCccf and the other objects that draw random numbers. All of them
+//! use the C library rand(), see seed.
+use num::complex::Complex32;
+
+use crate::liquid_dsp_sys as raw;
+use crate::utils::ToCPointerMut;
+
+use crate::errors::LiquidError;
+use crate::LiquidResult;
+
+fn check_positive(name: &str, value: f32) -> LiquidResult<()> {
+    if value <= 0f32 {
+        return Err(LiquidError::InvalidValue(format!(
+            "{}: {} must be greater than zero",
+            name, value
+        )));
+    }
+    Ok(())
+}
+
+/// seed the C library generator shared by liquid
+pub fn seed(s: u32) {
+    unsafe {
+        libc::srand(s);
+    }
+}
+
+/// uniform random number in [0, 1)
+pub fn uniform() -> f32 {
+    unsafe { raw::randf() }
+}
+
+/// uniform random number in [a, b)
+pub fn uniform_range(a: f32, b: f32) -> LiquidResult<f32> {
+    if b <= a {
+        return Err(LiquidError::InvalidValue(format!(
+            "invalid range: [{}, {})",
+            a, b
+        )));
+    }
+    Ok(unsafe { raw::randuf(a, b) })
+}
+
+/// Gauss random number, zero mean and unit variance
+pub fn gauss() -> f32 {
+    unsafe { raw::randnf() }
+}
+
+/// complex Gauss random number, zero mean and unit variance
+/// (1/2 on each component)
+pub fn complex_gauss() -> Complex32 {
+    let mut y = Complex32::default();
+    unsafe {
+        raw::crandnf(y.to_ptr_mut());
+    }
+    y
+}
+
+/// exponential random number
+///  lambda :   rate, lambda > 0
+pub fn exponential(lambda: f32) -> LiquidResult<f32> {
+    check_positive("lambda", lambda)?;
+    Ok(unsafe { raw::randexpf(lambda) })
+}
+
+/// Weibull random number
+///  alpha  :   scale, alpha > 0
+///  beta   :   shape, beta > 0
+///  gamma  :   location (threshold)
+pub fn weibull(alpha: f32, beta: f32, gamma: f32) -> LiquidResult<f32> {
+    check_positive("alpha", alpha)?;
+    check_positive("beta", beta)?;
+    Ok(unsafe { raw::randweibf(alpha, beta, gamma) })
+}
+
+/// gamma random number
+///  alpha  :   shape, alpha > 0
+///  beta   :   scale, beta > 0
+pub fn gamma(alpha: f32, beta: f32) -> LiquidResult<f32> {
+    check_positive("alpha", alpha)?;
+    check_positive("beta", beta)?;
+    Ok(unsafe { raw::randgammaf(alpha, beta) })
+}
+
+/// Nakagami-m random number
+///  m      :   shape, m >= 0.5
+///  omega  :   spread (mean power), omega > 0
+pub fn nakagami(m: f32, omega: f32) -> LiquidResult<f32> {
+    if m < 0.5 {
+        return Err(LiquidError::InvalidValue(format!(
+            "m: {} must be at least 0.5",
+            m
+        )));
+    }
+    check_positive("omega", omega)?;
+    Ok(unsafe { raw::randnakmf(m, omega) })
+}
+
+/// Rice-K random number, the envelope of a fading channel with a line of
+/// sight component
+///  k      :   ratio of the line of sight power to the scattered power, k >= 0
+///  omega  :   mean power, omega > 0
+pub fn rice(k: f32, omega: f32) -> LiquidResult<f32> {
+    check_rice(k, omega)?;
+    Ok(unsafe { raw::randricekf(k, omega) })
+}
+
+fn check_rice(k: f32, omega: f32) -> LiquidResult<()> {
+    if k < 0f32 {
+        return Err(LiquidError::InvalidValue(format!(
+            "K: {} must not be negative",
+            k
+        )));
+    }
+    check_positive("omega", omega)
+}
+
+/// Rayleigh random number, Rice-K with k = 0
+///  omega  :   mean power, omega > 0
+pub fn rayleigh(omega: f32) -> LiquidResult<f32> {
+    rice(0.0, omega)
+}
+
+/// fill x with Gauss random numbers, zero mean and unit variance
+pub fn fill_gauss(x: &mut [f32]) {
+    x.iter_mut().for_each(|v| *v = gauss());
+}
+
+/// fill x with complex Gauss random numbers, zero mean and unit variance
+pub fn fill_complex_gauss(x: &mut [Complex32]) {
+    x.iter_mut().for_each(|v| *v = complex_gauss());
+}
+
+/// fill x with uniform random numbers in [0, 1)
+pub fn fill_uniform(x: &mut [f32]) {
+    x.iter_mut().for_each(|v| *v = uniform());
+}
+
+/// fill x with Rice-K random numbers, see rice
+pub fn fill_rice(x: &mut [f32], k: f32, omega: f32) -> LiquidResult<()> {
+    check_rice(k, omega)?;
+    x.iter_mut()
+        .for_each(|v| *v = unsafe { raw::randricekf(k, omega) });
+    Ok(())
+}
+
+/// fill x with Rayleigh random numbers, see rayleigh
+pub fn fill_rayleigh(x: &mut [f32], omega: f32) -> LiquidResult<()> {
+    fill_rice(x, 0.0, omega)
+}
+
+/// add white Gauss noise to x
+///  nstd   :   noise standard deviation
+pub fn awgn(x: &mut [f32], nstd: f32) {
+    x.iter_mut().for_each(|v| unsafe { raw::awgn(v, nstd) });
+}
+
+/// add complex white Gauss noise to x
+///  nstd   :   noise standard deviation
+pub fn cawgn(x: &mut [Complex32], nstd: f32) {
+    x.iter_mut()
+        .for_each(|v| unsafe { raw::cawgn(v.to_ptr_mut(), nstd) });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{exponential, fill_rice, nakagami, rayleigh, rice, uniform_range, weibull};
+
+    #[test]
+    fn test_random_parameters() {
+        assert!(uniform_range(1.0, 1.0).is_err());
+        assert!(exponential(0.0).is_err());
+        assert!(weibull(1.0, -1.0, 0.0).is_err());
+        assert!(nakagami(0.4, 1.0).is_err());
+        assert!(rice(-1.0, 1.0).is_err());
+        assert!(rayleigh(0.0).is_err());
+        let mut x = [0f32; 4];
+        assert!(fill_rice(&mut x, -1.0, 1.0).is_err());
+        assert_eq!(x, [0f32; 4]);
+    }
+}
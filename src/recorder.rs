@@ -0,0 +1,179 @@
+//! Triggered signal recorder: keeps a circular history of the most recent
+//! samples and, once triggered, hands the pre-trigger history plus the
+//! following post-trigger samples to a sink.
+use std::collections::VecDeque;
+use std::fmt;
+
+use crate::errors::LiquidError;
+use crate::LiquidResult;
+
+/// destination of the captures produced by a TriggeredRecorder
+pub trait RecorderSink<T> {
+    /// called once per capture with the pre-trigger samples followed by
+    /// the post-trigger samples
+    fn save(&mut self, capture: &[T]);
+}
+
+impl<T, F> RecorderSink<T> for F
+where
+    F: FnMut(&[T]),
+{
+    fn save(&mut self, capture: &[T]) {
+        self(capture)
+    }
+}
+
+impl<T: Clone> RecorderSink<T> for Vec<Vec<T>> {
+    fn save(&mut self, capture: &[T]) {
+        self.push(capture.to_vec());
+    }
+}
+
+pub struct TriggeredRecorder<T, S> {
+    pre: usize,
+    post: usize,
+    history: VecDeque<T>,
+    capture: Vec<T>,
+    remaining: Option<usize>,
+    sink: S,
+}
+
+impl<T: Copy, S: RecorderSink<T>> TriggeredRecorder<T, S> {
+    /// create a recorder object
+    ///  pre    :   number of samples saved before the trigger
+    ///  post   :   number of samples saved after the trigger
+    ///  sink   :   destination of the captures
+    pub fn create(pre: usize, post: usize, sink: S) -> LiquidResult<Self> {
+        if pre == 0 && post == 0 {
+            return Err(LiquidError::InvalidValue(
+                "pre and post trigger lengths cannot both be zero".to_owned(),
+            ));
+        }
+        Ok(Self {
+            pre,
+            post,
+            history: VecDeque::with_capacity(pre),
+            capture: Vec::with_capacity(pre + post),
+            remaining: None,
+            sink,
+        })
+    }
+
+    /// push a single sample into the recorder
+    pub fn push(&mut self, x: T) {
+        if let Some(remaining) = self.remaining {
+            self.capture.push(x);
+            if remaining <= 1 {
+                self.finish();
+            } else {
+                self.remaining = Some(remaining - 1);
+            }
+        }
+        if self.pre > 0 {
+            if self.history.len() == self.pre {
+                self.history.pop_front();
+            }
+            self.history.push_back(x);
+        }
+    }
+
+    /// push a block of samples into the recorder
+    pub fn write(&mut self, x: &[T]) {
+        x.iter().for_each(|&v| self.push(v));
+    }
+
+    /// start a capture with the samples pushed so far as pre-trigger
+    /// history. Returns false if a capture is already in progress.
+    pub fn trigger(&mut self) -> bool {
+        if self.remaining.is_some() {
+            return false;
+        }
+        self.capture.clear();
+        self.capture.extend(self.history.iter());
+        if self.post == 0 {
+            self.finish();
+        } else {
+            self.remaining = Some(self.post);
+        }
+        true
+    }
+
+    /// is a capture waiting for post-trigger samples?
+    pub fn is_capturing(&self) -> bool {
+        self.remaining.is_some()
+    }
+
+    /// hand an incomplete capture to the sink, if any
+    pub fn flush(&mut self) {
+        if self.remaining.is_some() {
+            self.finish();
+        }
+    }
+
+    /// drop the recorded history and any capture in progress
+    pub fn reset(&mut self) {
+        self.history.clear();
+        self.capture.clear();
+        self.remaining = None;
+    }
+
+    pub fn sink(&self) -> &S {
+        &self.sink
+    }
+
+    pub fn sink_mut(&mut self) -> &mut S {
+        &mut self.sink
+    }
+
+    pub fn into_sink(self) -> S {
+        self.sink
+    }
+
+    fn finish(&mut self) {
+        self.sink.save(&self.capture);
+        self.capture.clear();
+        self.remaining = None;
+    }
+}
+
+impl<T, S> fmt::Debug for TriggeredRecorder<T, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "recorder [pre: {}, post: {}, capturing: {}]",
+            self.pre,
+            self.post,
+            self.remaining.is_some()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TriggeredRecorder;
+
+    #[test]
+    fn test_recorder_pre_post_trigger() {
+        let mut rec = TriggeredRecorder::create(3, 2, Vec::new()).unwrap();
+        rec.write(&[0, 1, 2, 3, 4]);
+        assert!(rec.trigger());
+        assert!(!rec.trigger());
+        rec.write(&[5, 6, 7, 8]);
+        assert!(!rec.is_capturing());
+        assert_eq!(rec.into_sink(), vec![vec![2, 3, 4, 5, 6]]);
+    }
+
+    #[test]
+    fn test_recorder_flush() {
+        let mut captures = Vec::new();
+        {
+            let mut rec =
+                TriggeredRecorder::create(2, 4, |c: &[f32]| captures.push(c.len())).unwrap();
+            rec.push(1.0);
+            rec.trigger();
+            rec.push(2.0);
+            rec.flush();
+        }
+        assert_eq!(captures, vec![2]);
+    }
+}
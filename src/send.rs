@@ -0,0 +1,128 @@
+//! Send implementations for the wrapper objects.
+//!
+//! Every wrapper owns exactly one liquid object, created in its constructor
+//! and destroyed in its Drop implementation. liquid objects keep all of
+//! their state in the heap allocation behind that handle and have no thread
+//! affinity, so moving the owning wrapper to another thread is sound as
+//! long as:
+//!
+//! - the handle is never shared: no wrapper hands out copies of its raw
+//!   pointer, and no object keeps a pointer into another wrapper's memory.
+//! - the object does not reference Rust data with a borrowed lifetime.
+//!
+//! None of the objects are Sync. liquid does not distinguish read-only
+//! calls from the ones that update the object, so methods taking &self are
+//! not guaranteed to be free of side effects and concurrent access through
+//! a shared reference is not allowed.
+//!
+//! Left out on purpose: Firdespm, as the design may hold a user supplied
+//! callback that is not required to be Send.
+//!
+//! Objects that draw random numbers (ChannelCccf, the seed of
+//! TvmpchCccf::create and Modem::gen_rand_sym) use the C library rand(),
+//! which is shared by all threads; results are still valid but not
+//! reproducible across threads.
+//! When liquid is built against FFTW, plan creation and destruction are not
+//! thread safe, so the FFT based objects (FftPlan, FftFilt*, Asgram*,
+//! Spgram*, Spwaterfall*) should be created and dropped from one thread at
+//! a time.
+use crate::agc::{AgcCrcf, AgcRrrf};
+use crate::cbuffer::{CbufferCf, CbufferRf};
+use crate::channel::ChannelCccf;
+use crate::cvsd::Cvsd;
+use crate::fec::{Fec, Interleaver, Packetizer};
+use crate::fft::{AsgramCf, AsgramRf, FftPlan, SpgramCf, SpgramRf, SpwaterfallCf, SpwaterfallRf};
+use crate::filter::{
+    AutoCorrCccf, AutoCorrRrrf, FftFiltCccf, FftFiltCrcf, FftFiltRrrf, FirFiltCccf, FirFiltCrcf,
+    FirFiltRrrf, FirHilbt, FirInterpCccf, FirInterpCrcf, FirInterpRrrf, IirFiltCccf, IirFiltCrcf,
+    IirFiltRrrf, IirHilbt, MsDecimCrcf, MsDecimRrrf, RresampCccf, RresampCrcf, RresampRrrf,
+};
+use crate::framing::{
+    BsyncCccf, BsyncCrcf, BsyncRrrf, DetectorCccf, Framegen64, OfdmFlexFrameGen, SymtrackCccf,
+};
+use crate::modem::{AmpModem, CpfskDem, CpfskMod, Modem};
+use crate::quantization::{QuantizerCf, QuantizerRf};
+use crate::sequence::{Bsequence, Msequence};
+
+macro_rules! send_impl {
+    ($($obj:ty),* $(,)?) => {
+        $(
+            // SAFETY: see the module documentation
+            unsafe impl Send for $obj {}
+        )*
+    };
+}
+
+send_impl!(
+    AgcCrcf,
+    AgcRrrf,
+    AmpModem,
+    AsgramCf,
+    AsgramRf,
+    AutoCorrCccf,
+    AutoCorrRrrf,
+    Bsequence,
+    BsyncCccf,
+    BsyncCrcf,
+    BsyncRrrf,
+    CbufferCf,
+    CbufferRf,
+    ChannelCccf,
+    CpfskDem,
+    CpfskMod,
+    Cvsd,
+    DetectorCccf,
+    Fec,
+    FftFiltCccf,
+    FftFiltCrcf,
+    FftFiltRrrf,
+    FftPlan,
+    FirFiltCccf,
+    FirFiltCrcf,
+    FirFiltRrrf,
+    FirHilbt,
+    FirInterpCccf,
+    FirInterpCrcf,
+    FirInterpRrrf,
+    Framegen64,
+    IirFiltCccf,
+    IirFiltCrcf,
+    IirFiltRrrf,
+    IirHilbt,
+    Interleaver,
+    Modem,
+    MsDecimCrcf,
+    MsDecimRrrf,
+    Msequence,
+    OfdmFlexFrameGen,
+    Packetizer,
+    QuantizerCf,
+    QuantizerRf,
+    RresampCccf,
+    RresampCrcf,
+    RresampRrrf,
+    SpgramCf,
+    SpgramRf,
+    SpwaterfallCf,
+    SpwaterfallRf,
+    SymtrackCccf,
+);
+
+#[cfg(test)]
+mod tests {
+    use crate::filter::FirFiltCrcf;
+    use crate::modem::{FmDiscriminator, Slicer};
+    use crate::{Packetizer, TriggeredRecorder};
+
+    fn assert_send<T: Send>() {}
+
+    #[test]
+    fn test_wrappers_are_send() {
+        assert_send::<FirFiltCrcf>();
+        assert_send::<Packetizer>();
+        // composite objects are Send through their fields
+        assert_send::<Slicer>();
+        assert_send::<FmDiscriminator>();
+        assert_send::<TriggeredRecorder<f32, Vec<Vec<f32>>>>();
+    }
+}
@@ -0,0 +1,348 @@
+//! Binary sequences: m-sequence linear feedback shift register generator
+//! and generic binary sequence objects
+use libc::c_uint;
+use std::fmt;
+
+use crate::errors::LiquidError;
+use crate::liquid_dsp_sys as raw;
+use crate::LiquidResult;
+
+/// maximal-length sequence (LFSR) generator
+pub struct Msequence {
+    inner: raw::msequence,
+}
+
+/// binary sequence
+pub struct Bsequence {
+    inner: raw::bsequence,
+}
+
+impl Msequence {
+    /// create a maximal-length sequence (m-sequence) object with
+    /// an internal shift register length of m bits.
+    ///  m      :   generator polynomial length, sequence length is (2^m)-1
+    ///  g      :   generator polynomial, starting with most-significant bit
+    ///  a      :   initial shift register state, non-zero, default: 000...001
+    pub fn create(m: u32, g: u32, a: u32) -> LiquidResult<Self> {
+        if !(2..=15).contains(&m) {
+            return Err(LiquidError::InvalidValue(format!(
+                "m: {} out of range [2, 15]",
+                m
+            )));
+        } else if g == 0 {
+            return Err(LiquidError::InvalidValue(
+                "generator polynomial must not be zero".to_owned(),
+            ));
+        } else if a == 0 {
+            // an all-zero shift register never leaves the zero state
+            return Err(LiquidError::InvalidValue(
+                "initial shift register state must not be zero".to_owned(),
+            ));
+        }
+        Ok(Self {
+            inner: unsafe { raw::msequence_create(m as c_uint, g as c_uint, a as c_uint) },
+        })
+    }
+
+    /// create a maximal-length sequence (m-sequence) object from a generator polynomial
+    ///  g      :   generator polynomial, starting with most-significant bit
+    pub fn create_genpoly(g: u32) -> LiquidResult<Self> {
+        if g < 2 {
+            return Err(LiquidError::InvalidValue(
+                "generator polynomial must be at least 2".to_owned(),
+            ));
+        }
+        Ok(Self {
+            inner: unsafe { raw::msequence_create_genpoly(g as c_uint) },
+        })
+    }
+
+    /// creates a default maximal-length sequence
+    ///  m      :   generator polynomial length, sequence length is (2^m)-1
+    pub fn create_default(m: u32) -> LiquidResult<Self> {
+        if !(2..=15).contains(&m) {
+            return Err(LiquidError::InvalidValue(format!(
+                "m: {} out of range [2, 15]",
+                m
+            )));
+        }
+        Ok(Self {
+            inner: unsafe { raw::msequence_create_default(m as c_uint) },
+        })
+    }
+
+    /// print m-sequence object internals
+    pub fn print(&self) {
+        unsafe {
+            raw::msequence_print(self.inner);
+        }
+    }
+
+    /// advance msequence on shift register, returning output bit
+    pub fn advance(&mut self) -> u32 {
+        unsafe { raw::msequence_advance(self.inner) as u32 }
+    }
+
+    /// generate pseudo-random symbol from shift register by
+    /// advancing _bps bits and returning compacted symbol
+    ///  bps    :   bits per symbol of output, in [1, 32]
+    pub fn generate_symbol(&mut self, bps: u32) -> LiquidResult<u32> {
+        if bps == 0 || bps > 32 {
+            return Err(LiquidError::InvalidValue(format!(
+                "bps: {} out of range [1, 32]",
+                bps
+            )));
+        }
+        unsafe { Ok(raw::msequence_generate_symbol(self.inner, bps as c_uint) as u32) }
+    }
+
+    /// fill a slice with the output bits of the sequence, one bit per element
+    pub fn fill(&mut self, bits: &mut [u8]) {
+        bits.iter_mut().for_each(|b| *b = self.advance() as u8);
+    }
+
+    /// reset msequence shift register to original state, typically '1'
+    pub fn reset(&mut self) {
+        unsafe {
+            raw::msequence_reset(self.inner);
+        }
+    }
+
+    /// get the length of the sequence
+    pub fn len(&self) -> usize {
+        unsafe { raw::msequence_get_length(self.inner) as usize }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// get the internal state of the sequence
+    pub fn get_state(&self) -> u32 {
+        unsafe { raw::msequence_get_state(self.inner) as u32 }
+    }
+
+    /// set the internal state of the sequence
+    pub fn set_state(&mut self, a: u32) {
+        unsafe {
+            raw::msequence_set_state(self.inner, a as c_uint);
+        }
+    }
+}
+
+impl fmt::Debug for Msequence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "msequence [len: {}, state: {:#x}]",
+            self.len(),
+            self.get_state()
+        )
+    }
+}
+
+impl Drop for Msequence {
+    fn drop(&mut self) {
+        unsafe {
+            raw::msequence_destroy(self.inner);
+        }
+    }
+}
+
+impl Bsequence {
+    /// create binary sequence object
+    ///  num_bits   :   number of bits in the sequence
+    pub fn create(num_bits: u32) -> LiquidResult<Self> {
+        if num_bits == 0 {
+            return Err(LiquidError::InvalidValue(
+                "number of bits must be greater than zero".to_owned(),
+            ));
+        }
+        Ok(Self {
+            inner: unsafe { raw::bsequence_create(num_bits as c_uint) },
+        })
+    }
+
+    /// create a new binary sequence from an m-sequence; the length
+    /// of the binary sequence is the length of the m-sequence
+    pub fn from_msequence(ms: &mut Msequence) -> Self {
+        let bs = Self {
+            inner: unsafe { raw::bsequence_create(ms.len() as c_uint) },
+        };
+        unsafe {
+            raw::bsequence_init_msequence(bs.inner, ms.inner);
+        }
+        bs
+    }
+
+    /// create complementary codes a and b
+    ///  n      :   length of each sequence, must be a power of 2, at least 8
+    pub fn create_ccodes(n: u32) -> LiquidResult<(Self, Self)> {
+        if n < 8 || !n.is_power_of_two() {
+            return Err(LiquidError::InvalidValue(format!(
+                "n: {} must be a power of 2 and at least 8",
+                n
+            )));
+        }
+        let a = Self::create(n)?;
+        let b = Self::create(n)?;
+        unsafe {
+            raw::bsequence_create_ccodes(a.inner, b.inner);
+        }
+        Ok((a, b))
+    }
+
+    /// reset binary sequence
+    pub fn reset(&mut self) {
+        unsafe {
+            raw::bsequence_reset(self.inner);
+        }
+    }
+
+    /// initialize sequence on external array
+    ///  v      :   packed bytes, at least ceil(len / 8) elements
+    pub fn init(&mut self, v: &[u8]) -> LiquidResult<()> {
+        let required = self.len().div_ceil(8);
+        if v.len() < required {
+            return Err(LiquidError::InvalidLength {
+                description: format!("v length: {} valid length: {}", v.len(), required),
+            });
+        }
+        unsafe {
+            raw::bsequence_init(self.inner, v.as_ptr() as _);
+        }
+        Ok(())
+    }
+
+    /// print binary sequence
+    pub fn print(&self) {
+        unsafe {
+            raw::bsequence_print(self.inner);
+        }
+    }
+
+    /// push bit into to back of a binary sequence
+    pub fn push(&mut self, bit: u32) {
+        unsafe {
+            raw::bsequence_push(self.inner, bit as c_uint);
+        }
+    }
+
+    /// circular shift (left)
+    pub fn circshift(&mut self) {
+        unsafe {
+            raw::bsequence_circshift(self.inner);
+        }
+    }
+
+    /// correlate two binary sequences together
+    pub fn correlate(&self, other: &Bsequence) -> LiquidResult<i32> {
+        self.check_len(other)?;
+        unsafe { Ok(raw::bsequence_correlate(self.inner, other.inner) as i32) }
+    }
+
+    /// compute the binary addition (xor) of two sequences
+    pub fn add(&self, other: &Bsequence) -> LiquidResult<Bsequence> {
+        self.check_len(other)?;
+        let out = Self::create(self.len() as u32)?;
+        unsafe {
+            raw::bsequence_add(self.inner, other.inner, out.inner);
+        }
+        Ok(out)
+    }
+
+    /// compute the binary multiplication (and) of two sequences
+    pub fn mul(&self, other: &Bsequence) -> LiquidResult<Bsequence> {
+        self.check_len(other)?;
+        let out = Self::create(self.len() as u32)?;
+        unsafe {
+            raw::bsequence_mul(self.inner, other.inner, out.inner);
+        }
+        Ok(out)
+    }
+
+    /// accumulate the 1's in a binary sequence
+    pub fn accumulate(&self) -> u32 {
+        unsafe { raw::bsequence_accumulate(self.inner) as u32 }
+    }
+
+    /// return length of the binary sequence
+    pub fn len(&self) -> usize {
+        unsafe { raw::bsequence_get_length(self.inner) as usize }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// return the i-th bit of the sequence
+    pub fn index(&self, i: usize) -> Option<u32> {
+        if i >= self.len() {
+            return None;
+        }
+        unsafe { Some(raw::bsequence_index(self.inner, i as c_uint) as u32) }
+    }
+
+    fn check_len(&self, other: &Bsequence) -> LiquidResult<()> {
+        if self.len() != other.len() {
+            return Err(LiquidError::InvalidLength {
+                description: format!(
+                    "sequence lengths must be equal: {} != {}",
+                    self.len(),
+                    other.len()
+                ),
+            });
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for Bsequence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "bsequence [len: {}]", self.len())
+    }
+}
+
+impl Drop for Bsequence {
+    fn drop(&mut self) {
+        unsafe {
+            raw::bsequence_destroy(self.inner);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Bsequence, Msequence};
+
+    #[test]
+    fn test_msequence_period() {
+        let mut ms = Msequence::create_default(5).unwrap();
+        assert_eq!(ms.len(), 31);
+        let mut bits = vec![0u8; 62];
+        ms.fill(&mut bits);
+        assert_eq!(&bits[..31], &bits[31..]);
+        // an m-sequence has 2^(m-1) ones per period
+        assert_eq!(bits[..31].iter().filter(|&&b| b == 1).count(), 16);
+    }
+
+    #[test]
+    fn test_msequence_create_state() {
+        // x^5 + x^2 + 1
+        assert!(Msequence::create(5, 0x25, 0).is_err());
+        let mut ms = Msequence::create(5, 0x25, 1).unwrap();
+        assert_eq!(ms.len(), 31);
+        let mut bits = vec![0u8; 31];
+        ms.fill(&mut bits);
+        assert_eq!(bits.iter().filter(|&&b| b == 1).count(), 16);
+    }
+
+    #[test]
+    fn test_bsequence_from_msequence() {
+        let mut ms = Msequence::create_default(4).unwrap();
+        let bs = Bsequence::from_msequence(&mut ms);
+        assert_eq!(bs.len(), 15);
+        assert_eq!(bs.accumulate(), 8);
+        assert!(bs.index(15).is_none());
+    }
+}
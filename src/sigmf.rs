@@ -0,0 +1,328 @@
+//! *sigmf* : export and import of recordings in the Signal Metadata
+//!         Format (https://sigmf.org)
+//!
+//! A recording is a pair of files sharing a base name: <base>.sigmf-data
+//! with the samples and <base>.sigmf-meta with the JSON metadata. The
+//! samples are written as cf32_le; cf32_le and ci16_le recordings can be
+//! read back, the ci16_le samples being scaled to [-1, 1).
+//!
+//! Annotations map to stream tags: the annotation starts at the tag
+//! offset, its label is the tag key and its comment the tag value. The
+//! value type is kept in the "liquid:value_type" field, so that tags
+//! written by this module are read back unchanged.
+use num::complex::Complex32;
+use serde_json::{json, Map, Value};
+use std::fs::{self, File};
+use std::io::{self, BufReader};
+use std::path::{Path, PathBuf};
+
+use crate::io::{write_iq_file, IqReader, SampleFormat};
+use crate::tag::{Tag, TagValue};
+
+const SIGMF_VERSION: &str = "1.0.0";
+
+/// annotation of a segment of the recording
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SigmfAnnotation {
+    /// index of the first sample of the segment
+    pub sample_start: u64,
+    /// number of samples of the segment
+    pub sample_count: Option<u64>,
+    /// lower and upper frequency edges of the segment [Hz]
+    pub freq_edges: Option<(f64, f64)>,
+    pub label: Option<String>,
+    pub comment: Option<String>,
+    /// type of the tag value the comment holds, see Tag conversion
+    value_type: Option<String>,
+}
+
+impl SigmfAnnotation {
+    pub fn new(sample_start: u64) -> Self {
+        Self {
+            sample_start,
+            ..Self::default()
+        }
+    }
+
+    /// annotation holding a tag
+    pub fn from_tag(tag: &Tag) -> Self {
+        let (value_type, comment) = match &tag.value {
+            TagValue::Bool(v) => ("bool", v.to_string()),
+            TagValue::Int(v) => ("int", v.to_string()),
+            TagValue::Float(v) => ("float", v.to_string()),
+            TagValue::Str(v) => ("str", v.clone()),
+            TagValue::Bytes(v) => ("bytes", v.iter().map(|b| format!("{:02x}", b)).collect()),
+        };
+        Self {
+            sample_start: tag.offset,
+            label: Some(tag.key.clone()),
+            comment: Some(comment),
+            value_type: Some(value_type.to_owned()),
+            ..Self::default()
+        }
+    }
+
+    /// tag at the start of the annotation, keyed by its label ("annotation"
+    /// if none); the value is restored from the comment when the
+    /// annotation was written from a tag, and is the comment string, or
+    /// true without comment, otherwise
+    pub fn to_tag(&self) -> Tag {
+        let key = self
+            .label
+            .clone()
+            .unwrap_or_else(|| "annotation".to_owned());
+        let comment = self.comment.clone().unwrap_or_default();
+        let value = match self.value_type.as_deref() {
+            Some("bool") => comment.parse().ok().map(TagValue::Bool),
+            Some("int") => comment.parse().ok().map(TagValue::Int),
+            Some("float") => comment.parse().ok().map(TagValue::Float),
+            Some("bytes") => (0..comment.len())
+                .step_by(2)
+                .map(|i| {
+                    comment
+                        .get(i..i + 2)
+                        .and_then(|b| u8::from_str_radix(b, 16).ok())
+                })
+                .collect::<Option<Vec<u8>>>()
+                .map(TagValue::Bytes),
+            _ => None,
+        };
+        let value = value.unwrap_or_else(|| match &self.comment {
+            Some(c) => TagValue::Str(c.clone()),
+            None => TagValue::Bool(true),
+        });
+        Tag::new(self.sample_start, key, value)
+    }
+}
+
+/// samples of a recording along with its metadata
+#[derive(Clone, Debug, PartialEq)]
+pub struct SigmfRecording {
+    /// sample rate [Hz]
+    pub sample_rate: f64,
+    /// center frequency of the capture [Hz]
+    pub frequency: Option<f64>,
+    pub description: Option<String>,
+    pub annotations: Vec<SigmfAnnotation>,
+    pub samples: Vec<Complex32>,
+}
+
+impl SigmfRecording {
+    pub fn new(samples: Vec<Complex32>, sample_rate: f64) -> Self {
+        Self {
+            sample_rate,
+            frequency: None,
+            description: None,
+            annotations: Vec::new(),
+            samples,
+        }
+    }
+
+    /// annotate the recording with tags, e.g. the frame detections of a
+    /// Pipeline::process_tagged run over the samples
+    pub fn add_tags(&mut self, tags: &[Tag]) {
+        self.annotations
+            .extend(tags.iter().map(SigmfAnnotation::from_tag));
+    }
+
+    /// tags of the annotations, sorted by offset
+    pub fn tags(&self) -> Vec<Tag> {
+        let mut tags: Vec<Tag> = self.annotations.iter().map(|a| a.to_tag()).collect();
+        tags.sort_by_key(|t| t.offset);
+        tags
+    }
+
+    /// write <base>.sigmf-meta and <base>.sigmf-data
+    pub fn write<P: AsRef<Path>>(&self, base: P) -> io::Result<()> {
+        let (meta, data) = paths(base.as_ref());
+        write_iq_file(data, &self.samples)?;
+        fs::write(meta, self.metadata())
+    }
+
+    /// read <base>.sigmf-meta and <base>.sigmf-data
+    pub fn read<P: AsRef<Path>>(base: P) -> io::Result<Self> {
+        let (meta, data) = paths(base.as_ref());
+        let meta: Value = serde_json::from_str(&fs::read_to_string(meta)?)?;
+        let global = meta
+            .get("global")
+            .ok_or_else(|| invalid_data("missing global object"))?;
+        let datatype = global
+            .get("core:datatype")
+            .and_then(Value::as_str)
+            .ok_or_else(|| invalid_data("missing core:datatype"))?;
+        let format = match datatype {
+            "cf32_le" => SampleFormat::F32,
+            "ci16_le" => SampleFormat::I16,
+            _ => return Err(invalid_data(&format!("unsupported datatype: {}", datatype))),
+        };
+        let sample_rate = global
+            .get("core:sample_rate")
+            .and_then(Value::as_f64)
+            .ok_or_else(|| invalid_data("missing core:sample_rate"))?;
+        let description = global
+            .get("core:description")
+            .and_then(Value::as_str)
+            .map(str::to_owned);
+        let frequency = meta
+            .get("captures")
+            .and_then(Value::as_array)
+            .and_then(|c| c.first())
+            .and_then(|c| c.get("core:frequency"))
+            .and_then(Value::as_f64);
+        let annotations = meta
+            .get("annotations")
+            .and_then(Value::as_array)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+            .iter()
+            .map(parse_annotation)
+            .collect::<io::Result<Vec<_>>>()?;
+
+        let reader = BufReader::new(File::open(data)?);
+        let mut reader = IqReader::with_format(Box::new(reader), format, 2, 4096, None)?;
+        let mut samples = Vec::new();
+        let mut chunk = Vec::new();
+        while reader.read_chunk(&mut chunk)? > 0 {
+            samples.extend_from_slice(&chunk);
+        }
+        Ok(Self {
+            sample_rate,
+            frequency,
+            description,
+            annotations,
+            samples,
+        })
+    }
+
+    /// JSON content of the .sigmf-meta file
+    pub fn metadata(&self) -> String {
+        let mut global = Map::new();
+        global.insert("core:datatype".to_owned(), "cf32_le".into());
+        global.insert("core:sample_rate".to_owned(), self.sample_rate.into());
+        if let Some(d) = &self.description {
+            global.insert("core:description".to_owned(), d.as_str().into());
+        }
+        global.insert("core:recorder".to_owned(), "liquid-dsp-rs".into());
+        global.insert("core:version".to_owned(), SIGMF_VERSION.into());
+        let mut capture = Map::new();
+        capture.insert("core:sample_start".to_owned(), 0u64.into());
+        if let Some(f) = self.frequency {
+            capture.insert("core:frequency".to_owned(), f.into());
+        }
+        let mut annotations: Vec<&SigmfAnnotation> = self.annotations.iter().collect();
+        annotations.sort_by_key(|a| a.sample_start);
+        let annotations: Vec<Value> = annotations.iter().map(|a| annotation(a)).collect();
+        format!(
+            "{:#}\n",
+            json!({
+                "global": global,
+                "captures": [capture],
+                "annotations": annotations,
+            })
+        )
+    }
+}
+
+fn paths(base: &Path) -> (PathBuf, PathBuf) {
+    let with_ext = |ext: &str| {
+        let mut p = base.as_os_str().to_owned();
+        p.push(ext);
+        PathBuf::from(p)
+    };
+    (with_ext(".sigmf-meta"), with_ext(".sigmf-data"))
+}
+
+fn annotation(a: &SigmfAnnotation) -> Value {
+    let mut fields = Map::new();
+    fields.insert("core:sample_start".to_owned(), a.sample_start.into());
+    if let Some(n) = a.sample_count {
+        fields.insert("core:sample_count".to_owned(), n.into());
+    }
+    if let Some((lo, hi)) = a.freq_edges {
+        fields.insert("core:freq_lower_edge".to_owned(), lo.into());
+        fields.insert("core:freq_upper_edge".to_owned(), hi.into());
+    }
+    if let Some(l) = &a.label {
+        fields.insert("core:label".to_owned(), l.as_str().into());
+    }
+    if let Some(c) = &a.comment {
+        fields.insert("core:comment".to_owned(), c.as_str().into());
+    }
+    if let Some(t) = &a.value_type {
+        fields.insert("liquid:value_type".to_owned(), t.as_str().into());
+    }
+    fields.into()
+}
+
+fn parse_annotation(a: &Value) -> io::Result<SigmfAnnotation> {
+    let u64_field = |k: &str| a.get(k).and_then(Value::as_u64);
+    let str_field = |k: &str| a.get(k).and_then(Value::as_str).map(str::to_owned);
+    let f64_field = |k: &str| a.get(k).and_then(Value::as_f64);
+    Ok(SigmfAnnotation {
+        sample_start: u64_field("core:sample_start")
+            .ok_or_else(|| invalid_data("annotation without core:sample_start"))?,
+        sample_count: u64_field("core:sample_count"),
+        freq_edges: f64_field("core:freq_lower_edge").zip(f64_field("core:freq_upper_edge")),
+        label: str_field("core:label"),
+        comment: str_field("core:comment"),
+        value_type: str_field("liquid:value_type"),
+    })
+}
+
+fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SigmfAnnotation, SigmfRecording};
+    use crate::tag::{Tag, TagValue};
+    use num::complex::Complex32;
+
+    #[test]
+    fn test_sigmf_round_trip() {
+        let samples: Vec<Complex32> = (0..32)
+            .map(|i| Complex32::new(i as f32, -0.5 * i as f32))
+            .collect();
+        let mut rec = SigmfRecording::new(samples, 1e6);
+        rec.frequency = Some(915e6);
+        rec.description = Some("frame \"capture\" \u{1f4e1}".to_owned());
+        let tags = vec![
+            Tag::new(3, "frame", TagValue::Int(7)),
+            Tag::new(10, "evm", TagValue::Float(-21.5)),
+            Tag::new(12, "header", TagValue::Bytes(vec![0, 0xab])),
+            Tag::new(20, "agc_squelch", TagValue::Str("fall".to_owned())),
+        ];
+        rec.add_tags(&tags);
+        // not representable as f64
+        let mut far = SigmfAnnotation::new((1 << 53) + 1);
+        far.sample_count = Some(u64::MAX);
+        rec.annotations.push(far);
+
+        let base = std::env::temp_dir().join(format!("liquid_sigmf_{}", std::process::id()));
+        rec.write(&base).unwrap();
+        let read = SigmfRecording::read(&base);
+
+        // surrogate pairs of escaped characters outside the BMP
+        let meta = base.with_extension("sigmf-meta");
+        std::fs::write(
+            &meta,
+            r#"{"global": {"core:datatype": "cf32_le", "core:sample_rate": 8000,
+                "core:description": "\ud83d\udce1"}}"#,
+        )
+        .unwrap();
+        let escaped = SigmfRecording::read(&base);
+        std::fs::write(&meta, r#"{"global": {"core:datatype": "cf32_le"}"#).unwrap();
+        let truncated = SigmfRecording::read(&base);
+        let _ = std::fs::remove_file(meta);
+        let _ = std::fs::remove_file(base.with_extension("sigmf-data"));
+
+        let read = read.unwrap();
+        assert_eq!(read, rec);
+        assert_eq!(read.tags()[..4], tags[..]);
+        let escaped = escaped.unwrap();
+        assert_eq!(escaped.description.as_deref(), Some("\u{1f4e1}"));
+        assert_eq!(escaped.samples, rec.samples);
+        assert!(truncated.is_err());
+    }
+}
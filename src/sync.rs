@@ -0,0 +1,645 @@
+//! Symbol timing recovery building blocks: a matched filter, timing error
+//! detectors working on interpolated samples, a proportional-integral loop
+//! filter and a Gardner recovery loop driving a Farrow interpolator, for
+//! custom loops where the symsync object does not fit. Carrier recovery is done by a
+//! Costas loop driving an NCO.
+//!
+//! The detectors return an error that is positive when the samples are
+//! taken late and that is, close to lock, proportional to the timing error
+//! in symbols; the gains below hold for unit-energy symbols shaped by a
+//! raised-cosine pulse with an excess bandwidth of 0.35.
+use num::complex::Complex32;
+
+use crate::block::Block;
+use crate::errors::LiquidError;
+use crate::filter::{FirFarrowCrcf, FirFiltCrcf, Firdes, FirdesFilterType};
+use crate::nco::{NcoCrcf, NcoType};
+use crate::utils::check_same_len;
+use crate::LiquidResult;
+
+/// slope of the Gardner detector at lock
+pub const GARDNER_GAIN: f32 = 1.08;
+
+/// slope of the Mueller-Muller detector at lock
+pub const MUELLER_MULLER_GAIN: f32 = 1.78;
+
+// Farrow interpolator of the recovery loop
+const INTERP_LEN: usize = 8;
+const INTERP_ORDER: u32 = 4;
+const INTERP_FC: f32 = 0.45;
+const INTERP_AS: f32 = 60.0;
+
+/// Gardner detector, two samples per symbol and insensitive to the
+/// carrier phase:
+///   e[k] = Re{ conj(y[k-1/2]) * (y[k] - y[k-1]) }
+#[derive(Clone, Debug, Default)]
+pub struct Gardner {
+    prev: Complex32,
+}
+
+impl Gardner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn reset(&mut self) {
+        self.prev = Complex32::default();
+    }
+
+    /// timing error of a symbol
+    ///  mid    :   sample halfway between the previous symbol and this one
+    ///  y      :   symbol sample
+    pub fn update(&mut self, mid: Complex32, y: Complex32) -> f32 {
+        let e = (mid.conj() * (y - self.prev)).re;
+        self.prev = y;
+        e
+    }
+}
+
+/// Mueller-Muller detector, one sample per symbol, decision directed:
+///   e[k] = Re{ conj(d[k]) * y[k-1] - conj(d[k-1]) * y[k] }
+#[derive(Clone, Debug, Default)]
+pub struct MuellerMuller {
+    prev: Complex32,
+    prev_decision: Complex32,
+}
+
+/// sign of a component, zero staying zero so that real signals have no
+/// imaginary decision
+fn slice(v: f32) -> f32 {
+    if v > 0f32 {
+        1.0
+    } else if v < 0f32 {
+        -1.0
+    } else {
+        0.0
+    }
+}
+
+impl MuellerMuller {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn reset(&mut self) {
+        self.prev = Complex32::default();
+        self.prev_decision = Complex32::default();
+    }
+
+    /// timing error of a symbol, with a hard BPSK/QPSK decision taken on
+    /// the sign of each component
+    pub fn update(&mut self, y: Complex32) -> f32 {
+        let d = Complex32::new(slice(y.re), slice(y.im));
+        self.update_with_decision(y, d)
+    }
+
+    /// timing error of a symbol, with the decision of another slicer, e.g.
+    /// the remodulated output of a demodulator
+    ///  y      :   symbol sample
+    ///  d      :   decision on y
+    pub fn update_with_decision(&mut self, y: Complex32, d: Complex32) -> f32 {
+        let e = (d.conj() * self.prev - self.prev_decision.conj() * y).re;
+        self.prev = y;
+        self.prev_decision = d;
+        e
+    }
+}
+
+/// proportional-integral loop filter
+///   v[k] = kp * e[k] + ki * sum(e[0..=k])
+#[derive(Clone, Debug, PartialEq)]
+pub struct LoopFilter {
+    kp: f32,
+    ki: f32,
+    integrator: f32,
+}
+
+impl LoopFilter {
+    /// create a loop filter from its gains
+    pub fn create(kp: f32, ki: f32) -> Self {
+        Self {
+            kp,
+            ki,
+            integrator: 0.0,
+        }
+    }
+
+    /// design the gains of a second-order loop
+    ///  bandwidth  :   noise bandwidth normalized to the update rate,
+    ///                 in (0, 0.5)
+    ///  damping    :   damping factor, damping > 0 (1/sqrt(2) suggested)
+    ///  gain       :   gain of the detector and controlled oscillator,
+    ///                 gain > 0
+    pub fn from_bandwidth(bandwidth: f32, damping: f32, gain: f32) -> LiquidResult<Self> {
+        if bandwidth <= 0f32 || bandwidth >= 0.5 {
+            return Err(LiquidError::InvalidValue(format!(
+                "loop bandwidth: {} must be in (0, 0.5)",
+                bandwidth
+            )));
+        } else if damping <= 0f32 {
+            return Err(LiquidError::InvalidValue(
+                "damping factor must be greater than zero".to_owned(),
+            ));
+        } else if gain <= 0f32 {
+            return Err(LiquidError::InvalidValue(
+                "loop gain must be greater than zero".to_owned(),
+            ));
+        }
+        let theta = bandwidth / (damping + 0.25 / damping);
+        let d = 1.0 + 2.0 * damping * theta + theta * theta;
+        Ok(Self::create(
+            4.0 * damping * theta / d / gain,
+            4.0 * theta * theta / d / gain,
+        ))
+    }
+
+    /// proportional and integral gains
+    pub fn get_gains(&self) -> (f32, f32) {
+        (self.kp, self.ki)
+    }
+
+    pub fn reset(&mut self) {
+        self.integrator = 0.0;
+    }
+
+    /// filter an error sample
+    /// # returns
+    /// the control value
+    pub fn update(&mut self, e: f32) -> f32 {
+        self.integrator += self.ki * e;
+        self.kp * e + self.integrator
+    }
+}
+
+/// Gardner timing recovery: a modulo-1 counter, decremented by w every
+/// input sample, strobes the Farrow interpolator twice per symbol, the
+/// fractional position being the counter value at underflow over w; the
+/// filtered detector output scales w = (2/k) * (1 + v), i.e. the symbols
+/// are taken v symbols earlier
+pub struct TimingRecovery {
+    interp: FirFarrowCrcf,
+    ted: Gardner,
+    filter: LoopFilter,
+    k: f32,
+    w: f32,
+    counter: f32,
+    // the next strobe is halfway between symbols
+    mid_next: bool,
+    mid: Complex32,
+}
+
+impl TimingRecovery {
+    /// create a recovery loop
+    ///  k          :   nominal samples per symbol, k >= 2
+    ///  bandwidth  :   loop noise bandwidth normalized to the symbol rate,
+    ///                 in (0, 0.5), e.g. 0.01
+    pub fn create(k: f32, bandwidth: f32) -> LiquidResult<Self> {
+        if k < 2f32 || !k.is_finite() {
+            return Err(LiquidError::InvalidValue(format!(
+                "samples per symbol: {} must be at least 2",
+                k
+            )));
+        }
+        Ok(Self {
+            interp: FirFarrowCrcf::create(INTERP_LEN, INTERP_ORDER, INTERP_FC, INTERP_AS)?,
+            ted: Gardner::new(),
+            filter: LoopFilter::from_bandwidth(bandwidth, 1.0 / 2f32.sqrt(), GARDNER_GAIN)?,
+            k,
+            w: 2.0 / k,
+            counter: 0.0,
+            mid_next: true,
+            mid: Complex32::default(),
+        })
+    }
+
+    /// clear the interpolator and restart the loop at the nominal rate
+    pub fn reset(&mut self) {
+        self.interp.reset();
+        self.ted.reset();
+        self.filter.reset();
+        self.w = 2.0 / self.k;
+        self.counter = 0.0;
+        self.mid_next = true;
+        self.mid = Complex32::default();
+    }
+
+    /// nominal samples per symbol
+    pub fn get_samples_per_symbol(&self) -> f32 {
+        self.k
+    }
+
+    /// samples per symbol tracked by the loop
+    pub fn get_rate_estimate(&self) -> f32 {
+        2.0 / self.w
+    }
+
+    /// number of symbols produced from *input_len* samples, give or take
+    /// one depending on the loop state
+    pub fn output_len_for(&self, input_len: usize) -> usize {
+        (input_len as f32 / self.get_rate_estimate()).ceil() as usize
+    }
+
+    /// push a block of samples, appending the recovered symbols to y
+    pub fn execute(&mut self, x: &[Complex32], y: &mut Vec<Complex32>) {
+        let w0 = 2.0 / self.k;
+        for v in x {
+            self.interp.push(*v);
+            if self.counter >= self.w {
+                self.counter -= self.w;
+                continue;
+            }
+            // interpolant between the previous and the current sample
+            let mu = self.counter / self.w;
+            self.interp
+                .set_delay(mu - 0.5)
+                .expect("fractional interval is in [0, 1)");
+            let s = self.interp.execute();
+            if self.mid_next {
+                self.mid = s;
+            } else {
+                let e = self.ted.update(self.mid, s);
+                let v = self.filter.update(e);
+                self.w = (w0 * (1.0 + v)).clamp(0.5 * w0, 1.0);
+                y.push(s);
+            }
+            self.mid_next = !self.mid_next;
+            self.counter += 1.0 - self.w;
+        }
+    }
+}
+
+impl Block<Complex32, Complex32> for TimingRecovery {
+    fn process(&mut self, input: &[Complex32], output: &mut Vec<Complex32>) -> LiquidResult<()> {
+        self.execute(input, output);
+        Ok(())
+    }
+
+    fn rate(&self) -> f32 {
+        1.0 / self.k
+    }
+}
+
+/// matched filter of a root-Nyquist pulse, e.g. in front of
+/// TimingRecovery: a unit symbol shaped by the same prototype, as done by
+/// FirInterpCrcf::create_prototype, comes out as a Nyquist pulse of unit
+/// peak
+pub struct MatchedFilter {
+    filter: FirFiltCrcf,
+    k: u32,
+    m: u32,
+}
+
+impl MatchedFilter {
+    /// create a matched filter
+    ///  ftype  :   root-Nyquist prototype, e.g. FirdesFilterType::Rrc
+    ///  k      :   samples per symbol, k >= 2
+    ///  m      :   filter delay [symbols], m > 0
+    ///  beta   :   excess bandwidth factor, in [0, 1]
+    pub fn create(ftype: FirdesFilterType, k: u32, m: u32, beta: f32) -> LiquidResult<Self> {
+        if k < 2 {
+            return Err(LiquidError::InvalidValue(format!(
+                "samples per symbol: {} must be at least 2",
+                k
+            )));
+        } else if m == 0 {
+            return Err(LiquidError::InvalidValue(
+                "filter delay must be greater than zero".to_owned(),
+            ));
+        } else if !(0f32..=1f32).contains(&beta) {
+            return Err(LiquidError::InvalidValue(format!(
+                "excess bandwidth factor: {} must be in [0, 1]",
+                beta
+            )));
+        }
+        let g = Firdes::prototype(ftype, k as usize, m as usize, beta, 0.0);
+        let energy: f32 = g.as_ref().iter().map(|v| v * v).sum();
+        // time-reversed pulse, the prototypes being symmetric anyway
+        let h: Vec<f32> = g.as_ref().iter().rev().map(|v| v / energy).collect();
+        Ok(Self {
+            filter: FirFiltCrcf::create(&h)?,
+            k,
+            m,
+        })
+    }
+
+    pub fn reset(&mut self) {
+        self.filter.reset();
+    }
+
+    /// samples per symbol
+    pub fn get_samples_per_symbol(&self) -> u32 {
+        self.k
+    }
+
+    /// group delay of the filter, k*m [samples]; that of the transmit and
+    /// matched filters together is twice as much
+    pub fn get_delay(&self) -> usize {
+        (self.k * self.m) as usize
+    }
+
+    /// filter a block of samples
+    ///  x      :   input samples [size: n x 1]
+    ///  y      :   output samples [size: n x 1]
+    pub fn execute_block(&mut self, x: &[Complex32], y: &mut [Complex32]) -> LiquidResult<()> {
+        check_same_len(x.len(), y.len())?;
+        self.filter.execute_block(x, y);
+        Ok(())
+    }
+}
+
+impl Block<Complex32, Complex32> for MatchedFilter {
+    fn process(&mut self, input: &[Complex32], output: &mut Vec<Complex32>) -> LiquidResult<()> {
+        let start = output.len();
+        output.resize(start + input.len(), Complex32::default());
+        self.execute_block(input, &mut output[start..])
+    }
+
+    fn delay(&self) -> f32 {
+        self.get_delay() as f32
+    }
+}
+
+/// constellation tracked by a Costas loop
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub enum CostasMode {
+    /// phase error sign(Re{y}) * Im{y}, 180 degree ambiguity
+    BPSK,
+    /// phase error (sign(Re{y}) * Im{y} - sign(Im{y}) * Re{y}) / sqrt(2),
+    /// 90 degree ambiguity
+    QPSK,
+}
+
+/// Costas loop: the input is mixed down by an NCO, whose phase and, for a
+/// second-order loop, frequency are driven by a decision-directed phase
+/// error; the detector gain is one for unit-energy symbols
+pub struct CarrierRecovery {
+    nco: NcoCrcf,
+    mode: CostasMode,
+    order: usize,
+    kp: f32,
+    ki: f32,
+}
+
+impl CarrierRecovery {
+    /// create a Costas loop
+    ///  mode       :   constellation, BPSK or QPSK
+    ///  order      :   loop order, 1 tracks a phase offset and 2 a
+    ///                 frequency offset as well
+    ///  bandwidth  :   loop noise bandwidth normalized to the sample rate,
+    ///                 in (0, 0.5), e.g. 0.01
+    pub fn create(mode: CostasMode, order: usize, bandwidth: f32) -> LiquidResult<Self> {
+        let (kp, ki) = match order {
+            1 => {
+                if bandwidth <= 0f32 || bandwidth >= 0.5 {
+                    return Err(LiquidError::InvalidValue(format!(
+                        "loop bandwidth: {} must be in (0, 0.5)",
+                        bandwidth
+                    )));
+                }
+                (4.0 * bandwidth / (1.0 + 2.0 * bandwidth), 0.0)
+            }
+            2 => LoopFilter::from_bandwidth(bandwidth, 1.0 / 2f32.sqrt(), 1.0)?.get_gains(),
+            _ => {
+                return Err(LiquidError::InvalidValue(format!(
+                    "loop order: {} must be 1 or 2",
+                    order
+                )))
+            }
+        };
+        Ok(Self {
+            nco: NcoCrcf::create(NcoType::VCO),
+            mode,
+            order,
+            kp,
+            ki,
+        })
+    }
+
+    /// restart the loop at zero phase and frequency
+    pub fn reset(&mut self) {
+        self.nco.reset();
+    }
+
+    pub fn get_mode(&self) -> CostasMode {
+        self.mode
+    }
+
+    pub fn get_order(&self) -> usize {
+        self.order
+    }
+
+    /// frequency offset estimate [radians/sample]
+    pub fn get_frequency(&self) -> f32 {
+        self.nco.get_frequency()
+    }
+
+    /// phase offset estimate [radians]
+    pub fn get_phase(&self) -> f32 {
+        self.nco.get_phase()
+    }
+
+    fn phase_error(&self, y: Complex32) -> f32 {
+        match self.mode {
+            CostasMode::BPSK => slice(y.re) * y.im,
+            CostasMode::QPSK => (slice(y.re) * y.im - slice(y.im) * y.re) / 2f32.sqrt(),
+        }
+    }
+
+    /// de-rotate a sample and update the loop
+    pub fn execute(&mut self, x: Complex32) -> Complex32 {
+        let y = self.nco.mix_down(x);
+        let e = self.phase_error(y);
+        self.nco.adjust_frequency(self.ki * e);
+        self.nco.adjust_phase(self.kp * e);
+        self.nco.step();
+        y
+    }
+
+    /// de-rotate a block of samples
+    ///  x      :   input samples [size: n x 1]
+    ///  y      :   output samples [size: n x 1]
+    pub fn execute_block(&mut self, x: &[Complex32], y: &mut [Complex32]) -> LiquidResult<()> {
+        check_same_len(x.len(), y.len())?;
+        for (a, b) in x.iter().zip(y.iter_mut()) {
+            *b = self.execute(*a);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        CarrierRecovery, CostasMode, Gardner, LoopFilter, MatchedFilter, MuellerMuller,
+        TimingRecovery,
+    };
+    use crate::block::Block;
+    use crate::filter::{FirInterpCrcf, FirdesFilterType};
+    use num::complex::Complex32;
+    use std::f32::consts::PI;
+
+    /// raised-cosine pulse, excess bandwidth 0.35, time in symbols
+    fn rcos(t: f32) -> f32 {
+        let b = 0.35;
+        if t.abs() < 1e-6 {
+            return 1.0;
+        } else if ((2.0 * b * t).abs() - 1.0).abs() < 1e-4 {
+            return PI / 4.0 * (PI * t).sin() / (PI * t);
+        }
+        (PI * t).sin() / (PI * t) * (PI * b * t).cos() / (1.0 - (2.0 * b * t).powi(2))
+    }
+
+    #[test]
+    fn test_timing_error_detectors() {
+        let c = |re: f32| Complex32::new(re, 0.0);
+        // late sampling of a -1 -> +1 transition, the zero crossing passed
+        let mut q = Gardner::new();
+        q.update(c(0.0), c(-1.0));
+        assert!(q.update(c(0.2), c(1.0)) > 0.0);
+        assert!(q.update(c(-0.2), c(-1.0)) > 0.0);
+        q.reset();
+
+        // late: each symbol sample leaks the following symbol
+        let mut q = MuellerMuller::new();
+        q.update(c(-0.8));
+        assert!(q.update(c(1.2)) > 0.0);
+        assert_eq!(q.update_with_decision(c(0.0), c(1.0)), 1.2);
+
+        assert!(LoopFilter::from_bandwidth(0.0, 0.7, 1.0).is_err());
+        assert!(LoopFilter::from_bandwidth(0.01, 0.7, 0.0).is_err());
+        let mut f = LoopFilter::from_bandwidth(0.01, 0.7, 1.0).unwrap();
+        let (kp, ki) = f.get_gains();
+        assert!(kp > ki && ki > 0.0);
+        assert_eq!(f.update(1.0), kp + ki);
+        assert_eq!(f.update(0.0), ki);
+        f.reset();
+        assert_eq!(f.update(0.0), 0.0);
+    }
+
+    #[test]
+    fn test_timing_recovery_lock() {
+        assert!(TimingRecovery::create(1.5, 0.01).is_err());
+        assert!(TimingRecovery::create(4.0, 0.0).is_err());
+
+        // BPSK at 4 samples per symbol with a fractional timing offset
+        let k = 4.0;
+        let symbols: Vec<f32> = (0..600)
+            .map(|i| if (i * 7 + i / 3) % 5 < 2 { 1.0 } else { -1.0 })
+            .collect();
+        let x: Vec<Complex32> = (0..2400)
+            .map(|n| {
+                let s = (n as f32 - 1.3) / k;
+                let i0 = s as i64;
+                let re = (i0 - 12..i0 + 12)
+                    .filter(|&i| i >= 0 && i < symbols.len() as i64)
+                    .map(|i| symbols[i as usize] * rcos(s - i as f32))
+                    .sum();
+                Complex32::new(re, 0.0)
+            })
+            .collect();
+        let mut q = TimingRecovery::create(k, 0.02).unwrap();
+        let mut y = Vec::new();
+        q.execute(&x, &mut y);
+        assert!((y.len() as f32 - 600.0).abs() < 4.0);
+        // eye open once locked
+        let tail = &y[y.len() - 200..y.len() - 10];
+        let err = tail.iter().map(|v| (v.re.abs() - 1.0).abs()).sum::<f32>() / tail.len() as f32;
+        assert!(err < 0.1);
+        assert!((q.get_rate_estimate() - k).abs() < 0.2);
+    }
+
+    #[test]
+    fn test_matched_filter() {
+        assert!(MatchedFilter::create(FirdesFilterType::Rrc, 1, 5, 0.35).is_err());
+        assert!(MatchedFilter::create(FirdesFilterType::Rrc, 4, 0, 0.35).is_err());
+        assert!(MatchedFilter::create(FirdesFilterType::Rrc, 4, 5, 1.5).is_err());
+
+        // RRC-shaped BPSK at 4 samples per symbol
+        let (k, m) = (4u32, 5u32);
+        let symbols: Vec<Complex32> = (0..400)
+            .map(|i| Complex32::new(if (i * 7 + i / 3) % 5 < 2 { 1.0 } else { -1.0 }, 0.0))
+            .collect();
+        let mut interp =
+            FirInterpCrcf::create_prototype(FirdesFilterType::Rrc, k, m, 0.35, 0.0).unwrap();
+        let mut x = vec![Complex32::default(); symbols.len() * k as usize];
+        interp.execute_block(&symbols, &mut x);
+
+        let mut mf = MatchedFilter::create(FirdesFilterType::Rrc, k, m, 0.35).unwrap();
+        assert_eq!(mf.get_delay(), 20);
+        let mut y = Vec::new();
+        mf.process(&x, &mut y).unwrap();
+        assert_eq!(y.len(), x.len());
+        // Nyquist pulse: the symbols come back at the optimum instants
+        let delay = 2 * mf.get_delay();
+        for (i, s) in symbols.iter().enumerate().take(380).skip(20) {
+            assert!((y[delay + i * k as usize] - s).norm() < 0.05);
+        }
+
+        // and the timing recovery locks on the matched filter output
+        let mut q = TimingRecovery::create(k as f32, 0.02).unwrap();
+        let mut z = Vec::new();
+        q.execute(&y, &mut z);
+        let tail = &z[z.len() - 100..z.len() - 10];
+        let err = tail.iter().map(|v| (v.re.abs() - 1.0).abs()).sum::<f32>() / tail.len() as f32;
+        assert!(err < 0.1);
+    }
+
+    #[test]
+    fn test_carrier_recovery() {
+        assert!(CarrierRecovery::create(CostasMode::BPSK, 3, 0.02).is_err());
+        assert!(CarrierRecovery::create(CostasMode::BPSK, 1, 0.0).is_err());
+
+        let x: Vec<Complex32> = (0..3000)
+            .map(|n| {
+                let re = if (n * 7 + n / 3) % 5 < 2 { 1.0 } else { -1.0 };
+                let im = if (n * 3 + n / 7) % 3 < 1 { 1.0 } else { -1.0 };
+                Complex32::new(re, im) / 2f32.sqrt()
+                    * Complex32::from_polar(1.0, 0.01 * n as f32 + 0.5)
+            })
+            .collect();
+        let mut q = CarrierRecovery::create(CostasMode::QPSK, 2, 0.02).unwrap();
+        let mut y = vec![Complex32::default(); x.len()];
+        q.execute_block(&x, &mut y).unwrap();
+        assert!((q.get_frequency() - 0.01).abs() < 1e-3);
+        // back on the constellation, up to a multiple of 90 degrees
+        for v in &y[2500..] {
+            assert!((v.re.abs() - 0.5f32.sqrt()).abs() < 0.05);
+            assert!((v.im.abs() - 0.5f32.sqrt()).abs() < 0.05);
+        }
+    }
+
+    #[test]
+    fn test_carrier_recovery_bpsk() {
+        let symbol = |n: usize| if (n * 7 + n / 3) % 5 < 2 { 1.0 } else { -1.0 };
+
+        // first-order loop locking to a phase offset
+        let x: Vec<Complex32> = (0..1000)
+            .map(|n| Complex32::from_polar(symbol(n), 0.6))
+            .collect();
+        let mut q = CarrierRecovery::create(CostasMode::BPSK, 1, 0.02).unwrap();
+        assert_eq!((q.get_mode(), q.get_order()), (CostasMode::BPSK, 1));
+        let mut y = vec![Complex32::default(); x.len()];
+        q.execute_block(&x, &mut y).unwrap();
+        assert_eq!(q.get_frequency(), 0.0);
+        // up to the 180 degree ambiguity
+        assert!((Complex32::from_polar(1.0, 2.0 * (q.get_phase() - 0.6)) - 1.0).norm() < 0.02);
+        for v in &y[800..] {
+            assert!((v.re.abs() - 1.0).abs() < 0.02 && v.im.abs() < 0.02);
+        }
+
+        // second-order loop tracking a frequency offset
+        let x: Vec<Complex32> = (0..3000)
+            .map(|n| Complex32::from_polar(symbol(n), 0.005 * n as f32 - 1.0))
+            .collect();
+        let mut q = CarrierRecovery::create(CostasMode::BPSK, 2, 0.02).unwrap();
+        let mut y = vec![Complex32::default(); x.len()];
+        q.execute_block(&x, &mut y).unwrap();
+        assert!((q.get_frequency() - 0.005).abs() < 5e-4);
+        for v in &y[2500..] {
+            assert!((v.re.abs() - 1.0).abs() < 0.05 && v.im.abs() < 0.05);
+        }
+
+        q.reset();
+        assert_eq!(q.get_frequency(), 0.0);
+    }
+}
@@ -0,0 +1,89 @@
+//! *tag* : metadata attached to sample positions of a stream, e.g. squelch
+//!           events or frame detections, carried alongside the samples by
+//!           Block::process_tagged
+//!
+//! Tag offsets are indices into the buffer the tags travel with: the input
+//! slice of a process_tagged call, or the output Vec for the tags it
+//! produces.
+
+/// value carried by a tag
+#[derive(Clone, Debug, PartialEq)]
+pub enum TagValue {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Str(String),
+    Bytes(Vec<u8>),
+}
+
+/// metadata attached to a sample
+#[derive(Clone, Debug, PartialEq)]
+pub struct Tag {
+    /// index of the sample the tag refers to
+    pub offset: u64,
+    pub key: String,
+    pub value: TagValue,
+}
+
+impl Tag {
+    pub fn new<K: Into<String>>(offset: u64, key: K, value: TagValue) -> Self {
+        Self {
+            offset,
+            key: key.into(),
+            value,
+        }
+    }
+}
+
+/// move the tags of an input buffer onto the output produced from it,
+/// scaling the offsets by the number of samples produced per sample
+/// consumed
+///  tags       :   tags of the input buffer
+///  n_in       :   number of input samples
+///  out_start  :   index of the first output sample produced
+///  n_out      :   number of output samples produced
+///  tags_out   :   output tags
+pub(crate) fn propagate_tags(
+    tags: &[Tag],
+    n_in: usize,
+    out_start: usize,
+    n_out: usize,
+    tags_out: &mut Vec<Tag>,
+) {
+    tags_out.extend(tags.iter().map(|tag| {
+        let offset = if n_in == 0 {
+            0
+        } else {
+            (tag.offset.min(n_in as u64 - 1) * n_out as u64 / n_in as u64)
+                .min(n_out.saturating_sub(1) as u64)
+        };
+        Tag {
+            offset: out_start as u64 + offset,
+            ..tag.clone()
+        }
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{propagate_tags, Tag, TagValue};
+
+    #[test]
+    fn test_propagate_tags() {
+        let tags = vec![
+            Tag::new(0, "a", TagValue::Int(1)),
+            Tag::new(3, "b", TagValue::Bool(true)),
+        ];
+        let mut out = Vec::new();
+        // interpolation by 4, appended after 10 existing samples
+        propagate_tags(&tags, 4, 10, 16, &mut out);
+        assert_eq!(out[0].offset, 10);
+        assert_eq!(out[1].offset, 22);
+        assert_eq!(out[1].key, "b");
+
+        // decimation by 2
+        out.clear();
+        propagate_tags(&tags, 4, 0, 2, &mut out);
+        assert_eq!(out[1].offset, 1);
+    }
+}
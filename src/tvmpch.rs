@@ -1,87 +1,200 @@
-use libc::c_uint;
+//! *tvmpch* : time-varying multi-path channel emulator
+//!
+//! Follows the model of liquid's tvmpch object: a direct path with unit
+//! gain followed by n-1 taps evolving as first order Gauss-Markov
+//! processes. liquid neither exposes the taps nor accepts a seed, so the
+//! channel is implemented here with its own generator; realizations can be
+//! reproduced with create_seeded and logged with get_coefficients.
 use num::complex::Complex32;
 
-use crate::liquid_dsp_sys as raw;
+use crate::utils::check_same_len;
+use crate::LiquidResult;
 
-use crate::utils::{ToCPointer, ToCPointerMut, ToCValue};
+/// xorshift64* generator with Box-Muller Gaussian samples
+#[derive(Clone, Debug)]
+pub(crate) struct GaussRng {
+    state: u64,
+}
+
+impl GaussRng {
+    pub(crate) fn new(seed: u64) -> Self {
+        // the all-zero state is a fixed point of xorshift
+        Self {
+            state: seed ^ 0x9e37_79b9_7f4a_7c15,
+        }
+    }
+
+    /// uniform sample in (0, 1]
+    fn uniform(&mut self) -> f32 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        let r = self.state.wrapping_mul(0x2545_f491_4f6c_dd1d);
+        ((r >> 40) + 1) as f32 / (1u64 << 24) as f32
+    }
+
+    /// complex Gauss sample with unit variance
+    pub(crate) fn crandn(&mut self) -> Complex32 {
+        let r = (-self.uniform().ln()).sqrt();
+        let theta = 2.0 * std::f32::consts::PI * self.uniform();
+        Complex32::from_polar(r, theta)
+    }
+}
 
-/// tvmpch : finite impulse response (FIR) filter
 pub struct TvmpchCccf {
-    inner: raw::tvmpch_cccf,
+    /// h[0] is the direct path, applied to the most recent sample
+    h: Vec<Complex32>,
+    /// circular buffer of the last h.len() input samples
+    w: Vec<Complex32>,
+    index: usize,
+    std: f32,
+    alpha: f32,
+    beta: f32,
+    rng: GaussRng,
 }
 
 impl TvmpchCccf {
-    /// create time-varying multi-path channel emulator object
+    /// create time-varying multi-path channel emulator object, seeded from
+    /// the C library rand()
     ///  n      :   number of coefficients
     ///  std    :   standard deviation
     ///  tau    :   coherence time
     pub fn create(n: u32, std: f32, tau: f32) -> Self {
+        let seed = unsafe { ((libc::rand() as u64) << 32) | libc::rand() as u64 };
+        Self::create_seeded(n, std, tau, seed)
+    }
+
+    /// create time-varying multi-path channel emulator object with a
+    /// reproducible tap evolution
+    ///  n      :   number of coefficients
+    ///  std    :   standard deviation of the multi-path taps
+    ///  tau    :   coherence time, each tap is updated on every sample as
+    ///             h = (1-tau)*h + sqrt(1-(1-tau)^2)*std*v, v ~ CN(0,1)
+    ///  seed   :   seed of the tap generator
+    pub fn create_seeded(n: u32, std: f32, tau: f32, seed: u64) -> Self {
         assert!(n > 0, "filter length must be greater than one");
         assert!(std > 0f32, "standard deviation must be positive");
         assert!(tau > 0f32 && tau < 1f32, "coherence time must be in [0,1]");
-        unsafe {
-            Self {
-                inner: raw::tvmpch_cccf_create(n, std, tau),
-            }
-        }
+        let alpha = 1.0 - tau;
+        let mut q = Self {
+            h: vec![Complex32::default(); n as usize],
+            w: vec![Complex32::default(); n as usize],
+            index: 0,
+            std,
+            alpha,
+            beta: (1.0 - alpha * alpha).sqrt(),
+            rng: GaussRng::new(seed),
+        };
+        q.reset();
+        q
     }
 
+    /// clear the buffer and restore the taps to a single direct path;
+    /// the tap generator is not re-seeded
     pub fn reset(&mut self) {
-        unsafe {
-            raw::tvmpch_cccf_reset(self.inner);
-        }
+        self.w.iter_mut().for_each(|v| *v = Complex32::default());
+        self.index = 0;
+        self.h.iter_mut().for_each(|v| *v = Complex32::default());
+        self.h[0] = Complex32::new(1.0, 0.0);
     }
 
     /// print channel object
     pub fn print(&self) {
-        unsafe {
-            raw::tvmpch_cccf_print(self.inner);
+        println!("tvmpch_cccf, length:{}", self.h.len());
+        for (i, v) in self.h.iter().enumerate() {
+            println!("  h[{:3}] = {:12.8} + j*{:12.8}", i, v.re, v.im);
         }
     }
 
-    /// push sample into filter object's internal buffer
+    /// number of coefficients
+    pub fn get_length(&self) -> usize {
+        self.h.len()
+    }
+
+    /// current channel taps, h[0] being the direct path
+    pub fn get_coefficients(&self) -> &[Complex32] {
+        &self.h
+    }
+
+    /// push sample into filter object's internal buffer, updating the
+    /// multi-path taps
     ///  sample      :   input sample
     pub fn push(&mut self, sample: Complex32) {
-        unsafe { raw::tvmpch_cccf_push(self.inner, sample.to_c_value()) }
+        let (alpha, beta, std) = (self.alpha, self.beta, self.std);
+        for i in 1..self.h.len() {
+            let v = self.rng.crandn() * std;
+            self.h[i] = self.h[i] * alpha + v * beta;
+        }
+        self.index = (self.index + 1) % self.w.len();
+        self.w[self.index] = sample;
     }
 
     /// Returns a compute output sample
     ///
     /// (dot product between internal
     /// filter coefficients and internal buffer)
-    pub fn execute(&self) -> Complex32 {
-        let mut s = Complex32::default();
-        unsafe {
-            raw::tvmpch_cccf_execute(self.inner, s.to_ptr_mut());
-            // TODO: should this function return an option???
-            s
-        }
+    pub fn execute(&mut self) -> Complex32 {
+        let n = self.w.len();
+        self.h
+            .iter()
+            .enumerate()
+            .map(|(i, h)| h * self.w[(self.index + n - i) % n])
+            .sum()
     }
 
     /// execute the filter on a block of input samples; the
     /// input and output buffers may be the same
     ///  samples     : input array [size: _n x 1]
     ///  output      : output array [size: _n x 1]
-    pub fn execute_block(&self, samples: &[Complex32], output: &mut [Complex32]) {
-        assert!(
-            samples.len() == output.len(),
-            "Input and output buffers with different length"
-        );
-        unsafe {
-            raw::tvmpch_cccf_execute_block(
-                self.inner,
-                samples.to_ptr() as *mut _,
-                samples.len() as c_uint,
-                output.to_ptr_mut(),
-            );
+    /// # panics
+    /// if the buffers have different lengths, see try_execute_block
+    pub fn execute_block(&mut self, samples: &[Complex32], output: &mut [Complex32]) {
+        self.try_execute_block(samples, output)
+            .unwrap_or_else(|e| panic!("{}", e));
+    }
+
+    /// execute the filter on a block of input samples
+    /// # returns
+    /// LiquidError::InvalidLength if the buffers have different lengths
+    pub fn try_execute_block(
+        &mut self,
+        samples: &[Complex32],
+        output: &mut [Complex32],
+    ) -> LiquidResult<()> {
+        check_same_len(samples.len(), output.len())?;
+        for (x, y) in samples.iter().zip(output.iter_mut()) {
+            self.push(*x);
+            *y = self.execute();
         }
+        Ok(())
     }
 }
 
-impl Drop for TvmpchCccf {
-    fn drop(&mut self) {
-        unsafe {
-            raw::tvmpch_cccf_destroy(self.inner);
-        }
+#[cfg(test)]
+mod tests {
+    use super::TvmpchCccf;
+    use num::complex::Complex32;
+
+    #[test]
+    fn test_tvmpch_seeded() {
+        let x: Vec<Complex32> = (0..64).map(|i| Complex32::new(i as f32, 0.0)).collect();
+        let mut y0 = vec![Complex32::default(); 64];
+        let mut y1 = vec![Complex32::default(); 64];
+
+        let mut q0 = TvmpchCccf::create_seeded(4, 0.1, 0.1, 7);
+        let mut q1 = TvmpchCccf::create_seeded(4, 0.1, 0.1, 7);
+        assert_eq!(q0.get_coefficients()[0], Complex32::new(1.0, 0.0));
+        q0.execute_block(&x, &mut y0);
+        q1.execute_block(&x, &mut y1);
+        assert_eq!(y0, y1);
+        assert_eq!(q0.get_coefficients(), q1.get_coefficients());
+        assert_ne!(q0.get_coefficients()[1], Complex32::default());
+
+        let mut q2 = TvmpchCccf::create_seeded(4, 0.1, 0.1, 8);
+        q2.execute_block(&x, &mut y1);
+        assert_ne!(y0, y1);
+
+        q0.reset();
+        assert_eq!(q0.get_coefficients()[1], Complex32::default());
     }
 }
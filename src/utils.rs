@@ -1,10 +1,20 @@
 use num::complex::Complex32;
+use std::mem;
 use std::panic::{self, AssertUnwindSafe};
 
+use crate::errors::{LengthMismatch, LiquidError};
 use crate::liquid_dsp_sys as raw;
+use crate::LiquidResult;
 
 pub(crate) type LiquidFloatComplex = raw::liquid_float_complex;
 
+// the block execute paths hand slices of Complex32 to liquid by casting
+// the pointer, without copying or converting the samples; both types are
+// #[repr(C)] pairs of f32 (re, im), the field order is checked in the tests
+const _: () = assert!(mem::size_of::<Complex32>() == mem::size_of::<LiquidFloatComplex>());
+const _: () = assert!(mem::align_of::<Complex32>() == mem::align_of::<LiquidFloatComplex>());
+const _: () = assert!(mem::size_of::<Complex32>() == 2 * mem::size_of::<f32>());
+
 pub(crate) trait ToCPointer {
     type Output;
     fn to_ptr(&self) -> Self::Output;
@@ -93,6 +103,24 @@ impl ToCPointerMut for [f32] {
     }
 }
 
+/// error out unless the input and output buffers of a block
+/// operation have the same length
+pub(crate) fn check_same_len(input: usize, output: usize) -> LiquidResult<()> {
+    check_same_len_as::<LiquidError>(input, output)
+}
+
+/// check_same_len() reporting the mismatch with the error enum E of the
+/// calling module
+pub(crate) fn check_same_len_as<E: LengthMismatch>(input: usize, output: usize) -> Result<(), E> {
+    if input != output {
+        return Err(E::length_mismatch(format!(
+            "input length: {} output length: {}, buffers must have the same length",
+            input, output
+        )));
+    }
+    Ok(())
+}
+
 pub(crate) fn catch<T, F: FnOnce() -> T>(f: F) -> Option<T> {
     match panic::catch_unwind(AssertUnwindSafe(f)) {
         Ok(ret) => Some(ret),
@@ -101,3 +129,145 @@ pub(crate) fn catch<T, F: FnOnce() -> T>(f: F) -> Option<T> {
         }
     }
 }
+
+/// pack binary array with a single bit per element into bytes
+///  sym_in     :   input bits, one bit per element [size: _sym_in_len x 1]
+///  sym_out    :   output packed bytes, at least ceil(sym_in.len() / 8) elements
+/// # returns
+/// the number of bytes written
+pub fn pack_bytes(sym_in: &[u8], sym_out: &mut [u8]) -> LiquidResult<usize> {
+    if sym_out.len() * 8 < sym_in.len() {
+        return Err(LiquidError::InvalidLength {
+            description: format!(
+                "sym_out length: {} valid length: {}",
+                sym_out.len(),
+                sym_in.len().div_ceil(8)
+            ),
+        });
+    }
+    let mut num_written = 0u32;
+    unsafe {
+        raw::liquid_pack_bytes(
+            sym_in.as_ptr() as _,
+            sym_in.len() as _,
+            sym_out.as_mut_ptr(),
+            sym_out.len() as _,
+            &mut num_written as *mut _,
+        );
+    }
+    Ok(num_written as usize)
+}
+
+/// unpack bytes into binary array with a single bit per element
+///  sym_in     :   input packed bytes [size: _sym_in_len x 1]
+///  sym_out    :   output bits, at least 8 * sym_in.len() elements
+/// # returns
+/// the number of bits written
+pub fn unpack_bytes(sym_in: &[u8], sym_out: &mut [u8]) -> LiquidResult<usize> {
+    if sym_out.len() < 8 * sym_in.len() {
+        return Err(LiquidError::InvalidLength {
+            description: format!(
+                "sym_out length: {} valid length: {}",
+                sym_out.len(),
+                8 * sym_in.len()
+            ),
+        });
+    }
+    let mut num_written = 0u32;
+    unsafe {
+        raw::liquid_unpack_bytes(
+            sym_in.as_ptr() as _,
+            sym_in.len() as _,
+            sym_out.as_mut_ptr(),
+            sym_out.len() as _,
+            &mut num_written as *mut _,
+        );
+    }
+    Ok(num_written as usize)
+}
+
+/// repack bytes with arbitrary symbol sizes
+///  sym_in     :   input symbols array [size: _sym_in_len x 1]
+///  in_bps     :   input bits per symbol, in [1, 8]
+///  sym_out    :   output symbols array
+///  out_bps    :   output bits per symbol, in [1, 8]
+/// # returns
+/// the number of symbols written
+pub fn repack_bytes(
+    sym_in: &[u8],
+    in_bps: u32,
+    sym_out: &mut [u8],
+    out_bps: u32,
+) -> LiquidResult<usize> {
+    if in_bps == 0 || in_bps > 8 || out_bps == 0 || out_bps > 8 {
+        return Err(LiquidError::InvalidValue(format!(
+            "in_bps: {} and out_bps: {} must be in [1, 8]",
+            in_bps, out_bps
+        )));
+    }
+    let in_bits = sym_in.len() * in_bps as usize;
+    let required = in_bits.div_ceil(out_bps as usize);
+    if sym_out.len() < required {
+        return Err(LiquidError::InvalidLength {
+            description: format!(
+                "sym_out length: {} valid length: {}",
+                sym_out.len(),
+                required
+            ),
+        });
+    }
+    let mut num_written = 0u32;
+    unsafe {
+        raw::liquid_repack_bytes(
+            sym_in.as_ptr() as _,
+            in_bps as _,
+            sym_in.len() as _,
+            sym_out.as_mut_ptr(),
+            out_bps as _,
+            sym_out.len() as _,
+            &mut num_written as *mut _,
+        );
+    }
+    Ok(num_written as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{pack_bytes, repack_bytes, unpack_bytes, ToCPointer};
+    use num::complex::Complex32;
+
+    #[test]
+    fn test_complex_layout() {
+        let x = [Complex32::new(1.0, 2.0), Complex32::new(3.0, 4.0)];
+        let p = x.to_ptr();
+        unsafe {
+            assert_eq!((*p).re, 1.0);
+            assert_eq!((*p).im, 2.0);
+            assert_eq!((*p.add(1)).re, 3.0);
+        }
+    }
+
+    #[test]
+    fn test_pack_unpack_bytes() {
+        let bits = [1u8, 0, 1, 1, 0, 0, 1, 0, 1, 1, 1, 1, 0, 0, 0, 0];
+        let mut packed = [0u8; 2];
+        assert_eq!(pack_bytes(&bits, &mut packed).unwrap(), 2);
+        assert_eq!(packed, [0xB2, 0xF0]);
+
+        let mut unpacked = [0u8; 16];
+        assert_eq!(unpack_bytes(&packed, &mut unpacked).unwrap(), 16);
+        assert_eq!(unpacked, bits);
+
+        let mut short = [0u8; 1];
+        assert!(pack_bytes(&bits, &mut short).is_err());
+    }
+
+    #[test]
+    fn test_repack_bytes() {
+        let bytes = [0xB2u8, 0xF0];
+        let mut syms = [0u8; 8];
+        assert_eq!(repack_bytes(&bytes, 8, &mut syms, 2).unwrap(), 8);
+        assert_eq!(syms, [2, 3, 0, 2, 3, 3, 0, 0]);
+        assert!(repack_bytes(&bytes, 8, &mut syms, 0).is_err());
+    }
+}
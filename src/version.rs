@@ -0,0 +1,88 @@
+//! *version* : version of the linked libliquid and probes for the optional
+//!           libraries it may have been built against
+use std::ffi::CStr;
+
+use crate::errors::LiquidError;
+use crate::liquid_dsp_sys as raw;
+use crate::LiquidResult;
+
+/// split liquid's version number, e.g. 1003002, into (major, minor, patch)
+fn split_version(number: u32) -> (u32, u32, u32) {
+    (number / 1_000_000, (number / 1000) % 1000, number % 1000)
+}
+
+/// version of the libliquid linked at run time, e.g. "1.3.2"
+pub fn version() -> &'static str {
+    unsafe {
+        CStr::from_ptr(raw::liquid_libversion())
+            .to_str()
+            .unwrap_or("unknown")
+    }
+}
+
+/// version of the libliquid linked at run time as (major, minor, patch)
+pub fn version_number() -> (u32, u32, u32) {
+    split_version(unsafe { raw::liquid_libversion_number() } as u32)
+}
+
+/// version of the liquid headers these bindings were generated from
+pub fn header_version() -> &'static str {
+    let v = raw::LIQUID_VERSION;
+    std::str::from_utf8(&v[..v.len() - 1]).unwrap_or("unknown")
+}
+
+/// version of the liquid headers as (major, minor, patch)
+pub fn header_version_number() -> (u32, u32, u32) {
+    split_version(raw::LIQUID_VERSION_NUMBER)
+}
+
+/// error out if the linked libliquid differs from the headers the bindings
+/// were generated from
+pub fn check_version() -> LiquidResult<()> {
+    if version_number() != header_version_number() {
+        return Err(LiquidError::InvalidValue(format!(
+            "libliquid version: {} does not match the headers version: {}",
+            version(),
+            header_version()
+        )));
+    }
+    Ok(())
+}
+
+/// true if libliquid was built against libfec, which provides the
+/// convolutional and Reed-Solomon codes, detected through the libfec
+/// symbols loaded in the process; always false on non-unix targets and
+/// when libfec is linked statically into the executable
+pub fn has_libfec() -> bool {
+    #[cfg(unix)]
+    unsafe {
+        let symbol = b"create_viterbi27\0";
+        !libc::dlsym(libc::RTLD_DEFAULT, symbol.as_ptr() as *const _).is_null()
+    }
+    #[cfg(not(unix))]
+    false
+}
+
+/// true if libliquid was built against FFTW, detected through the FFTW
+/// symbols loaded in the process; always false on non-unix targets
+pub fn has_fftw() -> bool {
+    #[cfg(unix)]
+    unsafe {
+        let symbol = b"fftwf_execute\0";
+        !libc::dlsym(libc::RTLD_DEFAULT, symbol.as_ptr() as *const _).is_null()
+    }
+    #[cfg(not(unix))]
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{header_version, header_version_number, split_version};
+
+    #[test]
+    fn test_version_number() {
+        assert_eq!(split_version(1003002), (1, 3, 2));
+        let (major, minor, patch) = header_version_number();
+        assert_eq!(header_version(), format!("{}.{}.{}", major, minor, patch));
+    }
+}